@@ -1,5 +1,8 @@
 use nalgebra_glm::{Vec3, vec3};
 
+use crate::chunk::BlockMeta;
+use crate::constants::LADDER_THICKNESS;
+
 /// Axis Aligned Bounding Box
 /// A 3-dimensional box where all the faces are parallel to the axis
 /// mins: the minimal corner of the box
@@ -27,11 +30,46 @@ impl AABB {
             (self.mins.z < other.maxs.z && self.maxs.z > other.mins.z)
     }
 
+    /// Checks whether this AABB touches the climbable side of the ladder at `block_coords`,
+    /// a thin slab against the wall it hangs on. `block_meta` holds the facing of the ladder
+    pub fn overlaps_ladder_face(&self, block_coords: &Vec3, block_meta: u8) -> bool {
+        let mut face = get_block_aabb(block_coords);
+        match BlockMeta(block_meta).facing_offset() {
+            (1, _) => face.maxs.x = face.mins.x + LADDER_THICKNESS,
+            (-1, _) => face.mins.x = face.maxs.x - LADDER_THICKNESS,
+            (_, 1) => face.maxs.z = face.mins.z + LADDER_THICKNESS,
+            _ => face.mins.z = face.maxs.z - LADDER_THICKNESS,
+        }
+        self.intersects(&face)
+    }
+
     pub fn contains_point(&self, other: &Vec3) -> bool {
         (self.mins.x < other.x && self.maxs.x > other.x) &&
             (self.mins.y < other.y && self.maxs.y > other.y) &&
             (self.mins.z < other.z && self.maxs.z > other.z)
     }
+
+    /// Distance along the ray from `origin` to where it enters this AABB, if it does before `max_distance`.
+    /// `direction` must be normalized
+    pub fn ray_distance(&self, origin: &Vec3, direction: &Vec3, max_distance: f32) -> Option<f32> {
+        let (mut t_min, mut t_max) = (0.0f32, max_distance);
+        for axis in 0..3 {
+            if direction[axis] == 0.0 {
+                if origin[axis] < self.mins[axis] || origin[axis] > self.maxs[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let t1 = (self.mins[axis] - origin[axis]) / direction[axis];
+            let t2 = (self.maxs[axis] - origin[axis]) / direction[axis];
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+            if t_min > t_max {
+                return None;
+            }
+        }
+        Some(t_min)
+    }
 }
 
 /// Creates an AABB box at mins with a length of 1 in every dimension
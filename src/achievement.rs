@@ -0,0 +1,163 @@
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use specs::{Join, World, WorldExt};
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{GUI_SCALING, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::shader_compilation::ShaderProgram;
+use crate::text::{GLYPH_HEIGHT, STAR_GLYPH, TextRenderer};
+use crate::inventory::Inventory;
+use crate::physics::Interpolator;
+use crate::player::PlayerPhysicsState;
+
+/// How long the banner of an unlocked achievement stays on screen
+pub const NOTIFICATION_DURATION: Duration = Duration::from_secs(5);
+/// Time taken by the banner to slide in and out, in seconds
+const SLIDE_DURATION: f32 = 0.4;
+
+pub struct Achievement {
+    pub id: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    /// Checked against the world until it returns true once.
+    /// It must not access the `AchievementRegistry` resource.
+    pub predicate: Box<dyn Fn(&World) -> bool + Send + Sync>,
+}
+
+pub struct AchievementNotification {
+    pub title: &'static str,
+    /// When the banner starts sliding in, notifications are shown one after the other
+    pub time_shown: Instant,
+}
+
+impl AchievementNotification {
+    /// Draws a banner in the top right corner that slides in from the right of the screen
+    pub fn draw(&self, now: Instant, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+        let elapsed = now.saturating_duration_since(self.time_shown).as_secs_f32();
+        let remaining = NOTIFICATION_DURATION.as_secs_f32() - elapsed;
+        let visible_fraction = (elapsed.min(remaining) / SLIDE_DURATION).max(0.0).min(1.0);
+
+        let header = "Achievement get!";
+        let padding = 4.0 * GUI_SCALING;
+        let line_height = (GLYPH_HEIGHT + 3.0) * GUI_SCALING;
+        let icon_size = 8.0 * GUI_SCALING;
+        let text_width = TextRenderer::text_width(header, GUI_SCALING)
+            .max(TextRenderer::text_width(self.title, GUI_SCALING));
+        let width = padding * 3.0 + icon_size + text_width;
+        let height = padding * 2.0 + line_height * 2.0;
+
+        let margin = 5.0 * GUI_SCALING;
+        let x = WINDOW_WIDTH as f32 - margin - width + (1.0 - visible_fraction) * (width + margin);
+        let y = WINDOW_HEIGHT as f32 - margin - height;
+
+        text_renderer.draw_rect(x, y, width, height, (0.1, 0.1, 0.1, 0.85), shader);
+        text_renderer.draw(&STAR_GLYPH.to_string(),
+                           x + padding, y + (height - icon_size) / 2.0 + GUI_SCALING,
+                           GUI_SCALING, (1.0, 0.8, 0.1, 1.0), shader);
+
+        let text_x = x + padding * 2.0 + icon_size;
+        text_renderer.draw(header, text_x, y + padding + line_height, GUI_SCALING, (1.0, 1.0, 0.3, 1.0), shader);
+        text_renderer.draw(self.title, text_x, y + padding, GUI_SCALING, (1.0, 1.0, 1.0, 1.0), shader);
+    }
+}
+
+pub struct AchievementRegistry {
+    pub achievements: Vec<Achievement>,
+    pub unlocked: HashSet<&'static str>,
+    pub notifications: VecDeque<AchievementNotification>,
+}
+
+impl AchievementRegistry {
+    pub fn new() -> Self {
+        let mut registry = AchievementRegistry {
+            achievements: Vec::new(),
+            unlocked: HashSet::new(),
+            notifications: VecDeque::new(),
+        };
+        registry.add(Achievement {
+            id: "getting_wood",
+            title: "Getting Wood",
+            description: "Pick up a log",
+            predicate: Box::new(|world| has_picked_up_where(world, |item| item.is_log())),
+        });
+        registry.add(Achievement {
+            id: "stone_age",
+            title: "Stone Age",
+            description: "Pick up cobblestone",
+            predicate: Box::new(|world| has_picked_up_where(world, |item| item == BlockID::Cobblestone)),
+        });
+        // There is no End yet, any portal will do
+        registry.add(Achievement {
+            id: "the_end",
+            title: "The End?",
+            description: "Step into a portal",
+            predicate: Box::new(|world| is_in_block(world, BlockID::NetherPortal)),
+        });
+        registry
+    }
+
+    pub fn add(&mut self, achievement: Achievement) {
+        self.achievements.push(achievement);
+    }
+
+    /// Unlocks the achievements whose predicate became true and queues their notification
+    pub fn update(&mut self, world: &World, now: Instant) {
+        let mut newly_unlocked = Vec::new();
+        for achievement in &self.achievements {
+            if !self.unlocked.contains(achievement.id) && (achievement.predicate)(world) {
+                newly_unlocked.push((achievement.id, achievement.title));
+            }
+        }
+
+        for (id, title) in newly_unlocked {
+            info!("Achievement unlocked: {}", title);
+            self.unlocked.insert(id);
+            let time_shown = match self.notifications.back() {
+                Some(last) => now.max(last.time_shown + NOTIFICATION_DURATION),
+                None => now,
+            };
+            self.notifications.push_back(AchievementNotification { title, time_shown });
+        }
+
+        while let Some(notification) = self.notifications.front() {
+            if now.saturating_duration_since(notification.time_shown) < NOTIFICATION_DURATION {
+                break;
+            }
+            self.notifications.pop_front();
+        }
+    }
+
+    /// The notification on screen at the given time
+    pub fn current_notification(&self, now: Instant) -> Option<&AchievementNotification> {
+        self.notifications.iter().find(|notification| {
+            notification.time_shown <= now
+                && now.duration_since(notification.time_shown) < NOTIFICATION_DURATION
+        })
+    }
+}
+
+impl Default for AchievementRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether the player picked up an item matching `predicate` from the ground, the items they start with don't count
+fn has_picked_up_where(world: &World, predicate: impl Fn(BlockID) -> bool) -> bool {
+    let inventory = world.read_storage::<Inventory>();
+    (&inventory).join().any(|inventory| inventory.picked_up.iter().any(|&item| predicate(item)))
+}
+
+fn is_in_block(world: &World, block: BlockID) -> bool {
+    let chunk_manager = world.read_resource::<Arc<ChunkManager>>();
+    let player_physics_state = world.read_storage::<Interpolator<PlayerPhysicsState>>();
+    (&player_physics_state).join().any(|player_physics_state| {
+        let position = player_physics_state.get_latest_state().position;
+        let (x, y, z) = (position.x.floor() as i32, position.y.floor() as i32, position.z.floor() as i32);
+        chunk_manager.get_block(x, y, z) == Some(block)
+            || chunk_manager.get_block(x, y + 1, z) == Some(block)
+    })
+}
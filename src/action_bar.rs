@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+
+use crate::constants::{GUI_SCALING, WINDOW_WIDTH};
+use crate::shader_compilation::ShaderProgram;
+use crate::text::TextRenderer;
+
+/// How long a message stays above the hotbar
+pub const ACTION_BAR_DURATION: Duration = Duration::from_secs(3);
+/// Time taken by a message to fade out at the end, in seconds
+const FADE_OUT_DURATION: f32 = 0.5;
+/// Height of the bottom of the text, above the health, armor and food bars
+const ACTION_BAR_HEIGHT: f32 = 52.0;
+
+/// Short message shown above the hotbar (e.g. when using a bed)
+#[derive(Default)]
+pub struct ActionBar {
+    message: Option<(String, Instant)>,
+}
+
+impl ActionBar {
+    /// Replaces the current message
+    pub fn show(&mut self, text: &str, now: Instant) {
+        info!("{}", text);
+        self.message = Some((text.to_string(), now));
+    }
+
+    pub fn draw(&self, now: Instant, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+        let (text, time_shown) = match &self.message {
+            Some(message) => message,
+            None => return,
+        };
+        let remaining = ACTION_BAR_DURATION.as_secs_f32() - now.saturating_duration_since(*time_shown).as_secs_f32();
+        if remaining <= 0.0 {
+            return;
+        }
+
+        let alpha = (remaining / FADE_OUT_DURATION).min(1.0);
+        let x = (WINDOW_WIDTH as f32 - TextRenderer::text_width(text, GUI_SCALING)) / 2.0;
+        let y = ACTION_BAR_HEIGHT * GUI_SCALING;
+        text_renderer.draw(text, x + GUI_SCALING, y - GUI_SCALING, GUI_SCALING, (0.25, 0.25, 0.25, alpha), shader);
+        text_renderer.draw(text, x, y, GUI_SCALING, (1.0, 1.0, 1.0, alpha), shader);
+    }
+}
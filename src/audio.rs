@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use nalgebra_glm::{distance2, Vec3};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+
+use crate::chunk::BlockID;
+use crate::constants::{FOOTSTEP_GRASS_SOUND, FOOTSTEP_SOUND, FOOTSTEP_STONE_SOUND, FOOTSTEP_WOOD_SOUND, SOUND_ATTENUATION};
+
+/// A sound to play, heard from everywhere or quieter the further the player is from its position
+#[derive(Debug, Copy, Clone)]
+pub struct PlaySound {
+    pub path: &'static str,
+    pub volume: f32,
+    pub position: Option<Vec3>,
+}
+
+impl PlaySound {
+    pub fn at(path: &'static str, volume: f32, position: Vec3) -> Self {
+        Self { path, volume, position: Some(position) }
+    }
+}
+
+/// The sounds asked for during the frame, they start playing at the end of it
+#[derive(Default)]
+pub struct SoundQueue {
+    pub events: Vec<PlaySound>,
+}
+
+impl SoundQueue {
+    pub fn play(&mut self, sound: PlaySound) {
+        self.events.push(sound);
+    }
+}
+
+lazy_static! {
+    /// The sound of the steps on each block, FOOTSTEP_SOUND for the others
+    pub static ref FOOTSTEP_SOUNDS: HashMap<BlockID, &'static str> = {
+        use BlockID::*;
+        let mut footstep_sounds = HashMap::new();
+        for &block in &[GrassBlock, Dirt, DirtPath] {
+            footstep_sounds.insert(block, FOOTSTEP_GRASS_SOUND);
+        }
+        for &block in &[Stone, Cobblestone, Bedrock, Obsidian, StoneBrick, Netherrack, NetherBrick, Granite, PolishedGranite,
+                        Diorite, PolishedDiorite, Andesite, PolishedAndesite, Furnace, Spawner, IronOre, DiamondOre] {
+            footstep_sounds.insert(block, FOOTSTEP_STONE_SOUND);
+        }
+        for &block in &[OakLog, OakPlanks, BirchLog, SpruceLog, JungleLog, Chest, CraftingTable, Bookshelf] {
+            footstep_sounds.insert(block, FOOTSTEP_WOOD_SOUND);
+        }
+        footstep_sounds
+    };
+}
+
+pub fn footstep_sound(block: BlockID) -> &'static str {
+    FOOTSTEP_SOUNDS.get(&block).cloned().unwrap_or(FOOTSTEP_SOUND)
+}
+
+/// Volume of a sound heard at the listener's position
+pub fn attenuated_volume(sound: &PlaySound, listener: &Vec3) -> f32 {
+    match sound.position {
+        Some(position) => sound.volume / (1.0 + distance2(&position, listener) * SOUND_ATTENUATION),
+        None => sound.volume,
+    }
+}
+
+/// Owns the audio output and a sink for each sound being played
+pub struct SoundSystem {
+    // The audio stops when the stream is dropped
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    sinks: Vec<Sink>,
+    /// The content of the sound files, read once
+    files: HashMap<&'static str, Arc<[u8]>>,
+}
+
+impl SoundSystem {
+    /// None when there is no audio device, the game is then silent
+    pub fn new() -> Option<Self> {
+        match OutputStream::try_default() {
+            Ok((stream, handle)) => Some(Self {
+                _stream: stream,
+                handle,
+                sinks: Vec::new(),
+                files: HashMap::new(),
+            }),
+            Err(err) => {
+                error!("No audio output: {}", err);
+                None
+            }
+        }
+    }
+
+    pub fn play(&mut self, path: &'static str, volume: f32) {
+        // Forget about the sounds that finished
+        self.sinks.retain(|sink| !sink.empty());
+
+        let bytes = match self.files.get(path) {
+            Some(bytes) => bytes.clone(),
+            None => match fs::read(path) {
+                Ok(bytes) => {
+                    let bytes: Arc<[u8]> = bytes.into();
+                    self.files.insert(path, bytes.clone());
+                    bytes
+                }
+                Err(err) => {
+                    error!("Filename: {}, error: {}", path, err);
+                    return;
+                }
+            },
+        };
+        let source = match Decoder::new(Cursor::new(bytes)) {
+            Ok(source) => source,
+            Err(err) => {
+                error!("Filename: {}, error: {}", path, err);
+                return;
+            }
+        };
+        let sink = match Sink::try_new(&self.handle) {
+            Ok(sink) => sink,
+            Err(err) => {
+                error!("{}", err);
+                return;
+            }
+        };
+        sink.set_volume(volume);
+        sink.append(source);
+        self.sinks.push(sink);
+    }
+}
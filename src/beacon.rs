@@ -0,0 +1,107 @@
+use std::any::Any;
+
+use crate::block_entity::BlockEntity;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{BEACON_BASE_RANGE, BEACON_MAX_LEVELS, BEACON_RANGE_PER_LEVEL, WORLD_HEIGHT_IN_CHUNKS};
+use crate::status_effect::StatusEffect;
+
+/// A beacon gives an effect to the players around it, when it stands on top of a pyramid of mineral blocks
+pub struct BeaconBlockEntity {
+    /// Number of complete layers of the pyramid under the beacon, from 0 to BEACON_MAX_LEVELS
+    pub levels: u8,
+    /// Chosen by right-clicking the beacon
+    pub effect: Option<StatusEffect>,
+}
+
+impl BeaconBlockEntity {
+    pub fn new() -> Self {
+        Self {
+            levels: 0,
+            effect: None,
+        }
+    }
+
+    /// The effects unlocked by a pyramid of `levels` layers
+    pub fn available_effects(levels: u8) -> &'static [StatusEffect] {
+        const EFFECTS: [StatusEffect; 5] = [
+            StatusEffect::Speed, StatusEffect::Haste,
+            StatusEffect::Resistance, StatusEffect::JumpBoost,
+            StatusEffect::Strength,
+        ];
+        match levels {
+            0 => &[],
+            1 => &EFFECTS[..2],
+            2 => &EFFECTS[..4],
+            _ => &EFFECTS,
+        }
+    }
+
+    /// Switches to the next effect unlocked by the pyramid, back to none after the last one
+    pub fn cycle_effect(&mut self) -> Option<StatusEffect> {
+        let available = Self::available_effects(self.levels);
+        self.effect = match self.effect.and_then(|effect| available.iter().position(|&e| e == effect)) {
+            Some(i) => available.get(i + 1).copied(),
+            None => available.first().copied(),
+        };
+        self.effect
+    }
+
+    /// The effect given to the players around and its level, if the pyramid still unlocks it.
+    /// A full pyramid gives Speed and Haste II
+    pub fn active_effect(&self) -> Option<(StatusEffect, u8)> {
+        let effect = self.effect.filter(|effect| Self::available_effects(self.levels).contains(effect))?;
+        let is_full_pyramid = self.levels >= BEACON_MAX_LEVELS;
+        let level = match effect {
+            StatusEffect::Speed | StatusEffect::Haste if is_full_pyramid => 2,
+            _ => 1,
+        };
+        Some((effect, level))
+    }
+
+    /// Horizontal distance up to which the players get the effect
+    pub fn effect_range(&self) -> f32 {
+        BEACON_BASE_RANGE + BEACON_RANGE_PER_LEVEL * self.levels as f32
+    }
+}
+
+impl BlockEntity for BeaconBlockEntity {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {}
+
+    fn serialize(&self) -> Vec<u8> {
+        vec![self.levels, self.effect.map_or(u8::MAX, |effect| effect as u8)]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Number of complete layers of the pyramid under the beacon at (x, y, z).
+/// The layer n blocks below the beacon is a (2n + 1) wide square of mineral blocks
+pub fn pyramid_levels(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> u8 {
+    for level in 1..=BEACON_MAX_LEVELS {
+        let size = level as i32;
+        let layer_y = y - size;
+        for layer_x in x - size..=x + size {
+            for layer_z in z - size..=z + size {
+                let is_base = chunk_manager.get_block(layer_x, layer_y, layer_z)
+                    .map_or(false, |block| block.is_beacon_base());
+                if !is_base {
+                    return level - 1;
+                }
+            }
+        }
+    }
+    BEACON_MAX_LEVELS
+}
+
+/// Whether the beam of the beacon at (x, y, z) can reach the top of the world, without any opaque block in its way
+pub fn is_beam_unobstructed(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> bool {
+    (y + 1..WORLD_HEIGHT_IN_CHUNKS * 16).all(|y| {
+        chunk_manager.get_block(x, y, z).map_or(true, |block| !block.is_opaque())
+    })
+}
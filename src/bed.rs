@@ -0,0 +1,62 @@
+use std::any::Any;
+
+use nalgebra_glm::{Vec3, vec3};
+
+use crate::block_entity::BlockEntity;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{SLEEP_FADE_DURATION, WAKE_UP_DURATION};
+
+/// Marks the head of a bed so that the bed gets drawn, the orientation is in the block meta
+#[derive(Default)]
+pub struct BedBlockEntity;
+
+impl BedBlockEntity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BlockEntity for BedBlockEntity {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {}
+
+    fn serialize(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Position of the head of the bed a half of which is at `(x, y, z)`, if there is a bed there
+pub fn bed_head_position(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> Option<(i32, i32, i32)> {
+    let block = chunk_manager.get_block(x, y, z)?;
+    if !block.is_bed() {
+        return None;
+    }
+    let meta = chunk_manager.get_block_meta(x, y, z)?;
+    if meta.is_bed_head() {
+        Some((x, y, z))
+    } else {
+        let (dx, dz) = meta.facing_offset();
+        Some((x + dx, y, z + dz))
+    }
+}
+
+/// Where the player respawns after sleeping in the bed, on top of its head
+pub fn bed_spawn_point((x, y, z): (i32, i32, i32)) -> Vec3 {
+    vec3(x as f32 + 0.5, y as f32 + 1.0, z as f32 + 0.5)
+}
+
+/// Opacity of the black screen `elapsed` seconds after going to bed
+pub fn sleep_darkness(elapsed: f32) -> f32 {
+    if elapsed < SLEEP_FADE_DURATION {
+        elapsed / SLEEP_FADE_DURATION
+    } else {
+        (1.0 - (elapsed - SLEEP_FADE_DURATION) / WAKE_UP_DURATION).max(0.0)
+    }
+}
@@ -0,0 +1,78 @@
+use std::any::Any;
+
+use crate::beacon::BeaconBlockEntity;
+use crate::bed::BedBlockEntity;
+use crate::campfire::CampfireBlockEntity;
+use crate::chest::ChestBlockEntity;
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::crafting_table::CraftingTableBlockEntity;
+use crate::dungeon::SpawnerBlockEntity;
+use crate::enchanting::EnchantingTableBlockEntity;
+use crate::fence_gate::FenceGateBlockEntity;
+use crate::furnace::FurnaceBlockEntity;
+use crate::inventory::item::ItemStack;
+use crate::mob::MobKind;
+use crate::observer::ObserverBlockEntity;
+use crate::piston::PistonBlockEntity;
+use crate::pressure_plate::PressurePlateBlockEntity;
+use crate::sign::SignBlockEntity;
+
+/// State attached to a single block of the world, stored in the `BlockEntityRegistry`
+pub trait BlockEntity: Any + Send + Sync {
+    /// Called once per game tick
+    fn tick(&mut self, position: (i32, i32, i32), chunk_manager: &ChunkManager);
+    /// The state of the block entity as bytes.
+    /// Nothing is saved with it yet: the chunks aren't written to disk, so neither are their block entities
+    fn serialize(&self) -> Vec<u8>;
+
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl dyn BlockEntity {
+    pub fn downcast_ref<T: BlockEntity>(&self) -> Option<&T> {
+        self.as_any().downcast_ref::<T>()
+    }
+
+    pub fn downcast_mut<T: BlockEntity>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut::<T>()
+    }
+}
+
+/// The block entity a block gets when placed, if it needs one
+pub fn create_block_entity(block: BlockID) -> Option<Box<dyn BlockEntity>> {
+    match block {
+        BlockID::Chest => Some(Box::new(ChestBlockEntity::new())),
+        BlockID::Furnace => Some(Box::new(FurnaceBlockEntity::new())),
+        BlockID::CraftingTable => Some(Box::new(CraftingTableBlockEntity::new())),
+        BlockID::Sign => Some(Box::new(SignBlockEntity::new())),
+        BlockID::BedHead => Some(Box::new(BedBlockEntity::new())),
+        BlockID::Piston |
+        BlockID::StickyPiston => Some(Box::new(PistonBlockEntity::new())),
+        BlockID::Observer => Some(Box::new(ObserverBlockEntity::new())),
+        BlockID::Spawner => Some(Box::new(SpawnerBlockEntity::new(MobKind::Zombie))),
+        BlockID::Beacon => Some(Box::new(BeaconBlockEntity::new())),
+        BlockID::StonePressurePlate |
+        BlockID::WoodenPressurePlate => Some(Box::new(PressurePlateBlockEntity::new())),
+        BlockID::OakFenceGate => Some(Box::new(FenceGateBlockEntity::new())),
+        BlockID::EnchantingTable => Some(Box::new(EnchantingTableBlockEntity::new())),
+        BlockID::Campfire |
+        BlockID::CampfireExtinguished => Some(Box::new(CampfireBlockEntity::new())),
+        _ => None,
+    }
+}
+
+/// Writes a slot as 1 byte for the item (0 if empty) followed by the amount on 4 bytes
+pub fn serialize_slot(slot: &Option<ItemStack>, bytes: &mut Vec<u8>) {
+    match slot {
+        Some(item_stack) => {
+            bytes.push(item_stack.item as u8);
+            bytes.extend(&item_stack.amount.to_le_bytes());
+        }
+        None => {
+            bytes.push(BlockID::Air as u8);
+            bytes.extend(&0u32.to_le_bytes());
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::VecDeque;
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+
+/// Light received from the warm (Glowstone) and cool (Sea Lantern) light sources, from 0 to 15 each.
+/// Both colors travel separately and are mixed in the fragment shader
+pub type BlockLight = (u8, u8);
+
+const WARM: usize = 0;
+const COOL: usize = 1;
+
+const NEIGHBOURS: [(i32, i32, i32); 6] = [
+    (1, 0, 0),
+    (-1, 0, 0),
+    (0, 1, 0),
+    (0, -1, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+
+#[inline]
+fn channel(light: BlockLight, channel: usize) -> u8 {
+    if channel == WARM { light.0 } else { light.1 }
+}
+
+#[inline]
+fn with_channel(light: BlockLight, channel: usize, level: u8) -> BlockLight {
+    if channel == WARM { (level, light.1) } else { (light.0, level) }
+}
+
+/// Light emitted by a block in each channel
+pub fn emitted_light(block: BlockID) -> BlockLight {
+    let level = block.light_level();
+    if block.emits_cool_light() { (0, level) } else { (level, 0) }
+}
+
+/// Must be called after the block at (x, y, z) changed.
+/// The light that went through the old block is removed first, then the surrounding lit blocks and the new
+/// block, if it emits light, spread it back. Every block whose light changed is marked dirty with `priority`
+pub fn update_block_light(chunk_manager: &ChunkManager, priority: i32, x: i32, y: i32, z: i32) {
+    let block = match chunk_manager.get_block(x, y, z) {
+        Some(block) => block,
+        None => return,
+    };
+
+    for &c in &[WARM, COOL] {
+        let mut refill = VecDeque::new();
+
+        let old_level = channel(chunk_manager.get_block_light(x, y, z), c);
+        if old_level > 0 {
+            remove_light(chunk_manager, priority, c, (x, y, z), old_level, &mut refill);
+        }
+
+        let emitted_level = channel(emitted_light(block), c);
+        if emitted_level > 0 {
+            set_level(chunk_manager, priority, c, (x, y, z), emitted_level);
+            refill.push_back((x, y, z));
+        } else if !block.is_opaque() {
+            // The light of the neighbours can flow into the block
+            for &(dx, dy, dz) in NEIGHBOURS.iter() {
+                let neighbour = (x + dx, y + dy, z + dz);
+                if channel(chunk_manager.get_block_light(neighbour.0, neighbour.1, neighbour.2), c) > 0 {
+                    refill.push_back(neighbour);
+                }
+            }
+        }
+
+        propagate_light(chunk_manager, priority, c, refill);
+    }
+}
+
+#[inline]
+fn set_level(chunk_manager: &ChunkManager, priority: i32, c: usize, (x, y, z): (i32, i32, i32), level: u8) {
+    let light = chunk_manager.get_block_light(x, y, z);
+    chunk_manager.set_block_light(priority, with_channel(light, c, level), x, y, z);
+}
+
+/// Darkens every block that was lit by the light of `start`.
+/// The blocks lit by other sources are queued in `refill` to spread their light back into the dark area
+fn remove_light(chunk_manager: &ChunkManager, priority: i32, c: usize, start: (i32, i32, i32), level: u8,
+                refill: &mut VecDeque<(i32, i32, i32)>) {
+    let mut queue = VecDeque::new();
+    set_level(chunk_manager, priority, c, start, 0);
+    queue.push_back((start, level));
+
+    while let Some(((x, y, z), level)) = queue.pop_front() {
+        for &(dx, dy, dz) in NEIGHBOURS.iter() {
+            let (n_x, n_y, n_z) = (x + dx, y + dy, z + dz);
+            let neighbour_level = channel(chunk_manager.get_block_light(n_x, n_y, n_z), c);
+            if neighbour_level == 0 {
+                continue;
+            }
+
+            if neighbour_level < level {
+                set_level(chunk_manager, priority, c, (n_x, n_y, n_z), 0);
+                queue.push_back(((n_x, n_y, n_z), neighbour_level));
+
+                // Light sources keep shining
+                let emitted_level = chunk_manager.get_block(n_x, n_y, n_z)
+                    .map_or(0, |block| channel(emitted_light(block), c));
+                if emitted_level > 0 {
+                    set_level(chunk_manager, priority, c, (n_x, n_y, n_z), emitted_level);
+                    refill.push_back((n_x, n_y, n_z));
+                }
+            } else {
+                refill.push_back((n_x, n_y, n_z));
+            }
+        }
+    }
+}
+
+/// Flood fills the light of the queued blocks, losing one level per block and stopping at opaque blocks
+fn propagate_light(chunk_manager: &ChunkManager, priority: i32, c: usize, mut queue: VecDeque<(i32, i32, i32)>) {
+    while let Some((x, y, z)) = queue.pop_front() {
+        let level = channel(chunk_manager.get_block_light(x, y, z), c);
+        if level <= 1 {
+            continue;
+        }
+
+        for &(dx, dy, dz) in NEIGHBOURS.iter() {
+            let (n_x, n_y, n_z) = (x + dx, y + dy, z + dz);
+            match chunk_manager.get_block(n_x, n_y, n_z) {
+                Some(block) if !block.is_opaque() => {}
+                _ => continue,
+            }
+
+            if channel(chunk_manager.get_block_light(n_x, n_y, n_z), c) + 2 <= level {
+                set_level(chunk_manager, priority, c, (n_x, n_y, n_z), level - 1);
+                queue.push_back((n_x, n_y, n_z));
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use crate::chunk::BlockID;
+use crate::chunk::BlockID::*;
+use crate::inventory::tool::{ToolKind, ToolMaterial};
+use crate::inventory::tool::ToolKind::*;
+
+/// How a block reacts to being mined and blown up
+#[derive(Debug, Copy, Clone)]
+pub struct BlockProperties {
+    /// Seconds needed to break the block by hand, infinite for unbreakable blocks
+    pub hardness: f32,
+    /// The kind of tool that breaks the block faster
+    pub best_tool: Option<ToolKind>,
+    /// The block survives the explosions weaker than this
+    pub blast_resistance: f32,
+    /// The item left in the world when the block is broken, if any
+    pub drop: Option<BlockID>,
+    /// The block only drops something when broken with its best tool made of this material or a better one
+    pub harvest_requires: Option<ToolMaterial>,
+}
+
+/// A block dropping itself when broken
+fn block(block: BlockID, hardness: f32, best_tool: Option<ToolKind>, blast_resistance: f32) -> (BlockID, BlockProperties) {
+    (block, BlockProperties { hardness, best_tool, blast_resistance, drop: Some(block), harvest_requires: None })
+}
+
+/// A block leaving nothing (or another item) behind when broken
+fn block_dropping(block: BlockID, hardness: f32, best_tool: Option<ToolKind>, blast_resistance: f32, drop: Option<BlockID>) -> (BlockID, BlockProperties) {
+    (block, BlockProperties { hardness, best_tool, blast_resistance, drop, harvest_requires: None })
+}
+
+/// A block that needs a tool made of `material` or of a better one to drop anything
+fn requiring(material: ToolMaterial, (block, properties): (BlockID, BlockProperties)) -> (BlockID, BlockProperties) {
+    (block, BlockProperties { harvest_requires: Some(material), ..properties })
+}
+
+/// Items that only exist in the inventory, they never have to be mined
+const ITEMS: [BlockID; 48] = [
+    Bread, Apple, RawBeef, CookedBeef, RottenFlesh,
+    LeatherHelmet, LeatherChestplate, LeatherLeggings, LeatherBoots,
+    GoldenHelmet, GoldenChestplate, GoldenLeggings, GoldenBoots,
+    IronHelmet, IronChestplate, IronLeggings, IronBoots,
+    DiamondHelmet, DiamondChestplate, DiamondLeggings, DiamondBoots,
+    Snowball, Arrow, Crossbow, Compass, FlintAndSteel, Map, FireworkRocket,
+    IronIngot, GoldIngot, Diamond, BlazeRod, NetherWart, Stick,
+    WoodenPickaxe, StonePickaxe, IronPickaxe, DiamondPickaxe,
+    WoodenAxe, StoneAxe, IronAxe, DiamondAxe,
+    WoodenShovel, StoneShovel, IronShovel, DiamondShovel,
+    WoodenSword, StoneSword,
+];
+
+lazy_static! {
+    /// The properties of every block and item
+    pub static ref BLOCK_PROPERTIES: HashMap<BlockID, BlockProperties> = {
+        let infinity = std::f32::INFINITY;
+        let mut block_properties: HashMap<BlockID, BlockProperties> = vec![
+            block_dropping(Air, 0.0, None, 0.0, None),
+            block(Dirt, 0.5, Some(Shovel), 0.5),
+            block(GrassBlock, 0.6, Some(Shovel), 0.6),
+            block_dropping(DirtPath, 0.65, Some(Shovel), 0.65, Some(Dirt)),
+            // Turns into cobblestone unless mined with Silk Touch
+            requiring(ToolMaterial::Wood, block_dropping(Stone, 1.5, Some(Pickaxe), 6.0, Some(Cobblestone))),
+            requiring(ToolMaterial::Wood, block(Cobblestone, 2.0, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Stone, block(IronOre, 3.0, Some(Pickaxe), 3.0)),
+            requiring(ToolMaterial::Iron, block_dropping(DiamondOre, 3.0, Some(Pickaxe), 3.0, Some(Diamond))),
+            block(Bedrock, infinity, None, infinity),
+            requiring(ToolMaterial::Diamond, block(Obsidian, 9.4, Some(Pickaxe), 1200.0)),
+            block(OakLog, 2.0, Some(Axe), 2.0),
+            block(OakLeaves, 0.2, None, 0.2),
+            block(OakPlanks, 2.0, Some(Axe), 3.0),
+            // Shatters unless mined with Silk Touch
+            block_dropping(Glass, 0.3, None, 0.3, None),
+            block_dropping(Water, 100.0, None, 100.0, None),
+            block(Chest, 2.5, Some(Axe), 2.5),
+            requiring(ToolMaterial::Wood, block(Furnace, 3.5, Some(Pickaxe), 3.5)),
+            requiring(ToolMaterial::Wood, block(Netherrack, 0.4, Some(Pickaxe), 0.4)),
+            block_dropping(Lava, 100.0, None, 100.0, None),
+            block_dropping(NetherPortal, infinity, None, 0.0, None),
+            block(Sign, 1.0, Some(Axe), 1.0),
+            // The item of a bed is its foot
+            block_dropping(BedHead, 0.2, Some(Axe), 0.2, Some(BedFoot)),
+            block(BedFoot, 0.2, Some(Axe), 0.2),
+            block(Tnt, 0.0, None, 0.0),
+            block(Glowstone, 0.3, None, 0.3),
+            block(SeaLantern, 0.3, None, 0.3),
+            block(Ladder, 0.4, Some(Axe), 0.4),
+            block(RedstoneWire, 0.0, None, 0.0),
+            requiring(ToolMaterial::Wood, block(RedstoneBlock, 5.0, Some(Pickaxe), 6.0)),
+            block(Piston, 0.5, Some(Pickaxe), 0.5),
+            block(StickyPiston, 0.5, Some(Pickaxe), 0.5),
+            requiring(ToolMaterial::Wood, block(Observer, 3.0, Some(Pickaxe), 3.0)),
+            block_dropping(PistonHead, 0.5, Some(Pickaxe), 0.5, None),
+            block_dropping(PistonMoving, 1.0, None, 0.0, None),
+            requiring(ToolMaterial::Wood, block(StoneBrick, 1.5, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Wood, block(Spawner, 5.0, Some(Pickaxe), 5.0)),
+            requiring(ToolMaterial::Wood, block(StonePressurePlate, 0.5, Some(Pickaxe), 0.5)),
+            block(WoodenPressurePlate, 0.5, Some(Axe), 0.5),
+            block(CraftingTable, 2.5, Some(Axe), 2.5),
+            block_dropping(Fire, 0.0, None, 0.0, None),
+            block(BirchLog, 2.0, Some(Axe), 2.0),
+            block(BirchLeaves, 0.2, None, 0.2),
+            block(SpruceLog, 2.0, Some(Axe), 2.0),
+            block(SpruceLeaves, 0.2, None, 0.2),
+            block(JungleLog, 2.0, Some(Axe), 2.0),
+            block(JungleLeaves, 0.2, None, 0.2),
+            block(Beacon, 3.0, None, 3.0),
+            requiring(ToolMaterial::Stone, block(IronBlock, 5.0, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Iron, block(GoldBlock, 3.0, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Iron, block(DiamondBlock, 5.0, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Iron, block(EmeraldBlock, 5.0, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Wood, block(NetherBrick, 2.0, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Wood, block(Granite, 1.5, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Wood, block(PolishedGranite, 1.5, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Wood, block(Diorite, 1.5, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Wood, block(PolishedDiorite, 1.5, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Wood, block(Andesite, 1.5, Some(Pickaxe), 6.0)),
+            requiring(ToolMaterial::Wood, block(PolishedAndesite, 1.5, Some(Pickaxe), 6.0)),
+            block(OakFenceGate, 2.0, Some(Axe), 3.0),
+            requiring(ToolMaterial::Wood, block(EnchantingTable, 5.0, Some(Pickaxe), 1200.0)),
+            block(Bookshelf, 1.5, Some(Axe), 1.5),
+            block(SlimeBlock, 0.0, None, 0.0),
+            block(Gravel, 0.6, Some(Shovel), 0.6),
+            block(Campfire, 2.0, Some(Axe), 2.0),
+            block_dropping(CampfireExtinguished, 2.0, Some(Axe), 2.0, Some(Campfire)),
+            block(Urss, 1.0, None, 1.0),
+            block(Hitler, 1.0, None, 1.0),
+            block(Debug, 1.0, None, 1.0),
+            block(Debug2, 1.0, None, 1.0),
+        ].into_iter().collect();
+
+        for &item in ITEMS.iter() {
+            block_properties.insert(item, BlockProperties { hardness: 1.0, best_tool: None, blast_resistance: 0.0, drop: Some(item), harvest_requires: None });
+        }
+        block_properties
+    };
+}
@@ -1,4 +1,4 @@
-use crate::types::{UVFaces, TextureLayer};
+use crate::types::{EmissiveFaces, UVFaces, TextureLayer};
 
 #[derive(Copy, Clone)]
 pub enum BlockFaces<T> {
@@ -7,10 +7,10 @@ pub enum BlockFaces<T> {
     Each { top: T, bottom: T, front: T, back: T, left: T, right: T },
 }
 
-/// Unpacks a BlockFaces<UVCoords> instance and returns a tuple of UV coordinates
-/// for each face of the block
-impl BlockFaces<TextureLayer> {
-    pub fn get_uv_of_every_face(&self) -> UVFaces {
+impl<T: Copy> BlockFaces<T> {
+    /// Unpacks a BlockFaces instance and returns a tuple of the value of each face:
+    /// front, back, top, bottom, left and right
+    pub fn get_every_face(&self) -> (T, T, T, T, T, T) {
         match self {
             &BlockFaces::All(uv) => (uv, uv, uv, uv, uv, uv),
             &BlockFaces::Sides { sides, top, bottom } =>
@@ -20,3 +20,31 @@ impl BlockFaces<TextureLayer> {
         }
     }
 }
+
+/// Unpacks a BlockFaces<UVCoords> instance and returns a tuple of UV coordinates
+/// for each face of the block
+impl BlockFaces<TextureLayer> {
+    pub fn get_uv_of_every_face(&self) -> UVFaces {
+        self.get_every_face()
+    }
+}
+
+/// The texture layers of the faces of a block.
+/// The faces glowing in the dark have an emissive mask in the layer right after their own:
+/// its opaque texels are drawn at full brightness, whatever the light around them
+#[derive(Copy, Clone)]
+pub struct BlockTextures {
+    pub faces: BlockFaces<TextureLayer>,
+    pub emissive: BlockFaces<bool>,
+}
+
+impl BlockTextures {
+    pub fn get_uv_of_every_face(&self) -> UVFaces {
+        self.faces.get_uv_of_every_face()
+    }
+
+    /// Whether each face has an emissive mask, in the same order as `get_uv_of_every_face`
+    pub fn get_emissive_of_every_face(&self) -> EmissiveFaces {
+        self.emissive.get_every_face()
+    }
+}
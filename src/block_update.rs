@@ -0,0 +1,205 @@
+use std::collections::{HashSet, VecDeque};
+
+use crate::chunk::{BlockID, BlockMeta};
+use crate::campfire::is_doused;
+use crate::chunk_manager::ChunkManager;
+use crate::dimension::is_portal_held;
+
+const NEIGHBOURS: [(i32, i32, i32); 6] = [
+    (1, 0, 0),
+    (-1, 0, 0),
+    (0, 1, 0),
+    (0, -1, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+
+const HORIZONTAL_NEIGHBOURS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// Blocks waiting to react to a change of one of their neighbours.
+/// A block is queued once however many of its neighbours changed, and is updated at most once per tick:
+/// notified again in the same tick, it waits for the next one. Blocks changing each other back and forth
+/// keep going one step per tick instead of looping endlessly
+#[derive(Default)]
+pub struct BlockUpdateQueue {
+    pub queue: VecDeque<(i32, i32, i32)>,
+    /// The blocks in the queue or waiting for the next tick
+    queued: HashSet<(i32, i32, i32)>,
+    /// The blocks updated during this tick
+    updated: HashSet<(i32, i32, i32)>,
+    /// The blocks to update during the next tick
+    deferred: Vec<(i32, i32, i32)>,
+    /// Every block that changed, kept until the simulations relying on them (e.g. redstone) take them
+    pub recent_changes: Vec<(i32, i32, i32)>,
+}
+
+impl BlockUpdateQueue {
+    /// Must be called after the block at (x, y, z) changed
+    pub fn enqueue_neighbours(&mut self, (x, y, z): (i32, i32, i32)) {
+        self.recent_changes.push((x, y, z));
+        for &(dx, dy, dz) in NEIGHBOURS.iter() {
+            let neighbour = (x + dx, y + dy, z + dz);
+            if self.queued.insert(neighbour) {
+                self.queue.push_back(neighbour);
+            }
+        }
+    }
+
+    /// The next block to update during this tick, the ones already updated are put off to the next tick
+    pub fn pop(&mut self) -> Option<(i32, i32, i32)> {
+        while let Some(position) = self.queue.pop_front() {
+            if self.updated.insert(position) {
+                self.queued.remove(&position);
+                return Some(position);
+            }
+            self.deferred.push(position);
+        }
+        None
+    }
+
+    /// Must be called at the beginning of every tick, the blocks put off during the previous one are queued again
+    pub fn start_tick(&mut self) {
+        self.updated.clear();
+        self.queue.extend(self.deferred.drain(..));
+    }
+}
+
+/// Water reaching a lava source turns it into obsidian, flowing lava meeting water turns into cobblestone
+fn cooled_lava(lava_meta: BlockMeta) -> BlockID {
+    if lava_meta.flow_level() == 0 {
+        BlockID::Obsidian
+    } else {
+        BlockID::Cobblestone
+    }
+}
+
+/// How a block reacts to the changes around it
+pub trait BlockUpdateBehavior {
+    /// Called when a neighbour of the block at `position` changed.
+    /// The changes made through `chunk_manager` cascade to their own neighbours
+    fn on_neighbor_change(&self, position: (i32, i32, i32), chunk_manager: &ChunkManager);
+}
+
+impl BlockUpdateBehavior for BlockID {
+    fn on_neighbor_change(&self, (x, y, z): (i32, i32, i32), chunk_manager: &ChunkManager) {
+        match self {
+            // Ladders fall off when the block they hang on is gone
+            BlockID::Ladder => {
+                let (dx, dz) = chunk_manager.get_block_meta(x, y, z).unwrap_or_default().facing_offset();
+                if !chunk_manager.is_solid_block_at(x - dx, y, z - dz) {
+                    chunk_manager.put_block(BlockID::Air, x, y, z);
+                    info!("Ladder at ({} {} {}) fell off", x, y, z);
+                }
+            }
+            // Redstone wires, pressure plates and fire need a block to lie on
+            BlockID::RedstoneWire | BlockID::StonePressurePlate | BlockID::WoodenPressurePlate | BlockID::Fire => {
+                if !chunk_manager.is_solid_block_at(x, y - 1, z) {
+                    chunk_manager.put_block(BlockID::Air, x, y, z);
+                }
+            }
+            // A dirt path is flattened back into dirt under a block
+            BlockID::DirtPath => {
+                if chunk_manager.get_block(x, y + 1, z).map_or(false, |block| block.is_opaque()) {
+                    chunk_manager.put_block(BlockID::Dirt, x, y, z);
+                }
+            }
+            BlockID::Campfire => {
+                if is_doused(chunk_manager, (x, y, z)) {
+                    chunk_manager.put_block(BlockID::CampfireExtinguished, x, y, z);
+                    info!("Campfire at ({} {} {}) was put out by water", x, y, z);
+                }
+            }
+            // A portal goes out once a block of its frame is broken, its blocks going out one after the other
+            BlockID::NetherPortal => {
+                if !is_portal_held(chunk_manager, (x, y, z)) {
+                    chunk_manager.put_block(BlockID::Air, x, y, z);
+                }
+            }
+            // The head of a piston goes away with the piston, and the piston retracts without its head
+            BlockID::PistonHead => {
+                let meta = chunk_manager.get_block_meta(x, y, z).unwrap_or_default();
+                let (dx, dz) = meta.facing_offset();
+                let is_held = chunk_manager.get_block(x - dx, y, z - dz).map_or(false, |block| block.is_piston())
+                    && chunk_manager.get_block_meta(x - dx, y, z - dz).map_or(false, |base| base.is_extended() && base.facing() == meta.facing());
+                if !is_held {
+                    chunk_manager.put_block(BlockID::Air, x, y, z);
+                }
+            }
+            BlockID::Piston | BlockID::StickyPiston => {
+                let meta = chunk_manager.get_block_meta(x, y, z).unwrap_or_default();
+                let (dx, dz) = meta.facing_offset();
+                if meta.is_extended() && chunk_manager.get_block(x + dx, y, z + dz) != Some(BlockID::PistonHead) {
+                    chunk_manager.set_block_meta(meta.with_extended(false), x, y, z);
+                }
+            }
+            // Lava next to water on the same level cools down
+            BlockID::Lava => {
+                let is_next_to_water = HORIZONTAL_NEIGHBOURS.iter()
+                    .any(|&(dx, dz)| chunk_manager.get_block(x + dx, y, z + dz) == Some(BlockID::Water));
+                if is_next_to_water {
+                    let meta = chunk_manager.get_block_meta(x, y, z).unwrap_or_default();
+                    chunk_manager.put_block(cooled_lava(meta), x, y, z);
+                }
+            }
+            BlockID::Water => {
+                for &(dx, dz) in HORIZONTAL_NEIGHBOURS.iter() {
+                    if chunk_manager.get_block(x + dx, y, z + dz) == Some(BlockID::Lava) {
+                        let lava_meta = chunk_manager.get_block_meta(x + dx, y, z + dz).unwrap_or_default();
+                        chunk_manager.put_block(cooled_lava(lava_meta), x + dx, y, z + dz);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::chunk::ChunkColumn;
+
+    #[test]
+    fn blocks_changing_each_other_are_updated_once_per_tick() {
+        let mut queue = BlockUpdateQueue::default();
+        queue.start_tick();
+        queue.enqueue_neighbours((0, 0, 0));
+        // Two neighbours changing only queue the block between them once
+        queue.enqueue_neighbours((2, 0, 0));
+        assert_eq!(queue.queue.len(), 11);
+
+        // (0, 0, 0) and (1, 0, 0) change every time they are updated, notifying each other
+        let mut updated = Vec::new();
+        while let Some(position) = queue.pop() {
+            updated.push(position);
+            if position == (0, 0, 0) || position == (1, 0, 0) {
+                queue.enqueue_neighbours(position);
+            }
+        }
+        let unique: HashSet<_> = updated.iter().cloned().collect();
+        assert_eq!(unique.len(), updated.len());
+        assert!(unique.contains(&(0, 0, 0)) && unique.contains(&(1, 0, 0)));
+
+        // The cascade isn't cut short, it goes on in the next tick
+        queue.start_tick();
+        let next_tick: Vec<_> = std::iter::from_fn(|| queue.pop()).collect();
+        assert!(next_tick.contains(&(1, 0, 0)));
+    }
+
+    #[test]
+    fn water_turns_lava_sources_into_obsidian_and_flowing_lava_into_cobblestone() {
+        let chunk_manager = ChunkManager::default();
+        chunk_manager.add_chunk_column((0, 0), Arc::new(ChunkColumn::new()));
+        chunk_manager.set_block(BlockID::Lava, 1, 1, 1);
+        chunk_manager.set_block(BlockID::Lava, 3, 1, 1);
+        chunk_manager.set_block_meta(BlockMeta::default().with_flow_level(3), 3, 1, 1);
+        chunk_manager.set_block(BlockID::Water, 2, 1, 1);
+        chunk_manager.set_block_meta(BlockMeta::default().with_flow_level(1), 2, 1, 1);
+
+        BlockID::Water.on_neighbor_change((2, 1, 1), &chunk_manager);
+        assert_eq!(chunk_manager.get_block(1, 1, 1), Some(BlockID::Obsidian));
+        assert_eq!(chunk_manager.get_block(3, 1, 1), Some(BlockID::Cobblestone));
+    }
+}
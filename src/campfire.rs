@@ -0,0 +1,121 @@
+use std::any::Any;
+
+use crate::block_entity::BlockEntity;
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{CAMPFIRE_COOK_TIME, CAMPFIRE_FRAMES_PER_SECOND, CAMPFIRE_SLOTS};
+use crate::furnace::SMELTING_RESULTS;
+use crate::types::TexturePack;
+
+/// Frames of the animation of the flames, the first one followed by the ones of `create_animation_frames_map`
+const CAMPFIRE_FRAMES: u32 = 4;
+
+/// What a campfire turns `item` into, only food can be cooked on it
+pub fn cooked_food(item: BlockID) -> Option<BlockID> {
+    SMELTING_RESULTS.get(&item).cloned().filter(|_| item.food_value().is_some())
+}
+
+/// The food put on a campfire. It only cooks while the campfire is lit and waits on it once cooked,
+/// until the player takes it
+#[derive(Default)]
+pub struct CampfireBlockEntity {
+    /// The raw food of each slot with the game ticks it has been cooking for
+    pub cooking: [Option<(BlockID, u32)>; CAMPFIRE_SLOTS],
+    pub cooked: [Option<BlockID>; CAMPFIRE_SLOTS],
+}
+
+impl CampfireBlockEntity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puts the raw food in the first free slot. Returns whether there was room for it
+    pub fn add_food(&mut self, item: BlockID) -> bool {
+        if cooked_food(item).is_none() {
+            return false;
+        }
+        match self.cooking.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some((item, 0));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Empties the slots of the cooked food
+    pub fn take_cooked(&mut self) -> Vec<BlockID> {
+        self.cooked.iter_mut().filter_map(|slot| slot.take()).collect()
+    }
+
+    /// Everything on the campfire, raw or cooked, dropped when it is broken
+    pub fn items(&self) -> Vec<BlockID> {
+        self.cooking.iter().flatten().map(|&(item, _)| item)
+            .chain(self.cooked.iter().flatten().cloned())
+            .collect()
+    }
+}
+
+impl BlockEntity for CampfireBlockEntity {
+    /// Cooks the food by one tick, the cooked food waits in the cooking slot until a slot of the cooked food is free
+    fn tick(&mut self, (x, y, z): (i32, i32, i32), chunk_manager: &ChunkManager) {
+        if chunk_manager.get_block(x, y, z) != Some(BlockID::Campfire) {
+            return;
+        }
+
+        for i in 0..CAMPFIRE_SLOTS {
+            let item = match &mut self.cooking[i] {
+                Some((item, progress)) => {
+                    *progress = (*progress + 1).min(CAMPFIRE_COOK_TIME);
+                    if *progress < CAMPFIRE_COOK_TIME {
+                        continue;
+                    }
+                    *item
+                }
+                None => continue,
+            };
+            if let Some(output) = self.cooked.iter_mut().find(|slot| slot.is_none()) {
+                *output = cooked_food(item);
+                self.cooking[i] = None;
+            }
+        }
+    }
+
+    /// The item and the cook progress of each cooking slot followed by the item of each slot of the cooked food
+    fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for slot in self.cooking.iter() {
+            let (item, progress) = slot.unwrap_or((BlockID::Air, 0));
+            bytes.push(item as u8);
+            bytes.extend(&progress.to_le_bytes());
+        }
+        for slot in self.cooked.iter() {
+            bytes.push(slot.unwrap_or(BlockID::Air) as u8);
+        }
+        bytes
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Whether water is flowing on top of the campfire at (x, y, z) or next to it, putting it out
+pub fn is_doused(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> bool {
+    [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, 0, 1), (0, 0, -1)].iter()
+        .any(|&(dx, dy, dz)| chunk_manager.get_block(x + dx, y + dy, z + dz) == Some(BlockID::Water))
+}
+
+/// The layer of the first frame of the flames of the campfires and the layer of the frame shown at `global_time`
+pub fn campfire_flames_layers(texture_pack: &TexturePack, global_time: f32) -> (i32, i32) {
+    let textures = &texture_pack[&BlockID::Campfire];
+    let first_frame = textures.get_uv_of_every_face().0;
+    // The frames are separated by their emissive masks
+    let layers_per_frame = 1 + textures.get_emissive_of_every_face().0 as u32;
+    let frame = (global_time * CAMPFIRE_FRAMES_PER_SECOND) as u32 % CAMPFIRE_FRAMES;
+    (first_frame as i32, (first_frame + frame * layers_per_frame) as i32)
+}
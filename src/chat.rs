@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::constants::GUI_SCALING;
+use crate::shader_compilation::ShaderProgram;
+use crate::text::{GLYPH_HEIGHT, TextRenderer};
+
+/// Messages kept in the log, the oldest ones are forgotten
+pub const CHAT_LOG_SIZE: usize = 100;
+/// Characters that can be typed in a message
+const CHAT_MAX_LENGTH: usize = 64;
+/// Lines of the log shown at once
+const CHAT_VISIBLE_LINES: usize = 10;
+/// Seconds a message stays on screen while the chat is closed, the last one of them fading out
+const MESSAGE_DURATION: f32 = 10.0;
+const FADE_OUT_DURATION: f32 = 1.0;
+/// Height of a line and of the bottom of the log, above the hotbar, in GUI pixels
+const LINE_HEIGHT: f32 = 10.0;
+const LOG_BOTTOM: f32 = 64.0;
+const MARGIN: f32 = 4.0;
+const LOG_WIDTH: f32 = 320.0;
+
+const BACKGROUND_COLOR: (f32, f32, f32, f32) = (0.0, 0.0, 0.0, 0.5);
+const TEXT_COLOR: (f32, f32, f32, f32) = (1.0, 1.0, 1.0, 1.0);
+
+/// The name of the player in singleplayer
+pub const PLAYER_NAME: &str = "Player";
+
+/// Width of the background of a line, wider than LOG_WIDTH for the long ones
+fn line_width(line: &str) -> f32 {
+    (LOG_WIDTH * GUI_SCALING).max(TextRenderer::text_width(line, GUI_SCALING) + 2.0 * MARGIN * GUI_SCALING)
+}
+
+#[derive(Debug, Clone)]
+pub struct ChatMessage {
+    /// Empty for the answers of the commands
+    pub sender: String,
+    pub text: String,
+    pub time: Instant,
+}
+
+impl ChatMessage {
+    fn line(&self) -> String {
+        if self.sender.is_empty() {
+            self.text.clone()
+        } else {
+            format!("<{}> {}", self.sender, self.text)
+        }
+    }
+}
+
+/// The messages sent during the game and the one being typed, opened with T
+#[derive(Default)]
+pub struct Chat {
+    pub chat_input: String,
+    pub message_log: VecDeque<ChatMessage>,
+    /// Lines scrolled up from the latest message while the chat is open
+    pub scroll: usize,
+}
+
+impl Chat {
+    pub fn open(&mut self) {
+        self.chat_input.clear();
+        self.scroll = 0;
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if self.chat_input.chars().count() < CHAT_MAX_LENGTH {
+            self.chat_input.push(c);
+        }
+    }
+
+    pub fn delete_char(&mut self) {
+        self.chat_input.pop();
+    }
+
+    /// Scrolls the log up for a positive `delta`, towards the older messages
+    pub fn scroll(&mut self, delta: f64) {
+        let max_scroll = self.message_log.len().saturating_sub(CHAT_VISIBLE_LINES);
+        self.scroll = if delta > 0.0 {
+            (self.scroll + 1).min(max_scroll)
+        } else {
+            self.scroll.saturating_sub(1)
+        };
+    }
+
+    /// Takes the typed message out of the input, None when nothing was typed
+    pub fn take_input(&mut self) -> Option<String> {
+        let text = std::mem::take(&mut self.chat_input).trim().to_string();
+        if text.is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    }
+
+    pub fn add_message(&mut self, sender: &str, text: &str, now: Instant) {
+        let message = ChatMessage {
+            sender: sender.to_string(),
+            text: text.to_string(),
+            time: now,
+        };
+        info!("[Chat] {}", message.line());
+        if self.message_log.len() >= CHAT_LOG_SIZE {
+            self.message_log.pop_front();
+        }
+        self.message_log.push_back(message);
+    }
+
+    /// Draws the latest messages in the bottom left corner. While the chat is open, the whole log can be scrolled
+    /// and the message being typed is shown below it, otherwise the messages fade out after MESSAGE_DURATION
+    pub fn draw(&self, is_open: bool, now: Instant, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+        let left = MARGIN * GUI_SCALING;
+        let line_height = LINE_HEIGHT * GUI_SCALING;
+        let text_offset = (LINE_HEIGHT - GLYPH_HEIGHT) / 2.0 * GUI_SCALING;
+
+        if is_open {
+            let input = format!("> {}_", self.chat_input);
+            text_renderer.draw_rect(left, left, line_width(&input), line_height, BACKGROUND_COLOR, shader);
+            text_renderer.draw(&input, 2.0 * left, left + text_offset, GUI_SCALING, TEXT_COLOR, shader);
+        }
+
+        let skipped = if is_open { self.scroll } else { 0 };
+        let lines = self.message_log.iter().rev().skip(skipped).take(CHAT_VISIBLE_LINES);
+        for (i, message) in lines.enumerate() {
+            let alpha = if is_open {
+                1.0
+            } else {
+                let remaining = MESSAGE_DURATION - now.saturating_duration_since(message.time).as_secs_f32();
+                if remaining <= 0.0 {
+                    // The older messages are gone too
+                    break;
+                }
+                (remaining / FADE_OUT_DURATION).min(1.0)
+            };
+            let line = message.line();
+            let y = LOG_BOTTOM * GUI_SCALING + i as f32 * line_height;
+            let (r, g, b, a) = BACKGROUND_COLOR;
+            text_renderer.draw_rect(left, y, line_width(&line), line_height, (r, g, b, a * alpha), shader);
+            let (r, g, b, _) = TEXT_COLOR;
+            text_renderer.draw(&line, 2.0 * left, y + text_offset, GUI_SCALING, (r, g, b, alpha), shader);
+        }
+    }
+}
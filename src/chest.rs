@@ -0,0 +1,168 @@
+use std::any::Any;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::{Mat4, vec3};
+
+use crate::block_entity::{BlockEntity, serialize_slot};
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{GUI_SCALING, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::inventory::item::ItemStack;
+use crate::shader_compilation::ShaderProgram;
+use crate::types::TexturePack;
+
+pub const CHEST_SIZE: usize = 27;
+pub const CHEST_ROWS: usize = 3;
+pub const CHEST_COLUMNS: usize = 9;
+
+pub struct ChestBlockEntity(pub Vec<Option<ItemStack>>);
+
+impl Default for ChestBlockEntity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockEntity for ChestBlockEntity {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {}
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for slot in self.0.iter() {
+            serialize_slot(slot, &mut bytes);
+        }
+        bytes
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The chest of a generated structure, holding the items rolled for it on the world generation thread.
+/// It is replaced by a `ChestBlockEntity` on the main thread, where its item stacks can create their GL buffers
+pub struct LootChestBlockEntity {
+    pub loot: Vec<(BlockID, u32)>,
+}
+
+impl LootChestBlockEntity {
+    pub fn new(loot: Vec<(BlockID, u32)>) -> Self {
+        Self { loot }
+    }
+}
+
+impl BlockEntity for LootChestBlockEntity {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {}
+
+    /// The same slots as the chest it becomes
+    fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for i in 0..CHEST_SIZE {
+            let (item, amount) = self.loot.get(i).cloned().unwrap_or((BlockID::Air, 0));
+            bytes.push(item as u8);
+            bytes.extend(&amount.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl ChestBlockEntity {
+    pub fn new() -> Self {
+        ChestBlockEntity(vec![None; CHEST_SIZE])
+    }
+
+    /// A chest filled with the items, those that don't fit are left out
+    pub fn with_items(items: &[(BlockID, u32)]) -> Self {
+        let mut chest = Self::new();
+        for &(item, count) in items {
+            chest.add_item_stack(ItemStack::new(count, item));
+        }
+        chest
+    }
+
+    /// Puts the item stack in the first empty slot.
+    /// Gives back the item stack if the chest is full.
+    pub fn add_item_stack(&mut self, item_stack: ItemStack) -> Option<ItemStack> {
+        match self.0.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some(item_stack);
+                None
+            }
+            None => Some(item_stack)
+        }
+    }
+
+    pub fn update_dirty_items(&mut self, texture_pack: &TexturePack) {
+        for item_stack in self.0.iter_mut() {
+            if let Some(item_stack) = item_stack {
+                item_stack.update_if_dirty(&texture_pack);
+            }
+        }
+    }
+
+    /// Center of a slot in screen coordinates.
+    /// The rows are stacked above the hotbar, the first row being the highest one.
+    pub fn slot_position(index: usize) -> (f32, f32) {
+        let interslot_spacing = 20.0;
+        let left_margin = WINDOW_WIDTH as f32 / 2.0 - 4.0 * interslot_spacing * GUI_SCALING;
+        let row = index / CHEST_COLUMNS;
+        let column = index % CHEST_COLUMNS;
+        (
+            left_margin + column as f32 * interslot_spacing * GUI_SCALING,
+            (45.0 + 22.0 * (CHEST_ROWS - 1 - row) as f32) * GUI_SCALING,
+        )
+    }
+
+    /// Returns the slot under the given point in screen coordinates (y pointing up)
+    pub fn slot_at(x: f32, y: f32) -> Option<usize> {
+        let half_slot_size = 10.0 * GUI_SCALING;
+        (0..CHEST_SIZE).find(|&i| {
+            let (slot_x, slot_y) = ChestBlockEntity::slot_position(i);
+            (x - slot_x).abs() <= half_slot_size && (y - slot_y).abs() <= half_slot_size
+        })
+    }
+
+    /// The background of every row reuses the hotbar texture
+    pub fn draw(&self, hotbar_vao: u32, shader: &mut ShaderProgram) {
+        let projection_matrix = nalgebra_glm::ortho(
+            0.0, WINDOW_WIDTH as f32, 0.0, WINDOW_HEIGHT as f32, -5.0, 5.0);
+
+        shader.use_program();
+        shader.set_uniform_matrix4fv("projection", projection_matrix.as_ptr());
+        shader.set_uniform1i("tex", 2);
+        gl_call!(gl::BindVertexArray(hotbar_vao));
+
+        for row in 0..CHEST_ROWS {
+            let (_, row_y) = ChestBlockEntity::slot_position(row * CHEST_COLUMNS);
+            let model_matrix = {
+                let translate_matrix = Matrix4::new_translation(&vec3(
+                    WINDOW_WIDTH as f32 / 2.0, row_y, 0.0));
+                let scale_matrix: Mat4 = Matrix4::new_nonuniform_scaling(&vec3(182.0 * GUI_SCALING, 22.0 * GUI_SCALING, 1.0));
+                translate_matrix * scale_matrix
+            };
+            shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+            gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 6));
+        }
+    }
+
+    pub fn draw_items(&self, shader: &mut ShaderProgram) {
+        for (i, slot) in self.0.iter().enumerate() {
+            if let Some(slot) = slot {
+                let (x, y) = ChestBlockEntity::slot_position(i);
+                slot.item_render.draw(x, y, shader);
+            }
+        }
+    }
+}
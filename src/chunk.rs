@@ -2,12 +2,20 @@ use bit_vec::BitVec;
 use rand::{random, Rng};
 use rand::distributions::Standard;
 use rand::prelude::Distribution;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::c_void;
 use std::ptr::null;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use crate::chunk_manager::{CHUNK_SIZE, CHUNK_VOLUME};
+use crate::constants::{MAX_INCREMENTAL_DIRTY_BLOCKS, MESH_SLACK_VERTICES, WORLD_HEIGHT_IN_CHUNKS};
+use crate::inventory::armor::armor_properties;
+use crate::inventory::tool::tool_properties;
 use crate::types::TexturePack;
-use crate::shapes::write_unit_cube_to_ptr;
-use parking_lot::RwLock;
+use crate::block_light::BlockLight;
+use crate::shapes::{DIRT_PATH_HEIGHT, write_box_to_ptr, write_redstone_wire_to_ptr};
+use parking_lot::{RwLock, RwLockWriteGuard};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum BlockID {
@@ -22,6 +30,108 @@ pub enum BlockID {
     OakLeaves,
     OakPlanks,
     Glass,
+    Water,
+    Chest,
+    Furnace,
+    Netherrack,
+    Lava,
+    NetherPortal,
+    Sign,
+    Bread,
+    Apple,
+    BedHead,
+    BedFoot,
+    LeatherHelmet,
+    LeatherChestplate,
+    LeatherLeggings,
+    LeatherBoots,
+    GoldenHelmet,
+    GoldenChestplate,
+    GoldenLeggings,
+    GoldenBoots,
+    IronHelmet,
+    IronChestplate,
+    IronLeggings,
+    IronBoots,
+    DiamondHelmet,
+    DiamondChestplate,
+    DiamondLeggings,
+    DiamondBoots,
+    Tnt,
+    Snowball,
+    Arrow,
+    WoodenPickaxe,
+    StonePickaxe,
+    IronPickaxe,
+    DiamondPickaxe,
+    WoodenAxe,
+    StoneAxe,
+    IronAxe,
+    DiamondAxe,
+    WoodenShovel,
+    StoneShovel,
+    IronShovel,
+    DiamondShovel,
+    Glowstone,
+    SeaLantern,
+    Ladder,
+    RedstoneWire,
+    RedstoneBlock,
+    Piston,
+    StickyPiston,
+    PistonHead,
+    PistonMoving,
+    StoneBrick,
+    Spawner,
+    StonePressurePlate,
+    WoodenPressurePlate,
+    CraftingTable,
+    Fire,
+    Crossbow,
+    BirchLog,
+    BirchLeaves,
+    SpruceLog,
+    SpruceLeaves,
+    JungleLog,
+    JungleLeaves,
+    Beacon,
+    IronBlock,
+    GoldBlock,
+    DiamondBlock,
+    EmeraldBlock,
+    RawBeef,
+    RottenFlesh,
+    IronIngot,
+    GoldIngot,
+    NetherBrick,
+    BlazeRod,
+    NetherWart,
+    Granite,
+    PolishedGranite,
+    Diorite,
+    PolishedDiorite,
+    Andesite,
+    PolishedAndesite,
+    OakFenceGate,
+    EnchantingTable,
+    Bookshelf,
+    Stick,
+    WoodenSword,
+    StoneSword,
+    Compass,
+    SlimeBlock,
+    FlintAndSteel,
+    Gravel,
+    Map,
+    Observer,
+    IronOre,
+    DiamondOre,
+    Diamond,
+    DirtPath,
+    FireworkRocket,
+    Campfire,
+    CampfireExtinguished,
+    CookedBeef,
     Urss,
     Hitler,
     Debug,
@@ -38,7 +148,31 @@ impl BlockID {
         match self {
             &BlockID::Air |
             &BlockID::OakLeaves |
-            &BlockID::Glass => true,
+            &BlockID::BirchLeaves |
+            &BlockID::SpruceLeaves |
+            &BlockID::JungleLeaves |
+            &BlockID::Glass |
+            &BlockID::SlimeBlock |
+            &BlockID::Water |
+            &BlockID::Chest |
+            &BlockID::Sign |
+            &BlockID::Ladder |
+            &BlockID::RedstoneWire |
+            &BlockID::Piston |
+            &BlockID::StickyPiston |
+            &BlockID::PistonHead |
+            &BlockID::PistonMoving |
+            &BlockID::Spawner |
+            &BlockID::StonePressurePlate |
+            &BlockID::WoodenPressurePlate |
+            &BlockID::OakFenceGate |
+            &BlockID::BedHead |
+            &BlockID::BedFoot |
+            &BlockID::Fire |
+            &BlockID::DirtPath |
+            &BlockID::Campfire |
+            &BlockID::CampfireExtinguished |
+            &BlockID::NetherPortal => true,
             _ => false
         }
     }
@@ -50,20 +184,370 @@ impl BlockID {
     pub fn is_transparent_not_air(&self) -> bool {
         match self {
             &BlockID::OakLeaves |
-            &BlockID::Glass => true,
+            &BlockID::BirchLeaves |
+            &BlockID::SpruceLeaves |
+            &BlockID::JungleLeaves |
+            &BlockID::Glass |
+            &BlockID::SlimeBlock |
+            &BlockID::Water |
+            &BlockID::Chest |
+            &BlockID::Sign |
+            &BlockID::Ladder |
+            &BlockID::RedstoneWire |
+            &BlockID::Piston |
+            &BlockID::StickyPiston |
+            &BlockID::PistonHead |
+            &BlockID::PistonMoving |
+            &BlockID::Spawner |
+            &BlockID::StonePressurePlate |
+            &BlockID::WoodenPressurePlate |
+            &BlockID::OakFenceGate |
+            &BlockID::BedHead |
+            &BlockID::BedFoot |
+            &BlockID::Fire |
+            &BlockID::DirtPath |
+            &BlockID::Campfire |
+            &BlockID::CampfireExtinguished |
+            &BlockID::NetherPortal => true,
             _ => false
         }
     }
+    /// Blocks darkening the corners of the faces around them (ambient occlusion).
+    /// These are the blocks hiding the faces next to them, so transparent blocks like leaves and glass cast no shadow
     #[inline]
-    pub fn is_transparent_no_leaves(&self) -> bool {
+    pub fn occludes_ambient_light(&self) -> bool {
+        self.is_opaque()
+    }
+    #[inline]
+    pub fn is_liquid(&self) -> bool {
         match self {
-            &BlockID::Air |
-            &BlockID::Glass => true,
+            &BlockID::Water |
+            &BlockID::Lava => true,
+            _ => false
+        }
+    }
+    /// Liquids drawn in the water pass, with reflections
+    #[inline]
+    pub fn is_reflective(&self) -> bool {
+        self == &BlockID::Water
+    }
+    /// Blocks the player and the particles collide with, fence gates only while they are closed
+    /// (see `ChunkManager::is_solid_block_at`)
+    #[inline]
+    pub fn is_solid(&self) -> bool {
+        !self.is_air() && !self.is_liquid() && self != &BlockID::NetherPortal && self != &BlockID::Sign
+            && self != &BlockID::Ladder && self != &BlockID::RedstoneWire && self != &BlockID::PistonMoving
+            && !self.is_pressure_plate() && self != &BlockID::Fire
+    }
+    /// Blocks the player can climb by walking into them
+    #[inline]
+    pub fn is_climbable(&self) -> bool {
+        self == &BlockID::Ladder
+    }
+    /// Blocks the player can aim at to break them or interact with them.
+    /// Like the liquids, portals are walked into and aimed through
+    #[inline]
+    pub fn is_targetable(&self) -> bool {
+        !self.is_air() && !self.is_liquid() && self != &BlockID::PistonMoving && self != &BlockID::NetherPortal
+    }
+    /// Food points and saturation restored by eating this item, if it is edible
+    #[inline]
+    pub fn food_value(&self) -> Option<(f32, f32)> {
+        match self {
+            &BlockID::Bread => Some((5.0, 6.0)),
+            &BlockID::Apple => Some((4.0, 2.4)),
+            &BlockID::RawBeef => Some((3.0, 1.8)),
+            &BlockID::CookedBeef => Some((8.0, 12.8)),
+            &BlockID::RottenFlesh => Some((4.0, 0.8)),
+            _ => None
+        }
+    }
+    /// Items that can be put in the world as a block
+    #[inline]
+    pub fn is_placeable(&self) -> bool {
+        self.food_value().is_none() && armor_properties(*self).is_none() && tool_properties(*self).is_none()
+            && !self.is_throwable() && self != &BlockID::Crossbow && self != &BlockID::FlintAndSteel
+            && self != &BlockID::FireworkRocket && !self.is_material()
+    }
+    /// Items only used to craft other items
+    #[inline]
+    pub fn is_material(&self) -> bool {
+        match self {
+            &BlockID::IronIngot |
+            &BlockID::GoldIngot |
+            &BlockID::Diamond |
+            &BlockID::Stick |
+            &BlockID::BlazeRod |
+            &BlockID::NetherWart => true,
+            _ => false
+        }
+    }
+    /// Items thrown as a projectile with a right click
+    #[inline]
+    pub fn is_throwable(&self) -> bool {
+        self == &BlockID::Snowball || self == &BlockID::Arrow
+    }
+    /// The trunks of every kind of tree
+    #[inline]
+    pub fn is_log(&self) -> bool {
+        match self {
+            &BlockID::OakLog |
+            &BlockID::BirchLog |
+            &BlockID::SpruceLog |
+            &BlockID::JungleLog => true,
+            _ => false
+        }
+    }
+    /// The leaves of every kind of tree
+    #[inline]
+    pub fn is_leaves(&self) -> bool {
+        match self {
+            &BlockID::OakLeaves |
+            &BlockID::BirchLeaves |
+            &BlockID::SpruceLeaves |
+            &BlockID::JungleLeaves => true,
+            _ => false
+        }
+    }
+    /// Blocks a beacon pyramid can be built with
+    #[inline]
+    pub fn is_beacon_base(&self) -> bool {
+        match self {
+            &BlockID::IronBlock |
+            &BlockID::GoldBlock |
+            &BlockID::DiamondBlock |
+            &BlockID::EmeraldBlock => true,
+            _ => false
+        }
+    }
+    /// Blocks shattered by projectiles
+    #[inline]
+    pub fn is_fragile(&self) -> bool {
+        self == &BlockID::Glass
+    }
+    /// Blocks mined for what they contain, Fortune drops more of it
+    #[inline]
+    pub fn is_ore(&self) -> bool {
+        match self {
+            BlockID::IronOre | BlockID::DiamondOre => true,
+            _ => false,
+        }
+    }
+    /// Blocks that only drop themselves when mined with Silk Touch
+    #[inline]
+    pub fn is_silk_touchable(&self) -> bool {
+        match self {
+            BlockID::Stone | BlockID::Glass | BlockID::DiamondOre => true,
+            _ => false,
+        }
+    }
+    /// Block light emitted by the block, from 0 to 15
+    #[inline]
+    pub fn light_level(&self) -> u8 {
+        match self {
+            &BlockID::Glowstone |
+            &BlockID::SeaLantern |
+            &BlockID::Beacon |
+            &BlockID::Fire |
+            &BlockID::Campfire |
+            &BlockID::Lava => 15,
+            _ => 0
+        }
+    }
+    /// Blocks emitting a cool light (blue channel) instead of a warm one (red channel)
+    #[inline]
+    pub fn emits_cool_light(&self) -> bool {
+        self == &BlockID::SeaLantern
+    }
+    /// Blocks powering the redstone wires next to them
+    #[inline]
+    pub fn is_power_source(&self) -> bool {
+        self == &BlockID::RedstoneBlock
+    }
+    /// Blocks pushing the blocks in front of them when they get powered
+    #[inline]
+    pub fn is_piston(&self) -> bool {
+        self == &BlockID::Piston || self == &BlockID::StickyPiston
+    }
+    /// Blocks that pistons can't push, extended pistons can't be pushed either
+    #[inline]
+    pub fn is_immovable(&self) -> bool {
+        match self {
+            &BlockID::Bedrock |
+            &BlockID::Obsidian |
+            &BlockID::NetherPortal |
+            &BlockID::BedHead |
+            &BlockID::BedFoot |
+            &BlockID::PistonHead |
+            &BlockID::PistonMoving => true,
+            _ => false
+        }
+    }
+    /// Both halves of a bed
+    #[inline]
+    pub fn is_bed(&self) -> bool {
+        self == &BlockID::BedHead || self == &BlockID::BedFoot
+    }
+    /// Plates powering the blocks around them while something stands on them
+    #[inline]
+    pub fn is_pressure_plate(&self) -> bool {
+        self == &BlockID::StonePressurePlate || self == &BlockID::WoodenPressurePlate
+    }
+    /// A campfire, lit or not. Both keep the same block entity
+    #[inline]
+    pub fn is_campfire(&self) -> bool {
+        self == &BlockID::Campfire || self == &BlockID::CampfireExtinguished
+    }
+    /// Blocks that aren't part of the chunk mesh because they are drawn with their own model
+    #[inline]
+    pub fn has_custom_model(&self) -> bool {
+        match self {
+            &BlockID::Chest |
+            &BlockID::Sign |
+            &BlockID::BedHead |
+            &BlockID::BedFoot |
+            &BlockID::Piston |
+            &BlockID::StickyPiston |
+            &BlockID::PistonHead |
+            &BlockID::PistonMoving |
+            &BlockID::StonePressurePlate |
+            &BlockID::WoodenPressurePlate |
+            &BlockID::OakFenceGate => true,
             _ => false
         }
     }
 }
 
+/// Extra data stored next to a block ID for blocks that need it (e.g. orientation)
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct BlockMeta(pub u8);
+
+impl BlockMeta {
+    /// Horizontal direction stored in the 2 lowest bits:
+    /// 0 = +Z (south), 1 = -X (west), 2 = -Z (north), 3 = +X (east)
+    #[inline]
+    pub fn facing(&self) -> u8 {
+        self.0 & 0b11
+    }
+
+    #[inline]
+    pub fn with_facing(self, facing: u8) -> Self {
+        BlockMeta((self.0 & !0b11) | (facing & 0b11))
+    }
+
+    /// Third bit, set on the head of a bed and cleared on its foot
+    #[inline]
+    pub fn is_bed_head(&self) -> bool {
+        self.0 & 0b100 != 0
+    }
+
+    #[inline]
+    pub fn with_bed_head(self, is_bed_head: bool) -> Self {
+        BlockMeta((self.0 & !0b100) | ((is_bed_head as u8) << 2))
+    }
+
+    /// Third bit, set on a piston whose arm is extended. The bit is shared with the head of a bed
+    #[inline]
+    pub fn is_extended(&self) -> bool {
+        self.0 & 0b100 != 0
+    }
+
+    #[inline]
+    pub fn with_extended(self, is_extended: bool) -> Self {
+        BlockMeta((self.0 & !0b100) | ((is_extended as u8) << 2))
+    }
+
+    /// Third bit, set on a fence gate while it is open. The bit is shared with the head of a bed
+    #[inline]
+    pub fn is_open(&self) -> bool {
+        self.0 & 0b100 != 0
+    }
+
+    #[inline]
+    pub fn with_open(self, is_open: bool) -> Self {
+        BlockMeta((self.0 & !0b100) | ((is_open as u8) << 2))
+    }
+
+    /// Third bit, set on an observer during its pulse. The bit is shared with the head of a bed
+    #[inline]
+    pub fn is_powered(&self) -> bool {
+        self.0 & 0b100 != 0
+    }
+
+    #[inline]
+    pub fn with_powered(self, is_powered: bool) -> Self {
+        BlockMeta((self.0 & !0b100) | ((is_powered as u8) << 2))
+    }
+
+    /// Lowest bit, set on a pressure plate while something stands on it
+    #[inline]
+    pub fn is_pressed(&self) -> bool {
+        self.0 & 0b1 != 0
+    }
+
+    #[inline]
+    pub fn with_pressed(self, is_pressed: bool) -> Self {
+        BlockMeta((self.0 & !0b1) | is_pressed as u8)
+    }
+
+    /// How far water is from its source, from 0 (the source) to 7, in the 3 lowest bits
+    #[inline]
+    pub fn flow_level(&self) -> u8 {
+        self.0 & 0b111
+    }
+
+    #[inline]
+    pub fn with_flow_level(self, level: u8) -> Self {
+        BlockMeta((self.0 & !0b111) | (level & 0b111))
+    }
+
+    /// Power of a redstone wire, from 0 to 15, in the 4 lowest bits
+    #[inline]
+    pub fn power(&self) -> u8 {
+        self.0 & 0b1111
+    }
+
+    #[inline]
+    pub fn with_power(self, power: u8) -> Self {
+        BlockMeta((self.0 & !0b1111) | (power & 0b1111))
+    }
+
+    /// Directions a redstone wire connects to, in the 4 highest bits.
+    /// Bit i is set when the wire connects to its neighbour in the direction of facing i
+    #[inline]
+    pub fn wire_connections(&self) -> u8 {
+        self.0 >> 4
+    }
+
+    #[inline]
+    pub fn with_wire_connections(self, connections: u8) -> Self {
+        BlockMeta((self.0 & 0b1111) | (connections << 4))
+    }
+
+    /// Offset (x, z) to the neighbour in the direction of `facing`
+    #[inline]
+    pub fn facing_offset(&self) -> (i32, i32) {
+        match self.facing() {
+            0 => (0, 1),
+            1 => (-1, 0),
+            2 => (0, -1),
+            _ => (1, 0),
+        }
+    }
+
+    /// Index, in the `[right, left, top, bottom, front, back]` order of the faces, of the face
+    /// pointing in the direction of `facing`
+    #[inline]
+    pub fn facing_face(&self) -> usize {
+        match self.facing() {
+            0 => 4,
+            1 => 1,
+            2 => 5,
+            _ => 0,
+        }
+    }
+}
+
 impl Distribution<BlockID> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BlockID {
         match rng.gen_range(1, 4) {
@@ -100,120 +584,70 @@ fn create_vao_vbo() -> (u32, u32) {
     gl_call!(gl::VertexArrayAttribFormat(vao, 3, 1 as i32, gl::FLOAT, gl::FALSE, 9 * std::mem::size_of::<f32>() as u32));
     gl_call!(gl::VertexArrayAttribBinding(vao, 3, 0));
 
+    // Block light (warm, cool)
+    gl_call!(gl::EnableVertexArrayAttrib(vao, 4));
+    gl_call!(gl::VertexArrayAttribFormat(vao, 4, 2 as i32, gl::FLOAT, gl::FALSE, 10 * std::mem::size_of::<f32>() as u32));
+    gl_call!(gl::VertexArrayAttribBinding(vao, 4, 0));
+
     let mut vbo = 0;
     gl_call!(gl::CreateBuffers(1, &mut vbo));
     // We intentionally don't initialize the buffer's data store because it's dynamically created
     // when the chunk is invalidated
 
-    gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (10 * std::mem::size_of::<f32>()) as i32));
+    gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (12 * std::mem::size_of::<f32>()) as i32));
     (vao, vbo)
 }
 
 pub struct ChunkColumn {
-    pub heighest_blocks: RwLock<Box<[u8; 16 * 16]>>,
+    pub heighest_blocks: RwLock<Box<[u16; 16 * 16]>>,
     pub has_foliage: RwLock<bool>,
-    pub chunks: Box<[Chunk; 16]>,
+    /// Temperature and humidity at the corners (-x -z, +x -z, -x +z, +x +z) of the column,
+    /// blended across the column to tint the grass
+    pub climate: RwLock<[(f32, f32); 4]>,
+    /// WORLD_HEIGHT_IN_CHUNKS chunks, from the bottom of the world to the top
+    pub chunks: Vec<Chunk>,
 }
 
 impl ChunkColumn {
-    pub fn new() -> Self {
+    fn from_chunks<F: Fn(i32) -> Chunk>(chunk: F) -> Self {
         Self {
             heighest_blocks: RwLock::new(Box::new([0; 16 * 16])),
             has_foliage: RwLock::new(false),
-            chunks: Box::new([
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-                Chunk::empty(),
-            ])
+            climate: RwLock::new([(0.5, 0.5); 4]),
+            chunks: (0..WORLD_HEIGHT_IN_CHUNKS).map(chunk).collect(),
         }
     }
 
+    pub fn new() -> Self {
+        Self::from_chunks(|_| Chunk::empty())
+    }
+
     pub fn random() -> Self {
-        Self {
-            heighest_blocks: RwLock::new(Box::new([0; 16 * 16])),
-            has_foliage: RwLock::new(false),
-            chunks: Box::new([
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-                Chunk::random(),
-            ]),
-        }
+        Self::from_chunks(|_| Chunk::random())
     }
 
     pub fn full_of_block(block: BlockID) -> Self {
-        Self {
-            heighest_blocks: RwLock::new(Box::new([0; 16 * 16])),
-            has_foliage: RwLock::new(false),
-            chunks: Box::new([
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-                Chunk::full_of_block(block),
-            ]),
-        }
+        Self::from_chunks(|_| Chunk::full_of_block(block))
+    }
+
+    /// Temperature and humidity of the column at (x, z), blended between its corners like the grass color
+    pub fn climate_at(&self, x: usize, z: usize) -> (f32, f32) {
+        let climate = self.climate.read();
+        let (tx, tz) = (x as f32 / 16.0, z as f32 / 16.0);
+        let blend = |get: fn(&(f32, f32)) -> f32| {
+            let top = get(&climate[0]) + (get(&climate[1]) - get(&climate[0])) * tx;
+            let bottom = get(&climate[2]) + (get(&climate[3]) - get(&climate[2])) * tx;
+            top + (bottom - top) * tz
+        };
+        (blend(|c| c.0), blend(|c| c.1))
     }
 
     pub fn alternating() -> Self {
-        Self {
-            heighest_blocks: RwLock::new(Box::new([0; 16 * 16])),
-            has_foliage: RwLock::new(false),
-            chunks: Box::new([
-                Chunk::full_of_block(BlockID::Dirt),
-                Chunk::full_of_block(BlockID::Cobblestone),
-                Chunk::full_of_block(BlockID::Dirt),
-                Chunk::full_of_block(BlockID::Cobblestone),
-                Chunk::full_of_block(BlockID::Dirt),
-                Chunk::full_of_block(BlockID::Cobblestone),
-                Chunk::full_of_block(BlockID::Dirt),
-                Chunk::full_of_block(BlockID::Cobblestone),
-                Chunk::full_of_block(BlockID::Dirt),
-                Chunk::full_of_block(BlockID::Cobblestone),
-                Chunk::full_of_block(BlockID::Dirt),
-                Chunk::full_of_block(BlockID::Cobblestone),
-                Chunk::full_of_block(BlockID::Dirt),
-                Chunk::full_of_block(BlockID::Cobblestone),
-                Chunk::full_of_block(BlockID::Dirt),
-                Chunk::full_of_block(BlockID::Cobblestone),
-            ]),
-        }
+        Self::from_chunks(|y| if y % 2 == 0 {
+            Chunk::full_of_block(BlockID::Dirt)
+        } else {
+            Chunk::full_of_block(BlockID::Cobblestone)
+        })
     }
 
     #[inline]
@@ -225,26 +659,71 @@ impl ChunkColumn {
     pub fn set_block(&self, block: BlockID, x: u32, y: u32, z: u32) {
         self.chunks[(y / 16) as usize].set_block(block, x, y % 16, z);
         let mut heighest_blocks = self.heighest_blocks.write();
-        let y = y as u8;
+        let y = y as u16;
         let i = (16 * z + x) as usize;
         if y > heighest_blocks[i] {
             heighest_blocks[i] = y;
         }
     }
+
+    /// Keeps the highest block of the (x, z) column up to date once `block` replaced the block at height `y`.
+    /// When the highest block is removed, looks down for the next one
+    pub fn update_heighest_block(&self, block: BlockID, x: u32, y: u32, z: u32) {
+        let i = (16 * z + x) as usize;
+        let mut heighest_blocks = self.heighest_blocks.write();
+        if !block.is_air() {
+            if y as u16 > heighest_blocks[i] {
+                heighest_blocks[i] = y as u16;
+            }
+        } else if y as u16 == heighest_blocks[i] {
+            heighest_blocks[i] = (0..y).rev()
+                .find(|&y| !self.chunks[(y / 16) as usize].get_block(x, y % 16, z).is_air())
+                .unwrap_or(0) as u16;
+        }
+    }
 }
 
+/// Versions given to the meshes of the chunks as they are uploaded, never reused
+static NEXT_MESH_VERSION: AtomicU64 = AtomicU64::new(1);
+
 pub struct Chunk {
     pub is_generated: RwLock<bool>,
     pub is_uploaded_to_gpu: RwLock<bool>,
-    pub blocks: RwLock<[BlockID; CHUNK_VOLUME as usize]>,
+    pub blocks: RwLock<ChunkStorage>,
+    // Sparse because only a few blocks need metadata
+    pub block_metas: RwLock<HashMap<usize, BlockMeta>>,
     pub number_of_opaque_blocks: RwLock<u32>,
     pub number_of_transparent_blocks: RwLock<u32>,
     pub active_faces: RwLock<BitVec>,
     pub ao_vertices: RwLock<[[[u8; 4]; 6]; CHUNK_VOLUME as usize]>,
+    // Sparse because only the blocks around light sources are lit
+    pub block_lights: RwLock<HashMap<usize, BlockLight>>,
+    // Light reaching each face of the blocks next to lit blocks, filled with the active faces
+    pub face_lights: RwLock<HashMap<usize, [BlockLight; 6]>>,
 
     pub vao: RwLock<u32>,
     pub vbo: RwLock<u32>,
     pub vertices_drawn: RwLock<u32>,
+    /// Different after every upload of the mesh, so that the renderer knows when to copy it again
+    pub mesh_version: RwLock<u64>,
+    /// The blocks updated since the last upload, None when the whole mesh has to be rebuilt
+    pub dirty_blocks: RwLock<Option<Vec<(u32, u32, u32)>>>,
+    /// Where the quads of each block are in the opaque and the water meshes
+    mesh_layout: RwLock<MeshLayout>,
+    water_mesh_layout: RwLock<MeshLayout>,
+
+    // Liquids are drawn in a separate pass after the opaque geometry
+    pub water_vao: RwLock<u32>,
+    pub water_vbo: RwLock<u32>,
+    pub water_vertices_drawn: RwLock<u32>,
+
+    // Two queries used on alternate frames, so that reading last frame's result never waits for the GPU
+    pub occlusion_query: RwLock<[u32; 2]>,
+    pub query_frame: RwLock<u8>,
+    pub is_occluded: RwLock<bool>,
+
+    // Fades from 0 to 1 once uploaded, and back to 0 beyond the render distance before being unloaded
+    pub opacity: RwLock<f32>,
 }
 
 impl Default for Chunk {
@@ -261,10 +740,17 @@ impl Chunk {
     pub fn reset(&self) {
         // self.unload_from_gpu();
         *self.is_generated.write() = false;
-        *self.blocks.write() = [BlockID::Air; CHUNK_VOLUME as usize];
+        *self.blocks.write() = ChunkStorage::Compressed(CompressedChunk::uniform(BlockID::Air));
+        self.block_metas.write().clear();
+        self.block_lights.write().clear();
+        self.face_lights.write().clear();
         *self.number_of_opaque_blocks.write() = 0;
         *self.number_of_transparent_blocks.write() = 0;
         *self.vertices_drawn.write() = 0;
+        *self.water_vertices_drawn.write() = 0;
+        *self.dirty_blocks.write() = None;
+        *self.is_occluded.write() = false;
+        *self.opacity.write() = 0.0;
     }
 
     /// Creates a chunk where every block is the same
@@ -281,15 +767,32 @@ impl Chunk {
         Self {
             is_generated: RwLock::new(false),
             is_uploaded_to_gpu: RwLock::new(false),
-            blocks: RwLock::new([block; CHUNK_VOLUME as usize]),
+            blocks: RwLock::new(ChunkStorage::Compressed(CompressedChunk::uniform(block))),
+            block_metas: RwLock::new(HashMap::new()),
             number_of_opaque_blocks: RwLock::new(opaque),
             number_of_transparent_blocks: RwLock::new(transparent),
             active_faces: RwLock::new(BitVec::from_elem(6 * CHUNK_VOLUME as usize, false)),
             ao_vertices: RwLock::new([[[0; 4]; 6]; CHUNK_VOLUME as usize]),
+            block_lights: RwLock::new(HashMap::new()),
+            face_lights: RwLock::new(HashMap::new()),
 
             vao: RwLock::new(0),
             vbo: RwLock::new(0),
             vertices_drawn: RwLock::new(0),
+            mesh_version: RwLock::new(0),
+            dirty_blocks: RwLock::new(None),
+            mesh_layout: RwLock::new(MeshLayout::default()),
+            water_mesh_layout: RwLock::new(MeshLayout::default()),
+
+            water_vao: RwLock::new(0),
+            water_vbo: RwLock::new(0),
+            water_vertices_drawn: RwLock::new(0),
+
+            occlusion_query: RwLock::new([0; 2]),
+            query_frame: RwLock::new(0),
+            is_occluded: RwLock::new(false),
+
+            opacity: RwLock::new(0.0),
         }
     }
 
@@ -303,21 +806,38 @@ impl Chunk {
         Self {
             is_generated: RwLock::new(false),
             is_uploaded_to_gpu: RwLock::new(false),
-            blocks: RwLock::new({
-                let mut blocks = [BlockID::Air; CHUNK_VOLUME as usize];
+            blocks: RwLock::new(ChunkStorage::Expanded({
+                let mut blocks = Box::new([BlockID::Air; CHUNK_VOLUME as usize]);
                 for i in 0..blocks.len() {
                     blocks[i] = random::<BlockID>();
                 }
                 blocks
-            }),
+            })),
+            block_metas: RwLock::new(HashMap::new()),
             number_of_opaque_blocks: RwLock::new(16 * 16 * 16),
             number_of_transparent_blocks: RwLock::new(0),
             active_faces: RwLock::new(BitVec::from_elem(6 * CHUNK_VOLUME as usize, false)),
             ao_vertices: RwLock::new([[[0; 4]; 6]; CHUNK_VOLUME as usize]),
+            block_lights: RwLock::new(HashMap::new()),
+            face_lights: RwLock::new(HashMap::new()),
 
             vao: RwLock::new(0),
             vbo: RwLock::new(0),
             vertices_drawn: RwLock::new(0),
+            mesh_version: RwLock::new(0),
+            dirty_blocks: RwLock::new(None),
+            mesh_layout: RwLock::new(MeshLayout::default()),
+            water_mesh_layout: RwLock::new(MeshLayout::default()),
+
+            water_vao: RwLock::new(0),
+            water_vbo: RwLock::new(0),
+            water_vertices_drawn: RwLock::new(0),
+
+            occlusion_query: RwLock::new([0; 2]),
+            query_frame: RwLock::new(0),
+            is_occluded: RwLock::new(false),
+
+            opacity: RwLock::new(0.0),
         }
     }
 
@@ -336,7 +856,33 @@ impl Chunk {
 
     #[inline]
     pub fn get_block(&self, x: u32, y: u32, z: u32) -> BlockID {
-        self.blocks.read()[Chunk::chunk_coords_to_array_index(x, y, z)]
+        let index = Chunk::chunk_coords_to_array_index(x, y, z);
+        // Compressed chunks answer from their runs without being expanded
+        self.blocks.read().get(index)
+    }
+
+    /// Replaces the blocks by their run-length encoded version.
+    /// Meant for freshly generated chunks, since most of them are either air or solid rock
+    pub fn store_compressed(&self) {
+        let compressed = self.compress();
+        *self.blocks.write() = ChunkStorage::Compressed(compressed);
+    }
+
+    /// Expands the run-length encoded blocks back into an array, if they aren't already.
+    /// The blocks stay locked so that they can't be compressed again before they are used
+    fn expand(&self) -> RwLockWriteGuard<'_, ChunkStorage> {
+        let mut blocks = self.blocks.write();
+        if let ChunkStorage::Compressed(compressed) = &*blocks {
+            *blocks = ChunkStorage::Expanded(compressed.expand());
+        }
+        blocks
+    }
+
+    pub fn compress(&self) -> CompressedChunk {
+        match &*self.blocks.read() {
+            ChunkStorage::Compressed(compressed) => compressed.clone(),
+            ChunkStorage::Expanded(blocks) => CompressedChunk::from_blocks(blocks.iter()),
+        }
     }
 
     /// Sets a block at some given coordinates
@@ -345,7 +891,8 @@ impl Chunk {
     pub fn set_block(&self, block: BlockID, x: u32, y: u32, z: u32) {
         let index = Chunk::chunk_coords_to_array_index(x, y, z);
 
-        let target = self.blocks.read()[index];
+        let mut blocks = self.expand();
+        let target = blocks.expanded()[index];
         if target.is_air() {
             if block.is_transparent_not_air() {
                 *self.number_of_transparent_blocks.write() += 1;
@@ -368,18 +915,95 @@ impl Chunk {
             }
         }
 
-        self.blocks.write()[index] = block;
+        blocks.expanded_mut()[index] = block;
+        // The metadata belonged to the replaced block
+        if target != block {
+            self.block_metas.write().remove(&index);
+        }
+    }
+
+    #[inline]
+    pub fn get_block_meta(&self, x: u32, y: u32, z: u32) -> BlockMeta {
+        self.block_metas.read()
+            .get(&Chunk::chunk_coords_to_array_index(x, y, z))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn set_block_meta(&self, meta: BlockMeta, x: u32, y: u32, z: u32) {
+        let index = Chunk::chunk_coords_to_array_index(x, y, z);
+        if meta == BlockMeta::default() {
+            self.block_metas.write().remove(&index);
+        } else {
+            self.block_metas.write().insert(index, meta);
+        }
+    }
+
+    #[inline]
+    pub fn get_block_light(&self, x: u32, y: u32, z: u32) -> BlockLight {
+        self.block_lights.read()
+            .get(&Chunk::chunk_coords_to_array_index(x, y, z))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    #[inline]
+    pub fn set_block_light(&self, light: BlockLight, x: u32, y: u32, z: u32) {
+        let index = Chunk::chunk_coords_to_array_index(x, y, z);
+        if light == (0, 0) {
+            self.block_lights.write().remove(&index);
+        } else {
+            self.block_lights.write().insert(index, light);
+        }
     }
 
     pub fn unload_from_gpu(&self) {
         *self.is_uploaded_to_gpu.write() = false;
-        let vbo = *self.vbo.read();
-        if vbo != 0 {
-            gl_call!(gl::NamedBufferData(vbo,
-                0,
-                null(),
-                gl::DYNAMIC_DRAW));
+        *self.dirty_blocks.write() = None;
+        for &vbo in [*self.vbo.read(), *self.water_vbo.read()].iter() {
+            if vbo != 0 {
+                gl_call!(gl::NamedBufferData(vbo,
+                    0,
+                    null(),
+                    gl::DYNAMIC_DRAW));
+            }
+        }
+        let mut occlusion_query = self.occlusion_query.write();
+        if occlusion_query[0] != 0 {
+            gl_call!(gl::DeleteQueries(2, occlusion_query.as_ptr()));
+            *occlusion_query = [0; 2];
         }
+        *self.is_occluded.write() = false;
+        *self.opacity.write() = 0.0;
+    }
+
+    /// Returns the query to use around this frame's bounding box draw, and swaps to the other one for the next frame
+    pub fn next_occlusion_query(&self) -> u32 {
+        let mut occlusion_query = self.occlusion_query.write();
+        if occlusion_query[0] == 0 {
+            gl_call!(gl::CreateQueries(gl::SAMPLES_PASSED, 2, occlusion_query.as_mut_ptr()));
+        }
+        let mut query_frame = self.query_frame.write();
+        let query = occlusion_query[*query_frame as usize];
+        *query_frame ^= 1;
+        query
+    }
+
+    /// Reads the result of the query issued during the previous frame.
+    /// If the GPU isn't done with it yet, the last known visibility is kept instead of stalling
+    pub fn update_occlusion(&self) -> bool {
+        let previous_query = self.occlusion_query.read()[(*self.query_frame.read() ^ 1) as usize];
+        if previous_query != 0 {
+            let mut available = 0;
+            gl_call!(gl::GetQueryObjectuiv(previous_query, gl::QUERY_RESULT_AVAILABLE, &mut available));
+            if available != 0 {
+                let mut samples_passed = 0;
+                gl_call!(gl::GetQueryObjectuiv(previous_query, gl::QUERY_RESULT, &mut samples_passed));
+                *self.is_occluded.write() = samples_passed == 0;
+            }
+        }
+        *self.is_occluded.read()
     }
 
     pub fn upload_to_gpu(&self, texture_pack: &TexturePack) {
@@ -387,55 +1011,389 @@ impl Chunk {
             let (vao, vbo) = create_vao_vbo();
             *self.vao.write() = vao;
             *self.vbo.write() = vbo;
+
+            let (water_vao, water_vbo) = create_vao_vbo();
+            *self.water_vao.write() = water_vao;
+            *self.water_vbo.write() = water_vbo;
+        }
+
+        self.lay_out_meshes();
+        *self.mesh_version.write() = NEXT_MESH_VERSION.fetch_add(1, Ordering::Relaxed);
+        *self.vertices_drawn.write() = self.write_mesh(*self.vbo.read(), texture_pack, false, &self.mesh_layout.read());
+        *self.water_vertices_drawn.write() = self.write_mesh(*self.water_vbo.read(), texture_pack, true, &self.water_mesh_layout.read());
+    }
+
+    /// Places the quads of every block one after the other in the opaque and the water meshes
+    pub(crate) fn lay_out_meshes(&self) {
+        // Compressed chunks are meshed from a temporary copy and stay compressed
+        let storage = self.blocks.read();
+        let blocks = storage.view();
+        let sides_vec = self.active_faces.read();
+        let layout = |is_water_mesh: bool| MeshLayout::new(blocks.iter().enumerate()
+            .filter(|&(_, &block)| is_meshed(block, is_water_mesh))
+            .map(|(j, &block)| (j, 6 * self.meshed_quads(block, j, &sides_vec) as u32)));
+        *self.mesh_layout.write() = layout(false);
+        *self.water_mesh_layout.write() = layout(true);
+    }
+
+    /// Rewrites in place the quads of the `dirty` blocks and of their neighbours in the chunk.
+    /// A block that gained quads moves to the free room at the end of its mesh, the whole meshes are
+    /// uploaded again once there is none left or when there are too many dirty blocks
+    pub fn upload_to_gpu_incremental(&self, dirty: &[(u32, u32, u32)], texture_pack: &TexturePack) {
+        if dirty.is_empty() || dirty.len() > MAX_INCREMENTAL_DIRTY_BLOCKS || *self.vbo.read() == 0 {
+            return self.upload_to_gpu(texture_pack);
+        }
+        let patches = match self.plan_incremental_upload(dirty) {
+            Some(patches) => patches,
+            None => return self.upload_to_gpu(texture_pack),
+        };
+
+        let vertex_size = 12 * std::mem::size_of::<f32>();
+        let write = |vbo: u32, first: u32, data: &[f32]| {
+            gl_call!(gl::NamedBufferSubData(vbo, (first as usize * vertex_size) as isize,
+                (data.len() * std::mem::size_of::<f32>()) as isize, data.as_ptr() as *const c_void));
+        };
+        {
+            let sides_vec = self.active_faces.read();
+            let ao_vec = self.ao_vertices.read();
+            let face_lights = self.face_lights.read();
+            for ((x, y, z), is_water_mesh, patch) in patches {
+                let (first, reserved, cleared) = match patch {
+                    MeshPatch::Write { first, reserved, cleared } => (first, reserved, cleared),
+                    _ => continue,
+                };
+                let vbo = if is_water_mesh { *self.water_vbo.read() } else { *self.vbo.read() };
+                // The vertices left at 0 are degenerate triangles, which draw nothing
+                if let Some((cleared_first, cleared_vertices)) = cleared {
+                    write(vbo, cleared_first, &vec![0.0; cleared_vertices as usize * 12]);
+                }
+                let mut data = vec![0.0f32; reserved as usize * 12];
+                let block = self.get_block(x, y, z);
+                let index = Chunk::chunk_coords_to_array_index(x, y, z);
+                if is_meshed(block, is_water_mesh) {
+                    self.write_block_to_ptr(data.as_mut_ptr(), block, (x, y, z), index, texture_pack,
+                                            &sides_vec, &ao_vec, &face_lights);
+                }
+                write(vbo, first, &data);
+            }
+        }
+        *self.vertices_drawn.write() = self.mesh_layout.read().end;
+        *self.water_vertices_drawn.write() = self.water_mesh_layout.read().end;
+        *self.mesh_version.write() = NEXT_MESH_VERSION.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Where the quads of the `dirty` blocks and of their neighbours in the chunk go in the meshes, as
+    /// (block, whether it is the water mesh, patch). None when the whole meshes must be written again,
+    /// the layouts of the meshes are then left half updated until they are
+    pub(crate) fn plan_incremental_upload(&self, dirty: &[(u32, u32, u32)]) -> Option<Vec<((u32, u32, u32), bool, MeshPatch)>> {
+        let mut blocks = Vec::new();
+        for &(x, y, z) in dirty {
+            for &(dx, dy, dz) in [(0, 0, 0), (1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)].iter() {
+                let (x, y, z) = (x as i32 + dx, y as i32 + dy, z as i32 + dz);
+                let size = CHUNK_SIZE as i32;
+                if x >= 0 && x < size && y >= 0 && y < size && z >= 0 && z < size && !blocks.contains(&(x as u32, y as u32, z as u32)) {
+                    blocks.push((x as u32, y as u32, z as u32));
+                }
+            }
+        }
+
+        let sides_vec = self.active_faces.read();
+        let mut layouts = [self.mesh_layout.write(), self.water_mesh_layout.write()];
+        let mut patches = Vec::new();
+        for (x, y, z) in blocks {
+            let block = self.get_block(x, y, z);
+            let index = Chunk::chunk_coords_to_array_index(x, y, z);
+            // A block turning into water or back moves from one mesh to the other
+            for (is_water_mesh, layout) in [false, true].iter().zip(layouts.iter_mut()) {
+                let vertices = if is_meshed(block, *is_water_mesh) {
+                    6 * self.meshed_quads(block, index, &sides_vec) as u32
+                } else {
+                    0
+                };
+                match layout.place(index, vertices) {
+                    MeshPatch::Full => return None,
+                    patch => patches.push(((x, y, z), *is_water_mesh, patch)),
+                }
+            }
+        }
+        Some(patches)
+    }
+
+    /// Faces of the block at `index` written into the mesh.
+    /// A ladder only has the face pointing away from the wall it is attached to
+    fn meshed_faces(&self, block: BlockID, index: usize, active_faces: &BitVec) -> [bool; 6] {
+        let mut sides = [false; 6];
+        if block == BlockID::Ladder {
+            let face = self.block_metas.read().get(&index).cloned().unwrap_or_default().facing_face();
+            sides[face] = active_faces[6 * index + face];
+        } else {
+            for (i, side) in sides.iter_mut().enumerate() {
+                *side = active_faces[6 * index + i];
+            }
         }
+        sides
+    }
 
-        let n_visible_faces = self.active_faces.read().iter().fold(0, |acc, b| acc + b as i32);
-        if n_visible_faces == 0 {
-            return;
+    /// Number of quads written into the mesh for the block at `index`.
+    /// A redstone wire is a square with an arm towards each of its connections
+    fn meshed_quads(&self, block: BlockID, index: usize, active_faces: &BitVec) -> usize {
+        if block == BlockID::RedstoneWire {
+            let meta = self.block_metas.read().get(&index).cloned().unwrap_or_default();
+            1 + meta.wire_connections().count_ones() as usize
+        } else {
+            self.meshed_faces(block, index, active_faces).iter().filter(|&&side| side).count()
         }
+    }
 
-        // Initialize the VBO
-        gl_call!(gl::NamedBufferData(*self.vbo.read(),
-                (6 * 10 * std::mem::size_of::<f32>() * n_visible_faces as usize) as isize,
+    /// Writes the visible faces of the blocks of the opaque or the water mesh into `vbo` where `layout` placed them,
+    /// and returns the number of vertices drawn
+    fn write_mesh(&self, vbo: u32, texture_pack: &TexturePack, is_water_mesh: bool, layout: &MeshLayout) -> u32 {
+        if layout.end == 0 {
+            return 0;
+        }
+
+        // Initialize the VBO, with the free room at its end
+        gl_call!(gl::NamedBufferData(vbo,
+                (12 * std::mem::size_of::<f32>() * layout.capacity as usize) as isize,
                 null(),
                 gl::DYNAMIC_DRAW));
 
         // Map VBO to virtual memory
-        let vbo_ptr: *mut f32 = gl_call!(gl::MapNamedBuffer(*self.vbo.read(), gl::WRITE_ONLY)) as *mut f32;
-        let mut vbo_offset = 0;
+        let vbo_ptr: *mut f32 = gl_call!(gl::MapNamedBuffer(vbo, gl::WRITE_ONLY)) as *mut f32;
 
-        let mut vertices_drawn = 0;
         let sides_vec = &self.active_faces.read();
         let ao_vec = &self.ao_vertices.read();
+        let face_lights = &self.face_lights.read();
+        let storage = self.blocks.read();
+        let blocks = storage.view();
         let mut j = 0;
 
         for (x, y, z) in BlockIterator::new() {
-            let block = self.get_block(x, y, z);
-            if block != BlockID::Air {
-                let active_sides = [
-                    sides_vec[6 * j],
-                    sides_vec[6 * j + 1],
-                    sides_vec[6 * j + 2],
-                    sides_vec[6 * j + 3],
-                    sides_vec[6 * j + 4],
-                    sides_vec[6 * j + 5],
-                ];
-
-                let ao_block = ao_vec[j];
-
-                let uvs = texture_pack.get(&block).unwrap().clone();
-                let uvs = uvs.get_uv_of_every_face();
-
-                let copied_vertices = unsafe { write_unit_cube_to_ptr(vbo_ptr.offset(vbo_offset), x as f32, y as f32, z as f32, uvs, active_sides, ao_block) };
-                // let cube_array = unit_cube_array(x as f32, y as f32, z as f32, uv_bl, uv_tr, active_sides);
-                // gl_call!(gl::NamedBufferSubData(self.vbo, (i * std::mem::size_of::<f32>()) as isize, (cube_array.len() * std::mem::size_of::<f32>()) as isize, cube_array.as_ptr() as *mut c_void));
-                vertices_drawn += copied_vertices;
-                vbo_offset += copied_vertices as isize * 10; // 5 floats per vertex
+            let block = blocks[j];
+            if let (true, Some(&(first, _))) = (is_meshed(block, is_water_mesh), layout.ranges.get(&j)) {
+                // 12 floats per vertex
+                self.write_block_to_ptr(unsafe { vbo_ptr.offset(first as isize * 12) }, block, (x, y, z), j,
+                                        texture_pack, sides_vec, ao_vec, face_lights);
             }
             j += 1;
         }
-        *self.vertices_drawn.write() = vertices_drawn;
-        gl_call!(gl::UnmapNamedBuffer(*self.vbo.read()));
+        gl_call!(gl::UnmapNamedBuffer(vbo));
+        layout.end
+    }
+
+    /// Writes the visible faces of the block at `index` to `ptr` and returns the number of vertices written
+    fn write_block_to_ptr(&self, ptr: *mut f32, block: BlockID, (x, y, z): (u32, u32, u32), index: usize, texture_pack: &TexturePack,
+                          sides_vec: &BitVec, ao_vec: &[[[u8; 4]; 6]; CHUNK_VOLUME as usize],
+                          face_lights: &HashMap<usize, [BlockLight; 6]>) -> u32 {
+        if block == BlockID::RedstoneWire {
+            let meta = self.get_block_meta(x, y, z);
+            let layer = texture_pack.get(&block).unwrap().get_uv_of_every_face().2;
+            let light = face_lights.get(&index).map_or((0, 0), |lights| lights[2]);
+            return unsafe { write_redstone_wire_to_ptr(ptr, x as f32, y as f32, z as f32, layer, meta.power(), meta.wire_connections(), light) };
+        }
+
+        let active_sides = self.meshed_faces(block, index, sides_vec);
+
+        // Ladders are drawn as the face of a cube pushed into the wall they hang on,
+        // so that only a sixteenth of it sticks out
+        let (x, z) = if block == BlockID::Ladder {
+            let (dx, dz) = self.get_block_meta(x, y, z).facing_offset();
+            (x as f32 - dx as f32 * 15.0 / 16.0, z as f32 - dz as f32 * 15.0 / 16.0)
+        } else {
+            (x as f32, z as f32)
+        };
+
+        // Dirt paths are a sixteenth lower than the blocks around them
+        let height = if block == BlockID::DirtPath { DIRT_PATH_HEIGHT } else { 1.0 };
+
+        let ao_block = ao_vec[index];
+        let light_block = face_lights.get(&index).cloned().unwrap_or_default();
+
+        let textures = texture_pack.get(&block).unwrap();
+        let uvs = textures.get_uv_of_every_face();
+        let emissive = textures.get_emissive_of_every_face();
+
+        unsafe { write_box_to_ptr(ptr, x, y as f32, z, height, uvs, emissive, active_sides, ao_block, light_block) }
+    }
+}
+
+/// Whether the quads of `block` go in the water mesh or in the opaque mesh of its chunk
+fn is_meshed(block: BlockID, is_water_mesh: bool) -> bool {
+    if is_water_mesh {
+        block.is_reflective()
+    } else {
+        !block.is_air() && !block.has_custom_model() && !block.is_reflective()
+    }
+}
+
+/// Where the quads of each block are in one of the meshes of a chunk.
+/// The buffer of the mesh has free room at its end for the blocks that outgrow their place after an edit
+#[derive(Default)]
+pub(crate) struct MeshLayout {
+    /// First vertex and vertices reserved for each meshed block. Its quads are followed by degenerate
+    /// triangles up to the end of its range
+    ranges: HashMap<usize, (u32, u32)>,
+    /// Vertices drawn, the free room starts after them
+    end: u32,
+    capacity: u32,
+}
+
+/// Where to rewrite the quads of a block in a mesh
+#[derive(Debug, PartialEq)]
+pub(crate) enum MeshPatch {
+    /// The block has no quads in the mesh and had none
+    Nothing,
+    /// Its quads go from `first`, followed by degenerate triangles up to `reserved` vertices.
+    /// `cleared` is the range it moved out of, filled with degenerate triangles
+    Write { first: u32, reserved: u32, cleared: Option<(u32, u32)> },
+    /// No room is left, the whole mesh must be written again
+    Full,
+}
+
+impl MeshLayout {
+    /// The blocks one after the other, given as (index, vertices) in `BlockIterator` order
+    fn new(blocks: impl Iterator<Item=(usize, u32)>) -> Self {
+        let mut layout = MeshLayout::default();
+        for (index, vertices) in blocks {
+            if vertices > 0 {
+                layout.ranges.insert(index, (layout.end, vertices));
+                layout.end += vertices;
+            }
+        }
+        // An empty mesh has no buffer to write into
+        layout.capacity = if layout.end == 0 { 0 } else { layout.end + MESH_SLACK_VERTICES };
+        layout
+    }
+
+    /// Finds room for the `vertices` of the block at `index`, in place if they fit in its range
+    fn place(&mut self, index: usize, vertices: u32) -> MeshPatch {
+        match self.ranges.get(&index).cloned() {
+            None if vertices == 0 => MeshPatch::Nothing,
+            Some((first, reserved)) if vertices <= reserved => MeshPatch::Write { first, reserved, cleared: None },
+            cleared => {
+                // Room for another quad, up to the 6 of a whole cube
+                let reserved = (vertices + 6).min(6 * 6).max(vertices);
+                if self.end + reserved > self.capacity {
+                    return MeshPatch::Full;
+                }
+                let first = self.end;
+                self.end += reserved;
+                self.ranges.insert(index, (first, reserved));
+                MeshPatch::Write { first, reserved, cleared }
+            }
+        }
+    }
+}
+
+/// The blocks of a chunk as (block, run length) pairs in `BlockIterator` order.
+/// Chunks that weren't modified after their generation are mostly made of large uniform regions
+/// (air, stone, bedrock...) which only take a few pairs
+#[derive(Clone)]
+pub struct CompressedChunk {
+    runs: Vec<(BlockID, u16)>,
+    /// Index of the first block of each run, to find the run of a block with a binary search
+    starts: Vec<u16>,
+}
+
+impl CompressedChunk {
+    pub fn uniform(block: BlockID) -> Self {
+        Self {
+            runs: vec![(block, CHUNK_VOLUME as u16)],
+            starts: vec![0],
+        }
+    }
+
+    pub fn from_blocks<'a>(blocks: impl Iterator<Item=&'a BlockID>) -> Self {
+        let mut runs: Vec<(BlockID, u16)> = Vec::new();
+        for &block in blocks {
+            match runs.last_mut() {
+                Some((run_block, length)) if *run_block == block => *length += 1,
+                _ => runs.push((block, 1)),
+            }
+        }
+        let starts = runs.iter()
+            .scan(0, |start, &(_, length)| {
+                let run_start = *start;
+                *start += length;
+                Some(run_start)
+            })
+            .collect();
+        Self { runs, starts }
+    }
+
+    /// The block filling the whole chunk, if there is only one
+    #[inline]
+    pub fn uniform_block(&self) -> Option<BlockID> {
+        match self.runs.as_slice() {
+            &[(block, _)] => Some(block),
+            _ => None,
+        }
+    }
+
+    /// The block at `index` in `BlockIterator` order, in the last run starting at or before it
+    pub fn get(&self, index: usize) -> BlockID {
+        assert!(index < CHUNK_VOLUME as usize, "Block index {} is outside of the chunk", index);
+        let run = self.starts.partition_point(|&start| start as usize <= index) - 1;
+        self.runs[run].0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uniform_block().map_or(false, |block| block.is_air())
+    }
+
+    pub fn is_fully_opaque(&self) -> bool {
+        self.uniform_block().map_or(false, |block| block.is_opaque())
+    }
+
+    pub fn expand(&self) -> Box<[BlockID; CHUNK_VOLUME as usize]> {
+        let mut blocks = Box::new([BlockID::Air; CHUNK_VOLUME as usize]);
+        let mut i = 0;
+        for &(block, length) in &self.runs {
+            blocks[i..i + length as usize].fill(block);
+            i += length as usize;
+        }
+        blocks
+    }
+}
+
+/// Chunks are expanded the first time one of their blocks is changed or they are meshed
+pub enum ChunkStorage {
+    Compressed(CompressedChunk),
+    Expanded(Box<[BlockID; CHUNK_VOLUME as usize]>),
+}
+
+impl ChunkStorage {
+    #[inline]
+    pub fn get(&self, index: usize) -> BlockID {
+        match self {
+            ChunkStorage::Compressed(compressed) => compressed.get(index),
+            ChunkStorage::Expanded(blocks) => blocks[index],
+        }
+    }
+
+    /// Every block in `BlockIterator` order. Compressed blocks are expanded into a temporary copy,
+    /// the storage itself is left compressed
+    pub fn view(&self) -> Cow<'_, [BlockID]> {
+        match self {
+            ChunkStorage::Expanded(blocks) => Cow::Borrowed(&blocks[..]),
+            ChunkStorage::Compressed(compressed) => Cow::Owned((compressed.expand() as Box<[BlockID]>).into_vec()),
+        }
+    }
+
+    #[inline]
+    pub fn expanded(&self) -> &[BlockID; CHUNK_VOLUME as usize] {
+        match self {
+            ChunkStorage::Expanded(blocks) => blocks,
+            ChunkStorage::Compressed(_) => panic!("The chunk must be expanded first"),
+        }
+    }
+
+    #[inline]
+    pub fn expanded_mut(&mut self) -> &mut [BlockID; CHUNK_VOLUME as usize] {
+        match self {
+            ChunkStorage::Expanded(blocks) => blocks,
+            ChunkStorage::Compressed(_) => panic!("The chunk must be expanded first"),
+        }
     }
 }
 
@@ -478,3 +1436,70 @@ impl Iterator for BlockIterator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Removes a block the way `ChunkManager::_set_block` does
+    fn remove_block(column: &ChunkColumn, x: u32, y: u32, z: u32) {
+        column.get_chunk((y / 16) as i32).set_block(BlockID::Air, x, y % 16, z);
+        column.update_heighest_block(BlockID::Air, x, y, z);
+    }
+
+    #[test]
+    fn digging_a_deep_shaft_lowers_the_highest_block() {
+        let column = ChunkColumn::new();
+        let (x, z) = (3, 5);
+        let surface = 70;
+        for y in 0..=surface {
+            column.set_block(BlockID::Stone, x, y, z);
+            column.set_block(BlockID::Stone, x + 1, y, z);
+        }
+        // A cave the shaft goes through
+        for y in 30..=40 {
+            remove_block(&column, x, y, z);
+        }
+        assert_eq!(column.heighest_blocks.read()[(16 * z + x) as usize], surface as u16);
+
+        for y in (10..=surface).rev() {
+            remove_block(&column, x, y, z);
+            let expected = if (30..=40).contains(&(y - 1)) { 29 } else { y - 1 };
+            assert_eq!(column.heighest_blocks.read()[(16 * z + x) as usize], expected as u16, "after digging at y = {}", y);
+        }
+        // The column next to the shaft is left alone
+        assert_eq!(column.heighest_blocks.read()[(16 * z + x + 1) as usize], surface as u16);
+    }
+
+    #[test]
+    fn compressed_chunks_are_read_without_being_expanded() {
+        let chunk = Chunk::empty();
+        for (x, y, z) in BlockIterator::new() {
+            if y < 5 || (x + z) % 7 == 0 {
+                chunk.set_block(BlockID::Stone, x, y, z);
+            }
+        }
+        chunk.store_compressed();
+
+        for (x, y, z) in BlockIterator::new() {
+            let expected = if y < 5 || (x + z) % 7 == 0 { BlockID::Stone } else { BlockID::Air };
+            assert_eq!(chunk.get_block(x, y, z), expected);
+        }
+        assert!(matches!(&*chunk.blocks.read(), ChunkStorage::Compressed(_)));
+    }
+
+    #[test]
+    fn compressed_chunks_stay_compressed_when_meshed() {
+        let chunk = Chunk::empty();
+        for (x, y, z) in BlockIterator::new() {
+            if y < 5 {
+                chunk.set_block(BlockID::Stone, x, y, z);
+            }
+        }
+        chunk.store_compressed();
+        chunk.lay_out_meshes();
+
+        assert!(matches!(&*chunk.blocks.read(), ChunkStorage::Compressed(_)));
+        assert_eq!(&*chunk.blocks.read().view(), &chunk.compress().expand()[..]);
+    }
+}
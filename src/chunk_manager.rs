@@ -1,11 +1,18 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use nalgebra::Matrix4;
-use nalgebra_glm::{Mat4, vec3};
+use nalgebra_glm::vec3;
 
 use crate::ambient_occlusion::compute_ao_of_block;
-use crate::chunk::{BlockID, Chunk, ChunkColumn};
+use crate::block_entity::BlockEntity;
+use crate::block_light::{BlockLight, emitted_light, update_block_light};
+use crate::chunk::{BlockID, BlockMeta, Chunk, ChunkColumn};
+use crate::constants::{MAX_INCREMENTAL_DIRTY_BLOCKS, WORLD_HEIGHT_IN_CHUNKS};
+use crate::dimension::Dimension;
+use crate::mob::MobEntity;
+use crate::pathfinding::NavCache;
 use crate::shader_compilation::ShaderProgram;
+use crate::structures::StructureRegistry;
 use std::sync::Arc;
 use parking_lot::RwLock;
 use owning_ref::OwningRef;
@@ -13,17 +20,46 @@ use owning_ref::OwningRef;
 pub const CHUNK_SIZE: u32 = 16;
 pub const CHUNK_VOLUME: u32 = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
 
+/// Whether the face of `this` next to `neighbour` is hidden. Opaque blocks hide the faces next to them, and there is
+/// no face inside a pane of glass, a pile of slime blocks or a body of liquid, but the faces between two different
+/// transparent blocks are drawn
+#[inline]
+pub fn should_cull_face(this: BlockID, neighbour: BlockID) -> bool {
+    neighbour.is_opaque() || (neighbour == this && (this.is_liquid() || this == BlockID::Glass || this == BlockID::SlimeBlock || this == BlockID::DirtPath))
+}
+
 #[derive(Default)]
 pub struct ChunkManager {
     pub loaded_chunk_columns: RwLock<HashMap<(i32, i32), Arc<ChunkColumn>>>,
-    pub(crate) block_changelist: RwLock<HashSet<(i32, BlockID, i32, i32, i32)>>,
+    /// Blocks whose faces must be updated: (x, y, z) -> (highest priority of their changes, latest block)
+    pub(crate) block_changelist: RwLock<HashMap<(i32, i32, i32), (i32, BlockID)>>,
+    /// Blocks changed since the last game tick, their neighbours are notified by `ProcessBlockUpdates`
+    pub(crate) changed_blocks: RwLock<Vec<(i32, i32, i32)>>,
+    /// Blocks whose metadata changed (e.g. the level of water, an open gate), only seen by the observers
+    pub(crate) changed_states: RwLock<Vec<(i32, i32, i32)>>,
+    /// Block entities created by the world generation, moved to the `BlockEntityRegistry` by `ChunkLoading`
+    pub(crate) generated_block_entities: RwLock<Vec<((i32, i32, i32), Box<dyn BlockEntity>)>>,
+    /// Bounds of the structures generated in the dimension, so that they don't overlap
+    pub(crate) structures: RwLock<StructureRegistry>,
+    /// Mobs living in the generated structures, added to the world by `SpawnMobs` once a player comes close
+    pub(crate) generated_mobs: RwLock<Vec<MobEntity>>,
+    /// Where the mobs of the dimension can stand, for their pathfinding
+    pub(crate) nav_cache: NavCache,
+    pub dimension: Dimension,
 }
 
 impl ChunkManager {
-    pub fn new() -> ChunkManager {
+    pub fn new(dimension: Dimension) -> ChunkManager {
         ChunkManager {
             loaded_chunk_columns: RwLock::new(HashMap::new()),
-            block_changelist: RwLock::new(HashSet::new()),
+            block_changelist: RwLock::new(HashMap::new()),
+            changed_blocks: RwLock::new(Vec::new()),
+            changed_states: RwLock::new(Vec::new()),
+            generated_block_entities: RwLock::new(Vec::new()),
+            structures: RwLock::new(StructureRegistry::new()),
+            generated_mobs: RwLock::new(Vec::new()),
+            nav_cache: NavCache::default(),
+            dimension,
         }
     }
 
@@ -34,7 +70,7 @@ impl ChunkManager {
 
     #[inline]
     pub fn get_chunk(&self, x: i32, y: i32, z: i32) -> Option<OwningRef<Arc<ChunkColumn>, Chunk>> {
-        if y < 0 || y >= 16 {
+        if y < 0 || y >= WORLD_HEIGHT_IN_CHUNKS {
             return None;
         }
         self.loaded_chunk_columns.read().get(&(x, z))
@@ -73,6 +109,21 @@ impl ChunkManager {
         self.add_chunk_column((0, 0), Arc::new(ChunkColumn::full_of_block(BlockID::Cobblestone)));
     }
 
+    /// Glass between leaves on a stone floor. The faces of the glass next to the leaves must be drawn,
+    /// and only the floor may darken their corners
+    pub fn leaves_next_to_glass(&mut self) {
+        self.add_chunk_column((0, 0), Arc::new(ChunkColumn::new()));
+        for x in 0..4 {
+            for z in 0..4 {
+                self.set_block(BlockID::Stone, x, 0, z);
+            }
+        }
+        self.set_block(BlockID::Glass, 1, 1, 1);
+        self.set_block(BlockID::OakLeaves, 2, 1, 1);
+        self.set_block(BlockID::OakLeaves, 1, 1, 2);
+        self.set_block(BlockID::OakLeaves, 1, 2, 1);
+    }
+
     // Transform global block coordinates into chunk local coordinates
     pub fn get_chunk_coords(x: i32, y: i32, z: i32) -> (i32, i32, i32, u32, u32, u32) {
         let chunk_x = if x < 0 { (x + 1) / 16 - 1 } else { x / 16 };
@@ -108,18 +159,36 @@ impl ChunkManager {
         let (chunk_x, chunk_y, chunk_z, block_x, block_y, block_z)
             = ChunkManager::get_chunk_coords(x, y, z);
 
-        match self.get_chunk(chunk_x, chunk_y, chunk_z) {
+        if chunk_y < 0 || chunk_y >= WORLD_HEIGHT_IN_CHUNKS {
+            return false;
+        }
+        match self.get_column(chunk_x, chunk_z) {
             None => false,
-            Some(chunk) => {
+            Some(column) => {
+                let chunk = column.get_chunk(chunk_y);
                 chunk.set_block(block, block_x, block_y, block_z);
+                column.update_heighest_block(block, block_x, y as u32, block_z);
                 if *chunk.is_uploaded_to_gpu.read() {
-                    self.block_changelist.write().insert((priority, block, x, y, z));
+                    self.add_to_changelist(priority, block, x, y, z);
+                }
+                // The blocks placed while generating the chunk don't notify their neighbours
+                if *chunk.is_generated.read() {
+                    self.changed_blocks.write().push((x, y, z));
                 }
+                update_block_light(self, priority, x, y, z);
                 true
             }
         }
     }
 
+    /// Queues the faces around the block for an update, a change made by the player
+    /// isn't slowed down by a change of the world generation at the same place
+    fn add_to_changelist(&self, priority: i32, block: BlockID, x: i32, y: i32, z: i32) {
+        let mut block_changelist = self.block_changelist.write();
+        let change = block_changelist.entry((x, y, z)).or_insert((priority, block));
+        *change = (change.0.max(priority), block);
+    }
+
     pub fn set_block(&self, block: BlockID, x: i32, y: i32, z: i32) -> bool {
         self._set_block(0, block, x, y, z)
     }
@@ -128,9 +197,83 @@ impl ChunkManager {
         self._set_block(1, block, x, y, z)
     }
 
+    pub fn get_block_meta(&self, x: i32, y: i32, z: i32) -> Option<BlockMeta> {
+        let (chunk_x, chunk_y, chunk_z, block_x, block_y, block_z)
+            = ChunkManager::get_chunk_coords(x, y, z);
+
+        self.get_chunk(chunk_x, chunk_y, chunk_z)
+            .map(|chunk|
+                chunk.get_block_meta(block_x, block_y, block_z))
+    }
+
+    /// Must be called after the block itself was placed since replacing a block clears its metadata
+    pub fn set_block_meta(&self, meta: BlockMeta, x: i32, y: i32, z: i32) -> bool {
+        let (chunk_x, chunk_y, chunk_z, block_x, block_y, block_z)
+            = ChunkManager::get_chunk_coords(x, y, z);
+
+        match self.get_chunk(chunk_x, chunk_y, chunk_z) {
+            None => false,
+            Some(chunk) => {
+                chunk.set_block_meta(meta, block_x, block_y, block_z);
+                if *chunk.is_uploaded_to_gpu.read() {
+                    // The metadata can change the look of the block
+                    let block = chunk.get_block(block_x, block_y, block_z);
+                    self.add_to_changelist(1, block, x, y, z);
+                    self.changed_states.write().push((x, y, z));
+                }
+                true
+            }
+        }
+    }
+
+    pub fn get_block_light(&self, x: i32, y: i32, z: i32) -> BlockLight {
+        let (chunk_x, chunk_y, chunk_z, block_x, block_y, block_z)
+            = ChunkManager::get_chunk_coords(x, y, z);
+
+        self.get_chunk(chunk_x, chunk_y, chunk_z)
+            .map_or((0, 0), |chunk|
+                chunk.get_block_light(block_x, block_y, block_z))
+    }
+
+    /// The faces around the block are updated with the same priority as block changes
+    pub fn set_block_light(&self, priority: i32, light: BlockLight, x: i32, y: i32, z: i32) -> bool {
+        let (chunk_x, chunk_y, chunk_z, block_x, block_y, block_z)
+            = ChunkManager::get_chunk_coords(x, y, z);
+
+        match self.get_chunk(chunk_x, chunk_y, chunk_z) {
+            None => false,
+            Some(chunk) => {
+                chunk.set_block_light(light, block_x, block_y, block_z);
+                if *chunk.is_uploaded_to_gpu.read() {
+                    let block = chunk.get_block(block_x, block_y, block_z);
+                    self.add_to_changelist(priority, block, x, y, z);
+                }
+                true
+            }
+        }
+    }
+
     pub fn is_solid_block_at(&self, x: i32, y: i32, z: i32) -> bool {
+        match self.get_block(x, y, z) {
+            // Open fence gates let everything through
+            Some(BlockID::OakFenceGate) => self.get_block_meta(x, y, z).map_or(false, |meta| !meta.is_open()),
+            Some(block) => block.is_solid(),
+            None => false,
+        }
+    }
+
+    /// Height of the highest solid block of the column containing (x, z), once its trees have grown
+    pub fn topmost_solid_block(&self, x: i32, z: i32) -> Option<i32> {
+        let (c_x, _, c_z, _, _, _) = ChunkManager::get_chunk_coords(x, 0, z);
+        if !*self.get_column(c_x, c_z)?.has_foliage.read() {
+            return None;
+        }
+        (0..WORLD_HEIGHT_IN_CHUNKS * 16).rev().find(|&y| self.is_solid_block_at(x, y, z))
+    }
+
+    pub fn is_targetable_block_at(&self, x: i32, y: i32, z: i32) -> bool {
         self.get_block(x, y, z)
-            .filter(|&block| block != BlockID::Air)
+            .filter(|&block| block.is_targetable())
             .is_some()
     }
 
@@ -171,7 +314,7 @@ impl ChunkManager {
 
             let (c_x_n, c_y_n, c_z_n, b_x, b_y, b_z) = ChunkManager::get_chunk_coords(w_x, w_y, w_z);
 
-            if c_y_n < 0 || c_y_n >= 16 {
+            if c_y_n < 0 || c_y_n >= WORLD_HEIGHT_IN_CHUNKS {
                 return BlockID::Air;
             }
 
@@ -187,26 +330,58 @@ impl ChunkManager {
         };
 
         #[inline]
-        fn compute_active_faces(column: &ChunkColumn, neighbourhood: &[Option<Arc<ChunkColumn>>; 9], c_x: i32, c_z: i32, x: i32, y: i32, z: i32) -> [bool; 6] {
-            let right = block_at(&column, &neighbourhood, c_x, c_z, x + 1, y, z).is_transparent();
-            let left = block_at(&column, &neighbourhood, c_x, c_z, x - 1, y, z).is_transparent();
-            let top = block_at(&column, &neighbourhood, c_x, c_z, x, y + 1, z).is_transparent();
-            let bottom = block_at(&column, &neighbourhood, c_x, c_z, x, y - 1, z).is_transparent();
-            let front = block_at(&column, &neighbourhood, c_x, c_z, x, y, z + 1).is_transparent();
-            let back = block_at(&column, &neighbourhood, c_x, c_z, x, y, z - 1).is_transparent();
+        fn light_at(column: &ChunkColumn, neighbourhood: &[Option<Arc<ChunkColumn>>; 9], c_x: i32, c_z: i32, w_x: i32, w_y: i32, w_z: i32) -> BlockLight {
+            let to_index = |x: i32, z: i32| -> usize {
+                3 * (x - c_x + 1) as usize + (z - c_z + 1) as usize
+            };
+
+            let (c_x_n, c_y_n, c_z_n, b_x, b_y, b_z) = ChunkManager::get_chunk_coords(w_x, w_y, w_z);
+
+            if c_y_n < 0 || c_y_n >= WORLD_HEIGHT_IN_CHUNKS {
+                return (0, 0);
+            }
+
+            if c_x == c_x_n && c_z == c_z_n {
+                column.get_chunk(c_y_n).get_block_light(b_x, b_y, b_z)
+            } else {
+                neighbourhood[to_index(c_x_n, c_z_n)].as_ref()
+                    .map_or((0, 0), |neighbour_column| neighbour_column.get_chunk(c_y_n).get_block_light(b_x, b_y, b_z))
+            }
+        }
+
+        #[inline]
+        fn compute_active_faces(block: BlockID, column: &ChunkColumn, neighbourhood: &[Option<Arc<ChunkColumn>>; 9], c_x: i32, c_z: i32, x: i32, y: i32, z: i32) -> [bool; 6] {
+            let right = !should_cull_face(block, block_at(&column, &neighbourhood, c_x, c_z, x + 1, y, z));
+            let left = !should_cull_face(block, block_at(&column, &neighbourhood, c_x, c_z, x - 1, y, z));
+            let top = !should_cull_face(block, block_at(&column, &neighbourhood, c_x, c_z, x, y + 1, z));
+            let bottom = !should_cull_face(block, block_at(&column, &neighbourhood, c_x, c_z, x, y - 1, z));
+            let front = !should_cull_face(block, block_at(&column, &neighbourhood, c_x, c_z, x, y, z + 1));
+            let back = !should_cull_face(block, block_at(&column, &neighbourhood, c_x, c_z, x, y, z - 1));
             [right, left, top, bottom, front, back]
         };
 
         let mut active_faces = this_chunk.active_faces.write();
         let mut ao_vertices = this_chunk.ao_vertices.write();
+        let mut face_lights = this_chunk.face_lights.write();
+        let mut dirty_blocks = this_chunk.dirty_blocks.write();
 
         for (b_x, b_y, b_z) in blocks {
-            if this_chunk.get_block(b_x, b_y, b_z) == BlockID::Air {
+            if let Some(dirty) = dirty_blocks.as_mut() {
+                if !dirty.contains(&(b_x, b_y, b_z)) {
+                    dirty.push((b_x, b_y, b_z));
+                }
+                if dirty.len() > MAX_INCREMENTAL_DIRTY_BLOCKS {
+                    *dirty_blocks = None;
+                }
+            }
+
+            let block = this_chunk.get_block(b_x, b_y, b_z);
+            if block == BlockID::Air {
                 continue;
             }
             let (w_x, w_y, w_z) = ChunkManager::get_global_coords((c_x, c_y, c_z, b_x, b_y, b_z));
 
-            let af = compute_active_faces(&this_column, &neighbourhood, c_x, c_z, w_x, w_y, w_z);
+            let af = compute_active_faces(block, &this_column, &neighbourhood, c_x, c_z, w_x, w_y, w_z);
             let array_index = (b_y * CHUNK_SIZE * CHUNK_SIZE + b_z * CHUNK_SIZE + b_x) as usize;
 
             active_faces.set(6 * array_index, af[0]);
@@ -219,10 +394,23 @@ impl ChunkManager {
             // Ambient Occlusion
 
             let block_ao = compute_ao_of_block(&|rx: i32, ry: i32, rz: i32| {
-                !block_at(&this_column, &neighbourhood, c_x, c_z, w_x + rx, w_y + ry, w_z + rz).is_transparent_no_leaves()
+                block_at(&this_column, &neighbourhood, c_x, c_z, w_x + rx, w_y + ry, w_z + rz).occludes_ambient_light()
             });
 
             ao_vertices[array_index] = block_ao;
+
+            // Block light, the faces of light sources are lit by the source itself
+            let emitted = emitted_light(block);
+            let mut block_face_lights = [(0, 0); 6];
+            for (face, &(dx, dy, dz)) in [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)].iter().enumerate() {
+                let light = light_at(&this_column, &neighbourhood, c_x, c_z, w_x + dx, w_y + dy, w_z + dz);
+                block_face_lights[face] = (light.0.max(emitted.0), light.1.max(emitted.1));
+            }
+            if block_face_lights.iter().any(|&light| light != (0, 0)) {
+                face_lights.insert(array_index, block_face_lights);
+            } else {
+                face_lights.remove(&array_index);
+            }
         }
     }
 
@@ -250,7 +438,7 @@ impl ChunkManager {
 
         let block_ao = compute_ao_of_block(&|rx: i32, ry: i32, rz: i32| {
             self.get_block(w_x + rx, w_y + ry, w_z + rz)
-                .filter(|b| !b.is_transparent_no_leaves())
+                .filter(|b| b.occludes_ambient_light())
                 .is_some()
         });
         self.get_chunk(c_x, c_y, c_z).unwrap().ao_vertices.write()[array_index] = block_ao;
@@ -258,45 +446,138 @@ impl ChunkManager {
 
     // An active face is a block face next to a transparent block that needs to be rendered
     pub fn get_active_faces_of_block(&self, x: i32, y: i32, z: i32) -> [bool; 6] {
-        let right = self.get_block(x + 1, y, z).filter(|&b| !b.is_transparent()).is_none();
-        let left = self.get_block(x - 1, y, z).filter(|&b| !b.is_transparent()).is_none();
-        let top = self.get_block(x, y + 1, z).filter(|&b| !b.is_transparent()).is_none();
-        let bottom = self.get_block(x, y - 1, z).filter(|&b| !b.is_transparent()).is_none();
-        let front = self.get_block(x, y, z + 1).filter(|&b| !b.is_transparent()).is_none();
-        let back = self.get_block(x, y, z - 1).filter(|&b| !b.is_transparent()).is_none();
+        let block = self.get_block(x, y, z).unwrap_or(BlockID::Air);
+        let is_visible = |neighbour: Option<BlockID>| neighbour.map_or(true, |b| !should_cull_face(block, b));
+        let right = is_visible(self.get_block(x + 1, y, z));
+        let left = is_visible(self.get_block(x - 1, y, z));
+        let top = is_visible(self.get_block(x, y + 1, z));
+        let bottom = is_visible(self.get_block(x, y - 1, z));
+        let front = is_visible(self.get_block(x, y, z + 1));
+        let back = is_visible(self.get_block(x, y, z - 1));
         [right, left, top, bottom, front, back]
     }
 
-    pub fn render_loaded_chunks(&self, program: &mut ShaderProgram) {
+    pub fn render_loaded_water(&self, program: &mut ShaderProgram) {
         for ((x, z), chunk_column) in self.loaded_chunk_columns.read().iter() {
-            for (ref y, chunk) in chunk_column.chunks.iter().enumerate() {
-                // Skip rendering the chunk if there is nothing to draw
-                let vao = *chunk.vao.read();
-                if !*chunk.is_uploaded_to_gpu.read() || chunk.is_empty() || vao == 0 {
+            for (y, chunk) in chunk_column.chunks.iter().enumerate() {
+                let water_vao = *chunk.water_vao.read();
+                let water_vertices_drawn = *chunk.water_vertices_drawn.read();
+                if !*chunk.is_uploaded_to_gpu.read() || water_vao == 0 || water_vertices_drawn == 0 {
                     continue;
                 }
 
-                let model_matrix = {
-                    let translate_matrix = Matrix4::new_translation(&vec3(
-                        *x as f32, *y as f32, *z as f32).scale(16.0));
-                    let rotate_matrix = Matrix4::from_euler_angles(
-                        0.0f32,
-                        0.0,
-                        0.0,
-                    );
-                    let scale_matrix: Mat4 = Matrix4::new_nonuniform_scaling(&vec3(1.0f32, 1.0f32, 1.0f32));
-                    translate_matrix * rotate_matrix * scale_matrix
-                };
+                let model_matrix = Matrix4::new_translation(&vec3(
+                    *x as f32, y as f32, *z as f32).scale(16.0));
 
-                gl_call!(gl::BindVertexArray(vao));
-                if vao == 0 {
-                    dbg!(vao);
-                    dbg!(*chunk.is_uploaded_to_gpu.read());
-                    dbg!(chunk.is_empty());
+                gl_call!(gl::BindVertexArray(water_vao));
+                program.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+                program.set_uniform1f("chunk_opacity", *chunk.opacity.read());
+                gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, water_vertices_drawn as i32));
+            }
+        }
+    }
+
+    /// Draws the bounding box of every chunk against the depth buffer, each inside its own occlusion query.
+    /// The results are only read during the next frame so that the CPU never waits for the GPU
+    pub fn issue_occlusion_queries(&self, program: &mut ShaderProgram, bounding_box_vao: u32) {
+        gl_call!(gl::BindVertexArray(bounding_box_vao));
+        for ((x, z), chunk_column) in self.loaded_chunk_columns.read().iter() {
+            for (y, chunk) in chunk_column.chunks.iter().enumerate() {
+                if !*chunk.is_uploaded_to_gpu.read() || chunk.is_empty() || *chunk.vao.read() == 0 {
+                    continue;
                 }
+
+                let model_matrix = Matrix4::new_translation(&vec3(
+                    *x as f32, y as f32, *z as f32).scale(16.0)) * Matrix4::new_scaling(16.0);
                 program.set_uniform_matrix4fv("model", model_matrix.as_ptr());
-                gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, *chunk.vertices_drawn.read() as i32));
+
+                let query = chunk.next_occlusion_query();
+                gl_call!(gl::BeginQuery(gl::SAMPLES_PASSED, query));
+                gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 36));
+                gl_call!(gl::EndQuery(gl::SAMPLES_PASSED));
             }
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::{BlockIterator, MeshPatch};
+
+    #[test]
+    fn glass_next_to_glass_is_culled() {
+        assert!(should_cull_face(BlockID::Glass, BlockID::Glass));
+    }
+
+    #[test]
+    fn glass_next_to_air_is_drawn() {
+        assert!(!should_cull_face(BlockID::Glass, BlockID::Air));
+    }
+
+    #[test]
+    fn glass_next_to_water_is_drawn() {
+        assert!(!should_cull_face(BlockID::Glass, BlockID::Water));
+        assert!(!should_cull_face(BlockID::Water, BlockID::Glass));
+    }
+
+    #[test]
+    fn glass_next_to_leaves_is_drawn() {
+        assert!(!should_cull_face(BlockID::Glass, BlockID::OakLeaves));
+        assert!(!should_cull_face(BlockID::OakLeaves, BlockID::Glass));
+    }
+
+    #[test]
+    fn glass_faces_next_to_leaves_are_drawn() {
+        let mut chunk_manager = ChunkManager::default();
+        chunk_manager.leaves_next_to_glass();
+        let glass = (1, 1, 1);
+        let array_index = (glass.1 * CHUNK_SIZE * CHUNK_SIZE + glass.2 * CHUNK_SIZE + glass.0) as usize;
+        let chunk = chunk_manager.get_chunk(0, 0, 0).unwrap();
+
+        chunk_manager.update_blocks(0, 0, 0, std::iter::once(glass));
+        let faces: Vec<bool> = (0..6).map(|face| chunk.active_faces.read()[6 * array_index + face]).collect();
+        // Right, left, top, front and back are next to leaves or air, the bottom lies on the floor
+        assert_eq!(faces, vec![true, true, true, false, true, true]);
+        let ao_with_leaves = chunk.ao_vertices.read()[array_index];
+
+        // Only the floor may darken the corners, so the leaves change nothing
+        for &(x, y, z) in [(2, 1, 1), (1, 1, 2), (1, 2, 1)].iter() {
+            chunk_manager.set_block(BlockID::Air, x, y, z);
+        }
+        chunk_manager.update_blocks(0, 0, 0, std::iter::once(glass));
+        assert_eq!(ao_with_leaves, chunk.ao_vertices.read()[array_index]);
+        assert_eq!(ao_with_leaves[2], [0; 4]);
+    }
+
+    #[test]
+    fn placing_a_block_rewrites_the_mesh_in_place() {
+        let mut chunk_manager = ChunkManager::default();
+        chunk_manager.add_chunk_column((0, 0), Arc::new(ChunkColumn::new()));
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk_manager.set_block(BlockID::Stone, x, 0, z);
+            }
+        }
+        chunk_manager.update_blocks(0, 0, 0, BlockIterator::new());
+        let chunk = chunk_manager.get_chunk(0, 0, 0).unwrap();
+        // What the first upload does, without the GL calls
+        chunk.lay_out_meshes();
+        *chunk.dirty_blocks.write() = Some(Vec::new());
+
+        chunk_manager.set_block(BlockID::Stone, 5, 1, 5);
+        let neighbours = [(5, 1, 5), (6, 1, 5), (4, 1, 5), (5, 2, 5), (5, 0, 5), (5, 1, 6), (5, 1, 4)];
+        chunk_manager.update_blocks(0, 0, 0, neighbours.iter().cloned());
+
+        let dirty = chunk.dirty_blocks.write().replace(Vec::new()).unwrap();
+        let patches = chunk.plan_incremental_upload(&dirty).expect("The whole mesh was uploaded again");
+        let patch_of = |block| patches.iter()
+            .find(|&&(position, is_water_mesh, _)| position == block && !is_water_mesh)
+            .map(|(_, _, patch)| patch)
+            .unwrap();
+        // The new block goes to the free room at the end of the mesh, the floor under it loses its top face in place
+        assert!(matches!(patch_of((5, 1, 5)), MeshPatch::Write { cleared: None, .. }));
+        assert!(matches!(patch_of((5, 0, 5)), MeshPatch::Write { cleared: None, .. }));
+        assert_eq!(*patch_of((5, 2, 5)), MeshPatch::Nothing);
+    }
+}
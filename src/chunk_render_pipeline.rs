@@ -0,0 +1,366 @@
+use std::collections::{HashMap, HashSet};
+use std::ptr::null;
+
+use nalgebra_glm::Vec3;
+
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{CLOSE_RING_DISTANCE, MEDIUM_RING_DISTANCE};
+
+/// Floats of a vertex of the chunk meshes
+const VERTEX_FLOATS: usize = 12;
+const VERTEX_SIZE: usize = VERTEX_FLOATS * std::mem::size_of::<f32>();
+/// Floats of the data of a chunk read once per draw: its origin and opacity, then its temperature and humidity
+const CHUNK_DATA_FLOATS: usize = 12;
+const NUMBER_OF_RINGS: usize = 3;
+
+/// Layout expected by glMultiDrawArraysIndirect
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct DrawArraysIndirectCommand {
+    pub count: u32,
+    pub instance_count: u32,
+    pub first: u32,
+    pub base_instance: u32,
+}
+
+/// Where the mesh of a chunk is in the vertex buffer of its ring
+struct ChunkSlot {
+    position: (i32, i32, i32),
+    mesh_version: u64,
+    first: u32,
+    count: u32,
+    /// Vertices reserved for the mesh, it is copied again in place as long as it fits
+    capacity: u32,
+}
+
+/// The chunks of a ring around the player, drawn with a single multi-draw.
+/// Their meshes are copied into a shared vertex buffer, each command draws one of them.
+/// Only the meshes of the chunks that changed or joined the ring are copied, the others keep their place.
+/// The base instance of a command points to the data of its chunk in an instanced vertex buffer
+pub struct ChunkRenderGroup {
+    /// 0 for the closest ring
+    pub ring: u8,
+    pub commands: Vec<DrawArraysIndirectCommand>,
+    /// Chunk drawn by each command
+    slots: Vec<ChunkSlot>,
+    /// Ranges of the vertex buffer left by the chunks that were removed, as (first vertex, vertices)
+    free_ranges: Vec<(u32, u32)>,
+    /// End of the last range in use
+    used_vertices: u32,
+    vertex_capacity: u32,
+    /// Chunks the chunk data and the command buffers have room for
+    chunk_capacity: usize,
+    vao: u32,
+    vertex_buffer: u32,
+    chunk_data_buffer: u32,
+    command_buffer: u32,
+}
+
+impl ChunkRenderGroup {
+    pub fn new(ring: u8) -> Self {
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        // Position, texture coords, normal, ambient occlusion and block light, like the meshes of the chunks
+        for &(attribute, size, offset) in [(0, 3, 0), (1, 3, 3), (2, 3, 6), (3, 1, 9), (4, 2, 10)].iter() {
+            gl_call!(gl::EnableVertexArrayAttrib(vao, attribute));
+            gl_call!(gl::VertexArrayAttribFormat(vao, attribute, size, gl::FLOAT, gl::FALSE, offset * std::mem::size_of::<f32>() as u32));
+            gl_call!(gl::VertexArrayAttribBinding(vao, attribute, 0));
+        }
+
+        // Origin and opacity, temperature and humidity of the chunk, advancing once per draw
+        for &(attribute, offset) in [(5, 0), (6, 4), (7, 8)].iter() {
+            gl_call!(gl::EnableVertexArrayAttrib(vao, attribute));
+            gl_call!(gl::VertexArrayAttribFormat(vao, attribute, 4, gl::FLOAT, gl::FALSE, offset * std::mem::size_of::<f32>() as u32));
+            gl_call!(gl::VertexArrayAttribBinding(vao, attribute, 1));
+        }
+        gl_call!(gl::VertexArrayBindingDivisor(vao, 1, 1));
+
+        let mut buffers = [0; 3];
+        gl_call!(gl::CreateBuffers(3, buffers.as_mut_ptr()));
+        let [vertex_buffer, chunk_data_buffer, command_buffer] = buffers;
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vertex_buffer, 0, VERTEX_SIZE as i32));
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 1, chunk_data_buffer, 0, (CHUNK_DATA_FLOATS * std::mem::size_of::<f32>()) as i32));
+
+        ChunkRenderGroup {
+            ring,
+            commands: Vec::new(),
+            slots: Vec::new(),
+            free_ranges: Vec::new(),
+            used_vertices: 0,
+            vertex_capacity: 0,
+            chunk_capacity: 0,
+            vao,
+            vertex_buffer,
+            chunk_data_buffer,
+            command_buffer,
+        }
+    }
+
+    /// Makes the ring draw `chunks`, given with the version of their mesh.
+    /// The chunks that left the ring free their range, the new and the changed ones are copied
+    fn update(&mut self, chunk_manager: &ChunkManager, chunks: &HashMap<(i32, i32, i32), u64>) {
+        let mut i = 0;
+        while i < self.slots.len() {
+            if chunks.contains_key(&self.slots[i].position) {
+                i += 1;
+            } else {
+                let slot = self.slots.swap_remove(i);
+                self.free(slot.first, slot.capacity);
+            }
+        }
+
+        let mut kept = HashSet::new();
+        for i in 0..self.slots.len() {
+            let version = chunks[&self.slots[i].position];
+            if self.slots[i].mesh_version != version {
+                self.copy_mesh(chunk_manager, i, version);
+            }
+            kept.insert(self.slots[i].position);
+        }
+
+        for (&position, &version) in chunks.iter() {
+            if kept.contains(&position) {
+                continue;
+            }
+            self.slots.push(ChunkSlot { position, mesh_version: version, first: 0, count: 0, capacity: 0 });
+            self.copy_mesh(chunk_manager, self.slots.len() - 1, version);
+        }
+
+        if self.slots.len() > self.chunk_capacity {
+            self.chunk_capacity = self.slots.len().max(2 * self.chunk_capacity);
+            gl_call!(gl::NamedBufferData(self.chunk_data_buffer,
+                (self.chunk_capacity * CHUNK_DATA_FLOATS * std::mem::size_of::<f32>()) as isize, null(), gl::DYNAMIC_DRAW));
+            gl_call!(gl::NamedBufferData(self.command_buffer,
+                (self.chunk_capacity * std::mem::size_of::<DrawArraysIndirectCommand>()) as isize, null(), gl::DYNAMIC_DRAW));
+        }
+    }
+
+    /// Copies the mesh of the chunk of the slot `i` into the vertex buffer, in place if it still fits
+    fn copy_mesh(&mut self, chunk_manager: &ChunkManager, i: usize, version: u64) {
+        let (x, y, z) = self.slots[i].position;
+        let chunk = chunk_manager.get_chunk(x, y, z);
+        let count = chunk.as_ref().map_or(0, |chunk| *chunk.vertices_drawn.read());
+        if count > self.slots[i].capacity {
+            let (first, capacity) = (self.slots[i].first, self.slots[i].capacity);
+            self.free(first, capacity);
+            // Room to grow a little, so that the next edits of the chunk are copied in place
+            let capacity = count + count / 4;
+            self.slots[i].first = self.allocate(capacity);
+            self.slots[i].capacity = capacity;
+        }
+        let slot = &mut self.slots[i];
+        slot.count = count;
+        slot.mesh_version = version;
+        if let Some(chunk) = chunk {
+            gl_call!(gl::CopyNamedBufferSubData(*chunk.vbo.read(), self.vertex_buffer,
+                0, (slot.first as usize * VERTEX_SIZE) as isize, (count as usize * VERTEX_SIZE) as isize));
+        }
+    }
+
+    /// First vertex of a free range of `vertices`, the vertex buffer grows when none is left
+    fn allocate(&mut self, vertices: u32) -> u32 {
+        if let Some(i) = self.free_ranges.iter().position(|&(_, length)| length >= vertices) {
+            let (first, length) = self.free_ranges[i];
+            if length == vertices {
+                self.free_ranges.remove(i);
+            } else {
+                self.free_ranges[i] = (first + vertices, length - vertices);
+            }
+            return first;
+        }
+
+        let first = self.used_vertices;
+        if first + vertices > self.vertex_capacity {
+            self.grow(first + vertices);
+        }
+        self.used_vertices += vertices;
+        first
+    }
+
+    /// Gives back a range of the vertex buffer, merged with the free ranges next to it
+    fn free(&mut self, first: u32, vertices: u32) {
+        if vertices == 0 {
+            return;
+        }
+        let i = self.free_ranges.iter().position(|&(other, _)| other > first).unwrap_or(self.free_ranges.len());
+        self.free_ranges.insert(i, (first, vertices));
+        if i + 1 < self.free_ranges.len() && first + vertices == self.free_ranges[i + 1].0 {
+            self.free_ranges[i].1 += self.free_ranges.remove(i + 1).1;
+        }
+        if i > 0 && self.free_ranges[i - 1].0 + self.free_ranges[i - 1].1 == first {
+            self.free_ranges[i - 1].1 += self.free_ranges.remove(i).1;
+        }
+        // The range at the end is given back to the unused part of the buffer
+        if let Some(&(last, length)) = self.free_ranges.last() {
+            if last + length == self.used_vertices {
+                self.used_vertices = last;
+                self.free_ranges.pop();
+            }
+        }
+    }
+
+    /// Replaces the vertex buffer by a bigger one, with the meshes already in it
+    fn grow(&mut self, vertices: u32) {
+        let capacity = vertices.max(2 * self.vertex_capacity);
+        let mut vertex_buffer = 0;
+        gl_call!(gl::CreateBuffers(1, &mut vertex_buffer));
+        gl_call!(gl::NamedBufferData(vertex_buffer, (capacity as usize * VERTEX_SIZE) as isize, null(), gl::DYNAMIC_DRAW));
+        if self.used_vertices > 0 {
+            gl_call!(gl::CopyNamedBufferSubData(self.vertex_buffer, vertex_buffer,
+                0, 0, (self.used_vertices as usize * VERTEX_SIZE) as isize));
+        }
+        gl_call!(gl::DeleteBuffers(1, &self.vertex_buffer));
+        gl_call!(gl::VertexArrayVertexBuffer(self.vao, 0, vertex_buffer, 0, VERTEX_SIZE as i32));
+        self.vertex_buffer = vertex_buffer;
+        self.vertex_capacity = capacity;
+    }
+
+    /// Refreshes what changes every frame (the opacity and the occlusion of the chunks), then draws the ring
+    fn draw(&mut self, chunk_manager: &ChunkManager, occlusion_camera: Option<&Vec3>) {
+        self.commands.clear();
+        if self.slots.is_empty() {
+            return;
+        }
+
+        let mut chunk_data = Vec::with_capacity(self.slots.len() * CHUNK_DATA_FLOATS);
+        for (i, slot) in self.slots.iter().enumerate() {
+            let (x, y, z) = slot.position;
+            let (opacity, climate, is_visible) = match chunk_manager.get_column(x, z) {
+                Some(column) => {
+                    let chunk = column.get_chunk(y);
+                    let is_visible = match occlusion_camera {
+                        Some(camera_position) => !chunk.update_occlusion() || is_camera_near_chunk(camera_position, x, y, z),
+                        None => true,
+                    };
+                    (*chunk.opacity.read(), *column.climate.read(), is_visible)
+                }
+                None => (0.0, [(0.5, 0.5); 4], false),
+            };
+            self.commands.push(DrawArraysIndirectCommand {
+                count: slot.count,
+                instance_count: is_visible as u32,
+                first: slot.first,
+                base_instance: i as u32,
+            });
+            chunk_data.extend(&[x as f32 * 16.0, y as f32 * 16.0, z as f32 * 16.0, opacity]);
+            chunk_data.extend(climate.iter().map(|&(temperature, _)| temperature));
+            chunk_data.extend(climate.iter().map(|&(_, humidity)| humidity));
+        }
+
+        gl_call!(gl::NamedBufferSubData(self.chunk_data_buffer, 0,
+            (chunk_data.len() * std::mem::size_of::<f32>()) as isize, chunk_data.as_ptr() as *const _));
+        gl_call!(gl::NamedBufferSubData(self.command_buffer, 0,
+            (self.commands.len() * std::mem::size_of::<DrawArraysIndirectCommand>()) as isize, self.commands.as_ptr() as *const _));
+        self.redraw();
+    }
+
+    /// Draws the ring again as it was last drawn
+    fn redraw(&self) {
+        if self.commands.is_empty() {
+            return;
+        }
+        gl_call!(gl::BindVertexArray(self.vao));
+        gl_call!(gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, self.command_buffer));
+        gl_call!(gl::MultiDrawArraysIndirect(gl::TRIANGLES, null(), self.commands.len() as i32, 0));
+        gl_call!(gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0));
+    }
+}
+
+/// Draws the opaque meshes of the loaded chunks, grouped in rings around the player.
+/// The rings are updated when a chunk is uploaded or unloaded, or when the player moves to another chunk
+pub struct ChunkRenderPipeline {
+    groups: Vec<ChunkRenderGroup>,
+    /// Every chunk drawn with the version of its mesh, compared every frame to find out when to update the rings
+    drawn_chunks: Vec<((i32, i32, i32), u64)>,
+    /// Chunk column the rings are centered on
+    center: Option<(i32, i32)>,
+}
+
+impl Default for ChunkRenderPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChunkRenderPipeline {
+    pub fn new() -> Self {
+        ChunkRenderPipeline {
+            groups: (0..NUMBER_OF_RINGS).map(|ring| ChunkRenderGroup::new(ring as u8)).collect(),
+            drawn_chunks: Vec::new(),
+            center: None,
+        }
+    }
+
+    /// Ring of the chunk column at (x, z) when the player is in the column at (center_x, center_z)
+    pub fn ring_of((x, z): (i32, i32), (center_x, center_z): (i32, i32)) -> u8 {
+        let distance = (x - center_x).abs().max((z - center_z).abs());
+        if distance <= CLOSE_RING_DISTANCE {
+            0
+        } else if distance <= MEDIUM_RING_DISTANCE {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// The chunks with an opaque mesh on the GPU, with the version of their mesh
+    fn drawable_chunks(chunk_manager: &ChunkManager) -> Vec<((i32, i32, i32), u64)> {
+        let mut chunks = Vec::new();
+        for (&(x, z), chunk_column) in chunk_manager.loaded_chunk_columns.read().iter() {
+            for (y, chunk) in chunk_column.chunks.iter().enumerate() {
+                if !*chunk.is_uploaded_to_gpu.read() || chunk.is_empty() || *chunk.vao.read() == 0
+                    || *chunk.vertices_drawn.read() == 0 {
+                    continue;
+                }
+                chunks.push(((x, y as i32, z), *chunk.mesh_version.read()));
+            }
+        }
+        chunks.sort_unstable();
+        chunks
+    }
+
+    /// Sorts the drawn chunks into the rings, each ring only copies the meshes it didn't have yet
+    fn update_rings(&mut self, chunk_manager: &ChunkManager, center: (i32, i32)) {
+        let mut rings = vec![HashMap::new(); NUMBER_OF_RINGS];
+        for &((x, y, z), version) in self.drawn_chunks.iter() {
+            rings[ChunkRenderPipeline::ring_of((x, z), center) as usize].insert((x, y, z), version);
+        }
+        for (group, chunks) in self.groups.iter_mut().zip(rings) {
+            group.update(chunk_manager, &chunks);
+        }
+    }
+
+    /// Draws every ring with the shader in use, the voxel shader or the depth prepass.
+    /// The chunks hidden behind others are skipped when `occlusion_camera` is given
+    pub fn draw_all_rings(&mut self, chunk_manager: &ChunkManager, player_position: &Vec3, occlusion_camera: Option<&Vec3>) {
+        let (c_x, _, c_z, _, _, _) = ChunkManager::get_chunk_coords(
+            player_position.x as i32, player_position.y as i32, player_position.z as i32);
+        let drawn_chunks = ChunkRenderPipeline::drawable_chunks(chunk_manager);
+        if drawn_chunks != self.drawn_chunks || self.center != Some((c_x, c_z)) {
+            self.drawn_chunks = drawn_chunks;
+            self.center = Some((c_x, c_z));
+            self.update_rings(chunk_manager, (c_x, c_z));
+        }
+
+        for group in self.groups.iter_mut() {
+            group.draw(chunk_manager, occlusion_camera);
+        }
+    }
+
+    /// Draws the same chunks as the last `draw_all_rings` with the shader in use, e.g. to shade them after a depth prepass
+    pub fn redraw_all_rings(&self) {
+        for group in self.groups.iter() {
+            group.redraw();
+        }
+    }
+}
+
+/// The bounding box of a chunk around the camera gets clipped by the near plane and can wrongly report
+/// no visible samples, so these chunks are always drawn
+fn is_camera_near_chunk(camera_position: &Vec3, x: i32, y: i32, z: i32) -> bool {
+    let margin = 1.0;
+    let mins = nalgebra_glm::vec3(x as f32, y as f32, z as f32).scale(16.0).add_scalar(-margin);
+    let maxs = mins.add_scalar(16.0 + 2.0 * margin);
+    (0..3).all(|i| camera_position[i] >= mins[i] && camera_position[i] <= maxs[i])
+}
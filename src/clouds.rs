@@ -0,0 +1,96 @@
+use std::ffi::c_void;
+
+use noise::{NoiseFn, Point2, Seedable, SuperSimplex};
+
+use crate::day_cycle::{DayCycle, DAWN, DUSK};
+
+pub const CLOUD_TEXTURE_UNIT: u32 = 12;
+/// Side of the cloud texture, in texels
+const CLOUD_TEXTURE_SIZE: usize = 256;
+/// Texels per period of the noise
+const CLOUD_NOISE_SCALE: f64 = 24.0;
+/// A texel is cloudy where the noise is above this
+const CLOUD_COVER_THRESHOLD: f64 = 0.15;
+
+const NOON_COLOR: (f32, f32, f32) = (1.0, 1.0, 1.0);
+const SUNSET_COLOR: (f32, f32, f32) = (1.0, 0.6, 0.35);
+const NIGHT_COLOR: (f32, f32, f32) = (0.25, 0.25, 0.28);
+/// Part of the day around sunrise and sunset during which the clouds are orange
+const SUNSET_DURATION: f32 = 0.08;
+
+/// Noise wrapping around every CLOUD_TEXTURE_SIZE texels, by blending it with its copies shifted by a period
+fn tiled_noise(noise_fn: &SuperSimplex, x: f64, y: f64) -> f64 {
+    let size = CLOUD_TEXTURE_SIZE as f64;
+    let sample = |x: f64, y: f64| noise_fn.get(Point2::from([x / CLOUD_NOISE_SCALE, y / CLOUD_NOISE_SCALE]));
+    let (u, v) = (x / size, y / size);
+    sample(x, y) * (1.0 - u) * (1.0 - v)
+        + sample(x - size, y) * u * (1.0 - v)
+        + sample(x, y - size) * (1.0 - u) * v
+        + sample(x - size, y - size) * u * v
+}
+
+/// The clouds seen from below, a texel is either fully opaque or transparent. They are the same for a given world seed
+pub fn create_cloud_texture(seed: u32) -> u32 {
+    let noise_fn = SuperSimplex::new().set_seed(seed.wrapping_add(1234));
+    let mut pixels = Vec::with_capacity(CLOUD_TEXTURE_SIZE * CLOUD_TEXTURE_SIZE);
+    for y in 0..CLOUD_TEXTURE_SIZE {
+        for x in 0..CLOUD_TEXTURE_SIZE {
+            let is_cloudy = tiled_noise(&noise_fn, x as f64, y as f64) > CLOUD_COVER_THRESHOLD;
+            pixels.push(if is_cloudy { 255u8 } else { 0 });
+        }
+    }
+
+    let mut cloud_texture = 0;
+    gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut cloud_texture));
+    gl_call!(gl::TextureParameteri(cloud_texture, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32));
+    gl_call!(gl::TextureParameteri(cloud_texture, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32));
+    gl_call!(gl::TextureParameteri(cloud_texture, gl::TEXTURE_WRAP_S, gl::REPEAT as i32));
+    gl_call!(gl::TextureParameteri(cloud_texture, gl::TEXTURE_WRAP_T, gl::REPEAT as i32));
+    gl_call!(gl::TextureStorage2D(cloud_texture, 1, gl::R8, CLOUD_TEXTURE_SIZE as i32, CLOUD_TEXTURE_SIZE as i32));
+    gl_call!(gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1));
+    gl_call!(gl::TextureSubImage2D(
+            cloud_texture, 0,
+            0, 0, CLOUD_TEXTURE_SIZE as i32, CLOUD_TEXTURE_SIZE as i32,
+            gl::RED, gl::UNSIGNED_BYTE,
+            pixels.as_ptr() as *mut c_void));
+    gl_call!(gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4));
+    cloud_texture
+}
+
+/// A horizontal square from (-1, 0, -1) to (1, 0, 1)
+pub fn create_cloud_vao() -> u32 {
+    let mut vao = 0;
+    gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+    // Position
+    gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+    gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+    gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+    let vbo_data: [f32; 18] = [
+        -1.0, 0.0, -1.0, 1.0, 0.0, -1.0, 1.0, 0.0, 1.0,
+        1.0, 0.0, 1.0, -1.0, 0.0, 1.0, -1.0, 0.0, -1.0,
+    ];
+
+    let mut vbo = 0;
+    gl_call!(gl::CreateBuffers(1, &mut vbo));
+    gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (3 * std::mem::size_of::<f32>()) as i32));
+    gl_call!(gl::NamedBufferData(vbo,
+                (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                vbo_data.as_ptr() as *const c_void,
+                gl::STATIC_DRAW));
+    vao
+}
+
+/// White during the day, orange around sunrise and sunset and dark gray at night
+pub fn cloud_color(day_cycle: &DayCycle) -> [f32; 3] {
+    let mix = |a: (f32, f32, f32), b: (f32, f32, f32), t: f32| (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t, a.2 + (b.2 - a.2) * t);
+
+    let base = mix(NIGHT_COLOR, NOON_COLOR, day_cycle.daylight());
+    // DAWN is at the start of the cycle, it is also close to its end
+    let time = day_cycle.sky_time;
+    let from_sunset = (time - DAWN).abs().min((1.0 - time + DAWN).abs()).min((time - DUSK).abs());
+    let sunset = (1.0 - from_sunset / SUNSET_DURATION).max(0.0);
+    let (r, g, b) = mix(base, SUNSET_COLOR, sunset * day_cycle.daylight());
+    [r, g, b]
+}
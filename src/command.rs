@@ -0,0 +1,16 @@
+use crate::weather::Weather;
+
+/// Runs a command typed in the chat, without its leading slash (e.g. `weather rain`).
+/// Returns the answer shown in the chat
+pub fn run_command(command: &str, weather: &mut Weather) -> Result<String, String> {
+    let mut words = command.trim().splitn(2, ' ');
+    let name = words.next().unwrap_or("");
+    let argument = words.next().unwrap_or("").trim();
+    match name {
+        "weather" => {
+            weather.run_command(argument)?;
+            Ok(format!("Set the weather to {}", argument))
+        }
+        _ => Err(format!("Unknown command \"/{}\"", name)),
+    }
+}
@@ -1,5 +1,5 @@
+use std::path::PathBuf;
 use std::time::Duration;
-use rand::{thread_rng, RngCore};
 
 // Window
 pub const OPENGL_MAJOR_VERSION: u32 = 4;
@@ -18,19 +18,117 @@ lazy_static! {
 pub const GUI_SCALING: f32 = 2.0;
 pub const CROSSHAIR_SIZE: f32 = 40.0;
 pub const BLOCK_OUTLINE_WIDTH: f32 = 3.0;
+// Color of the outline of the targeted block, depending on whether the item in hand is the right tool to mine it.
+// In Creative every block is broken at once, so the outline keeps its plain color
+pub const OUTLINE_COLOR: (f32, f32, f32, f32) = (0.0, 0.0, 0.0, 0.35);
+pub const OUTLINE_HARVESTABLE_COLOR: (f32, f32, f32, f32) = (0.2, 0.8, 0.2, 0.5);
+pub const OUTLINE_WRONG_TOOL_COLOR: (f32, f32, f32, f32) = (0.9, 0.8, 0.1, 0.5);
+pub const OUTLINE_UNBREAKABLE_COLOR: (f32, f32, f32, f32) = (0.8, 0.1, 0.1, 0.5);
 
-lazy_static! {
-    pub static ref WORLD_SEED: u32 = {
-        let seed = thread_rng().next_u32();
-        println!("Seed: {}", seed);
-        seed
-    };
+/// Seed of the world generation, given by `--seed <N>` on the command line or by the MEINKRAFT_SEED
+/// environment variable. The world is picked in the main menu without them
+pub fn get_world_seed() -> Option<u32> {
+    let args: Vec<String> = std::env::args().collect();
+    let from_args = args.iter()
+        .position(|arg| arg == "--seed")
+        .and_then(|i| args.get(i + 1));
+    if let Some(seed) = from_args {
+        match seed.parse() {
+            Ok(seed) => return Some(seed),
+            Err(e) => error!("Invalid seed \"{}\": {}", seed, e),
+        }
+    }
+
+    if let Ok(seed) = std::env::var("MEINKRAFT_SEED") {
+        match seed.parse() {
+            Ok(seed) => return Some(seed),
+            Err(e) => error!("Invalid seed \"{}\" in MEINKRAFT_SEED: {}", seed, e),
+        }
+    }
+
+    None
+}
+
+/// Directory of the texture pack given by `--texture-pack <path>` on the command line
+pub fn get_texture_pack_path() -> Option<PathBuf> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|arg| arg == "--texture-pack")
+        .and_then(|i| args.get(i + 1))
+        .map(PathBuf::from)
 }
 
+// Texture packs
+/// Optional file of a texture pack naming the textures its files replace
+pub const TEXTURE_PACK_MANIFEST: &str = "manifest.json";
+// The array texture is built again when a file in there changes, checked every TEXTURE_RELOAD_INTERVAL seconds
+pub const BLOCK_TEXTURES_DIRECTORY: &str = "textures/blocks";
+pub const TEXTURE_RELOAD_INTERVAL: f32 = 2.0;
+
+// World generation
+// Number of chunks stacked in a column, the world is 16 times as many blocks high
+pub const WORLD_HEIGHT_IN_CHUNKS: i32 = 32;
+// Every air block below this height (included) is filled with water
+pub const WATER_LEVEL: i32 = 100;
+// Rivers may start every RIVER_SPACING blocks and flow downhill until the sea or RIVER_MAX_LENGTH blocks
+pub const RIVER_SPACING: i32 = 64;
+pub const RIVER_SOURCE_CHANCE: f32 = 0.25;
+pub const RIVER_MAX_LENGTH: usize = 512;
+// The stone is replaced by granite, diorite or andesite where their noise gets above this value
+pub const STONE_VARIANT_THRESHOLD: f64 = 0.6;
+// One block in the Nether is worth this many blocks in the Overworld
+pub const NETHER_SCALE: i32 = 8;
+pub const NETHER_CEILING_HEIGHT: i32 = 127;
+// Every cavern below this height (included) is filled with lava
+pub const NETHER_LAVA_LEVEL: i32 = 31;
+// At most one Nether fortress is generated in every NETHER_FORTRESS_REGION_SIZE wide region, with this chance.
+// Its floor is at NETHER_FORTRESS_Y
+pub const NETHER_FORTRESS_REGION_SIZE: i32 = 192;
+pub const NETHER_FORTRESS_CHANCE: f64 = 0.5;
+pub const NETHER_FORTRESS_Y: i32 = 64;
+pub const NETHER_BACKGROUND_COLOR: (f32, f32, f32, f32) = (0.2, 0.03, 0.03, 1.0);
+
 // Rendering
 pub const RENDER_DISTANCE: i32 = 10;
+// How dark the ambient occlusion makes the corners, from 0 (turned off) to 1
+pub const AO_STRENGTH: f32 = 1.0;
+// Samples taken by the texture filtering at oblique angles, a power of two from 1 (turned off) to 16
+pub const ANISOTROPY: u32 = 4;
 pub const ENABLE_FOG: bool = true;
 pub const CHUNK_UPLOADS_PER_FRAME: usize = 2;
+// Updated blocks of a chunk above which its whole mesh is rebuilt instead of rewriting their faces in place
+pub const MAX_INCREMENTAL_DIRTY_BLOCKS: usize = 64;
+// Free vertices left at the end of the meshes of the chunks, for the blocks that gain quads when a block next to them changes
+pub const MESH_SLACK_VERTICES: u32 = 8 * 36;
+// Added to the upload priority of the chunks changed by the player, more than the squared distance of any loaded chunk
+pub const CHUNK_UPLOAD_INTERACTION_PRIORITY: i32 = 1000;
+// Seconds taken by the chunks to fade in once loaded, and to fade out at the render distance before being unloaded
+pub const CHUNK_FADE_DURATION: f32 = 0.5;
+// Columns are generated this many chunks beyond the render distance in the direction the player is moving
+pub const CHUNK_PREGENERATION_DISTANCE: i32 = 3;
+// Below this horizontal speed, the player isn't considered to be moving anywhere
+pub const CHUNK_PREGENERATION_MIN_SPEED: f32 = 1.0;
+// Direction pointing towards the sun, doesn't need to be normalized
+pub const SUN_DIRECTION: (f32, f32, f32) = (0.4, 0.7, 0.3);
+pub const SUN_COLOR: (f32, f32, f32) = (1.0, 0.9, 0.7);
+pub const ENABLE_GOD_RAYS: bool = true;
+pub const GOD_RAYS_INTENSITY: f32 = 0.6;
+// A flat layer of clouds at CLOUD_HEIGHT, each texel of the cloud texture covering CLOUD_TEXEL_SIZE blocks.
+// It drifts along +x by CLOUD_SPEED blocks per game tick and is drawn up to CLOUD_RADIUS blocks around the player
+pub const CLOUD_HEIGHT: f32 = 192.0;
+pub const CLOUD_TEXEL_SIZE: f32 = 12.0;
+pub const CLOUD_SPEED: f32 = 0.002;
+pub const CLOUD_RADIUS: f32 = 384.0;
+// How much the clouds darken the terrain under them at noon
+pub const CLOUD_SHADOW_STRENGTH: f32 = 0.25;
+// Skips drawing the chunks hidden behind others, found with hardware occlusion queries
+pub const ENABLE_OCCLUSION_CULLING: bool = true;
+// Writes the depth of the chunks before shading them, so the voxel shader only runs for the visible fragments
+pub const ENABLE_DEPTH_PREPASS: bool = true;
+// The chunks are drawn in rings around the player, one multi-draw each: the close ring holds the chunks at most
+// CLOSE_RING_DISTANCE chunks away (Chebyshev distance), the medium ring those up to MEDIUM_RING_DISTANCE, the far ring the rest
+pub const CLOSE_RING_DISTANCE: i32 = 2;
+pub const MEDIUM_RING_DISTANCE: i32 = 5;
 lazy_static! {
     pub static ref WORLD_GENERATION_THREAD_POOL_SIZE: usize = {
         let cpus = num_cpus::get();
@@ -42,13 +140,124 @@ lazy_static! {
 pub const MOUSE_SENSITIVITY_X: f32 = 0.5;
 pub const MOUSE_SENSITIVITY_Y: f32 = 0.5;
 
+// Settings changed in the options screen are saved there, the values above are the defaults
+pub const SETTINGS_PATH: &str = "config/settings.toml";
+
+// Parameters of the world generation, reloaded when the file changes
+pub const WORLDGEN_CONFIG_PATH: &str = "config/worldgen.toml";
+// Every world gets a directory named after its seed in there
+pub const SAVES_DIRECTORY: &str = "saves";
+// The screenshot gallery of the pause menu shows the PNG files in there
+pub const SCREENSHOTS_DIRECTORY: &str = "screenshots";
+
 // Physics
 pub const PHYSICS_TICKRATE: f32 = 60.0;
+// The game can be slowed down or sped up with the numpad + and -, between these multiples of the normal speed
+pub const MIN_TIME_SCALE: f32 = 0.25;
+pub const MAX_TIME_SCALE: f32 = 4.0;
 pub const GRAVITY: f32 = -28.0;
 pub const MAX_VERTICAL_VELOCITY: f32 = 90.0;
+pub const PROJECTILE_GRAVITY: f32 = -12.0;
+// Seconds the right button must be held for a crossbow to charge, then the speed and damage of the arrows it shoots
+pub const CROSSBOW_CHARGE_DURATION: f32 = 1.5;
+pub const CROSSBOW_ARROW_SPEED: f32 = 40.0;
+pub const CROSSBOW_ARROW_DAMAGE: f32 = 9.0;
+// Degrees the crossbow in the hand is raised by while charging
+pub const CROSSBOW_CHARGED_ANGLE: f32 = 30.0;
+// Arrows stuck in a block disappear after this many seconds
+pub const ARROW_DESPAWN_TIME: f32 = 60.0;
+// Dropped items disappear after ITEM_DESPAWN_TIME seconds, the player picks up those closer than ITEM_PICKUP_RANGE
+pub const ITEM_DESPAWN_TIME: f32 = 300.0;
+pub const ITEM_PICKUP_RANGE: f32 = 1.5;
+// Dropped items bob up and down once every ITEM_BOB_PERIOD seconds
+pub const ITEM_BOB_PERIOD: f32 = 1.5;
+// Experience orbs fly towards the player closer than XP_ORB_ATTRACTION_RANGE, speeding up by XP_ORB_ATTRACTION every game tick.
+// They are worth at most XP_ORB_MAX_VALUE points each and disappear after XP_ORB_DESPAWN_TIME seconds
+pub const XP_ORB_ATTRACTION_RANGE: f32 = 8.0;
+pub const XP_ORB_ATTRACTION: f32 = 5.0;
+pub const XP_ORB_MAX_VALUE: u32 = 3;
+pub const XP_ORB_DESPAWN_TIME: f32 = 300.0;
+// Seconds the screen flashes for when the player reaches a new level, then the opacity of the flash at its start
+pub const LEVEL_UP_FLASH_DURATION: f32 = 0.5;
+pub const LEVEL_UP_FLASH_ALPHA: f32 = 0.3;
+// Blocks closer than this to an exploding TNT block are destroyed
+pub const EXPLOSION_RADIUS: f32 = 3.0;
+// Blocks with a blast resistance at least this high survive the explosions (obsidian, bedrock and liquids)
+pub const EXPLOSION_STRENGTH: f32 = 10.0;
+// Damage dealt by an explosion at its center, down to 0 at twice EXPLOSION_RADIUS
+pub const EXPLOSION_DAMAGE: f32 = 15.0;
+
+// Game logic (furnaces, ...) runs at a fixed number of ticks per second
+pub const TICKS_PER_SECOND: f32 = 20.0;
+// Blocks reacting to a change of their neighbours in a single game tick
+pub const BLOCK_UPDATES_PER_TICK: usize = 64;
+// Water blocks spreading in a single game tick, and how far water flows from its source
+pub const WATER_FLOW_STEPS_PER_TICK: usize = 32;
+pub const MAX_WATER_FLOW_LEVEL: u8 = 7;
+// Wires powered at once from a single change
+pub const MAX_REDSTONE_NETWORK_SIZE: usize = 4096;
+// At most one dungeon is generated in every DUNGEON_REGION_SIZE wide region, where the dungeon noise peaks
+// above DUNGEON_NOISE_THRESHOLD. Its center is between DUNGEON_MIN_Y and DUNGEON_MAX_Y
+pub const DUNGEON_REGION_SIZE: i32 = 200;
+pub const DUNGEON_NOISE_THRESHOLD: f64 = 0.6;
+pub const DUNGEON_MIN_Y: i32 = 10;
+pub const DUNGEON_MAX_Y: i32 = 40;
+// At most one village is generated in every VILLAGE_REGION_SIZE wide region, with this chance and only in the plains
+// and the deserts. Its houses are within VILLAGE_RADIUS blocks of its well
+pub const VILLAGE_REGION_SIZE: i32 = 400;
+pub const VILLAGE_CHANCE: f64 = 0.75;
+pub const VILLAGE_RADIUS: i32 = 28;
+// Blocks a piston can push at once, and game ticks taken by its arm to extend or retract
+pub const PISTON_PUSH_LIMIT: i32 = 12;
+pub const PISTON_MOVE_TICKS: u32 = 2;
+// Game ticks an observer powers the block behind it for, once it saw the block in front of it change
+pub const OBSERVER_PULSE_TICKS: u8 = 1;
+// A pressure plate springs back up PRESSURE_PLATE_RELEASE_TICKS game ticks after nothing stands on it anymore.
+// It is PRESSURE_PLATE_HEIGHT blocks high, half of that while pressed
+pub const PRESSURE_PLATE_RELEASE_TICKS: u32 = 5;
+pub const PRESSURE_PLATE_HEIGHT: f32 = 1.0 / 16.0;
+// A fence gate swings FENCE_GATE_OPEN_ANGLE degrees around its hinge in FENCE_GATE_SWING_TICKS game ticks
+pub const FENCE_GATE_OPEN_ANGLE: f32 = 90.0;
+pub const FENCE_GATE_SWING_TICKS: u32 = 5;
+// A firework rocket rises at FIREWORK_SPEED blocks per second and bursts after its flight time times
+// FIREWORK_TICKS_PER_FLIGHT_TIME game ticks, into FIREWORK_STARS stars falling for FIREWORK_STAR_LIFETIME seconds
+pub const FIREWORK_SPEED: f32 = 15.0;
+pub const FIREWORK_TICKS_PER_FLIGHT_TIME: u32 = 10;
+pub const FIREWORK_STARS: u8 = 32;
+pub const FIREWORK_STAR_LIFETIME: f32 = 1.0;
+// A campfire cooks CAMPFIRE_SLOTS items at once, each of them for CAMPFIRE_COOK_TIME game ticks.
+// Its flames cycle through their frames CAMPFIRE_FRAMES_PER_SECOND times a second and it puts out some smoke
+// CAMPFIRE_SMOKE_CHANCE of the game ticks, lit or not
+pub const CAMPFIRE_SLOTS: usize = 4;
+pub const CAMPFIRE_COOK_TIME: u32 = 30 * TICKS_PER_SECOND as u32;
+pub const CAMPFIRE_FRAMES_PER_SECOND: f32 = 8.0;
+pub const CAMPFIRE_SMOKE_CHANCE: f32 = 0.2;
+// Length of a day and night cycle, in seconds
+pub const DAY_DURATION: f32 = 1200.0;
+pub const TICKS_PER_DAY: u64 = 24000;
+// The sky light fades out after dusk and back in before dawn, over this fraction of the night.
+// In the dark, the world is drawn MIN_SKY_BRIGHTNESS times as bright as in the day
+pub const SKY_LIGHT_FADE: f32 = 0.1;
+pub const MIN_SKY_BRIGHTNESS: f32 = 0.1;
+
+// Difficulty, more hostile mobs are allowed as the world gets older
+pub const BASE_MAX_HOSTILE_MOBS: u32 = 70;
+pub const MAX_HOSTILE_MOBS_CAP: u32 = 120;
+
+// The enchanting table offers up to MAX_ENCHANTING_COST levels, ENCHANTING_LEVELS_PER_BOOKSHELF more for every bookshelf around it
+pub const MAX_ENCHANTING_COST: u32 = 30;
+pub const ENCHANTING_LEVELS_PER_BOOKSHELF: u32 = 2;
+// Every level of Knockback pushes the hit mobs KNOCKBACK_SPEED_PER_LEVEL blocks per second faster and makes them hop
+// up at KNOCKBACK_HOP_VELOCITY. The push loses KNOCKBACK_DAMPING of its speed every tick
+pub const KNOCKBACK_SPEED_PER_LEVEL: f32 = 8.0;
+pub const KNOCKBACK_DAMPING: f32 = 0.3;
+pub const KNOCKBACK_HOP_VELOCITY: f32 = 4.0;
+
+// Inventory
+pub const MAX_STACK_SIZE: u32 = 64;
 
 // Texture pack
-pub const ITEM_ARRAY_TEXTURE_LAYERS: u32 = 50;
+pub const ITEM_ARRAY_TEXTURE_LAYERS: u32 = 256;
 pub const BLOCK_TEXTURE_SIZE: u32 = 16;
 
 // Some values are taken from the minecraft gamepedia
@@ -56,6 +265,9 @@ pub const BLOCK_TEXTURE_SIZE: u32 = 16;
 pub const PLAYER_WIDTH: f32 = 0.6;
 pub const PLAYER_HEIGHT: f32 = 1.8;
 pub const PLAYER_EYES_HEIGHT: f32 = 1.62;
+// The hitbox and the eyes of the player are lower while sneaking
+pub const PLAYER_SNEAK_HEIGHT: f32 = 1.5;
+pub const PLAYER_SNEAK_EYES_HEIGHT: f32 = 1.27;
 pub const REACH_DISTANCE: f32 = 7.0;
 pub const JUMP_HEIGHT: f32 = 1.3;
 pub const HORIZONTAL_ACCELERATION: f32 = 30.0;
@@ -64,6 +276,12 @@ pub const SPRINTING_SPEED: f32 = 6.0;
 pub const SNEAKING_SPEED: f32 = 2.0;
 pub const FLYING_SPEED: f32 = 10.92;
 pub const FLYING_SPRINTING_SPEED: f32 = 50.0;
+// Spectators fly through the blocks
+pub const SPECTATOR_FLYING_SPEED: f32 = FLYING_SPEED * 2.0;
+// The scroll wheel multiplies the speed of the spectators by SPECTATOR_SPEED_STEP per notch, within these bounds
+pub const MIN_SPECTATOR_SPEED_MULTIPLIER: f32 = 0.5;
+pub const MAX_SPECTATOR_SPEED_MULTIPLIER: f32 = 10.0;
+pub const SPECTATOR_SPEED_STEP: f32 = 1.25;
 pub const ON_GROUND_FRICTION: f32 = 12.0;
 pub const IN_AIR_FRICTION: f32 = 2.0;
 
@@ -71,8 +289,182 @@ pub const IN_AIR_FRICTION: f32 = 2.0;
 // Calculation of the initial velocity in order to reach the jump height
 lazy_static! {
     pub static ref JUMP_IMPULSE: f32 = (JUMP_HEIGHT * 2.0 * -GRAVITY).sqrt();
+    // Speed of the player after falling from FALL_DAMAGE_HEIGHT blocks
+    pub static ref FALL_DAMAGE_MIN_VELOCITY: f32 = (FALL_DAMAGE_HEIGHT * 2.0 * -GRAVITY).sqrt();
     pub static ref FLYING_TRIGGER_INTERVAL: Duration = Duration::from_millis(250);
     pub static ref SPRINTING_TRIGGER_INTERVAL: Duration = Duration::from_millis(250);
+    // Time before the player can dash again, the FOV stays wider in the meantime
+    pub static ref DASH_COOLDOWN: Duration = Duration::from_millis(500);
 }
+// A dash pushes the player forward, faster than sprinting
+pub const DASH_SPEED_MULTIPLIER: f32 = 1.8;
+pub const DASH_DUST_PARTICLES: u8 = 24;
 pub const PLAYER_HALF_WIDTH: f32 = PLAYER_WIDTH / 2.0;
+pub const LADDER_CLIMBING_SPEED: f32 = 2.35;
+// Depth of the part of a ladder the player grabs, against its wall
+pub const LADDER_THICKNESS: f32 = 3.0 / 16.0;
+// Fraction of the jump impulse given when climbing over the top of a ladder
+pub const LADDER_TOP_IMPULSE: f32 = 0.3;
+// Seconds the player has to stand in a portal before being teleported, the portals do nothing for
+// PORTAL_COOLDOWN seconds after arriving
+pub const PORTAL_TRAVEL_TIME: f32 = 4.0;
+pub const PORTAL_COOLDOWN: f32 = 4.0;
+// Size of the inside of the obsidian frames that can be lit into a portal
+pub const PORTAL_MIN_WIDTH: i32 = 2;
+pub const PORTAL_MIN_HEIGHT: i32 = 3;
+pub const PORTAL_MAX_SIZE: i32 = 21;
 pub const PLAYER_HALF_HEIGHT: f32 = PLAYER_HEIGHT / 2.0;
+
+// Health and hunger, both counted in half icons
+pub const MAX_HEALTH: f32 = 20.0;
+pub const MAX_FOOD_LEVEL: f32 = 20.0;
+// Food points lost per second, the saturation is consumed first
+pub const FOOD_DEPLETION_RATE: f32 = 0.02;
+pub const SPRINTING_FOOD_DEPLETION_MULTIPLIER: f32 = 5.0;
+// Above this food level, the player regenerates REGENERATION_RATE health points per second
+pub const REGENERATION_FOOD_LEVEL: f32 = 18.0;
+pub const REGENERATION_RATE: f32 = 0.5;
+// Health points lost per second when the food level is 0
+pub const STARVATION_DAMAGE_RATE: f32 = 1.0;
+// The player is too hungry to sprint at this food level or below
+pub const SPRINTING_MIN_FOOD_LEVEL: f32 = 6.0;
+
+// Air of the player, counted in game ticks spent under water
+pub const MAX_AIR_SUPPLY: f32 = 300.0;
+// Air recovered per tick once the head of the player is out of the water
+pub const AIR_REFILL_RATE: f32 = 4.0;
+// Air shown by a single bubble of the air bar
+pub const AIR_PER_BUBBLE: f32 = 30.0;
+// Without air, the player loses DROWNING_DAMAGE health points every DROWNING_DAMAGE_INTERVAL ticks
+pub const DROWNING_DAMAGE: f32 = 1.0;
+pub const DROWNING_DAMAGE_INTERVAL: u32 = 20;
+// Lava burns the player by LAVA_DAMAGE_PER_SECOND health points, who keeps burning by FIRE_DAMAGE_PER_SECOND
+// for FIRE_DURATION_TICKS game ticks after leaving it
+pub const LAVA_DAMAGE_PER_SECOND: f32 = 4.0;
+pub const FIRE_DAMAGE_PER_SECOND: f32 = 1.0;
+pub const FIRE_DURATION_TICKS: u32 = 160;
+// Landing from higher than FALL_DAMAGE_HEIGHT blocks hurts the player by FALL_DAMAGE_PER_VELOCITY health points
+// per unit of speed above the speed reached by such a fall. Each level of Feather Falling on the boots takes 12% off
+pub const FALL_DAMAGE_HEIGHT: f32 = 3.0;
+pub const FALL_DAMAGE_PER_VELOCITY: f32 = 1.5;
+pub const FEATHER_FALLING_REDUCTION_PER_LEVEL: f32 = 0.12;
+// The camera shakes by up to CAMERA_SHAKE_AMPLITUDE blocks for CAMERA_SHAKE_DURATION seconds after a fall that hurt
+pub const CAMERA_SHAKE_AMPLITUDE: f32 = 0.1;
+pub const CAMERA_SHAKE_DURATION: f32 = 0.3;
+// Landing on a slime block faster than SLIME_BOUNCE_MIN_VELOCITY sends the player and the mobs back up
+// with SLIME_BOUNCE_RESTITUTION of their speed, the slower landings don't bounce so they can walk on it
+pub const SLIME_BOUNCE_RESTITUTION: f32 = 0.9;
+pub const SLIME_BOUNCE_MIN_VELOCITY: f32 = 2.0;
+// Mobs closer than MELEE_REACH_DISTANCE can be hit with the left mouse button, the damage depends on the item in hand
+pub const MELEE_REACH_DISTANCE: f32 = 3.0;
+pub const HAND_DAMAGE: f32 = 1.0;
+pub const AXE_DAMAGE: f32 = 3.0;
+pub const SWORD_DAMAGE: f32 = 4.0;
+// Blocks broken without a tool able to harvest them take this many times longer to break, and drop nothing
+pub const UNHARVESTABLE_BREAK_TIME_MULTIPLIER: f32 = 5.0;
+// Every game tick, LAVA_DRIP_SAMPLES random blocks closer than LAVA_DRIP_RADIUS to the player may drip lava
+pub const LAVA_DRIP_SAMPLES: u32 = 300;
+pub const LAVA_DRIP_RADIUS: i32 = 16;
+
+// Where the player respawns without a bed
+pub const WORLD_SPAWN_POINT: (f32, f32, f32) = (0.0, 200.0, 0.0);
+// Seconds taken by the screen to fade to black when sleeping, then to fade back after waking up
+pub const SLEEP_FADE_DURATION: f32 = 3.0;
+pub const WAKE_UP_DURATION: f32 = 1.0;
+// Sleeping is impossible with a hostile mob closer than this
+pub const SLEEP_MONSTER_DISTANCE: f32 = 8.0;
+
+// Average seconds of clear weather before it rains, and of rain before it clears up
+pub const CLEAR_WEATHER_DURATION: f32 = 600.0;
+pub const RAIN_DURATION: f32 = 300.0;
+// Chance for the rain to turn into a thunderstorm instead of clearing up
+pub const THUNDER_CHANCE: f64 = 0.2;
+// Seconds taken by the sky to darken to RAIN_SKY_BRIGHTNESS and the fog to thicken by RAIN_FOG_DENSITY_INCREASE
+pub const RAIN_FADE_DURATION: f32 = 5.0;
+pub const RAIN_SKY_BRIGHTNESS: f32 = 0.6;
+pub const RAIN_FOG_DENSITY_INCREASE: f32 = 0.3;
+// Every game tick, RAIN_DROPS_PER_TICK drops fall from RAIN_HEIGHT blocks above the camera,
+// somewhere in a column of RAIN_COLUMN_SIZE by RAIN_COLUMN_SIZE blocks centered on it
+pub const RAIN_DROPS_PER_TICK: u32 = 40;
+pub const RAIN_HEIGHT: f32 = 24.0;
+pub const RAIN_COLUMN_SIZE: i32 = 48;
+pub const RAIN_DROP_SPEED: f32 = 20.0;
+// Seconds between two lightning strikes, then seconds of the white flash of each strike
+pub const THUNDER_FLASH_INTERVAL: (f32, f32) = (1.0, 5.0);
+pub const THUNDER_FLASH_DURATION: f32 = 0.1;
+// Lightning strikes closer than this to the player
+pub const LIGHTNING_RADIUS: i32 = 32;
+
+// Beacons check their pyramid and give their effect every BEACON_UPDATE_TICKS game ticks,
+// to the players closer than BEACON_BASE_RANGE + BEACON_RANGE_PER_LEVEL * the number of layers of the pyramid
+pub const BEACON_UPDATE_TICKS: u32 = 80;
+pub const BEACON_MAX_LEVELS: u8 = 4;
+pub const BEACON_BASE_RANGE: f32 = 16.0;
+pub const BEACON_RANGE_PER_LEVEL: f32 = 16.0;
+// The effect lasts BEACON_EFFECT_BASE_DURATION + BEACON_EFFECT_DURATION_PER_LEVEL * the number of layers, in seconds
+pub const BEACON_EFFECT_BASE_DURATION: f32 = 9.0;
+pub const BEACON_EFFECT_DURATION_PER_LEVEL: f32 = 2.0;
+// The beam of a beacon cycles through the colors in this many seconds, and is this many blocks wide at the top of the world
+pub const BEACON_BEAM_COLOR_PERIOD: f32 = 30.0;
+pub const BEACON_BEAM_TOP_WIDTH: f32 = 2.0;
+
+// Bonus of each level of the status effects
+pub const SPEED_BONUS_PER_LEVEL: f32 = 0.2;
+pub const HASTE_BONUS_PER_LEVEL: f32 = 0.2;
+pub const RESISTANCE_PER_LEVEL: f32 = 0.2;
+pub const JUMP_BOOST_PER_LEVEL: f32 = 2.0;
+pub const STRENGTH_DAMAGE_PER_LEVEL: f32 = 3.0;
+
+// Mobs
+pub const COW_WALKING_SPEED: f32 = 1.5;
+pub const ZOMBIE_WALKING_SPEED: f32 = 2.3;
+// Zombies walk towards the player when they are closer than this
+pub const ZOMBIE_FOLLOW_RANGE: f32 = 24.0;
+// The mobs burning in the sunlight lose this many health points per second under the open sky during the day
+pub const SUNLIGHT_BURN_DAMAGE_PER_SECOND: f32 = 1.0;
+// Hostile mobs hit the player closer than MOB_ATTACK_RANGE blocks, at most once every MOB_ATTACK_COOLDOWN_TICKS game ticks
+pub const MOB_ATTACK_RANGE: f32 = 1.5;
+pub const MOB_ATTACK_COOLDOWN_TICKS: u32 = 20;
+pub const BLAZE_WALKING_SPEED: f32 = 2.0;
+pub const BLAZE_FOLLOW_RANGE: f32 = 16.0;
+pub const VILLAGER_WALKING_SPEED: f32 = 1.2;
+pub const IRON_GOLEM_WALKING_SPEED: f32 = 0.8;
+// The path of a mob is computed again every PATH_RECOMPUTE_TICKS game ticks. The search gives up after visiting
+// PATHFINDING_MAX_NODES blocks, the mob then wanders WANDER_PATH_STEPS random steps
+pub const PATH_RECOMPUTE_TICKS: u32 = 40;
+pub const PATHFINDING_MAX_NODES: usize = 200;
+pub const WANDER_PATH_STEPS: usize = 4;
+// Blocks whose walkability is remembered for the pathfinding, the cache starts over beyond that
+pub const NAV_CACHE_MAX_SIZE: usize = 65536;
+
+// Mob spawning, every MOB_SPAWN_INTERVAL_TICKS game ticks the mobs with fewer than their cap within MOB_CAP_RADIUS
+// blocks of the player are spawned between MOB_SPAWN_MIN_DISTANCE and MOB_SPAWN_MAX_DISTANCE blocks from them
+pub const MOB_SPAWN_INTERVAL_TICKS: u32 = 400;
+pub const MOB_SPAWN_MIN_DISTANCE: f32 = 24.0;
+pub const MOB_SPAWN_MAX_DISTANCE: f32 = 32.0;
+pub const MOB_SPAWN_ATTEMPTS: u32 = 16;
+pub const MOB_CAP_RADIUS: f32 = 256.0;
+pub const COW_SPAWN_CAP: u32 = 10;
+// Hostile mobs only spawn where the light is below this level
+pub const HOSTILE_SPAWN_MAX_LIGHT: u8 = 8;
+// Mobs further than this from every player disappear
+pub const MOB_DESPAWN_DISTANCE: f32 = 96.0;
+
+// Sounds, their volume is divided by 1 + SOUND_ATTENUATION * (distance to the player)²
+pub const SOUND_ATTENUATION: f32 = 0.01;
+pub const BLOCK_PLACE_SOUND: &str = "sounds/block_place.wav";
+pub const BLOCK_BREAK_SOUND: &str = "sounds/block_break.wav";
+pub const FOOTSTEP_SOUND: &str = "sounds/footstep.wav";
+pub const FOOTSTEP_GRASS_SOUND: &str = "sounds/footstep_grass.wav";
+pub const FOOTSTEP_STONE_SOUND: &str = "sounds/footstep_stone.wav";
+pub const FOOTSTEP_WOOD_SOUND: &str = "sounds/footstep_wood.wav";
+pub const SPLASH_SOUND: &str = "sounds/splash.wav";
+pub const BLOCK_SOUND_VOLUME: f32 = 0.8;
+pub const FOOTSTEP_VOLUME: f32 = 0.3;
+pub const SPLASH_VOLUME: f32 = 0.4;
+
+// Head bob, the head goes down and a foot hits the ground every HEAD_BOB_STRIDE blocks walked
+pub const HEAD_BOB_STRIDE: f32 = 0.9;
+pub const HEAD_BOB_HEIGHT: f32 = 0.05;
+// How fast the head bob fades in and out when the player starts and stops walking, per second
+pub const HEAD_BOB_FADE_SPEED: f32 = 6.0;
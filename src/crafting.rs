@@ -0,0 +1,135 @@
+use crate::chunk::BlockID;
+use crate::inventory::item::ItemStack;
+
+pub const CRAFTING_GRID_SIZE: usize = 3;
+
+/// The items put in a crafting grid, by row then column
+pub type CraftingGrid = [[Option<ItemStack>; CRAFTING_GRID_SIZE]; CRAFTING_GRID_SIZE];
+
+/// How the ingredients of a recipe must be laid out in the grid
+pub enum RecipeShape {
+    /// Rows of the pattern, from the top one, None where the grid must be empty.
+    /// The pattern can be put anywhere in the grid as long as the rest of the grid is empty
+    Shaped(Vec<Vec<Option<BlockID>>>),
+    /// One item per slot, in any slot
+    Shapeless(Vec<BlockID>),
+}
+
+/// A recipe crafting `amount` items of `result` from its ingredients
+pub struct Recipe {
+    pub shape: RecipeShape,
+    pub result: BlockID,
+    pub amount: u32,
+}
+
+impl Recipe {
+    /// Builds a recipe from rows of characters, each key character standing for an item and spaces for empty slots
+    pub fn shaped(rows: &[&str], keys: &[(char, BlockID)], result: BlockID, amount: u32) -> Self {
+        let pattern = rows.iter()
+            .map(|row| row.chars()
+                .map(|c| keys.iter().find(|&&(key, _)| key == c).map(|&(_, item)| item))
+                .collect())
+            .collect();
+        Recipe { shape: RecipeShape::Shaped(pattern), result, amount }
+    }
+
+    pub fn shapeless(ingredients: &[BlockID], result: BlockID, amount: u32) -> Self {
+        let mut ingredients = ingredients.to_vec();
+        ingredients.sort_by_key(|&item| item as u32);
+        Recipe { shape: RecipeShape::Shapeless(ingredients), result, amount }
+    }
+
+    /// `items` are the items of the grid, by row then column
+    fn matches(&self, items: &[[Option<BlockID>; CRAFTING_GRID_SIZE]; CRAFTING_GRID_SIZE]) -> bool {
+        match &self.shape {
+            RecipeShape::Shaped(pattern) => {
+                let height = pattern.len();
+                let width = pattern.iter().map(Vec::len).max().unwrap_or(0);
+                if height > CRAFTING_GRID_SIZE || width > CRAFTING_GRID_SIZE {
+                    return false;
+                }
+                // Every position of the pattern in the grid
+                (0..=CRAFTING_GRID_SIZE - height).any(|top| (0..=CRAFTING_GRID_SIZE - width).any(|left| {
+                    (0..CRAFTING_GRID_SIZE).all(|row| (0..CRAFTING_GRID_SIZE).all(|column| {
+                        let expected = row.checked_sub(top)
+                            .zip(column.checked_sub(left))
+                            .and_then(|(row, column)| pattern.get(row)?.get(column).cloned())
+                            .flatten();
+                        items[row][column] == expected
+                    }))
+                }))
+            }
+            RecipeShape::Shapeless(ingredients) => {
+                let mut grid_items: Vec<BlockID> = items.iter().flat_map(|row| row.iter()).filter_map(|&item| item).collect();
+                grid_items.sort_by_key(|&item| item as u32);
+                &grid_items == ingredients
+            }
+        }
+    }
+}
+
+/// Every recipe known to the crafting tables
+pub struct RecipeRegistry {
+    recipes: Vec<Recipe>,
+}
+
+impl Default for RecipeRegistry {
+    fn default() -> Self {
+        RecipeRegistry {
+            recipes: vec![
+                Recipe::shapeless(&[BlockID::OakLog], BlockID::OakPlanks, 4),
+                // There are only oak planks
+                Recipe::shapeless(&[BlockID::BirchLog], BlockID::OakPlanks, 4),
+                Recipe::shapeless(&[BlockID::SpruceLog], BlockID::OakPlanks, 4),
+                Recipe::shapeless(&[BlockID::JungleLog], BlockID::OakPlanks, 4),
+                Recipe::shaped(&["P", "P"], &[('P', BlockID::OakPlanks)], BlockID::Stick, 4),
+                Recipe::shaped(&["MMM", " S ", " S "], &[('M', BlockID::OakPlanks), ('S', BlockID::Stick)], BlockID::WoodenPickaxe, 1),
+                Recipe::shaped(&["MM", "MS", " S"], &[('M', BlockID::OakPlanks), ('S', BlockID::Stick)], BlockID::WoodenAxe, 1),
+                Recipe::shaped(&["M", "S", "S"], &[('M', BlockID::OakPlanks), ('S', BlockID::Stick)], BlockID::WoodenShovel, 1),
+                Recipe::shaped(&["M", "M", "S"], &[('M', BlockID::OakPlanks), ('S', BlockID::Stick)], BlockID::WoodenSword, 1),
+                Recipe::shaped(&["MMM", " S ", " S "], &[('M', BlockID::Cobblestone), ('S', BlockID::Stick)], BlockID::StonePickaxe, 1),
+                Recipe::shaped(&["MM", "MS", " S"], &[('M', BlockID::Cobblestone), ('S', BlockID::Stick)], BlockID::StoneAxe, 1),
+                Recipe::shaped(&["M", "S", "S"], &[('M', BlockID::Cobblestone), ('S', BlockID::Stick)], BlockID::StoneShovel, 1),
+                Recipe::shaped(&["M", "M", "S"], &[('M', BlockID::Cobblestone), ('S', BlockID::Stick)], BlockID::StoneSword, 1),
+                Recipe::shaped(&["MMM", " S ", " S "], &[('M', BlockID::IronIngot), ('S', BlockID::Stick)], BlockID::IronPickaxe, 1),
+                Recipe::shaped(&["MM", "MS", " S"], &[('M', BlockID::IronIngot), ('S', BlockID::Stick)], BlockID::IronAxe, 1),
+                Recipe::shaped(&["M", "S", "S"], &[('M', BlockID::IronIngot), ('S', BlockID::Stick)], BlockID::IronShovel, 1),
+                Recipe::shaped(&["MMM", " S ", " S "], &[('M', BlockID::Diamond), ('S', BlockID::Stick)], BlockID::DiamondPickaxe, 1),
+                Recipe::shaped(&["MM", "MS", " S"], &[('M', BlockID::Diamond), ('S', BlockID::Stick)], BlockID::DiamondAxe, 1),
+                Recipe::shaped(&["M", "S", "S"], &[('M', BlockID::Diamond), ('S', BlockID::Stick)], BlockID::DiamondShovel, 1),
+                Recipe::shaped(&["MMM", "MMM", "MMM"], &[('M', BlockID::IronIngot)], BlockID::IronBlock, 1),
+                Recipe::shaped(&["MMM", "MMM", "MMM"], &[('M', BlockID::Diamond)], BlockID::DiamondBlock, 1),
+                Recipe::shaped(&[" I ", "IRI", " I "], &[('I', BlockID::IronIngot), ('R', BlockID::RedstoneWire)], BlockID::Compass, 1),
+                Recipe::shaped(&["SSS", "SCS", "SSS"], &[('S', BlockID::Stick), ('C', BlockID::Compass)], BlockID::Map, 1),
+                Recipe::shaped(&["CCC", "RRR", "CCC"], &[('C', BlockID::Cobblestone), ('R', BlockID::RedstoneWire)], BlockID::Observer, 1),
+                // There is no paper nor gunpowder, redstone makes the charge
+                Recipe::shapeless(&[BlockID::RedstoneWire, BlockID::Stick], BlockID::FireworkRocket, 3),
+                Recipe::shaped(&["PP", "PP"], &[('P', BlockID::OakPlanks)], BlockID::CraftingTable, 1),
+                Recipe::shaped(&["PPP", "P P", "PPP"], &[('P', BlockID::OakPlanks)], BlockID::Chest, 1),
+                Recipe::shaped(&["CCC", "C C", "CCC"], &[('C', BlockID::Cobblestone)], BlockID::Furnace, 1),
+                // There is no coal, the logs burn on their own
+                Recipe::shaped(&[" S ", "SLS", "LLL"], &[('S', BlockID::Stick), ('L', BlockID::OakLog)], BlockID::Campfire, 1),
+                Recipe::shaped(&["SS", "SS"], &[('S', BlockID::Stone)], BlockID::StoneBrick, 4),
+                Recipe::shaped(&["SS", "SS"], &[('S', BlockID::Granite)], BlockID::PolishedGranite, 4),
+                Recipe::shaped(&["SS", "SS"], &[('S', BlockID::Diorite)], BlockID::PolishedDiorite, 4),
+                Recipe::shaped(&["SS", "SS"], &[('S', BlockID::Andesite)], BlockID::PolishedAndesite, 4),
+            ],
+        }
+    }
+}
+
+impl RecipeRegistry {
+    /// The recipe crafted by the items of the grid, if any
+    pub fn match_grid(&self, grid: &CraftingGrid) -> Option<&Recipe> {
+        if grid.iter().all(|row| row.iter().all(Option::is_none)) {
+            return None;
+        }
+        let mut items = [[None; CRAFTING_GRID_SIZE]; CRAFTING_GRID_SIZE];
+        for (row, grid_row) in grid.iter().enumerate() {
+            for (column, slot) in grid_row.iter().enumerate() {
+                items[row][column] = slot.as_ref().map(|item_stack| item_stack.item);
+            }
+        }
+        self.recipes.iter().find(|recipe| recipe.matches(&items))
+    }
+}
@@ -0,0 +1,205 @@
+use std::any::Any;
+use std::ffi::c_void;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::{Mat4, vec3};
+
+use crate::block_entity::{BlockEntity, serialize_slot};
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{GUI_SCALING, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::crafting::{CRAFTING_GRID_SIZE, CraftingGrid, RecipeRegistry};
+use crate::inventory::item::ItemStack;
+use crate::shader_compilation::ShaderProgram;
+use crate::shapes::quad;
+use crate::types::TexturePack;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CraftingTableSlot {
+    /// Row then column, from the top left corner
+    Grid(usize, usize),
+    Output,
+}
+
+/// The grid stays in the table when the screen is closed, so that it is found again when reopened
+#[derive(Default)]
+pub struct CraftingTableBlockEntity {
+    pub grid: CraftingGrid,
+    /// Preview of what the grid crafts, refreshed by `update_output` after every change of the grid
+    pub output: Option<ItemStack>,
+}
+
+impl CraftingTableBlockEntity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update_output(&mut self, recipe_registry: &RecipeRegistry) {
+        let recipe = recipe_registry.match_grid(&self.grid);
+        let is_up_to_date = match (recipe, &self.output) {
+            (Some(recipe), Some(output)) => output.item == recipe.result && output.amount == recipe.amount,
+            (None, None) => true,
+            _ => false,
+        };
+        if !is_up_to_date {
+            self.output = recipe.map(|recipe| ItemStack::new(recipe.amount, recipe.result));
+        }
+    }
+
+    /// Uses up one item of every filled slot of the grid and gives what they crafted, if anything
+    pub fn craft(&mut self, recipe_registry: &RecipeRegistry) -> Option<ItemStack> {
+        let output = self.output.take()?;
+        for slot in self.grid.iter_mut().flat_map(|row| row.iter_mut()) {
+            if let Some(item_stack) = slot {
+                if item_stack.amount > 1 {
+                    item_stack.amount -= 1;
+                } else {
+                    *slot = None;
+                }
+            }
+        }
+        self.update_output(recipe_registry);
+        Some(output)
+    }
+
+    pub fn update_dirty_items(&mut self, texture_pack: &TexturePack) {
+        for item_stack in self.grid.iter_mut().flat_map(|row| row.iter_mut()).chain(Some(&mut self.output)) {
+            if let Some(item_stack) = item_stack {
+                item_stack.update_if_dirty(&texture_pack);
+            }
+        }
+    }
+}
+
+impl BlockEntity for CraftingTableBlockEntity {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {}
+
+    /// The slots of the grid row by row, the output is crafted again from them
+    fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for slot in self.grid.iter().flat_map(|row| row.iter()) {
+            serialize_slot(slot, &mut bytes);
+        }
+        bytes
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Draws the crafting table screen above the hotbar, with the slots and the arrow of the furnace texture.
+/// The arrow is filled while the grid crafts something
+pub struct CraftingTableUI {
+    vao: u32,
+    vbo: u32,
+}
+
+impl CraftingTableUI {
+    pub fn new() -> Self {
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        // Position
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+        // Texture coords
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 1, 2 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+        let mut vbo = 0;
+        gl_call!(gl::CreateBuffers(1, &mut vbo));
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (5 * std::mem::size_of::<f32>()) as i32));
+        gl_call!(gl::NamedBufferData(vbo,
+                    (30 * std::mem::size_of::<f32>() as usize) as isize,
+                    std::ptr::null(),
+                    gl::DYNAMIC_DRAW));
+
+        CraftingTableUI { vao, vbo }
+    }
+
+    fn slots() -> impl Iterator<Item=CraftingTableSlot> {
+        (0..CRAFTING_GRID_SIZE * CRAFTING_GRID_SIZE)
+            .map(|i| CraftingTableSlot::Grid(i / CRAFTING_GRID_SIZE, i % CRAFTING_GRID_SIZE))
+            .chain(Some(CraftingTableSlot::Output))
+    }
+
+    /// Center of a slot in screen coordinates
+    pub fn slot_position(slot: CraftingTableSlot) -> (f32, f32) {
+        let center_x = WINDOW_WIDTH as f32 / 2.0;
+        match slot {
+            CraftingTableSlot::Grid(row, column) => (
+                center_x + (-58.0 + 18.0 * column as f32) * GUI_SCALING,
+                (70.0 + 18.0 * (CRAFTING_GRID_SIZE - 1 - row) as f32) * GUI_SCALING,
+            ),
+            CraftingTableSlot::Output => (center_x + 36.0 * GUI_SCALING, 88.0 * GUI_SCALING),
+        }
+    }
+
+    /// Returns the slot under the given point in screen coordinates (y pointing up)
+    pub fn slot_at(x: f32, y: f32) -> Option<CraftingTableSlot> {
+        let half_slot_size = 9.0 * GUI_SCALING;
+        CraftingTableUI::slots().find(|&slot| {
+            let (slot_x, slot_y) = CraftingTableUI::slot_position(slot);
+            (x - slot_x).abs() <= half_slot_size && (y - slot_y).abs() <= half_slot_size
+        })
+    }
+
+    /// Draws a part of the furnace texture.
+    /// (x, y) is the bottom left corner on the screen, uv is (left, top, width, height) in pixels
+    fn draw_sprite(&self, x: f32, y: f32, (u, v, width, height): (f32, f32, f32, f32), shader: &mut ShaderProgram) {
+        let texture_size = 64.0;
+        let vbo_data = quad((u / texture_size, v / texture_size, (u + width) / texture_size, (v + height) / texture_size));
+        gl_call!(gl::NamedBufferSubData(self.vbo,
+                    0,
+                    (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                    vbo_data.as_ptr() as *mut c_void));
+
+        let model_matrix = {
+            let translate_matrix = Matrix4::new_translation(&vec3(
+                x + width * GUI_SCALING / 2.0, y + height * GUI_SCALING / 2.0, 0.0));
+            let scale_matrix: Mat4 = Matrix4::new_nonuniform_scaling(&vec3(width * GUI_SCALING, height * GUI_SCALING, 1.0));
+            translate_matrix * scale_matrix
+        };
+        shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+        gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 6));
+    }
+
+    pub fn draw(&self, crafting_table: &CraftingTableBlockEntity, shader: &mut ShaderProgram) {
+        let projection_matrix = nalgebra_glm::ortho(
+            0.0, WINDOW_WIDTH as f32, 0.0, WINDOW_HEIGHT as f32, -5.0, 5.0);
+
+        shader.use_program();
+        shader.set_uniform_matrix4fv("projection", projection_matrix.as_ptr());
+        shader.set_uniform1i("tex", 5);
+        gl_call!(gl::BindVertexArray(self.vao));
+
+        for slot in CraftingTableUI::slots() {
+            let (x, y) = CraftingTableUI::slot_position(slot);
+            self.draw_sprite(x - 9.0 * GUI_SCALING, y - 9.0 * GUI_SCALING, (0.0, 0.0, 18.0, 18.0), shader);
+        }
+
+        let (arrow_x, arrow_y) = (WINDOW_WIDTH as f32 / 2.0 - 4.0 * GUI_SCALING, 80.0 * GUI_SCALING);
+        let arrow_u = if crafting_table.output.is_some() { 24.0 } else { 0.0 };
+        self.draw_sprite(arrow_x, arrow_y, (arrow_u, 32.0, 24.0, 17.0), shader);
+    }
+
+    pub fn draw_items(&self, crafting_table: &CraftingTableBlockEntity, shader: &mut ShaderProgram) {
+        for slot in CraftingTableUI::slots() {
+            let item_stack = match slot {
+                CraftingTableSlot::Grid(row, column) => &crafting_table.grid[row][column],
+                CraftingTableSlot::Output => &crafting_table.output,
+            };
+            if let Some(item_stack) = item_stack {
+                let (x, y) = CraftingTableUI::slot_position(slot);
+                item_stack.item_render.draw(x, y, shader);
+            }
+        }
+    }
+}
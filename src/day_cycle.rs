@@ -0,0 +1,56 @@
+use crate::constants::{DAY_DURATION, MIN_SKY_BRIGHTNESS, SKY_LIGHT_FADE};
+
+/// `sky_time` at which the sun rises
+pub const DAWN: f32 = 0.0;
+/// `sky_time` at which the sun sets, the night lasts until the end of the cycle
+pub const DUSK: f32 = 0.5;
+
+/// Time of the day, `sky_time` goes from 0 to 1 over a whole day and night cycle
+pub struct DayCycle {
+    pub sky_time: f32,
+}
+
+impl Default for DayCycle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DayCycle {
+    pub fn new() -> Self {
+        // Start in the morning
+        DayCycle { sky_time: 0.05 }
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.sky_time = (self.sky_time + dt / DAY_DURATION).fract();
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.sky_time > DUSK
+    }
+
+    /// How much of the sunlight reaches the ground, from 1 during the day to 0 in the middle of the night
+    pub fn daylight(&self) -> f32 {
+        if !self.is_night() {
+            return 1.0;
+        }
+        let night_progress = (self.sky_time - DUSK) / (1.0 - DUSK);
+        let darkness = night_progress.min(1.0 - night_progress) / SKY_LIGHT_FADE;
+        1.0 - darkness.min(1.0)
+    }
+
+    /// Light level of the blocks under the open sky, 15 during the day and down to 0 at night
+    pub fn sky_light(&self) -> u8 {
+        (self.daylight() * 15.0).round() as u8
+    }
+
+    /// Multiplies the lighting of the world, from MIN_SKY_BRIGHTNESS at night to 1 during the day
+    pub fn sky_brightness(&self) -> f32 {
+        MIN_SKY_BRIGHTNESS + (1.0 - MIN_SKY_BRIGHTNESS) * self.daylight()
+    }
+
+    pub fn skip_to_dawn(&mut self) {
+        self.sky_time = DAWN;
+    }
+}
@@ -0,0 +1,63 @@
+use nalgebra_glm::Vec3;
+
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{ENABLE_DEPTH_PREPASS, GUI_SCALING, WINDOW_HEIGHT};
+use crate::difficulty::{Difficulty, WorldAge, difficulty_factor};
+use crate::player::PlayerState;
+use crate::shader_compilation::ShaderProgram;
+use crate::text::TextRenderer;
+
+/// Height of a line of text, in GUI pixels
+const LINE_HEIGHT: f32 = 10.0;
+
+/// Debugging information drawn in the top left corner, toggled with F3 and always shown to the spectators
+#[derive(Default)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    /// Frames drawn during the last second
+    pub fps: u64,
+    /// Milliseconds the GPU took to draw the chunks, measured with timer queries
+    pub chunk_render_time: Option<f32>,
+}
+
+/// Shows how hard the world got with its age
+pub struct DifficultyDisplay;
+
+impl DifficultyDisplay {
+    pub fn text(world_age: &WorldAge) -> String {
+        let factor = difficulty_factor(world_age.ticks);
+        format!("Difficulty: {} ({:.2})", Difficulty::from_factor(factor), factor)
+    }
+}
+
+impl DebugOverlay {
+    pub fn draw(&self, world_age: &WorldAge, player_state: &PlayerState, position: &Vec3, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+        if !self.visible && !player_state.is_spectator() {
+            return;
+        }
+
+        let chunk_render_time = self.chunk_render_time.map_or("-".to_string(), |time| format!("{:.2} ms", time));
+        let (c_x, c_y, c_z, b_x, b_y, b_z) = ChunkManager::get_chunk_coords(
+            position.x.floor() as i32, position.y.floor() as i32, position.z.floor() as i32);
+        let mut game_mode = format!("Game mode: {:?}", player_state.game_mode);
+        if player_state.is_spectator() {
+            game_mode += &format!(" (speed x{:.1})", player_state.spectator_speed_multiplier);
+        }
+        let lines = [
+            format!("{} fps", self.fps),
+            format!("XYZ: {:.2} / {:.2} / {:.2}", position.x, position.y, position.z),
+            format!("Chunk: {} {} {} (block {} {} {})", c_x, c_y, c_z, b_x, b_y, b_z),
+            format!("Day {}", world_age.days() + 1),
+            DifficultyDisplay::text(world_age),
+            game_mode,
+            format!("Chunks: {} ({})", chunk_render_time, if ENABLE_DEPTH_PREPASS { "depth prepass" } else { "no prepass" }),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            let x = 2.0 * GUI_SCALING;
+            let y = WINDOW_HEIGHT as f32 - (i + 1) as f32 * LINE_HEIGHT * GUI_SCALING;
+            text_renderer.draw_rect(x - GUI_SCALING, y - GUI_SCALING, TextRenderer::text_width(line, GUI_SCALING) + 2.0 * GUI_SCALING,
+                                    LINE_HEIGHT * GUI_SCALING, (0.3, 0.3, 0.3, 0.5), shader);
+            text_renderer.draw(line, x, y, GUI_SCALING, (0.9, 0.9, 0.9, 1.0), shader);
+        }
+    }
+}
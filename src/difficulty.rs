@@ -0,0 +1,111 @@
+use std::fmt;
+
+use crate::constants::{BASE_MAX_HOSTILE_MOBS, MAX_HOSTILE_MOBS_CAP, TICKS_PER_DAY};
+
+/// Number of game ticks since the world was created
+#[derive(Default)]
+pub struct WorldAge {
+    pub ticks: u64,
+}
+
+impl WorldAge {
+    pub fn days(&self) -> u64 {
+        self.ticks / TICKS_PER_DAY
+    }
+}
+
+/// More hostile mobs can be around after the third night
+pub fn max_hostile_mobs(world_age: u64) -> u32 {
+    let days = world_age / TICKS_PER_DAY;
+    if days <= 3 {
+        return BASE_MAX_HOSTILE_MOBS;
+    }
+    (BASE_MAX_HOSTILE_MOBS + (days - 3) as u32 * 5).min(MAX_HOSTILE_MOBS_CAP)
+}
+
+/// Mobs hit 5% harder for every day past the fifth one
+pub fn attack_damage_multiplier(world_age: u64) -> f32 {
+    let days = world_age / TICKS_PER_DAY;
+    1.0 + days.saturating_sub(5) as f32 * 0.05
+}
+
+/// How much harder the world got, 1 for a new world
+pub fn difficulty_factor(world_age: u64) -> f32 {
+    max_hostile_mobs(world_age) as f32 / BASE_MAX_HOSTILE_MOBS as f32 * attack_damage_multiplier(world_age)
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn from_factor(factor: f32) -> Self {
+        if factor <= 1.0 {
+            Difficulty::Easy
+        } else if factor < 1.5 {
+            Difficulty::Normal
+        } else {
+            Difficulty::Hard
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(day: u64) -> u64 {
+        day * TICKS_PER_DAY
+    }
+
+    #[test]
+    fn more_hostile_mobs_after_the_third_night() {
+        assert_eq!(max_hostile_mobs(0), 70);
+        assert_eq!(max_hostile_mobs(day(3)), 70);
+        assert_eq!(max_hostile_mobs(day(4) - 1), 70);
+        assert_eq!(max_hostile_mobs(day(4)), 75);
+        assert_eq!(max_hostile_mobs(day(13)), 120);
+        assert_eq!(max_hostile_mobs(day(14)), 120);
+        assert_eq!(max_hostile_mobs(day(1000)), 120);
+    }
+
+    #[test]
+    fn mobs_hit_harder_after_the_fifth_day() {
+        assert_eq!(attack_damage_multiplier(0), 1.0);
+        assert_eq!(attack_damage_multiplier(day(5)), 1.0);
+        assert_eq!(attack_damage_multiplier(day(6) - 1), 1.0);
+        assert!((attack_damage_multiplier(day(6)) - 1.05).abs() < 1e-6);
+        assert!((attack_damage_multiplier(day(10)) - 1.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn difficulty_boundaries() {
+        assert_eq!(Difficulty::from_factor(0.5), Difficulty::Easy);
+        assert_eq!(Difficulty::from_factor(1.0), Difficulty::Easy);
+        assert_eq!(Difficulty::from_factor(1.01), Difficulty::Normal);
+        assert_eq!(Difficulty::from_factor(1.49), Difficulty::Normal);
+        assert_eq!(Difficulty::from_factor(1.5), Difficulty::Hard);
+        assert_eq!(Difficulty::from_factor(3.0), Difficulty::Hard);
+    }
+
+    #[test]
+    fn a_new_world_is_easy() {
+        assert_eq!(Difficulty::from_factor(difficulty_factor(0)), Difficulty::Easy);
+        assert_eq!(Difficulty::from_factor(difficulty_factor(day(4))), Difficulty::Normal);
+        assert_eq!(Difficulty::from_factor(difficulty_factor(day(30))), Difficulty::Hard);
+    }
+}
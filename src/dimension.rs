@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{BACKGROUND_COLOR, NETHER_BACKGROUND_COLOR, NETHER_CEILING_HEIGHT, NETHER_LAVA_LEVEL, NETHER_SCALE, PORTAL_MAX_SIZE, PORTAL_MIN_HEIGHT, PORTAL_MIN_WIDTH};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Dimension {
+    Overworld,
+    Nether,
+}
+
+impl Default for Dimension {
+    fn default() -> Self {
+        Dimension::Overworld
+    }
+}
+
+impl Dimension {
+    /// The dimension a portal of this dimension leads to
+    pub fn other(&self) -> Dimension {
+        match self {
+            Dimension::Overworld => Dimension::Nether,
+            Dimension::Nether => Dimension::Overworld,
+        }
+    }
+
+    pub fn sky_color(&self) -> (f32, f32, f32, f32) {
+        match self {
+            Dimension::Overworld => BACKGROUND_COLOR,
+            Dimension::Nether => NETHER_BACKGROUND_COLOR,
+        }
+    }
+
+    /// Converts block coordinates of this dimension to the corresponding ones in `to`.
+    /// One block in the Nether is worth 8 blocks in the Overworld.
+    pub fn convert_position(&self, to: Dimension, (x, y, z): (i32, i32, i32)) -> (i32, i32, i32) {
+        match (self, to) {
+            (Dimension::Overworld, Dimension::Nether) => (
+                x.div_euclid(NETHER_SCALE),
+                // Stay between the lava sea and the ceiling
+                y.max(NETHER_LAVA_LEVEL + 2).min(NETHER_CEILING_HEIGHT - 4),
+                z.div_euclid(NETHER_SCALE),
+            ),
+            (Dimension::Nether, Dimension::Overworld) => (x * NETHER_SCALE, y, z * NETHER_SCALE),
+            _ => (x, y, z),
+        }
+    }
+}
+
+/// Owns the world of every dimension.
+/// The `Arc<ChunkManager>` resource always points to the chunk manager of the current dimension.
+pub struct DimensionManager {
+    pub current: Dimension,
+    chunk_managers: HashMap<Dimension, Arc<ChunkManager>>,
+}
+
+impl DimensionManager {
+    pub fn new() -> Self {
+        let mut chunk_managers = HashMap::new();
+        for &dimension in [Dimension::Overworld, Dimension::Nether].iter() {
+            chunk_managers.insert(dimension, Arc::new(ChunkManager::new(dimension)));
+        }
+        DimensionManager {
+            current: Dimension::Overworld,
+            chunk_managers,
+        }
+    }
+
+    pub fn get(&self, dimension: Dimension) -> Arc<ChunkManager> {
+        Arc::clone(&self.chunk_managers[&dimension])
+    }
+
+    pub fn current_chunk_manager(&self) -> Arc<ChunkManager> {
+        self.get(self.current)
+    }
+}
+
+impl Default for DimensionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Links every known portal to its counterpart in the other dimension.
+/// Portals are identified by their lowest portal block.
+#[derive(Default)]
+pub struct PortalRegistry {
+    links: HashMap<(Dimension, (i32, i32, i32)), (i32, i32, i32)>,
+}
+
+impl PortalRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the portal in the other dimension linked to this one
+    pub fn get(&self, dimension: Dimension, position: (i32, i32, i32)) -> Option<(i32, i32, i32)> {
+        self.links.get(&(dimension, position)).cloned()
+    }
+
+    pub fn link(&mut self, dimension: Dimension, position: (i32, i32, i32), other_position: (i32, i32, i32)) {
+        self.links.insert((dimension, position), other_position);
+        self.links.insert((dimension.other(), other_position), position);
+    }
+}
+
+/// Blocks a portal can be lit over
+fn is_portal_interior_block(block: Option<BlockID>) -> bool {
+    block == Some(BlockID::Air) || block == Some(BlockID::Fire)
+}
+
+/// The inside of the obsidian frame around `position`, if there is one. The frame stands along the x or the z axis,
+/// its corners can be missing
+pub fn find_portal_interior(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> Option<Vec<(i32, i32, i32)>> {
+    let is_interior = |x: i32, y: i32, z: i32| is_portal_interior_block(chunk_manager.get_block(x, y, z));
+    let is_obsidian = |x: i32, y: i32, z: i32| chunk_manager.get_block(x, y, z) == Some(BlockID::Obsidian);
+    if !is_interior(x, y, z) {
+        return None;
+    }
+
+    [(1, 0), (0, 1)].iter().find_map(|&(dx, dz)| {
+        // The blocks of the frame are numbered along its axis, from (x, z)
+        let along = |n: i32| (x + n * dx, z + n * dz);
+        let is_interior_at = |n: i32, y: i32| { let (x, z) = along(n); is_interior(x, y, z) };
+        let is_obsidian_at = |n: i32, y: i32| { let (x, z) = along(n); is_obsidian(x, y, z) };
+
+        let floor = (0..PORTAL_MAX_SIZE).map(|dy| y - dy).find(|&y| !is_interior_at(0, y - 1))?;
+        let left = (1..=PORTAL_MAX_SIZE).find(|&n| !is_interior_at(-n, floor))?;
+        let left = 1 - left;
+        let right = (left..=left + PORTAL_MAX_SIZE).find(|&n| !is_interior_at(n, floor))?;
+        let ceiling = (floor..=floor + PORTAL_MAX_SIZE).find(|&y| !is_interior_at(left, y))?;
+        let (width, height) = (right - left, ceiling - floor);
+        if width < PORTAL_MIN_WIDTH || width > PORTAL_MAX_SIZE || height < PORTAL_MIN_HEIGHT || height > PORTAL_MAX_SIZE {
+            return None;
+        }
+
+        let is_framed = (left..right).all(|n| is_obsidian_at(n, floor - 1) && is_obsidian_at(n, ceiling))
+            && (floor..ceiling).all(|y| is_obsidian_at(left - 1, y) && is_obsidian_at(right, y));
+        let is_empty = (left..right).all(|n| (floor..ceiling).all(|y| is_interior_at(n, y)));
+        if !is_framed || !is_empty {
+            return None;
+        }
+        Some((left..right)
+            .flat_map(|n| (floor..ceiling).map(move |y| { let (x, z) = along(n); (x, y, z) }))
+            .collect())
+    })
+}
+
+/// Whether the portal block at (x, y, z) is still held by portal blocks or obsidian above, below and on both sides
+pub fn is_portal_held(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> bool {
+    let holds = |x: i32, y: i32, z: i32| match chunk_manager.get_block(x, y, z) {
+        Some(BlockID::NetherPortal) | Some(BlockID::Obsidian) => true,
+        _ => false,
+    };
+    holds(x, y - 1, z) && holds(x, y + 1, z)
+        && ((holds(x - 1, y, z) && holds(x + 1, y, z)) || (holds(x, y, z - 1) && holds(x, y, z + 1)))
+}
@@ -0,0 +1,176 @@
+use std::any::Any;
+use std::collections::HashSet;
+
+use noise::{NoiseFn, Point2, SuperSimplex};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::block_entity::BlockEntity;
+use crate::chest::LootChestBlockEntity;
+use crate::chunk::{BlockID, BlockMeta};
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{DUNGEON_MAX_Y, DUNGEON_MIN_Y, DUNGEON_NOISE_THRESHOLD, DUNGEON_REGION_SIZE};
+use crate::loot::{DUNGEON_CHEST, roll};
+use crate::mob::MobKind;
+
+/// Distance from the center of a dungeon to its walls, the room is 9 blocks wide walls included
+const HALF_SIZE: i32 = 4;
+/// Distance between two samples of the noise when looking for the center of a dungeon
+const SAMPLE_SPACING: i32 = 8;
+
+/// Marks a monster spawner, in the middle of a dungeon or of a Nether fortress
+pub struct SpawnerBlockEntity {
+    /// The kind of mob coming out of the spawner
+    pub mob: MobKind,
+}
+
+impl SpawnerBlockEntity {
+    pub fn new(mob: MobKind) -> Self {
+        Self { mob }
+    }
+}
+
+impl BlockEntity for SpawnerBlockEntity {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {}
+
+    fn serialize(&self) -> Vec<u8> {
+        vec![self.mob as u8]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+fn region_rng(world_seed: u32, region_x: i32, region_z: i32) -> StdRng {
+    let seed = (world_seed as u64) << 32
+        ^ (region_x as u32 as u64).wrapping_mul(83_492_791)
+        ^ (region_z as u32 as u64).wrapping_mul(25_165_843);
+    StdRng::seed_from_u64(seed)
+}
+
+/// Center of the dungeon of a DUNGEON_REGION_SIZE wide region, if it has one.
+/// It is the highest peak of a low frequency noise, if it gets above DUNGEON_NOISE_THRESHOLD.
+/// The samples keep away from the edges of the region so that the dungeons never overlap
+pub fn dungeon_center(noise_fn: &SuperSimplex, world_seed: u32, region_x: i32, region_z: i32) -> Option<(i32, i32, i32)> {
+    let scale = 60.0;
+    let mut peak = None;
+    let mut peak_value = DUNGEON_NOISE_THRESHOLD;
+    for i in 1..DUNGEON_REGION_SIZE / SAMPLE_SPACING {
+        for j in 1..DUNGEON_REGION_SIZE / SAMPLE_SPACING {
+            let x = region_x * DUNGEON_REGION_SIZE + i * SAMPLE_SPACING;
+            let z = region_z * DUNGEON_REGION_SIZE + j * SAMPLE_SPACING;
+            let value = noise_fn.get(Point2::from([x as f64 / scale, z as f64 / scale]));
+            if value > peak_value {
+                peak = Some((x, z));
+                peak_value = value;
+            }
+        }
+    }
+
+    let (x, z) = peak?;
+    let y = region_rng(world_seed, region_x, region_z).gen_range(DUNGEON_MIN_Y, DUNGEON_MAX_Y + 1);
+    Some((x, y, z))
+}
+
+/// A chest full of random items from the DUNGEON_CHEST loot table
+fn loot_chest(rng: &mut StdRng) -> LootChestBlockEntity {
+    LootChestBlockEntity::new(roll(&DUNGEON_CHEST, rng))
+}
+
+/// The blocks of a dungeon, relative to its center, with their metadata and block entity.
+/// The walls and the ceiling are made of stone bricks and the floor of cobblestone.
+/// A spawner stands in the middle of the floor and 1 or 2 chests against the walls
+fn dungeon_blocks(rng: &mut StdRng) -> Vec<((i32, i32, i32), BlockID, BlockMeta, Option<Box<dyn BlockEntity>>)> {
+    let mut blocks = Vec::new();
+    for dy in -HALF_SIZE..=HALF_SIZE {
+        for dx in -HALF_SIZE..=HALF_SIZE {
+            for dz in -HALF_SIZE..=HALF_SIZE {
+                let block = if dy == -HALF_SIZE {
+                    BlockID::Cobblestone
+                } else if dy == HALF_SIZE || dx.abs() == HALF_SIZE || dz.abs() == HALF_SIZE {
+                    BlockID::StoneBrick
+                } else {
+                    BlockID::Air
+                };
+                blocks.push(((dx, dy, dz), block, BlockMeta::default(), None));
+            }
+        }
+    }
+
+    let floor = -HALF_SIZE + 1;
+    let mut furniture: Vec<((i32, i32, i32), BlockID, BlockMeta, Option<Box<dyn BlockEntity>>)> = vec![
+        ((0, floor, 0), BlockID::Spawner, BlockMeta::default(), Some(Box::new(SpawnerBlockEntity::new(MobKind::Zombie)))),
+    ];
+    for _ in 0..rng.gen_range(1, 3) {
+        // Against a wall, facing the middle of the room
+        let meta = BlockMeta::default().with_facing(rng.gen_range(0, 4));
+        let (dx, dz) = meta.facing_offset();
+        let along = rng.gen_range(-2, 3);
+        let position = (-dx * (HALF_SIZE - 1) + dz * along, floor, -dz * (HALF_SIZE - 1) + dx * along);
+        if furniture.iter().any(|&(p, _, _, _)| p == position) {
+            continue;
+        }
+        furniture.push((position, BlockID::Chest, meta, Some(Box::new(loot_chest(rng)))));
+    }
+
+    // The furniture replaces the air of the room
+    blocks.retain(|&(p, _, _, _)| furniture.iter().all(|&(f, _, _, _)| f != p));
+    blocks.extend(furniture);
+    blocks
+}
+
+/// Builds the parts of the dungeons that lie in the newly generated columns, the others already got theirs.
+/// The block entities of the dungeons are queued in the chunk manager for the registry
+pub fn place_dungeons(noise_fn: &SuperSimplex, world_seed: u32, chunk_manager: &ChunkManager, new_columns: &[(i32, i32)]) {
+    if new_columns.is_empty() {
+        return;
+    }
+    let new_column_set: HashSet<(i32, i32)> = new_columns.iter().cloned().collect();
+
+    let min_x = new_columns.iter().map(|&(x, _)| 16 * x).min().unwrap() - HALF_SIZE;
+    let max_x = new_columns.iter().map(|&(x, _)| 16 * x + 16).max().unwrap() + HALF_SIZE;
+    let min_z = new_columns.iter().map(|&(_, z)| 16 * z).min().unwrap() - HALF_SIZE;
+    let max_z = new_columns.iter().map(|&(_, z)| 16 * z + 16).max().unwrap() + HALF_SIZE;
+
+    for region_x in min_x.div_euclid(DUNGEON_REGION_SIZE)..=max_x.div_euclid(DUNGEON_REGION_SIZE) {
+        for region_z in min_z.div_euclid(DUNGEON_REGION_SIZE)..=max_z.div_euclid(DUNGEON_REGION_SIZE) {
+            let (x, y, z) = match dungeon_center(noise_fn, world_seed, region_x, region_z) {
+                Some(center) => center,
+                None => continue,
+            };
+
+            // Seeded by the region so that every column gets the same dungeon
+            let mut rng = region_rng(world_seed, region_x, region_z);
+            let mut is_built = false;
+            for ((dx, dy, dz), block, meta, block_entity) in dungeon_blocks(&mut rng) {
+                let (w_x, w_y, w_z) = (x + dx, y + dy, z + dz);
+                let (c_x, _, c_z, b_x, b_y, b_z) = ChunkManager::get_chunk_coords(w_x, w_y, w_z);
+                if !new_column_set.contains(&(c_x, c_z)) {
+                    continue;
+                }
+                let column = match chunk_manager.get_column(c_x, c_z) {
+                    Some(column) => column,
+                    None => continue,
+                };
+
+                let chunk = column.get_chunk(w_y / 16);
+                chunk.set_block(block, b_x, b_y, b_z);
+                chunk.set_block_meta(meta, b_x, b_y, b_z);
+                // A room carved close to the surface can remove the highest block of the column
+                column.update_heighest_block(block, b_x, w_y as u32, b_z);
+                if let Some(block_entity) = block_entity {
+                    chunk_manager.generated_block_entities.write().push(((w_x, w_y, w_z), block_entity));
+                }
+                is_built = true;
+            }
+            if is_built {
+                info!("Generated a dungeon at ({} {} {})", x, y, z);
+            }
+        }
+    }
+}
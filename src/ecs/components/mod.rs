@@ -2,9 +2,14 @@ use specs::Component;
 use specs::DenseVecStorage;
 use specs::NullStorage;
 
+use crate::firework::FireworkEntity;
 use crate::inventory::Inventory;
 use crate::physics::Interpolator;
 use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::projectile::Projectile;
+use crate::item_entity::ItemEntity;
+use crate::mob::MobEntity;
+use crate::xp_orb::XpOrb;
 
 impl Component for Interpolator<PlayerPhysicsState> {
     type Storage = DenseVecStorage<Self>;
@@ -23,4 +28,24 @@ impl Component for MainHandItemChanged {
 
 impl Component for Inventory {
     type Storage = DenseVecStorage<Self>;
-}
\ No newline at end of file
+}
+
+impl Component for Projectile {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for FireworkEntity {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for ItemEntity {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for MobEntity {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Component for XpOrb {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -0,0 +1,47 @@
+use std::time::Instant;
+
+use specs::{RunNow, World, WorldExt};
+
+use crate::achievement::AchievementRegistry;
+use crate::constants::TICKS_PER_SECOND;
+use crate::timer::Timer;
+
+/// Number of ticks between two checks of the achievements
+const CHECK_INTERVAL_TICKS: f32 = 20.0;
+
+/// Checks the predicates of the achievements at a fixed rate.
+/// The predicates need the whole world, so this runs outside of the dispatcher.
+pub struct UpdateAchievements {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl UpdateAchievements {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> RunNow<'a> for UpdateAchievements {
+    fn run_now(&mut self, world: &'a World) {
+        let now = world.read_resource::<Timer>().time();
+        let frame_time = now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+        self.accumulator += frame_time;
+
+        let check_interval = CHECK_INTERVAL_TICKS / TICKS_PER_SECOND;
+        if self.accumulator < check_interval {
+            return;
+        }
+        self.accumulator %= check_interval;
+
+        world.write_resource::<AchievementRegistry>().update(world, now);
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        world.entry::<AchievementRegistry>().or_insert_with(AchievementRegistry::new);
+    }
+}
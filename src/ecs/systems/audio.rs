@@ -0,0 +1,126 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra_glm::{vec2, Vec3};
+use specs::{Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::audio::{attenuated_volume, footstep_sound, PlaySound, SoundQueue, SoundSystem};
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{FOOTSTEP_VOLUME, HEAD_BOB_FADE_SPEED, SPLASH_SOUND, SPLASH_VOLUME};
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::timer::Timer;
+
+/// Starts the sounds asked for during the frame, heard from the camera of the player.
+/// The audio output isn't thread-safe so the system owns it rather than the world
+pub struct PlaySoundEvents {
+    sound_system: Option<SoundSystem>,
+}
+
+impl PlaySoundEvents {
+    pub fn new() -> Self {
+        Self {
+            sound_system: SoundSystem::new(),
+        }
+    }
+}
+
+impl<'a> System<'a> for PlaySoundEvents {
+    type SystemData = (
+        Write<'a, SoundQueue>,
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut sound_queue,
+            player_state,
+            player_physics_state,
+        ) = data;
+
+        let sounds = std::mem::take(&mut sound_queue.events);
+        let sound_system = match &mut self.sound_system {
+            Some(sound_system) => sound_system,
+            None => return,
+        };
+
+        for (player_state, player_physics_state) in (&player_state, &player_physics_state).join() {
+            let camera_position = player_physics_state.get_interpolated_state().position
+                + nalgebra_glm::vec3(0.0, *player_state.camera_height.get_interpolated_state(), 0.0);
+            for sound in &sounds {
+                sound_system.play(sound.path, attenuated_volume(sound, &camera_position));
+            }
+        }
+    }
+}
+
+/// Bobs the head of the player while they walk, a footstep sound of the block under their feet is played
+/// every time it is at its lowest. In the water the steps are splashes, flying makes no sound
+pub struct PlayFootsteps {
+    last_time: Instant,
+    last_position: Option<Vec3>,
+}
+
+impl PlayFootsteps {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            last_position: None,
+        }
+    }
+}
+
+impl<'a> System<'a> for PlayFootsteps {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, SoundQueue>,
+        WriteStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            mut sound_queue,
+            mut player_state,
+            player_physics_state,
+        ) = data;
+
+        let now = global_timer.time();
+        let dt = now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        for (player_state, player_physics_state) in (&mut player_state, &player_physics_state).join() {
+            let player = player_physics_state.get_interpolated_state();
+            let position = player.position;
+            let distance = self.last_position
+                .map_or(0.0, |last_position| (vec2(position.x, position.z) - vec2(last_position.x, last_position.z)).norm());
+            self.last_position = Some(position);
+
+            let is_in_water = player.is_touching_block(BlockID::Water, &chunk_manager);
+            let is_walking = !player_state.is_flying && (player_state.is_on_ground || is_in_water) && distance > 0.0;
+
+            let target_amplitude = if is_walking && !is_in_water { 1.0 } else { 0.0 };
+            player_state.head_bob_amplitude += (target_amplitude - player_state.head_bob_amplitude) * (HEAD_BOB_FADE_SPEED * dt).min(1.0);
+
+            if !is_walking || !player_state.advance_head_bob(distance) {
+                continue;
+            }
+            if is_in_water {
+                sound_queue.play(PlaySound::at(SPLASH_SOUND, SPLASH_VOLUME, position));
+                continue;
+            }
+            let block_below = chunk_manager.get_block(
+                position.x.floor() as i32,
+                position.y.floor() as i32 - 1,
+                position.z.floor() as i32);
+            if let Some(block) = block_below.filter(|&block| block != BlockID::Air) {
+                sound_queue.play(PlaySound::at(footstep_sound(block), FOOTSTEP_VOLUME, position));
+            }
+        }
+    }
+}
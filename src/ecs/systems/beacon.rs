@@ -0,0 +1,199 @@
+use std::ffi::c_void;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use nalgebra::Matrix4;
+use nalgebra_glm::vec3;
+use specs::{Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::beacon::{BeaconBlockEntity, is_beam_unobstructed, pyramid_levels};
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{BEACON_BEAM_COLOR_PERIOD, BEACON_BEAM_TOP_WIDTH, BEACON_EFFECT_BASE_DURATION, BEACON_EFFECT_DURATION_PER_LEVEL, BEACON_UPDATE_TICKS, TICKS_PER_SECOND, WORLD_HEIGHT_IN_CHUNKS};
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::timer::Timer;
+use crate::types::{BlockEntityRegistry, Shaders};
+
+/// Every BEACON_UPDATE_TICKS game ticks, checks the pyramid under every beacon
+/// and gives the chosen effect to the players in range
+pub struct UpdateBeacons {
+    last_time: Instant,
+    accumulator: f32,
+    ticks: u32,
+}
+
+impl UpdateBeacons {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+            ticks: 0,
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateBeacons {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, BlockEntityRegistry>,
+        WriteStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            mut block_entity_registry,
+            mut player_state,
+            player_physics_state,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            self.accumulator -= tick_duration;
+            self.ticks += 1;
+            if self.ticks < BEACON_UPDATE_TICKS {
+                continue;
+            }
+            self.ticks = 0;
+
+            for (&(x, y, z), block_entity) in block_entity_registry.iter_mut() {
+                let beacon = match block_entity.downcast_mut::<BeaconBlockEntity>() {
+                    Some(beacon) => beacon,
+                    None => continue,
+                };
+                beacon.levels = pyramid_levels(&chunk_manager, (x, y, z));
+                let (effect, level) = match beacon.active_effect() {
+                    Some(effect) => effect,
+                    None => continue,
+                };
+
+                let range = beacon.effect_range();
+                let duration = Duration::from_secs_f32(BEACON_EFFECT_BASE_DURATION + BEACON_EFFECT_DURATION_PER_LEVEL * beacon.levels as f32);
+                for (player_state, player_physics_state) in (&mut player_state, &player_physics_state).join() {
+                    let position = player_physics_state.get_latest_state().position;
+                    let (dx, dz) = (position.x - (x as f32 + 0.5), position.z - (z as f32 + 0.5));
+                    if dx.abs() <= range && dz.abs() <= range {
+                        player_state.status_effects.give(effect, level, duration, now);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The 4 sides of a box going from (-0.5, 0, -0.5) to (0.5, 1, 0.5), without the top and the bottom
+fn create_beam_vao() -> u32 {
+    let mut vao = 0;
+    gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+    // Position
+    gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+    gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+    gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+    let mut vbo_data: Vec<f32> = Vec::new();
+    let corners = [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)];
+    for i in 0..4 {
+        let (x0, z0) = corners[i];
+        let (x1, z1) = corners[(i + 1) % 4];
+        vbo_data.extend_from_slice(&[
+            x0, 0.0, z0, x1, 0.0, z1, x1, 1.0, z1,
+            x1, 1.0, z1, x0, 1.0, z0, x0, 0.0, z0,
+        ]);
+    }
+
+    let mut vbo = 0;
+    gl_call!(gl::CreateBuffers(1, &mut vbo));
+    gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (3 * std::mem::size_of::<f32>()) as i32));
+    gl_call!(gl::NamedBufferData(vbo,
+                (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                vbo_data.as_ptr() as *const c_void,
+                gl::STATIC_DRAW));
+    vao
+}
+
+/// Draws a beam of light from every beacon standing on a pyramid up to the top of the world,
+/// unless an opaque block is in the way. The beam cycles through the colors every BEACON_BEAM_COLOR_PERIOD seconds
+pub struct RenderBeaconBeams {
+    vao: u32,
+    start: Instant,
+}
+
+impl RenderBeaconBeams {
+    pub fn new() -> Self {
+        Self {
+            vao: create_beam_vao(),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl<'a> System<'a> for RenderBeaconBeams {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, BlockEntityRegistry>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            block_entity_registry,
+            mut shaders,
+        ) = data;
+
+        let beacons: Vec<(i32, i32, i32)> = block_entity_registry.iter()
+            .filter(|(_, block_entity)| {
+                block_entity.downcast_ref::<BeaconBlockEntity>().map_or(false, |beacon| beacon.levels > 0)
+            })
+            .map(|(&position, _)| position)
+            .filter(|&(x, y, z)| chunk_manager.get_block(x, y, z) == Some(BlockID::Beacon))
+            .filter(|&position| is_beam_unobstructed(&chunk_manager, position))
+            .collect();
+        if beacons.is_empty() {
+            return;
+        }
+
+        let time = global_timer.time().saturating_duration_since(self.start).as_secs_f32();
+        let phase = 2.0 * PI * time / BEACON_BEAM_COLOR_PERIOD;
+        let color = [
+            0.5 + 0.5 * phase.cos(),
+            0.5 + 0.5 * (phase - 2.0 * PI / 3.0).cos(),
+            0.5 + 0.5 * (phase + 2.0 * PI / 3.0).cos(),
+        ];
+
+        let beam_shader = shaders.get_mut("beacon_beam_shader").unwrap();
+        beam_shader.use_program();
+        beam_shader.set_uniform3f("color", &color);
+        beam_shader.set_uniform1f("top_width", BEACON_BEAM_TOP_WIDTH);
+
+        // Additive blending, the beam lights up what is behind it
+        gl_call!(gl::Disable(gl::CULL_FACE));
+        gl_call!(gl::DepthMask(gl::FALSE));
+        gl_call!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE));
+        gl_call!(gl::BindVertexArray(self.vao));
+
+        for &(x, y, z) in &beacons {
+            let height = (WORLD_HEIGHT_IN_CHUNKS * 16 - (y + 1)) as f32;
+            let model_matrix = Matrix4::new_translation(&vec3(x as f32 + 0.5, (y + 1) as f32, z as f32 + 0.5))
+                * Matrix4::new_nonuniform_scaling(&vec3(1.0, height, 1.0));
+            beam_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+            gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 24));
+        }
+
+        gl_call!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
+        gl_call!(gl::DepthMask(gl::TRUE));
+        gl_call!(gl::Enable(gl::CULL_FACE));
+    }
+}
@@ -0,0 +1,165 @@
+use std::ffi::c_void;
+use std::sync::Arc;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::vec3;
+use specs::{Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::bed::BedBlockEntity;
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{SLEEP_FADE_DURATION, WAKE_UP_DURATION};
+use crate::day_cycle::DayCycle;
+use crate::player::PlayerState;
+use crate::shapes::bed_mesh;
+use crate::timer::Timer;
+use crate::types::{BlockEntityRegistry, Shaders, TexturePack};
+use crate::util::is_sphere_in_frustum;
+
+/// Skips the night once the screen is black, then wakes the player up
+pub struct Sleep;
+
+impl<'a> System<'a> for Sleep {
+    type SystemData = (
+        Read<'a, Timer>,
+        Write<'a, DayCycle>,
+        WriteStorage<'a, PlayerState>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            mut day_cycle,
+            mut player_state,
+        ) = data;
+
+        let now = global_timer.time();
+        for player_state in (&mut player_state).join() {
+            let sleep_started = match player_state.sleep_started {
+                Some(sleep_started) => sleep_started,
+                None => continue,
+            };
+
+            let elapsed = now.saturating_duration_since(sleep_started).as_secs_f32();
+            if elapsed >= SLEEP_FADE_DURATION && day_cycle.is_night() {
+                day_cycle.skip_to_dawn();
+                info!("Slept until dawn");
+            }
+            if elapsed >= SLEEP_FADE_DURATION + WAKE_UP_DURATION {
+                player_state.sleep_started = None;
+            }
+        }
+    }
+}
+
+pub struct RenderBeds {
+    vao: u32,
+    vbo: u32,
+    is_uploaded: bool,
+}
+
+impl RenderBeds {
+    pub fn new() -> Self {
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        // Position
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+        // Texture coords
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 1, 3 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+        // Normals
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 2));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 2, 3 as i32, gl::FLOAT, gl::FALSE, 6 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 2, 0));
+
+        let mut vbo = 0;
+        gl_call!(gl::CreateBuffers(1, &mut vbo));
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (9 * std::mem::size_of::<f32>()) as i32));
+
+        Self {
+            vao,
+            vbo,
+            is_uploaded: false,
+        }
+    }
+
+    /// The meshes of the head in the 4 orientations followed by the ones of the foot, 36 vertices each
+    fn upload_model(&mut self, texture_pack: &TexturePack) {
+        let head_uvs = texture_pack.get(&BlockID::BedHead).unwrap().get_uv_of_every_face();
+        let foot_uvs = texture_pack.get(&BlockID::BedFoot).unwrap().get_uv_of_every_face();
+        let vbo_data: Vec<f32> = (0..4).flat_map(|facing| bed_mesh(facing, head_uvs))
+            .chain((0..4).flat_map(|facing| bed_mesh(facing, foot_uvs)))
+            .collect();
+
+        gl_call!(gl::NamedBufferData(self.vbo,
+                    (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                    vbo_data.as_ptr() as *const c_void,
+                    gl::STATIC_DRAW));
+        self.is_uploaded = true;
+    }
+}
+
+impl<'a> System<'a> for RenderBeds {
+    type SystemData = (
+        Read<'a, BlockEntityRegistry>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        ReadStorage<'a, PlayerState>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            block_entity_registry,
+            chunk_manager,
+            texture_pack,
+            player_state,
+            mut shaders,
+        ) = data;
+
+        if !self.is_uploaded {
+            self.upload_model(&texture_pack);
+        }
+
+        for player_state in (&player_state).join() {
+            let view_projection = player_state.projection_matrix * player_state.view_matrix;
+
+            let model_shader = shaders.get_mut("model_shader").unwrap();
+            model_shader.use_program();
+            model_shader.set_uniform1i("tex", 0);
+            gl_call!(gl::BindVertexArray(self.vao));
+
+            // The bed block entity is stored at the head
+            for (&(x, y, z), _) in block_entity_registry.iter()
+                .filter(|(_, block_entity)| block_entity.downcast_ref::<BedBlockEntity>().is_some()) {
+                // The chunk isn't loaded
+                let meta = match chunk_manager.get_block_meta(x, y, z) {
+                    Some(meta) => meta,
+                    None => continue,
+                };
+                let facing = meta.facing();
+                let (dx, dz) = meta.facing_offset();
+
+                // Between the head and the foot
+                let center = vec3(x as f32 + 0.5 - dx as f32 / 2.0, y as f32 + 0.5, z as f32 + 0.5 - dz as f32 / 2.0);
+                if !is_sphere_in_frustum(&view_projection, &center, 1.23) {
+                    continue;
+                }
+
+                let model_matrix = Matrix4::new_translation(&vec3(x as f32, y as f32, z as f32));
+                model_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+                gl_call!(gl::DrawArrays(gl::TRIANGLES, 36 * facing as i32, 36));
+
+                let model_matrix = Matrix4::new_translation(&vec3((x - dx) as f32, y as f32, (z - dz) as f32));
+                model_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+                gl_call!(gl::DrawArrays(gl::TRIANGLES, 36 * (4 + facing as i32), 36));
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use specs::{Read, System, Write};
+
+use crate::chunk_manager::ChunkManager;
+use crate::constants::TICKS_PER_SECOND;
+use crate::timer::Timer;
+use crate::types::BlockEntityRegistry;
+
+/// Ticks every block entity at a fixed rate, whether it is being looked at or not
+pub struct UpdateBlockEntities {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl UpdateBlockEntities {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateBlockEntities {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, BlockEntityRegistry>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            mut block_entity_registry,
+        ) = data;
+
+        let now = global_timer.time();
+        let frame_time = now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+        self.accumulator += frame_time;
+
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            for (&position, block_entity) in block_entity_registry.iter_mut() {
+                block_entity.tick(position, &chunk_manager);
+            }
+            self.accumulator -= tick_duration;
+        }
+    }
+}
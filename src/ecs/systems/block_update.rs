@@ -0,0 +1,130 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use specs::{Read, System, Write};
+
+use crate::block_update::{BlockUpdateBehavior, BlockUpdateQueue};
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{BLOCK_UPDATES_PER_TICK, OBSERVER_PULSE_TICKS, TICKS_PER_SECOND};
+use crate::observer::{observers_watching, ObserverBlockEntity};
+use crate::timer::Timer;
+use crate::types::BlockEntityRegistry;
+
+/// Lets the blocks react to the changes of their neighbours, a limited number of them every game tick.
+/// The blocks changed by these updates notify their own neighbours in turn.
+/// The observers looking at a changed block emit a pulse
+pub struct ProcessBlockUpdates {
+    last_time: Instant,
+    accumulator: f32,
+    /// The observers emitting their pulse, only they are ticked
+    pulsing_observers: Vec<(i32, i32, i32)>,
+}
+
+impl ProcessBlockUpdates {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+            pulsing_observers: Vec::new(),
+        }
+    }
+
+    /// Powers the observers looking at the changed block, unless they are already emitting a pulse
+    fn trigger_observers(&mut self, position: (i32, i32, i32), chunk_manager: &ChunkManager,
+                         block_entity_registry: &mut BlockEntityRegistry, block_update_queue: &mut BlockUpdateQueue) {
+        for (x, y, z) in observers_watching(chunk_manager, position) {
+            let observer = match block_entity_registry.get_mut(&(x, y, z))
+                .and_then(|block_entity| block_entity.downcast_mut::<ObserverBlockEntity>()) {
+                Some(observer) if !observer.was_powered => observer,
+                _ => continue,
+            };
+            observer.was_powered = true;
+            observer.ticks_since_triggered = 0;
+            let meta = chunk_manager.get_block_meta(x, y, z).unwrap_or_default();
+            chunk_manager.set_block_meta(meta.with_powered(true), x, y, z);
+            block_update_queue.enqueue_neighbours((x, y, z));
+            self.pulsing_observers.push((x, y, z));
+        }
+    }
+
+    /// Ends the pulses that lasted OBSERVER_PULSE_TICKS, the observers broken in the meantime are forgotten
+    fn tick_observers(&mut self, chunk_manager: &ChunkManager,
+                      block_entity_registry: &mut BlockEntityRegistry, block_update_queue: &mut BlockUpdateQueue) {
+        self.pulsing_observers.retain(|&(x, y, z)| {
+            let observer = match block_entity_registry.get_mut(&(x, y, z))
+                .and_then(|block_entity| block_entity.downcast_mut::<ObserverBlockEntity>()) {
+                Some(observer) if chunk_manager.get_block(x, y, z) == Some(BlockID::Observer) => observer,
+                _ => return false,
+            };
+            observer.ticks_since_triggered += 1;
+            if observer.ticks_since_triggered < OBSERVER_PULSE_TICKS {
+                return true;
+            }
+            observer.was_powered = false;
+            let meta = chunk_manager.get_block_meta(x, y, z).unwrap_or_default();
+            chunk_manager.set_block_meta(meta.with_powered(false), x, y, z);
+            block_update_queue.enqueue_neighbours((x, y, z));
+            false
+        });
+    }
+
+    /// Queues the neighbours of the blocks changed since the last call, and triggers the observers looking at them
+    /// or at the blocks whose state changed. The mobs look again whether they can walk around the changed blocks
+    fn take_changes(&mut self, chunk_manager: &ChunkManager,
+                    block_entity_registry: &mut BlockEntityRegistry, block_update_queue: &mut BlockUpdateQueue) {
+        let changed_blocks: Vec<_> = chunk_manager.changed_blocks.write().drain(..).collect();
+        let changed_states: Vec<_> = chunk_manager.changed_states.write().drain(..).collect();
+        for &position in changed_blocks.iter() {
+            block_update_queue.enqueue_neighbours(position);
+            chunk_manager.nav_cache.invalidate(position);
+        }
+        for &position in changed_blocks.iter().chain(changed_states.iter()) {
+            self.trigger_observers(position, chunk_manager, block_entity_registry, block_update_queue);
+        }
+    }
+}
+
+impl<'a> System<'a> for ProcessBlockUpdates {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, BlockEntityRegistry>,
+        Write<'a, BlockUpdateQueue>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            mut block_entity_registry,
+            mut block_update_queue,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            block_update_queue.start_tick();
+
+            self.tick_observers(&chunk_manager, &mut block_entity_registry, &mut block_update_queue);
+            self.take_changes(&chunk_manager, &mut block_entity_registry, &mut block_update_queue);
+
+            for _ in 0..BLOCK_UPDATES_PER_TICK {
+                let (x, y, z) = match block_update_queue.pop() {
+                    Some(position) => position,
+                    None => break,
+                };
+                if let Some(block) = chunk_manager.get_block(x, y, z) {
+                    block.on_neighbor_change((x, y, z), &chunk_manager);
+                }
+
+                // The changes made by the updates notify their own neighbours
+                self.take_changes(&chunk_manager, &mut block_entity_registry, &mut block_update_queue);
+            }
+            self.accumulator -= tick_duration;
+        }
+    }
+}
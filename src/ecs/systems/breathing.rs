@@ -0,0 +1,73 @@
+use std::time::Instant;
+
+use nalgebra_glm::vec3;
+use specs::{Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::constants::TICKS_PER_SECOND;
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::timer::Timer;
+use crate::types::{ParticleSystems, TexturePack};
+
+/// Consumes the air of the player while their head is under water, one game tick at a time.
+/// A bubble rises from the head of the player every time a bubble of the air bar pops
+pub struct UpdateBreathing {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl UpdateBreathing {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateBreathing {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, TexturePack>,
+        Write<'a, ParticleSystems>,
+        WriteStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            texture_pack,
+            mut particle_systems,
+            mut player_state,
+            player_physics_state,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            for (player_state, player_physics_state) in (&mut player_state, &player_physics_state).join() {
+                // Spectators don't need to breathe
+                if player_state.is_spectator() {
+                    continue;
+                }
+                let bubble_popped = player_state.update_air_supply(now);
+
+                if bubble_popped {
+                    let head = player_physics_state.get_latest_state().position
+                        + vec3(0.0, *player_state.camera_height.get_latest_state(), 0.0);
+                    let bubble_particles = particle_systems.get_mut("bubble_particles").unwrap();
+                    bubble_particles.emit_bubble_particles(head, 4, &texture_pack);
+                }
+
+                if player_state.health <= 0.0 && !player_state.is_dead {
+                    player_state.die("Drowned");
+                }
+            }
+            self.accumulator -= tick_duration;
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra_glm::vec3;
+use rand::Rng;
+use specs::{Read, System, Write};
+
+use crate::campfire::CampfireBlockEntity;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{CAMPFIRE_SMOKE_CHANCE, TICKS_PER_SECOND};
+use crate::timer::Timer;
+use crate::types::{BlockEntityRegistry, ParticleSystems, TexturePack};
+
+/// Makes smoke rise from the campfires of the loaded chunks, the ones put out keep smoking too
+pub struct EmitCampfireSmoke {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl EmitCampfireSmoke {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for EmitCampfireSmoke {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        Read<'a, BlockEntityRegistry>,
+        Write<'a, ParticleSystems>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            texture_pack,
+            block_entity_registry,
+            mut particle_systems,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let mut rng = rand::thread_rng();
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            let smoke_particles = particle_systems.get_mut("smoke_particles").unwrap();
+            for (&(x, y, z), block_entity) in block_entity_registry.iter() {
+                let is_campfire = block_entity.downcast_ref::<CampfireBlockEntity>().is_some()
+                    && chunk_manager.get_block(x, y, z).map_or(false, |block| block.is_campfire());
+                if is_campfire && rng.gen::<f32>() < CAMPFIRE_SMOKE_CHANCE {
+                    smoke_particles.emit_campfire_smoke_particle(vec3(x as f32, y as f32, z as f32), &texture_pack);
+                }
+            }
+            self.accumulator -= tick_duration;
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use glfw::{Action, Key, WindowEvent};
+use specs::{Read, System, Write};
+
+use crate::chat::{Chat, PLAYER_NAME};
+use crate::command::run_command;
+use crate::input::InputCache;
+use crate::screen::Screen;
+use crate::timer::Timer;
+use crate::weather::Weather;
+
+/// Opens the chat with T, types in it and sends the message with Enter, Escape closes it without sending.
+/// The messages starting with a slash are commands, their answer is shown instead.
+/// The keyboard and scroll events are consumed while the chat is open so that typing doesn't move the player
+pub struct HandleChatInput;
+
+impl<'a> System<'a> for HandleChatInput {
+    type SystemData = (
+        Read<'a, Timer>,
+        Write<'a, InputCache>,
+        Write<'a, Screen>,
+        Write<'a, Chat>,
+        Write<'a, Weather>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            mut input_cache,
+            mut screen,
+            mut chat,
+            mut weather,
+        ) = data;
+
+        if *screen == Screen::None {
+            let t_pressed = input_cache.events.iter().any(|event| match event {
+                WindowEvent::Key(Key::T, _, Action::Press, _) => true,
+                _ => false
+            });
+            if !t_pressed {
+                return;
+            }
+            chat.open();
+            *screen = Screen::Chat;
+            info!("Opened {:?}", *screen);
+        } else if *screen == Screen::Chat {
+            for event in &input_cache.events {
+                match event {
+                    WindowEvent::Char(c) => chat.insert_char(*c),
+                    WindowEvent::Scroll(_, y) => chat.scroll(*y),
+                    WindowEvent::Key(Key::Backspace, _, Action::Press, _) |
+                    WindowEvent::Key(Key::Backspace, _, Action::Repeat, _) => chat.delete_char(),
+                    WindowEvent::Key(Key::Enter, _, Action::Press, _) => {
+                        let now = global_timer.time();
+                        match chat.take_input() {
+                            Some(text) if text.starts_with('/') => match run_command(&text[1..], &mut weather) {
+                                Ok(answer) => chat.add_message("", &answer, now),
+                                Err(err) => chat.add_message("", &err, now),
+                            },
+                            Some(text) => chat.add_message(PLAYER_NAME, &text, now),
+                            None => {}
+                        }
+                        *screen = Screen::None;
+                        break;
+                    }
+                    WindowEvent::Key(Key::Escape, _, Action::Press, _) => {
+                        *screen = Screen::None;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        } else {
+            return;
+        }
+
+        // The T typed to open the chat isn't part of the message
+        input_cache.events.retain(|event| match event {
+            WindowEvent::Char(_) | WindowEvent::Key(..) | WindowEvent::Scroll(..) => false,
+            _ => true
+        });
+        input_cache.key_states.clear();
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::time::Instant;
+
+use glfw::{Action, MouseButton, WindowEvent};
+use nalgebra::Matrix4;
+use nalgebra_glm::{pi, vec3};
+use specs::{Entities, Join, Read, System, Write, WriteStorage};
+
+use crate::chest::ChestBlockEntity;
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::WINDOW_HEIGHT;
+use crate::ecs::components::MainHandItemChanged;
+use crate::input::InputCache;
+use crate::inventory::Inventory;
+use crate::physics::Interpolator;
+use crate::screen::Screen;
+use crate::shapes::textured_box;
+use crate::timer::Timer;
+use crate::types::{BlockEntityRegistry, Shaders, TexturePack};
+use std::sync::Arc;
+
+pub struct HandleChestInput;
+
+impl<'a> System<'a> for HandleChestInput {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, InputCache>,
+        Write<'a, Screen>,
+        Write<'a, BlockEntityRegistry>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, MainHandItemChanged>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            input_cache,
+            mut screen,
+            mut block_entity_registry,
+            mut inventory,
+            mut main_hand_item_changed,
+        ) = data;
+
+        let (x, y, z) = match *screen {
+            Screen::Chest(position) => position,
+            _ => return,
+        };
+
+        for (e, inventory) in (&entities, &mut inventory).join() {
+            let chest = match block_entity_registry.get_mut(&(x, y, z))
+                .and_then(|block_entity| block_entity.downcast_mut::<ChestBlockEntity>()) {
+                Some(chest) => chest,
+                None => {
+                    *screen = Screen::None;
+                    return;
+                }
+            };
+
+            let selected_item = inventory.get_selected_item();
+
+            for event in &input_cache.events {
+                match event {
+                    // Clicking on a stack moves it between the chest and the hotbar
+                    WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                        let cursor_x = input_cache.last_cursor_pos.x as f32;
+                        let cursor_y = WINDOW_HEIGHT as f32 - input_cache.last_cursor_pos.y as f32;
+
+                        if let Some(i) = ChestBlockEntity::slot_at(cursor_x, cursor_y) {
+                            if let Some(item_stack) = chest.0[i].take() {
+                                chest.0[i] = inventory.add_item_stack(item_stack);
+                            }
+                        } else if let Some(i) = Inventory::hotbar_slot_at(cursor_x, cursor_y) {
+                            if let Some(item_stack) = inventory.slots[i].take() {
+                                inventory.slots[i] = chest.add_item_stack(item_stack);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            if inventory.get_selected_item() != selected_item {
+                if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                    error!("{}", e);
+                }
+            }
+        }
+    }
+}
+
+const LID_OPENED_ANGLE: f32 = 80.0;
+const LID_SPEED: f32 = 360.0;
+
+// The lid rotates around the top of the back face of the base
+const HINGE: (f32, f32) = (9.0 / 16.0, 1.0 / 16.0);
+
+pub struct RenderChests {
+    vao: u32,
+    vbo: u32,
+    is_uploaded: bool,
+    lid_angles: HashMap<(i32, i32, i32), Interpolator<f32>>,
+}
+
+impl RenderChests {
+    pub fn new() -> Self {
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        // Position
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+        // Texture coords
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 1, 3 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+        // Normals
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 2));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 2, 3 as i32, gl::FLOAT, gl::FALSE, 6 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 2, 0));
+
+        let mut vbo = 0;
+        gl_call!(gl::CreateBuffers(1, &mut vbo));
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (9 * std::mem::size_of::<f32>()) as i32));
+
+        Self {
+            vao,
+            vbo,
+            is_uploaded: false,
+            lid_angles: HashMap::new(),
+        }
+    }
+
+    /// The base is made of 36 vertices, the lid (with its latch) of the 72 next ones
+    fn upload_model(&mut self, texture_pack: &TexturePack) {
+        let uvs = texture_pack.get(&BlockID::Chest).unwrap().get_uv_of_every_face();
+        let mut vbo_data = textured_box(
+            (1.0 / 16.0, 0.0, 1.0 / 16.0),
+            (15.0 / 16.0, 10.0 / 16.0, 15.0 / 16.0), uvs);
+        vbo_data.extend(textured_box(
+            (1.0 / 16.0, 9.0 / 16.0, 1.0 / 16.0),
+            (15.0 / 16.0, 14.0 / 16.0, 15.0 / 16.0), uvs));
+        vbo_data.extend(textured_box(
+            (7.0 / 16.0, 7.0 / 16.0, 15.0 / 16.0),
+            (9.0 / 16.0, 11.0 / 16.0, 1.0), uvs));
+
+        gl_call!(gl::NamedBufferData(self.vbo,
+                    (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                    vbo_data.as_ptr() as *const c_void,
+                    gl::STATIC_DRAW));
+        self.is_uploaded = true;
+    }
+}
+
+impl<'a> System<'a> for RenderChests {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Screen>,
+        Read<'a, BlockEntityRegistry>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            screen,
+            block_entity_registry,
+            chunk_manager,
+            texture_pack,
+            mut shaders,
+        ) = data;
+
+        if !self.is_uploaded {
+            self.upload_model(&texture_pack);
+        }
+
+        let chests: Vec<(i32, i32, i32)> = block_entity_registry.iter()
+            .filter(|(_, block_entity)| block_entity.downcast_ref::<ChestBlockEntity>().is_some())
+            .map(|(&position, _)| position)
+            .collect();
+
+        // Forget the animation of chests that don't exist anymore
+        self.lid_angles.retain(|position, _| chests.contains(position));
+
+        let model_shader = shaders.get_mut("model_shader").unwrap();
+        model_shader.use_program();
+        model_shader.set_uniform1i("tex", 0);
+        gl_call!(gl::BindVertexArray(self.vao));
+
+        for &(x, y, z) in chests.iter() {
+            // The chunk isn't loaded
+            let meta = match chunk_manager.get_block_meta(x, y, z) {
+                Some(meta) => meta,
+                None => continue,
+            };
+
+            let target_angle = if *screen == Screen::Chest((x, y, z)) {
+                LID_OPENED_ANGLE
+            } else {
+                0.0
+            };
+            let lid_angle = self.lid_angles.entry((x, y, z))
+                .or_insert_with(|| Interpolator::new(1.0 / 60.0, 0.0));
+            lid_angle.interpolate_lid_angle(global_timer.time(), target_angle);
+            let lid_angle = *lid_angle.get_interpolated_state();
+
+            let base_model_matrix = {
+                let translate_matrix = Matrix4::new_translation(&vec3(
+                    x as f32 + 0.5, y as f32, z as f32 + 0.5));
+                let rotate_matrix = Matrix4::from_euler_angles(
+                    0.0f32,
+                    -(meta.facing() as f32) * pi::<f32>() / 2.0,
+                    0.0,
+                );
+                let center_matrix = Matrix4::new_translation(&vec3(-0.5, 0.0, -0.5));
+                translate_matrix * rotate_matrix * center_matrix
+            };
+
+            let lid_model_matrix = {
+                let (hinge_y, hinge_z) = HINGE;
+                let to_hinge = Matrix4::new_translation(&vec3(0.0, hinge_y, hinge_z));
+                let rotate_matrix = Matrix4::from_euler_angles(-lid_angle.to_radians(), 0.0, 0.0);
+                let from_hinge = Matrix4::new_translation(&vec3(0.0, -hinge_y, -hinge_z));
+                base_model_matrix * to_hinge * rotate_matrix * from_hinge
+            };
+
+            model_shader.set_uniform_matrix4fv("model", base_model_matrix.as_ptr());
+            gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 36));
+            model_shader.set_uniform_matrix4fv("model", lid_model_matrix.as_ptr());
+            gl_call!(gl::DrawArrays(gl::TRIANGLES, 36, 72));
+        }
+    }
+}
+
+impl Interpolator<f32> {
+    pub fn interpolate_lid_angle(&mut self, time: Instant, target_angle: f32) {
+        self.step(time, &mut |&angle, _t, dt| {
+            if angle < target_angle {
+                (angle + LID_SPEED * dt).min(target_angle)
+            } else {
+                (angle - LID_SPEED * dt).max(target_angle)
+            }
+        });
+    }
+}
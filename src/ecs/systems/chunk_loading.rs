@@ -6,17 +6,27 @@ use std::time::{Duration, Instant};
 
 use bit_vec::BitVec;
 use crossbeam_channel::{Receiver, Sender, unbounded};
+use nalgebra_glm::{Vec2, vec2, Vec3};
 use noise::{NoiseFn, Point2, Point3, Seedable, SuperSimplex};
 use num_traits::abs;
 use parking_lot::RwLock;
-use specs::{Join, Read, ReadStorage, System};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use specs::{Join, Read, ReadStorage, System, Write};
 
+use crate::chest::{ChestBlockEntity, LootChestBlockEntity};
 use crate::chunk::{BlockID, BlockIterator, Chunk, ChunkColumn};
 use crate::chunk_manager::ChunkManager;
-use crate::constants::{CHUNK_UPLOADS_PER_FRAME, RENDER_DISTANCE, WORLD_GENERATION_THREAD_POOL_SIZE, WORLD_SEED};
+use crate::constants::{CHUNK_FADE_DURATION, CHUNK_PREGENERATION_DISTANCE, CHUNK_PREGENERATION_MIN_SPEED, CHUNK_UPLOAD_INTERACTION_PRIORITY, CHUNK_UPLOADS_PER_FRAME, NETHER_CEILING_HEIGHT, NETHER_LAVA_LEVEL, RENDER_DISTANCE, RIVER_MAX_LENGTH, RIVER_SOURCE_CHANCE, RIVER_SPACING, STONE_VARIANT_THRESHOLD, WATER_LEVEL, WORLD_GENERATION_THREAD_POOL_SIZE, WORLD_HEIGHT_IN_CHUNKS};
+use crate::dimension::{Dimension, DimensionManager};
+use crate::dungeon::place_dungeons;
 use crate::physics::Interpolator;
 use crate::player::PlayerPhysicsState;
-use crate::types::TexturePack;
+use crate::settings::Settings;
+use crate::structures::{NetherFortressGenerator, VillageGenerator};
+use crate::tree::{generate_tree, tree_rng, TreeKind};
+use crate::types::{BlockEntityRegistry, TexturePack};
+use crate::worldgen::{BlendedBiomeResult, WorldGenConfig};
 
 #[derive(Eq)]
 struct PrioritizedItem<T> {
@@ -52,6 +62,14 @@ impl<T> Deref for PrioritizedItem<T> {
 
 pub struct ChunkLoading {
     noise_fn: SuperSimplex,
+    nether_noise_fn: SuperSimplex,
+    climate_noise_fn: SuperSimplex,
+    dungeon_noise_fn: SuperSimplex,
+    /// One noise for each of the STONE_VARIANTS
+    stone_variant_noise_fns: [SuperSimplex; 3],
+    seed: u32,
+    /// Dimension of the chunk manager the loaded columns belong to
+    dimension: Dimension,
     chunk_column_pool: Arc<RwLock<Vec<Arc<ChunkColumn>>>>,
 
     request_chunk_columns_tx: Sender<()>,
@@ -66,11 +84,13 @@ pub struct ChunkLoading {
     chunk_upload_priority_queue: BinaryHeap<PrioritizedItem<(i32, i32, i32)>>,
 
     expand_chunks: Arc<RwLock<bool>>,
+    /// The chunks fade by the time elapsed since the previous frame
+    last_fade: Instant,
     world_generation_thread_pool: rayon::ThreadPool,
-    player_interaction_thread_pool: rayon::ThreadPool,
+    player_interaction_thread_pool: Arc<rayon::ThreadPool>,
 }
 
-fn compute_tree_placement_in_chunk(noise: &SuperSimplex, x: f64, z: f64) -> Vec<(u32, u32)> {
+fn compute_tree_placement_in_chunk(noise: &SuperSimplex, config: &WorldGenConfig, x: f64, z: f64) -> Vec<(u32, u32)> {
     let mut maximums = Vec::new();
 
     #[inline]
@@ -96,7 +116,7 @@ fn compute_tree_placement_in_chunk(noise: &SuperSimplex, x: f64, z: f64) -> Vec<
                         if ni == i && nj == j {
                             continue;
                         }
-                        if samples[index(ni, nj)] >= center {
+                        if samples[index(ni, nj)] >= center - config.tree_density_threshold {
                             return false;
                         }
                     }
@@ -111,8 +131,346 @@ fn compute_tree_placement_in_chunk(noise: &SuperSimplex, x: f64, z: f64) -> Vec<
     maximums
 }
 
+fn generate_overworld_terrain(noise_fn: &SuperSimplex, config: &WorldGenConfig, x: i32, z: i32, climate: &[(f32, f32); 4], column: &ChunkColumn) {
+    let biomes: Vec<BlendedBiomeResult> = (0..256).map(|i| BlendedBiomeResult::new(climate, i % 16, i / 16)).collect();
+
+    // Stone, the noise can't bring it above this height
+    let max_height = biomes.iter()
+        .map(|biome| (256.0 - config.terrain_height_offset - biome.height_offset() + config.terrain_amplitude * biome.height_scale()) / 1.7)
+        .fold(0.0, f64::max) as i32;
+    for y in (0..WORLD_HEIGHT_IN_CHUNKS).rev() {
+        let y = 16 * y;
+        if y > max_height {
+            continue;
+        }
+        for b_y in 0..16 {
+            for b_x in 0..16 {
+                for b_z in 0..16 {
+                    let x = 16 * x;
+                    let z = 16 * z;
+
+                    let scale = config.terrain_scale;
+
+                    // Scale the input for the noise function
+                    let (xf, yf, zf) = (
+                        (x + b_x as i32) as f64 / scale,
+                        (y + b_y as i32) as f64 / (scale / 1.0),
+                        (z + b_z as i32) as f64 / scale);
+
+                    // Near the borders of the biomes, the height of the terrain is blended between them
+                    let biome = &biomes[16 * b_z as usize + b_x as usize];
+                    let height = (y + b_y as i32) as f64;
+                    let noise = noise_fn.get(Point3::from([xf, yf, zf])) * config.terrain_amplitude * biome.height_scale()
+                        + config.terrain_height_offset + biome.height_offset() + height * 1.7;
+
+                    if noise < 256.0 {
+                        column.set_block(BlockID::Stone, b_x, y as u32 + b_y, b_z);
+                    }
+                };
+            }
+        }
+    }
+
+    // Grass and dirt, or whatever the biome covers its terrain with
+    for b_x in 0..16 {
+        for b_z in 0..16 {
+            let y = column.heighest_blocks.read()[16 * b_z + b_x] as i32;
+            let biome = biomes[16 * b_z + b_x].primary;
+
+            let chunk_y = (y / 16) as i32;
+            let block_y = (y % 16) as usize;
+            column.get_chunk(chunk_y).set_block(biome.surface_block(), b_x as u32, block_y as u32, b_z as u32);
+
+            for y in (y - 3)..y {
+                let chunk_y = (y / 16) as i32;
+                let block_y = (y % 16) as usize;
+
+                let chunk = column.get_chunk(chunk_y);
+                if chunk.get_block(b_x as u32, block_y as u32, b_z as u32).is_air() {
+                    continue;
+                }
+                chunk.set_block(biome.filler_block(), b_x as u32, block_y as u32, b_z as u32);
+            }
+        }
+    }
+
+    // Water
+    for b_x in 0..16 {
+        for b_z in 0..16 {
+            let y = column.heighest_blocks.read()[16 * b_z + b_x] as i32;
+            if y >= WATER_LEVEL {
+                continue;
+            }
+
+            // Grass doesn't grow underwater
+            column.get_chunk(y / 16).set_block(BlockID::Dirt, b_x as u32, (y % 16) as u32, b_z as u32);
+            for y in y + 1..=WATER_LEVEL {
+                column.set_block(BlockID::Water, b_x as u32, y as u32, b_z as u32);
+            }
+        }
+    }
+
+    // Bedrock
+    let chunk = column.get_chunk(0);
+    for b_x in 0..16 {
+        for b_z in 0..16 {
+            chunk.set_block(BlockID::Bedrock, b_x, 0, b_z);
+            chunk.set_block(BlockID::Bedrock, b_x, 1, b_z);
+            chunk.set_block(BlockID::Bedrock, b_x, 2, b_z);
+        }
+    }
+}
+
+/// The stone variants with the frequency of their noise, the lower the bigger the blobs
+const STONE_VARIANTS: [(BlockID, f64); 3] = [
+    (BlockID::Granite, 0.02),
+    (BlockID::Diorite, 0.015),
+    (BlockID::Andesite, 0.025),
+];
+
+/// Replaces the stone of a column by blobs of granite, diorite and andesite, each following its own noise
+fn place_stone_variants(noise_fns: &[SuperSimplex; 3], x: i32, z: i32, column: &ChunkColumn) {
+    for b_x in 0..16 {
+        for b_z in 0..16 {
+            let top = column.heighest_blocks.read()[16 * b_z + b_x] as u32;
+            for y in 0..=top {
+                let chunk = column.get_chunk((y / 16) as i32);
+                if chunk.get_block(b_x as u32, y % 16, b_z as u32) != BlockID::Stone {
+                    continue;
+                }
+                let (w_x, w_y, w_z) = ((16 * x + b_x as i32) as f64, y as f64, (16 * z + b_z as i32) as f64);
+                let variant = STONE_VARIANTS.iter().zip(noise_fns.iter())
+                    .find(|&(&(_, frequency), noise_fn)| {
+                        noise_fn.get(Point3::from([w_x * frequency, w_y * frequency, w_z * frequency])) > STONE_VARIANT_THRESHOLD
+                    });
+                if let Some((&(block, _), _)) = variant {
+                    chunk.set_block(block, b_x as u32, y % 16, b_z as u32);
+                }
+            }
+        }
+    }
+}
+
+/// The ores with the number of veins in a column, the height below which the veins start and their number of blocks
+const ORES: [(BlockID, u32, u32, u32); 2] = [
+    (BlockID::IronOre, 12, 80, 8),
+    (BlockID::DiamondOre, 1, 20, 6),
+];
+
+/// Replaces some of the stone of a column with veins of ore, each a random walk from a random block
+fn place_ores(world_seed: u32, x: i32, z: i32, column: &ChunkColumn) {
+    let seed = (world_seed as u64) << 32
+        ^ (x as u32 as u64).wrapping_mul(83_492_791)
+        ^ (z as u32 as u64).wrapping_mul(29_765_723);
+    let mut rng = StdRng::seed_from_u64(seed);
+    for &(ore, veins, max_height, size) in ORES.iter() {
+        for _ in 0..veins {
+            let (mut b_x, mut y, mut b_z) = (rng.gen_range(0, 16), rng.gen_range(3, max_height), rng.gen_range(0, 16));
+            for _ in 0..size {
+                let chunk = column.get_chunk((y / 16) as i32);
+                if chunk.get_block(b_x, y % 16, b_z) == BlockID::Stone {
+                    chunk.set_block(ore, b_x, y % 16, b_z);
+                }
+                // The veins stay in the column
+                match rng.gen_range(0, 3) {
+                    0 => b_x = (b_x as i32 + rng.gen_range(-1, 2)).max(0).min(15) as u32,
+                    1 => y = (y as i32 + rng.gen_range(-1, 2)).max(3) as u32,
+                    _ => b_z = (b_z as i32 + rng.gen_range(-1, 2)).max(0).min(15) as u32,
+                }
+            }
+        }
+    }
+}
+
+/// Temperature and humidity between 0 and 1 at the given block coordinates
+fn climate_at(noise_fn: &SuperSimplex, x: i32, z: i32) -> (f32, f32) {
+    let scale = 400.0;
+    let (xf, zf) = (x as f64 / scale, z as f64 / scale);
+    let temperature = noise_fn.get(Point2::from([xf, zf])) * 0.5 + 0.5;
+    // Sample far away so that humidity doesn't follow temperature
+    let humidity = noise_fn.get(Point2::from([xf + 1000.0, zf - 1000.0])) * 0.5 + 0.5;
+    (temperature.max(0.0).min(1.0) as f32, humidity.max(0.0).min(1.0) as f32)
+}
+
+/// The climate at the corners (-x -z, +x -z, -x +z, +x +z) of the chunk column (x, z)
+fn column_climate(noise_fn: &SuperSimplex, x: i32, z: i32) -> [(f32, f32); 4] {
+    [
+        climate_at(noise_fn, 16 * x, 16 * z),
+        climate_at(noise_fn, 16 * (x + 1), 16 * z),
+        climate_at(noise_fn, 16 * x, 16 * (z + 1)),
+        climate_at(noise_fn, 16 * (x + 1), 16 * (z + 1)),
+    ]
+}
+
+/// Rough height of the terrain, sampling the noise of the stone pass at a fixed altitude.
+/// Good enough to know which way is downhill without generating the columns
+fn approximate_terrain_height(noise_fn: &SuperSimplex, climate_noise_fn: &SuperSimplex, config: &WorldGenConfig, x: f64, z: f64) -> f64 {
+    let scale = config.terrain_scale;
+    let noise = noise_fn.get(Point3::from([x / scale, WATER_LEVEL as f64 / scale, z / scale]));
+    let (c_x, _, c_z, b_x, _, b_z) = ChunkManager::get_chunk_coords(x.floor() as i32, 0, z.floor() as i32);
+    let biome = BlendedBiomeResult::new(&column_climate(climate_noise_fn, c_x, c_z), b_x as usize, b_z as usize);
+    // Stone is placed where noise * amplitude + offset + height * 1.7 < 256
+    (256.0 - config.terrain_height_offset - biome.height_offset() - noise * config.terrain_amplitude * biome.height_scale()) / 1.7
+}
+
+/// At most one river starts in every RIVER_SPACING wide cell, at a position only depending on the world seed
+fn river_source(world_seed: u32, cell_x: i32, cell_z: i32) -> Option<(f64, f64)> {
+    let seed = (world_seed as u64) << 32
+        ^ (cell_x as u32 as u64).wrapping_mul(73_856_093)
+        ^ (cell_z as u32 as u64).wrapping_mul(19_349_663);
+    let mut rng = StdRng::seed_from_u64(seed);
+    if rng.gen::<f32>() >= RIVER_SOURCE_CHANCE {
+        return None;
+    }
+    Some((
+        (cell_x * RIVER_SPACING) as f64 + rng.gen::<f64>() * RIVER_SPACING as f64,
+        (cell_z * RIVER_SPACING) as f64 + rng.gen::<f64>() * RIVER_SPACING as f64,
+    ))
+}
+
+/// Follows the terrain downhill from `source`, one block per step, until the sea level or RIVER_MAX_LENGTH.
+/// Returns the position and direction of every step
+fn trace_river(noise_fn: &SuperSimplex, climate_noise_fn: &SuperSimplex, config: &WorldGenConfig, source: (f64, f64)) -> Vec<((f64, f64), (f64, f64))> {
+    let mut path = Vec::new();
+    let (mut x, mut z) = source;
+    let mut direction = (0.0, 0.0);
+
+    for _ in 0..RIVER_MAX_LENGTH {
+        if approximate_terrain_height(noise_fn, climate_noise_fn, config, x, z) <= WATER_LEVEL as f64 {
+            break;
+        }
+
+        let gradient = (
+            approximate_terrain_height(noise_fn, climate_noise_fn, config, x + 1.0, z) - approximate_terrain_height(noise_fn, climate_noise_fn, config, x - 1.0, z),
+            approximate_terrain_height(noise_fn, climate_noise_fn, config, x, z + 1.0) - approximate_terrain_height(noise_fn, climate_noise_fn, config, x, z - 1.0),
+        );
+        let slope = (gradient.0 * gradient.0 + gradient.1 * gradient.1).sqrt();
+        let downhill = if slope > 1e-6 { (-gradient.0 / slope, -gradient.1 / slope) } else { direction };
+
+        // Some momentum carries the river across flat areas
+        let (dx, dz) = (direction.0 * 0.5 + downhill.0, direction.1 * 0.5 + downhill.1);
+        let length = (dx * dx + dz * dz).sqrt();
+        if length < 1e-6 {
+            break;
+        }
+        direction = (dx / length, dz / length);
+
+        path.push(((x, z), direction));
+        x += direction.0;
+        z += direction.1;
+    }
+    path
+}
+
+/// Digs `depth` blocks down from `surface`, the height of the terrain before any river was carved,
+/// so that overlapping river steps don't dig any deeper. Only the dug blocks at or below the sea level are filled with water
+fn carve_river_bed(column: &ChunkColumn, b_x: u32, b_z: u32, surface: i32, depth: i32) {
+    // Already under the sea
+    if surface <= WATER_LEVEL {
+        return;
+    }
+
+    let bottom = surface - depth + 1;
+    for y in bottom..=surface {
+        let block = if y <= WATER_LEVEL { BlockID::Water } else { BlockID::Air };
+        column.get_chunk(y / 16).set_block(block, b_x, (y % 16) as u32, b_z);
+    }
+    // Grass doesn't grow underwater
+    if bottom >= 1 {
+        let chunk = column.get_chunk((bottom - 1) / 16);
+        if chunk.get_block(b_x, ((bottom - 1) % 16) as u32, b_z) == BlockID::GrassBlock {
+            chunk.set_block(BlockID::Dirt, b_x, ((bottom - 1) % 16) as u32, b_z);
+        }
+    }
+    // Trees check for grass at the highest block, which is now either the water or the dirt of the bed
+    let top = if bottom <= WATER_LEVEL { WATER_LEVEL } else { bottom - 1 };
+    let i = (16 * b_z + b_x) as usize;
+    let mut heighest_blocks = column.heighest_blocks.write();
+    heighest_blocks[i] = heighest_blocks[i].min(top as u16);
+}
+
+/// Carves the rivers flowing through the newly generated columns.
+/// Rivers are traced from every source that could reach them, but only these columns are dug,
+/// the others already got their part of the rivers when they were generated
+fn carve_rivers(noise_fn: &SuperSimplex, climate_noise_fn: &SuperSimplex, config: &WorldGenConfig, world_seed: u32, chunk_manager: &ChunkManager, new_columns: &[(i32, i32)]) {
+    if new_columns.is_empty() {
+        return;
+    }
+    // The terrain height of the new columns before carving, where every river bed is measured from
+    let surfaces: HashMap<(i32, i32), Box<[u16; 16 * 16]>> = new_columns
+        .iter()
+        .filter_map(|&(c_x, c_z)| {
+            chunk_manager.get_column(c_x, c_z).map(|column| ((c_x, c_z), column.heighest_blocks.read().clone()))
+        })
+        .collect();
+
+    let reach = RIVER_MAX_LENGTH as i32;
+    let min_x = new_columns.iter().map(|&(x, _)| 16 * x).min().unwrap() - reach;
+    let max_x = new_columns.iter().map(|&(x, _)| 16 * x + 16).max().unwrap() + reach;
+    let min_z = new_columns.iter().map(|&(_, z)| 16 * z).min().unwrap() - reach;
+    let max_z = new_columns.iter().map(|&(_, z)| 16 * z + 16).max().unwrap() + reach;
+
+    for cell_x in min_x.div_euclid(RIVER_SPACING)..=max_x.div_euclid(RIVER_SPACING) {
+        for cell_z in min_z.div_euclid(RIVER_SPACING)..=max_z.div_euclid(RIVER_SPACING) {
+            let source = match river_source(world_seed, cell_x, cell_z) {
+                Some(source) => source,
+                None => continue,
+            };
+
+            for ((x, z), (dx, dz)) in trace_river(noise_fn, climate_noise_fn, config, source) {
+                // U-shaped cross-section, deeper in the middle
+                for &(offset, depth) in [(-1.0, 1), (0.0, 2), (1.0, 1)].iter() {
+                    let w_x = (x - dz * offset).floor() as i32;
+                    let w_z = (z + dx * offset).floor() as i32;
+                    let (c_x, _, c_z, b_x, _, b_z) = ChunkManager::get_chunk_coords(w_x, 0, w_z);
+                    let surface = match surfaces.get(&(c_x, c_z)) {
+                        Some(surface) => surface[(16 * b_z + b_x) as usize] as i32,
+                        None => continue,
+                    };
+                    if let Some(column) = chunk_manager.get_column(c_x, c_z) {
+                        carve_river_bed(&column, b_x, b_z, surface, depth);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn generate_nether_terrain(noise_fn: &SuperSimplex, x: i32, z: i32, column: &ChunkColumn) {
+    for y in 0..NETHER_CEILING_HEIGHT {
+        for b_x in 0..16 {
+            for b_z in 0..16 {
+                let scale = 40.0;
+                let (xf, yf, zf) = (
+                    (16 * x + b_x as i32) as f64 / scale,
+                    y as f64 / (scale * 0.6),
+                    (16 * z + b_z as i32) as f64 / scale);
+
+                // The caverns close up near the floor and the ceiling
+                let distance_to_middle = (y as f64 / NETHER_CEILING_HEIGHT as f64 - 0.5).abs() * 2.0;
+                let density = noise_fn.get(Point3::from([xf, yf, zf])) + distance_to_middle.powi(4) * 1.5 - 0.1;
+
+                if density > 0.0 {
+                    column.set_block(BlockID::Netherrack, b_x, y as u32, b_z);
+                } else if y <= NETHER_LAVA_LEVEL {
+                    column.set_block(BlockID::Lava, b_x, y as u32, b_z);
+                }
+            }
+        }
+    }
+
+    // Bedrock floor and ceiling
+    for b_x in 0..16 {
+        for b_z in 0..16 {
+            for &y in [0, 1, 2, NETHER_CEILING_HEIGHT as u32].iter() {
+                column.set_block(BlockID::Bedrock, b_x, y, b_z);
+            }
+        }
+    }
+}
+
 impl ChunkLoading {
-    pub fn new() -> Self {
+    pub fn new(seed: u32) -> Self {
         let (request_chunk_column_tx, request_chunk_column_rx) = unbounded();
         let (requested_chunk_column_tx, requested_chunk_column_rx) = unbounded();
         let (upload_chunks_tx, upload_chunks_rx) = unbounded();
@@ -120,12 +478,34 @@ impl ChunkLoading {
         Self {
             noise_fn: {
                 let mut ss = SuperSimplex::new();
-                ss = ss.set_seed(*WORLD_SEED);
+                ss = ss.set_seed(seed);
+                ss
+            },
+            nether_noise_fn: {
+                let mut ss = SuperSimplex::new();
+                ss = ss.set_seed(seed.wrapping_add(1));
                 ss
             },
+            climate_noise_fn: {
+                let mut ss = SuperSimplex::new();
+                ss = ss.set_seed(seed.wrapping_add(2));
+                ss
+            },
+            dungeon_noise_fn: {
+                let mut ss = SuperSimplex::new();
+                ss = ss.set_seed(seed.wrapping_add(3));
+                ss
+            },
+            stone_variant_noise_fns: [
+                SuperSimplex::new().set_seed(seed.wrapping_add(4)),
+                SuperSimplex::new().set_seed(seed.wrapping_add(5)),
+                SuperSimplex::new().set_seed(seed.wrapping_add(6)),
+            ],
+            seed,
+            dimension: Dimension::Overworld,
             chunk_column_pool: Arc::new(RwLock::new({
                 let mut vec = Vec::new();
-                let matrix_width = (2 * (RENDER_DISTANCE + 2) + 1) as usize;
+                let matrix_width = (2 * (RENDER_DISTANCE + CHUNK_PREGENERATION_DISTANCE) + 1) as usize;
 
                 let reserved_columns = matrix_width * matrix_width;
                 vec.reserve(reserved_columns);
@@ -142,31 +522,64 @@ impl ChunkLoading {
             upload_chunks_rx,
             chunk_upload_priority_queue: BinaryHeap::new(),
             expand_chunks: Arc::new(RwLock::new(true)),
+            last_fade: Instant::now(),
             world_generation_thread_pool: rayon::ThreadPoolBuilder::new()
                 .stack_size(4 * 1024 * 1024)
                 .num_threads(*WORLD_GENERATION_THREAD_POOL_SIZE)
                 .build().unwrap(),
-            player_interaction_thread_pool: rayon::ThreadPoolBuilder::new()
+            player_interaction_thread_pool: Arc::new(rayon::ThreadPoolBuilder::new()
                 .num_threads(1)
-                .build().unwrap(),
+                .build().unwrap()),
         }
     }
 
-    fn flood_fill_unloaded_columns(chunk_manager: &ChunkManager, x: i32, z: i32, distance: i32) -> Vec<(i32, i32)> {
+    /// Shared with the systems that work in the background for the player, e.g. the pathfinding of the mobs
+    pub fn player_interaction_thread_pool(&self) -> Arc<rayon::ThreadPool> {
+        Arc::clone(&self.player_interaction_thread_pool)
+    }
+
+    /// Horizontal direction the player is moving in, zero when they are (almost) standing still
+    fn movement_direction(velocity: &Vec3) -> Vec2 {
+        let velocity = vec2(velocity.x, velocity.z);
+        if velocity.norm() < CHUNK_PREGENERATION_MIN_SPEED {
+            vec2(0.0, 0.0)
+        } else {
+            velocity.normalize()
+        }
+    }
+
+    /// How far ahead of the player's movement the column at (offset_x, offset_z) from the player is
+    fn ahead_of_movement(offset_x: i32, offset_z: i32, direction: &Vec2) -> f32 {
+        vec2(offset_x as f32, offset_z as f32).dot(direction)
+    }
+
+    /// Upload priority of a chunk, the closer to the player's chunk the higher.
+    /// The chunks changed by the player (`player_interaction` is 1) go before the ones of the world generation
+    fn chunk_upload_priority((c_x, c_y, c_z): (i32, i32, i32), (p_x, p_y, p_z): (i32, i32, i32), player_interaction: i32) -> i32 {
+        let (dx, dy, dz) = (c_x - p_x, c_y - p_y, c_z - p_z);
+        CHUNK_UPLOAD_INTERACTION_PRIORITY * player_interaction - (dx * dx + dy * dy + dz * dz)
+    }
+
+    /// The closest ring of unloaded columns within `distance`, or within `ahead_distance` in the direction the player is moving.
+    /// The columns ahead of the movement come first
+    fn flood_fill_unloaded_columns(chunk_manager: &ChunkManager, x: i32, z: i32, distance: i32, ahead_distance: i32, direction: Vec2) -> Vec<(i32, i32)> {
         assert!(distance >= 2);
+        let max_distance = distance.max(ahead_distance);
 
-        let matrix_width = 2 * distance + 1;
+        let matrix_width = 2 * max_distance + 1;
         let mut is_visited = BitVec::from_elem(
             (matrix_width * matrix_width) as usize, false);
 
         let center = (x, z);
         let matrix_index = move |x: i32, z: i32| {
-            (matrix_width * (x - center.0 + distance)
-                + (z - center.1 + distance)) as usize
+            (matrix_width * (x - center.0 + max_distance)
+                + (z - center.1 + max_distance)) as usize
         };
 
         let is_position_valid = |c_x: i32, c_z: i32| {
-            abs(x - c_x) <= distance && abs(z - c_z) <= distance
+            let within = |distance: i32| abs(x - c_x) <= distance && abs(z - c_z) <= distance;
+            within(distance) ||
+                (within(ahead_distance) && Self::ahead_of_movement(c_x - x, c_z - z, &direction) > 0.0)
         };
 
         let mut queue = VecDeque::new();
@@ -207,6 +620,11 @@ impl ChunkLoading {
                 }
             }
             if !unloaded_columns.is_empty() {
+                unloaded_columns.sort_by(|a, b| {
+                    let a = Self::ahead_of_movement(a.0 - x, a.1 - z, &direction);
+                    let b = Self::ahead_of_movement(b.0 - x, b.1 - z, &direction);
+                    b.partial_cmp(&a).unwrap_or(Ordering::Equal)
+                });
                 return unloaded_columns;
             } else {
                 queue.extend(ring.iter());
@@ -349,7 +767,7 @@ impl ChunkLoading {
 
             let mut unloaded_chunks = Vec::new();
             for &(x, y, z) in &ring {
-                if y >= 0 && y < 16 {
+                if y >= 0 && y < WORLD_HEIGHT_IN_CHUNKS {
                     let chunk = chunk_manager.get_chunk(x, y, z).unwrap();
                     if criteria(chunk.as_ref()) {
                         unloaded_chunks.push((x, y, z));
@@ -371,16 +789,62 @@ impl<'a> System<'a> for ChunkLoading {
     type SystemData = (
         ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
         Read<'a, Arc<ChunkManager>>,
+        Read<'a, DimensionManager>,
         Read<'a, TexturePack>,
+        Read<'a, Settings>,
+        Read<'a, WorldGenConfig>,
+        Write<'a, BlockEntityRegistry>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             player_physics_state,
             chunk_manager,
+            dimension_manager,
             texture_pack,
+            settings,
+            worldgen_config,
+            mut block_entity_registry,
         ) = data;
 
+        // The chests and spawners of the newly generated dungeons and Nether fortresses.
+        // The item stacks of the chests are only made here, on the thread with the GL context
+        for (position, block_entity) in chunk_manager.generated_block_entities.write().drain(..) {
+            let chest = block_entity.downcast_ref::<LootChestBlockEntity>()
+                .map(|loot_chest| ChestBlockEntity::with_items(&loot_chest.loot));
+            match chest {
+                Some(chest) => block_entity_registry.insert(position, Box::new(chest)),
+                None => block_entity_registry.insert(position, block_entity),
+            };
+        }
+
+        // The player changed dimension, give back the columns of the previous one
+        if chunk_manager.dimension != self.dimension {
+            // Wait for the world generation to stop writing into them
+            if !*self.expand_chunks.read() {
+                return;
+            }
+
+            let previous_chunk_manager = dimension_manager.get(self.dimension);
+            let columns: Vec<(i32, i32)> = previous_chunk_manager.loaded_chunk_columns.read().keys().cloned().collect();
+            for xz in columns {
+                if let Some(column) = previous_chunk_manager.remove_chunk_column(&xz) {
+                    for chunk in column.chunks.iter() {
+                        chunk.unload_from_gpu();
+                    }
+                    self.chunk_column_pool.write().push(column);
+                }
+            }
+            previous_chunk_manager.block_changelist.write().clear();
+
+            for _ in self.upload_chunks_rx.try_iter() {}
+            self.chunk_upload_priority_queue.clear();
+            self.dimension = chunk_manager.dimension;
+        }
+
+        // Changed in the options screen, the chunks beyond it are unloaded and the missing ones generated
+        let render_distance = settings.render_distance;
+        let mut player_chunk = (0, 0, 0);
         for player_physics_state in (&player_physics_state).join() {
             let state = player_physics_state.get_latest_state();
             let (c_x, c_y, c_z, _, _, _) = ChunkManager::get_chunk_coords(
@@ -388,26 +852,51 @@ impl<'a> System<'a> for ChunkLoading {
                 state.position.y as i32,
                 state.position.z as i32,
             );
+            player_chunk = (c_x, c_y, c_z);
 
-            // Remove distant chunk columns and unload their chunks
+            // Fade the chunks in and out, then remove distant chunk columns and unload their chunks
+            // once they have faded out, so that they don't pop out of view
             {
-                if *self.expand_chunks.read() {
-                    let mut columns_to_remove = Vec::new();
-                    for (&(x, z), column) in chunk_manager.loaded_chunk_columns.read().iter() {
-                        for (y, chunk) in column.chunks.iter().enumerate() {
-                            let y = y as i32;
-                            if abs(x - c_x) > RENDER_DISTANCE ||
-                                abs(y - c_y) > RENDER_DISTANCE ||
-                                abs(z - c_z) > RENDER_DISTANCE {
-                                chunk.unload_from_gpu();
-                            }
+                let now = Instant::now();
+                let fade_step = now.saturating_duration_since(self.last_fade).as_secs_f32() / CHUNK_FADE_DURATION;
+                self.last_fade = now;
+
+                let expand_chunks = *self.expand_chunks.read();
+                let mut columns_to_remove = Vec::new();
+                for (&(x, z), column) in chunk_manager.loaded_chunk_columns.read().iter() {
+                    let mut is_faded_out = true;
+                    for (y, chunk) in column.chunks.iter().enumerate() {
+                        if !*chunk.is_uploaded_to_gpu.read() {
+                            continue;
                         }
-
-                        if abs(x - c_x) > RENDER_DISTANCE + 2 ||
-                            abs(z - c_z) > RENDER_DISTANCE + 2 {
-                            columns_to_remove.push((x, z));
+                        let y = y as i32;
+                        let is_distant = abs(x - c_x) > render_distance ||
+                            abs(y - c_y) > render_distance ||
+                            abs(z - c_z) > render_distance;
+
+                        let opacity = {
+                            let mut opacity = chunk.opacity.write();
+                            *opacity = if is_distant {
+                                (*opacity - fade_step).max(0.0)
+                            } else {
+                                (*opacity + fade_step).min(1.0)
+                            };
+                            *opacity
+                        };
+                        if is_distant && opacity == 0.0 && expand_chunks {
+                            chunk.unload_from_gpu();
+                        } else {
+                            is_faded_out = false;
                         }
                     }
+
+                    // Keep the columns pre-generated ahead of the player
+                    if is_faded_out && (abs(x - c_x) > render_distance + CHUNK_PREGENERATION_DISTANCE ||
+                        abs(z - c_z) > render_distance + CHUNK_PREGENERATION_DISTANCE) {
+                        columns_to_remove.push((x, z));
+                    }
+                }
+                if expand_chunks {
                     for xz in columns_to_remove {
                         if let Some(column) = chunk_manager.remove_chunk_column(&xz) {
                             self.chunk_column_pool.write().push(column);
@@ -429,6 +918,7 @@ impl<'a> System<'a> for ChunkLoading {
                             }
                             column.heighest_blocks.write().fill(0);
                             *column.has_foliage.write() = false;
+                            *column.climate.write() = [(0.5, 0.5); 4];
                             column
                         },
                         None => {
@@ -453,7 +943,11 @@ impl<'a> System<'a> for ChunkLoading {
                     if let Some(prioritized_chunk) = self.chunk_upload_priority_queue.pop() {
                         let (c_x, c_y, c_z) = *prioritized_chunk;
                         if let Some(chunk) = chunk_manager.get_chunk(c_x, c_y, c_z) {
-                            chunk.upload_to_gpu(&texture_pack);
+                            let dirty_blocks = chunk.dirty_blocks.write().replace(Vec::new());
+                            match dirty_blocks {
+                                Some(dirty) if *chunk.is_uploaded_to_gpu.read() => chunk.upload_to_gpu_incremental(&dirty, &texture_pack),
+                                _ => chunk.upload_to_gpu(&texture_pack),
+                            }
                             *chunk.is_uploaded_to_gpu.write() = true;
                         }
                     }
@@ -464,14 +958,25 @@ impl<'a> System<'a> for ChunkLoading {
                 *self.expand_chunks.write() = false;
 
                 let noise_fn = self.noise_fn;
+                let nether_noise_fn = self.nether_noise_fn;
+                let climate_noise_fn = self.climate_noise_fn;
+                let dungeon_noise_fn = self.dungeon_noise_fn;
+                let stone_variant_noise_fns = self.stone_variant_noise_fns;
+                let worldgen_config = *worldgen_config;
+                let seed = self.seed;
+                let dimension = chunk_manager.dimension;
                 let upload_chunks_tx = self.upload_chunks_tx.clone();
                 let chunk_manager = Arc::clone(&chunk_manager);
                 let expand_chunks = Arc::clone(&self.expand_chunks);
                 let request_chunk_columns_tx = self.request_chunk_columns_tx.clone();
                 let requested_chunk_column_rx = self.requested_chunk_column_rx.clone();
+                let direction = Self::movement_direction(&state.velocity);
 
                 self.world_generation_thread_pool.spawn(move || {
-                    let new_columns = Self::flood_fill_unloaded_columns(&chunk_manager, c_x, c_z, RENDER_DISTANCE + 2);
+                    // Generate further in the direction the player is moving, so that they don't outrun the generation
+                    let new_columns = Self::flood_fill_unloaded_columns(
+                        &chunk_manager, c_x, c_z,
+                        render_distance + 2, render_distance + CHUNK_PREGENERATION_DISTANCE, direction);
                     for _ in 0..new_columns.len() {
                         request_chunk_columns_tx.send(()).unwrap();
                     }
@@ -487,6 +992,7 @@ impl<'a> System<'a> for ChunkLoading {
                         };
                         unloaded_columns.push((x, z, column));
                     }
+                    let new_column_coords: Vec<(i32, i32)> = unloaded_columns.iter().map(|&(x, z, _)| (x, z)).collect();
 
                     // Terrain generation
                     {
@@ -498,119 +1004,72 @@ impl<'a> System<'a> for ChunkLoading {
                                     let column = Arc::clone(&column);
                                     let chunk_manager = Arc::clone(&cm);
                                     s.spawn(move |_s| {
-                                        // Stone
-                                        for y in (0..16).rev() {
-                                            let y = 16 * y;
-                                            for b_y in 0..16 {
-                                                for b_x in 0..16 {
-                                                    for b_z in 0..16 {
-                                                        let x = 16 * x;
-                                                        let z = 16 * z;
-
-                                                        let scale = 90.0;
-
-                                                        // Scale the input for the noise function
-                                                        let (xf, yf, zf) = (
-                                                            (x + b_x as i32) as f64 / scale,
-                                                            (y + b_y as i32) as f64 / (scale / 1.0),
-                                                            (z + b_z as i32) as f64 / scale);
-
-                                                        let height = (y + b_y as i32) as f64;
-                                                        let noise = noise_fn.get(Point3::from([xf, yf, zf])) * 80.0
-                                                            + 64.0 + height * 1.7;
-
-                                                        if noise < 256.0 {
-                                                            column.set_block(BlockID::Stone, b_x, y as u32 + b_y, b_z);
-                                                        }
-                                                    };
-                                                }
+                                        match dimension {
+                                            Dimension::Overworld => {
+                                                let climate = column_climate(&climate_noise_fn, x, z);
+                                                generate_overworld_terrain(&noise_fn, &worldgen_config, x, z, &climate, &column);
+                                                place_stone_variants(&stone_variant_noise_fns, x, z, &column);
+                                                place_ores(seed, x, z, &column);
+                                                *column.climate.write() = climate;
                                             }
+                                            Dimension::Nether => generate_nether_terrain(&nether_noise_fn, x, z, &column),
                                         }
 
-                                        // Grass and dirt
-                                        for b_x in 0..16 {
-                                            for b_z in 0..16 {
-                                                let y = column.heighest_blocks.read()[16 * b_z + b_x] as i32;
-
-                                                let chunk_y = (y / 16) as i32;
-                                                let block_y = (y % 16) as usize;
-                                                column.get_chunk(chunk_y).set_block(BlockID::GrassBlock, b_x as u32, block_y as u32, b_z as u32);
-
-                                                for y in (y - 3)..y {
-                                                    let chunk_y = (y / 16) as i32;
-                                                    let block_y = (y % 16) as usize;
-
-                                                    let chunk = column.get_chunk(chunk_y);
-                                                    if chunk.get_block(b_x as u32, block_y as u32, b_z as u32).is_air() {
-                                                        continue;
-                                                    }
-                                                    chunk.set_block(BlockID::Dirt, b_x as u32, block_y as u32, b_z as u32);
-                                                }
-                                            }
+                                        for chunk in column.chunks.iter() {
+                                            chunk.store_compressed();
                                         }
-
-                                        // Bedrock
-                                        let chunk = column.get_chunk(0);
-                                        for b_x in 0..16 {
-                                            for b_z in 0..16 {
-                                                chunk.set_block(BlockID::Bedrock, b_x, 0, b_z);
-                                                chunk.set_block(BlockID::Bedrock, b_x, 1, b_z);
-                                                chunk.set_block(BlockID::Bedrock, b_x, 2, b_z);
-                                            }
-                                        }
-
                                         chunk_manager.add_chunk_column((x, z), column);
                                     });
                                 }
                             });
 
+                            // Rivers are carved before the trees so that none grows in a riverbed
+                            if dimension == Dimension::Overworld {
+                                carve_rivers(&noise_fn, &climate_noise_fn, &worldgen_config, seed, &chunk_manager, &new_column_coords);
+                                place_dungeons(&dungeon_noise_fn, seed, &chunk_manager, &new_column_coords);
+                            } else if dimension == Dimension::Nether {
+                                NetherFortressGenerator::new(seed).place(&chunk_manager, &new_column_coords);
+                            }
+
                             let chunk_manager = Arc::clone(&chunk_manager);
                             rayon::scope(|_s| {
-                                let unfoliated_columns = Self::flood_fill_unfoliated_columns(&chunk_manager, c_x, c_z, RENDER_DISTANCE);
+                                let unfoliated_columns = Self::flood_fill_unfoliated_columns(&chunk_manager, c_x, c_z, render_distance);
                                 for (cx, cz) in unfoliated_columns {
                                     let column = chunk_manager.get_column(cx, cz).unwrap();
                                     *column.has_foliage.write() = true;
 
+                                    // Nothing grows in the Nether
+                                    if dimension != Dimension::Overworld {
+                                        continue;
+                                    }
+
+                                    // Villages are built before the trees so that none grows in them
+                                    VillageGenerator::new(seed).place(cx, cz, &chunk_manager, |x, z| climate_at(&climate_noise_fn, x, z));
+
                                     // Trees
                                     for (x, z) in compute_tree_placement_in_chunk(
-                                            &noise_fn,
+                                            &noise_fn, &worldgen_config,
                                             (cx * 16) as f64, (cz * 16) as f64
                                         ) {
                                         let (x, z) = (x as usize, z as usize);
                                         let y = column.heighest_blocks.read()[16 * z + x] as i32;
 
-                                        if true {
-                                            let x = cx * 16 + x as i32;
-                                            let z = cz * 16 + z as i32;
-
-                                            let h = 5;
-                                            for i in y + 1..y + 1 + h {
-                                                chunk_manager.set_block(BlockID::OakLog, x, i, z);
-                                            }
-
-                                            for yy in y + h - 2..=y + h - 1 {
-                                                for xx in x - 2..=x + 2 {
-                                                    for zz in z - 2..=z + 2 {
-                                                        if xx != x || zz != z {
-                                                            chunk_manager.set_block(BlockID::OakLeaves, xx, yy, zz);
-                                                        }
-                                                    }
-                                                }
-                                            }
-
-                                            for xx in x - 1..=x + 1 {
-                                                for zz in z - 1..=z + 1 {
-                                                    if xx != x || zz != z {
-                                                        chunk_manager.set_block(BlockID::OakLeaves, xx, y + h, zz);
-                                                    }
-                                                }
-                                            }
+                                        // Trees only grow on grass, not on water nor on the bare rock of the mountains
+                                        if column.get_chunk(y / 16).get_block(x as u32, (y % 16) as u32, z as u32) != BlockID::GrassBlock {
+                                            continue;
+                                        }
 
-                                            chunk_manager.set_block(BlockID::OakLeaves, x, y + h + 1, z);
-                                            chunk_manager.set_block(BlockID::OakLeaves, x + 1, y + h + 1, z);
-                                            chunk_manager.set_block(BlockID::OakLeaves, x - 1, y + h + 1, z);
-                                            chunk_manager.set_block(BlockID::OakLeaves, x, y + h + 1, z + 1);
-                                            chunk_manager.set_block(BlockID::OakLeaves, x, y + h + 1, z - 1);
+                                        // The kind of tree depends on the climate
+                                        let (temperature, humidity) = column.climate_at(x, z);
+                                        let kind = TreeKind::from_climate(temperature, humidity);
+                                        let x = cx * 16 + x as i32;
+                                        let z = cz * 16 + z as i32;
+                                        if chunk_manager.structures.read().values().any(|bounds| bounds.contains((x, y, z))) {
+                                            continue;
+                                        }
+                                        let trunk_height = kind.random_trunk_height(&mut tree_rng(seed, x, z));
+                                        for (dx, dy, dz, block) in generate_tree(kind, trunk_height) {
+                                            chunk_manager.set_block(block, x + dx, y + dy, z + dz);
                                         }
                                     }
                                 }
@@ -620,8 +1079,9 @@ impl<'a> System<'a> for ChunkLoading {
 
                     // Chunk face culling & AO
                     let chunk_manager = Arc::clone(&chunk_manager);
+                    let player_chunk = (c_x, c_y, c_z);
                     rayon::scope(move |s| {
-                        let new_chunks = Self::flood_fill_chunks(&chunk_manager, c_x, c_y, c_z, RENDER_DISTANCE);
+                        let new_chunks = Self::flood_fill_chunks(&chunk_manager, c_x, c_y, c_z, render_distance);
                         for (c_x, c_y, c_z) in new_chunks {
                             let chunk_manager = Arc::clone(&chunk_manager);
                             let send_chunk = upload_chunks_tx.clone();
@@ -638,7 +1098,7 @@ impl<'a> System<'a> for ChunkLoading {
 
                                     if let Err(err) = send_chunk.send(PrioritizedItem {
                                         item: (c_x, c_y, c_z),
-                                        priority: 0,
+                                        priority: Self::chunk_upload_priority((c_x, c_y, c_z), player_chunk, 0),
                                     }) {
                                         error!("{}", err);
                                     }
@@ -653,16 +1113,15 @@ impl<'a> System<'a> for ChunkLoading {
 
         // Dirty chunks (changelist)
         let mut changelist_per_chunk: HashMap<(i32, i32, i32), Vec<(i32, u32, u32, u32)>> = HashMap::new();
-        for &change in &*chunk_manager.block_changelist.read() {
+        for (&(w_x, w_y, w_z), &(priority, _)) in &*chunk_manager.block_changelist.read() {
             for x in -1..=1 {
                 for y in -1..=1 {
                     for z in -1..=1 {
                         let (
                             c_x, c_y, c_z,
                             b_x, b_y, b_z,
-                        ) = ChunkManager::get_chunk_coords(change.2 + x, change.3 + y, change.4 + z);
-                        // change.0 is priority
-                        changelist_per_chunk.entry((c_x, c_y, c_z)).or_default().push((change.0, b_x, b_y, b_z));
+                        ) = ChunkManager::get_chunk_coords(w_x + x, w_y + y, w_z + z);
+                        changelist_per_chunk.entry((c_x, c_y, c_z)).or_default().push((priority, b_x, b_y, b_z));
                     }
                 }
             }
@@ -690,7 +1149,7 @@ impl<'a> System<'a> for ChunkLoading {
                         if *chunk.is_uploaded_to_gpu.read() {
                             send_chunks.send(PrioritizedItem {
                                 item: (c_x, c_y, c_z),
-                                priority: highest_priority,
+                                priority: Self::chunk_upload_priority((c_x, c_y, c_z), player_chunk, highest_priority),
                             }).unwrap();
                         }
                     }
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::vec3;
+use specs::{Join, Read, ReadStorage, System, Write};
+
+use crate::chunk_manager::ChunkManager;
+use crate::clouds::{cloud_color, create_cloud_vao, CLOUD_TEXTURE_UNIT};
+use crate::constants::{CLOUD_HEIGHT, CLOUD_RADIUS, CLOUD_SPEED, CLOUD_TEXEL_SIZE, TICKS_PER_SECOND};
+use crate::day_cycle::DayCycle;
+use crate::dimension::Dimension;
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::types::Shaders;
+
+/// Draws the layer of clouds above the player, it follows them so that it never ends
+pub struct RenderClouds {
+    vao: u32,
+}
+
+impl RenderClouds {
+    pub fn new() -> Self {
+        Self {
+            vao: create_cloud_vao(),
+        }
+    }
+}
+
+impl<'a> System<'a> for RenderClouds {
+    type SystemData = (
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, DayCycle>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_state,
+            player_physics_state,
+            chunk_manager,
+            day_cycle,
+            mut shaders,
+        ) = data;
+
+        // There is no sky in the Nether
+        if chunk_manager.dimension != Dimension::Overworld {
+            return;
+        }
+
+        let cloud_shader = shaders.get_mut("cloud_shader").unwrap();
+        cloud_shader.use_program();
+        cloud_shader.set_uniform1i("clouds", CLOUD_TEXTURE_UNIT as i32);
+        cloud_shader.set_uniform3f("color", &cloud_color(&day_cycle));
+        cloud_shader.set_uniform1f("texel_size", CLOUD_TEXEL_SIZE);
+        cloud_shader.set_uniform1f("speed", CLOUD_SPEED * TICKS_PER_SECOND);
+        cloud_shader.set_uniform1f("radius", CLOUD_RADIUS);
+
+        // Seen from below and from above, hiding what is behind without hiding what is drawn later
+        gl_call!(gl::Disable(gl::CULL_FACE));
+        gl_call!(gl::DepthMask(gl::FALSE));
+        gl_call!(gl::BindVertexArray(self.vao));
+
+        for (player_state, player_physics_state) in (&player_state, &player_physics_state).join() {
+            let camera_position = player_physics_state.get_interpolated_state().position
+                + vec3(0.0, *player_state.camera_height.get_interpolated_state(), 0.0);
+            cloud_shader.set_uniform3f("camera_position", camera_position.as_slice());
+
+            let model_matrix = Matrix4::new_translation(&vec3(camera_position.x, CLOUD_HEIGHT, camera_position.z))
+                * Matrix4::new_nonuniform_scaling(&vec3(CLOUD_RADIUS, 1.0, CLOUD_RADIUS));
+            cloud_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+            gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 6));
+        }
+
+        gl_call!(gl::DepthMask(gl::TRUE));
+        gl_call!(gl::Enable(gl::CULL_FACE));
+    }
+}
@@ -0,0 +1,105 @@
+use glfw::{Action, Modifiers, MouseButton, WindowEvent};
+use specs::{Entities, Join, Read, System, Write, WriteStorage};
+
+use crate::constants::WINDOW_HEIGHT;
+use crate::crafting::RecipeRegistry;
+use crate::crafting_table::{CraftingTableBlockEntity, CraftingTableSlot, CraftingTableUI};
+use crate::ecs::components::MainHandItemChanged;
+use crate::input::InputCache;
+use crate::inventory::Inventory;
+use crate::inventory::item::ItemStack;
+use crate::screen::Screen;
+use crate::types::BlockEntityRegistry;
+
+/// Clicking on an empty slot of the grid puts one item of the selected hotbar slot in it,
+/// clicking on a filled one gives its items back. Shift-clicking on the output crafts it
+pub struct HandleCraftingTableInput;
+
+impl<'a> System<'a> for HandleCraftingTableInput {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, InputCache>,
+        Read<'a, RecipeRegistry>,
+        Write<'a, Screen>,
+        Write<'a, BlockEntityRegistry>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, MainHandItemChanged>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            input_cache,
+            recipe_registry,
+            mut screen,
+            mut block_entity_registry,
+            mut inventory,
+            mut main_hand_item_changed,
+        ) = data;
+
+        let position = match *screen {
+            Screen::CraftingTable(position) => position,
+            _ => return,
+        };
+
+        for (e, inventory) in (&entities, &mut inventory).join() {
+            let crafting_table = match block_entity_registry.get_mut(&position)
+                .and_then(|block_entity| block_entity.downcast_mut::<CraftingTableBlockEntity>()) {
+                Some(crafting_table) => crafting_table,
+                None => {
+                    *screen = Screen::None;
+                    return;
+                }
+            };
+
+            let selected_item = inventory.get_selected_item();
+
+            for event in &input_cache.events {
+                match event {
+                    WindowEvent::MouseButton(MouseButton::Button1, Action::Press, modifiers) => {
+                        let cursor_x = input_cache.last_cursor_pos.x as f32;
+                        let cursor_y = WINDOW_HEIGHT as f32 - input_cache.last_cursor_pos.y as f32;
+
+                        match CraftingTableUI::slot_at(cursor_x, cursor_y) {
+                            Some(CraftingTableSlot::Grid(row, column)) => {
+                                let slot = &mut crafting_table.grid[row][column];
+                                if let Some(item_stack) = slot.take() {
+                                    *slot = inventory.add_item_stack(item_stack);
+                                } else if let Some(item_stack) = &mut inventory.slots[inventory.selected_hotbar_slot] {
+                                    if item_stack.amount > 1 {
+                                        item_stack.amount -= 1;
+                                        *slot = Some(ItemStack::new(1, item_stack.item));
+                                    } else {
+                                        *slot = inventory.slots[inventory.selected_hotbar_slot].take();
+                                    }
+                                }
+                                crafting_table.update_output(&recipe_registry);
+                            }
+                            Some(CraftingTableSlot::Output) if modifiers.contains(Modifiers::Shift) => {
+                                // Nothing is crafted while the inventory has no room for it
+                                if crafting_table.output.is_some() && inventory.slots.iter().any(Option::is_none) {
+                                    if let Some(output) = crafting_table.craft(&recipe_registry) {
+                                        inventory.add_item_stack(output);
+                                    }
+                                }
+                            }
+                            Some(CraftingTableSlot::Output) => {}
+                            None => {
+                                if let Some(i) = Inventory::hotbar_slot_at(cursor_x, cursor_y) {
+                                    inventory.select_item(i);
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            if inventory.get_selected_item() != selected_item {
+                if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                    error!("{}", e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::time::Instant;
+
+use specs::{Read, System, Write};
+
+use crate::constants::TICKS_PER_SECOND;
+use crate::day_cycle::DayCycle;
+use crate::difficulty::WorldAge;
+use crate::timer::Timer;
+
+pub struct AdvanceDayCycle {
+    last_time: Instant,
+}
+
+impl AdvanceDayCycle {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+        }
+    }
+}
+
+impl<'a> System<'a> for AdvanceDayCycle {
+    type SystemData = (
+        Read<'a, Timer>,
+        Write<'a, DayCycle>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            mut day_cycle,
+        ) = data;
+
+        let now = global_timer.time();
+        let dt = now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        day_cycle.advance(dt);
+    }
+}
+
+/// Counts the game ticks since the world was created
+pub struct AdvanceWorldAge {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl AdvanceWorldAge {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for AdvanceWorldAge {
+    type SystemData = (
+        Read<'a, Timer>,
+        Write<'a, WorldAge>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            mut world_age,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            world_age.ticks += 1;
+            self.accumulator -= tick_duration;
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::process::exit;
+
+use glfw::{Action, MouseButton, WindowEvent};
+use specs::{Join, Read, System, Write, WriteStorage};
+
+use crate::constants::WINDOW_HEIGHT;
+use crate::input::InputCache;
+use crate::menu::DeathButton;
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::screen::Screen;
+use crate::statistics::Statistics;
+use crate::timer::Timer;
+
+/// Opens the death screen and pauses the game when the player dies
+pub struct OpenDeathScreen;
+
+impl<'a> System<'a> for OpenDeathScreen {
+    type SystemData = (
+        Write<'a, Screen>,
+        Write<'a, Timer>,
+        Write<'a, Statistics>,
+        WriteStorage<'a, PlayerState>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut screen,
+            mut global_timer,
+            mut statistics,
+            mut player_state,
+        ) = data;
+
+        for player_state in (&mut player_state).join() {
+            if player_state.health <= 0.0 && !player_state.is_dead {
+                player_state.die("Died");
+            }
+            if player_state.is_dead && *screen != Screen::Death {
+                info!("{}", player_state.death_message);
+                statistics.deaths += 1;
+                *screen = Screen::Death;
+                global_timer.pause();
+            }
+        }
+    }
+}
+
+/// Handles the clicks on the buttons of the death screen. Respawning brings the player back to their spawn point,
+/// leaving saves the statistics and quits the game
+pub struct HandleDeathScreenInput;
+
+impl<'a> System<'a> for HandleDeathScreenInput {
+    type SystemData = (
+        Read<'a, InputCache>,
+        Read<'a, Statistics>,
+        Write<'a, Screen>,
+        Write<'a, Timer>,
+        WriteStorage<'a, PlayerState>,
+        WriteStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            input_cache,
+            statistics,
+            mut screen,
+            mut global_timer,
+            mut player_state,
+            mut player_physics_state,
+        ) = data;
+
+        if *screen != Screen::Death {
+            return;
+        }
+
+        let cursor_x = input_cache.last_cursor_pos.x as f32;
+        let cursor_y = WINDOW_HEIGHT as f32 - input_cache.last_cursor_pos.y as f32;
+
+        for (player_state, player_physics_state) in (&mut player_state, &mut player_physics_state).join() {
+            for event in &input_cache.events {
+                match event {
+                    WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                        match DeathButton::at(cursor_x, cursor_y) {
+                            Some(DeathButton::Respawn) => {
+                                player_state.respawn();
+                                player_physics_state.teleport(player_state.spawn_point);
+                                *screen = Screen::None;
+                                global_timer.resume();
+                            }
+                            Some(DeathButton::TitleScreen) => {
+                                statistics.save();
+                                exit(0);
+                            }
+                            None => {}
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
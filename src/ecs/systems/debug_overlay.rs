@@ -0,0 +1,113 @@
+use glfw::{Action, Key, WindowEvent};
+use specs::{Join, Read, System, Write, WriteStorage};
+
+use crate::action_bar::ActionBar;
+use crate::debug_overlay::DebugOverlay;
+use crate::input::InputCache;
+use crate::physics::PhysicsConfig;
+use crate::player::PlayerState;
+use crate::timer::Timer;
+
+/// Toggles the overlay when F3 is released, unless it was used in a combination like F3+F4
+pub struct ToggleDebugOverlay {
+    used_in_combination: bool,
+}
+
+impl ToggleDebugOverlay {
+    pub fn new() -> Self {
+        Self {
+            used_in_combination: false,
+        }
+    }
+}
+
+impl<'a> System<'a> for ToggleDebugOverlay {
+    type SystemData = (
+        Read<'a, InputCache>,
+        Write<'a, DebugOverlay>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            input_cache,
+            mut debug_overlay,
+        ) = data;
+
+        for event in input_cache.events.iter() {
+            match event {
+                WindowEvent::Key(Key::F3, _, Action::Press, _) => {
+                    self.used_in_combination = false;
+                }
+                WindowEvent::Key(Key::F4, _, Action::Press, _) if input_cache.is_key_pressed(Key::F3) => {
+                    self.used_in_combination = true;
+                }
+                WindowEvent::Key(Key::F3, _, Action::Release, _) => {
+                    if !self.used_in_combination {
+                        debug_overlay.visible = !debug_overlay.visible;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Switches between survival, creative and spectator mode with F3+F4
+pub struct CycleGameMode;
+
+impl<'a> System<'a> for CycleGameMode {
+    type SystemData = (
+        Read<'a, InputCache>,
+        WriteStorage<'a, PlayerState>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            input_cache,
+            mut player_state,
+        ) = data;
+
+        for event in input_cache.events.iter() {
+            if let WindowEvent::Key(Key::F4, _, Action::Press, _) = event {
+                if !input_cache.is_key_pressed(Key::F3) {
+                    continue;
+                }
+                for player_state in (&mut player_state).join() {
+                    let game_mode = player_state.game_mode.next();
+                    player_state.set_game_mode(game_mode);
+                }
+            }
+        }
+    }
+}
+
+/// Halves or doubles the speed of the game with the numpad - and +, for slow motion and fast forward
+pub struct ChangeTimeScale;
+
+impl<'a> System<'a> for ChangeTimeScale {
+    type SystemData = (
+        Read<'a, InputCache>,
+        Read<'a, Timer>,
+        Write<'a, PhysicsConfig>,
+        Write<'a, ActionBar>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            input_cache,
+            global_timer,
+            mut physics_config,
+            mut action_bar,
+        ) = data;
+
+        for event in input_cache.events.iter() {
+            let factor = match event {
+                WindowEvent::Key(Key::KpAdd, _, Action::Press, _) => 2.0,
+                WindowEvent::Key(Key::KpSubtract, _, Action::Press, _) => 0.5,
+                _ => continue,
+            };
+            physics_config.scale_time(factor);
+            action_bar.show(&format!("Game speed: {}x", physics_config.time_scale), global_timer.time());
+        }
+    }
+}
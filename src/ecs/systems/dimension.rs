@@ -0,0 +1,195 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra_glm::vec3;
+use specs::{Join, Read, System, Write, WriteStorage};
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{PORTAL_COOLDOWN, PORTAL_TRAVEL_TIME};
+use crate::dimension::{Dimension, DimensionManager, PortalRegistry};
+use crate::physics::Interpolator;
+use crate::player::PlayerPhysicsState;
+use crate::timer::Timer;
+
+/// A teleportation waiting for the destination to be generated
+struct PendingArrival {
+    /// The destination portal, or where to build it
+    position: (i32, i32, i32),
+    /// The portal the player came from, when there is no portal to arrive at yet
+    unlinked_portal: Option<(Dimension, (i32, i32, i32))>,
+}
+
+/// Sends the player to the other dimension after standing in a portal for `PORTAL_TRAVEL_TIME` seconds.
+/// A portal is built on arrival if the one the player came from isn't linked yet. The portals can't be used
+/// again for `PORTAL_COOLDOWN` seconds after arriving
+pub struct TravelThroughPortals {
+    entered_portal_at: Option<Instant>,
+    arrived_at: Option<Instant>,
+    pending_arrival: Option<PendingArrival>,
+}
+
+impl TravelThroughPortals {
+    pub fn new() -> Self {
+        Self {
+            entered_portal_at: None,
+            arrived_at: None,
+            pending_arrival: None,
+        }
+    }
+}
+
+impl<'a> System<'a> for TravelThroughPortals {
+    type SystemData = (
+        Read<'a, Timer>,
+        Write<'a, Arc<ChunkManager>>,
+        Write<'a, DimensionManager>,
+        Write<'a, PortalRegistry>,
+        WriteStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            mut chunk_manager,
+            mut dimension_manager,
+            mut portal_registry,
+            mut player_physics_state,
+        ) = data;
+
+        for player_physics_state in (&mut player_physics_state).join() {
+            if let Some(arrival) = &self.pending_arrival {
+                let (x, y, z) = arrival.position;
+                // The destination doesn't exist yet, keep the player from falling into the void
+                let (c_x, _, c_z, b_x, _, b_z) = ChunkManager::get_chunk_coords(x, y, z);
+                let column = match chunk_manager.get_column(c_x, c_z) {
+                    Some(column) => column,
+                    None => {
+                        teleport(player_physics_state, arrival.position);
+                        continue;
+                    }
+                };
+
+                let position = match arrival.unlinked_portal {
+                    Some((dimension, portal)) => {
+                        // Arrive on the surface of the Overworld
+                        let y = if dimension_manager.current == Dimension::Overworld {
+                            column.heighest_blocks.read()[16 * b_z as usize + b_x as usize] as i32 + 1
+                        } else {
+                            y
+                        };
+                        let new_portal = build_portal(&chunk_manager, (x, y, z));
+                        portal_registry.link(dimension, portal, new_portal);
+                        (x, y, z)
+                    }
+                    None => {
+                        let (x, y, z) = portal_exit(&chunk_manager, (x, y, z));
+                        chunk_manager.put_block(BlockID::Air, x, y, z);
+                        chunk_manager.put_block(BlockID::Air, x, y + 1, z);
+                        (x, y, z)
+                    }
+                };
+                teleport(player_physics_state, position);
+                self.pending_arrival = None;
+                self.arrived_at = Some(global_timer.time());
+                continue;
+            }
+
+            let position = player_physics_state.get_latest_state().position;
+            let (x, y, z) = (position.x.floor() as i32, position.y.floor() as i32, position.z.floor() as i32);
+            let portal = [y, y + 1].iter()
+                .find(|&&y| chunk_manager.get_block(x, y, z) == Some(BlockID::NetherPortal))
+                .map(|&y| (x, y, z));
+            let portal = match portal {
+                Some(portal) => portal,
+                None => {
+                    self.entered_portal_at = None;
+                    continue;
+                }
+            };
+
+            let now = global_timer.time();
+            let is_cooling_down = self.arrived_at
+                .map_or(false, |arrived_at| now.saturating_duration_since(arrived_at).as_secs_f32() < PORTAL_COOLDOWN);
+            if is_cooling_down {
+                self.entered_portal_at = None;
+                continue;
+            }
+            let entered_portal_at = *self.entered_portal_at.get_or_insert(now);
+            if now.saturating_duration_since(entered_portal_at).as_secs_f32() < PORTAL_TRAVEL_TIME {
+                continue;
+            }
+            self.entered_portal_at = None;
+
+            let portal = portal_base(&chunk_manager, portal);
+            let from = dimension_manager.current;
+            let to = from.other();
+            let arrival = match portal_registry.get(from, portal) {
+                Some(linked_portal) => PendingArrival {
+                    position: linked_portal,
+                    unlinked_portal: None,
+                },
+                None => PendingArrival {
+                    position: from.convert_position(to, portal),
+                    unlinked_portal: Some((from, portal)),
+                },
+            };
+            info!("Travelling from the {:?} to the {:?}", from, to);
+
+            dimension_manager.current = to;
+            *chunk_manager = dimension_manager.current_chunk_manager();
+            teleport(player_physics_state, arrival.position);
+            self.pending_arrival = Some(arrival);
+        }
+    }
+}
+
+/// Moves the player to the center of the block at (x, y, z)
+fn teleport(player_physics_state: &mut Interpolator<PlayerPhysicsState>, (x, y, z): (i32, i32, i32)) {
+    player_physics_state.teleport(vec3(x as f32 + 0.5, y as f32, z as f32 + 0.5));
+}
+
+/// The lowest portal block of the portal containing `portal`, on its -x or -z side
+fn portal_base(chunk_manager: &ChunkManager, (mut x, mut y, mut z): (i32, i32, i32)) -> (i32, i32, i32) {
+    let is_portal = |x: i32, y: i32, z: i32| chunk_manager.get_block(x, y, z) == Some(BlockID::NetherPortal);
+    while is_portal(x, y - 1, z) {
+        y -= 1;
+    }
+    while is_portal(x - 1, y, z) {
+        x -= 1;
+    }
+    while is_portal(x, y, z - 1) {
+        z -= 1;
+    }
+    (x, y, z)
+}
+
+/// Where the player stands when arriving at the portal of base `portal`, in front of it
+fn portal_exit(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> (i32, i32, i32) {
+    if chunk_manager.get_block(x, y, z + 1) == Some(BlockID::NetherPortal) {
+        (x + 1, y, z)
+    } else {
+        (x, y, z + 1)
+    }
+}
+
+/// Carves a small room on an obsidian floor around `position` with a framed portal on its north side.
+/// Returns the base of the new portal.
+fn build_portal(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> (i32, i32, i32) {
+    for xx in x - 1..=x + 2 {
+        for zz in z..=z + 1 {
+            chunk_manager.put_block(BlockID::Obsidian, xx, y - 1, zz);
+            for yy in y..=y + 2 {
+                chunk_manager.put_block(BlockID::Air, xx, yy, zz);
+            }
+        }
+    }
+    for xx in x - 1..=x + 2 {
+        for yy in y - 1..=y + 3 {
+            let is_frame = xx == x - 1 || xx == x + 2 || yy == y - 1 || yy == y + 3;
+            let block = if is_frame { BlockID::Obsidian } else { BlockID::NetherPortal };
+            chunk_manager.put_block(block, xx, yy, z - 1);
+        }
+    }
+    (x, y, z - 1)
+}
@@ -0,0 +1,108 @@
+use glfw::{Action, MouseButton, WindowEvent};
+use specs::{Entities, Join, Read, System, Write, WriteStorage};
+
+use crate::action_bar::ActionBar;
+use crate::constants::WINDOW_HEIGHT;
+use crate::ecs::components::MainHandItemChanged;
+use crate::enchanting::{EnchantingTableBlockEntity, enchantment_label, offer_at, random_enchantment};
+use crate::input::InputCache;
+use crate::inventory::Inventory;
+use crate::player::PlayerState;
+use crate::screen::Screen;
+use crate::timer::Timer;
+use crate::types::BlockEntityRegistry;
+
+/// Clicking on an offer the player has the levels for puts a random enchantment on the selected tool
+/// and takes the levels it costs. Clicking on the hotbar selects another item
+pub struct HandleEnchantingTableInput;
+
+impl<'a> System<'a> for HandleEnchantingTableInput {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Timer>,
+        Read<'a, InputCache>,
+        Read<'a, BlockEntityRegistry>,
+        Write<'a, Screen>,
+        Write<'a, ActionBar>,
+        WriteStorage<'a, PlayerState>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, MainHandItemChanged>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            global_timer,
+            input_cache,
+            block_entity_registry,
+            mut screen,
+            mut action_bar,
+            mut player_state,
+            mut inventory,
+            mut main_hand_item_changed,
+        ) = data;
+
+        let position = match *screen {
+            Screen::EnchantingTable(position) => position,
+            _ => return,
+        };
+
+        for (e, player_state, inventory) in (&entities, &mut player_state, &mut inventory).join() {
+            let enchanting_table = match block_entity_registry.get(&position)
+                .and_then(|block_entity| block_entity.downcast_ref::<EnchantingTableBlockEntity>()) {
+                Some(enchanting_table) => enchanting_table,
+                None => {
+                    *screen = Screen::None;
+                    return;
+                }
+            };
+
+            let selected_item = inventory.get_selected_item();
+
+            for event in &input_cache.events {
+                match event {
+                    WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                        let cursor_x = input_cache.last_cursor_pos.x as f32;
+                        let cursor_y = WINDOW_HEIGHT as f32 - input_cache.last_cursor_pos.y as f32;
+
+                        match offer_at(cursor_x, cursor_y) {
+                            Some(offer) => {
+                                let cost = enchanting_table.offers()[offer];
+                                if player_state.level < cost {
+                                    continue;
+                                }
+                                let item_stack = match &mut inventory.slots[inventory.selected_hotbar_slot] {
+                                    Some(item_stack) => item_stack,
+                                    None => continue,
+                                };
+                                let enchantment = match random_enchantment(item_stack.item, cost, &mut rand::thread_rng()) {
+                                    Some(enchantment) => enchantment,
+                                    None => continue,
+                                };
+
+                                // The progress towards the next level is kept
+                                player_state.spend_levels(cost);
+                                item_stack.enchantments.retain(|&(id, _)| id != enchantment.0);
+                                item_stack.enchantments.push(enchantment);
+                                action_bar.show(&format!("Enchanted with {}", enchantment_label(enchantment)), global_timer.time());
+                                info!("Enchanted {:?} with {:?}", item_stack.item, enchantment);
+                            }
+                            None => {
+                                if let Some(i) = Inventory::hotbar_slot_at(cursor_x, cursor_y) {
+                                    inventory.select_item(i);
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            if inventory.get_selected_item() != selected_item {
+                if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                    error!("{}", e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::ffi::c_void;
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::{pi, vec3};
+use specs::{Join, Read, ReadStorage, System, Write};
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::TICKS_PER_SECOND;
+use crate::fence_gate::FenceGateBlockEntity;
+use crate::player::PlayerState;
+use crate::redstone::RedstoneSimulator;
+use crate::shapes::fence_gate_mesh;
+use crate::timer::Timer;
+use crate::types::{BlockEntityRegistry, Shaders, TexturePack};
+use crate::util::is_sphere_in_frustum;
+
+/// Opens the fence gates whose redstone input got powered and closes the ones that lost their power
+pub struct UpdateFenceGates {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl UpdateFenceGates {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateFenceGates {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, RedstoneSimulator>,
+        Write<'a, BlockEntityRegistry>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            redstone_simulator,
+            mut block_entity_registry,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            for (&(x, y, z), block_entity) in block_entity_registry.iter_mut() {
+                let gate = match block_entity.downcast_mut::<FenceGateBlockEntity>() {
+                    Some(gate) => gate,
+                    None => continue,
+                };
+                // The chunk isn't loaded
+                let meta = match chunk_manager.get_block_meta(x, y, z) {
+                    Some(meta) => meta,
+                    None => continue,
+                };
+
+                // A powered gate is kept open, it closes once the power goes away
+                let is_powered = redstone_simulator.input_power(&chunk_manager, (x, y, z)) > 0;
+                if is_powered && !meta.is_open() {
+                    chunk_manager.set_block_meta(meta.with_open(true), x, y, z);
+                    gate.swing(true);
+                } else if !is_powered && gate.is_powered && meta.is_open() {
+                    chunk_manager.set_block_meta(meta.with_open(false), x, y, z);
+                    gate.swing(false);
+                }
+                gate.is_powered = is_powered;
+            }
+            self.accumulator -= tick_duration;
+        }
+    }
+}
+
+pub struct RenderFenceGates {
+    vao: u32,
+    vbo: u32,
+    is_uploaded: bool,
+}
+
+impl RenderFenceGates {
+    pub fn new() -> Self {
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        // Position
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+        // Texture coords
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 1, 3 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+        // Normals
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 2));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 2, 3 as i32, gl::FLOAT, gl::FALSE, 6 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 2, 0));
+
+        let mut vbo = 0;
+        gl_call!(gl::CreateBuffers(1, &mut vbo));
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (9 * std::mem::size_of::<f32>()) as i32));
+
+        Self {
+            vao,
+            vbo,
+            is_uploaded: false,
+        }
+    }
+
+    /// The panel, 36 vertices
+    fn upload_model(&mut self, texture_pack: &TexturePack) {
+        let uvs = texture_pack.get(&BlockID::OakFenceGate).unwrap().get_uv_of_every_face();
+        let vbo_data = fence_gate_mesh(uvs);
+
+        gl_call!(gl::NamedBufferData(self.vbo,
+                    (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                    vbo_data.as_ptr() as *const c_void,
+                    gl::STATIC_DRAW));
+        self.is_uploaded = true;
+    }
+}
+
+impl<'a> System<'a> for RenderFenceGates {
+    type SystemData = (
+        Read<'a, BlockEntityRegistry>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        ReadStorage<'a, PlayerState>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            block_entity_registry,
+            chunk_manager,
+            texture_pack,
+            player_state,
+            mut shaders,
+        ) = data;
+
+        if !self.is_uploaded {
+            self.upload_model(&texture_pack);
+        }
+
+        for player_state in (&player_state).join() {
+            let view_projection = player_state.projection_matrix * player_state.view_matrix;
+
+            let model_shader = shaders.get_mut("model_shader").unwrap();
+            model_shader.use_program();
+            model_shader.set_uniform1i("tex", 0);
+            gl_call!(gl::BindVertexArray(self.vao));
+
+            for (&(x, y, z), block_entity) in block_entity_registry.iter() {
+                let gate = match block_entity.downcast_ref::<FenceGateBlockEntity>() {
+                    Some(gate) => gate,
+                    None => continue,
+                };
+                // The chunk isn't loaded
+                let meta = match chunk_manager.get_block_meta(x, y, z) {
+                    Some(meta) => meta,
+                    None => continue,
+                };
+
+                let center = vec3(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5);
+                if !is_sphere_in_frustum(&view_projection, &center, 0.87) {
+                    continue;
+                }
+
+                let base_model_matrix = {
+                    let translate_matrix = Matrix4::new_translation(&vec3(
+                        x as f32 + 0.5, y as f32, z as f32 + 0.5));
+                    let rotate_matrix = Matrix4::from_euler_angles(
+                        0.0f32,
+                        -(meta.facing() as f32) * pi::<f32>() / 2.0,
+                        0.0,
+                    );
+                    let center_matrix = Matrix4::new_translation(&vec3(-0.5, 0.0, -0.5));
+                    translate_matrix * rotate_matrix * center_matrix
+                };
+
+                // The panel swings away from the front of the gate around its hinge
+                let model_matrix = {
+                    let to_hinge = Matrix4::new_translation(&vec3(0.0, 0.0, 0.5));
+                    let rotate_matrix = Matrix4::from_euler_angles(0.0, gate.angle_current.to_radians(), 0.0);
+                    let from_hinge = Matrix4::new_translation(&vec3(0.0, 0.0, -0.5));
+                    base_model_matrix * to_hinge * rotate_matrix * from_hinge
+                };
+
+                model_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+                gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 36));
+            }
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use glfw::{Action, MouseButton, WindowEvent};
+use nalgebra_glm::{floor, vec3, Vec3};
+use specs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::TICKS_PER_SECOND;
+use crate::ecs::components::MainHandItemChanged;
+use crate::firework::FireworkEntity;
+use crate::input::InputCache;
+use crate::inventory::Inventory;
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::screen::Screen;
+use crate::timer::Timer;
+use crate::types::{ParticleSystems, TexturePack};
+
+/// Launches the firework rocket held by the player when right-clicking, from the top of the targeted block
+/// or from the feet of the player. The meta of the rocket's item stack is its flight time
+pub struct LaunchFireworks;
+
+impl<'a> System<'a> for LaunchFireworks {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, InputCache>,
+        Read<'a, Screen>,
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, FireworkEntity>,
+        WriteStorage<'a, MainHandItemChanged>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            input_cache,
+            screen,
+            player_state,
+            player_physics_state,
+            mut inventory,
+            mut fireworks,
+            mut main_hand_item_changed,
+        ) = data;
+
+        if screen.is_open() {
+            return;
+        }
+
+        let right_clicked = input_cache.events.iter().any(|event| match event {
+            WindowEvent::MouseButton(MouseButton::Button2, Action::Press, _) => true,
+            _ => false
+        });
+        if !right_clicked {
+            return;
+        }
+
+        let mut launched = Vec::new();
+        for (e, player_state, player_physics_state, inventory) in (&entities, &player_state, &player_physics_state, &mut inventory).join() {
+            let flight_time = match inventory.get_selected_item_stack() {
+                Some(item_stack) if item_stack.item == BlockID::FireworkRocket => item_stack.meta,
+                _ => continue,
+            };
+            if player_state.is_spectator() {
+                continue;
+            }
+
+            let position = match player_state.targeted_block {
+                Some(((x, y, z), normal)) => vec3(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5)
+                    + vec3(normal.x as f32, normal.y as f32, normal.z as f32) * 0.6,
+                None => player_physics_state.get_latest_state().position,
+            };
+            launched.push(FireworkEntity::launched(position, flight_time, &mut rand::thread_rng()));
+            inventory.consume_selected_item();
+            info!("Launched a firework rocket");
+
+            if inventory.get_selected_item() != Some(BlockID::FireworkRocket) {
+                if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                    error!("{}", e);
+                }
+            }
+        }
+
+        for firework in launched {
+            if let Err(e) = fireworks.insert(entities.create(), firework) {
+                error!("{}", e);
+            }
+        }
+    }
+}
+
+/// Moves the firework rockets, leaving a trail of sparks behind them, and makes them burst once their flight
+/// time is over or when they run into a block
+pub struct UpdateFireworks {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl UpdateFireworks {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateFireworks {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        Write<'a, ParticleSystems>,
+        WriteStorage<'a, FireworkEntity>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            global_timer,
+            chunk_manager,
+            texture_pack,
+            mut particle_systems,
+            mut fireworks,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let particle_system = particle_systems.get_mut("firework_particles").unwrap();
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        let mut ticks = 0;
+        while self.accumulator >= tick_duration {
+            self.accumulator -= tick_duration;
+            ticks += 1;
+        }
+
+        for (e, firework) in (&entities, &mut fireworks).join() {
+            for _ in 0..ticks {
+                let next_position: Vec3 = firework.position + firework.velocity * tick_duration;
+                let cell = floor(&next_position);
+                let is_blocked = chunk_manager.is_solid_block_at(cell.x as i32, cell.y as i32, cell.z as i32);
+                if !is_blocked {
+                    firework.position = next_position;
+                }
+                firework.ticks_flown += 1;
+                particle_system.emit_firework_trail_particle(firework.position, &texture_pack);
+
+                if is_blocked || firework.should_detonate() {
+                    particle_system.emit_firework_burst(firework.position, &firework.colors, &texture_pack);
+                    if let Err(e) = entities.delete(e) {
+                        error!("{}", e);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::time::Instant;
-use specs::System;
+use specs::{System, Write};
+
+use crate::debug_overlay::DebugOverlay;
 
 const MAXIMUM_OPTIMAL_FRAMETIME: f32 = 1.0 / 60.0;
 
@@ -10,9 +12,9 @@ pub struct FpsCounter {
 }
 
 impl<'a> System<'a> for FpsCounter {
-    type SystemData = ();
+    type SystemData = Write<'a, DebugOverlay>;
 
-    fn run(&mut self, _data: Self::SystemData) {
+    fn run(&mut self, mut debug_overlay: Self::SystemData) {
         let current_time = Instant::now();
         self.nb_frames += 1;
 
@@ -26,6 +28,7 @@ impl<'a> System<'a> for FpsCounter {
 
         if current_time.duration_since(self.last_second).as_secs_f32() >= 1.0 {
             info!("{} fps", self.nb_frames);
+            debug_overlay.fps = self.nb_frames;
             self.nb_frames = 0;
             self.last_second = current_time;
         }
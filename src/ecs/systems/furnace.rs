@@ -0,0 +1,89 @@
+use glfw::{Action, MouseButton, WindowEvent};
+use specs::{Entities, Join, Read, System, Write, WriteStorage};
+
+use crate::constants::WINDOW_HEIGHT;
+use crate::ecs::components::MainHandItemChanged;
+use crate::furnace::{FUEL_BURN_TIMES, FurnaceBlockEntity, FurnaceSlot, FurnaceUI, SMELTING_RESULTS};
+use crate::input::InputCache;
+use crate::inventory::Inventory;
+use crate::screen::Screen;
+use crate::types::BlockEntityRegistry;
+
+pub struct HandleFurnaceInput;
+
+impl<'a> System<'a> for HandleFurnaceInput {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, InputCache>,
+        Write<'a, Screen>,
+        Write<'a, BlockEntityRegistry>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, MainHandItemChanged>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            input_cache,
+            mut screen,
+            mut block_entity_registry,
+            mut inventory,
+            mut main_hand_item_changed,
+        ) = data;
+
+        let position = match *screen {
+            Screen::Furnace(position) => position,
+            _ => return,
+        };
+
+        for (e, inventory) in (&entities, &mut inventory).join() {
+            let furnace = match block_entity_registry.get_mut(&position)
+                .and_then(|block_entity| block_entity.downcast_mut::<FurnaceBlockEntity>()) {
+                Some(furnace) => furnace,
+                None => {
+                    *screen = Screen::None;
+                    return;
+                }
+            };
+
+            let selected_item = inventory.get_selected_item();
+
+            for event in &input_cache.events {
+                match event {
+                    WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                        let cursor_x = input_cache.last_cursor_pos.x as f32;
+                        let cursor_y = WINDOW_HEIGHT as f32 - input_cache.last_cursor_pos.y as f32;
+
+                        if let Some(slot) = FurnaceUI::slot_at(cursor_x, cursor_y) {
+                            let slot = furnace.slot_mut(slot);
+                            if let Some(item_stack) = slot.take() {
+                                *slot = inventory.add_item_stack(item_stack);
+                            }
+                        } else if let Some(i) = Inventory::hotbar_slot_at(cursor_x, cursor_y) {
+                            // Smeltable items go to the input slot, fuel goes below
+                            if let Some(item_stack) = inventory.slots[i].clone() {
+                                let furnace_slot = if SMELTING_RESULTS.contains_key(&item_stack.item) {
+                                    furnace.slot_mut(FurnaceSlot::Input)
+                                } else if FUEL_BURN_TIMES.contains_key(&item_stack.item) {
+                                    furnace.slot_mut(FurnaceSlot::Fuel)
+                                } else {
+                                    continue;
+                                };
+                                if furnace_slot.is_none() {
+                                    *furnace_slot = inventory.slots[i].take();
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            if inventory.get_selected_item() != selected_item {
+                if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                    error!("{}", e);
+                }
+            }
+        }
+    }
+}
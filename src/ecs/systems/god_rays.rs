@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use nalgebra_glm::vec3;
+use specs::{Join, Read, ReadStorage, System, Write};
+
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{ENABLE_GOD_RAYS, GOD_RAYS_INTENSITY, SUN_COLOR, SUN_DIRECTION, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::dimension::Dimension;
+use crate::god_rays::GodRayPass;
+use crate::player::PlayerState;
+use crate::types::Shaders;
+use crate::util::Forward;
+
+/// Draws the light shafts of the sun on top of the world, before the GUI
+pub struct RenderGodRays {
+    god_ray_pass: GodRayPass,
+}
+
+impl RenderGodRays {
+    pub fn new() -> Self {
+        Self {
+            god_ray_pass: GodRayPass::new(WINDOW_WIDTH as i32, WINDOW_HEIGHT as i32),
+        }
+    }
+}
+
+impl<'a> System<'a> for RenderGodRays {
+    type SystemData = (
+        ReadStorage<'a, PlayerState>,
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_state,
+            chunk_manager,
+            mut shaders,
+        ) = data;
+
+        // There is no sky in the Nether
+        if !ENABLE_GOD_RAYS || chunk_manager.dimension != Dimension::Overworld {
+            return;
+        }
+
+        let sun_direction = vec3(SUN_DIRECTION.0, SUN_DIRECTION.1, SUN_DIRECTION.2).normalize();
+        // The sun is under the horizon
+        if sun_direction.y <= 0.0 {
+            return;
+        }
+
+        for player_state in (&player_state).join() {
+            // The rays are the strongest when looking at the sun
+            let view_direction = player_state.rotation.forward().normalize();
+            let intensity = view_direction.dot(&sun_direction).max(0.0) * GOD_RAYS_INTENSITY;
+            if intensity <= 0.0 {
+                continue;
+            }
+
+            self.god_ray_pass.render(
+                &player_state.view_matrix, &player_state.projection_matrix,
+                &sun_direction, SUN_COLOR, intensity, &mut shaders);
+        }
+    }
+}
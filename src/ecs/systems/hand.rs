@@ -4,7 +4,8 @@ use nalgebra::{Matrix4, Vector3};
 use nalgebra_glm::vec3;
 use specs::{Join, Read, ReadStorage, System, Write, WriteStorage};
 
-use crate::constants::{FAR_PLANE, NEAR_PLANE, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::chunk::BlockID;
+use crate::constants::{CROSSBOW_CHARGE_DURATION, CROSSBOW_CHARGED_ANGLE, FAR_PLANE, NEAR_PLANE, WINDOW_HEIGHT, WINDOW_WIDTH};
 use crate::ecs::components::MainHandItemChanged;
 use crate::inventory::Inventory;
 use crate::main_hand::MainHand;
@@ -94,7 +95,8 @@ impl<'a> System<'a> for RenderMainHand {
                 self.y_velocity = 0.0;
             }
 
-            if main_hand.showing_item.is_none() {
+            // Spectators have no hand
+            if main_hand.showing_item.is_none() || player_state.is_spectator() {
                 return;
             }
 
@@ -126,6 +128,14 @@ impl<'a> System<'a> for RenderMainHand {
 
                 let rotate_matrix = nalgebra_glm::rotation(-35.0f32.to_radians(), &up) * rotate_matrix;
 
+                // A charging crossbow is raised little by little, until it points up when charged
+                let charge = match (main_hand.showing_item, player_state.crossbow_charge_started) {
+                    (Some(BlockID::Crossbow), Some(started)) =>
+                        (global_timer.time().saturating_duration_since(started).as_secs_f32() / CROSSBOW_CHARGE_DURATION).min(1.0),
+                    _ => 0.0,
+                };
+                let rotate_matrix = nalgebra_glm::rotation(charge * CROSSBOW_CHARGED_ANGLE.to_radians(), &right) * rotate_matrix;
+
                 translate_matrix * rotate_matrix * translate_matrix2
             };
 
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use specs::{Join, Read, System, WriteStorage};
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::inventory::Inventory;
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::timer::Timer;
+
+/// Hurts the player landing from too high and shakes the camera. Flying players and the ones landing in water are unharmed
+pub struct UpdatePlayerHealth;
+
+impl<'a> System<'a> for UpdatePlayerHealth {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        WriteStorage<'a, PlayerState>,
+        WriteStorage<'a, Interpolator<PlayerPhysicsState>>,
+        WriteStorage<'a, Inventory>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            mut player_state,
+            mut player_physics_state,
+            mut inventory,
+        ) = data;
+
+        for (player_state, player_physics_state, inventory) in (&mut player_state, &mut player_physics_state, &mut inventory).join() {
+            let physics_state = player_physics_state.get_latest_state_mut();
+            let fall_velocity = std::mem::take(&mut physics_state.last_fall_velocity);
+            if fall_velocity <= 0.0 || player_state.is_flying || player_state.is_dead
+                || physics_state.is_touching_block(BlockID::Water, &chunk_manager) {
+                continue;
+            }
+
+            let damage = player_state.take_fall_damage(fall_velocity, &mut inventory.armor, global_timer.time());
+            if damage <= 0.0 {
+                continue;
+            }
+            info!("Fell at {:.1} blocks per second, took {:.1} damage", fall_velocity, damage);
+            player_state.camera_shake_started = Some(global_timer.time());
+            if player_state.health <= 0.0 {
+                player_state.die("Fell from a high place");
+            }
+        }
+    }
+}
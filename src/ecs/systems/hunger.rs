@@ -0,0 +1,112 @@
+use std::time::Instant;
+
+use glfw::{Action, MouseButton, WindowEvent};
+use specs::{Entities, Join, Read, System, WriteStorage};
+
+use crate::ecs::components::MainHandItemChanged;
+use crate::input::InputCache;
+use crate::inventory::Inventory;
+use crate::player::PlayerState;
+use crate::screen::Screen;
+use crate::timer::Timer;
+
+/// Makes the player hungry over time, the food level drives health regeneration and starvation
+pub struct UpdateHunger {
+    last_time: Instant,
+}
+
+impl UpdateHunger {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateHunger {
+    type SystemData = (
+        Read<'a, Timer>,
+        WriteStorage<'a, PlayerState>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            mut player_state,
+        ) = data;
+
+        let now = global_timer.time();
+        let dt = now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        for player_state in (&mut player_state).join() {
+            // Spectators never get hungry
+            if player_state.is_spectator() {
+                continue;
+            }
+            player_state.update_hunger(dt);
+            if player_state.health <= 0.0 && !player_state.is_dead {
+                player_state.die("Starved to death");
+            }
+        }
+    }
+}
+
+/// Eats the selected food item when right-clicking without aiming at a block
+pub struct EatFood;
+
+impl<'a> System<'a> for EatFood {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, InputCache>,
+        Read<'a, Screen>,
+        WriteStorage<'a, PlayerState>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, MainHandItemChanged>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            input_cache,
+            screen,
+            mut player_state,
+            mut inventory,
+            mut main_hand_item_changed,
+        ) = data;
+
+        if screen.is_open() {
+            return;
+        }
+
+        let right_clicked = input_cache.events.iter().any(|event| match event {
+            WindowEvent::MouseButton(MouseButton::Button2, Action::Press, _) => true,
+            _ => false
+        });
+        if !right_clicked {
+            return;
+        }
+
+        for (e, player_state, inventory) in (&entities, &mut player_state, &mut inventory).join() {
+            if player_state.targeted_block.is_some() {
+                continue;
+            }
+
+            let food = match inventory.get_selected_item() {
+                Some(food) => food,
+                None => continue,
+            };
+            if let Some((food_points, saturation)) = food.food_value() {
+                player_state.eat(food_points, saturation);
+                inventory.consume_selected_item();
+                info!("Ate {:?}, food level: {}", food, player_state.food_level);
+
+                if inventory.get_selected_item() != Some(food) {
+                    if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                        error!("{}", e);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,10 +1,12 @@
 use std::process::exit;
 use std::sync::mpsc::Receiver;
 
-use glfw::{Action, Context, Glfw, Key, Window, WindowEvent};
-use specs::{System, Write};
+use glfw::{Action, Context, CursorMode, Glfw, Key, Window, WindowEvent};
+use specs::{Read, System, Write};
 
 use crate::input::InputCache;
+use crate::screen::Screen;
+use crate::statistics::Statistics;
 use crate::timer::Timer;
 
 pub struct ReadWindowEvents {
@@ -17,31 +19,43 @@ impl<'a> System<'a> for ReadWindowEvents {
     type SystemData = (
         Write<'a, InputCache>,
         Write<'a, Timer>,
+        Read<'a, Screen>,
+        Read<'a, Statistics>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             mut input_cache,
             mut global_timer,
+            screen,
+            statistics,
         ) = data;
 
         if self.window.should_close() {
+            statistics.save();
             exit(0);
         }
 
         self.window.swap_buffers();
 
+        // Release the cursor while a screen is open
+        let cursor_mode = if screen.is_open() {
+            CursorMode::Normal
+        } else {
+            CursorMode::Disabled
+        };
+        if self.window.get_cursor_mode() != cursor_mode {
+            self.window.set_cursor_mode(cursor_mode);
+        }
+
         input_cache.events.clear();
+        input_cache.dash_events.clear();
         self.glfw.poll_events();
         for (_, event) in glfw::flush_messages(&self.events) {
             input_cache.handle_event(&event);
 
             match event {
-                WindowEvent::Key(Key::Escape, _, Action::Press, _) => {
-                    self.window.set_should_close(true);
-                }
-
-                WindowEvent::Key(Key::P, _, Action::Press, _) => {
+                WindowEvent::Key(Key::P, _, Action::Press, _) if !screen.captures_keyboard() && !screen.is_menu() => {
                     if global_timer.is_paused() {
                         global_timer.resume()
                     } else {
@@ -1,12 +1,19 @@
-use glfw::{MouseButton, WindowEvent};
+use glfw::{Action, Modifiers, MouseButton, WindowEvent};
+use nalgebra_glm::vec3;
 use specs::{Entities, Join, Read, ReadStorage, System, WriteStorage};
 
 use crate::chunk_manager::ChunkManager;
+use crate::constants::WINDOW_HEIGHT;
 use crate::ecs::components::MainHandItemChanged;
 use crate::input::InputCache;
-use crate::inventory::Inventory;
+use crate::inventory::{HOTBAR_SIZE, INVENTORY_SIZE, Inventory};
+use crate::inventory::armor::armor_properties;
 use crate::inventory::item::ItemStack;
-use crate::player::PlayerState;
+use crate::item_entity::ItemEntity;
+use crate::physics::Interpolator;
+use crate::player::{GameMode, PlayerPhysicsState, PlayerState};
+use crate::screen::Screen;
+use crate::util::Forward;
 use std::sync::Arc;
 
 pub struct InventoryHandleInput;
@@ -25,9 +32,12 @@ impl<'a> System<'a> for InventoryHandleInput {
         Entities<'a>,
         Read<'a, InputCache>,
         Read<'a, Arc<ChunkManager>>,
+        Read<'a, Screen>,
         ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
         WriteStorage<'a, Inventory>,
         WriteStorage<'a, MainHandItemChanged>,
+        WriteStorage<'a, ItemEntity>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -35,12 +45,15 @@ impl<'a> System<'a> for InventoryHandleInput {
             entities,
             input_cache,
             chunk_manager,
+            screen,
             player_state,
+            player_physics_state,
             mut inventory,
             mut main_hand_item_changed,
+            mut item_entities,
         ) = data;
 
-        for (e, inventory, player_state) in (&entities, &mut inventory, &player_state).join() {
+        for (e, inventory, player_state, player_physics_state) in (&entities, &mut inventory, &player_state, &player_physics_state).join() {
             let mut f = || {
                 if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
                     error!("{}", e);
@@ -48,9 +61,9 @@ impl<'a> System<'a> for InventoryHandleInput {
             };
 
             for event in &input_cache.events {
-                use glfw::{Key, Action};
+                use glfw::Key;
                 match event {
-                    WindowEvent::Scroll(_, y) => {
+                    WindowEvent::Scroll(_, y) if !player_state.is_spectator() => {
                         if y.is_sign_positive() {
                             inventory.select_previous_item();
                         } else {
@@ -58,7 +71,8 @@ impl<'a> System<'a> for InventoryHandleInput {
                         }
                         f();
                     }
-                    WindowEvent::MouseButton(MouseButton::Button3, Action::Press, _) => {
+                    // Picking the targeted block only exists in creative, survival has to mine it
+                    WindowEvent::MouseButton(MouseButton::Button3, Action::Press, _) if player_state.game_mode == GameMode::Creative => {
                         if let Some(((x, y, z), _)) = player_state.targeted_block {
                             if let Some(block) = chunk_manager.get_block(x, y, z) {
                                 inventory.slots[inventory.selected_hotbar_slot] = Some(ItemStack::new(1, block));
@@ -66,6 +80,24 @@ impl<'a> System<'a> for InventoryHandleInput {
                             }
                         }
                     }
+                    // Throw one of the selected items in front of the player, unless typing in a screen
+                    WindowEvent::Key(Key::Q, _, Action::Press, _) if !screen.is_open() => {
+                        if let Some(item_stack) = inventory.get_selected_item_stack() {
+                            let item = item_stack.item;
+                            let direction = player_state.rotation.forward().normalize();
+                            let eyes = player_physics_state.get_latest_state().position
+                                + vec3(0.0, *player_state.camera_height.get_interpolated_state(), 0.0);
+                            let item_entity = ItemEntity::thrown(item_stack.split_one(), eyes - vec3(0.0, 0.3, 0.0), direction);
+                            if let Err(e) = item_entities.insert(entities.create(), item_entity) {
+                                error!("{}", e);
+                            }
+                            inventory.consume_selected_item();
+                            info!("Dropped {:?}", item);
+                            if inventory.get_selected_item() != Some(item) {
+                                f();
+                            }
+                        }
+                    }
                     WindowEvent::Key(Key::Num1, _, Action::Press, _) => Self::select_item(inventory, 0, &mut f),
                     WindowEvent::Key(Key::Num2, _, Action::Press, _) => Self::select_item(inventory, 1, &mut f),
                     WindowEvent::Key(Key::Num3, _, Action::Press, _) => Self::select_item(inventory, 2, &mut f),
@@ -81,3 +113,78 @@ impl<'a> System<'a> for InventoryHandleInput {
         }
     }
 }
+
+/// Moves the stacks around in the inventory screen.
+/// Clicking on an armor piece wears it, clicking on a worn piece takes it off.
+/// Other stacks move between the hotbar and the main storage, shift-clicking merges them with the stacks there.
+pub struct HandleInventoryScreenInput;
+
+impl<'a> System<'a> for HandleInventoryScreenInput {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, InputCache>,
+        Read<'a, Screen>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, MainHandItemChanged>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            input_cache,
+            screen,
+            mut inventory,
+            mut main_hand_item_changed,
+        ) = data;
+
+        if *screen != Screen::Inventory {
+            return;
+        }
+
+        for (e, inventory) in (&entities, &mut inventory).join() {
+            let selected_item = inventory.get_selected_item();
+
+            for event in &input_cache.events {
+                match event {
+                    WindowEvent::MouseButton(MouseButton::Button1, Action::Press, modifiers) => {
+                        let cursor_x = input_cache.last_cursor_pos.x as f32;
+                        let cursor_y = WINDOW_HEIGHT as f32 - input_cache.last_cursor_pos.y as f32;
+
+                        if let Some(slot) = Inventory::armor_slot_at(cursor_x, cursor_y) {
+                            if let Some(item_stack) = inventory.armor.get_mut(slot).take() {
+                                *inventory.armor.get_mut(slot) = inventory.add_item_stack(item_stack);
+                            }
+                            continue;
+                        }
+
+                        let (i, destination) = if let Some(i) = Inventory::hotbar_slot_at(cursor_x, cursor_y) {
+                            (i, HOTBAR_SIZE..INVENTORY_SIZE)
+                        } else if let Some(i) = Inventory::storage_slot_at(cursor_x, cursor_y) {
+                            (i, 0..HOTBAR_SIZE)
+                        } else {
+                            continue;
+                        };
+
+                        if modifiers.contains(Modifiers::Shift) {
+                            inventory.shift_click(i);
+                        } else if let Some(item_stack) = inventory.slots[i].take() {
+                            inventory.slots[i] = if armor_properties(item_stack.item).is_some() {
+                                // The piece that was worn takes the place of the new one
+                                inventory.armor.equip(item_stack)
+                            } else {
+                                inventory.add_item_stack_in(item_stack, destination)
+                            };
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            if inventory.get_selected_item() != selected_item {
+                if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                    error!("{}", e);
+                }
+            }
+        }
+    }
+}
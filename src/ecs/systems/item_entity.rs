@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::{Vec3, vec3};
+use num_traits::Zero;
+use specs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::aabb::get_block_aabb;
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{GRAVITY, ITEM_BOB_PERIOD, ITEM_PICKUP_RANGE};
+use crate::ecs::components::MainHandItemChanged;
+use crate::inventory::Inventory;
+use crate::inventory::item::ItemRender;
+use crate::item_entity::ItemEntity;
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::timer::Timer;
+use crate::types::{Shaders, TexturePack};
+use crate::util::is_sphere_in_frustum;
+
+/// Size of the cube drawn for a dropped item
+const ITEM_SCALE: f32 = 0.25;
+
+/// Moves the dropped items and the experience orbs like the particles, one axis at a time so that they slide along
+/// the blocks. The callers apply the gravity
+pub fn move_with_collisions(position: &mut Vec3, velocity: &mut Vec3, chunk_manager: &ChunkManager, dt: f32) {
+    let initial_velocity = *velocity;
+    for v in &[vec3(initial_velocity.x, 0.0, 0.0), vec3(0.0, initial_velocity.y, 0.0), vec3(0.0, 0.0, initial_velocity.z)] {
+        *position += v * dt;
+
+        let (x, y, z) = (
+            position.x.floor() as i32,
+            position.y.floor() as i32,
+            position.z.floor() as i32,
+        );
+        if !chunk_manager.is_solid_block_at(x, y, z) {
+            continue;
+        }
+        let block_aabb = get_block_aabb(&vec3(x as f32, y as f32, z as f32));
+
+        let padding = 0.001;
+        if !v.x.is_zero() {
+            position.x = if v.x < 0.0 { block_aabb.maxs.x + padding } else { block_aabb.mins.x - padding };
+            velocity.x = 0.0;
+        }
+        if !v.y.is_zero() {
+            position.y = if v.y < 0.0 { block_aabb.maxs.y + padding } else { block_aabb.mins.y - padding };
+            // Sliding on the ground slows the item down
+            if v.y < 0.0 {
+                velocity.x *= 0.5;
+                velocity.z *= 0.5;
+            }
+            velocity.y = 0.0;
+        }
+        if !v.z.is_zero() {
+            position.z = if v.z < 0.0 { block_aabb.maxs.z + padding } else { block_aabb.mins.z - padding };
+            velocity.z = 0.0;
+        }
+    }
+}
+
+/// Moves the dropped items, despawns the old ones and gives the player those in its pickup range
+pub struct UpdateItemEntities {
+    last_time: Instant,
+}
+
+impl UpdateItemEntities {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateItemEntities {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, ItemEntity>,
+        WriteStorage<'a, MainHandItemChanged>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            global_timer,
+            chunk_manager,
+            player_physics_state,
+            mut inventory,
+            mut item_entities,
+            mut main_hand_item_changed,
+        ) = data;
+
+        let now = global_timer.time();
+        let dt = now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        for (e, item_entity) in (&entities, &mut item_entities).join() {
+            item_entity.age += dt;
+            let (x, y, z) = (
+                item_entity.position.x.floor() as i32,
+                item_entity.position.y.floor() as i32,
+                item_entity.position.z.floor() as i32,
+            );
+            // Items lying in chunks that aren't loaded yet wait for them
+            if chunk_manager.get_block(x, y, z).is_some() {
+                item_entity.velocity.y += GRAVITY * dt;
+                move_with_collisions(&mut item_entity.position, &mut item_entity.velocity, &chunk_manager, dt);
+            }
+
+            // Fell out of the world or lay there for too long
+            if item_entity.age >= item_entity.despawn_after || item_entity.position.y < 0.0 {
+                if let Err(e) = entities.delete(e) {
+                    error!("{}", e);
+                }
+            }
+        }
+
+        for (player_entity, player_physics_state, inventory) in (&entities, &player_physics_state, &mut inventory).join() {
+            let aabb = player_physics_state.get_latest_state().aabb;
+            for (e, item_entity) in (&entities, &mut item_entities).join() {
+                if !item_entity.can_be_picked_up() {
+                    continue;
+                }
+                // Distance to the closest point of the player
+                let position = item_entity.position;
+                let closest = vec3(
+                    position.x.max(aabb.mins.x).min(aabb.maxs.x),
+                    position.y.max(aabb.mins.y).min(aabb.maxs.y),
+                    position.z.max(aabb.mins.z).min(aabb.maxs.z),
+                );
+                if (position - closest).norm() > ITEM_PICKUP_RANGE {
+                    continue;
+                }
+
+                let selected_item = inventory.get_selected_item();
+                let item = item_entity.item_stack.item;
+                let picked_up = item_entity.item_stack.amount;
+                // What doesn't fit stays on the ground
+                match inventory.pick_up(item_entity.item_stack.clone()) {
+                    Some(left) if left.amount == picked_up => continue,
+                    Some(left) => item_entity.item_stack = left,
+                    None => if let Err(e) = entities.delete(e) {
+                        error!("{}", e);
+                    }
+                }
+                // There is no sound engine yet, the pickup is only logged
+                info!("Picked up {:?}", item);
+                if inventory.get_selected_item() != selected_item {
+                    if let Err(e) = main_hand_item_changed.insert(player_entity, MainHandItemChanged) {
+                        error!("{}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draws the dropped items as small cubes spinning around the Y axis and bobbing up and down
+pub struct RenderItemEntities {
+    /// One mesh for every kind of item lying in the world
+    item_renders: HashMap<BlockID, ItemRender>,
+}
+
+impl RenderItemEntities {
+    pub fn new() -> Self {
+        Self {
+            item_renders: HashMap::new(),
+        }
+    }
+}
+
+impl<'a> System<'a> for RenderItemEntities {
+    type SystemData = (
+        Read<'a, TexturePack>,
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, ItemEntity>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            texture_pack,
+            player_state,
+            item_entities,
+            mut shaders,
+        ) = data;
+
+        for player_state in (&player_state).join() {
+            let view_projection = player_state.projection_matrix * player_state.view_matrix;
+            let item_shader = shaders.get_mut("item_shader").unwrap();
+
+            for item_entity in (&item_entities).join() {
+                let bob = 0.1 * (1.0 + (2.0 * PI * item_entity.age / ITEM_BOB_PERIOD).sin());
+                let center: Vec3 = item_entity.position + vec3(0.0, ITEM_SCALE / 2.0 + bob, 0.0);
+                if !is_sphere_in_frustum(&view_projection, &center, ITEM_SCALE) {
+                    continue;
+                }
+
+                let item = item_entity.item_stack.item;
+                let item_render = self.item_renders.entry(item).or_insert_with(ItemRender::new);
+                item_render.update_vbo_if_dirty(item, &texture_pack);
+
+                let model_matrix = Matrix4::new_translation(&center)
+                    * Matrix4::from_euler_angles(0.0, item_entity.age * 2.0, 0.0)
+                    * Matrix4::new_scaling(ITEM_SCALE);
+                item_render.draw_in_world(&model_matrix, &view_projection, item_shader);
+            }
+        }
+    }
+}
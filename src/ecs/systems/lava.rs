@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra_glm::vec3;
+use rand::Rng;
+use specs::{Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{LAVA_DRIP_RADIUS, LAVA_DRIP_SAMPLES, TICKS_PER_SECOND};
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::timer::Timer;
+use crate::types::{ParticleSystems, TexturePack};
+
+/// Burns the player touching lava or fire, one game tick at a time, until the fire goes out or water puts it out
+pub struct UpdateBurning {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl UpdateBurning {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateBurning {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        WriteStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            mut player_state,
+            player_physics_state,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            for (player_state, player_physics_state) in (&mut player_state, &player_physics_state).join() {
+                // Spectators fly through the lava unharmed
+                if player_state.is_spectator() {
+                    continue;
+                }
+                let physics_state = player_physics_state.get_latest_state();
+                let is_in_lava = physics_state.is_touching_block(BlockID::Lava, &chunk_manager);
+                let is_in_fire = physics_state.is_touching_block(BlockID::Fire, &chunk_manager);
+                let is_in_water = physics_state.is_touching_block(BlockID::Water, &chunk_manager);
+                player_state.update_burning(is_in_lava, is_in_fire, is_in_water, now);
+
+                if player_state.health <= 0.0 && !player_state.is_dead {
+                    player_state.die("Burned to death");
+                }
+            }
+            self.accumulator -= tick_duration;
+        }
+    }
+}
+
+/// Makes drops of lava fall from the blocks under lava, where there is room for them to fall.
+/// Random blocks around the player are checked every game tick instead of all of them
+pub struct EmitLavaDrips {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl EmitLavaDrips {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for EmitLavaDrips {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        Write<'a, ParticleSystems>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            texture_pack,
+            mut particle_systems,
+            player_physics_state,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let mut rng = rand::thread_rng();
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            for player_physics_state in (&player_physics_state).join() {
+                let position = player_physics_state.get_latest_state().position;
+                let (p_x, p_y, p_z) = (position.x.floor() as i32, position.y.floor() as i32, position.z.floor() as i32);
+
+                for _ in 0..LAVA_DRIP_SAMPLES {
+                    let x = p_x + rng.gen_range(-LAVA_DRIP_RADIUS, LAVA_DRIP_RADIUS + 1);
+                    let y = p_y + rng.gen_range(-LAVA_DRIP_RADIUS, LAVA_DRIP_RADIUS + 1);
+                    let z = p_z + rng.gen_range(-LAVA_DRIP_RADIUS, LAVA_DRIP_RADIUS + 1);
+                    if chunk_manager.get_block(x, y, z) != Some(BlockID::Lava)
+                        || !chunk_manager.is_solid_block_at(x, y - 1, z)
+                        || chunk_manager.is_solid_block_at(x, y - 2, z) {
+                        continue;
+                    }
+
+                    let lava_drip_particles = particle_systems.get_mut("lava_drip_particles").unwrap();
+                    lava_drip_particles.emit_lava_drip_particle(vec3(x as f32, (y - 1) as f32, z as f32), &texture_pack);
+                }
+            }
+            self.accumulator -= tick_duration;
+        }
+    }
+}
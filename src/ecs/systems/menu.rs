@@ -0,0 +1,156 @@
+use std::process::exit;
+
+use glfw::{Action, Key, MouseButton, WindowEvent};
+use specs::{Read, System, Write};
+
+use crate::constants::WINDOW_HEIGHT;
+use crate::input::InputCache;
+use crate::menu::{is_on_done_button, thumbnail_at, PageButton, PauseButton, SettingSlider, STATISTICS_VISIBLE_ROWS, THUMBNAILS_PER_PAGE};
+use crate::screen::Screen;
+use crate::screenshots::ScreenshotGallery;
+use crate::settings::Settings;
+use crate::statistics::Statistics;
+use crate::timer::Timer;
+
+/// Opens the pause menu when the player presses Escape, and handles the clicks on its buttons
+/// and on the sliders of the options screen. The settings are applied while the sliders are dragged
+/// and saved when the options screen is left. The statistics are saved when the game is paused or quit.
+/// The screenshots are listed again every time the gallery is opened
+pub struct HandleMenuInput {
+    dragged_slider: Option<SettingSlider>,
+}
+
+impl HandleMenuInput {
+    pub fn new() -> Self {
+        Self {
+            dragged_slider: None,
+        }
+    }
+}
+
+impl<'a> System<'a> for HandleMenuInput {
+    type SystemData = (
+        Read<'a, InputCache>,
+        Write<'a, Screen>,
+        Read<'a, Statistics>,
+        Write<'a, Settings>,
+        Write<'a, Timer>,
+        Write<'a, ScreenshotGallery>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            input_cache,
+            mut screen,
+            statistics,
+            mut settings,
+            mut global_timer,
+            mut screenshot_gallery,
+        ) = data;
+
+        // Escape is used to leave the screens that capture the keyboard
+        if screen.captures_keyboard() {
+            return;
+        }
+
+        let cursor_x = input_cache.last_cursor_pos.x as f32;
+        let cursor_y = WINDOW_HEIGHT as f32 - input_cache.last_cursor_pos.y as f32;
+
+        for event in &input_cache.events {
+            match event {
+                WindowEvent::Key(Key::Escape, _, Action::Press, _) => {
+                    match *screen {
+                        Screen::None => {
+                            *screen = Screen::Pause;
+                            global_timer.pause();
+                            statistics.save();
+                        }
+                        Screen::Pause => {
+                            *screen = Screen::None;
+                            global_timer.resume();
+                        }
+                        Screen::Options => {
+                            settings.save();
+                            *screen = Screen::Pause;
+                        }
+                        Screen::Statistics(_) | Screen::Screenshots(_) => *screen = Screen::Pause,
+                        Screen::Screenshot(index) => *screen = Screen::Screenshots(index / THUMBNAILS_PER_PAGE),
+                        // The player has to choose between respawning and leaving
+                        Screen::Death => {}
+                        _ => *screen = Screen::None,
+                    }
+                }
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                    match *screen {
+                        Screen::Pause => match PauseButton::at(cursor_x, cursor_y) {
+                            Some(PauseButton::BackToGame) => {
+                                *screen = Screen::None;
+                                global_timer.resume();
+                            }
+                            Some(PauseButton::Options) => *screen = Screen::Options,
+                            Some(PauseButton::Statistics) => *screen = Screen::Statistics(0),
+                            Some(PauseButton::Screenshots) => {
+                                screenshot_gallery.refresh();
+                                *screen = Screen::Screenshots(0);
+                            }
+                            Some(PauseButton::QuitGame) => {
+                                statistics.save();
+                                exit(0);
+                            }
+                            None => {}
+                        },
+                        Screen::Options => {
+                            if is_on_done_button(cursor_x, cursor_y) {
+                                settings.save();
+                                *screen = Screen::Pause;
+                            } else {
+                                self.dragged_slider = SettingSlider::at(cursor_x, cursor_y);
+                            }
+                        }
+                        Screen::Statistics(_) if is_on_done_button(cursor_x, cursor_y) => *screen = Screen::Pause,
+                        Screen::Screenshots(page) => {
+                            if is_on_done_button(cursor_x, cursor_y) {
+                                *screen = Screen::Pause;
+                            } else if let Some(slot) = thumbnail_at(cursor_x, cursor_y) {
+                                let index = page * THUMBNAILS_PER_PAGE + slot;
+                                if index < screenshot_gallery.screenshots.len() {
+                                    *screen = Screen::Screenshot(index);
+                                }
+                            } else {
+                                match PageButton::at(cursor_x, cursor_y) {
+                                    Some(PageButton::Previous) if page > 0 => *screen = Screen::Screenshots(page - 1),
+                                    Some(PageButton::Next) if page + 1 < screenshot_gallery.pages() => *screen = Screen::Screenshots(page + 1),
+                                    _ => {}
+                                }
+                            }
+                        }
+                        // Clicking anywhere goes back to the gallery
+                        Screen::Screenshot(index) => *screen = Screen::Screenshots(index / THUMBNAILS_PER_PAGE),
+                        _ => {}
+                    }
+                }
+                WindowEvent::Scroll(_, y) => {
+                    if let Screen::Statistics(scroll) = *screen {
+                        let last_scroll = statistics.rows().len().saturating_sub(STATISTICS_VISIBLE_ROWS);
+                        *screen = if *y > 0.0 {
+                            Screen::Statistics(scroll.saturating_sub(1))
+                        } else {
+                            Screen::Statistics((scroll + 1).min(last_scroll))
+                        };
+                    }
+                }
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) => {
+                    self.dragged_slider = None;
+                }
+                _ => {}
+            }
+        }
+
+        if *screen != Screen::Options {
+            self.dragged_slider = None;
+        }
+        if let Some(slider) = self.dragged_slider {
+            slider.set_value(&mut settings, slider.value_at(cursor_x));
+        }
+    }
+}
@@ -0,0 +1,277 @@
+use std::ffi::c_void;
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::{floor, Vec3, vec3};
+use specs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{MOB_ATTACK_COOLDOWN_TICKS, MOB_ATTACK_RANGE, PATH_RECOMPUTE_TICKS, PATHFINDING_MAX_NODES, SUNLIGHT_BURN_DAMAGE_PER_SECOND, TICKS_PER_SECOND, WANDER_PATH_STEPS};
+use crate::day_cycle::DayCycle;
+use crate::difficulty::{WorldAge, attack_damage_multiplier};
+use crate::input::InputCache;
+use crate::inventory::Inventory;
+use crate::inventory::item::ItemStack;
+use crate::item_entity::ItemEntity;
+use crate::loot::roll;
+use crate::mob::{MobEntity, MobEntityState};
+use crate::mob_spawning::is_under_sky;
+use crate::pathfinding::{astar_path, ground_below, wander_path};
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::shapes::textured_box;
+use crate::statistics::Statistics;
+use crate::timer::Timer;
+use crate::types::Shaders;
+use crate::util::is_sphere_in_frustum;
+use crate::xp_orb::{XpOrb, split_into_orbs};
+
+/// Ticks every mob at a fixed rate: physics first, then its behaviour.
+/// The mobs burning in the sunlight lose health while standing under the open sky during the day.
+/// The mobs following the player walk along a path found on the player interaction thread pool,
+/// the hostile ones hit the player once close enough.
+/// Mobs standing in chunks that aren't loaded yet wait for them
+pub struct UpdateMobs {
+    last_time: Instant,
+    accumulator: f32,
+    thread_pool: Arc<rayon::ThreadPool>,
+}
+
+impl UpdateMobs {
+    pub fn new(thread_pool: Arc<rayon::ThreadPool>) -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+            thread_pool,
+        }
+    }
+
+    /// Takes the path computed since the last tick, and asks for a new one every PATH_RECOMPUTE_TICKS ticks
+    /// or as soon as a block gets in the way. Without a way to the player, the mob wanders a few steps
+    fn update_path(&self, state: &mut MobEntityState, player_position: &Vec3, chunk_manager: &Arc<ChunkManager>) {
+        state.path_age += 1;
+        if let Some(path) = state.computed_path.write().take() {
+            state.path = path;
+            state.is_path_requested = false;
+        }
+        if state.is_path_requested || (state.path_age < PATH_RECOMPUTE_TICKS && !state.is_path_blocked(chunk_manager)) {
+            return;
+        }
+
+        // The block in the way may have changed after the last block updates, before the cache forgot it
+        if let Some(&next) = state.path.first() {
+            chunk_manager.nav_cache.invalidate(next);
+        }
+        let start = state.block_position();
+        let player_block = (player_position.x.floor() as i32, player_position.y.floor() as i32, player_position.z.floor() as i32);
+        // The player may be jumping or flying a little above the ground
+        let goal = ground_below(player_block, chunk_manager, 3).unwrap_or(player_block);
+        let computed_path = Arc::clone(&state.computed_path);
+        let chunk_manager = Arc::clone(chunk_manager);
+        self.thread_pool.spawn(move || {
+            let path = astar_path(start, goal, &chunk_manager, PATHFINDING_MAX_NODES)
+                .unwrap_or_else(|| wander_path(start, &chunk_manager, WANDER_PATH_STEPS, &mut rand::thread_rng()));
+            *computed_path.write() = Some(path);
+        });
+        state.is_path_requested = true;
+        state.path_age = 0;
+    }
+}
+
+impl<'a> System<'a> for UpdateMobs {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Timer>,
+        Read<'a, InputCache>,
+        Read<'a, WorldAge>,
+        Read<'a, DayCycle>,
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, Statistics>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        WriteStorage<'a, PlayerState>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, MobEntity>,
+        WriteStorage<'a, XpOrb>,
+        WriteStorage<'a, ItemEntity>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            global_timer,
+            input_cache,
+            world_age,
+            day_cycle,
+            chunk_manager,
+            mut statistics,
+            player_physics_state,
+            mut player_state,
+            mut inventory,
+            mut mobs,
+            mut xp_orbs,
+            mut item_entities,
+        ) = data;
+
+        let now = global_timer.time();
+        let frame_time = now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+        self.accumulator += frame_time;
+
+        let player_position = (&player_physics_state).join().next()
+            .map(|player_physics_state| player_physics_state.get_latest_state().position);
+        let damage_multiplier = attack_damage_multiplier(world_age.ticks);
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            for mob in (&mut mobs).join() {
+                // Dead mobs are removed once the ticks are done
+                if mob.is_dead() {
+                    continue;
+                }
+                let state = &mut mob.state;
+                state.previous_position = state.position;
+
+                let cell = floor(&state.position);
+                if chunk_manager.get_block(cell.x as i32, cell.y as i32, cell.z as i32).is_none() {
+                    continue;
+                }
+
+                state.dt = tick_duration;
+                state.update_physics(&chunk_manager);
+                if mob.burns_in_sunlight && !mob.wears_helmet && !day_cycle.is_night()
+                    && is_under_sky(&chunk_manager, cell.x as i32, cell.y as i32, cell.z as i32) {
+                    state.health -= SUNLIGHT_BURN_DAMAGE_PER_SECOND * tick_duration;
+                }
+                (mob.on_tick)(&mut mob.state, &chunk_manager, &input_cache, damage_multiplier);
+                if let Some(player_position) = player_position {
+                    if (player_position - mob.state.position).norm() < mob.state.follow_range {
+                        self.update_path(&mut mob.state, &player_position, &chunk_manager);
+                        mob.state.follow_path();
+                    }
+                }
+
+                mob.state.attack_cooldown = mob.state.attack_cooldown.saturating_sub(1);
+                if mob.kind.attack_damage() <= 0.0 || mob.state.attack_cooldown > 0 {
+                    continue;
+                }
+                for (player_state, player_physics_state, inventory) in (&mut player_state, &player_physics_state, &mut inventory).join() {
+                    let position = player_physics_state.get_latest_state().position;
+                    if player_state.is_spectator() || player_state.is_dead || (position - mob.state.position).norm() > MOB_ATTACK_RANGE {
+                        continue;
+                    }
+                    let damage = player_state.take_damage(mob.kind.attack_damage() * damage_multiplier, &mut inventory.armor, now);
+                    info!("A {:?} hit the player for {:.1} damage", mob.kind, damage);
+                    if player_state.health <= 0.0 {
+                        player_state.die(mob.kind.death_message());
+                    }
+                    mob.state.attack_cooldown = MOB_ATTACK_COOLDOWN_TICKS;
+                }
+            }
+            self.accumulator -= tick_duration;
+        }
+
+        for (e, mob) in (&entities, &mobs).join() {
+            if !mob.is_dead() {
+                continue;
+            }
+            statistics.mobs_killed += 1;
+            for value in split_into_orbs(mob.kind.xp_reward()) {
+                if let Err(e) = xp_orbs.insert(entities.create(), XpOrb::new(value, mob.state.position)) {
+                    error!("{}", e);
+                }
+            }
+            let drop_position = mob.state.position + vec3(0.0, 0.5, 0.0);
+            for (item, count) in roll(mob.kind.loot_table(), &mut rand::thread_rng()) {
+                if let Err(e) = item_entities.insert(entities.create(), ItemEntity::popped(ItemStack::new(count, item), drop_position)) {
+                    error!("{}", e);
+                }
+            }
+            if let Err(e) = entities.delete(e) {
+                error!("{}", e);
+            }
+        }
+
+        let alpha = self.accumulator / tick_duration;
+        for mob in (&mut mobs).join() {
+            let state = &mut mob.state;
+            state.render_position = state.previous_position.lerp(&state.position, alpha);
+        }
+    }
+}
+
+/// A unit box standing on the origin with its normals, drawn with the mob shader
+pub fn create_colored_box_vao() -> u32 {
+    let mut vao = 0;
+    gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+    // Position
+    gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+    gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+    gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+    // Normals
+    gl_call!(gl::EnableVertexArrayAttrib(vao, 2));
+    gl_call!(gl::VertexArrayAttribFormat(vao, 2, 3 as i32, gl::FLOAT, gl::FALSE, 6 * std::mem::size_of::<f32>() as u32));
+    gl_call!(gl::VertexArrayAttribBinding(vao, 2, 0));
+
+    let vbo_data = textured_box((-0.5, 0.0, -0.5), (0.5, 1.0, 0.5), (0, 0, 0, 0, 0, 0));
+    let mut vbo = 0;
+    gl_call!(gl::CreateBuffers(1, &mut vbo));
+    gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (9 * std::mem::size_of::<f32>()) as i32));
+    gl_call!(gl::NamedBufferData(vbo,
+                (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                vbo_data.as_ptr() as *const c_void,
+                gl::STATIC_DRAW));
+    vao
+}
+
+/// Draws every mob as a colored box until they get proper models
+pub struct RenderMobs {
+    vao: u32,
+}
+
+impl RenderMobs {
+    pub fn new() -> Self {
+        Self {
+            vao: create_colored_box_vao(),
+        }
+    }
+}
+
+impl<'a> System<'a> for RenderMobs {
+    type SystemData = (
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, MobEntity>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_state,
+            mobs,
+            mut shaders,
+        ) = data;
+
+        for player_state in (&player_state).join() {
+            let view_projection = player_state.projection_matrix * player_state.view_matrix;
+
+            let mob_shader = shaders.get_mut("mob_shader").unwrap();
+            mob_shader.use_program();
+            gl_call!(gl::BindVertexArray(self.vao));
+
+            for mob in (&mobs).join() {
+                let state = &mob.state;
+                if !is_sphere_in_frustum(&view_projection, &state.render_position, state.height()) {
+                    continue;
+                }
+
+                let model_matrix = Matrix4::new_translation(&state.render_position)
+                    * Matrix4::from_euler_angles(0.0, state.yaw, 0.0)
+                    * Matrix4::new_nonuniform_scaling(&vec3(state.width(), state.height(), state.width()));
+                mob_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+                mob_shader.set_uniform3f("color", &[state.color.0, state.color.1, state.color.2]);
+                gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 36));
+            }
+        }
+    }
+}
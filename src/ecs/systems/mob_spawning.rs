@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra_glm::Vec3;
+use parking_lot::RwLock;
+use specs::{Entities, Join, Read, ReadStorage, System, WriteStorage};
+
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{MOB_CAP_RADIUS, MOB_DESPAWN_DISTANCE, MOB_SPAWN_INTERVAL_TICKS, TICKS_PER_SECOND};
+use crate::day_cycle::DayCycle;
+use crate::difficulty::WorldAge;
+use crate::mob::{MobEntity, MobKind};
+use crate::mob_spawning::{MobSpawnManager, find_spawn_positions};
+use crate::physics::Interpolator;
+use crate::player::PlayerPhysicsState;
+use crate::timer::Timer;
+
+/// Every MOB_SPAWN_INTERVAL_TICKS game ticks, spawns the mobs that are below their cap around the player.
+/// The places they spawn at are looked for on the player interaction thread pool,
+/// the mobs appear on the frame after they were found. The mobs of the generated structures appear
+/// once a player gets within MOB_DESPAWN_DISTANCE of them
+pub struct SpawnMobs {
+    last_time: Instant,
+    accumulator: f32,
+    ticks: u32,
+    thread_pool: Arc<rayon::ThreadPool>,
+    found_positions: Arc<RwLock<Vec<(MobKind, Vec3)>>>,
+}
+
+impl SpawnMobs {
+    pub fn new(thread_pool: Arc<rayon::ThreadPool>) -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+            ticks: 0,
+            thread_pool,
+            found_positions: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
+
+impl<'a> System<'a> for SpawnMobs {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Timer>,
+        Read<'a, WorldAge>,
+        Read<'a, DayCycle>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, MobSpawnManager>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        WriteStorage<'a, MobEntity>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            global_timer,
+            world_age,
+            day_cycle,
+            chunk_manager,
+            mob_spawn_manager,
+            player_physics_state,
+            mut mobs,
+        ) = data;
+
+        for (kind, position) in self.found_positions.write().drain(..) {
+            if let Err(e) = mobs.insert(entities.create(), kind.spawn(position)) {
+                error!("{}", e);
+            }
+        }
+
+        // The mobs of the structures would despawn right away if they appeared further
+        let player_positions: Vec<Vec3> = (&player_physics_state).join()
+            .map(|player_physics_state| player_physics_state.get_latest_state().position)
+            .collect();
+        let mut generated_mobs = chunk_manager.generated_mobs.write();
+        let (nearby_mobs, far_mobs) = generated_mobs.drain(..)
+            .partition(|mob: &MobEntity| player_positions.iter()
+                .any(|position| (mob.state.position - position).norm() < MOB_DESPAWN_DISTANCE));
+        *generated_mobs = far_mobs;
+        drop(generated_mobs);
+        for mob in nearby_mobs {
+            if let Err(e) = mobs.insert(entities.create(), mob) {
+                error!("{}", e);
+            }
+        }
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            self.ticks += 1;
+            self.accumulator -= tick_duration;
+        }
+        if self.ticks < MOB_SPAWN_INTERVAL_TICKS {
+            return;
+        }
+        self.ticks = 0;
+
+        for player_physics_state in (&player_physics_state).join() {
+            let player_position = player_physics_state.get_latest_state().position;
+
+            let mut mob_counts = HashMap::new();
+            for mob in (&mobs).join() {
+                if (mob.state.position - player_position).norm() <= MOB_CAP_RADIUS {
+                    *mob_counts.entry(mob.kind).or_insert(0) += 1;
+                }
+            }
+
+            for &kind in MobKind::ALL.iter() {
+                let count = mob_counts.get(&kind).copied().unwrap_or(0);
+                let cap = mob_spawn_manager.cap(kind, world_age.ticks);
+                if count >= cap {
+                    continue;
+                }
+
+                let chunk_manager = Arc::clone(&chunk_manager);
+                let found_positions = Arc::clone(&self.found_positions);
+                let sky_light = day_cycle.sky_light();
+                self.thread_pool.spawn(move || {
+                    let positions = find_spawn_positions(kind, player_position, cap - count, &chunk_manager, sky_light);
+                    found_positions.write().extend(positions.into_iter().map(|position| (kind, position)));
+                });
+            }
+        }
+    }
+}
+
+/// Removes the mobs that are too far away from every player
+pub struct DespawnMobs;
+
+impl<'a> System<'a> for DespawnMobs {
+    type SystemData = (
+        Entities<'a>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        ReadStorage<'a, MobEntity>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_physics_state,
+            mobs,
+        ) = data;
+
+        let player_positions: Vec<Vec3> = (&player_physics_state).join()
+            .map(|player_physics_state| player_physics_state.get_latest_state().position)
+            .collect();
+        if player_positions.is_empty() {
+            return;
+        }
+
+        for (e, mob) in (&entities, &mobs).join() {
+            let is_far = player_positions.iter()
+                .all(|position| (mob.state.position - position).norm() > MOB_DESPAWN_DISTANCE);
+            if is_far {
+                if let Err(e) = entities.delete(e) {
+                    error!("{}", e);
+                }
+            }
+        }
+    }
+}
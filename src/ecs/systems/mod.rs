@@ -1,33 +1,115 @@
-use specs::{System, Write};
+use specs::{Read, System, Write};
 
+pub use achievement::*;
+pub use audio::*;
+pub use beacon::*;
+pub use bed::*;
+pub use breathing::*;
+pub use campfire::*;
+pub use chat::*;
+pub use block_entity::*;
+pub use block_update::*;
+pub use chest::*;
+pub use clouds::*;
+pub use crafting_table::*;
+pub use day_cycle::*;
+pub use death::*;
+pub use debug_overlay::*;
+pub use dimension::*;
+pub use enchanting_table::*;
+pub use fence_gate::*;
+pub use firework::*;
 pub use fps_counter::*;
+pub use furnace::*;
+pub use god_rays::*;
 pub use hand::*;
+pub use health::*;
+pub use hunger::*;
 pub use input::*;
 pub use inventory::*;
+pub use item_entity::*;
+pub use lava::*;
+pub use menu::*;
+pub use mob::*;
+pub use mob_spawning::*;
 pub use physics::*;
+pub use piston::*;
 pub use player::*;
+pub use pressure_plate::*;
+pub use projectile::*;
+pub use redstone::*;
 pub use rendering::*;
+pub use screen::*;
+pub use sign::*;
+pub use statistics::*;
+pub use texture_pack::*;
+pub use water::*;
+pub use weather::*;
+pub use worldgen::*;
+pub use xp_orb::*;
 
+use crate::physics::PhysicsConfig;
 use crate::timer::Timer;
 
 pub mod input;
 pub mod physics;
+pub mod piston;
 pub mod player;
+pub mod pressure_plate;
+pub mod projectile;
+pub mod redstone;
 pub mod fps_counter;
 pub mod hand;
+pub mod chat;
+pub mod health;
+pub mod hunger;
 pub mod inventory;
+pub mod item_entity;
+pub mod lava;
+pub mod menu;
+pub mod mob;
+pub mod mob_spawning;
 pub mod rendering;
 pub mod chunk_loading;
+pub mod achievement;
+pub mod audio;
+pub mod beacon;
+pub mod bed;
+pub mod breathing;
+pub mod campfire;
+pub mod block_entity;
+pub mod block_update;
+pub mod chest;
+pub mod clouds;
+pub mod crafting_table;
+pub mod day_cycle;
+pub mod death;
+pub mod debug_overlay;
+pub mod dimension;
+pub mod enchanting_table;
+pub mod fence_gate;
+pub mod firework;
+pub mod furnace;
+pub mod god_rays;
+pub mod screen;
+pub mod sign;
+pub mod statistics;
+pub mod texture_pack;
+pub mod water;
+pub mod weather;
+pub mod worldgen;
+pub mod xp_orb;
 
 pub struct AdvanceGlobalTime;
 
 impl<'a> System<'a> for AdvanceGlobalTime {
     type SystemData = (
+        Read<'a, PhysicsConfig>,
         Write<'a, Timer>,
     );
 
-    fn run(&mut self, (mut global_timer, ): Self::SystemData) {
-        global_timer.tick();
+    fn run(&mut self, (physics_config, mut global_timer): Self::SystemData) {
+        global_timer.tick(physics_config.time_scale);
     }
 }
 
@@ -1,11 +1,12 @@
-use nalgebra_glm::vec3;
+use nalgebra_glm::{vec3, Vec3};
 use num_traits::Zero;
 use specs::{Read, System, WriteStorage};
 
+use crate::chunk::BlockID;
 use crate::chunk_manager::ChunkManager;
-use crate::constants::{GRAVITY, PLAYER_HALF_WIDTH};
+use crate::constants::{GRAVITY, JUMP_IMPULSE, LADDER_CLIMBING_SPEED, LADDER_TOP_IMPULSE, PLAYER_HALF_WIDTH};
 use crate::input::InputCache;
-use crate::physics::Interpolator;
+use crate::physics::{Interpolator, PhysicsConfig};
 use crate::player::{PlayerPhysicsState, PlayerState};
 use crate::timer::Timer;
 use std::sync::Arc;
@@ -15,6 +16,7 @@ pub struct UpdatePlayerPhysics;
 impl<'a> System<'a> for UpdatePlayerPhysics {
     type SystemData = (
         Read<'a, Timer>,
+        Read<'a, PhysicsConfig>,
         Read<'a, InputCache>,
         Read<'a, Arc<ChunkManager>>,
         WriteStorage<'a, Interpolator<PlayerPhysicsState>>,
@@ -24,6 +26,7 @@ impl<'a> System<'a> for UpdatePlayerPhysics {
     fn run(&mut self, data: Self::SystemData) {
         let (
             global_timer,
+            physics_config,
             input_cache,
             chunk_manager,
             mut player_physics_state,
@@ -31,24 +34,83 @@ impl<'a> System<'a> for UpdatePlayerPhysics {
 
         use specs::Join;
         for (player_physics_state, player_state) in (&mut player_physics_state, &mut player_state).join() {
-            player_physics_state.step(global_timer.time(), &mut |player: &PlayerPhysicsState, _t: f32, dt: f32| {
+            // Land on the topmost solid block once the terrain under the player is generated
+            if !player_state.is_spawned {
+                let position = player_physics_state.get_latest_state().position;
+                let (x, z) = (position.x.floor() as i32, position.z.floor() as i32);
+                match chunk_manager.topmost_solid_block(x, z) {
+                    Some(y) => {
+                        let position = vec3(position.x, (y + 1) as f32, position.z);
+                        player_physics_state.teleport(position);
+                        player_state.spawn_point = position;
+                        player_state.is_spawned = true;
+                    }
+                    None => continue,
+                }
+            }
+
+            player_physics_state.dt = physics_config.physics_dt;
+            let now = global_timer.time();
+            player_physics_state.step(now, &mut |player: &PlayerPhysicsState, _t: f32, dt: f32| {
                 let mut player = player.clone();
-                if !player_state.is_flying {
+
+                // Spectators fly through the blocks, nothing collides with them
+                if player_state.is_spectator() {
+                    player_state.is_flying = true;
+                    player_state.is_on_ground = false;
+                    player.apply_keyboard_mouvement(player_state, &input_cache, now);
+                    player.acceleration *= player_state.spectator_speed_multiplier;
+                    player.velocity += player.acceleration * dt;
+                    player.apply_friction(dt, &player_state);
+                    player.limit_velocity(&player_state, now);
+                    player.aabb.ip_translate(&(player.velocity * dt));
+
+                    player.position.x = player.aabb.mins.x + PLAYER_HALF_WIDTH;
+                    player.position.y = player.aabb.mins.y;
+                    player.position.z = player.aabb.mins.z + PLAYER_HALF_WIDTH;
+                    player.acceleration = vec3(0.0, 0.0, 0.0);
+                    return player;
+                }
+
+                // The player keeps sneaking under a ceiling too low to stand up
+                if !player.update_height(player_state.is_sneaking, &chunk_manager) {
+                    player_state.is_sneaking = true;
+                }
+                let was_climbing = player_state.is_climbing;
+                player_state.is_climbing = !player_state.is_flying && player.is_touching_ladder(&chunk_manager);
+                if !player_state.is_flying && !player_state.is_climbing {
                     player.acceleration.y += GRAVITY;
                 }
 
-                player.apply_keyboard_mouvement(player_state, &input_cache);
+                player.apply_keyboard_mouvement(player_state, &input_cache, now);
                 player.velocity += player.acceleration * dt;
                 player.apply_friction(dt, &player_state);
-                player.limit_velocity(&player_state);
+                player.limit_velocity(&player_state, now);
+
+                // Ladders aren't solid, the player walks into them and climbs them instead of falling
+                if player_state.is_climbing {
+                    player.velocity.y = if input_cache.is_key_pressed(glfw::Key::W) {
+                        LADDER_CLIMBING_SPEED
+                    } else if input_cache.is_key_pressed(glfw::Key::S) {
+                        -LADDER_CLIMBING_SPEED
+                    } else {
+                        0.0
+                    };
+                } else if was_climbing && player.velocity.y > 0.0 {
+                    // Climbing over the top of the ladder
+                    player.velocity.y = *JUMP_IMPULSE * LADDER_TOP_IMPULSE;
+                }
 
+                let block_at = |coords: &Vec3| chunk_manager.get_block(coords.x as i32, coords.y as i32, coords.z as i32)
+                    .unwrap_or(BlockID::Air);
                 let will_hit_ground = |player: &PlayerPhysicsState| {
                     let mut player = player.clone();
                     let vy = vec3(0.0, player.velocity.y, 0.0);
                     player.aabb.ip_translate(&(vy * dt));
                     let colliding_block = player.get_colliding_block_coords(&chunk_manager);
                     if let Some(colliding_block) = colliding_block {
-                        player.separate_from_block(&vy, &colliding_block)
+                        let block = block_at(&colliding_block);
+                        player.separate_from_block(&vy, &colliding_block, block, player_state.is_sneaking)
                     } else {
                         false
                     }
@@ -70,7 +132,8 @@ impl<'a> System<'a> for UpdatePlayerPhysics {
 
                     // Collision resolution
                     if let Some(colliding_block) = colliding_block {
-                        is_player_on_ground |= player.separate_from_block(&v, &colliding_block);
+                        let block = block_at(&colliding_block);
+                        is_player_on_ground |= player.separate_from_block(&v, &colliding_block, block, player_state.is_sneaking);
                     }
 
                     // Don't let the player fall if he's sneaking on the block
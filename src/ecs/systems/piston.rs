@@ -0,0 +1,294 @@
+use std::ffi::c_void;
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::{pi, vec3};
+use specs::{Read, System, Write};
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{PISTON_MOVE_TICKS, TICKS_PER_SECOND};
+use crate::piston::{extend_piston, finish_piston_move, PistonArm, PistonBlockEntity, retract_piston};
+use crate::redstone::RedstoneSimulator;
+use crate::shapes::textured_box;
+use crate::timer::Timer;
+use crate::types::{BlockEntityRegistry, Shaders, TexturePack};
+
+/// Extends the pistons whose redstone input got powered and retracts the ones that lost their power,
+/// then moves their arms at a fixed rate
+pub struct UpdatePistons {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl UpdatePistons {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdatePistons {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, RedstoneSimulator>,
+        Write<'a, BlockEntityRegistry>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            redstone_simulator,
+            mut block_entity_registry,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            let pistons: Vec<(i32, i32, i32)> = block_entity_registry.iter()
+                .filter(|(_, block_entity)| block_entity.downcast_ref::<PistonBlockEntity>().is_some())
+                .map(|(&position, _)| position)
+                .collect();
+
+            for position in pistons {
+                let (x, y, z) = position;
+                let (block, meta) = match (chunk_manager.get_block(x, y, z), chunk_manager.get_block_meta(x, y, z)) {
+                    (Some(block), Some(meta)) if block.is_piston() => (block, meta),
+                    _ => continue,
+                };
+
+                // The piston leaves the registry while it moves the blocks around it, it may have been
+                // pushed away by another piston during this tick
+                let mut block_entity = match block_entity_registry.remove(&position) {
+                    Some(block_entity) => block_entity,
+                    None => continue,
+                };
+                if let Some(piston) = block_entity.downcast_mut::<PistonBlockEntity>() {
+                    match piston.moving.take() {
+                        Some(mut moving) => {
+                            moving.ticks += 1;
+                            if moving.ticks >= PISTON_MOVE_TICKS {
+                                finish_piston_move(&chunk_manager, &mut block_entity_registry, position, moving);
+                            } else {
+                                piston.moving = Some(moving);
+                            }
+                        }
+                        None => {
+                            let is_powered = redstone_simulator.input_power(&chunk_manager, position) > 0;
+                            if is_powered && !piston.is_powered && !meta.is_extended() {
+                                piston.moving = extend_piston(&chunk_manager, &mut block_entity_registry, position, meta, now);
+                            } else if !is_powered && piston.is_powered && meta.is_extended() {
+                                piston.moving = Some(retract_piston(&chunk_manager, &mut block_entity_registry, position, block, meta, now));
+                            }
+                            piston.is_powered = is_powered;
+                        }
+                    }
+                }
+                block_entity_registry.insert(position, block_entity);
+            }
+
+            // Forget the arms of the heads that fell off
+            block_entity_registry.retain(|&(x, y, z), block_entity| {
+                block_entity.downcast_ref::<PistonArm>().is_none()
+                    || chunk_manager.get_block(x, y, z).map_or(true, |block| block == BlockID::PistonHead)
+            });
+            self.accumulator -= tick_duration;
+        }
+    }
+}
+
+/// Depth of the plate at the end of the arm
+const PLATE_DEPTH: f32 = 4.0 / 16.0;
+/// Half of the width of the rod holding the plate
+const ROD_HALF_WIDTH: f32 = 2.0 / 16.0;
+
+fn create_model_vao() -> (u32, u32) {
+    let mut vao = 0;
+    gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+    // Position
+    gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+    gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+    gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+    // Texture coords
+    gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+    gl_call!(gl::VertexArrayAttribFormat(vao, 1, 3 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+    gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+    // Normals
+    gl_call!(gl::EnableVertexArrayAttrib(vao, 2));
+    gl_call!(gl::VertexArrayAttribFormat(vao, 2, 3 as i32, gl::FLOAT, gl::FALSE, 6 * std::mem::size_of::<f32>() as u32));
+    gl_call!(gl::VertexArrayAttribBinding(vao, 2, 0));
+
+    let mut vbo = 0;
+    gl_call!(gl::CreateBuffers(1, &mut vbo));
+    gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (9 * std::mem::size_of::<f32>()) as i32));
+    (vao, vbo)
+}
+
+/// Draws the pistons with their arm, and the blocks they are moving at their interpolated position
+pub struct RenderPistons {
+    vao: u32,
+    vbo: u32,
+    is_uploaded: bool,
+    moving_blocks_vao: u32,
+    moving_blocks_vbo: u32,
+}
+
+impl RenderPistons {
+    pub fn new() -> Self {
+        let (vao, vbo) = create_model_vao();
+        let (moving_blocks_vao, moving_blocks_vbo) = create_model_vao();
+
+        Self {
+            vao,
+            vbo,
+            is_uploaded: false,
+            moving_blocks_vao,
+            moving_blocks_vbo,
+        }
+    }
+
+    /// The base is made of 36 vertices, followed by the plate, the sticky plate and the rod.
+    /// The arm is drawn retracted, the rod is one block long
+    fn upload_model(&mut self, texture_pack: &TexturePack) {
+        let uvs = texture_pack.get(&BlockID::Piston).unwrap().get_uv_of_every_face();
+        let sticky_uvs = texture_pack.get(&BlockID::StickyPiston).unwrap().get_uv_of_every_face();
+        let inner = texture_pack.get(&BlockID::PistonHead).unwrap().get_uv_of_every_face().0;
+        let wood = uvs.0;
+
+        let mut vbo_data = textured_box(
+            (0.0, 0.0, 0.0),
+            (1.0, 1.0, 1.0 - PLATE_DEPTH), (inner, uvs.1, uvs.2, uvs.3, uvs.4, uvs.5));
+        vbo_data.extend(textured_box(
+            (0.0, 0.0, 1.0 - PLATE_DEPTH),
+            (1.0, 1.0, 1.0), (wood, wood, wood, wood, wood, wood)));
+        vbo_data.extend(textured_box(
+            (0.0, 0.0, 1.0 - PLATE_DEPTH),
+            (1.0, 1.0, 1.0), (sticky_uvs.0, wood, wood, wood, wood, wood)));
+        vbo_data.extend(textured_box(
+            (0.5 - ROD_HALF_WIDTH, 0.5 - ROD_HALF_WIDTH, 0.0),
+            (0.5 + ROD_HALF_WIDTH, 0.5 + ROD_HALF_WIDTH, 1.0), (wood, wood, wood, wood, wood, wood)));
+
+        gl_call!(gl::NamedBufferData(self.vbo,
+                    (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                    vbo_data.as_ptr() as *const c_void,
+                    gl::STATIC_DRAW));
+        self.is_uploaded = true;
+    }
+}
+
+impl<'a> System<'a> for RenderPistons {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, BlockEntityRegistry>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            block_entity_registry,
+            chunk_manager,
+            texture_pack,
+            mut shaders,
+        ) = data;
+
+        if !self.is_uploaded {
+            self.upload_model(&texture_pack);
+        }
+
+        let now = global_timer.time();
+        let pistons: Vec<((i32, i32, i32), &PistonBlockEntity)> = block_entity_registry.iter()
+            .filter_map(|(&position, block_entity)| block_entity.downcast_ref::<PistonBlockEntity>()
+                .map(|piston| (position, piston)))
+            .collect();
+
+        // A unit cube per moving block, the blocks that aren't solid (wires, ladders, ...) only show up
+        // once they arrived
+        let mut moving_blocks = Vec::new();
+        let mut vbo_data = Vec::new();
+        for (_, piston) in pistons.iter() {
+            if let Some(moving) = &piston.moving {
+                let remaining = 1.0 - moving.progress(now);
+                let (dx, dy, dz) = moving.direction;
+                for moving_block in moving.blocks.iter().filter(|moving_block| moving_block.block.is_solid()) {
+                    let uvs = match texture_pack.get(&moving_block.block) {
+                        Some(faces) => faces.get_uv_of_every_face(),
+                        None => continue,
+                    };
+                    let (x, y, z) = moving_block.destination;
+                    moving_blocks.push(vec3(
+                        x as f32 - dx as f32 * remaining,
+                        y as f32 - dy as f32 * remaining,
+                        z as f32 - dz as f32 * remaining));
+                    vbo_data.extend(textured_box((0.0, 0.0, 0.0), (1.0, 1.0, 1.0), uvs));
+                }
+            }
+        }
+        if !vbo_data.is_empty() {
+            gl_call!(gl::NamedBufferData(self.moving_blocks_vbo,
+                        (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                        vbo_data.as_ptr() as *const c_void,
+                        gl::DYNAMIC_DRAW));
+        }
+
+        let model_shader = shaders.get_mut("model_shader").unwrap();
+        model_shader.use_program();
+        model_shader.set_uniform1i("tex", 0);
+
+        gl_call!(gl::BindVertexArray(self.vao));
+        for &((x, y, z), piston) in pistons.iter() {
+            // The chunk isn't loaded
+            let (block, meta) = match (chunk_manager.get_block(x, y, z), chunk_manager.get_block_meta(x, y, z)) {
+                (Some(block), Some(meta)) => (block, meta),
+                _ => continue,
+            };
+            let extension = piston.extension(meta, now);
+
+            let base_model_matrix = {
+                let translate_matrix = Matrix4::new_translation(&vec3(
+                    x as f32 + 0.5, y as f32, z as f32 + 0.5));
+                let rotate_matrix = Matrix4::from_euler_angles(
+                    0.0f32,
+                    -(meta.facing() as f32) * pi::<f32>() / 2.0,
+                    0.0,
+                );
+                let center_matrix = Matrix4::new_translation(&vec3(-0.5, 0.0, -0.5));
+                translate_matrix * rotate_matrix * center_matrix
+            };
+            let plate_model_matrix = base_model_matrix * Matrix4::new_translation(&vec3(0.0, 0.0, extension));
+            let rod_model_matrix = base_model_matrix
+                * Matrix4::new_translation(&vec3(0.0, 0.0, 1.0 - PLATE_DEPTH))
+                * Matrix4::new_nonuniform_scaling(&vec3(1.0, 1.0, extension));
+
+            model_shader.set_uniform_matrix4fv("model", base_model_matrix.as_ptr());
+            gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 36));
+            model_shader.set_uniform_matrix4fv("model", plate_model_matrix.as_ptr());
+            let plate_first = if block == BlockID::StickyPiston { 72 } else { 36 };
+            gl_call!(gl::DrawArrays(gl::TRIANGLES, plate_first, 36));
+            if extension > 0.0 {
+                model_shader.set_uniform_matrix4fv("model", rod_model_matrix.as_ptr());
+                gl_call!(gl::DrawArrays(gl::TRIANGLES, 108, 36));
+            }
+        }
+
+        gl_call!(gl::BindVertexArray(self.moving_blocks_vao));
+        for (i, position) in moving_blocks.iter().enumerate() {
+            let model_matrix = Matrix4::new_translation(position);
+            model_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+            gl_call!(gl::DrawArrays(gl::TRIANGLES, 36 * i as i32, 36));
+        }
+    }
+}
@@ -1,22 +1,42 @@
-use std::time::Instant;
-
 use glfw::{Action, MouseButton};
 use nalgebra::Vector3;
-use nalgebra_glm::{IVec3, vec3};
-use specs::{Join, Read, ReadStorage, System, Write, WriteStorage};
-
-use crate::aabb::{AABB, get_block_aabb};
-use crate::chunk::BlockID;
+use nalgebra_glm::{IVec3, Vec3, vec3};
+use specs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::aabb::get_block_aabb;
+use crate::action_bar::ActionBar;
+use crate::beacon::{BeaconBlockEntity, pyramid_levels};
+use crate::bed::{bed_head_position, bed_spawn_point};
+use crate::audio::{PlaySound, SoundQueue};
+use crate::block_entity::create_block_entity;
+use crate::campfire::{CampfireBlockEntity, is_doused};
+use crate::chest::{ChestBlockEntity, LootChestBlockEntity};
+use crate::chunk::{BlockID, BlockMeta};
 use crate::chunk_manager::ChunkManager;
-use crate::constants::{FAR_PLANE, FLYING_TRIGGER_INTERVAL, FOV, JUMP_IMPULSE, NEAR_PLANE, PLAYER_EYES_HEIGHT, REACH_DISTANCE, SPRINTING_TRIGGER_INTERVAL, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::crafting_table::CraftingTableBlockEntity;
+use crate::day_cycle::DayCycle;
+use crate::dimension::find_portal_interior;
+use crate::enchanting::{EnchantmentId, knockback_speed, mined_block_drop, sharpness_bonus};
+use crate::constants::{BLOCK_BREAK_SOUND, BLOCK_PLACE_SOUND, BLOCK_SOUND_VOLUME, SLEEP_MONSTER_DISTANCE, WORLD_SPAWN_POINT, FAR_PLANE, FLYING_TRIGGER_INTERVAL, HASTE_BONUS_PER_LEVEL, KNOCKBACK_HOP_VELOCITY, NEAR_PLANE, PLAYER_EYES_HEIGHT, PLAYER_SNEAK_EYES_HEIGHT, REACH_DISTANCE, MELEE_REACH_DISTANCE, DASH_DUST_PARTICLES, DASH_SPEED_MULTIPLIER, SPRINTING_SPEED, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::ecs::components::MainHandItemChanged;
+use crate::fence_gate::FenceGateBlockEntity;
 use crate::input::InputCache;
 use crate::inventory::Inventory;
+use crate::inventory::item::ItemStack;
+use crate::inventory::tool::{break_time, can_harvest, melee_damage, tool_properties, ToolKind};
+use crate::item_entity::ItemEntity;
+use crate::mob::MobEntity;
 use crate::particle_system::ParticleSystem;
 use crate::physics::Interpolator;
-use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::piston::{finish_piston_move, PistonBlockEntity};
+use crate::player::{GameMode, PlayerPhysicsState, PlayerState};
 use crate::raycast;
+use crate::screen::Screen;
+use crate::settings::Settings;
+use crate::statistics::Statistics;
+use crate::status_effect::StatusEffect;
 use crate::timer::Timer;
-use crate::types::{ParticleSystems, TexturePack};
+use crate::types::{BlockEntityRegistry, ParticleSystems, TexturePack};
 use crate::util::Forward;
 use std::sync::Arc;
 
@@ -24,18 +44,32 @@ pub struct HandlePlayerInput;
 
 impl<'a> System<'a> for HandlePlayerInput {
     type SystemData = (
+        Read<'a, Timer>,
         Read<'a, InputCache>,
+        Read<'a, Screen>,
+        Read<'a, Settings>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        Write<'a, ParticleSystems>,
         WriteStorage<'a, PlayerState>,
         WriteStorage<'a, Interpolator<PlayerPhysicsState>>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
+            global_timer,
             input_cache,
+            screen,
+            settings,
+            chunk_manager,
+            texture_pack,
+            mut particle_systems,
             mut player_state,
             mut player_physics_state,
         ) = data;
 
+        let now = global_timer.time();
+
         for (player_state, player_physics_state) in (&mut player_state, &mut player_physics_state).join() {
             let mut player_state = player_state as &mut PlayerState;
             let player_physics_state = player_physics_state as &mut Interpolator<PlayerPhysicsState>;
@@ -43,27 +77,35 @@ impl<'a> System<'a> for HandlePlayerInput {
 
             for event in &input_cache.events {
                 match &event {
-                    glfw::WindowEvent::CursorPos(_, _) => {
+                    glfw::WindowEvent::CursorPos(_, _) if !screen.is_open() => {
                         player_state.rotate_camera(
                             input_cache.cursor_rel_pos.x as f32,
-                            input_cache.cursor_rel_pos.y as f32);
+                            input_cache.cursor_rel_pos.y as f32,
+                            &settings);
+                    }
+
+                    // Spectators have no hotbar, the scroll wheel changes their speed instead
+                    glfw::WindowEvent::Scroll(_, y) if player_state.is_spectator() && !screen.is_open() => {
+                        player_state.scale_spectator_speed(*y);
                     }
 
                     glfw::WindowEvent::Key(glfw::Key::Space, _, glfw::Action::Press, _) => {
-                        // Player state
-                        if player_state.fly_throttle {
-                            player_state.fly_throttle = false;
-                        } else if Instant::now().duration_since(player_state.fly_last_toggled) < *FLYING_TRIGGER_INTERVAL {
-                            player_state.is_flying = !player_state.is_flying;
-                            info!("Flying: {}", player_state.is_flying);
-                            player_state.fly_throttle = true;
+                        // Player state, spectators can't stop flying
+                        if !player_state.is_spectator() {
+                            if player_state.fly_throttle {
+                                player_state.fly_throttle = false;
+                            } else if now.saturating_duration_since(player_state.fly_last_toggled) < *FLYING_TRIGGER_INTERVAL {
+                                player_state.is_flying = !player_state.is_flying;
+                                info!("Flying: {}", player_state.is_flying);
+                                player_state.fly_throttle = true;
+                            }
+                            player_state.fly_last_toggled = now;
                         }
-                        player_state.fly_last_toggled = Instant::now();
 
                         // Player physics state
                         if player_state.is_on_ground {
-                            player_physics_state.velocity.y = *JUMP_IMPULSE;
-                            player_state.jump_last_executed = Instant::now();
+                            player_physics_state.velocity.y = player_state.jump_impulse(now);
+                            player_state.jump_last_executed = now;
                         }
                     }
 
@@ -77,21 +119,34 @@ impl<'a> System<'a> for HandlePlayerInput {
                         player_state.is_sprinting = false;
                     }
 
-                    // Sprint on double press
-                    glfw::WindowEvent::Key(glfw::Key::W, _, glfw::Action::Press, _) => {
-                        if player_state.sprint_throttle {
-                            player_state.sprint_throttle = false;
-                        } else if Instant::now().duration_since(player_state.sprint_last_toggled) < *SPRINTING_TRIGGER_INTERVAL {
-                            player_state.is_sprinting = true;
-                            player_state.sprint_throttle = true;
-                        }
-                        player_state.sprint_last_toggled = Instant::now();
-                    }
-
                     _ => {}
                 }
             }
 
+            // Dash forward and keep sprinting on double press
+            for _ in input_cache.dash_events.iter() {
+                if player_state.is_dashing(now) || player_state.is_sneaking || !player_state.can_sprint() {
+                    continue;
+                }
+                player_state.is_sprinting = true;
+                player_state.dash_started = Some(now);
+
+                let forward = -player_state.rotation.forward().cross(&Vector3::y()).cross(&Vector3::y());
+                if forward.norm() > 0.0 {
+                    let impulse = forward.normalize() * SPRINTING_SPEED * DASH_SPEED_MULTIPLIER;
+                    player_physics_state.velocity.x = impulse.x;
+                    player_physics_state.velocity.z = impulse.z;
+                }
+
+                // A cloud of dust of the block the player stands on
+                let feet = player_physics_state.position;
+                let ground = chunk_manager.get_block(feet.x.floor() as i32, (feet.y - 0.5).floor() as i32, feet.z.floor() as i32);
+                if let Some(ground) = ground.filter(|block| block.is_solid()) {
+                    let particle_system = particle_systems.get_mut("block_particles").unwrap();
+                    particle_system.emit_block_particles(ground, feet - vec3(0.5, 0.5, 0.5), DASH_DUST_PARTICLES, &texture_pack);
+                }
+            }
+
             // Sneaking
             if input_cache.is_key_pressed(glfw::Key::LeftShift) && player_state.is_on_ground {
                 player_state.is_sneaking = true;
@@ -101,7 +156,8 @@ impl<'a> System<'a> for HandlePlayerInput {
             // Sprinting
             if input_cache.is_key_pressed(glfw::Key::LeftControl)
                 && input_cache.is_key_pressed(glfw::Key::W)
-                && !player_state.is_sneaking {
+                && !player_state.is_sneaking
+                && player_state.can_sprint() {
                 player_state.is_sprinting = true;
             }
         }
@@ -113,6 +169,7 @@ pub struct UpdatePlayerState;
 impl<'a> System<'a> for UpdatePlayerState {
     type SystemData = (
         Read<'a, Timer>,
+        Read<'a, Settings>,
         Read<'a, Arc<ChunkManager>>,
         WriteStorage<'a, PlayerState>,
         ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
@@ -121,6 +178,7 @@ impl<'a> System<'a> for UpdatePlayerState {
     fn run(&mut self, data: Self::SystemData) {
         let (
             global_timer,
+            settings,
             chunk_manager,
             mut player_state,
             player_physics_state,
@@ -133,38 +191,54 @@ impl<'a> System<'a> for UpdatePlayerState {
 
             // Camera height
             let target_camera_height = if player_state.is_sneaking {
-                PLAYER_EYES_HEIGHT - 1.0 / 8.0
+                PLAYER_SNEAK_EYES_HEIGHT
             } else {
                 PLAYER_EYES_HEIGHT
             };
             player_state.camera_height.interpolate_camera_height(t, target_camera_height);
 
             // FOV
-            let target_fov = if player_state.is_flying {
+            let fov = settings.fov_radians();
+            let target_fov = if player_state.is_dashing(t) {
+                fov + fov * 0.25
+            } else if player_state.is_flying {
                 if player_state.is_sprinting {
-                    *FOV + *FOV * 0.30
+                    fov + fov * 0.30
                 } else {
-                    *FOV + *FOV * 0.15
+                    fov + fov * 0.15
                 }
             } else {
                 if player_state.is_sprinting {
-                    *FOV + *FOV * 0.15
+                    fov + fov * 0.10
                 } else {
-                    *FOV
+                    fov
                 }
             };
             player_state.fov.interpolate_fov(t, target_fov);
 
-            // Targeted block
-            player_state.targeted_block = {
-                let is_solid_block_at = |x: i32, y: i32, z: i32| {
-                    chunk_manager.is_solid_block_at(x, y, z)
+            // Whether the head of the player is in the water
+            player_state.is_submerged = {
+                let player = player_physics_state.get_interpolated_state();
+                let camera_position = player.position + vec3(0., *player_state.camera_height.get_interpolated_state(), 0.);
+                chunk_manager.get_block(
+                    camera_position.x.floor() as i32,
+                    camera_position.y.floor() as i32,
+                    camera_position.z.floor() as i32) == Some(BlockID::Water)
+            };
+
+            // Targeted block, spectators go through the blocks and can't aim at them
+            player_state.targeted_block = if player_state.is_spectator() {
+                None
+            } else {
+                // Signs can be aimed at even though the player walks through them
+                let is_targetable_block_at = |x: i32, y: i32, z: i32| {
+                    chunk_manager.is_targetable_block_at(x, y, z)
                 };
 
                 let fw = player_state.rotation.forward();
                 let player = player_physics_state.get_interpolated_state();
                 raycast::raycast(
-                    &is_solid_block_at,
+                    &is_targetable_block_at,
                     &(player.position + vec3(0., *player_state.camera_height.get_interpolated_state(), 0.)),
                     &fw.normalize(),
                     REACH_DISTANCE)
@@ -173,7 +247,9 @@ impl<'a> System<'a> for UpdatePlayerState {
             // View and projection matrix
             player_state.view_matrix = {
                 let player_physics_state = player_physics_state.get_interpolated_state();
-                let camera_position = player_physics_state.position + vec3(0., *player_state.camera_height.get_interpolated_state(), 0.);
+                let camera_height = *player_state.camera_height.get_interpolated_state() + player_state.head_bob_offset();
+                let camera_position = player_physics_state.position + vec3(0., camera_height, 0.)
+                    + player_state.camera_shake_offset(t);
                 let looking_dir = player_state.rotation.forward();
                 nalgebra_glm::look_at(&camera_position, &(camera_position + looking_dir), &Vector3::y())
             };
@@ -190,45 +266,177 @@ pub struct PlaceAndBreakBlocks;
 
 impl<'a> System<'a> for PlaceAndBreakBlocks {
     type SystemData = (
+        Entities<'a>,
         Write<'a, Arc<ChunkManager>>,
         Write<'a, ParticleSystems>,
         Read<'a, InputCache>,
         Read<'a, TexturePack>,
+        Read<'a, Timer>,
+        Read<'a, DayCycle>,
+        Write<'a, ActionBar>,
+        Write<'a, Screen>,
+        Write<'a, BlockEntityRegistry>,
+        Write<'a, Statistics>,
+        Write<'a, SoundQueue>,
         WriteStorage<'a, PlayerState>,
         ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
-        ReadStorage<'a, Inventory>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, MainHandItemChanged>,
+        WriteStorage<'a, ItemEntity>,
+        WriteStorage<'a, MobEntity>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
+            entities,
             mut chunk_manager,
             mut particle_systems,
             input_cache,
             texture_pack,
+            global_timer,
+            day_cycle,
+            mut action_bar,
+            mut screen,
+            mut block_entity_registry,
+            mut statistics,
+            mut sound_queue,
             mut player_state,
             player_physics_state,
-            inventory,
+            mut inventory,
+            mut main_hand_item_changed,
+            mut item_entities,
+            mut mobs,
         ) = data;
 
-        for (player_state, player_physics_state, inventory) in (&mut player_state, &player_physics_state, &inventory).join() {
+        // The player is interacting with a GUI
+        if screen.is_open() {
+            return;
+        }
+
+        for (e, player_state, player_physics_state, inventory) in (&entities, &mut player_state, &player_physics_state, &mut inventory).join() {
+            // Spectators only watch
+            if player_state.is_spectator() {
+                continue;
+            }
             let player_physics_state = player_physics_state.get_latest_state();
 
+            // A mob in front of the targeted block is hit instead of mining the block
+            let eyes = player_physics_state.position + vec3(0.0, *player_state.camera_height.get_interpolated_state(), 0.0);
+            let direction = player_state.rotation.forward().normalize();
+            let max_distance = player_state.targeted_block.map_or(MELEE_REACH_DISTANCE, |(block, normal)|
+                raycast::distance_to_face(&eyes, &direction, block, &normal).min(MELEE_REACH_DISTANCE));
+            let targeted_mob = (&entities, &mobs).join()
+                .filter(|(_, mob)| !mob.is_dead())
+                .filter_map(|(mob_entity, mob)| mob.state.aabb.ray_distance(&eyes, &direction, max_distance)
+                    .map(|distance| (mob_entity, distance)))
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                .map(|(mob_entity, _)| mob_entity);
+
             // Place or break a block by clicking on a mouse button
+            let mut is_block_clicked = false;
             for event in &input_cache.events {
                 match event {
                     glfw::WindowEvent::MouseButton(button, Action::Press, _) => {
-                        player_state.block_placing_last_executed = Instant::now();
+                        player_state.block_placing_last_executed = global_timer.time();
 
                         match button {
                             MouseButton::Button1 => {
-                                if let &Some(((x, y, z), _)) = &player_state.targeted_block {
-                                    let mut particle_system = particle_systems.get_mut("block_particles").unwrap();
-                                    break_block((x, y, z), &chunk_manager, &mut particle_system, &texture_pack);
+                                if let Some(mob) = targeted_mob.and_then(|mob_entity| mobs.get_mut(mob_entity)) {
+                                    let sharpness = inventory.get_selected_item_stack()
+                                        .map_or(0, |item_stack| item_stack.enchantment_level(EnchantmentId::Sharpness));
+                                    let damage = melee_damage(inventory.get_selected_item()) + sharpness_bonus(sharpness);
+                                    let damage = player_state.attack_damage(damage, global_timer.time());
+                                    mob.state.health -= damage;
+                                    info!("Hit a {:?} for {} damage", mob.kind, damage);
+                                    let knockback = inventory.get_selected_item_stack()
+                                        .map_or(0, |item_stack| item_stack.enchantment_level(EnchantmentId::Knockback));
+                                    let push = vec3(direction.x, 0.0, direction.z);
+                                    if knockback > 0 && push.norm() > 0.0 {
+                                        mob.state.knockback = push.normalize() * knockback_speed(knockback);
+                                        mob.state.velocity.y = KNOCKBACK_HOP_VELOCITY;
+                                    }
+                                    if inventory.damage_selected_item() {
+                                        if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                                            error!("{}", e);
+                                        }
+                                    }
+                                } else {
+                                    is_block_clicked = true;
                                 }
                             }
                             MouseButton::Button2 => {
                                 if let &Some(((x, y, z), normal)) = &player_state.targeted_block {
-                                    place_block((x, y, z), &normal, &player_physics_state.aabb, &inventory, &chunk_manager);
+                                    let is_bed = chunk_manager.get_block(x, y, z).map_or(false, |block| block.is_bed());
+                                    if is_bed && !player_state.is_sneaking {
+                                        let hostile_mob_positions: Vec<Vec3> = (&mobs).join()
+                                            .filter(|mob| mob.kind.is_hostile() && !mob.is_dead())
+                                            .map(|mob| mob.state.position)
+                                            .collect();
+                                        use_bed((x, y, z), &hostile_mob_positions, &chunk_manager, &day_cycle, &global_timer, &mut action_bar, player_state);
+                                        break;
+                                    }
+                                    let is_beacon = chunk_manager.get_block(x, y, z) == Some(BlockID::Beacon);
+                                    if is_beacon && !player_state.is_sneaking {
+                                        use_beacon((x, y, z), &chunk_manager, &global_timer, &mut block_entity_registry, &mut action_bar);
+                                        break;
+                                    }
+                                    let is_fence_gate = chunk_manager.get_block(x, y, z) == Some(BlockID::OakFenceGate);
+                                    if is_fence_gate && !player_state.is_sneaking {
+                                        use_fence_gate((x, y, z), &chunk_manager, &mut block_entity_registry);
+                                        break;
+                                    }
+                                    let is_campfire = chunk_manager.get_block(x, y, z).map_or(false, |block| block.is_campfire());
+                                    if is_campfire && !player_state.is_sneaking {
+                                        let selected_item = inventory.get_selected_item();
+                                        if use_campfire((x, y, z), inventory, &mut block_entity_registry, &entities, &mut item_entities) {
+                                            if inventory.get_selected_item() != selected_item {
+                                                if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                                                    error!("{}", e);
+                                                }
+                                            }
+                                            break;
+                                        }
+                                    }
+                                    if inventory.get_selected_item() == Some(BlockID::FlintAndSteel) {
+                                        if use_flint_and_steel((x, y, z), &normal, &chunk_manager) && inventory.damage_selected_item() {
+                                            if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                                                error!("{}", e);
+                                            }
+                                        }
+                                        break;
+                                    }
+                                    let is_shovel = inventory.get_selected_item().and_then(tool_properties)
+                                        .map_or(false, |tool| tool.kind == ToolKind::Shovel);
+                                    if is_shovel && use_shovel((x, y, z), &chunk_manager) {
+                                        sound_queue.play(PlaySound::at(BLOCK_PLACE_SOUND, BLOCK_SOUND_VOLUME, block_center((x, y, z), &normal)));
+                                        if inventory.damage_selected_item() {
+                                            if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                                                error!("{}", e);
+                                            }
+                                        }
+                                        break;
+                                    }
+
+                                    // Sneaking allows placing blocks against interactive blocks
+                                    let block_screen = chunk_manager.get_block(x, y, z)
+                                        .and_then(|block| Screen::of_block(block, (x, y, z)))
+                                        .filter(|_| !player_state.is_sneaking);
+                                    if let Some(block_screen) = block_screen {
+                                        // Blocks placed before having a block entity get one when opened
+                                        if !block_entity_registry.contains_key(&(x, y, z)) {
+                                            let block = chunk_manager.get_block(x, y, z).unwrap();
+                                            if let Some(block_entity) = create_block_entity(block) {
+                                                block_entity_registry.insert((x, y, z), block_entity);
+                                            }
+                                        }
+                                        *screen = block_screen;
+                                        info!("Opened {:?}", block_screen);
+                                        break;
+                                    }
+                                    if place_block((x, y, z), &normal, &player_physics_state, &inventory, &chunk_manager, &mut block_entity_registry) {
+                                        statistics.blocks_placed += 1;
+                                        sound_queue.play(PlaySound::at(BLOCK_PLACE_SOUND, BLOCK_SOUND_VOLUME, block_center((x, y, z), &normal)));
+                                    }
                                 }
                             }
                             _ => {}
@@ -238,45 +446,377 @@ impl<'a> System<'a> for PlaceAndBreakBlocks {
                 }
             }
 
-            // Repeated block placing or breaking while the mouse button is pressed
-            let now = Instant::now();
-            if now.duration_since(player_state.block_placing_last_executed).as_secs_f32() >= 0.25 {
-                if input_cache.is_mouse_button_pressed(glfw::MouseButtonLeft) {
-                    if let &Some(((x, y, z), _)) = &player_state.targeted_block {
+            // Breaking a block takes time while the mouse button is pressed, a matching tool speeds it up
+            // and wears out with every block it breaks. In creative, a click breaks the block at once
+            // and holding the button breaks another one every 0.25 seconds, without dropping them.
+            let now = global_timer.time();
+            let is_creative = player_state.game_mode == GameMode::Creative;
+            player_state.mining = match (input_cache.is_mouse_button_pressed(glfw::MouseButtonLeft), player_state.targeted_block) {
+                (true, Some(((x, y, z), _))) if targeted_mob.is_none() => {
+                    let started = match player_state.mining {
+                        Some((position, started)) if position == (x, y, z) => started,
+                        _ => now,
+                    };
+                    let block = chunk_manager.get_block(x, y, z).unwrap();
+                    let is_broken = if is_creative {
+                        is_block_clicked || now.duration_since(player_state.block_placing_last_executed).as_secs_f32() >= 0.25
+                    } else {
+                        let haste = player_state.status_effects.level(StatusEffect::Haste, now) as f32;
+                        let break_time = break_time(block, inventory.get_selected_item_stack()) / (1.0 + HASTE_BONUS_PER_LEVEL * haste);
+                        now.duration_since(started).as_secs_f32() >= break_time
+                    };
+                    if is_broken {
+                        for item_stack in take_contained_items((x, y, z), &mut block_entity_registry) {
+                            if let Err(e) = item_entities.insert(entities.create(), ItemEntity::dropped(item_stack, (x, y, z))) {
+                                error!("{}", e);
+                            }
+                        }
                         let mut particle_system = particle_systems.get_mut("block_particles").unwrap();
-                        break_block((x, y, z), &chunk_manager, &mut particle_system, &texture_pack);
+                        let broken = break_block((x, y, z), &chunk_manager, &mut particle_system, &texture_pack, &mut block_entity_registry, player_state);
+                        if broken.is_some() {
+                            statistics.blocks_broken += 1;
+                            sound_queue.play(PlaySound::at(BLOCK_BREAK_SOUND, BLOCK_SOUND_VOLUME, block_center((x, y, z), &IVec3::zeros())));
+                        }
+                        if is_creative {
+                            player_state.block_placing_last_executed = now;
+                        } else {
+                            // Without a tool able to harvest it, the block drops nothing
+                            let drop = broken
+                                .filter(|&block| can_harvest(block, inventory.get_selected_item()))
+                                .and_then(|block| mined_block_drop(block, inventory.get_selected_item_stack(), &mut rand::thread_rng()));
+                            if let Some((item, amount)) = drop {
+                                if let Err(e) = item_entities.insert(entities.create(), ItemEntity::dropped(ItemStack::new(amount, item), (x, y, z))) {
+                                    error!("{}", e);
+                                }
+                            }
+                            // Only the tool meant for the block wears out
+                            let is_worn = inventory.get_selected_item().and_then(tool_properties)
+                                .map_or(false, |tool| tool.is_correct_tool_for(block));
+                            if is_worn && inventory.damage_selected_item() {
+                                if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                                    error!("{}", e);
+                                }
+                            }
+                        }
+                        None
+                    } else {
+                        Some(((x, y, z), started))
                     }
-                    player_state.block_placing_last_executed = Instant::now();
-                } else if input_cache.is_mouse_button_pressed(glfw::MouseButtonRight) {
+                }
+                _ => None,
+            };
+
+            // Repeated block placing while the mouse button is pressed
+            if now.duration_since(player_state.block_placing_last_executed).as_secs_f32() >= 0.25 {
+                if input_cache.is_mouse_button_pressed(glfw::MouseButtonRight) {
                     if let &Some(((x, y, z), normal)) = &player_state.targeted_block {
-                        place_block((x, y, z), &normal, &player_physics_state.aabb, &inventory, &mut chunk_manager);
+                        let is_interactive = chunk_manager.get_block(x, y, z)
+                            .map_or(false, |block| block.is_bed() || block == BlockID::Beacon || block == BlockID::OakFenceGate || block.is_campfire() || Screen::of_block(block, (x, y, z)).is_some());
+                        if !is_interactive || player_state.is_sneaking {
+                            if place_block((x, y, z), &normal, &player_physics_state, &inventory, &mut chunk_manager, &mut block_entity_registry) {
+                                statistics.blocks_placed += 1;
+                                sound_queue.play(PlaySound::at(BLOCK_PLACE_SOUND, BLOCK_SOUND_VOLUME, block_center((x, y, z), &normal)));
+                            }
+                        }
                     }
-                    player_state.block_placing_last_executed = Instant::now();
+                    player_state.block_placing_last_executed = now;
+                }
+            }
+        }
+    }
+}
+
+/// Lights the obsidian frame next to the clicked face into a Nether portal, or sets the block on fire.
+/// Returns whether anything was lit
+fn use_flint_and_steel((x, y, z): (i32, i32, i32), normal: &IVec3, chunk_manager: &ChunkManager) -> bool {
+    if chunk_manager.get_block(x, y, z) == Some(BlockID::CampfireExtinguished) {
+        if is_doused(chunk_manager, (x, y, z)) {
+            return false;
+        }
+        chunk_manager.put_block(BlockID::Campfire, x, y, z);
+        info!("Lit the campfire at ({} {} {})", x, y, z);
+        return true;
+    }
+    let (x, y, z) = (x + normal.x, y + normal.y, z + normal.z);
+    if let Some(interior) = find_portal_interior(chunk_manager, (x, y, z)) {
+        for &(x, y, z) in &interior {
+            chunk_manager.put_block(BlockID::NetherPortal, x, y, z);
+        }
+        info!("Lit a Nether portal at ({} {} {})", x, y, z);
+        return true;
+    }
+    if chunk_manager.get_block(x, y, z) == Some(BlockID::Air) && chunk_manager.is_solid_block_at(x, y - 1, z) {
+        chunk_manager.put_block(BlockID::Fire, x, y, z);
+        return true;
+    }
+    false
+}
+
+/// Flattens a grass block into a dirt path, unless a block above it is in the way, and puts out a campfire or the fire
+/// around the clicked block. Returns whether the shovel did anything
+fn use_shovel((x, y, z): (i32, i32, i32), chunk_manager: &ChunkManager) -> bool {
+    let mut used = false;
+    if chunk_manager.get_block(x, y, z) == Some(BlockID::Campfire) {
+        chunk_manager.put_block(BlockID::CampfireExtinguished, x, y, z);
+        info!("Put out the campfire at ({} {} {})", x, y, z);
+        used = true;
+    }
+    for &(dx, dy, dz) in [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)].iter() {
+        if chunk_manager.get_block(x + dx, y + dy, z + dz) == Some(BlockID::Fire) {
+            chunk_manager.put_block(BlockID::Air, x + dx, y + dy, z + dz);
+            used = true;
+        }
+    }
+    let is_covered = chunk_manager.get_block(x, y + 1, z).map_or(true, |block| block.is_opaque());
+    if chunk_manager.get_block(x, y, z) == Some(BlockID::GrassBlock) && !is_covered {
+        chunk_manager.put_block(BlockID::DirtPath, x, y, z);
+        info!("Made a dirt path at ({} {} {})", x, y, z);
+        used = true;
+    }
+    used
+}
+
+/// Takes the cooked food off the campfire, what doesn't fit in the inventory pops out of it.
+/// Otherwise puts the selected raw food on it. Returns whether the campfire was used
+fn use_campfire(position: (i32, i32, i32), inventory: &mut Inventory, block_entity_registry: &mut BlockEntityRegistry,
+                entities: &Entities, item_entities: &mut WriteStorage<ItemEntity>) -> bool {
+    let campfire = block_entity_registry.entry(position)
+        .or_insert_with(|| Box::new(CampfireBlockEntity::new()))
+        .downcast_mut::<CampfireBlockEntity>();
+    let campfire = match campfire {
+        Some(campfire) => campfire,
+        None => return false,
+    };
+
+    let cooked = campfire.take_cooked();
+    if !cooked.is_empty() {
+        for item in cooked {
+            if !inventory.add_item(item) {
+                let (x, y, z) = position;
+                let drop = ItemEntity::popped(ItemStack::new(1, item), vec3(x as f32 + 0.5, y as f32 + 1.0, z as f32 + 0.5));
+                if let Err(e) = item_entities.insert(entities.create(), drop) {
+                    error!("{}", e);
                 }
             }
         }
+        info!("Took the cooked food off the campfire");
+        return true;
+    }
+
+    match inventory.get_selected_item() {
+        Some(item) if campfire.add_food(item) => {
+            inventory.consume_selected_item();
+            info!("Put {:?} on the campfire", item);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Sets the respawn point and goes to sleep if it is night and there are no monsters around
+fn use_bed(position: (i32, i32, i32), hostile_mob_positions: &[Vec3], chunk_manager: &ChunkManager, day_cycle: &DayCycle, global_timer: &Timer, action_bar: &mut ActionBar, player_state: &mut PlayerState) {
+    let now = global_timer.time();
+    let head = match bed_head_position(chunk_manager, position) {
+        Some(head) => head,
+        None => return,
+    };
+    if !day_cycle.is_night() {
+        action_bar.show("You can only sleep at night", now);
+        return;
+    }
+
+    let spawn_point = bed_spawn_point(head);
+    if hostile_mob_positions.iter().any(|mob| (mob - spawn_point).norm() < SLEEP_MONSTER_DISTANCE) {
+        action_bar.show("You may not rest now, there are monsters nearby", now);
+        return;
+    }
+
+    player_state.spawn_point = spawn_point;
+    player_state.sleep_started = Some(now);
+    action_bar.show("Respawn point set", now);
+}
+
+/// Checks the pyramid under the beacon and switches to the next effect it unlocks
+fn use_beacon(position: (i32, i32, i32), chunk_manager: &ChunkManager, global_timer: &Timer, block_entity_registry: &mut BlockEntityRegistry, action_bar: &mut ActionBar) {
+    let now = global_timer.time();
+    let beacon = block_entity_registry.entry(position)
+        .or_insert_with(|| Box::new(BeaconBlockEntity::new()))
+        .downcast_mut::<BeaconBlockEntity>();
+    let beacon = match beacon {
+        Some(beacon) => beacon,
+        None => return,
+    };
+
+    beacon.levels = pyramid_levels(chunk_manager, position);
+    if beacon.levels == 0 {
+        action_bar.show("The beacon needs a pyramid of mineral blocks under it", now);
+        return;
+    }
+    match beacon.cycle_effect() {
+        Some(effect) => action_bar.show(&format!("Beacon effect: {}", effect.name()), now),
+        None => action_bar.show("Beacon effect: none", now),
+    }
+}
+
+/// Opens the fence gate if it is closed and closes it otherwise
+fn use_fence_gate((x, y, z): (i32, i32, i32), chunk_manager: &ChunkManager, block_entity_registry: &mut BlockEntityRegistry) {
+    let meta = match chunk_manager.get_block_meta(x, y, z) {
+        Some(meta) => meta,
+        None => return,
+    };
+    let is_open = !meta.is_open();
+    chunk_manager.set_block_meta(meta.with_open(is_open), x, y, z);
+
+    let gate = block_entity_registry.entry((x, y, z))
+        .or_insert_with(|| Box::new(FenceGateBlockEntity::new()))
+        .downcast_mut::<FenceGateBlockEntity>();
+    if let Some(gate) = gate {
+        gate.swing(is_open);
+    }
+}
+
+/// Takes out the items kept in the block entity at `position`, which fall when the block is broken
+fn take_contained_items(position: (i32, i32, i32), block_entity_registry: &mut BlockEntityRegistry) -> Vec<ItemStack> {
+    let block_entity = match block_entity_registry.get_mut(&position) {
+        Some(block_entity) => block_entity,
+        None => return Vec::new(),
+    };
+    if let Some(campfire) = block_entity.downcast_ref::<CampfireBlockEntity>() {
+        // The food on a campfire falls off with it
+        campfire.items().into_iter().map(|item| ItemStack::new(1, item)).collect()
+    } else if let Some(chest) = block_entity.downcast_mut::<ChestBlockEntity>() {
+        chest.0.iter_mut().filter_map(Option::take).collect()
+    } else if let Some(chest) = block_entity.downcast_ref::<LootChestBlockEntity>() {
+        // Never opened, the loot was still waiting to become item stacks
+        chest.loot.iter().map(|&(item, count)| ItemStack::new(count, item)).collect()
+    } else if let Some(crafting_table) = block_entity.downcast_mut::<CraftingTableBlockEntity>() {
+        // The output is only a preview of what the grid crafts
+        crafting_table.output = None;
+        crafting_table.grid.iter_mut().flat_map(|row| row.iter_mut()).filter_map(Option::take).collect()
+    } else {
+        Vec::new()
     }
 }
 
-fn break_block((x, y, z): (i32, i32, i32), chunk_manager: &ChunkManager, particle_system: &mut ParticleSystem, uv_map: &TexturePack) {
+/// Returns the block that was broken, if there was one
+fn break_block((x, y, z): (i32, i32, i32), chunk_manager: &ChunkManager, particle_system: &mut ParticleSystem, uv_map: &TexturePack, block_entity_registry: &mut BlockEntityRegistry, player_state: &mut PlayerState) -> Option<BlockID> {
     let block = chunk_manager.get_block(x, y, z).unwrap();
+    if block.is_bed() {
+        // Both halves go away together
+        if let Some((head_x, head_y, head_z)) = bed_head_position(chunk_manager, (x, y, z)) {
+            let (dx, dz) = chunk_manager.get_block_meta(x, y, z).unwrap().facing_offset();
+            chunk_manager.put_block(BlockID::Air, head_x, head_y, head_z);
+            chunk_manager.put_block(BlockID::Air, head_x - dx, head_y, head_z - dz);
+            block_entity_registry.remove(&(head_x, head_y, head_z));
+            if player_state.spawn_point == bed_spawn_point((head_x, head_y, head_z)) {
+                player_state.spawn_point = vec3(WORLD_SPAWN_POINT.0, WORLD_SPAWN_POINT.1, WORLD_SPAWN_POINT.2);
+                info!("Respawn point reset to the world spawn");
+            }
+        }
+    }
+    // The blocks moved by a piston get to their destination before it goes away
+    let moving = block_entity_registry.get_mut(&(x, y, z))
+        .and_then(|block_entity| block_entity.downcast_mut::<PistonBlockEntity>())
+        .and_then(|piston| piston.moving.take());
+    if let Some(moving) = moving {
+        finish_piston_move(chunk_manager, block_entity_registry, (x, y, z), moving);
+    }
     if block != BlockID::Air {
         chunk_manager.put_block(BlockID::Air, x, y, z);
-        particle_system.spawn_block_breaking_particles(vec3(x as f32, y as f32, z as f32), &uv_map, block);
+        block_entity_registry.remove(&(x, y, z));
+        particle_system.emit_block_particles(block, vec3(x as f32, y as f32, z as f32), 64, &uv_map);
         info!("Destroyed block at ({} {} {})", x, y, z);
+        return Some(block);
     }
+    None
+}
+
+/// Center of the block next to the given one in the direction of the normal
+fn block_center((x, y, z): (i32, i32, i32), normal: &IVec3) -> Vec3 {
+    vec3((x + normal.x) as f32 + 0.5, (y + normal.y) as f32 + 0.5, (z + normal.z) as f32 + 0.5)
 }
 
-fn place_block((x, y, z): (i32, i32, i32), normal: &IVec3, player_aabb: &AABB, inventory: &Inventory, chunk_manager: &ChunkManager) {
+/// Returns whether a block was put
+fn place_block((x, y, z): (i32, i32, i32), normal: &IVec3, player: &PlayerPhysicsState, inventory: &Inventory, chunk_manager: &ChunkManager, block_entity_registry: &mut BlockEntityRegistry) -> bool {
     let adjacent_block = IVec3::new(x, y, z) + normal;
     let adjacent_block_aabb = get_block_aabb(&vec3(
         adjacent_block.x as f32,
         adjacent_block.y as f32,
         adjacent_block.z as f32));
-    if !player_aabb.intersects(&adjacent_block_aabb) {
-        if let Some(block) = inventory.get_selected_item() {
+    if !player.aabb.intersects(&adjacent_block_aabb) {
+        if let Some(block) = inventory.get_selected_item().filter(|block| block.is_placeable()) {
+            if block == BlockID::BedFoot {
+                return place_bed(&adjacent_block, player, chunk_manager, block_entity_registry);
+            }
+            if block == BlockID::Ladder {
+                return place_ladder(&adjacent_block, normal, chunk_manager);
+            }
+            if (block == BlockID::RedstoneWire || block.is_pressure_plate()) && !chunk_manager.is_solid_block_at(adjacent_block.x, adjacent_block.y - 1, adjacent_block.z) {
+                return false;
+            }
             chunk_manager.put_block(block, adjacent_block.x, adjacent_block.y, adjacent_block.z);
+
+            let position = (adjacent_block.x, adjacent_block.y, adjacent_block.z);
+            if block == BlockID::Chest || block == BlockID::Sign || block.is_piston() || block == BlockID::Observer || block == BlockID::OakFenceGate {
+                let facing = facing_towards(&adjacent_block, &player.position);
+                chunk_manager.set_block_meta(BlockMeta::default().with_facing(facing), adjacent_block.x, adjacent_block.y, adjacent_block.z);
+            }
+            if let Some(block_entity) = create_block_entity(block) {
+                block_entity_registry.insert(position, block_entity);
+            }
+            info!("Put block at ({} {} {})", adjacent_block.x, adjacent_block.y, adjacent_block.z);
+            return true;
         }
-        info!("Put block at ({} {} {})", adjacent_block.x, adjacent_block.y, adjacent_block.z);
     }
-}
\ No newline at end of file
+    false
+}
+
+/// Puts the foot of the bed at `foot` and its head further away from the player, if there is room for it
+fn place_bed(foot: &IVec3, player: &PlayerPhysicsState, chunk_manager: &ChunkManager, block_entity_registry: &mut BlockEntityRegistry) -> bool {
+    let facing = (facing_towards(foot, &player.position) + 2) % 4;
+    let meta = BlockMeta::default().with_facing(facing);
+    let (dx, dz) = meta.facing_offset();
+    let head = IVec3::new(foot.x + dx, foot.y, foot.z + dz);
+
+    let head_aabb = get_block_aabb(&vec3(head.x as f32, head.y as f32, head.z as f32));
+    if chunk_manager.get_block(head.x, head.y, head.z) != Some(BlockID::Air) || player.aabb.intersects(&head_aabb) {
+        return false;
+    }
+
+    chunk_manager.put_block(BlockID::BedFoot, foot.x, foot.y, foot.z);
+    chunk_manager.set_block_meta(meta.with_bed_head(false), foot.x, foot.y, foot.z);
+    chunk_manager.put_block(BlockID::BedHead, head.x, head.y, head.z);
+    chunk_manager.set_block_meta(meta.with_bed_head(true), head.x, head.y, head.z);
+    if let Some(block_entity) = create_block_entity(BlockID::BedHead) {
+        block_entity_registry.insert((head.x, head.y, head.z), block_entity);
+    }
+    info!("Put bed at ({} {} {})", foot.x, foot.y, foot.z);
+    true
+}
+
+/// Hangs a ladder on the side of the block that was clicked, ladders can't be put on the floor or the ceiling
+fn place_ladder(position: &IVec3, normal: &IVec3, chunk_manager: &ChunkManager) -> bool {
+    let facing = match (normal.x, normal.z) {
+        (0, 1) => 0,
+        (-1, 0) => 1,
+        (0, -1) => 2,
+        (1, 0) => 3,
+        _ => return false,
+    };
+
+    chunk_manager.put_block(BlockID::Ladder, position.x, position.y, position.z);
+    chunk_manager.set_block_meta(BlockMeta::default().with_facing(facing), position.x, position.y, position.z);
+    info!("Put ladder at ({} {} {})", position.x, position.y, position.z);
+    true
+}
+
+/// The horizontal direction (as stored in `BlockMeta`) pointing from the block to the position
+fn facing_towards(block: &IVec3, position: &Vec3) -> u8 {
+    let dx = position.x - (block.x as f32 + 0.5);
+    let dz = position.z - (block.z as f32 + 0.5);
+    if dx.abs() > dz.abs() {
+        if dx > 0.0 { 3 } else { 1 }
+    } else {
+        if dz > 0.0 { 0 } else { 2 }
+    }
+}
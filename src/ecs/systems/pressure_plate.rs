@@ -0,0 +1,215 @@
+use std::ffi::c_void;
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::vec3;
+use specs::{Join, Read, ReadStorage, System, Write};
+
+use crate::aabb::AABB;
+use crate::block_update::BlockUpdateQueue;
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{PRESSURE_PLATE_HEIGHT, PRESSURE_PLATE_RELEASE_TICKS, TICKS_PER_SECOND};
+use crate::mob::MobEntity;
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::pressure_plate::PressurePlateBlockEntity;
+use crate::shapes::{PRESSURE_PLATE_INSET, flat_slab_mesh};
+use crate::timer::Timer;
+use crate::types::{BlockEntityRegistry, Shaders, TexturePack};
+use crate::util::is_sphere_in_frustum;
+
+/// Presses the pressure plates the players and the mobs stand on, which powers the redstone around them.
+/// A plate springs back up a few ticks after it is left
+pub struct UpdatePressurePlates {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl UpdatePressurePlates {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdatePressurePlates {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, BlockEntityRegistry>,
+        Write<'a, BlockUpdateQueue>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        ReadStorage<'a, MobEntity>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            mut block_entity_registry,
+            mut block_update_queue,
+            player_physics_state,
+            mob_entity,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            let entity_aabbs: Vec<AABB> = (&player_physics_state).join()
+                .map(|player_physics_state| player_physics_state.get_latest_state().aabb)
+                .chain((&mob_entity).join().map(|mob| mob.state.aabb))
+                .collect();
+
+            for (&(x, y, z), block_entity) in block_entity_registry.iter_mut() {
+                let plate = match block_entity.downcast_mut::<PressurePlateBlockEntity>() {
+                    Some(plate) => plate,
+                    None => continue,
+                };
+                // The chunk isn't loaded
+                let meta = match chunk_manager.get_block_meta(x, y, z) {
+                    Some(meta) => meta,
+                    None => continue,
+                };
+
+                let plate_aabb = AABB::new(
+                    vec3(x as f32 + PRESSURE_PLATE_INSET, y as f32, z as f32 + PRESSURE_PLATE_INSET),
+                    vec3(x as f32 + 1.0 - PRESSURE_PLATE_INSET, y as f32 + PRESSURE_PLATE_HEIGHT, z as f32 + 1.0 - PRESSURE_PLATE_INSET));
+                if entity_aabbs.iter().any(|aabb| aabb.intersects(&plate_aabb)) {
+                    plate.release_ticks = PRESSURE_PLATE_RELEASE_TICKS;
+                    if !meta.is_pressed() {
+                        chunk_manager.set_block_meta(meta.with_pressed(true), x, y, z);
+                        block_update_queue.enqueue_neighbours((x, y, z));
+                    }
+                } else if meta.is_pressed() {
+                    plate.release_ticks = plate.release_ticks.saturating_sub(1);
+                    if plate.release_ticks == 0 {
+                        chunk_manager.set_block_meta(meta.with_pressed(false), x, y, z);
+                        block_update_queue.enqueue_neighbours((x, y, z));
+                    }
+                }
+            }
+
+            // Forget the plates that were broken or popped off
+            block_entity_registry.retain(|&(x, y, z), block_entity| {
+                block_entity.downcast_ref::<PressurePlateBlockEntity>().is_none()
+                    || chunk_manager.get_block(x, y, z).map_or(true, |block| block.is_pressure_plate())
+            });
+
+            self.accumulator -= tick_duration;
+        }
+    }
+}
+
+pub struct RenderPressurePlates {
+    vao: u32,
+    vbo: u32,
+    is_uploaded: bool,
+}
+
+impl RenderPressurePlates {
+    pub fn new() -> Self {
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        // Position
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+        // Texture coords
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 1, 3 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+        // Normals
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 2));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 2, 3 as i32, gl::FLOAT, gl::FALSE, 6 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 2, 0));
+
+        let mut vbo = 0;
+        gl_call!(gl::CreateBuffers(1, &mut vbo));
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (9 * std::mem::size_of::<f32>()) as i32));
+
+        Self {
+            vao,
+            vbo,
+            is_uploaded: false,
+        }
+    }
+
+    /// The stone plate released then pressed, followed by the wooden one, 36 vertices each
+    fn upload_model(&mut self, texture_pack: &TexturePack) {
+        let vbo_data: Vec<f32> = [BlockID::StonePressurePlate, BlockID::WoodenPressurePlate].iter()
+            .flat_map(|block| {
+                let uvs = texture_pack.get(block).unwrap().get_uv_of_every_face();
+                flat_slab_mesh(PRESSURE_PLATE_HEIGHT, uvs).into_iter()
+                    .chain(flat_slab_mesh(PRESSURE_PLATE_HEIGHT / 2.0, uvs))
+            })
+            .collect();
+
+        gl_call!(gl::NamedBufferData(self.vbo,
+                    (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                    vbo_data.as_ptr() as *const c_void,
+                    gl::STATIC_DRAW));
+        self.is_uploaded = true;
+    }
+}
+
+impl<'a> System<'a> for RenderPressurePlates {
+    type SystemData = (
+        Read<'a, BlockEntityRegistry>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        ReadStorage<'a, PlayerState>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            block_entity_registry,
+            chunk_manager,
+            texture_pack,
+            player_state,
+            mut shaders,
+        ) = data;
+
+        if !self.is_uploaded {
+            self.upload_model(&texture_pack);
+        }
+
+        for player_state in (&player_state).join() {
+            let view_projection = player_state.projection_matrix * player_state.view_matrix;
+
+            let model_shader = shaders.get_mut("model_shader").unwrap();
+            model_shader.use_program();
+            model_shader.set_uniform1i("tex", 0);
+            gl_call!(gl::BindVertexArray(self.vao));
+
+            for (&(x, y, z), _) in block_entity_registry.iter()
+                .filter(|(_, block_entity)| block_entity.downcast_ref::<PressurePlateBlockEntity>().is_some()) {
+                // The chunk isn't loaded
+                let (block, meta) = match (chunk_manager.get_block(x, y, z), chunk_manager.get_block_meta(x, y, z)) {
+                    (Some(block), Some(meta)) if block.is_pressure_plate() => (block, meta),
+                    _ => continue,
+                };
+
+                let center = vec3(x as f32 + 0.5, y as f32, z as f32 + 0.5);
+                if !is_sphere_in_frustum(&view_projection, &center, 0.71) {
+                    continue;
+                }
+
+                let mesh_index = 2 * (block == BlockID::WoodenPressurePlate) as i32 + meta.is_pressed() as i32;
+                let model_matrix = Matrix4::new_translation(&vec3(x as f32, y as f32, z as f32));
+                model_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+                gl_call!(gl::DrawArrays(gl::TRIANGLES, 36 * mesh_index, 36));
+            }
+        }
+    }
+}
@@ -0,0 +1,474 @@
+use std::ffi::c_void;
+use std::sync::Arc;
+use std::time::Instant;
+
+use glfw::{Action, MouseButton, WindowEvent};
+use nalgebra::Matrix4;
+use nalgebra_glm::{floor, vec3, Vec3};
+use specs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{ARROW_DESPAWN_TIME, CROSSBOW_CHARGE_DURATION, PLAYER_HALF_HEIGHT, PROJECTILE_GRAVITY};
+use crate::ecs::components::MainHandItemChanged;
+use crate::explosion::{explode, explosion_damage};
+use crate::input::InputCache;
+use crate::inventory::Inventory;
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::mob::MobEntity;
+use crate::projectile::{CrossbowState, Projectile};
+use crate::raycast;
+use crate::screen::Screen;
+use crate::shapes::textured_box;
+use crate::timer::Timer;
+use crate::types::{BlockEntityRegistry, ParticleSystems, Shaders, TexturePack};
+use crate::util::{Forward, is_sphere_in_frustum};
+
+/// Every item that can be thrown, in the order of their meshes in the VBO of `RenderProjectiles`
+const PROJECTILE_ITEMS: [BlockID; 2] = [BlockID::Snowball, BlockID::Arrow];
+/// Distance between the center of a stuck arrow and the face of its block, the arrow being half a block long
+const ARROW_STICK_BACK_OFFSET: f32 = 0.15;
+/// Projectiles only hit the player after flying this far, so that they don't hit the one throwing them
+const PLAYER_HIT_MIN_DISTANCE: f32 = 2.0;
+
+/// Throws the selected item in the direction the player is looking at when right-clicking
+pub struct ThrowProjectiles;
+
+impl<'a> System<'a> for ThrowProjectiles {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Timer>,
+        Read<'a, InputCache>,
+        Read<'a, Screen>,
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, Projectile>,
+        WriteStorage<'a, MainHandItemChanged>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            global_timer,
+            input_cache,
+            screen,
+            player_state,
+            player_physics_state,
+            mut inventory,
+            mut projectiles,
+            mut main_hand_item_changed,
+        ) = data;
+
+        // Right-clicking an interactive block opened its screen instead
+        if screen.is_open() {
+            return;
+        }
+
+        let right_clicked = input_cache.events.iter().any(|event| match event {
+            WindowEvent::MouseButton(MouseButton::Button2, Action::Press, _) => true,
+            _ => false
+        });
+        if !right_clicked {
+            return;
+        }
+
+        let mut thrown = Vec::new();
+        for (e, player_state, player_physics_state, inventory) in (&entities, &player_state, &player_physics_state, &mut inventory).join() {
+            let item = match inventory.get_selected_item().filter(|item| item.is_throwable()) {
+                Some(item) => item,
+                None => continue,
+            };
+
+            let direction = player_state.rotation.forward().normalize();
+            let eyes = player_physics_state.get_latest_state().position
+                + vec3(0.0, *player_state.camera_height.get_interpolated_state(), 0.0);
+            if let Some(mut projectile) = Projectile::thrown(item, eyes + direction * 0.3, direction) {
+                projectile.damage = player_state.attack_damage(projectile.damage, global_timer.time());
+                thrown.push(projectile);
+                inventory.consume_selected_item();
+                info!("Threw {:?}", item);
+
+                if inventory.get_selected_item() != Some(item) {
+                    if let Err(e) = main_hand_item_changed.insert(e, MainHandItemChanged) {
+                        error!("{}", e);
+                    }
+                }
+            }
+        }
+
+        for projectile in thrown {
+            if let Err(e) = projectiles.insert(entities.create(), projectile) {
+                error!("{}", e);
+            }
+        }
+    }
+}
+
+/// Charges the crossbow held by the player while the right button is held, with an arrow in the inventory.
+/// Releasing the button once it's charged shoots the arrow, releasing it earlier or putting the crossbow away uncharges it
+pub struct ShootCrossbows;
+
+impl<'a> System<'a> for ShootCrossbows {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Timer>,
+        Read<'a, InputCache>,
+        Read<'a, Screen>,
+        WriteStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        WriteStorage<'a, Inventory>,
+        WriteStorage<'a, Projectile>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            global_timer,
+            input_cache,
+            screen,
+            mut player_state,
+            player_physics_state,
+            mut inventory,
+            mut projectiles,
+        ) = data;
+
+        let now = global_timer.time();
+        let is_held = !screen.is_open() && input_cache.is_mouse_button_pressed(MouseButton::Button2);
+
+        let mut shot = Vec::new();
+        for (player_state, player_physics_state, inventory) in (&mut player_state, &player_physics_state, &mut inventory).join() {
+            let selected_hotbar_slot = inventory.selected_hotbar_slot;
+            for (i, slot) in inventory.slots.iter_mut().enumerate() {
+                if let Some(item_stack) = slot {
+                    if item_stack.item == BlockID::Crossbow && i != selected_hotbar_slot {
+                        item_stack.meta = CrossbowState::Uncharged as u8;
+                    }
+                }
+            }
+
+            let has_arrow = inventory.slots.iter().flatten().any(|item_stack| item_stack.item == BlockID::Arrow);
+            let crossbow = match &mut inventory.slots[selected_hotbar_slot] {
+                Some(item_stack) if item_stack.item == BlockID::Crossbow => item_stack,
+                _ => {
+                    player_state.crossbow_charge_started = None;
+                    continue;
+                }
+            };
+
+            match (CrossbowState::from_meta(crossbow.meta), is_held) {
+                (CrossbowState::Uncharged, true) if has_arrow => {
+                    crossbow.meta = CrossbowState::Charging as u8;
+                    player_state.crossbow_charge_started = Some(now);
+                }
+                (CrossbowState::Charging, true) => {
+                    let charged = player_state.crossbow_charge_started
+                        .map_or(true, |started| now.saturating_duration_since(started).as_secs_f32() >= CROSSBOW_CHARGE_DURATION);
+                    if charged {
+                        crossbow.meta = CrossbowState::Charged as u8;
+                    }
+                }
+                (CrossbowState::Charged, false) => {
+                    crossbow.meta = CrossbowState::Uncharged as u8;
+                    player_state.crossbow_charge_started = None;
+                    if inventory.consume_item(BlockID::Arrow) {
+                        let direction = player_state.rotation.forward().normalize();
+                        let eyes = player_physics_state.get_latest_state().position
+                            + vec3(0.0, *player_state.camera_height.get_interpolated_state(), 0.0);
+                        let mut arrow = Projectile::shot_from_crossbow(eyes + direction * 0.3, direction);
+                        arrow.damage = player_state.attack_damage(arrow.damage, now);
+                        shot.push(arrow);
+                        info!("Shot an arrow with the crossbow");
+                    }
+                }
+                (CrossbowState::Charging, false) => {
+                    crossbow.meta = CrossbowState::Uncharged as u8;
+                    player_state.crossbow_charge_started = None;
+                }
+                _ => {}
+            }
+        }
+
+        for projectile in shot {
+            if let Err(e) = projectiles.insert(entities.create(), projectile) {
+                error!("{}", e);
+            }
+        }
+    }
+}
+
+/// Moves the projectiles and handles what they hit.
+/// A projectile shatters the fragile block it hits, a burning one also ignites TNT.
+/// Projectiles hurt the mobs and the player they hit, arrows stick in the other blocks they hit.
+/// The explosions hurt the player around them
+pub struct UpdateProjectiles {
+    last_time: Instant,
+}
+
+impl UpdateProjectiles {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateProjectiles {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        Write<'a, ParticleSystems>,
+        Write<'a, BlockEntityRegistry>,
+        WriteStorage<'a, Projectile>,
+        WriteStorage<'a, MobEntity>,
+        WriteStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        WriteStorage<'a, Inventory>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            global_timer,
+            chunk_manager,
+            texture_pack,
+            mut particle_systems,
+            mut block_entity_registry,
+            mut projectiles,
+            mut mobs,
+            mut player_state,
+            player_physics_state,
+            mut inventory,
+        ) = data;
+
+        let now = global_timer.time();
+        let dt = now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let particle_system = particle_systems.get_mut("block_particles").unwrap();
+        let is_solid_block_at = |x: i32, y: i32, z: i32| chunk_manager.is_solid_block_at(x, y, z);
+
+        for (e, projectile) in (&entities, &mut projectiles).join() {
+            if let Some((x, y, z)) = projectile.stuck_in {
+                projectile.stuck_time += dt;
+                if projectile.stuck_time > ARROW_DESPAWN_TIME {
+                    if let Err(e) = entities.delete(e) {
+                        error!("{}", e);
+                    }
+                } else if !chunk_manager.is_solid_block_at(x, y, z) {
+                    // The block is gone, the arrow falls
+                    projectile.stuck_in = None;
+                    projectile.velocity = Vec3::zeros();
+                }
+                continue;
+            }
+
+            projectile.velocity.y += PROJECTILE_GRAVITY * dt;
+            let step: Vec3 = projectile.velocity * dt;
+            let step_length = step.norm();
+            if step_length == 0.0 {
+                continue;
+            }
+            let direction = step / step_length;
+
+            let block_hit = raycast::raycast(&is_solid_block_at, &projectile.position, &direction, step_length);
+            let block_distance = block_hit
+                .map_or(step_length, |(block, normal)| raycast::distance_to_face(&projectile.position, &direction, block, &normal));
+            let mob_hit = (&mut mobs).join()
+                .filter(|mob| !mob.is_dead())
+                .filter_map(|mob| mob.state.aabb.ray_distance(&projectile.position, &direction, block_distance)
+                    .map(|distance| (mob, distance)))
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+            if let Some((mob, _)) = mob_hit {
+                mob.state.health -= projectile.damage;
+                info!("{:?} hit a {:?} for {} damage", projectile.item, mob.kind, projectile.damage);
+                if let Err(e) = entities.delete(e) {
+                    error!("{}", e);
+                }
+                continue;
+            }
+
+            let player_hit = (&mut player_state, &player_physics_state, &mut inventory).join()
+                .filter(|(player_state, _, _)| !player_state.is_spectator() && !player_state.is_dead)
+                .find(|(_, player_physics_state, _)| projectile.traveled > PLAYER_HIT_MIN_DISTANCE
+                    && player_physics_state.get_latest_state().aabb.ray_distance(&projectile.position, &direction, block_distance).is_some());
+            if let Some((player_state, _, inventory)) = player_hit {
+                let damage = player_state.take_damage(projectile.damage, &mut inventory.armor, now);
+                info!("{:?} hit the player for {:.1} damage", projectile.item, damage);
+                if player_state.health <= 0.0 {
+                    player_state.die("Was shot");
+                }
+                if let Err(e) = entities.delete(e) {
+                    error!("{}", e);
+                }
+                continue;
+            }
+
+            match block_hit {
+                Some(((x, y, z), _)) => {
+                    let block = chunk_manager.get_block(x, y, z).unwrap();
+                    let mut sticks = projectile.item == BlockID::Arrow;
+                    if block == BlockID::Tnt && projectile.on_fire {
+                        let exploded = explode((x, y, z), &chunk_manager, &mut block_entity_registry, particle_system, &texture_pack);
+                        for (player_state, player_physics_state, inventory) in (&mut player_state, &player_physics_state, &mut inventory).join() {
+                            if player_state.is_spectator() || player_state.is_dead {
+                                continue;
+                            }
+                            let center = player_physics_state.get_latest_state().position + vec3(0.0, PLAYER_HALF_HEIGHT, 0.0);
+                            let damage = exploded.iter().map(|&tnt| explosion_damage(tnt, &center)).sum();
+                            player_state.take_damage(damage, &mut inventory.armor, now);
+                            if player_state.health <= 0.0 {
+                                player_state.die("Blew up");
+                            }
+                        }
+                        sticks = false;
+                    } else if block.is_fragile() {
+                        chunk_manager.put_block(BlockID::Air, x, y, z);
+                        particle_system.emit_block_particles(block, vec3(x as f32, y as f32, z as f32), 64, &texture_pack);
+                        info!("{:?} shattered {:?} at ({} {} {})", projectile.item, block, x, y, z);
+                        sticks = false;
+                    }
+                    if projectile.item == BlockID::Snowball {
+                        let position = floor(&projectile.position);
+                        particle_system.emit_block_particles(projectile.item, position, 8, &texture_pack);
+                    }
+
+                    if sticks {
+                        // The tip of the arrow sinks a little into the block, the velocity only keeps it pointing there
+                        projectile.position += direction * (block_distance - ARROW_STICK_BACK_OFFSET);
+                        projectile.velocity = direction;
+                        projectile.stuck_in = Some((x, y, z));
+                        projectile.stuck_time = 0.0;
+                    } else if let Err(e) = entities.delete(e) {
+                        error!("{}", e);
+                    }
+                }
+                None => {
+                    projectile.position += step;
+                    projectile.traveled += step_length;
+
+                    let cell = floor(&projectile.position);
+                    let block = chunk_manager.get_block(cell.x as i32, cell.y as i32, cell.z as i32);
+                    if block == Some(BlockID::Lava) && projectile.item == BlockID::Arrow {
+                        projectile.on_fire = true;
+                    }
+                    // Fell out of the loaded world or flew too far
+                    if block.is_none() || projectile.traveled > projectile.max_distance {
+                        if let Err(e) = entities.delete(e) {
+                            error!("{}", e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct RenderProjectiles {
+    vao: u32,
+    vbo: u32,
+    is_uploaded: bool,
+}
+
+impl RenderProjectiles {
+    pub fn new() -> Self {
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        // Position
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+        // Texture coords
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 1, 3 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+        // Normals
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 2));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 2, 3 as i32, gl::FLOAT, gl::FALSE, 6 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 2, 0));
+
+        let mut vbo = 0;
+        gl_call!(gl::CreateBuffers(1, &mut vbo));
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (9 * std::mem::size_of::<f32>()) as i32));
+
+        Self {
+            vao,
+            vbo,
+            is_uploaded: false,
+        }
+    }
+
+    /// One box of 36 vertices per item, centered on the origin
+    fn upload_model(&mut self, texture_pack: &TexturePack) {
+        let vbo_data: Vec<f32> = PROJECTILE_ITEMS.iter()
+            .flat_map(|&item| {
+                let (x, y, z) = Projectile::half_extents(item);
+                let uvs = texture_pack.get(&item).unwrap().get_uv_of_every_face();
+                textured_box((-x, -y, -z), (x, y, z), uvs)
+            })
+            .collect();
+
+        gl_call!(gl::NamedBufferData(self.vbo,
+                    (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                    vbo_data.as_ptr() as *const c_void,
+                    gl::STATIC_DRAW));
+        self.is_uploaded = true;
+    }
+}
+
+impl<'a> System<'a> for RenderProjectiles {
+    type SystemData = (
+        Read<'a, TexturePack>,
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Projectile>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            texture_pack,
+            player_state,
+            projectiles,
+            mut shaders,
+        ) = data;
+
+        if !self.is_uploaded {
+            self.upload_model(&texture_pack);
+        }
+
+        for player_state in (&player_state).join() {
+            let view_projection = player_state.projection_matrix * player_state.view_matrix;
+
+            let model_shader = shaders.get_mut("model_shader").unwrap();
+            model_shader.use_program();
+            model_shader.set_uniform1i("tex", 0);
+            gl_call!(gl::BindVertexArray(self.vao));
+
+            for projectile in (&projectiles).join() {
+                if !is_sphere_in_frustum(&view_projection, &projectile.position, 0.5) {
+                    continue;
+                }
+                let mesh_index = match PROJECTILE_ITEMS.iter().position(|&item| item == projectile.item) {
+                    Some(mesh_index) => mesh_index,
+                    None => continue,
+                };
+
+                // Turn the Z axis of the box towards the velocity
+                let v = projectile.velocity;
+                let yaw = v.x.atan2(v.z);
+                let pitch = v.y.atan2((v.x * v.x + v.z * v.z).sqrt());
+                let model_matrix = Matrix4::new_translation(&projectile.position)
+                    * Matrix4::from_euler_angles(0.0, yaw, 0.0)
+                    * Matrix4::from_euler_angles(-pitch, 0.0, 0.0);
+                model_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+                gl_call!(gl::DrawArrays(gl::TRIANGLES, 36 * mesh_index as i32, 36));
+            }
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use specs::{Read, System, Write};
+
+use crate::block_update::BlockUpdateQueue;
+use crate::chunk_manager::ChunkManager;
+use crate::redstone::RedstoneSimulator;
+
+/// Powers the redstone wires connected to the blocks that changed since the last frame
+pub struct UpdateRedstone;
+
+impl<'a> System<'a> for UpdateRedstone {
+    type SystemData = (
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, BlockUpdateQueue>,
+        Write<'a, RedstoneSimulator>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            chunk_manager,
+            mut block_update_queue,
+            mut redstone_simulator,
+        ) = data;
+
+        for position in block_update_queue.recent_changes.drain(..) {
+            redstone_simulator.update(&chunk_manager, position);
+        }
+    }
+}
@@ -1,52 +1,281 @@
 use nalgebra::Matrix4;
-use nalgebra_glm::vec3;
+use nalgebra_glm::{vec3, vec4};
 use specs::{Join, Read, ReadStorage, System, Write, WriteStorage};
 
+use crate::chest::ChestBlockEntity;
 use crate::chunk_manager::ChunkManager;
-use crate::constants::{BACKGROUND_COLOR, BLOCK_OUTLINE_WIDTH, RENDER_DISTANCE, ENABLE_FOG};
-use crate::gui::{create_block_outline_vao, create_crosshair_vao, create_hotbar_selection_vao, create_hotbar_vao, draw_crosshair};
+use crate::chunk_render_pipeline::ChunkRenderPipeline;
+use crate::clouds::CLOUD_TEXTURE_UNIT;
+use crate::constants::{BLOCK_OUTLINE_WIDTH, CLOUD_HEIGHT, CLOUD_SHADOW_STRENGTH, CLOUD_SPEED, CLOUD_TEXEL_SIZE, ENABLE_DEPTH_PREPASS, ENABLE_FOG, ENABLE_OCCLUSION_CULLING, OUTLINE_COLOR, OUTLINE_HARVESTABLE_COLOR, OUTLINE_UNBREAKABLE_COLOR, OUTLINE_WRONG_TOOL_COLOR, SUN_DIRECTION, TICKS_PER_SECOND, WINDOW_HEIGHT, WINDOW_WIDTH, WORLD_SPAWN_POINT};
+use crate::gui::{create_block_outline_vao, create_compass_dial_vao, create_compass_vao, create_crosshair_vao, create_hotbar_selection_vao, create_hotbar_vao, draw_compass, draw_crosshair};
 use crate::inventory::Inventory;
-use crate::player::PlayerState;
+use crate::map::{create_map_texture, create_map_vao, draw_map, draw_map_marker, MapItem, MAP_TEXTURE_UNIT};
+use crate::inventory::tool::{harvest_compatibility, HarvestCompatibility};
+use crate::menu::{draw_death_screen, draw_options, draw_pause_menu, draw_screenshot_background, draw_screenshot_caption, draw_screenshot_gallery, draw_statistics};
+use crate::physics::Interpolator;
+use crate::player::{GameMode, PlayerPhysicsState, PlayerState};
+use crate::shapes::centered_unit_cube;
+use crate::screen::Screen;
+use crate::screenshots::{create_screenshot_vao, ScreenshotGallery};
+use crate::settings::Settings;
+use crate::sign::SignEditor;
+use crate::statistics::Statistics;
 use crate::timer::Timer;
-use crate::types::{ParticleSystems, Shaders, TexturePack};
+use crate::furnace::{FurnaceBlockEntity, FurnaceUI};
+use crate::crafting_table::{CraftingTableBlockEntity, CraftingTableUI};
+use crate::achievement::AchievementRegistry;
+use crate::action_bar::ActionBar;
+use crate::chat::Chat;
+use crate::debug_overlay::DebugOverlay;
+use crate::enchanting::{draw_enchanting_table, EnchantingTableBlockEntity};
+use crate::frame_data::{FrameData, FrameUBO};
+use crate::gpu_timer::GpuTimer;
+use crate::difficulty::WorldAge;
+use crate::day_cycle::DayCycle;
+use crate::dimension::Dimension;
+use crate::ecs::systems::CAUSTICS_TEXTURE_UNIT;
+use crate::water::{is_water_surface_in_view, water_surface_height};
+use crate::bed::sleep_darkness;
+use crate::campfire::campfire_flames_layers;
+use crate::status_bars::{StatusBars, draw_fire_overlay, draw_level_up_flash, draw_xp_bar};
+use crate::text::TextRenderer;
+use crate::texture_pack::{set_anisotropy, AnisotropicTextures};
+use crate::types::{BlockEntityRegistry, ParticleSystems, Shaders, TexturePack};
+use crate::weather::Weather;
 use std::sync::Arc;
+use std::ffi::c_void;
+use std::time::Instant;
+use crate::chunk::BlockID;
 
-pub struct RenderChunks;
+/// Texture unit the grass colormap is bound to
+pub const GRASS_COLORMAP_TEXTURE_UNIT: u32 = 3;
+
+/// Applies the anisotropic filtering chosen in the settings whenever it changes
+pub struct ApplyAnisotropicFiltering;
+
+impl<'a> System<'a> for ApplyAnisotropicFiltering {
+    type SystemData = (
+        Read<'a, Settings>,
+        Write<'a, AnisotropicTextures>,
+    );
+
+    fn run(&mut self, (settings, mut anisotropic_textures): Self::SystemData) {
+        if anisotropic_textures.anisotropy == settings.anisotropy {
+            return;
+        }
+        anisotropic_textures.anisotropy = settings.anisotropy;
+        for &texture in &anisotropic_textures.textures {
+            set_anisotropy(texture, settings.anisotropy);
+        }
+    }
+}
+
+/// Uploads the camera, the fog and the time shared by the shaders drawing the world, before any of them is used
+pub struct UploadFrameData {
+    // Blocks are animated from this point in time
+    started_at: Instant,
+}
+
+impl UploadFrameData {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+        }
+    }
+}
+
+impl<'a> System<'a> for UploadFrameData {
+    type SystemData = (
+        Read<'a, Timer>,
+        ReadStorage<'a, PlayerState>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, Settings>,
+        Read<'a, Weather>,
+        Write<'a, FrameUBO>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            player_state,
+            chunk_manager,
+            settings,
+            weather,
+            mut frame_ubo,
+        ) = data;
+
+        let (r, g, b, _) = weather.sky_color(chunk_manager.dimension);
+        let sun_direction = vec3(SUN_DIRECTION.0, SUN_DIRECTION.1, SUN_DIRECTION.2).normalize();
+        for player_state in (&player_state).join() {
+            frame_ubo.upload(FrameData {
+                view: player_state.view_matrix,
+                projection: player_state.projection_matrix,
+                sun_direction: vec4(sun_direction.x, sun_direction.y, sun_direction.z, 0.0),
+                fog_color: vec4(r, g, b, 1.0),
+                global_time: global_timer.time().saturating_duration_since(self.started_at).as_secs_f32(),
+                render_distance: settings.render_distance as f32,
+                fog_density_multiplier: weather.fog_density_multiplier(chunk_manager.dimension),
+            });
+        }
+    }
+}
+
+pub struct RenderChunks {
+    bounding_box_vao: u32,
+    /// Time the GPU spends drawing the chunks, shown in the debug overlay
+    gpu_timer: GpuTimer,
+}
+
+impl RenderChunks {
+    pub fn new() -> Self {
+        Self {
+            bounding_box_vao: create_chunk_bounding_box_vao(),
+            gpu_timer: GpuTimer::new(),
+        }
+    }
+}
 
 impl<'a> System<'a> for RenderChunks {
     type SystemData = (
         ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
         Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        Read<'a, Settings>,
+        Read<'a, Weather>,
+        Read<'a, DayCycle>,
+        Read<'a, FrameUBO>,
         Write<'a, Shaders>,
+        Write<'a, ChunkRenderPipeline>,
+        Write<'a, DebugOverlay>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             player_state,
+            player_physics_state,
             chunk_manager,
+            texture_pack,
+            settings,
+            weather,
+            day_cycle,
+            frame_ubo,
             mut shaders,
+            mut chunk_render_pipeline,
+            mut debug_overlay,
         ) = data;
 
-        let mut voxel_shader = shaders.get_mut("voxel_shader").unwrap();
+        let voxel_shader = shaders.get_mut("voxel_shader").unwrap();
         voxel_shader.use_program();
         voxel_shader.set_uniform1i("array_texture", 0);
-        let (r, g, b, a) = BACKGROUND_COLOR;
+        voxel_shader.set_uniform1i("grasscolor_map", GRASS_COLORMAP_TEXTURE_UNIT as i32);
+        voxel_shader.set_uniform1i("grass_top_layer", texture_pack[&BlockID::GrassBlock].get_uv_of_every_face().2 as i32);
+        voxel_shader.set_uniform1i("portal_layer", texture_pack[&BlockID::NetherPortal].get_uv_of_every_face().0 as i32);
+        let (campfire_layer, campfire_frame_layer) = campfire_flames_layers(&texture_pack, frame_ubo.data.global_time);
+        voxel_shader.set_uniform1i("campfire_layer", campfire_layer);
+        voxel_shader.set_uniform1i("campfire_frame_layer", campfire_frame_layer);
+        let (r, g, b, a) = weather.sky_color(chunk_manager.dimension);
         voxel_shader.set_uniform1i("enable_fog", ENABLE_FOG as i32);
-        voxel_shader.set_uniform3f("sky_color", &[r, g, b]);
-        voxel_shader.set_uniform1f("render_distance", RENDER_DISTANCE as f32);
+        voxel_shader.set_uniform1f("ao_strength", settings.ao_strength);
+        // There is no day in the Nether
+        let sky_brightness = if chunk_manager.dimension == Dimension::Overworld { day_cycle.sky_brightness() } else { 1.0 };
+        voxel_shader.set_uniform1f("sky_brightness", sky_brightness);
+        voxel_shader.set_uniform1i("light_tick", (frame_ubo.data.global_time * TICKS_PER_SECOND) as i32);
+        voxel_shader.set_uniform1i("caustics", CAUSTICS_TEXTURE_UNIT as i32);
+        voxel_shader.set_uniform1i("clouds", CLOUD_TEXTURE_UNIT as i32);
+        voxel_shader.set_uniform1f("cloud_height", CLOUD_HEIGHT);
+        voxel_shader.set_uniform1f("cloud_texel_size", CLOUD_TEXEL_SIZE);
+        voxel_shader.set_uniform1f("cloud_speed", CLOUD_SPEED * TICKS_PER_SECOND);
+        // The clouds cast no shadow at night, nor in the Nether
+        let cloud_shadow_strength = if chunk_manager.dimension == Dimension::Overworld { CLOUD_SHADOW_STRENGTH * day_cycle.daylight() } else { 0.0 };
+        voxel_shader.set_uniform1f("cloud_shadow_strength", cloud_shadow_strength);
 
         gl_call!(gl::ClearColor(r, g, b, a));
         gl_call!(gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT));
         gl_call!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
 
-        for player_state in (&player_state).join() {
-            voxel_shader.set_uniform_matrix4fv("view", player_state.view_matrix.as_ptr());
-            voxel_shader.set_uniform_matrix4fv("projection", player_state.projection_matrix.as_ptr());
-            chunk_manager.render_loaded_chunks(&mut voxel_shader);
+        for (player_state, player_physics_state) in (&player_state, &player_physics_state).join() {
+            let camera_position = player_physics_state.get_interpolated_state().position
+                + vec3(0.0, *player_state.camera_height.get_interpolated_state(), 0.0);
+            let occlusion_camera = if ENABLE_OCCLUSION_CULLING { Some(&camera_position) } else { None };
+
+            let voxel_shader = shaders.get_mut("voxel_shader").unwrap();
+            voxel_shader.use_program();
+            // Caustics are only drawn under the sea, and only when it can be seen.
+            // The water level is pushed below the world to turn them off
+            let view_projection = player_state.projection_matrix * player_state.view_matrix;
+            let water_level = if chunk_manager.dimension == Dimension::Overworld
+                && is_water_surface_in_view(&chunk_manager, &view_projection) {
+                water_surface_height()
+            } else {
+                -1.0
+            };
+            voxel_shader.set_uniform1f("water_level", water_level);
+            voxel_shader.set_uniform1i("underwater", player_state.is_submerged as i32);
+
+            self.gpu_timer.begin();
+            if ENABLE_DEPTH_PREPASS {
+                let depth_prepass_shader = shaders.get_mut("depth_prepass_shader").unwrap();
+                depth_prepass_shader.use_program();
+                depth_prepass_shader.set_uniform1i("array_texture", 0);
+                depth_prepass_shader.set_uniform1i("campfire_layer", campfire_layer);
+                depth_prepass_shader.set_uniform1i("campfire_frame_layer", campfire_frame_layer);
+                gl_call!(gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE));
+                chunk_render_pipeline.draw_all_rings(&chunk_manager, &camera_position, occlusion_camera);
+                gl_call!(gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE));
+
+                // Only the closest fragment of every pixel is shaded
+                shaders.get_mut("voxel_shader").unwrap().use_program();
+                gl_call!(gl::DepthFunc(gl::EQUAL));
+                gl_call!(gl::DepthMask(gl::FALSE));
+                chunk_render_pipeline.redraw_all_rings();
+                gl_call!(gl::DepthMask(gl::TRUE));
+                gl_call!(gl::DepthFunc(gl::LESS));
+            } else {
+                chunk_render_pipeline.draw_all_rings(&chunk_manager, &camera_position, occlusion_camera);
+            }
+            self.gpu_timer.end();
+            debug_overlay.chunk_render_time = self.gpu_timer.elapsed_ms;
+
+            if ENABLE_OCCLUSION_CULLING {
+                let occlusion_shader = shaders.get_mut("occlusion_shader").unwrap();
+                occlusion_shader.use_program();
+
+                // The boxes are only tested against the depth of the chunks drawn above, they don't write anything
+                gl_call!(gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE));
+                gl_call!(gl::DepthMask(gl::FALSE));
+                gl_call!(gl::Disable(gl::CULL_FACE));
+                chunk_manager.issue_occlusion_queries(occlusion_shader, self.bounding_box_vao);
+                gl_call!(gl::Enable(gl::CULL_FACE));
+                gl_call!(gl::DepthMask(gl::TRUE));
+                gl_call!(gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE));
+            }
         }
     }
 }
 
+/// A unit cube scaled to the size of a chunk when issuing occlusion queries
+fn create_chunk_bounding_box_vao() -> u32 {
+    let mut vao = 0;
+    gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+    // Position, the UVs and normals of the cube are ignored
+    gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+    gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+    gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+    let mut vbo = 0;
+    gl_call!(gl::CreateBuffers(1, &mut vbo));
+
+    let cube = centered_unit_cube(0.0, 0.0, 0.0, (0, 0, 0, 0, 0, 0));
+    gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (9 * std::mem::size_of::<f32>()) as i32));
+    gl_call!(gl::NamedBufferData(vbo,
+                    (cube.len() * std::mem::size_of::<f32>()) as isize,
+                    cube.as_ptr() as *const c_void,
+                    gl::STATIC_DRAW));
+    vao
+}
+
 pub struct RenderParticles;
 
 impl<'a> System<'a> for RenderParticles {
@@ -97,26 +326,42 @@ impl RenderBlockOutline {
 
 impl<'a> System<'a> for RenderBlockOutline {
     type SystemData = (
+        Read<'a, Arc<ChunkManager>>,
         ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Inventory>,
         Write<'a, Shaders>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
+            chunk_manager,
             player_state,
+            inventory,
             mut shaders,
         ) = data;
 
-        for player_state in (&player_state).join() {
+        for (player_state, inventory) in (&player_state, &inventory).join() {
             if let Some(((x, y, z), _)) = player_state.targeted_block {
+                let block = match chunk_manager.get_block(x, y, z) {
+                    Some(block) => block,
+                    None => continue,
+                };
+                let (r, g, b, a) = if player_state.game_mode == GameMode::Creative {
+                    OUTLINE_COLOR
+                } else {
+                    match harvest_compatibility(block, inventory.get_selected_item()) {
+                        HarvestCompatibility::Harvestable => OUTLINE_HARVESTABLE_COLOR,
+                        HarvestCompatibility::WrongTool => OUTLINE_WRONG_TOOL_COLOR,
+                        HarvestCompatibility::Unbreakable => OUTLINE_UNBREAKABLE_COLOR,
+                    }
+                };
                 let (x, y, z) = (x as f32, y as f32, z as f32);
                 let model_matrix = Matrix4::new_translation(&vec3(x, y, z));
 
                 let outline_shader = shaders.get_mut("outline_shader").unwrap();
                 outline_shader.use_program();
                 outline_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
-                outline_shader.set_uniform_matrix4fv("view", player_state.view_matrix.as_ptr());
-                outline_shader.set_uniform_matrix4fv("projection", player_state.projection_matrix.as_ptr());
+                outline_shader.set_uniform4f("outline_color", &[r, g, b, a]);
 
                 gl_call!(gl::LineWidth(BLOCK_OUTLINE_WIDTH));
                 gl_call!(gl::BindVertexArray(self.vao));
@@ -128,45 +373,237 @@ impl<'a> System<'a> for RenderBlockOutline {
 
 pub(crate) struct RenderGUI {
     crosshair_vao: u32,
+    compass_dial_vao: u32,
+    compass_vao: u32,
+    map_vao: u32,
+    map_texture: u32,
+    /// Created the first time a map is held, centered where the player was
+    map: Option<MapItem>,
+    screenshot_vao: u32,
     hotbar_vao: u32,
     hotbar_selection_vao: u32,
+    furnace_ui: FurnaceUI,
+    crafting_table_ui: CraftingTableUI,
+    text_renderer: TextRenderer,
+    status_bars: StatusBars,
+    /// Time the animated overlays are based on
+    created: Instant,
 }
 
 impl RenderGUI {
     pub fn new() -> Self {
+        let map_texture = create_map_texture();
+        gl_call!(gl::BindTextureUnit(MAP_TEXTURE_UNIT, map_texture));
         Self {
             crosshair_vao: create_crosshair_vao(),
+            compass_dial_vao: create_compass_dial_vao(),
+            compass_vao: create_compass_vao(),
+            map_vao: create_map_vao(),
+            map_texture,
+            map: None,
+            screenshot_vao: create_screenshot_vao(),
             hotbar_vao: create_hotbar_vao(),
             hotbar_selection_vao: create_hotbar_selection_vao(),
+            furnace_ui: FurnaceUI::new(),
+            crafting_table_ui: CraftingTableUI::new(),
+            text_renderer: TextRenderer::new(),
+            status_bars: StatusBars::new(),
+            created: Instant::now(),
         }
     }
 }
 
 impl<'a> System<'a> for RenderGUI {
     type SystemData = (
+        Read<'a, Timer>,
         Read<'a, TexturePack>,
+        Read<'a, Screen>,
+        Read<'a, AchievementRegistry>,
+        Read<'a, SignEditor>,
+        Read<'a, ActionBar>,
+        Read<'a, Chat>,
+        Read<'a, DebugOverlay>,
+        Read<'a, WorldAge>,
+        Read<'a, Settings>,
+        Read<'a, Statistics>,
+        Read<'a, Weather>,
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, BlockEntityRegistry>,
         Write<'a, Shaders>,
+        Write<'a, ScreenshotGallery>,
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
         WriteStorage<'a, Inventory>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
+            global_timer,
             texture_pack,
+            screen,
+            achievement_registry,
+            sign_editor,
+            action_bar,
+            chat,
+            debug_overlay,
+            world_age,
+            settings,
+            statistics,
+            weather,
+            chunk_manager,
+            mut block_entity_registry,
             mut shaders,
+            mut screenshot_gallery,
+            player_state,
+            player_physics_state,
             mut inventory,
         ) = data;
 
-        for inventory in (&mut inventory).join() {
+        for (player_state, player_physics_state, inventory) in (&player_state, &player_physics_state, &mut inventory).join() {
             let mut gui_shader = shaders.get_mut("gui_shader").unwrap();
             draw_crosshair(self.crosshair_vao, &mut gui_shader);
             gl_call!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
             gl_call!(gl::Disable(gl::DEPTH_TEST));
             inventory.update_dirty_items(&texture_pack);
-            inventory.draw_hotbar(self.hotbar_vao, &mut gui_shader);
-            inventory.draw_hotbar_selection_box(self.hotbar_selection_vao, &mut gui_shader);
+            // Spectators have no hotbar nor status bars, only the debug overlay
+            let is_spectator = player_state.is_spectator();
+            if !is_spectator {
+                inventory.draw_hotbar(self.hotbar_vao, &mut gui_shader);
+                inventory.draw_hotbar_selection_box(self.hotbar_selection_vao, &mut gui_shader);
+                self.status_bars.draw(player_state, inventory.armor.total_defense(), &mut gui_shader);
+            }
+            if !is_spectator && inventory.get_selected_item() == Some(BlockID::Compass) {
+                // The needle points to the world spawn, relative to where the player is looking
+                let position = player_physics_state.get_interpolated_state().position;
+                let spawn_direction = (WORLD_SPAWN_POINT.2 - position.z).atan2(WORLD_SPAWN_POINT.0 - position.x);
+                draw_compass(self.compass_dial_vao, self.compass_vao, spawn_direction - player_state.rotation.y, &mut gui_shader);
+            }
+            let position = player_physics_state.get_interpolated_state().position;
+            let holds_map = !is_spectator && inventory.get_selected_item() == Some(BlockID::Map);
+            if holds_map {
+                let block = (position.x.floor() as i32, position.z.floor() as i32);
+                let map = self.map.get_or_insert_with(|| MapItem::new(block));
+                // The texture only changes when walking reveals a part of the map
+                if map.explore(&chunk_manager, block) {
+                    map.upload(self.map_texture);
+                }
+                draw_map(self.map_vao, &mut gui_shader);
+            }
 
-            let mut item_shader = shaders.get_mut("item_shader").unwrap();
-            inventory.draw_hotbar_items(&mut item_shader);
+            match *screen {
+                Screen::Chest(position) => {
+                    if let Some(chest) = block_entity_registry.get_mut(&position)
+                        .and_then(|block_entity| block_entity.downcast_mut::<ChestBlockEntity>()) {
+                        chest.update_dirty_items(&texture_pack);
+                        chest.draw(self.hotbar_vao, &mut gui_shader);
+                        let mut item_shader = shaders.get_mut("item_shader").unwrap();
+                        chest.draw_items(&mut item_shader);
+                    }
+                }
+                Screen::Furnace(position) => {
+                    if let Some(furnace) = block_entity_registry.get_mut(&position)
+                        .and_then(|block_entity| block_entity.downcast_mut::<FurnaceBlockEntity>()) {
+                        furnace.update_dirty_items(&texture_pack);
+                        self.furnace_ui.draw(furnace, &mut gui_shader);
+                        let mut item_shader = shaders.get_mut("item_shader").unwrap();
+                        self.furnace_ui.draw_items(furnace, &mut item_shader);
+                    }
+                }
+                Screen::CraftingTable(position) => {
+                    if let Some(crafting_table) = block_entity_registry.get_mut(&position)
+                        .and_then(|block_entity| block_entity.downcast_mut::<CraftingTableBlockEntity>()) {
+                        crafting_table.update_dirty_items(&texture_pack);
+                        self.crafting_table_ui.draw(crafting_table, &mut gui_shader);
+                        let mut item_shader = shaders.get_mut("item_shader").unwrap();
+                        self.crafting_table_ui.draw_items(crafting_table, &mut item_shader);
+                    }
+                }
+                Screen::EnchantingTable(position) => {
+                    if let Some(enchanting_table) = block_entity_registry.get(&position)
+                        .and_then(|block_entity| block_entity.downcast_ref::<EnchantingTableBlockEntity>()) {
+                        let player_level = player_state.level;
+                        let text_shader = shaders.get_mut("text_shader").unwrap();
+                        draw_enchanting_table(enchanting_table, player_level, inventory.get_selected_item(), &mut self.text_renderer, text_shader);
+                    }
+                }
+                Screen::Inventory => {
+                    inventory.draw_storage(self.hotbar_vao, &mut gui_shader);
+                    let text_shader = shaders.get_mut("text_shader").unwrap();
+                    inventory.draw_armor_slots(&mut self.text_renderer, text_shader);
+                    let mut item_shader = shaders.get_mut("item_shader").unwrap();
+                    inventory.draw_storage_items(&mut item_shader);
+                }
+                Screen::Sign(_) => {
+                    let text_shader = shaders.get_mut("text_shader").unwrap();
+                    sign_editor.draw(&mut self.text_renderer, text_shader);
+                }
+                // The menus are drawn on top of everything else below
+                Screen::None | Screen::Pause | Screen::Options | Screen::Statistics(_) | Screen::Screenshots(_) | Screen::Screenshot(_)
+                | Screen::Death | Screen::Chat => {}
+            }
+
+            if !is_spectator {
+                let mut item_shader = shaders.get_mut("item_shader").unwrap();
+                inventory.draw_hotbar_items(&mut item_shader);
+            }
+
+            let text_shader = shaders.get_mut("text_shader").unwrap();
+            if !is_spectator {
+                inventory.draw_hotbar_durability_bars(&mut self.text_renderer, text_shader);
+                draw_xp_bar(player_state, &mut self.text_renderer, text_shader);
+            }
+            if let Some(map) = self.map.as_ref().filter(|_| holds_map) {
+                draw_map_marker(map, (position.x, position.z), &mut self.text_renderer, text_shader);
+            }
+            if *screen == Screen::Inventory {
+                inventory.draw_storage_durability_bars(&mut self.text_renderer, text_shader);
+            }
+            if player_state.on_fire {
+                let time = global_timer.time().saturating_duration_since(self.created).as_secs_f32();
+                draw_fire_overlay(time, &mut self.text_renderer, text_shader);
+            }
+            if let Some(level_up_started) = player_state.level_up_started {
+                let elapsed = global_timer.time().saturating_duration_since(level_up_started).as_secs_f32();
+                draw_level_up_flash(elapsed, &mut self.text_renderer, text_shader);
+            }
+            if let Some(sleep_started) = player_state.sleep_started {
+                let elapsed = global_timer.time().saturating_duration_since(sleep_started).as_secs_f32();
+                self.text_renderer.draw_rect(0.0, 0.0, WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32,
+                                             (0.0, 0.0, 0.0, sleep_darkness(elapsed)), text_shader);
+            }
+            let flash_brightness = weather.flash_brightness();
+            if flash_brightness > 0.0 {
+                self.text_renderer.draw_rect(0.0, 0.0, WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32,
+                                             (1.0, 1.0, 1.0, flash_brightness), text_shader);
+            }
+            action_bar.draw(global_timer.time(), &mut self.text_renderer, text_shader);
+            chat.draw(*screen == Screen::Chat, global_timer.time(), &mut self.text_renderer, text_shader);
+            debug_overlay.draw(&world_age, player_state, &position, &mut self.text_renderer, text_shader);
+
+            if let Some(notification) = achievement_registry.current_notification(global_timer.time()) {
+                let text_shader = shaders.get_mut("text_shader").unwrap();
+                notification.draw(global_timer.time(), &mut self.text_renderer, text_shader);
+            }
+
+            let text_shader = shaders.get_mut("text_shader").unwrap();
+            match *screen {
+                Screen::Pause => draw_pause_menu(&mut self.text_renderer, text_shader),
+                Screen::Options => draw_options(&settings, &mut self.text_renderer, text_shader),
+                Screen::Statistics(scroll) => draw_statistics(&statistics, scroll, &mut self.text_renderer, text_shader),
+                Screen::Death => draw_death_screen(player_state.death_message, player_state.xp_total, &mut self.text_renderer, text_shader),
+                Screen::Screenshots(page) => {
+                    draw_screenshot_gallery(screenshot_gallery.screenshots.len(), page, &mut self.text_renderer, text_shader);
+                    screenshot_gallery.draw_thumbnails(page, self.screenshot_vao, shaders.get_mut("gui_shader").unwrap());
+                }
+                Screen::Screenshot(index) => {
+                    draw_screenshot_background(&mut self.text_renderer, text_shader);
+                    screenshot_gallery.draw_full_view(index, self.screenshot_vao, shaders.get_mut("gui_shader").unwrap());
+                    let screenshot = &screenshot_gallery.screenshots[index];
+                    draw_screenshot_caption(&screenshot.name, &screenshot.timestamp(), &mut self.text_renderer,
+                                            shaders.get_mut("text_shader").unwrap());
+                }
+                _ => {}
+            }
             gl_call!(gl::Enable(gl::DEPTH_TEST));
         }
     }
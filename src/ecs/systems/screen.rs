@@ -0,0 +1,61 @@
+use glfw::{Action, Key, WindowEvent};
+use nalgebra_glm::vec3;
+use specs::{Join, Read, ReadStorage, System, Write};
+
+use crate::constants::REACH_DISTANCE;
+use crate::input::InputCache;
+use crate::physics::Interpolator;
+use crate::player::PlayerPhysicsState;
+use crate::screen::Screen;
+
+/// Opens the inventory when the player presses E.
+/// Closes the opened screen when the player presses E again or walks away from the block.
+pub struct CloseScreen;
+
+impl<'a> System<'a> for CloseScreen {
+    type SystemData = (
+        Read<'a, InputCache>,
+        Write<'a, Screen>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            input_cache,
+            mut screen,
+            player_physics_state,
+        ) = data;
+
+        let e_pressed = input_cache.events.iter().any(|event| match event {
+            WindowEvent::Key(Key::E, _, Action::Press, _) => true,
+            _ => false
+        });
+
+        if !screen.is_open() {
+            if e_pressed {
+                *screen = Screen::Inventory;
+                info!("Opened {:?}", *screen);
+            }
+            return;
+        }
+
+        for player_physics_state in (&player_physics_state).join() {
+            // Screens capturing the keyboard and the menus are closed by their own input system
+            let close_requested = !screen.captures_keyboard() && !screen.is_menu() && e_pressed;
+
+            let too_far = match screen.block_position() {
+                Some((x, y, z)) => {
+                    let block_center = vec3(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5);
+                    let player_position = player_physics_state.get_latest_state().position;
+                    (player_position - block_center).norm() > REACH_DISTANCE
+                }
+                None => false
+            };
+
+            if close_requested || too_far {
+                info!("Closed {:?}", *screen);
+                *screen = Screen::None;
+            }
+        }
+    }
+}
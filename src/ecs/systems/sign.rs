@@ -0,0 +1,235 @@
+use std::ffi::c_void;
+use std::sync::Arc;
+
+use glfw::{Action, Key, WindowEvent};
+use nalgebra::Matrix4;
+use nalgebra_glm::{pi, vec3};
+use specs::{Join, Read, ReadStorage, System, Write};
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::input::InputCache;
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::screen::Screen;
+use crate::shapes::{SIGN_BOARD_DEPTH, SIGN_BOARD_HEIGHT, sign_mesh};
+use crate::sign::{SIGN_LINE_HEIGHT, SIGN_LINES, SignBlockEntity, SignEditor};
+use crate::text::TextRenderer;
+use crate::types::{BlockEntityRegistry, Shaders, TexturePack};
+use crate::util::is_sphere_in_frustum;
+
+/// Edits the text of the opened sign.
+/// The keyboard events are consumed so that typing doesn't move the player.
+pub struct HandleSignInput;
+
+impl<'a> System<'a> for HandleSignInput {
+    type SystemData = (
+        Write<'a, InputCache>,
+        Write<'a, Screen>,
+        Write<'a, SignEditor>,
+        Write<'a, BlockEntityRegistry>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            mut input_cache,
+            mut screen,
+            mut sign_editor,
+            mut block_entity_registry,
+        ) = data;
+
+        let position = match *screen {
+            Screen::Sign(position) => position,
+            _ => {
+                // The screen was closed without validating the text
+                sign_editor.close();
+                return;
+            }
+        };
+
+        let sign = match block_entity_registry.get_mut(&position)
+            .and_then(|block_entity| block_entity.downcast_mut::<SignBlockEntity>()) {
+            Some(sign) => sign,
+            None => {
+                *screen = Screen::None;
+                return;
+            }
+        };
+
+        if sign_editor.position != Some(position) {
+            sign_editor.open(position, sign);
+        }
+
+        for event in &input_cache.events {
+            match event {
+                WindowEvent::Char(c) => sign_editor.insert_char(*c),
+                WindowEvent::Key(key, _, Action::Press, _) |
+                WindowEvent::Key(key, _, Action::Repeat, _) => match key {
+                    Key::Backspace => sign_editor.delete_char(),
+                    Key::Up => sign_editor.move_cursor(-1, 0),
+                    Key::Down => sign_editor.move_cursor(1, 0),
+                    Key::Left => sign_editor.move_cursor(0, -1),
+                    Key::Right => sign_editor.move_cursor(0, 1),
+                    Key::Enter | Key::Escape => {
+                        sign.lines = sign_editor.lines.clone();
+                        sign_editor.close();
+                        info!("Closed {:?}", *screen);
+                        *screen = Screen::None;
+                        break;
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        input_cache.events.retain(|event| match event {
+            WindowEvent::Char(_) | WindowEvent::Key(..) => false,
+            _ => true
+        });
+        input_cache.key_states.clear();
+    }
+}
+
+/// Scale of the text written on signs, in blocks per font pixel
+const SIGN_TEXT_SCALE: f32 = 1.0 / 100.0;
+/// Distance between the text and the board, to avoid z-fighting
+const SIGN_TEXT_OFFSET: f32 = 0.005;
+
+pub struct RenderSigns {
+    vao: u32,
+    vbo: u32,
+    is_uploaded: bool,
+    text_renderer: TextRenderer,
+}
+
+impl RenderSigns {
+    pub fn new() -> Self {
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        // Position
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+        // Texture coords
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 1, 3 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+        // Normals
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 2));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 2, 3 as i32, gl::FLOAT, gl::FALSE, 6 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 2, 0));
+
+        let mut vbo = 0;
+        gl_call!(gl::CreateBuffers(1, &mut vbo));
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (9 * std::mem::size_of::<f32>()) as i32));
+
+        Self {
+            vao,
+            vbo,
+            is_uploaded: false,
+            text_renderer: TextRenderer::new(),
+        }
+    }
+
+    /// The meshes of the 4 orientations one after the other, 72 vertices each
+    fn upload_model(&mut self, texture_pack: &TexturePack) {
+        let uvs = texture_pack.get(&BlockID::Sign).unwrap().get_uv_of_every_face();
+        let vbo_data: Vec<f32> = (0..4).flat_map(|facing| sign_mesh(facing, uvs)).collect();
+
+        gl_call!(gl::NamedBufferData(self.vbo,
+                    (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                    vbo_data.as_ptr() as *const c_void,
+                    gl::STATIC_DRAW));
+        self.is_uploaded = true;
+    }
+}
+
+impl<'a> System<'a> for RenderSigns {
+    type SystemData = (
+        Read<'a, BlockEntityRegistry>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            block_entity_registry,
+            chunk_manager,
+            texture_pack,
+            player_state,
+            player_physics_state,
+            mut shaders,
+        ) = data;
+
+        if !self.is_uploaded {
+            self.upload_model(&texture_pack);
+        }
+
+        for (player_state, player_physics_state) in (&player_state, &player_physics_state).join() {
+            let view_projection = player_state.projection_matrix * player_state.view_matrix;
+            let player_position = player_physics_state.get_interpolated_state().position;
+
+            let signs: Vec<((i32, i32, i32), u8, &SignBlockEntity)> = block_entity_registry.iter()
+                .filter_map(|(&position, block_entity)| {
+                    block_entity.downcast_ref::<SignBlockEntity>().map(|sign| (position, sign))
+                })
+                .filter(|&((x, y, z), _)| {
+                    let center = vec3(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5);
+                    is_sphere_in_frustum(&view_projection, &center, 0.87)
+                })
+                .filter_map(|((x, y, z), sign)| {
+                    // The chunk isn't loaded
+                    chunk_manager.get_block_meta(x, y, z).map(|meta| ((x, y, z), meta.facing(), sign))
+                })
+                .collect();
+
+            let model_shader = shaders.get_mut("model_shader").unwrap();
+            model_shader.use_program();
+            model_shader.set_uniform1i("tex", 0);
+            gl_call!(gl::BindVertexArray(self.vao));
+
+            for &((x, y, z), facing, _) in signs.iter() {
+                let model_matrix = Matrix4::new_translation(&vec3(x as f32, y as f32, z as f32));
+                model_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+                gl_call!(gl::DrawArrays(gl::TRIANGLES, 72 * facing as i32, 72));
+            }
+
+            let text_shader = shaders.get_mut("text_shader").unwrap();
+            gl_call!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
+            for &((x, y, z), facing, sign) in signs.iter() {
+                let angle = -(facing as f32) * pi::<f32>() / 2.0;
+                let front = vec3(angle.sin(), 0.0, angle.cos());
+                let center = vec3(x as f32 + 0.5, y as f32, z as f32 + 0.5);
+
+                // Write on the side of the board the player is looking at
+                let side_angle = if (player_position - center).dot(&front) >= 0.0 {
+                    angle
+                } else {
+                    angle + pi::<f32>()
+                };
+                let model_matrix = Matrix4::new_translation(&center)
+                    * Matrix4::from_euler_angles(0.0, side_angle, 0.0)
+                    * Matrix4::new_translation(&vec3(0.0, 0.0, SIGN_BOARD_DEPTH / 2.0 + SIGN_TEXT_OFFSET))
+                    * Matrix4::new_scaling(SIGN_TEXT_SCALE);
+
+                // The lines are centered on the board, in font pixels
+                let (board_bottom, board_top) = SIGN_BOARD_HEIGHT;
+                let board_middle = (board_bottom + board_top) / 2.0 / SIGN_TEXT_SCALE;
+                let text_top = board_middle + SIGN_LINES as f32 * SIGN_LINE_HEIGHT / 2.0;
+                for (i, line) in sign.lines.iter().enumerate() {
+                    let line_x = -TextRenderer::text_width(line, 1.0) / 2.0;
+                    let line_y = text_top - (i + 1) as f32 * SIGN_LINE_HEIGHT + 1.5;
+                    self.text_renderer.draw_transformed(line, line_x, line_y, 1.0, (0.0, 0.0, 0.0, 1.0),
+                                                        &model_matrix, &view_projection, text_shader);
+                }
+            }
+        }
+    }
+}
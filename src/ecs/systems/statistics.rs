@@ -0,0 +1,70 @@
+use std::time::Instant;
+
+use nalgebra_glm::{Vec3, vec2};
+use specs::{Join, Read, ReadStorage, System, Write};
+
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::statistics::Statistics;
+use crate::timer::Timer;
+
+/// Moving further than this in a single frame is a teleportation, not a walk
+const MAX_STEP_LENGTH: f32 = 10.0;
+
+/// Counts the time played and the distance walked by the player.
+/// Nothing is counted while the game is paused
+pub struct UpdateStatistics {
+    last_time: Instant,
+    last_position: Option<Vec3>,
+    // The fractions of a second and of a block not counted yet
+    time_remainder: f32,
+    distance_remainder: f32,
+}
+
+impl UpdateStatistics {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            last_position: None,
+            time_remainder: 0.0,
+            distance_remainder: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateStatistics {
+    type SystemData = (
+        Read<'a, Timer>,
+        Write<'a, Statistics>,
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            mut statistics,
+            player_state,
+            player_physics_state,
+        ) = data;
+
+        let now = global_timer.time();
+        self.time_remainder += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+        statistics.time_played_seconds += self.time_remainder as u64;
+        self.time_remainder = self.time_remainder.fract();
+
+        for (player_state, player_physics_state) in (&player_state, &player_physics_state).join() {
+            let position = player_physics_state.get_latest_state().position;
+            if let Some(last_position) = self.last_position {
+                let step = vec2(position.x - last_position.x, position.z - last_position.z).norm();
+                if player_state.is_on_ground && !player_state.is_flying && step < MAX_STEP_LENGTH {
+                    self.distance_remainder += step;
+                    statistics.distance_walked += self.distance_remainder as u64;
+                    self.distance_remainder = self.distance_remainder.fract();
+                }
+            }
+            self.last_position = Some(position);
+        }
+    }
+}
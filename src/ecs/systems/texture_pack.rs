@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use specs::{System, Write};
+
+use crate::constants::{get_texture_pack_path, BLOCK_TEXTURES_DIRECTORY, TEXTURE_RELOAD_INTERVAL};
+use crate::texture_pack::{generate_array_texture, AnisotropicTextures, ArrayTexture};
+use crate::types::TexturePack;
+
+/// When each file of the block textures was last modified
+fn block_texture_times() -> HashMap<PathBuf, SystemTime> {
+    let entries = match fs::read_dir(BLOCK_TEXTURES_DIRECTORY) {
+        Ok(entries) => entries,
+        Err(_) => return HashMap::new(),
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|extension| extension.to_str()) == Some("png"))
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+/// Builds the array texture again when one of the block textures changes, so they can be edited in-game.
+/// The layers of the textures stay the same, the chunk meshes don't need to be rebuilt
+pub struct ReloadArrayTexture {
+    last_check: Instant,
+    modified_times: HashMap<PathBuf, SystemTime>,
+}
+
+impl ReloadArrayTexture {
+    pub fn new() -> Self {
+        Self {
+            last_check: Instant::now(),
+            modified_times: block_texture_times(),
+        }
+    }
+}
+
+impl<'a> System<'a> for ReloadArrayTexture {
+    type SystemData = (
+        Write<'a, ArrayTexture>,
+        Write<'a, TexturePack>,
+        Write<'a, AnisotropicTextures>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            array_texture,
+            mut texture_pack,
+            mut anisotropic_textures,
+        ) = data;
+
+        if self.last_check.elapsed() < Duration::from_secs_f32(TEXTURE_RELOAD_INTERVAL) {
+            return;
+        }
+        self.last_check = Instant::now();
+
+        let modified_times = block_texture_times();
+        let changed_files: Vec<_> = modified_times.iter()
+            .filter(|&(path, modified)| self.modified_times.get(path) != Some(modified))
+            .map(|(path, _)| path.display().to_string())
+            .collect();
+        self.modified_times = modified_times;
+        if changed_files.is_empty() {
+            return;
+        }
+        info!("Reloading the block textures, {} changed", changed_files.join(", "));
+
+        let (new_texture, new_texture_pack) = generate_array_texture(get_texture_pack_path().as_deref());
+        gl_call!(gl::BindTextureUnit(0, new_texture));
+        let old_texture = std::mem::replace(&mut *array_texture.0.write(), new_texture);
+        gl_call!(gl::DeleteTextures(1, &old_texture));
+
+        *texture_pack = new_texture_pack;
+        for texture in anisotropic_textures.textures.iter_mut().filter(|texture| **texture == old_texture) {
+            *texture = new_texture;
+        }
+        // Filters every texture again, the new one included
+        anisotropic_textures.anisotropy = 0;
+    }
+}
@@ -0,0 +1,223 @@
+use specs::{Join, Read, ReadStorage, System, Write};
+
+use crate::block_update::BlockUpdateQueue;
+use crate::campfire::campfire_flames_layers;
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::chunk_render_pipeline::ChunkRenderPipeline;
+use crate::dimension::Dimension;
+use crate::frame_data::{FrameData, FrameUBO};
+use crate::constants::{ENABLE_FOG, TICKS_PER_SECOND, WATER_FLOW_STEPS_PER_TICK, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::settings::Settings;
+use crate::timer::Timer;
+use crate::types::{Shaders, TexturePack};
+use crate::ecs::systems::GRASS_COLORMAP_TEXTURE_UNIT;
+use crate::water::{create_reflection_framebuffer, reflection_matrix, water_surface_height, WaterFlowSimulator};
+use crate::weather::Weather;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Texture unit the reflection texture is bound to
+pub const REFLECTION_TEXTURE_UNIT: u32 = 4;
+/// Texture unit the caustics texture is bound to
+pub const CAUSTICS_TEXTURE_UNIT: u32 = 8;
+
+/// Lets the water flow into the air around the blocks that changed, a limited number of blocks every game tick.
+/// Must run before `UpdateRedstone`, which takes the recent changes
+pub struct FlowWater {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl FlowWater {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for FlowWater {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, BlockUpdateQueue>,
+        Write<'a, WaterFlowSimulator>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            block_update_queue,
+            mut water_flow_simulator,
+        ) = data;
+
+        for &position in block_update_queue.recent_changes.iter() {
+            water_flow_simulator.on_block_changed(&chunk_manager, position);
+        }
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        while self.accumulator >= tick_duration {
+            for _ in 0..WATER_FLOW_STEPS_PER_TICK {
+                if !water_flow_simulator.step(&chunk_manager) {
+                    break;
+                }
+            }
+            self.accumulator -= tick_duration;
+        }
+    }
+}
+
+/// Renders the chunks seen from a camera mirrored across the water surface into a texture.
+/// Must run before the main render pass.
+pub struct RenderReflection {
+    framebuffer: u32,
+}
+
+impl RenderReflection {
+    pub fn new() -> Self {
+        let (framebuffer, reflection_texture) = create_reflection_framebuffer(WINDOW_WIDTH as i32, WINDOW_HEIGHT as i32);
+        gl_call!(gl::BindTextureUnit(REFLECTION_TEXTURE_UNIT, reflection_texture));
+        Self {
+            framebuffer
+        }
+    }
+}
+
+impl<'a> System<'a> for RenderReflection {
+    type SystemData = (
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        Read<'a, Settings>,
+        Read<'a, Weather>,
+        Write<'a, FrameUBO>,
+        Write<'a, Shaders>,
+        Write<'a, ChunkRenderPipeline>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_state,
+            player_physics_state,
+            chunk_manager,
+            texture_pack,
+            settings,
+            weather,
+            mut frame_ubo,
+            mut shaders,
+            mut chunk_render_pipeline,
+        ) = data;
+
+        // There is no water to reflect anything in the Nether
+        if chunk_manager.dimension != Dimension::Overworld {
+            return;
+        }
+
+        let water_height = water_surface_height();
+
+        for (player_state, player_physics_state) in (&player_state, &player_physics_state).join() {
+            // The surface can't be seen from above, there is nothing to reflect
+            let camera_height = player_physics_state.get_interpolated_state().position.y
+                + *player_state.camera_height.get_interpolated_state();
+            if camera_height < water_height {
+                continue;
+            }
+
+            let mut viewport = [0; 4];
+            gl_call!(gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr()));
+
+            gl_call!(gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer));
+            gl_call!(gl::Viewport(0, 0, WINDOW_WIDTH as i32, WINDOW_HEIGHT as i32));
+            let (r, g, b, a) = weather.sky_color(chunk_manager.dimension);
+            gl_call!(gl::ClearColor(r, g, b, a));
+            gl_call!(gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT));
+
+            // Discard everything under the water
+            gl_call!(gl::Enable(gl::CLIP_DISTANCE0));
+            // Mirroring the scene inverts the winding order of the triangles
+            gl_call!(gl::FrontFace(gl::CW));
+
+            // Seen from the mirrored camera for this pass only
+            let frame_data = frame_ubo.data;
+            frame_ubo.upload(FrameData {
+                view: player_state.view_matrix * reflection_matrix(water_height),
+                ..frame_data
+            });
+
+            let voxel_shader = shaders.get_mut("voxel_shader").unwrap();
+            voxel_shader.use_program();
+            voxel_shader.set_uniform1i("array_texture", 0);
+            voxel_shader.set_uniform1i("grasscolor_map", GRASS_COLORMAP_TEXTURE_UNIT as i32);
+            voxel_shader.set_uniform1i("grass_top_layer", texture_pack[&BlockID::GrassBlock].get_uv_of_every_face().2 as i32);
+            voxel_shader.set_uniform1i("portal_layer", texture_pack[&BlockID::NetherPortal].get_uv_of_every_face().0 as i32);
+            let (campfire_layer, campfire_frame_layer) = campfire_flames_layers(&texture_pack, frame_data.global_time);
+            voxel_shader.set_uniform1i("campfire_layer", campfire_layer);
+            voxel_shader.set_uniform1i("campfire_frame_layer", campfire_frame_layer);
+            voxel_shader.set_uniform1i("enable_fog", ENABLE_FOG as i32);
+            voxel_shader.set_uniform1f("ao_strength", settings.ao_strength);
+            voxel_shader.set_uniform4f("clip_plane", &[0.0, 1.0, 0.0, -water_height]);
+            chunk_render_pipeline.draw_all_rings(&chunk_manager, &player_physics_state.get_interpolated_state().position, None);
+            frame_ubo.upload(frame_data);
+
+            gl_call!(gl::FrontFace(gl::CCW));
+            gl_call!(gl::Disable(gl::CLIP_DISTANCE0));
+            gl_call!(gl::BindFramebuffer(gl::FRAMEBUFFER, 0));
+            gl_call!(gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]));
+        }
+    }
+}
+
+pub struct RenderWater;
+
+impl<'a> System<'a> for RenderWater {
+    type SystemData = (
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_state,
+            player_physics_state,
+            chunk_manager,
+            mut shaders,
+        ) = data;
+
+        let mut viewport = [0; 4];
+        gl_call!(gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr()));
+
+        let water_shader = shaders.get_mut("water_shader").unwrap();
+        water_shader.use_program();
+        water_shader.set_uniform1i("array_texture", 0);
+        water_shader.set_uniform1i("reflection_texture", REFLECTION_TEXTURE_UNIT as i32);
+        water_shader.set_uniform2f("screen_size", &[viewport[2] as f32, viewport[3] as f32]);
+        water_shader.set_uniform1i("enable_fog", ENABLE_FOG as i32);
+
+        // The surface must also be visible from underwater
+        gl_call!(gl::Disable(gl::CULL_FACE));
+        gl_call!(gl::DepthMask(gl::FALSE));
+        gl_call!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
+
+        for (player_state, player_physics_state) in (&player_state, &player_physics_state).join() {
+            let camera_position = player_physics_state.get_interpolated_state().position
+                + nalgebra_glm::vec3(0.0, *player_state.camera_height.get_interpolated_state(), 0.0);
+            water_shader.set_uniform3f("camera_position", &[camera_position.x, camera_position.y, camera_position.z]);
+            chunk_manager.render_loaded_water(water_shader);
+        }
+
+        gl_call!(gl::DepthMask(gl::TRUE));
+        gl_call!(gl::Enable(gl::CULL_FACE));
+    }
+}
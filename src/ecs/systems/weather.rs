@@ -0,0 +1,148 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra_glm::vec3;
+use rand::Rng;
+use specs::{Join, Read, ReadStorage, System, Write};
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{LIGHTNING_RADIUS, RAIN_COLUMN_SIZE, RAIN_DROP_SPEED, RAIN_DROPS_PER_TICK, RAIN_HEIGHT, TICKS_PER_SECOND};
+use crate::dimension::Dimension;
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::timer::Timer;
+use crate::types::{ParticleSystems, TexturePack};
+use crate::weather::Weather;
+
+/// Changes the weather over time. During thunderstorms, lightning strikes around the player and sets fire to the ground
+pub struct UpdateWeather {
+    last_time: Instant,
+}
+
+impl UpdateWeather {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateWeather {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, Weather>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            mut weather,
+            player_physics_state,
+        ) = data;
+
+        let now = global_timer.time();
+        let dt = now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        // The weather of the Overworld waits for the player to come back, the flash must not start in the Nether
+        if chunk_manager.dimension != Dimension::Overworld || !weather.advance(dt) {
+            return;
+        }
+
+        let mut rng = rand::thread_rng();
+        for player_physics_state in (&player_physics_state).join() {
+            let position = player_physics_state.get_latest_state().position;
+            let x = position.x.floor() as i32 + rng.gen_range(-LIGHTNING_RADIUS, LIGHTNING_RADIUS + 1);
+            let z = position.z.floor() as i32 + rng.gen_range(-LIGHTNING_RADIUS, LIGHTNING_RADIUS + 1);
+            if let Some(y) = chunk_manager.topmost_solid_block(x, z) {
+                info!("Lightning struck at ({} {} {})", x, y, z);
+                if chunk_manager.get_block(x, y + 1, z) == Some(BlockID::Air) {
+                    chunk_manager.put_block(BlockID::Fire, x, y + 1, z);
+                }
+            }
+        }
+    }
+}
+
+/// Makes rain fall around the camera, one game tick at a time.
+/// The drops start high above the camera and disappear when they reach the highest solid block of their column
+pub struct EmitRainDrops {
+    last_time: Instant,
+    accumulator: f32,
+}
+
+impl EmitRainDrops {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+            accumulator: 0.0,
+        }
+    }
+}
+
+impl<'a> System<'a> for EmitRainDrops {
+    type SystemData = (
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Read<'a, TexturePack>,
+        Read<'a, Weather>,
+        Write<'a, ParticleSystems>,
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            global_timer,
+            chunk_manager,
+            texture_pack,
+            weather,
+            mut particle_systems,
+            player_state,
+            player_physics_state,
+        ) = data;
+
+        let now = global_timer.time();
+        self.accumulator += now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        let tick_duration = 1.0 / TICKS_PER_SECOND;
+        if !weather.is_raining() || chunk_manager.dimension != Dimension::Overworld {
+            self.accumulator %= tick_duration;
+            return;
+        }
+
+        let mut rng = rand::thread_rng();
+        let rain_drop_particles = particle_systems.get_mut("rain_drop_particles").unwrap();
+        while self.accumulator >= tick_duration {
+            for (player_state, player_physics_state) in (&player_state, &player_physics_state).join() {
+                let camera_position = player_physics_state.get_latest_state().position
+                    + vec3(0.0, *player_state.camera_height.get_latest_state(), 0.0);
+                let top = camera_position.y + RAIN_HEIGHT;
+
+                for _ in 0..RAIN_DROPS_PER_TICK {
+                    let x = camera_position.x + rng.gen_range(-0.5, 0.5) * RAIN_COLUMN_SIZE as f32;
+                    let z = camera_position.z + rng.gen_range(-0.5, 0.5) * RAIN_COLUMN_SIZE as f32;
+                    // No rain falls in the columns that aren't generated yet
+                    let ground = match chunk_manager.topmost_solid_block(x.floor() as i32, z.floor() as i32) {
+                        Some(y) => (y + 1) as f32,
+                        None => continue,
+                    };
+                    if ground >= top {
+                        continue;
+                    }
+
+                    let fall_duration = (top - ground) / RAIN_DROP_SPEED;
+                    if !rain_drop_particles.emit_rain_drop_particle(vec3(x, top, z), fall_duration, &texture_pack) {
+                        break;
+                    }
+                }
+            }
+            self.accumulator -= tick_duration;
+        }
+    }
+}
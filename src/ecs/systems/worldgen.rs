@@ -0,0 +1,42 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use specs::{System, Write};
+
+use crate::worldgen::WorldGenConfig;
+
+/// Reads the world generation config again when its file changes.
+/// Only the chunks generated afterwards use the new parameters
+pub struct ReloadWorldGenConfig {
+    last_check: Instant,
+    last_modified: Option<SystemTime>,
+}
+
+impl ReloadWorldGenConfig {
+    pub fn new() -> Self {
+        Self {
+            last_check: Instant::now(),
+            last_modified: WorldGenConfig::modified_time(),
+        }
+    }
+}
+
+impl<'a> System<'a> for ReloadWorldGenConfig {
+    type SystemData = (
+        Write<'a, WorldGenConfig>,
+    );
+
+    fn run(&mut self, (mut config, ): Self::SystemData) {
+        // Asking the file system every frame would be wasteful
+        if self.last_check.elapsed() < Duration::from_secs(1) {
+            return;
+        }
+        self.last_check = Instant::now();
+
+        let modified = WorldGenConfig::modified_time();
+        if modified != self.last_modified {
+            self.last_modified = modified;
+            *config = WorldGenConfig::load();
+            info!("Reloaded the world generation config");
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::{Vec3, vec3};
+use specs::{Entities, Join, Read, ReadStorage, System, Write, WriteStorage};
+
+use crate::action_bar::ActionBar;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{GRAVITY, ITEM_PICKUP_RANGE, TICKS_PER_SECOND, XP_ORB_ATTRACTION, XP_ORB_ATTRACTION_RANGE, XP_ORB_DESPAWN_TIME};
+use crate::ecs::systems::item_entity::move_with_collisions;
+use crate::ecs::systems::mob::create_colored_box_vao;
+use crate::physics::Interpolator;
+use crate::player::{PlayerPhysicsState, PlayerState};
+use crate::timer::Timer;
+use crate::types::Shaders;
+use crate::util::is_sphere_in_frustum;
+use crate::xp_orb::XpOrb;
+
+/// Size of the cube drawn for an experience orb
+const XP_ORB_SCALE: f32 = 0.2;
+
+/// Moves the experience orbs, pulls them towards the player close enough and gives the player those touching them
+pub struct UpdateXpOrbs {
+    last_time: Instant,
+}
+
+impl UpdateXpOrbs {
+    pub fn new() -> Self {
+        Self {
+            last_time: Instant::now(),
+        }
+    }
+}
+
+impl<'a> System<'a> for UpdateXpOrbs {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, Timer>,
+        Read<'a, Arc<ChunkManager>>,
+        Write<'a, ActionBar>,
+        ReadStorage<'a, Interpolator<PlayerPhysicsState>>,
+        WriteStorage<'a, PlayerState>,
+        WriteStorage<'a, XpOrb>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            global_timer,
+            chunk_manager,
+            mut action_bar,
+            player_physics_state,
+            mut player_state,
+            mut xp_orbs,
+        ) = data;
+
+        let now = global_timer.time();
+        let dt = now.saturating_duration_since(self.last_time).as_secs_f32().min(0.25);
+        self.last_time = now;
+
+        for (player_physics_state, player_state) in (&player_physics_state, &mut player_state).join() {
+            let physics_state = player_physics_state.get_latest_state();
+            let aabb = physics_state.aabb;
+            // Towards the middle of the body
+            let target = physics_state.position + vec3(0.0, (aabb.maxs.y - aabb.mins.y) / 2.0, 0.0);
+
+            for (e, xp_orb) in (&entities, &mut xp_orbs).join() {
+                xp_orb.age += dt;
+
+                let to_player: Vec3 = target - xp_orb.position;
+                let distance = to_player.norm();
+                if distance > 0.0 && distance < XP_ORB_ATTRACTION_RANGE {
+                    xp_orb.velocity += to_player / distance * XP_ORB_ATTRACTION * TICKS_PER_SECOND * dt;
+                }
+
+                let (x, y, z) = (
+                    xp_orb.position.x.floor() as i32,
+                    xp_orb.position.y.floor() as i32,
+                    xp_orb.position.z.floor() as i32,
+                );
+                // Orbs lying in chunks that aren't loaded yet wait for them
+                if chunk_manager.get_block(x, y, z).is_some() {
+                    xp_orb.velocity.y += GRAVITY * dt;
+                    move_with_collisions(&mut xp_orb.position, &mut xp_orb.velocity, &chunk_manager, dt);
+                }
+
+                // Distance to the closest point of the player
+                let position = xp_orb.position;
+                let closest = vec3(
+                    position.x.max(aabb.mins.x).min(aabb.maxs.x),
+                    position.y.max(aabb.mins.y).min(aabb.maxs.y),
+                    position.z.max(aabb.mins.z).min(aabb.maxs.z),
+                );
+                let is_collected = (position - closest).norm() <= ITEM_PICKUP_RANGE;
+                if is_collected {
+                    if player_state.add_xp(xp_orb.value, now) {
+                        action_bar.show("Level up!", now);
+                    }
+                }
+
+                if is_collected || xp_orb.age >= XP_ORB_DESPAWN_TIME || xp_orb.position.y < 0.0 {
+                    if let Err(e) = entities.delete(e) {
+                        error!("{}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draws the experience orbs as small glowing green cubes
+pub struct RenderXpOrbs {
+    vao: u32,
+}
+
+impl RenderXpOrbs {
+    pub fn new() -> Self {
+        Self {
+            vao: create_colored_box_vao(),
+        }
+    }
+}
+
+impl<'a> System<'a> for RenderXpOrbs {
+    type SystemData = (
+        ReadStorage<'a, PlayerState>,
+        ReadStorage<'a, XpOrb>,
+        Write<'a, Shaders>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_state,
+            xp_orbs,
+            mut shaders,
+        ) = data;
+
+        for player_state in (&player_state).join() {
+            let view_projection = player_state.projection_matrix * player_state.view_matrix;
+
+            let mob_shader = shaders.get_mut("mob_shader").unwrap();
+            mob_shader.use_program();
+            gl_call!(gl::BindVertexArray(self.vao));
+
+            for xp_orb in (&xp_orbs).join() {
+                if !is_sphere_in_frustum(&view_projection, &xp_orb.position, XP_ORB_SCALE) {
+                    continue;
+                }
+
+                // The bigger orbs are worth more
+                let scale = XP_ORB_SCALE * (1.0 + 0.15 * xp_orb.value as f32);
+                let model_matrix = Matrix4::new_translation(&xp_orb.position)
+                    * Matrix4::from_euler_angles(0.0, xp_orb.age * 3.0, 0.0)
+                    * Matrix4::new_scaling(scale);
+                mob_shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+                // Flickers between green and yellow
+                let flicker = 0.5 + 0.5 * (xp_orb.age * 6.0).sin();
+                mob_shader.set_uniform3f("color", &[0.5 + 0.4 * flicker, 1.0, 0.2]);
+                gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 36));
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::any::Any;
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+use crate::block_entity::BlockEntity;
+use crate::block_properties::BLOCK_PROPERTIES;
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{ENCHANTING_LEVELS_PER_BOOKSHELF, GUI_SCALING, KNOCKBACK_SPEED_PER_LEVEL, MAX_ENCHANTING_COST, WINDOW_WIDTH};
+use crate::inventory::armor::{armor_properties, ArmorSlot};
+use crate::inventory::item::ItemStack;
+use crate::inventory::tool::{tool_properties, ToolKind};
+use crate::shader_compilation::ShaderProgram;
+use crate::text::{GLYPH_HEIGHT, TextRenderer};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum EnchantmentId {
+    /// Mines faster
+    Efficiency,
+    /// Wears out slower
+    Unbreaking,
+    /// Deals more damage
+    Sharpness,
+    /// Charges a crossbow faster
+    QuickCharge,
+    /// Softens the falls, on boots
+    FeatherFalling,
+    /// Mined blocks drop more of what they turn into
+    Fortune,
+    /// Mined blocks drop themselves instead of what they turn into
+    SilkTouch,
+    /// Pushes the hit mobs away, on swords
+    Knockback,
+}
+
+impl EnchantmentId {
+    pub const ALL: [EnchantmentId; 8] = [
+        EnchantmentId::Efficiency,
+        EnchantmentId::Unbreaking,
+        EnchantmentId::Sharpness,
+        EnchantmentId::QuickCharge,
+        EnchantmentId::FeatherFalling,
+        EnchantmentId::Fortune,
+        EnchantmentId::SilkTouch,
+        EnchantmentId::Knockback,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnchantmentId::Efficiency => "Efficiency",
+            EnchantmentId::Unbreaking => "Unbreaking",
+            EnchantmentId::Sharpness => "Sharpness",
+            EnchantmentId::QuickCharge => "Quick Charge",
+            EnchantmentId::FeatherFalling => "Feather Falling",
+            EnchantmentId::Fortune => "Fortune",
+            EnchantmentId::SilkTouch => "Silk Touch",
+            EnchantmentId::Knockback => "Knockback",
+        }
+    }
+
+    pub fn max_level(&self) -> u8 {
+        match self {
+            EnchantmentId::Efficiency | EnchantmentId::Sharpness => 5,
+            EnchantmentId::FeatherFalling => 4,
+            EnchantmentId::Unbreaking | EnchantmentId::QuickCharge | EnchantmentId::Fortune => 3,
+            EnchantmentId::SilkTouch => 1,
+            EnchantmentId::Knockback => 2,
+        }
+    }
+
+    /// Whether the enchantment can be put on the item
+    pub fn applies_to(&self, item: BlockID) -> bool {
+        let tool = tool_properties(item);
+        match self {
+            EnchantmentId::Efficiency => tool.is_some(),
+            EnchantmentId::Unbreaking => tool.is_some() || item == BlockID::Crossbow,
+            EnchantmentId::Sharpness => tool.map_or(false, |tool| tool.kind == ToolKind::Axe || tool.kind == ToolKind::Sword),
+            EnchantmentId::QuickCharge => item == BlockID::Crossbow,
+            EnchantmentId::FeatherFalling => armor_properties(item).map_or(false, |armor| armor.slot == ArmorSlot::Boots),
+            EnchantmentId::Fortune | EnchantmentId::SilkTouch => tool.map_or(false, |tool| tool.kind != ToolKind::Sword),
+            EnchantmentId::Knockback => tool.map_or(false, |tool| tool.kind == ToolKind::Sword),
+        }
+    }
+}
+
+/// Mining speed multiplier given by Efficiency
+pub fn efficiency_multiplier(level: u8) -> f32 {
+    let level = level as f32;
+    1.0 + level * level + level
+}
+
+/// Damage added to the hits by Sharpness
+pub fn sharpness_bonus(level: u8) -> f32 {
+    if level == 0 {
+        0.0
+    } else {
+        0.5 + 0.5 * level as f32
+    }
+}
+
+/// Whether a use wears out an item with Unbreaking, only one use out of `level + 1` does on average
+pub fn consumes_durability(unbreaking: u8, rng: &mut impl Rng) -> bool {
+    rng.gen_range(0, unbreaking as u32 + 1) == 0
+}
+
+/// The items left by a mined block, with their amount. Silk Touch keeps the blocks turning into something
+/// else (e.g. stone into cobblestone) intact, Fortune multiplies what the ores turn into
+pub fn mined_block_drop(block: BlockID, tool: Option<&ItemStack>, rng: &mut impl Rng) -> Option<(BlockID, u32)> {
+    let level = |enchantment| tool.map_or(0, |item_stack| item_stack.enchantment_level(enchantment));
+    let drop = BLOCK_PROPERTIES[&block].drop;
+    if drop == Some(block) || !block.is_silk_touchable() {
+        return drop.map(|item| (item, 1));
+    }
+    if level(EnchantmentId::SilkTouch) > 0 {
+        return Some((block, 1));
+    }
+    if !block.is_ore() {
+        return drop.map(|item| (item, 1));
+    }
+    drop.map(|item| (item, 1 + rng.gen_range(0, level(EnchantmentId::Fortune) as u32 + 1)))
+}
+
+/// Horizontal speed given to a mob hit with Knockback, in blocks per second
+pub fn knockback_speed(level: u8) -> f32 {
+    KNOCKBACK_SPEED_PER_LEVEL * level as f32
+}
+
+/// The name of the enchantment followed by its level in roman numerals, e.g. "Efficiency III"
+pub fn enchantment_label((enchantment, level): (EnchantmentId, u8)) -> String {
+    const NUMERALS: [&str; 5] = ["I", "II", "III", "IV", "V"];
+    match NUMERALS.get(level as usize - 1) {
+        Some(numeral) => format!("{} {}", enchantment.name(), numeral),
+        None => format!("{} {}", enchantment.name(), level),
+    }
+}
+
+/// The tools and the weapons
+pub fn is_enchantable(item: BlockID) -> bool {
+    EnchantmentId::ALL.iter().any(|enchantment| enchantment.applies_to(item))
+}
+
+/// Picks one of the enchantments that can be put on the item, the more levels it costs the stronger it is
+pub fn random_enchantment(item: BlockID, cost: u32, rng: &mut impl Rng) -> Option<(EnchantmentId, u8)> {
+    let candidates: Vec<EnchantmentId> = EnchantmentId::ALL.iter().cloned()
+        .filter(|enchantment| enchantment.applies_to(item))
+        .collect();
+    let enchantment = *candidates.choose(rng)?;
+    let max_level = enchantment.max_level() as u32;
+    let level = ((cost * max_level + MAX_ENCHANTING_COST - 1) / MAX_ENCHANTING_COST).max(1).min(max_level);
+    Some((enchantment, level as u8))
+}
+
+/// Keeps track of the bookshelves around an enchanting table, they unlock the costlier offers
+#[derive(Default)]
+pub struct EnchantingTableBlockEntity {
+    pub bookshelf_count: u32,
+}
+
+impl EnchantingTableBlockEntity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Levels asked by the three offers, the last one being the strongest
+    pub fn offers(&self) -> [u32; 3] {
+        let max_cost = (self.bookshelf_count * ENCHANTING_LEVELS_PER_BOOKSHELF).min(MAX_ENCHANTING_COST);
+        [(max_cost / 3).max(1), (max_cost * 2 / 3).max(1), max_cost.max(1)]
+    }
+}
+
+/// Bookshelves two blocks away from the table, on its level or the one above, with air between them and the table
+fn count_bookshelves(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> u32 {
+    let is_bookshelf = |x: i32, y: i32, z: i32| chunk_manager.get_block(x, y, z) == Some(BlockID::Bookshelf);
+    let is_air = |x: i32, y: i32, z: i32| chunk_manager.get_block(x, y, z) == Some(BlockID::Air);
+
+    let mut count = 0;
+    for dz in -1..=1 {
+        for dx in -1..=1 {
+            if (dx == 0 && dz == 0) || !is_air(x + dx, y, z + dz) || !is_air(x + dx, y + 1, z + dz) {
+                continue;
+            }
+            // The bookshelves behind the gap, and the ones next to them when the gap is in a corner
+            let mut shelves = vec![(2 * dx, 2 * dz)];
+            if dx != 0 && dz != 0 {
+                shelves.push((2 * dx, dz));
+                shelves.push((dx, 2 * dz));
+            }
+            for (sx, sz) in shelves {
+                for sy in 0..=1 {
+                    if is_bookshelf(x + sx, y + sy, z + sz) {
+                        count += 1;
+                    }
+                }
+            }
+        }
+    }
+    count
+}
+
+impl BlockEntity for EnchantingTableBlockEntity {
+    fn tick(&mut self, position: (i32, i32, i32), chunk_manager: &ChunkManager) {
+        self.bookshelf_count = count_bookshelves(chunk_manager, position);
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        vec![self.bookshelf_count as u8]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Size of an offer of the enchanting table screen, in GUI pixels
+const OFFER_WIDTH: f32 = 108.0;
+const OFFER_HEIGHT: f32 = 19.0;
+
+const OFFER_COLOR: (f32, f32, f32, f32) = (0.35, 0.2, 0.4, 0.9);
+const LOCKED_OFFER_COLOR: (f32, f32, f32, f32) = (0.2, 0.2, 0.2, 0.9);
+const TEXT_COLOR: (f32, f32, f32, f32) = (1.0, 1.0, 1.0, 1.0);
+const LOCKED_TEXT_COLOR: (f32, f32, f32, f32) = (0.6, 0.6, 0.6, 1.0);
+
+/// Center of an offer in screen coordinates, the first one at the top
+fn offer_position(offer: usize) -> (f32, f32) {
+    (WINDOW_WIDTH as f32 / 2.0, (110.0 - 21.0 * offer as f32) * GUI_SCALING)
+}
+
+/// Returns the offer under the given point in screen coordinates (y pointing up)
+pub fn offer_at(x: f32, y: f32) -> Option<usize> {
+    (0..3).find(|&offer| {
+        let (center_x, center_y) = offer_position(offer);
+        (x - center_x).abs() <= OFFER_WIDTH * GUI_SCALING / 2.0 && (y - center_y).abs() <= OFFER_HEIGHT * GUI_SCALING / 2.0
+    })
+}
+
+/// Draws the three offers above the hotbar, the ones the player can't take are greyed out
+pub fn draw_enchanting_table(enchanting_table: &EnchantingTableBlockEntity, player_level: u32, selected_item: Option<BlockID>,
+                             text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    let title = format!("Enchant ({} bookshelves)", enchanting_table.bookshelf_count);
+    let (title_x, title_y) = offer_position(0);
+    text_renderer.draw(&title, title_x - TextRenderer::text_width(&title, GUI_SCALING) / 2.0,
+                       title_y + 16.0 * GUI_SCALING, GUI_SCALING, TEXT_COLOR, shader);
+
+    let is_enchantable = selected_item.map_or(false, is_enchantable);
+    for (offer, &cost) in enchanting_table.offers().iter().enumerate() {
+        let (center_x, center_y) = offer_position(offer);
+        let is_available = is_enchantable && player_level >= cost;
+        let (width, height) = (OFFER_WIDTH * GUI_SCALING, OFFER_HEIGHT * GUI_SCALING);
+        let color = if is_available { OFFER_COLOR } else { LOCKED_OFFER_COLOR };
+        text_renderer.draw_rect(center_x - width / 2.0, center_y - height / 2.0, width, height, color, shader);
+
+        let label = format!("{} levels", cost);
+        let text_color = if is_available { TEXT_COLOR } else { LOCKED_TEXT_COLOR };
+        text_renderer.draw(&label, center_x - TextRenderer::text_width(&label, GUI_SCALING) / 2.0,
+                           center_y - GLYPH_HEIGHT * GUI_SCALING / 2.0, GUI_SCALING, text_color, shader);
+    }
+}
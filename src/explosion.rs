@@ -0,0 +1,56 @@
+use std::collections::VecDeque;
+
+use nalgebra_glm::{vec3, Vec3};
+
+use crate::block_properties::BLOCK_PROPERTIES;
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{EXPLOSION_DAMAGE, EXPLOSION_RADIUS, EXPLOSION_STRENGTH};
+use crate::particle_system::ParticleSystem;
+use crate::types::{BlockEntityRegistry, TexturePack};
+
+/// Blows up the TNT block at `center` and destroys every block around it resisting less than `EXPLOSION_STRENGTH`.
+/// The TNT blocks caught in the blast explode as well. Returns where every TNT block exploded
+pub fn explode(center: (i32, i32, i32), chunk_manager: &ChunkManager, block_entity_registry: &mut BlockEntityRegistry,
+               particle_system: &mut ParticleSystem, texture_pack: &TexturePack) -> Vec<(i32, i32, i32)> {
+    let mut exploded = Vec::new();
+    let radius = EXPLOSION_RADIUS.ceil() as i32;
+    let mut explosions = VecDeque::new();
+    chunk_manager.put_block(BlockID::Air, center.0, center.1, center.2);
+    explosions.push_back(center);
+
+    while let Some((c_x, c_y, c_z)) = explosions.pop_front() {
+        info!("TNT exploded at ({} {} {})", c_x, c_y, c_z);
+        exploded.push((c_x, c_y, c_z));
+
+        for dx in -radius..=radius {
+            for dy in -radius..=radius {
+                for dz in -radius..=radius {
+                    if ((dx * dx + dy * dy + dz * dz) as f32).sqrt() > EXPLOSION_RADIUS {
+                        continue;
+                    }
+
+                    let (x, y, z) = (c_x + dx, c_y + dy, c_z + dz);
+                    let block = match chunk_manager.get_block(x, y, z) {
+                        Some(block) if !block.is_air() && BLOCK_PROPERTIES[&block].blast_resistance < EXPLOSION_STRENGTH => block,
+                        _ => continue,
+                    };
+                    chunk_manager.put_block(BlockID::Air, x, y, z);
+                    block_entity_registry.remove(&(x, y, z));
+                    if block == BlockID::Tnt {
+                        explosions.push_back((x, y, z));
+                    } else {
+                        particle_system.emit_block_particles(block, vec3(x as f32, y as f32, z as f32), 4, texture_pack);
+                    }
+                }
+            }
+        }
+    }
+    exploded
+}
+
+/// Damage dealt to what stands at `position` by the explosion of the TNT block at `center`
+pub fn explosion_damage((x, y, z): (i32, i32, i32), position: &Vec3) -> f32 {
+    let distance = (position - vec3(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5)).norm();
+    EXPLOSION_DAMAGE * (1.0 - distance / (2.0 * EXPLOSION_RADIUS)).max(0.0)
+}
@@ -0,0 +1,50 @@
+use std::any::Any;
+
+use crate::block_entity::BlockEntity;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{FENCE_GATE_OPEN_ANGLE, FENCE_GATE_SWING_TICKS};
+
+/// Swings a fence gate around its hinge, whether it is open is in the block meta
+#[derive(Default)]
+pub struct FenceGateBlockEntity {
+    /// Angle of the panel once it stops swinging, in degrees
+    pub angle_target: f32,
+    /// Angle of the panel drawn this tick, in degrees
+    pub angle_current: f32,
+    /// Whether the redstone input was powered during the last tick
+    pub is_powered: bool,
+}
+
+impl FenceGateBlockEntity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn swing(&mut self, is_open: bool) {
+        self.angle_target = if is_open { FENCE_GATE_OPEN_ANGLE } else { 0.0 };
+    }
+}
+
+impl BlockEntity for FenceGateBlockEntity {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {
+        let step = FENCE_GATE_OPEN_ANGLE / FENCE_GATE_SWING_TICKS as f32;
+        let delta = self.angle_target - self.angle_current;
+        self.angle_current += delta.max(-step).min(step);
+    }
+
+    fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(&self.angle_target.to_le_bytes());
+        bytes.extend(&self.angle_current.to_le_bytes());
+        bytes.push(self.is_powered as u8);
+        bytes
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,57 @@
+use nalgebra_glm::{Vec3, Vec4, vec3, vec4};
+use rand::Rng;
+
+use crate::constants::{FIREWORK_SPEED, FIREWORK_TICKS_PER_FLIGHT_TIME};
+
+/// Colors a rocket can burst into, RGBA packed as 0xRRGGBBAA
+const FIREWORK_COLORS: [u32; 8] = [
+    0xE02020FF, // Red
+    0xF08020FF, // Orange
+    0xF0E040FF, // Yellow
+    0x40D040FF, // Green
+    0x40C0F0FF, // Light blue
+    0x3050E0FF, // Blue
+    0xB040E0FF, // Purple
+    0xF0F0F0FF, // White
+];
+
+/// A rocket rising from where it was launched until it bursts into stars of its colors
+pub struct FireworkEntity {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Colors of the stars, RGBA packed as 0xRRGGBBAA
+    pub colors: Vec<u32>,
+    /// The rocket bursts after flying for `flight_time` * FIREWORK_TICKS_PER_FLIGHT_TIME game ticks
+    pub flight_time: u8,
+    pub ticks_flown: u32,
+}
+
+impl FireworkEntity {
+    /// A rocket going up from `position`, slightly tilted, with one to three random colors
+    pub fn launched<R: Rng>(position: Vec3, flight_time: u8, rng: &mut R) -> Self {
+        let colors = (0..rng.gen_range(1, 4))
+            .map(|_| FIREWORK_COLORS[rng.gen_range(0, FIREWORK_COLORS.len())])
+            .collect();
+        FireworkEntity {
+            position,
+            velocity: vec3(rng.gen_range(-0.05, 0.05), 1.0, rng.gen_range(-0.05, 0.05)).normalize() * FIREWORK_SPEED,
+            colors,
+            flight_time: flight_time.max(1),
+            ticks_flown: 0,
+        }
+    }
+
+    pub fn should_detonate(&self) -> bool {
+        self.ticks_flown >= self.flight_time as u32 * FIREWORK_TICKS_PER_FLIGHT_TIME
+    }
+}
+
+/// Unpacks a 0xRRGGBBAA color
+pub fn unpack_color(color: u32) -> Vec4 {
+    vec4(
+        (color >> 24 & 0xFF) as f32 / 255.0,
+        (color >> 16 & 0xFF) as f32 / 255.0,
+        (color >> 8 & 0xFF) as f32 / 255.0,
+        (color & 0xFF) as f32 / 255.0,
+    )
+}
@@ -0,0 +1,85 @@
+use nalgebra_glm::{Mat4, Vec4};
+
+/// Binding point of the `FrameData` uniform block, set in the layout of its declaration in the shaders
+pub const FRAME_DATA_BINDING: u32 = 0;
+
+/// The uniforms shared by the shaders drawing the world, the same for every draw call of a frame.
+/// Laid out like the `std140` block the shaders declare, its size rounded up to a multiple of 16 bytes:
+///
+/// ```glsl
+/// layout(std140, binding = 0) uniform FrameData {
+///     mat4 view;
+///     mat4 projection;
+///     vec4 sun_direction;
+///     vec4 fog_color;
+///     float global_time;
+///     float render_distance;
+///     float fog_density_multiplier;
+/// };
+/// ```
+#[repr(C, align(16))]
+#[derive(Copy, Clone, Debug)]
+pub struct FrameData {
+    pub view: Mat4,
+    pub projection: Mat4,
+    /// Normalized, the w component is unused
+    pub sun_direction: Vec4,
+    /// The color of the sky, the distant blocks fade into it. The alpha is unused
+    pub fog_color: Vec4,
+    /// Seconds since the start of the game, used to animate the blocks
+    pub global_time: f32,
+    /// The fog thickens with the render distance and the weather
+    pub render_distance: f32,
+    pub fog_density_multiplier: f32,
+}
+
+impl Default for FrameData {
+    fn default() -> Self {
+        Self {
+            view: Mat4::identity(),
+            projection: Mat4::identity(),
+            sun_direction: Vec4::zeros(),
+            fog_color: Vec4::zeros(),
+            global_time: 0.0,
+            render_distance: 0.0,
+            fog_density_multiplier: 1.0,
+        }
+    }
+}
+
+/// The buffer the `FrameData` of the frame is uploaded to, once before the world is drawn
+pub struct FrameUBO {
+    buffer: u32,
+    /// What was last uploaded
+    pub data: FrameData,
+}
+
+impl Default for FrameUBO {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameUBO {
+    pub fn new() -> Self {
+        let mut buffer = 0;
+        gl_call!(gl::CreateBuffers(1, &mut buffer));
+        gl_call!(gl::NamedBufferStorage(buffer,
+                std::mem::size_of::<FrameData>() as isize,
+                std::ptr::null(),
+                gl::DYNAMIC_STORAGE_BIT));
+        gl_call!(gl::BindBufferBase(gl::UNIFORM_BUFFER, FRAME_DATA_BINDING, buffer));
+        Self {
+            buffer,
+            data: FrameData::default(),
+        }
+    }
+
+    pub fn upload(&mut self, data: FrameData) {
+        self.data = data;
+        gl_call!(gl::NamedBufferSubData(self.buffer,
+                0,
+                std::mem::size_of::<FrameData>() as isize,
+                &self.data as *const FrameData as *const std::ffi::c_void));
+    }
+}
@@ -0,0 +1,282 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::ffi::c_void;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::{Mat4, vec3};
+
+use crate::block_entity::{BlockEntity, serialize_slot};
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{GUI_SCALING, MAX_STACK_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::inventory::item::ItemStack;
+use crate::shader_compilation::ShaderProgram;
+use crate::shapes::quad;
+use crate::types::TexturePack;
+
+/// Number of ticks needed to smelt one item
+pub const COOK_TIME: u32 = 200;
+
+lazy_static! {
+    /// Number of ticks a single fuel item burns for
+    pub static ref FUEL_BURN_TIMES: HashMap<BlockID, u32> = {
+        let mut fuel_burn_times = HashMap::new();
+        fuel_burn_times.insert(BlockID::OakPlanks, 300);
+        fuel_burn_times.insert(BlockID::OakLog, 400);
+        fuel_burn_times.insert(BlockID::BirchLog, 400);
+        fuel_burn_times.insert(BlockID::SpruceLog, 400);
+        fuel_burn_times.insert(BlockID::JungleLog, 400);
+        fuel_burn_times
+    };
+
+    pub static ref SMELTING_RESULTS: HashMap<BlockID, BlockID> = {
+        let mut smelting_results = HashMap::new();
+        smelting_results.insert(BlockID::Cobblestone, BlockID::Stone);
+        smelting_results.insert(BlockID::IronOre, BlockID::IronIngot);
+        smelting_results.insert(BlockID::RawBeef, BlockID::CookedBeef);
+        smelting_results
+    };
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FurnaceSlot {
+    Input,
+    Fuel,
+    Output,
+}
+
+#[derive(Default)]
+pub struct FurnaceBlockEntity {
+    pub input: Option<ItemStack>,
+    pub fuel: Option<ItemStack>,
+    pub output: Option<ItemStack>,
+    /// Remaining ticks of the fuel currently burning
+    pub burn_time: u32,
+    /// Total ticks of the fuel currently burning, used to draw the flame
+    pub max_burn_time: u32,
+    pub cook_progress: u32,
+}
+
+impl FurnaceBlockEntity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_burning(&self) -> bool {
+        self.burn_time > 0
+    }
+
+    pub fn slot_mut(&mut self, slot: FurnaceSlot) -> &mut Option<ItemStack> {
+        match slot {
+            FurnaceSlot::Input => &mut self.input,
+            FurnaceSlot::Fuel => &mut self.fuel,
+            FurnaceSlot::Output => &mut self.output,
+        }
+    }
+
+    /// Whether the item in the input slot can be smelted into the output slot, which must have room left
+    fn can_smelt(&self) -> bool {
+        let result = match self.input.as_ref().and_then(|input| SMELTING_RESULTS.get(&input.item)) {
+            Some(&result) => result,
+            None => return false,
+        };
+        self.output.as_ref().map_or(true, |output| output.item == result && output.amount < MAX_STACK_SIZE)
+    }
+
+    pub fn update_dirty_items(&mut self, texture_pack: &TexturePack) {
+        for item_stack in [&mut self.input, &mut self.fuel, &mut self.output].iter_mut() {
+            if let Some(item_stack) = item_stack {
+                item_stack.update_if_dirty(&texture_pack);
+            }
+        }
+    }
+}
+
+impl BlockEntity for FurnaceBlockEntity {
+    /// Advances the furnace by one tick
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {
+        let can_smelt = self.can_smelt();
+
+        // Light up a new fuel item
+        if !self.is_burning() && can_smelt {
+            if let Some(&burn_time) = self.fuel.as_ref().and_then(|fuel| FUEL_BURN_TIMES.get(&fuel.item)) {
+                take_one(&mut self.fuel);
+                self.burn_time = burn_time;
+                self.max_burn_time = burn_time;
+            }
+        }
+
+        if !self.is_burning() {
+            self.cook_progress = 0;
+            return;
+        }
+
+        self.burn_time -= 1;
+        if !can_smelt {
+            self.cook_progress = 0;
+            return;
+        }
+
+        self.cook_progress += 1;
+        if self.cook_progress >= COOK_TIME {
+            self.cook_progress = 0;
+            let result = SMELTING_RESULTS[&self.input.as_ref().unwrap().item];
+            take_one(&mut self.input);
+            match &mut self.output {
+                Some(output) => output.amount += 1,
+                None => self.output = Some(ItemStack::new(1, result)),
+            }
+        }
+    }
+
+    /// The input, fuel and output slots followed by the burn time, the max burn time and the cook progress
+    fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for slot in [&self.input, &self.fuel, &self.output].iter() {
+            serialize_slot(slot, &mut bytes);
+        }
+        bytes.extend(&self.burn_time.to_le_bytes());
+        bytes.extend(&self.max_burn_time.to_le_bytes());
+        bytes.extend(&self.cook_progress.to_le_bytes());
+        bytes
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+fn take_one(slot: &mut Option<ItemStack>) {
+    if let Some(item_stack) = slot {
+        if item_stack.amount > 1 {
+            item_stack.amount -= 1;
+        } else {
+            *slot = None;
+        }
+    }
+}
+
+/// Draws the furnace screen above the hotbar.
+/// The flame and the arrow are partially drawn depending on the state of the furnace,
+/// so the UV coordinates of the quad are rewritten before each draw.
+pub struct FurnaceUI {
+    vao: u32,
+    vbo: u32,
+}
+
+impl FurnaceUI {
+    pub fn new() -> Self {
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        // Position
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+        // Texture coords
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 1, 2 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+        let mut vbo = 0;
+        gl_call!(gl::CreateBuffers(1, &mut vbo));
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (5 * std::mem::size_of::<f32>()) as i32));
+        gl_call!(gl::NamedBufferData(vbo,
+                    (30 * std::mem::size_of::<f32>() as usize) as isize,
+                    std::ptr::null(),
+                    gl::DYNAMIC_DRAW));
+
+        FurnaceUI { vao, vbo }
+    }
+
+    /// Center of a slot in screen coordinates
+    pub fn slot_position(slot: FurnaceSlot) -> (f32, f32) {
+        let center_x = WINDOW_WIDTH as f32 / 2.0;
+        match slot {
+            FurnaceSlot::Input => (center_x - 30.0 * GUI_SCALING, 108.0 * GUI_SCALING),
+            FurnaceSlot::Fuel => (center_x - 30.0 * GUI_SCALING, 68.0 * GUI_SCALING),
+            FurnaceSlot::Output => (center_x + 36.0 * GUI_SCALING, 88.0 * GUI_SCALING),
+        }
+    }
+
+    /// Returns the slot under the given point in screen coordinates (y pointing up)
+    pub fn slot_at(x: f32, y: f32) -> Option<FurnaceSlot> {
+        let half_slot_size = 9.0 * GUI_SCALING;
+        [FurnaceSlot::Input, FurnaceSlot::Fuel, FurnaceSlot::Output].iter()
+            .cloned()
+            .find(|&slot| {
+                let (slot_x, slot_y) = FurnaceUI::slot_position(slot);
+                (x - slot_x).abs() <= half_slot_size && (y - slot_y).abs() <= half_slot_size
+            })
+    }
+
+    /// Draws a part of the furnace texture.
+    /// (x, y) is the bottom left corner on the screen, uv is (left, top, width, height) in pixels
+    fn draw_sprite(&self, x: f32, y: f32, (u, v, width, height): (f32, f32, f32, f32), shader: &mut ShaderProgram) {
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+        let texture_size = 64.0;
+        let vbo_data = quad((u / texture_size, v / texture_size, (u + width) / texture_size, (v + height) / texture_size));
+        gl_call!(gl::NamedBufferSubData(self.vbo,
+                    0,
+                    (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                    vbo_data.as_ptr() as *mut c_void));
+
+        let model_matrix = {
+            let translate_matrix = Matrix4::new_translation(&vec3(
+                x + width * GUI_SCALING / 2.0, y + height * GUI_SCALING / 2.0, 0.0));
+            let scale_matrix: Mat4 = Matrix4::new_nonuniform_scaling(&vec3(width * GUI_SCALING, height * GUI_SCALING, 1.0));
+            translate_matrix * scale_matrix
+        };
+        shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+        gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 6));
+    }
+
+    pub fn draw(&self, furnace: &FurnaceBlockEntity, shader: &mut ShaderProgram) {
+        let projection_matrix = nalgebra_glm::ortho(
+            0.0, WINDOW_WIDTH as f32, 0.0, WINDOW_HEIGHT as f32, -5.0, 5.0);
+
+        shader.use_program();
+        shader.set_uniform_matrix4fv("projection", projection_matrix.as_ptr());
+        shader.set_uniform1i("tex", 5);
+        gl_call!(gl::BindVertexArray(self.vao));
+
+        for &slot in [FurnaceSlot::Input, FurnaceSlot::Fuel, FurnaceSlot::Output].iter() {
+            let (x, y) = FurnaceUI::slot_position(slot);
+            self.draw_sprite(x - 9.0 * GUI_SCALING, y - 9.0 * GUI_SCALING, (0.0, 0.0, 18.0, 18.0), shader);
+        }
+
+        // The flame burns down from the top
+        let (flame_x, _) = FurnaceUI::slot_position(FurnaceSlot::Input);
+        let (flame_x, flame_y) = (flame_x - 7.0 * GUI_SCALING, 81.0 * GUI_SCALING);
+        self.draw_sprite(flame_x, flame_y, (0.0, 18.0, 14.0, 14.0), shader);
+        if furnace.is_burning() {
+            let burnt = 14.0 * furnace.burn_time as f32 / furnace.max_burn_time as f32;
+            self.draw_sprite(flame_x, flame_y, (14.0, 18.0 + 14.0 - burnt, 14.0, burnt), shader);
+        }
+
+        // The arrow fills up from the left
+        let (arrow_x, arrow_y) = (WINDOW_WIDTH as f32 / 2.0 - 12.0 * GUI_SCALING, 80.0 * GUI_SCALING);
+        self.draw_sprite(arrow_x, arrow_y, (0.0, 32.0, 24.0, 17.0), shader);
+        let cooked = 24.0 * furnace.cook_progress as f32 / COOK_TIME as f32;
+        self.draw_sprite(arrow_x, arrow_y, (24.0, 32.0, cooked, 17.0), shader);
+    }
+
+    pub fn draw_items(&self, furnace: &FurnaceBlockEntity, shader: &mut ShaderProgram) {
+        for &(slot, item_stack) in [
+            (FurnaceSlot::Input, &furnace.input),
+            (FurnaceSlot::Fuel, &furnace.fuel),
+            (FurnaceSlot::Output, &furnace.output)].iter() {
+            if let Some(item_stack) = item_stack {
+                let (x, y) = FurnaceUI::slot_position(slot);
+                item_stack.item_render.draw(x, y, shader);
+            }
+        }
+    }
+}
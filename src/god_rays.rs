@@ -0,0 +1,147 @@
+use nalgebra_glm::{Mat4, Vec2, Vec3, vec2, vec4};
+
+use crate::types::Shaders;
+
+/// Texture unit the textures of the god rays are bound to while drawing the passes
+pub const GOD_RAYS_TEXTURE_UNIT: u32 = 7;
+
+/// Screen-space light shafts coming from the sun.
+/// The depth buffer of the scene is copied into a texture, then
+/// 1. the occlusion pass marches from every pixel to the sun and accumulates the light of the sky
+/// 2. the blur pass smooths the result along the lines going to the sun
+/// 3. the composite pass adds it to the scene.
+/// The first two passes are done at half the resolution of the screen.
+pub struct GodRayPass {
+    width: i32,
+    height: i32,
+    vao: u32,
+    depth_texture: u32,
+    occlusion_framebuffer: u32,
+    occlusion_texture: u32,
+    blur_framebuffer: u32,
+    blur_texture: u32,
+}
+
+impl GodRayPass {
+    pub fn new(width: i32, height: i32) -> Self {
+        // The fullscreen triangle is generated in the vertex shader
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        let mut depth_texture = 0;
+        gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut depth_texture));
+        gl_call!(gl::TextureParameteri(depth_texture, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32));
+        gl_call!(gl::TextureParameteri(depth_texture, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32));
+        gl_call!(gl::TextureParameteri(depth_texture, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32));
+        gl_call!(gl::TextureParameteri(depth_texture, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32));
+        gl_call!(gl::TextureStorage2D(depth_texture, 1, gl::DEPTH_COMPONENT24, width, height));
+
+        let (occlusion_framebuffer, occlusion_texture) = create_color_framebuffer(width / 2, height / 2);
+        let (blur_framebuffer, blur_texture) = create_color_framebuffer(width / 2, height / 2);
+
+        GodRayPass {
+            width,
+            height,
+            vao,
+            depth_texture,
+            occlusion_framebuffer,
+            occlusion_texture,
+            blur_framebuffer,
+            blur_texture,
+        }
+    }
+
+    /// Adds the god rays to the scene drawn in the default framebuffer.
+    /// `sun_direction` must be normalized and `intensity` is the weight of the rays in the final image.
+    pub fn render(&mut self, view_matrix: &Mat4, projection_matrix: &Mat4, sun_direction: &Vec3,
+                  sun_color: (f32, f32, f32), intensity: f32, shaders: &mut Shaders) {
+        let (sun_position, sun_depth) = match project_sun(view_matrix, projection_matrix, sun_direction) {
+            Some(sun) => sun,
+            None => return,
+        };
+
+        let mut viewport = [0; 4];
+        gl_call!(gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr()));
+
+        gl_call!(gl::BindFramebuffer(gl::FRAMEBUFFER, 0));
+        gl_call!(gl::CopyTextureSubImage2D(self.depth_texture, 0, 0, 0, 0, 0, self.width, self.height));
+
+        gl_call!(gl::Disable(gl::DEPTH_TEST));
+        gl_call!(gl::Disable(gl::BLEND));
+        gl_call!(gl::BindVertexArray(self.vao));
+        gl_call!(gl::Viewport(0, 0, self.width / 2, self.height / 2));
+
+        gl_call!(gl::BindFramebuffer(gl::FRAMEBUFFER, self.occlusion_framebuffer));
+        gl_call!(gl::BindTextureUnit(GOD_RAYS_TEXTURE_UNIT, self.depth_texture));
+        let occlusion_shader = shaders.get_mut("god_rays_occlusion_shader").unwrap();
+        occlusion_shader.use_program();
+        occlusion_shader.set_uniform1i("depth_map", GOD_RAYS_TEXTURE_UNIT as i32);
+        occlusion_shader.set_uniform2f("sun_position", &[sun_position.x, sun_position.y]);
+        occlusion_shader.set_uniform1f("sun_depth", sun_depth);
+        occlusion_shader.set_uniform1f("aspect_ratio", self.width as f32 / self.height as f32);
+        gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 3));
+
+        gl_call!(gl::BindFramebuffer(gl::FRAMEBUFFER, self.blur_framebuffer));
+        gl_call!(gl::BindTextureUnit(GOD_RAYS_TEXTURE_UNIT, self.occlusion_texture));
+        let blur_shader = shaders.get_mut("god_rays_blur_shader").unwrap();
+        blur_shader.use_program();
+        blur_shader.set_uniform1i("occlusion_map", GOD_RAYS_TEXTURE_UNIT as i32);
+        blur_shader.set_uniform2f("sun_position", &[sun_position.x, sun_position.y]);
+        gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 3));
+
+        gl_call!(gl::BindFramebuffer(gl::FRAMEBUFFER, 0));
+        gl_call!(gl::Viewport(viewport[0], viewport[1], viewport[2], viewport[3]));
+        gl_call!(gl::BindTextureUnit(GOD_RAYS_TEXTURE_UNIT, self.blur_texture));
+        gl_call!(gl::Enable(gl::BLEND));
+        gl_call!(gl::BlendFunc(gl::ONE, gl::ONE));
+        let composite_shader = shaders.get_mut("god_rays_composite_shader").unwrap();
+        composite_shader.use_program();
+        composite_shader.set_uniform1i("god_rays_map", GOD_RAYS_TEXTURE_UNIT as i32);
+        composite_shader.set_uniform3f("sun_color", &[sun_color.0, sun_color.1, sun_color.2]);
+        composite_shader.set_uniform1f("intensity", intensity);
+        gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 3));
+
+        gl_call!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
+        gl_call!(gl::Enable(gl::DEPTH_TEST));
+    }
+}
+
+/// Position of the sun on the screen in texture coordinates and its depth.
+/// The sun is placed just before the far plane, so that only the sky is behind it.
+/// Returns None when the sun is behind the camera.
+fn project_sun(view_matrix: &Mat4, projection_matrix: &Mat4, sun_direction: &Vec3) -> Option<(Vec2, f32)> {
+    // Only the rotation of the view matters for a point at an infinite distance
+    let view_direction = view_matrix * vec4(sun_direction.x, sun_direction.y, sun_direction.z, 0.0);
+    if view_direction.z >= 0.0 {
+        return None;
+    }
+
+    // Distance along the sun direction at which the depth of the far plane is almost reached
+    let far_distance = projection_matrix[(2, 3)] / (projection_matrix[(2, 2)] + 1.0);
+    let sun_view_position = view_direction * far_distance * 0.99 + vec4(0.0, 0.0, 0.0, 1.0);
+    let clip = projection_matrix * sun_view_position;
+    let ndc = clip / clip.w;
+    Some((vec2(ndc.x * 0.5 + 0.5, ndc.y * 0.5 + 0.5), ndc.z * 0.5 + 0.5))
+}
+
+/// Creates a framebuffer with a single color texture.
+/// Returns (framebuffer, color texture)
+fn create_color_framebuffer(width: i32, height: i32) -> (u32, u32) {
+    let mut texture = 0;
+    gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut texture));
+    gl_call!(gl::TextureParameteri(texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32));
+    gl_call!(gl::TextureParameteri(texture, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32));
+    gl_call!(gl::TextureParameteri(texture, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32));
+    gl_call!(gl::TextureParameteri(texture, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32));
+    gl_call!(gl::TextureStorage2D(texture, 1, gl::RGBA8, width, height));
+
+    let mut framebuffer = 0;
+    gl_call!(gl::CreateFramebuffers(1, &mut framebuffer));
+    gl_call!(gl::NamedFramebufferTexture(framebuffer, gl::COLOR_ATTACHMENT0, texture, 0));
+
+    let status = gl_call!(gl::CheckNamedFramebufferStatus(framebuffer, gl::FRAMEBUFFER));
+    if status != gl::FRAMEBUFFER_COMPLETE {
+        error!("God rays framebuffer is incomplete (status {:#x})", status);
+    }
+    (framebuffer, texture)
+}
@@ -0,0 +1,42 @@
+/// Measures how long the GPU takes to run the commands between `begin` and `end` with timer queries.
+/// The result of a frame is read when the next measure begins, so waiting for it doesn't stall the pipeline
+pub struct GpuTimer {
+    queries: [u32; 2],
+    is_issued: [bool; 2],
+    frame: usize,
+    /// Milliseconds taken by the last measure whose result came back
+    pub elapsed_ms: Option<f32>,
+}
+
+impl GpuTimer {
+    pub fn new() -> Self {
+        let mut queries = [0; 2];
+        gl_call!(gl::CreateQueries(gl::TIME_ELAPSED, 2, queries.as_mut_ptr()));
+        Self {
+            queries,
+            is_issued: [false; 2],
+            frame: 0,
+            elapsed_ms: None,
+        }
+    }
+
+    pub fn begin(&mut self) {
+        let previous = self.frame ^ 1;
+        if self.is_issued[previous] {
+            let mut available = 0;
+            gl_call!(gl::GetQueryObjectuiv(self.queries[previous], gl::QUERY_RESULT_AVAILABLE, &mut available));
+            if available != 0 {
+                let mut nanoseconds = 0;
+                gl_call!(gl::GetQueryObjectui64v(self.queries[previous], gl::QUERY_RESULT, &mut nanoseconds));
+                self.elapsed_ms = Some(nanoseconds as f32 / 1_000_000.0);
+            }
+        }
+        gl_call!(gl::BeginQuery(gl::TIME_ELAPSED, self.queries[self.frame]));
+    }
+
+    pub fn end(&mut self) {
+        gl_call!(gl::EndQuery(gl::TIME_ELAPSED));
+        self.is_issued[self.frame] = true;
+        self.frame ^= 1;
+    }
+}
@@ -4,10 +4,11 @@ use image::GenericImageView;
 use nalgebra::Matrix4;
 use nalgebra_glm::{Mat4, vec3};
 
-use crate::constants::{CROSSHAIR_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::constants::{CROSSHAIR_SIZE, GUI_SCALING, WINDOW_HEIGHT, WINDOW_WIDTH};
 use crate::shader_compilation::ShaderProgram;
 use crate::shapes::block_outline;
 use crate::shapes::quad;
+use crate::texture_pack::mip_levels;
 
 pub fn create_gui_icons_texture() -> u32 {
     let gui_icons_image = match image::open("textures/gui/icons.png") {
@@ -22,14 +23,15 @@ pub fn create_gui_icons_texture() -> u32 {
     // Upload the image to the GPU
     let mut gui_icons_texture = 0;
     gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut gui_icons_texture));
-    gl_call!(gl::TextureParameteri(gui_icons_texture, gl::TEXTURE_MIN_FILTER, gl::NEAREST_MIPMAP_NEAREST as i32));
+    gl_call!(gl::TextureParameteri(gui_icons_texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32));
     gl_call!(gl::TextureParameteri(gui_icons_texture, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32));
-    gl_call!(gl::TextureStorage2D(gui_icons_texture, 1, gl::RGBA8, gui_icons_image.width() as i32, gui_icons_image.height() as i32));
+    gl_call!(gl::TextureStorage2D(gui_icons_texture, mip_levels(gui_icons_image.width(), gui_icons_image.height()), gl::RGBA8, gui_icons_image.width() as i32, gui_icons_image.height() as i32));
     gl_call!(gl::TextureSubImage2D(
             gui_icons_texture, 0,
             0, 0, gui_icons_image.width() as i32, gui_icons_image.height() as i32,
             gl::RGBA, gl::UNSIGNED_BYTE,
             gui_icons_image.raw_pixels().as_ptr() as *mut c_void));
+    gl_call!(gl::GenerateTextureMipmap(gui_icons_texture));
     gui_icons_texture
 }
 
@@ -113,17 +115,43 @@ pub fn create_widgets_texture() -> u32 {
     // Upload the image to the GPU
     let mut widgets_texture = 0;
     gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut widgets_texture));
-    gl_call!(gl::TextureParameteri(widgets_texture, gl::TEXTURE_MIN_FILTER, gl::NEAREST_MIPMAP_NEAREST as i32));
+    gl_call!(gl::TextureParameteri(widgets_texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32));
     gl_call!(gl::TextureParameteri(widgets_texture, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32));
-    gl_call!(gl::TextureStorage2D(widgets_texture, 1, gl::RGBA8, widgets_image.width() as i32, widgets_image.height() as i32));
+    gl_call!(gl::TextureStorage2D(widgets_texture, mip_levels(widgets_image.width(), widgets_image.height()), gl::RGBA8, widgets_image.width() as i32, widgets_image.height() as i32));
     gl_call!(gl::TextureSubImage2D(
             widgets_texture, 0,
             0, 0, widgets_image.width() as i32, widgets_image.height() as i32,
             gl::RGBA, gl::UNSIGNED_BYTE,
             widgets_image.raw_pixels().as_ptr() as *mut c_void));
+    gl_call!(gl::GenerateTextureMipmap(widgets_texture));
     widgets_texture
 }
 
+pub fn create_furnace_texture() -> u32 {
+    let furnace_image = match image::open("textures/gui/furnace.png") {
+        Ok(img) => img,
+        Err(err) => panic!("Filename: {}, error: {}", "textures/gui/furnace.png", err.to_string())
+    };
+    match furnace_image.color() {
+        image::RGBA(8) => {}
+        _ => panic!("Texture format not supported")
+    };
+
+    // Upload the image to the GPU
+    let mut furnace_texture = 0;
+    gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut furnace_texture));
+    gl_call!(gl::TextureParameteri(furnace_texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32));
+    gl_call!(gl::TextureParameteri(furnace_texture, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32));
+    gl_call!(gl::TextureStorage2D(furnace_texture, mip_levels(furnace_image.width(), furnace_image.height()), gl::RGBA8, furnace_image.width() as i32, furnace_image.height() as i32));
+    gl_call!(gl::TextureSubImage2D(
+            furnace_texture, 0,
+            0, 0, furnace_image.width() as i32, furnace_image.height() as i32,
+            gl::RGBA, gl::UNSIGNED_BYTE,
+            furnace_image.raw_pixels().as_ptr() as *mut c_void));
+    gl_call!(gl::GenerateTextureMipmap(furnace_texture));
+    furnace_texture
+}
+
 pub fn create_hotbar_vao() -> u32 {
     let mut hotbar_vao = 0;
     gl_call!(gl::CreateVertexArrays(1, &mut hotbar_vao));
@@ -174,4 +202,105 @@ pub fn create_hotbar_selection_vao() -> u32 {
                     // quad((0.0, 0.0, 1.0, 1.0)).as_ptr() as *const c_void,
                     gl::STATIC_DRAW));
     hotbar_selection_vao
-}
\ No newline at end of file
+}
+
+/// Texture unit of the dial of the compass, with the colors of its needle below it
+pub const COMPASS_TEXTURE_UNIT: u32 = 9;
+/// The dial is 64x64 pixels, followed by 8 rows with the red and the gray of the needle
+const COMPASS_TEXTURE_HEIGHT: f32 = 72.0;
+/// Size of the compass in the bottom right corner of the screen, in GUI pixels
+const COMPASS_SIZE: f32 = 32.0;
+
+pub fn create_compass_texture() -> u32 {
+    let compass_image = match image::open("textures/gui/compass.png") {
+        Ok(img) => img,
+        Err(err) => panic!("Filename: {}, error: {}", "textures/gui/compass.png", err.to_string())
+    };
+    match compass_image.color() {
+        image::RGBA(8) => {}
+        _ => panic!("Texture format not supported")
+    };
+
+    // Upload the image to the GPU
+    let mut compass_texture = 0;
+    gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut compass_texture));
+    gl_call!(gl::TextureParameteri(compass_texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32));
+    gl_call!(gl::TextureParameteri(compass_texture, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32));
+    gl_call!(gl::TextureStorage2D(compass_texture, mip_levels(compass_image.width(), compass_image.height()), gl::RGBA8, compass_image.width() as i32, compass_image.height() as i32));
+    gl_call!(gl::TextureSubImage2D(
+            compass_texture, 0,
+            0, 0, compass_image.width() as i32, compass_image.height() as i32,
+            gl::RGBA, gl::UNSIGNED_BYTE,
+            compass_image.raw_pixels().as_ptr() as *mut c_void));
+    gl_call!(gl::GenerateTextureMipmap(compass_texture));
+    compass_texture
+}
+
+/// A VAO of the GUI shader holding the given vertices, 5 floats each
+pub fn create_gui_vao(vertices: &[f32]) -> u32 {
+    let mut vao = 0;
+    gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+    // Position
+    gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+    gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+    gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+    // Texture coords
+    gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+    gl_call!(gl::VertexArrayAttribFormat(vao, 1, 2 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+    gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+    let mut vbo = 0;
+    gl_call!(gl::CreateBuffers(1, &mut vbo));
+
+    gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (5 * std::mem::size_of::<f32>()) as i32));
+    gl_call!(gl::NamedBufferData(vbo,
+                    (vertices.len() * std::mem::size_of::<f32>()) as isize,
+                    vertices.as_ptr() as *const c_void,
+                    gl::STATIC_DRAW));
+    vao
+}
+
+pub fn create_compass_dial_vao() -> u32 {
+    create_gui_vao(&quad((0.0, 0.0, 1.0, 64.0 / COMPASS_TEXTURE_HEIGHT)))
+}
+
+/// The needle of the compass, a thin red triangle pointing up and a gray one pointing down
+pub fn create_compass_vao() -> u32 {
+    let red = (4.0 / 64.0, 68.0 / COMPASS_TEXTURE_HEIGHT);
+    let gray = (12.0 / 64.0, 68.0 / COMPASS_TEXTURE_HEIGHT);
+    create_gui_vao(&[
+        -0.07, 0.0, 0.0, red.0, red.1,
+        0.07, 0.0, 0.0, red.0, red.1,
+        0.0, 0.4, 0.0, red.0, red.1,
+        0.07, 0.0, 0.0, gray.0, gray.1,
+        -0.07, 0.0, 0.0, gray.0, gray.1,
+        0.0, -0.4, 0.0, gray.0, gray.1,
+    ])
+}
+
+/// Draws the compass in the bottom right corner, its needle turned clockwise by `angle` radians from the top
+pub fn draw_compass(dial_vao: u32, needle_vao: u32, angle: f32, shader: &mut ShaderProgram) {
+    let size = COMPASS_SIZE * GUI_SCALING;
+    let translate_matrix = Matrix4::new_translation(&vec3(
+        WINDOW_WIDTH as f32 - size / 2.0 - 8.0 * GUI_SCALING, size / 2.0 + 8.0 * GUI_SCALING, 0.0));
+    let scale_matrix: Mat4 = Matrix4::new_nonuniform_scaling(&vec3(size, size, 1.0));
+    let projection_matrix = nalgebra_glm::ortho(
+        0.0, WINDOW_WIDTH as f32, 0.0, WINDOW_HEIGHT as f32, -5.0, 5.0);
+
+    shader.use_program();
+    shader.set_uniform_matrix4fv("projection", projection_matrix.as_ptr());
+    shader.set_uniform1i("tex", COMPASS_TEXTURE_UNIT as i32);
+
+    let model_matrix = translate_matrix * scale_matrix;
+    shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+    gl_call!(gl::BindVertexArray(dial_vao));
+    gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 6));
+
+    let rotate_matrix = Matrix4::from_euler_angles(0.0, 0.0, -angle);
+    let model_matrix = translate_matrix * rotate_matrix * scale_matrix;
+    shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+    gl_call!(gl::BindVertexArray(needle_vao));
+    gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 6));
+}
@@ -1,8 +1,14 @@
 use std::collections::HashMap;
+use std::time::Instant;
 
 use glfw::{Action, Key, WindowEvent, MouseButton};
 use nalgebra_glm::{DVec2, vec2};
 
+use crate::constants::SPRINTING_TRIGGER_INTERVAL;
+
+/// W was pressed twice in a row, quickly enough for the player to dash
+pub struct DashEvent;
+
 pub struct InputCache {
     pub events: Vec<WindowEvent>,
     pub last_cursor_pos: DVec2,
@@ -10,6 +16,12 @@ pub struct InputCache {
 
     pub key_states: HashMap<Key, Action>,
     pub mouse_button_states: HashMap<MouseButton, Action>,
+
+    /// Double tap detection of W
+    pub w_last_pressed: Instant,
+    pub w_press_count: u8,
+    /// Dashes triggered during the last frame
+    pub dash_events: Vec<DashEvent>,
 }
 
 impl Default for InputCache {
@@ -20,6 +32,9 @@ impl Default for InputCache {
             cursor_rel_pos: vec2(0.0, 0.0),
             key_states: HashMap::default(),
             mouse_button_states: HashMap::default(),
+            w_last_pressed: Instant::now(),
+            w_press_count: 0,
+            dash_events: vec![],
         }
     }
 }
@@ -38,6 +53,21 @@ impl InputCache {
 
             &glfw::WindowEvent::Key(key, _, action, _) => {
                 self.key_states.insert(key, action);
+
+                if key == Key::W && action == Action::Press {
+                    let now = Instant::now();
+                    if now.duration_since(self.w_last_pressed) < *SPRINTING_TRIGGER_INTERVAL {
+                        self.w_press_count += 1;
+                    } else {
+                        self.w_press_count = 1;
+                    }
+                    self.w_last_pressed = now;
+
+                    if self.w_press_count == 2 {
+                        self.dash_events.push(DashEvent);
+                        self.w_press_count = 0;
+                    }
+                }
             }
 
             &glfw::WindowEvent::MouseButton(button, action, _) => {
@@ -0,0 +1,135 @@
+use crate::chunk::BlockID;
+use crate::enchanting::EnchantmentId;
+use crate::inventory::item::ItemStack;
+
+/// Damage is reduced by 1/25 for each defense point
+pub const DEFENSE_POINTS_PER_DAMAGE_REDUCTION: f32 = 25.0;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ArmorSlot {
+    Helmet,
+    Chestplate,
+    Leggings,
+    Boots,
+}
+
+/// From the head to the feet
+pub const ARMOR_SLOTS: [ArmorSlot; 4] = [ArmorSlot::Helmet, ArmorSlot::Chestplate, ArmorSlot::Leggings, ArmorSlot::Boots];
+
+#[derive(Debug, Copy, Clone)]
+pub struct ArmorProperties {
+    pub slot: ArmorSlot,
+    pub defense_points: u8,
+    pub max_durability: u32,
+}
+
+/// The properties of the item if it is an armor piece.
+/// The durability is the base durability of the slot times the multiplier of the material.
+pub fn armor_properties(item: BlockID) -> Option<ArmorProperties> {
+    let (slot, defense_points, durability_multiplier) = match item {
+        BlockID::LeatherHelmet => (ArmorSlot::Helmet, 1, 5),
+        BlockID::LeatherChestplate => (ArmorSlot::Chestplate, 3, 5),
+        BlockID::LeatherLeggings => (ArmorSlot::Leggings, 2, 5),
+        BlockID::LeatherBoots => (ArmorSlot::Boots, 1, 5),
+        BlockID::GoldenHelmet => (ArmorSlot::Helmet, 2, 7),
+        BlockID::GoldenChestplate => (ArmorSlot::Chestplate, 5, 7),
+        BlockID::GoldenLeggings => (ArmorSlot::Leggings, 3, 7),
+        BlockID::GoldenBoots => (ArmorSlot::Boots, 1, 7),
+        BlockID::IronHelmet => (ArmorSlot::Helmet, 2, 15),
+        BlockID::IronChestplate => (ArmorSlot::Chestplate, 6, 15),
+        BlockID::IronLeggings => (ArmorSlot::Leggings, 5, 15),
+        BlockID::IronBoots => (ArmorSlot::Boots, 2, 15),
+        BlockID::DiamondHelmet => (ArmorSlot::Helmet, 3, 33),
+        BlockID::DiamondChestplate => (ArmorSlot::Chestplate, 8, 33),
+        BlockID::DiamondLeggings => (ArmorSlot::Leggings, 6, 33),
+        BlockID::DiamondBoots => (ArmorSlot::Boots, 3, 33),
+        _ => return None,
+    };
+    let base_durability = match slot {
+        ArmorSlot::Helmet => 11,
+        ArmorSlot::Chestplate => 16,
+        ArmorSlot::Leggings => 15,
+        ArmorSlot::Boots => 13,
+    };
+    Some(ArmorProperties {
+        slot,
+        defense_points,
+        max_durability: base_durability * durability_multiplier,
+    })
+}
+
+/// The armor pieces worn by the player
+#[derive(Default)]
+pub struct ArmorInventory {
+    pub helmet: Option<ItemStack>,
+    pub chestplate: Option<ItemStack>,
+    pub leggings: Option<ItemStack>,
+    pub boots: Option<ItemStack>,
+}
+
+impl ArmorInventory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, slot: ArmorSlot) -> &Option<ItemStack> {
+        match slot {
+            ArmorSlot::Helmet => &self.helmet,
+            ArmorSlot::Chestplate => &self.chestplate,
+            ArmorSlot::Leggings => &self.leggings,
+            ArmorSlot::Boots => &self.boots,
+        }
+    }
+
+    pub fn get_mut(&mut self, slot: ArmorSlot) -> &mut Option<ItemStack> {
+        match slot {
+            ArmorSlot::Helmet => &mut self.helmet,
+            ArmorSlot::Chestplate => &mut self.chestplate,
+            ArmorSlot::Leggings => &mut self.leggings,
+            ArmorSlot::Boots => &mut self.boots,
+        }
+    }
+
+    /// Puts the armor piece in its slot and returns the piece that was worn there.
+    /// Gives back the item stack if it isn't an armor piece.
+    pub fn equip(&mut self, item_stack: ItemStack) -> Option<ItemStack> {
+        match armor_properties(item_stack.item) {
+            Some(properties) => self.get_mut(properties.slot).replace(item_stack),
+            None => Some(item_stack),
+        }
+    }
+
+    pub fn total_defense(&self) -> u8 {
+        ARMOR_SLOTS.iter()
+            .filter_map(|&slot| self.get(slot).as_ref())
+            .filter_map(|item_stack| armor_properties(item_stack.item))
+            .map(|properties| properties.defense_points)
+            .sum()
+    }
+
+    /// Returns the damage left after the armor absorbed its share.
+    /// Every worn piece loses 1 durability point and breaks when it reaches 0.
+    pub fn absorb_damage(&mut self, damage: f32) -> f32 {
+        let reduction = (self.total_defense() as f32 / DEFENSE_POINTS_PER_DAMAGE_REDUCTION).min(1.0);
+
+        for &slot in ARMOR_SLOTS.iter() {
+            let piece = self.get_mut(slot);
+            if let Some(item_stack) = piece {
+                if let Some(durability) = &mut item_stack.durability {
+                    *durability = durability.saturating_sub(1);
+                    if *durability == 0 {
+                        info!("{:?} broke", item_stack.item);
+                        *piece = None;
+                    }
+                }
+            }
+        }
+
+        damage * (1.0 - reduction)
+    }
+
+    /// Level of the enchantment on the piece worn in `slot`, 0 without it
+    pub fn enchantment_level(&self, slot: ArmorSlot, enchantment: EnchantmentId) -> u8 {
+        self.get(slot).as_ref().map_or(0, |item_stack| item_stack.enchantment_level(enchantment))
+    }
+}
@@ -6,14 +6,34 @@ use nalgebra_glm::{Mat4, pi, vec3};
 
 use crate::chunk::BlockID;
 use crate::constants::{GUI_SCALING, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::enchanting::EnchantmentId;
+use crate::inventory::armor::armor_properties;
+use crate::inventory::tool::tool_properties;
 use crate::shader_compilation::ShaderProgram;
 use crate::shapes::centered_unit_cube;
+use crate::text::TextRenderer;
 use crate::types::TexturePack;
 
-#[derive(Copy, Clone)]
+/// Uses of a new flint and steel
+const FLINT_AND_STEEL_DURABILITY: u32 = 64;
+
+/// Number of uses of a new item, for items that wear out (armor, tools and flint and steel)
+pub fn max_durability(item: BlockID) -> Option<u32> {
+    armor_properties(item).map(|armor| armor.max_durability)
+        .or_else(|| tool_properties(item).map(|tool| tool.max_durability))
+        .or_else(|| Some(FLINT_AND_STEEL_DURABILITY).filter(|_| item == BlockID::FlintAndSteel))
+}
+
+#[derive(Clone)]
 pub struct ItemStack {
     pub item: BlockID,
     pub amount: u32,
+    /// Uses left before the item breaks, for items that wear out
+    pub durability: Option<u32>,
+    /// Extra state of the item, e.g. how far a crossbow is charged
+    pub meta: u8,
+    /// Enchantments put on the item at an enchanting table, with their level
+    pub enchantments: Vec<(EnchantmentId, u8)>,
     pub(crate) item_render: ItemRender,
 }
 
@@ -22,13 +42,57 @@ impl ItemStack {
         ItemStack {
             item: block,
             amount,
+            durability: max_durability(block),
+            meta: 0,
+            enchantments: Vec::new(),
             item_render: ItemRender::new()
         }
     }
 
+    /// A stack of one of these items, with the same wear, state and enchantments
+    pub fn split_one(&self) -> Self {
+        ItemStack {
+            durability: self.durability,
+            meta: self.meta,
+            enchantments: self.enchantments.clone(),
+            ..ItemStack::new(1, self.item)
+        }
+    }
+
+    /// Whether `other` can be put on top of this stack
+    pub fn stacks_with(&self, other: &ItemStack) -> bool {
+        self.item == other.item && self.meta == other.meta
+            // Items that wear out or are enchanted don't stack
+            && self.durability.is_none() && other.durability.is_none()
+            && self.enchantments.is_empty() && other.enchantments.is_empty()
+    }
+
+    /// Level of the enchantment on the item, 0 without it
+    pub fn enchantment_level(&self, enchantment: EnchantmentId) -> u8 {
+        self.enchantments.iter()
+            .find(|&&(id, _)| id == enchantment)
+            .map_or(0, |&(_, level)| level)
+    }
+
     pub fn update_if_dirty(&mut self, texture_pack: &TexturePack) {
         self.item_render.update_vbo_if_dirty(self.item, &texture_pack);
     }
+
+    /// Draws a bar under the icon centered at (x, y) once the item started wearing out.
+    /// It goes from green to red as the durability decreases.
+    pub fn draw_durability_bar(&self, x: f32, y: f32, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+        let (durability, max_durability) = match (self.durability, max_durability(self.item)) {
+            (Some(durability), Some(max_durability)) if durability < max_durability => (durability, max_durability),
+            _ => return,
+        };
+        let fraction = durability as f32 / max_durability as f32;
+
+        let (width, height) = (13.0 * GUI_SCALING, 2.0 * GUI_SCALING);
+        let (left, bottom) = (x - width / 2.0, y - 7.0 * GUI_SCALING);
+        text_renderer.draw_rect(left, bottom, width, height, (0.0, 0.0, 0.0, 1.0), shader);
+        text_renderer.draw_rect(left, bottom + height / 2.0, (width * fraction).max(GUI_SCALING), height / 2.0,
+                                (1.0 - fraction, fraction, 0.0, 1.0), shader);
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -99,6 +163,11 @@ impl ItemRender {
                     vbo_data.as_ptr() as *mut c_void));
     }
 
+    /// Draws the item as a cube in the world, `model_matrix` places it and `view_projection` looks at it
+    pub fn draw_in_world(&self, model_matrix: &Mat4, view_projection: &Mat4, shader: &mut ShaderProgram) {
+        self.draw_cube(model_matrix, view_projection, shader);
+    }
+
     pub fn draw(&self, x: f32, y: f32, shader: &mut ShaderProgram) {
         let model_matrix = {
             let translate_matrix = Matrix4::new_translation(&vec3(
@@ -111,10 +180,13 @@ impl ItemRender {
             let scale_matrix: Mat4 = Matrix4::new_nonuniform_scaling(&(GUI_SCALING * vec3(10.0, 10.0, 10.0)));
             translate_matrix * rotate_matrix * scale_matrix
         };
+        self.draw_cube(&model_matrix, &self.projection_matrix, shader);
+    }
 
+    fn draw_cube(&self, model_matrix: &Mat4, projection_matrix: &Mat4, shader: &mut ShaderProgram) {
         shader.use_program();
         shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
-        shader.set_uniform_matrix4fv("projection", self.projection_matrix.as_ptr());
+        shader.set_uniform_matrix4fv("projection", projection_matrix.as_ptr());
         shader.set_uniform1i("tex", 0);
 
         gl_call!(gl::BindVertexArray(self.vao));
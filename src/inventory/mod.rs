@@ -1,20 +1,33 @@
+use std::collections::HashSet;
+
 use nalgebra::Matrix4;
 use nalgebra_glm::{Mat4, vec3};
 
 use crate::chunk::BlockID;
-use crate::constants::{GUI_SCALING, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::constants::{GUI_SCALING, MAX_STACK_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::enchanting::{consumes_durability, EnchantmentId};
+use crate::inventory::armor::{ARMOR_SLOTS, ArmorInventory, ArmorSlot};
 use crate::inventory::item::ItemStack;
 use crate::shader_compilation::ShaderProgram;
+use crate::text::TextRenderer;
 use crate::types::TexturePack;
 
+pub mod armor;
 pub mod item;
+pub mod tool;
 
-const INVENTORY_SIZE: usize = 36;
-const HOTBAR_SIZE: usize = 9;
+pub const INVENTORY_SIZE: usize = 36;
+pub const HOTBAR_SIZE: usize = 9;
+/// The main storage (every slot after the hotbar) is shown as rows of this many slots
+pub const STORAGE_COLUMNS: usize = 9;
+pub const STORAGE_ROWS: usize = (INVENTORY_SIZE - HOTBAR_SIZE) / STORAGE_COLUMNS;
 
 pub struct Inventory {
     pub slots: [Option<ItemStack>; INVENTORY_SIZE],
     pub selected_hotbar_slot: usize,
+    pub armor: ArmorInventory,
+    /// Every item picked up from the ground, for the achievements
+    pub picked_up: HashSet<BlockID>,
 }
 
 impl Default for Inventory {
@@ -27,7 +40,7 @@ impl Inventory {
     pub fn new() -> Inventory {
         Inventory {
             slots: {
-                let mut slots = [None; INVENTORY_SIZE];
+                let mut slots: [Option<ItemStack>; INVENTORY_SIZE] = std::array::from_fn(|_| None);
                 slots[0] = Some(ItemStack::new(1, BlockID::Dirt));
                 slots[1] = Some(ItemStack::new(1, BlockID::GrassBlock));
                 slots[2] = Some(ItemStack::new(1, BlockID::Cobblestone));
@@ -40,11 +53,17 @@ impl Inventory {
                 slots
             },
             selected_hotbar_slot: 0,
+            armor: ArmorInventory::new(),
+            picked_up: HashSet::new(),
         }
     }
 
     pub fn get_selected_item(&self) -> Option<BlockID> {
-        self.slots[self.selected_hotbar_slot].map(|item_stack| item_stack.item)
+        self.slots[self.selected_hotbar_slot].as_ref().map(|item_stack| item_stack.item)
+    }
+
+    pub fn get_selected_item_stack(&self) -> Option<&ItemStack> {
+        self.slots[self.selected_hotbar_slot].as_ref()
     }
 
     pub fn select_item(&mut self, index: usize) {
@@ -66,12 +85,204 @@ impl Inventory {
         }
     }
 
+    /// Puts the item stack in the first empty slot, starting with the hotbar.
+    /// Gives back the item stack if the inventory is full.
+    pub fn add_item_stack(&mut self, item_stack: ItemStack) -> Option<ItemStack> {
+        self.add_item_stack_in(item_stack, 0..INVENTORY_SIZE)
+    }
+
+    /// Adds a single item on top of a stack of the same item that isn't full, or in the first empty slot.
+    /// Items that wear out don't stack. Returns whether there was room for it.
+    pub fn add_item(&mut self, item: BlockID) -> bool {
+        let stack = self.slots.iter_mut().flatten()
+            .find(|item_stack| item_stack.item == item && item_stack.durability.is_none() && item_stack.amount < MAX_STACK_SIZE);
+        match stack {
+            Some(item_stack) => {
+                item_stack.amount += 1;
+                true
+            }
+            None => self.add_item_stack(ItemStack::new(1, item)).is_none(),
+        }
+    }
+
+    /// Tops up the stacks the picked up items stack with, up to MAX_STACK_SIZE, then takes the first empty slot.
+    /// Gives back what doesn't fit
+    pub fn pick_up(&mut self, mut item_stack: ItemStack) -> Option<ItemStack> {
+        let (item, amount) = (item_stack.item, item_stack.amount);
+        for other in self.slots.iter_mut().flatten() {
+            if !other.stacks_with(&item_stack) || item_stack.amount == 0 {
+                continue;
+            }
+            let moved = MAX_STACK_SIZE.saturating_sub(other.amount).min(item_stack.amount);
+            other.amount += moved;
+            item_stack.amount -= moved;
+        }
+        let left = if item_stack.amount == 0 { None } else { self.add_item_stack(item_stack) };
+        if left.as_ref().map_or(true, |left| left.amount < amount) {
+            self.picked_up.insert(item);
+        }
+        left
+    }
+
+    /// Puts the item stack in the first empty slot of the given range.
+    /// Gives back the item stack if there is no room for it.
+    pub fn add_item_stack_in(&mut self, item_stack: ItemStack, range: std::ops::Range<usize>) -> Option<ItemStack> {
+        match self.slots[range].iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some(item_stack);
+                None
+            }
+            None => Some(item_stack)
+        }
+    }
+
+    /// Moves the stack of `slot` from the hotbar to the main storage, or from the main storage to the hotbar.
+    /// It first tops up the stacks of the same item there, up to MAX_STACK_SIZE, then takes the first empty slot.
+    /// What doesn't fit stays in `slot`
+    pub fn shift_click(&mut self, slot: usize) {
+        let mut item_stack = match self.slots[slot].take() {
+            Some(item_stack) => item_stack,
+            None => return,
+        };
+        let destination = if slot < HOTBAR_SIZE {
+            HOTBAR_SIZE..INVENTORY_SIZE
+        } else {
+            0..HOTBAR_SIZE
+        };
+
+        // Items that wear out don't stack
+        if item_stack.durability.is_none() {
+            for other in self.slots[destination.clone()].iter_mut().flatten() {
+                if other.item != item_stack.item || other.durability.is_some() || other.meta != item_stack.meta {
+                    continue;
+                }
+                let moved = MAX_STACK_SIZE.saturating_sub(other.amount).min(item_stack.amount);
+                other.amount += moved;
+                item_stack.amount -= moved;
+                if item_stack.amount == 0 {
+                    return;
+                }
+            }
+        }
+        self.slots[slot] = self.add_item_stack_in(item_stack, destination);
+    }
+
+    /// Removes one item from the selected stack
+    pub fn consume_selected_item(&mut self) {
+        let slot = &mut self.slots[self.selected_hotbar_slot];
+        if let Some(item_stack) = slot {
+            if item_stack.amount > 1 {
+                item_stack.amount -= 1;
+            } else {
+                *slot = None;
+            }
+        }
+    }
+
+    /// Removes one item of the first stack of `item`, starting with the hotbar.
+    /// Returns whether there was any
+    pub fn consume_item(&mut self, item: BlockID) -> bool {
+        let slot = match self.slots.iter_mut().find(|slot| slot.as_ref().map_or(false, |item_stack| item_stack.item == item)) {
+            Some(slot) => slot,
+            None => return false,
+        };
+        if let Some(item_stack) = slot {
+            if item_stack.amount > 1 {
+                item_stack.amount -= 1;
+            } else {
+                *slot = None;
+            }
+        }
+        true
+    }
+
+    /// Wears out the selected item by one use, it is removed when its durability reaches 0.
+    /// Unbreaking spares some of the uses. Returns whether the item broke.
+    pub fn damage_selected_item(&mut self) -> bool {
+        let slot = &mut self.slots[self.selected_hotbar_slot];
+        let unbreaking = slot.as_ref().map_or(0, |item_stack| item_stack.enchantment_level(EnchantmentId::Unbreaking));
+        if let Some(ItemStack { item, durability: Some(durability), .. }) = slot {
+            if !consumes_durability(unbreaking, &mut rand::thread_rng()) {
+                return false;
+            }
+            *durability = durability.saturating_sub(1);
+            if *durability == 0 {
+                info!("{:?} broke", item);
+                *slot = None;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns the hotbar slot under the given point in screen coordinates (y pointing up)
+    pub fn hotbar_slot_at(x: f32, y: f32) -> Option<usize> {
+        let interslot_spacing = 20.0;
+        let hotbar_left_margin = WINDOW_WIDTH as f32 / 2.0 - 4.0 * interslot_spacing * GUI_SCALING;
+        let half_slot_size = 10.0 * GUI_SCALING;
+
+        if (y - 11.0 * GUI_SCALING).abs() > half_slot_size {
+            return None;
+        }
+        (0..HOTBAR_SIZE).find(|&i| {
+            let slot_x = hotbar_left_margin + i as f32 * interslot_spacing * GUI_SCALING;
+            (x - slot_x).abs() <= half_slot_size
+        })
+    }
+
+    /// Center of a main storage slot in screen coordinates.
+    /// The rows are stacked above the hotbar like the ones of a chest, the first row being the highest one.
+    pub fn storage_slot_position(index: usize) -> (f32, f32) {
+        let interslot_spacing = 20.0;
+        let left_margin = WINDOW_WIDTH as f32 / 2.0 - 4.0 * interslot_spacing * GUI_SCALING;
+        let row = (index - HOTBAR_SIZE) / STORAGE_COLUMNS;
+        let column = (index - HOTBAR_SIZE) % STORAGE_COLUMNS;
+        (
+            left_margin + column as f32 * interslot_spacing * GUI_SCALING,
+            (45.0 + 22.0 * (STORAGE_ROWS - 1 - row) as f32) * GUI_SCALING,
+        )
+    }
+
+    /// Returns the main storage slot under the given point in screen coordinates (y pointing up)
+    pub fn storage_slot_at(x: f32, y: f32) -> Option<usize> {
+        let half_slot_size = 10.0 * GUI_SCALING;
+        (HOTBAR_SIZE..INVENTORY_SIZE).find(|&i| {
+            let (slot_x, slot_y) = Inventory::storage_slot_position(i);
+            (x - slot_x).abs() <= half_slot_size && (y - slot_y).abs() <= half_slot_size
+        })
+    }
+
+    /// Center of an armor slot in screen coordinates.
+    /// The armor slots are in a column on the left of the main storage, the helmet at the top.
+    pub fn armor_slot_position(slot: ArmorSlot) -> (f32, f32) {
+        let (storage_left, _) = Inventory::storage_slot_position(HOTBAR_SIZE);
+        let index = ARMOR_SLOTS.iter().position(|&s| s == slot).unwrap();
+        (
+            storage_left - 30.0 * GUI_SCALING,
+            (45.0 + 22.0 * (ARMOR_SLOTS.len() - 1 - index) as f32) * GUI_SCALING,
+        )
+    }
+
+    /// Returns the armor slot under the given point in screen coordinates (y pointing up)
+    pub fn armor_slot_at(x: f32, y: f32) -> Option<ArmorSlot> {
+        let half_slot_size = 10.0 * GUI_SCALING;
+        ARMOR_SLOTS.iter().cloned().find(|&slot| {
+            let (slot_x, slot_y) = Inventory::armor_slot_position(slot);
+            (x - slot_x).abs() <= half_slot_size && (y - slot_y).abs() <= half_slot_size
+        })
+    }
+
     pub fn update_dirty_items(&mut self, texture_pack: &TexturePack) {
         for item_stack in self.slots.iter_mut() {
             if let Some(item_stack) = item_stack {
                 item_stack.update_if_dirty(&texture_pack);
             }
         }
+        for &slot in ARMOR_SLOTS.iter() {
+            if let Some(item_stack) = self.armor.get_mut(slot) {
+                item_stack.update_if_dirty(&texture_pack);
+            }
+        }
     }
 
     pub fn draw_hotbar(&self, vao: u32, shader: &mut ShaderProgram) {
@@ -115,13 +326,25 @@ impl Inventory {
         gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 6));
     }
 
+    pub fn draw_hotbar_durability_bars(&self, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+        let interslot_spacing = 20.0;
+        let hotbar_left_margin = WINDOW_WIDTH as f32 / 2.0 - 4.0 * interslot_spacing * GUI_SCALING;
+
+        for (i, slot) in self.slots.iter().take(HOTBAR_SIZE).enumerate() {
+            if let Some(slot) = slot {
+                let item_x_pos = hotbar_left_margin + i as f32 * interslot_spacing * GUI_SCALING;
+                slot.draw_durability_bar(item_x_pos, 11.0 * GUI_SCALING, text_renderer, shader);
+            }
+        }
+    }
+
     pub fn draw_hotbar_items(&self, shader: &mut ShaderProgram) {
         let interslot_spacing = 20.0;
         let hotbar_left_margin = WINDOW_WIDTH as f32 / 2.0 - 4.0 * interslot_spacing * GUI_SCALING;
 
         let mut x = 0;
         let y = 11;
-        for slot in self.slots.iter() {
+        for slot in self.slots.iter().take(HOTBAR_SIZE) {
             if let Some(slot) = slot {
                 let item_x_pos = hotbar_left_margin + (x as f32) * interslot_spacing * GUI_SCALING;
                 slot.item_render.draw(item_x_pos, (y as f32) * GUI_SCALING, shader);
@@ -129,4 +352,67 @@ impl Inventory {
             x += 1;
         }
     }
-}
\ No newline at end of file
+
+    /// The background of every row of the main storage reuses the hotbar texture
+    pub fn draw_storage(&self, hotbar_vao: u32, shader: &mut ShaderProgram) {
+        let projection_matrix = nalgebra_glm::ortho(
+            0.0, WINDOW_WIDTH as f32, 0.0, WINDOW_HEIGHT as f32, -5.0, 5.0);
+
+        shader.use_program();
+        shader.set_uniform_matrix4fv("projection", projection_matrix.as_ptr());
+        shader.set_uniform1i("tex", 2);
+        gl_call!(gl::BindVertexArray(hotbar_vao));
+
+        for row in 0..STORAGE_ROWS {
+            let (_, row_y) = Inventory::storage_slot_position(HOTBAR_SIZE + row * STORAGE_COLUMNS);
+            let model_matrix = {
+                let translate_matrix = Matrix4::new_translation(&vec3(
+                    WINDOW_WIDTH as f32 / 2.0, row_y, 0.0));
+                let scale_matrix: Mat4 = Matrix4::new_nonuniform_scaling(&vec3(182.0 * GUI_SCALING, 22.0 * GUI_SCALING, 1.0));
+                translate_matrix * scale_matrix
+            };
+            shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+            gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 6));
+        }
+    }
+
+    /// The armor slots are plain squares
+    pub fn draw_armor_slots(&self, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+        for &slot in ARMOR_SLOTS.iter() {
+            let (x, y) = Inventory::armor_slot_position(slot);
+            let (outer, inner) = (11.0 * GUI_SCALING, 9.0 * GUI_SCALING);
+            text_renderer.draw_rect(x - outer, y - outer, 2.0 * outer, 2.0 * outer, (0.2, 0.2, 0.2, 0.9), shader);
+            text_renderer.draw_rect(x - inner, y - inner, 2.0 * inner, 2.0 * inner, (0.55, 0.55, 0.55, 0.9), shader);
+        }
+    }
+
+    pub fn draw_storage_durability_bars(&self, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+        for (i, slot) in self.slots.iter().enumerate().skip(HOTBAR_SIZE) {
+            if let Some(slot) = slot {
+                let (x, y) = Inventory::storage_slot_position(i);
+                slot.draw_durability_bar(x, y, text_renderer, shader);
+            }
+        }
+        for &slot in ARMOR_SLOTS.iter() {
+            if let Some(item_stack) = self.armor.get(slot) {
+                let (x, y) = Inventory::armor_slot_position(slot);
+                item_stack.draw_durability_bar(x, y, text_renderer, shader);
+            }
+        }
+    }
+
+    pub fn draw_storage_items(&self, shader: &mut ShaderProgram) {
+        for (i, slot) in self.slots.iter().enumerate().skip(HOTBAR_SIZE) {
+            if let Some(slot) = slot {
+                let (x, y) = Inventory::storage_slot_position(i);
+                slot.item_render.draw(x, y, shader);
+            }
+        }
+        for &slot in ARMOR_SLOTS.iter() {
+            if let Some(item_stack) = self.armor.get(slot) {
+                let (x, y) = Inventory::armor_slot_position(slot);
+                item_stack.item_render.draw(x, y, shader);
+            }
+        }
+    }
+}
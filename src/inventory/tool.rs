@@ -0,0 +1,151 @@
+use crate::block_properties::BLOCK_PROPERTIES;
+use crate::chunk::BlockID;
+use crate::constants::{AXE_DAMAGE, HAND_DAMAGE, SWORD_DAMAGE, UNHARVESTABLE_BREAK_TIME_MULTIPLIER};
+use crate::enchanting::{efficiency_multiplier, EnchantmentId};
+use crate::inventory::item::ItemStack;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ToolKind {
+    Pickaxe,
+    Axe,
+    Shovel,
+    /// Only meant for fighting, no block is mined faster with it
+    Sword,
+}
+
+/// What a tool is made of, each material harvesting the blocks of the previous ones
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum ToolMaterial {
+    Wood,
+    Stone,
+    Iron,
+    Diamond,
+}
+
+impl ToolMaterial {
+    /// Efficiency and durability of the tools made of this material
+    fn stats(self) -> (f32, u32) {
+        match self {
+            ToolMaterial::Wood => WOODEN,
+            ToolMaterial::Stone => STONE,
+            ToolMaterial::Iron => IRON,
+            ToolMaterial::Diamond => DIAMOND,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ToolProperties {
+    pub kind: ToolKind,
+    pub material: ToolMaterial,
+    /// Mining speed multiplier against the blocks this kind of tool is meant for
+    pub efficiency: f32,
+    pub max_durability: u32,
+}
+
+impl ToolProperties {
+    pub fn is_correct_tool_for(&self, block: BlockID) -> bool {
+        BLOCK_PROPERTIES[&block].best_tool == Some(self.kind)
+    }
+
+    /// Whether the block drops something when broken with this tool, the blocks requiring a material need the right
+    /// kind of tool made of it or of a better one
+    pub fn can_harvest(&self, block: BlockID) -> bool {
+        match BLOCK_PROPERTIES[&block].harvest_requires {
+            Some(material) => self.is_correct_tool_for(block) && self.material >= material,
+            None => true,
+        }
+    }
+
+    /// A tool used on the wrong block is no better than a bare hand
+    pub fn efficiency_for(&self, block: BlockID) -> f32 {
+        if self.is_correct_tool_for(block) {
+            self.efficiency
+        } else {
+            1.0
+        }
+    }
+}
+
+/// The properties of the item if it is a tool, the material gives the efficiency and the durability
+pub fn tool_properties(item: BlockID) -> Option<ToolProperties> {
+    let (kind, material) = match item {
+        BlockID::WoodenPickaxe => (ToolKind::Pickaxe, ToolMaterial::Wood),
+        BlockID::StonePickaxe => (ToolKind::Pickaxe, ToolMaterial::Stone),
+        BlockID::IronPickaxe => (ToolKind::Pickaxe, ToolMaterial::Iron),
+        BlockID::DiamondPickaxe => (ToolKind::Pickaxe, ToolMaterial::Diamond),
+        BlockID::WoodenAxe => (ToolKind::Axe, ToolMaterial::Wood),
+        BlockID::StoneAxe => (ToolKind::Axe, ToolMaterial::Stone),
+        BlockID::IronAxe => (ToolKind::Axe, ToolMaterial::Iron),
+        BlockID::DiamondAxe => (ToolKind::Axe, ToolMaterial::Diamond),
+        BlockID::WoodenShovel => (ToolKind::Shovel, ToolMaterial::Wood),
+        BlockID::StoneShovel => (ToolKind::Shovel, ToolMaterial::Stone),
+        BlockID::IronShovel => (ToolKind::Shovel, ToolMaterial::Iron),
+        BlockID::DiamondShovel => (ToolKind::Shovel, ToolMaterial::Diamond),
+        BlockID::WoodenSword => (ToolKind::Sword, ToolMaterial::Wood),
+        BlockID::StoneSword => (ToolKind::Sword, ToolMaterial::Stone),
+        _ => return None,
+    };
+    let (efficiency, max_durability) = material.stats();
+    Some(ToolProperties { kind, material, efficiency, max_durability })
+}
+
+// Efficiency and durability of each material
+const WOODEN: (f32, u32) = (1.5, 59);
+const STONE: (f32, u32) = (2.0, 131);
+const IRON: (f32, u32) = (5.0, 250);
+const DIAMOND: (f32, u32) = (8.0, 1561);
+
+/// How well the item in hand fits the block, shown by the color of the block outline
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HarvestCompatibility {
+    /// The block drops something when broken with the item
+    Harvestable,
+    /// The block needs a better tool to drop anything
+    WrongTool,
+    /// Nothing can break the block, like bedrock
+    Unbreakable,
+}
+
+pub fn harvest_compatibility(block: BlockID, item_in_hand: Option<BlockID>) -> HarvestCompatibility {
+    if !BLOCK_PROPERTIES[&block].hardness.is_finite() {
+        HarvestCompatibility::Unbreakable
+    } else if can_harvest(block, item_in_hand) {
+        HarvestCompatibility::Harvestable
+    } else {
+        HarvestCompatibility::WrongTool
+    }
+}
+
+/// Whether the block drops something when broken with the item in hand
+pub fn can_harvest(block: BlockID, item_in_hand: Option<BlockID>) -> bool {
+    BLOCK_PROPERTIES[&block].harvest_requires.is_none()
+        || item_in_hand.and_then(tool_properties).map_or(false, |tool| tool.can_harvest(block))
+}
+
+/// Seconds needed to break the block with the item in hand (a tool or anything else), Efficiency speeds up
+/// the right tool. Breaking a block without a tool able to harvest it takes UNHARVESTABLE_BREAK_TIME_MULTIPLIER
+/// times longer
+pub fn break_time(block: BlockID, item_in_hand: Option<&ItemStack>) -> f32 {
+    let hardness = BLOCK_PROPERTIES[&block].hardness;
+    if !can_harvest(block, item_in_hand.map(|item_stack| item_stack.item)) {
+        return hardness * UNHARVESTABLE_BREAK_TIME_MULTIPLIER;
+    }
+    let tool = item_in_hand.and_then(|item_stack| tool_properties(item_stack.item).map(|tool| (tool, item_stack)));
+    match tool.filter(|(tool, _)| tool.is_correct_tool_for(block)) {
+        Some((tool, item_stack)) => {
+            let efficiency = efficiency_multiplier(item_stack.enchantment_level(EnchantmentId::Efficiency));
+            hardness / (tool.efficiency_for(block) * efficiency * 1.5)
+        }
+        None => hardness,
+    }
+}
+
+/// Damage dealt by hitting a mob with the item in hand, swords being the best at it
+pub fn melee_damage(item_in_hand: Option<BlockID>) -> f32 {
+    match item_in_hand.and_then(tool_properties).map(|tool| tool.kind) {
+        Some(ToolKind::Sword) => SWORD_DAMAGE,
+        Some(ToolKind::Axe) => AXE_DAMAGE,
+        _ => HAND_DAMAGE,
+    }
+}
@@ -0,0 +1,55 @@
+use nalgebra_glm::{Vec3, vec3};
+use rand::Rng;
+
+use crate::constants::ITEM_DESPAWN_TIME;
+use crate::inventory::item::ItemStack;
+
+/// An item stack lying in the world, moved by `UpdateItemEntities` until the player picks it up.
+/// The whole stack is kept so that tools keep their wear and enchantments
+pub struct ItemEntity {
+    pub item_stack: ItemStack,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Seconds since the item was dropped
+    pub age: f32,
+    pub despawn_after: f32,
+    /// Thrown items can't be picked up before this age, so that they leave the player first
+    pub pickup_delay: f32,
+}
+
+impl ItemEntity {
+    pub fn new(item_stack: ItemStack, position: Vec3, velocity: Vec3) -> Self {
+        ItemEntity {
+            item_stack,
+            position,
+            velocity,
+            age: 0.0,
+            despawn_after: ITEM_DESPAWN_TIME,
+            pickup_delay: 0.0,
+        }
+    }
+
+    /// The items of a broken block, popping out of the middle of the block in a random direction
+    pub fn dropped(item_stack: ItemStack, (x, y, z): (i32, i32, i32)) -> Self {
+        Self::popped(item_stack, vec3(x as f32 + 0.5, y as f32 + 0.25, z as f32 + 0.5))
+    }
+
+    /// Items popping out of `position` in a random direction
+    pub fn popped(item_stack: ItemStack, position: Vec3) -> Self {
+        let mut rng = rand::thread_rng();
+        let velocity = vec3(rng.gen_range(-1.0, 1.0), rng.gen_range(2.0, 4.0), rng.gen_range(-1.0, 1.0));
+        Self::new(item_stack, position, velocity)
+    }
+
+    /// An item thrown out of the inventory. `direction` must be normalized.
+    pub fn thrown(item_stack: ItemStack, position: Vec3, direction: Vec3) -> Self {
+        ItemEntity {
+            pickup_delay: 1.0,
+            ..Self::new(item_stack, position, direction * 6.0 + vec3(0.0, 2.0, 0.0))
+        }
+    }
+
+    pub fn can_be_picked_up(&self) -> bool {
+        self.age >= self.pickup_delay
+    }
+}
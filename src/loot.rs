@@ -0,0 +1,120 @@
+use std::ops::RangeInclusive;
+
+use rand::Rng;
+
+use crate::chunk::BlockID;
+
+/// An item that can be picked from a loot table
+pub struct LootEntry {
+    pub item: BlockID,
+    /// Number of items in the stack, no stack is given when it rolls 0
+    pub count: RangeInclusive<u32>,
+    /// How likely the entry is to be picked compared to the other entries of the table
+    pub weight: u32,
+}
+
+/// The items found in a chest or dropped by a mob
+pub struct LootTable {
+    /// Number of entries picked, from 1 to 5
+    pub rolls: RangeInclusive<u32>,
+    pub entries: Vec<LootEntry>,
+}
+
+fn entry(item: BlockID, count: RangeInclusive<u32>, weight: u32) -> LootEntry {
+    LootEntry { item, count, weight }
+}
+
+lazy_static! {
+    /// The chests of the dungeons
+    pub static ref DUNGEON_CHEST: LootTable = LootTable {
+        rolls: 3..=5,
+        entries: vec![
+            entry(BlockID::Bread, 1..=4, 3),
+            entry(BlockID::Apple, 1..=3, 2),
+            entry(BlockID::IronIngot, 1..=4, 2),
+            entry(BlockID::GoldIngot, 1..=4, 2),
+            entry(BlockID::Arrow, 4..=16, 2),
+            entry(BlockID::RedstoneWire, 4..=16, 2),
+            entry(BlockID::IronPickaxe, 1..=1, 1),
+            entry(BlockID::IronHelmet, 1..=1, 1),
+            entry(BlockID::Tnt, 1..=3, 1),
+            entry(BlockID::Glowstone, 1..=4, 1),
+        ],
+    };
+
+    /// The chests along the corridors of the Nether fortresses
+    pub static ref NETHER_FORTRESS_LOOT: LootTable = LootTable {
+        rolls: 2..=5,
+        entries: vec![
+            entry(BlockID::GoldIngot, 1..=3, 4),
+            entry(BlockID::NetherWart, 3..=7, 3),
+            entry(BlockID::BlazeRod, 1..=3, 2),
+            entry(BlockID::IronIngot, 1..=5, 2),
+            entry(BlockID::GoldenChestplate, 1..=1, 1),
+            entry(BlockID::Obsidian, 2..=4, 1),
+        ],
+    };
+
+    pub static ref COW_DROPS: LootTable = LootTable {
+        rolls: 1..=1,
+        entries: vec![
+            entry(BlockID::RawBeef, 1..=3, 1),
+        ],
+    };
+
+    /// Mostly rotten flesh, rarely an iron ingot
+    pub static ref ZOMBIE_DROPS: LootTable = LootTable {
+        rolls: 1..=1,
+        entries: vec![
+            entry(BlockID::RottenFlesh, 0..=2, 39),
+            entry(BlockID::IronIngot, 1..=1, 1),
+        ],
+    };
+
+    pub static ref BLAZE_DROPS: LootTable = LootTable {
+        rolls: 1..=1,
+        entries: vec![
+            entry(BlockID::BlazeRod, 0..=1, 1),
+        ],
+    };
+
+    /// Villagers drop nothing
+    pub static ref VILLAGER_DROPS: LootTable = LootTable {
+        rolls: 1..=1,
+        entries: Vec::new(),
+    };
+
+    pub static ref IRON_GOLEM_DROPS: LootTable = LootTable {
+        rolls: 1..=1,
+        entries: vec![
+            entry(BlockID::IronIngot, 3..=5, 1),
+        ],
+    };
+}
+
+/// Picks random entries of `table`, the entries with a bigger weight being picked more often.
+/// Gives plain items with their count rather than item stacks, which create GL buffers,
+/// so that the structures can be filled with loot on the world generation thread
+pub fn roll(table: &LootTable, rng: &mut impl Rng) -> Vec<(BlockID, u32)> {
+    let total_weight: u32 = table.entries.iter().map(|entry| entry.weight).sum();
+    if total_weight == 0 {
+        return Vec::new();
+    }
+
+    let mut items = Vec::new();
+    for _ in 0..rng.gen_range(*table.rolls.start(), *table.rolls.end() + 1) {
+        let mut pick = rng.gen_range(0, total_weight);
+        for entry in &table.entries {
+            if pick >= entry.weight {
+                pick -= entry.weight;
+                continue;
+            }
+            let count = rng.gen_range(*entry.count.start(), *entry.count.end() + 1);
+            if count > 0 {
+                items.push((entry.item, count));
+            }
+            break;
+        }
+    }
+    items
+}
@@ -13,29 +13,59 @@ use core::ffi::c_void;
 use std::collections::HashMap;
 
 use nalgebra_glm::vec3;
-use specs::{Builder, DispatcherBuilder, World, WorldExt};
+use specs::{Builder, DispatcherBuilder, RunNow, World, WorldExt};
+use parking_lot::RwLock;
 
 use ecs::components::*;
 use ecs::systems::*;
 use timer::Timer;
 
-use crate::chunk_manager::ChunkManager;
 use crate::constants::*;
 use crate::debugging::*;
+use crate::dimension::{DimensionManager, PortalRegistry};
+use crate::achievement::AchievementRegistry;
+use crate::text::{create_font_texture, FONT_TEXTURE_UNIT};
 use crate::fps_counter::FpsCounter;
-use crate::gui::{create_gui_icons_texture, create_widgets_texture};
+use crate::gui::{create_compass_texture, create_furnace_texture, create_gui_icons_texture, create_widgets_texture, COMPASS_TEXTURE_UNIT};
 use crate::input::InputCache;
 use crate::inventory::Inventory;
 use crate::main_hand::MainHand;
 use crate::particle_system::ParticleSystem;
-use crate::physics::Interpolator;
+use crate::physics::{Interpolator, PhysicsConfig};
 use crate::player::{PlayerPhysicsState, PlayerState};
 use crate::shader_compilation::ShaderProgram;
-use crate::texture_pack::generate_array_texture;
-use crate::types::Shaders;
+use crate::texture_pack::{create_caustics_texture, create_grass_colormap_texture, generate_array_texture, AnisotropicTextures, ArrayTexture};
+use crate::screen::Screen;
+use crate::sign::SignEditor;
+use crate::day_cycle::DayCycle;
+use crate::action_bar::ActionBar;
+use crate::chat::Chat;
+use crate::block_update::BlockUpdateQueue;
+use crate::redstone::RedstoneSimulator;
+use crate::water::WaterFlowSimulator;
+use crate::difficulty::WorldAge;
+use crate::debug_overlay::DebugOverlay;
+use crate::screenshots::ScreenshotGallery;
+use crate::settings::Settings;
+use crate::statistics::Statistics;
+use crate::crafting::RecipeRegistry;
+use crate::audio::SoundQueue;
+use crate::chunk_render_pipeline::ChunkRenderPipeline;
+use crate::clouds::{create_cloud_texture, CLOUD_TEXTURE_UNIT};
+use crate::worldgen::WorldGenConfig;
+use crate::mob_spawning::MobSpawnManager;
+use crate::xp_orb::XpOrb;
+use crate::weather::Weather;
+use crate::projectile::Projectile;
+use crate::firework::FireworkEntity;
+use crate::item_entity::ItemEntity;
+use crate::mob::{MobEntity, cow_mob, zombie_mob};
+use crate::types::{BlockEntityRegistry, Shaders};
 use crate::window::create_window;
+use crate::main_menu::run_main_menu;
+use crate::world_info::WorldInfo;
 use crate::ecs::systems::chunk_loading::ChunkLoading;
-use std::sync::Arc;
+use crate::frame_data::{FrameUBO, FRAME_DATA_BINDING};
 use std::thread;
 use std::time::Duration;
 
@@ -47,6 +77,8 @@ pub mod shapes;
 pub mod util;
 pub mod chunk_manager;
 pub mod chunk;
+pub mod block_properties;
+pub mod chunk_render_pipeline;
 pub mod raycast;
 pub mod block_texture_faces;
 pub mod physics;
@@ -64,6 +96,62 @@ pub mod timer;
 pub mod particle_system;
 pub mod ecs;
 pub mod main_hand;
+pub mod chest;
+pub mod furnace;
+pub mod crafting;
+pub mod crafting_table;
+pub mod screen;
+pub mod sign;
+pub mod status_bars;
+pub mod water;
+pub mod dimension;
+pub mod text;
+pub mod achievement;
+pub mod god_rays;
+pub mod block_entity;
+pub mod bed;
+pub mod day_cycle;
+pub mod action_bar;
+pub mod projectile;
+pub mod item_entity;
+pub mod explosion;
+pub mod block_light;
+pub mod block_update;
+pub mod redstone;
+pub mod piston;
+pub mod pressure_plate;
+pub mod fence_gate;
+pub mod dungeon;
+pub mod mob;
+pub mod pathfinding;
+pub mod difficulty;
+pub mod debug_overlay;
+pub mod settings;
+pub mod menu;
+pub mod statistics;
+pub mod worldgen;
+pub mod mob_spawning;
+pub mod xp_orb;
+pub mod weather;
+pub mod tree;
+pub mod status_effect;
+pub mod beacon;
+pub mod loot;
+pub mod structures;
+pub mod enchanting;
+pub mod audio;
+pub mod world_info;
+pub mod chat;
+pub mod command;
+pub mod main_menu;
+pub mod frame_data;
+pub mod map;
+pub mod observer;
+pub mod gpu_timer;
+pub mod screenshots;
+pub mod clouds;
+pub mod firework;
+pub mod campfire;
 use parking_lot::deadlock;
 
 fn main() {
@@ -88,47 +176,131 @@ fn main() {
 
     pretty_env_logger::init();
 
+    let (mut glfw, mut window, events) = create_window(WINDOW_WIDTH, WINDOW_HEIGHT, WINDOW_NAME);
+
+    gl_call!(gl::Enable(gl::DEBUG_OUTPUT));
+    gl_call!(gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS));
+    gl_call!(gl::DebugMessageCallback(Some(debug_message_callback), 0 as *const c_void));
+    gl_call!(gl::DebugMessageControl(gl::DONT_CARE, gl::DONT_CARE, gl::DONT_CARE, 0, 0 as *const u32, gl::TRUE));
+    gl_call!(gl::Enable(gl::CULL_FACE));
+    gl_call!(gl::CullFace(gl::BACK));
+    gl_call!(gl::Enable(gl::DEPTH_TEST));
+    gl_call!(gl::Enable(gl::BLEND));
+    let window_size = window.get_size();
+    gl_call!(gl::Viewport(0, 0, window_size.0, window_size.1));
+
+    // The main menu draws text too
+    let font_texture = create_font_texture();
+    gl_call!(gl::BindTextureUnit(FONT_TEXTURE_UNIT, font_texture));
+
+    // Skip the main menu when the world is given on the command line
+    let mut world_info = match get_world_seed() {
+        Some(seed) => WorldInfo::load(seed),
+        None => run_main_menu(&mut glfw, &mut window, &events),
+    };
+    world_info.touch();
+    let seed = world_info.seed;
+    println!("World: {}, seed: {}", world_info.name, seed);
+
     let mut world = World::new();
     world.register::<PlayerState>();
     world.register::<Interpolator<PlayerPhysicsState>>();
     world.register::<Inventory>();
     world.register::<MainHand>();
     world.register::<MainHandItemChanged>();
+    world.register::<Projectile>();
+    world.register::<FireworkEntity>();
+    world.register::<ItemEntity>();
+    world.register::<MobEntity>();
+    world.register::<XpOrb>();
 
+    let chunk_loading = ChunkLoading::new(seed);
     let mut dispatcher = DispatcherBuilder::new()
-        .with_thread_local({
-            let (glfw, window, events) = create_window(WINDOW_WIDTH, WINDOW_HEIGHT, WINDOW_NAME);
-
-            gl_call!(gl::Enable(gl::DEBUG_OUTPUT));
-            gl_call!(gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS));
-            gl_call!(gl::DebugMessageCallback(Some(debug_message_callback), 0 as *const c_void));
-            gl_call!(gl::DebugMessageControl(gl::DONT_CARE, gl::DONT_CARE, gl::DONT_CARE, 0, 0 as *const u32, gl::TRUE));
-            gl_call!(gl::Enable(gl::CULL_FACE));
-            gl_call!(gl::CullFace(gl::BACK));
-            gl_call!(gl::Enable(gl::DEPTH_TEST));
-            gl_call!(gl::Enable(gl::BLEND));
-            let window_size = window.get_size();
-            gl_call!(gl::Viewport(0, 0, window_size.0, window_size.1));
-
-            ReadWindowEvents {
-                glfw,
-                window,
-                events,
-            }
+        .with_thread_local(ReadWindowEvents {
+            glfw,
+            window,
+            events,
         })
+        .with_thread_local(HandleMenuInput::new())
+        .with_thread_local(HandleDeathScreenInput)
+        .with_thread_local(HandleSignInput)
+        .with_thread_local(HandleChatInput)
         .with_thread_local(InventoryHandleInput)
+        .with_thread_local(HandleInventoryScreenInput)
+        .with_thread_local(CloseScreen)
+        .with_thread_local(ToggleDebugOverlay::new())
+        .with_thread_local(CycleGameMode)
+        .with_thread_local(ChangeTimeScale)
+        .with_thread_local(HandleChestInput)
+        .with_thread_local(HandleFurnaceInput)
+        .with_thread_local(HandleCraftingTableInput)
+        .with_thread_local(HandleEnchantingTableInput)
         .with_thread_local(HandlePlayerInput)
         .with_thread_local(UpdatePlayerPhysics)
+        .with_thread_local(TravelThroughPortals::new())
+        .with_thread_local(PlayFootsteps::new())
         .with_thread_local(UpdatePlayerState)
         .with_thread_local(PlaceAndBreakBlocks)
+        .with_thread_local(EatFood)
+        .with_thread_local(ThrowProjectiles)
+        .with_thread_local(ShootCrossbows)
+        .with_thread_local(UpdateProjectiles::new())
+        .with_thread_local(LaunchFireworks)
+        .with_thread_local(UpdateFireworks::new())
+        .with_thread_local(UpdateItemEntities::new())
+        .with_thread_local(UpdateMobs::new(chunk_loading.player_interaction_thread_pool()))
+        .with_thread_local(SpawnMobs::new(chunk_loading.player_interaction_thread_pool()))
+        .with_thread_local(DespawnMobs)
+        .with_thread_local(UpdateXpOrbs::new())
+        .with_thread_local(UpdateHunger::new())
+        .with_thread_local(UpdateBreathing::new())
+        .with_thread_local(UpdateBurning::new())
+        .with_thread_local(UpdatePlayerHealth)
+        .with_thread_local(OpenDeathScreen)
+        .with_thread_local(EmitLavaDrips::new())
+        .with_thread_local(EmitCampfireSmoke::new())
+        .with_thread_local(Sleep)
+        .with_thread_local(AdvanceDayCycle::new())
+        .with_thread_local(UpdateWeather::new())
+        .with_thread_local(EmitRainDrops::new())
+        .with_thread_local(AdvanceWorldAge::new())
+        .with_thread_local(UpdateStatistics::new())
         .with_thread_local(UpdateMainHand)
-        .with_thread_local(ChunkLoading::new())
+        .with_thread_local(UpdateBlockEntities::new())
+        .with_thread_local(UpdateBeacons::new())
+        .with_thread_local(UpdatePressurePlates::new())
+        .with_thread_local(UpdateFenceGates::new())
+        .with_thread_local(ProcessBlockUpdates::new())
+        .with_thread_local(FlowWater::new())
+        .with_thread_local(UpdateRedstone)
+        .with_thread_local(UpdatePistons::new())
+        .with_thread_local(ReloadWorldGenConfig::new())
+        .with_thread_local(ReloadArrayTexture::new())
+        .with_thread_local(chunk_loading)
 
-        .with_thread_local(RenderChunks)
+        .with_thread_local(ApplyAnisotropicFiltering)
+        .with_thread_local(UploadFrameData::new())
+        .with_thread_local(RenderReflection::new())
+        .with_thread_local(RenderChunks::new())
+        .with_thread_local(RenderChests::new())
+        .with_thread_local(RenderPistons::new())
+        .with_thread_local(RenderSigns::new())
+        .with_thread_local(RenderBeds::new())
+        .with_thread_local(RenderPressurePlates::new())
+        .with_thread_local(RenderFenceGates::new())
+        .with_thread_local(RenderProjectiles::new())
+        .with_thread_local(RenderItemEntities::new())
+        .with_thread_local(RenderMobs::new())
+        .with_thread_local(RenderXpOrbs::new())
+        .with_thread_local(RenderWater)
+        .with_thread_local(RenderClouds::new())
+        .with_thread_local(RenderBeaconBeams::new())
         .with_thread_local(RenderParticles)
+        .with_thread_local(RenderGodRays::new())
         .with_thread_local(RenderBlockOutline::new())
         .with_thread_local(RenderMainHand::new())
         .with_thread_local(RenderGUI::new())
+        .with_thread_local(PlaySoundEvents::new())
 
         .with_thread_local(AdvanceGlobalTime)
         .with_thread_local(FpsCounter::new())
@@ -137,27 +309,74 @@ fn main() {
 
     world.insert(InputCache::default());
     world.insert(Timer::default());
-    world.insert({
-        let (item_array_texture, texture_pack) = generate_array_texture();
-        gl_call!(gl::BindTextureUnit(0, item_array_texture));
-        texture_pack
-    });
+    world.insert(PhysicsConfig::default());
+    let (item_array_texture, texture_pack) = generate_array_texture(get_texture_pack_path().as_deref());
+    gl_call!(gl::BindTextureUnit(0, item_array_texture));
+    world.insert(ArrayTexture(RwLock::new(item_array_texture)));
+    world.insert(texture_pack);
     world.insert({
         let mut particle_systems: HashMap<&str, ParticleSystem> = HashMap::new();
         particle_systems.insert("block_particles", ParticleSystem::new(500));
+        particle_systems.insert("bubble_particles", ParticleSystem::new(50));
+        particle_systems.insert("lava_drip_particles", ParticleSystem::new(100));
+        particle_systems.insert("rain_drop_particles", ParticleSystem::new(1000));
+        particle_systems.insert("firework_particles", ParticleSystem::new(300));
+        particle_systems.insert("smoke_particles", ParticleSystem::new(300));
         particle_systems
     });
     world.insert({
         let mut shaders_resource = Shaders::new();
         shaders_resource.insert("voxel_shader", ShaderProgram::compile("src/shaders/voxel.vert", "src/shaders/voxel.frag"));
         shaders_resource.insert("gui_shader", ShaderProgram::compile("src/shaders/gui.vert", "src/shaders/gui.frag"));
+        shaders_resource.insert("depth_prepass_shader", ShaderProgram::compile("src/shaders/voxel.vert", "src/shaders/depth_prepass.frag"));
+        shaders_resource.insert("occlusion_shader", ShaderProgram::compile("src/shaders/occlusion.vert", "src/shaders/occlusion.frag"));
         shaders_resource.insert("outline_shader", ShaderProgram::compile("src/shaders/outline.vert", "src/shaders/outline.frag"));
         shaders_resource.insert("item_shader", ShaderProgram::compile("src/shaders/item.vert", "src/shaders/item.frag"));
         shaders_resource.insert("particle_shader", ShaderProgram::compile("src/shaders/particle.vert", "src/shaders/particle.frag"));
         shaders_resource.insert("hand_shader", ShaderProgram::compile("src/shaders/hand.vert", "src/shaders/hand.frag"));
+        shaders_resource.insert("mob_shader", ShaderProgram::compile("src/shaders/mob.vert", "src/shaders/mob.frag"));
+        shaders_resource.insert("model_shader", ShaderProgram::compile("src/shaders/model.vert", "src/shaders/model.frag"));
+        shaders_resource.insert("water_shader", ShaderProgram::compile("src/shaders/water.vert", "src/shaders/water.frag"));
+        shaders_resource.insert("cloud_shader", ShaderProgram::compile("src/shaders/cloud.vert", "src/shaders/cloud.frag"));
+        shaders_resource.insert("beacon_beam_shader", ShaderProgram::compile("src/shaders/beacon_beam.vert", "src/shaders/beacon_beam.frag"));
+        shaders_resource.insert("text_shader", ShaderProgram::compile("src/shaders/gui.vert", "src/shaders/text.frag"));
+        shaders_resource.insert("god_rays_occlusion_shader", ShaderProgram::compile("src/shaders/fullscreen.vert", "src/shaders/god_rays_occlusion.frag"));
+        shaders_resource.insert("god_rays_blur_shader", ShaderProgram::compile("src/shaders/fullscreen.vert", "src/shaders/god_rays_blur.frag"));
+        shaders_resource.insert("god_rays_composite_shader", ShaderProgram::compile("src/shaders/fullscreen.vert", "src/shaders/god_rays_composite.frag"));
+        // The shaders drawing the world read the camera and the fog from the same buffer
+        for name in &["voxel_shader", "occlusion_shader", "outline_shader", "mob_shader", "model_shader", "water_shader", "beacon_beam_shader"] {
+            shaders_resource.get_mut(name).unwrap().bind_ubo_block("FrameData", FRAME_DATA_BINDING);
+        }
         shaders_resource
     });
-    world.insert(Arc::new(ChunkManager::new()));
+    world.insert(FrameUBO::new());
+    {
+        let dimension_manager = DimensionManager::new();
+        world.insert(dimension_manager.current_chunk_manager());
+        world.insert(dimension_manager);
+    }
+    world.insert(PortalRegistry::new());
+    world.insert(AchievementRegistry::new());
+    world.insert(Screen::default());
+    world.insert(BlockEntityRegistry::new());
+    world.insert(BlockUpdateQueue::default());
+    world.insert(RedstoneSimulator::default());
+    world.insert(WaterFlowSimulator::default());
+    world.insert(SignEditor::default());
+    world.insert(DayCycle::new());
+    world.insert(Weather::new());
+    world.insert(ActionBar::default());
+    world.insert(Chat::default());
+    world.insert(WorldAge::default());
+    world.insert(DebugOverlay::default());
+    world.insert(ScreenshotGallery::default());
+    world.insert(Settings::load());
+    world.insert(WorldGenConfig::load());
+    world.insert(MobSpawnManager::default());
+    world.insert(RecipeRegistry::default());
+    world.insert(SoundQueue::default());
+    world.insert(ChunkRenderPipeline::new());
+    world.insert(Statistics::load(seed));
 
     {
         let gui_icons_texture = create_gui_icons_texture();
@@ -167,20 +386,52 @@ fn main() {
         let gui_widgets_texture = create_widgets_texture();
         gl_call!(gl::ActiveTexture(gl::TEXTURE0 + 2));
         gl_call!(gl::BindTexture(gl::TEXTURE_2D, gui_widgets_texture));
+
+        let furnace_texture = create_furnace_texture();
+        gl_call!(gl::ActiveTexture(gl::TEXTURE0 + 5));
+        gl_call!(gl::BindTexture(gl::TEXTURE_2D, furnace_texture));
+
+        let compass_texture = create_compass_texture();
+        gl_call!(gl::BindTextureUnit(COMPASS_TEXTURE_UNIT, compass_texture));
+
+        let grass_colormap_texture = create_grass_colormap_texture();
+        gl_call!(gl::BindTextureUnit(GRASS_COLORMAP_TEXTURE_UNIT, grass_colormap_texture));
+
+        let caustics_texture = create_caustics_texture();
+        gl_call!(gl::BindTextureUnit(CAUSTICS_TEXTURE_UNIT, caustics_texture));
+
+        let cloud_texture = create_cloud_texture(seed);
+        gl_call!(gl::BindTextureUnit(CLOUD_TEXTURE_UNIT, cloud_texture));
+
+        world.insert(AnisotropicTextures::new(vec![item_array_texture, gui_icons_texture, gui_widgets_texture, furnace_texture]));
     }
 
     let _player = world.create_entity()
         .with(PlayerState::new())
         .with(Interpolator::new(
-            1.0 / PHYSICS_TICKRATE,
-            PlayerPhysicsState::new_at_position(vec3(8.0f32, 195.0, 8.0)),
+            PhysicsConfig::default().physics_dt,
+            PlayerPhysicsState::new_at_position(vec3(8.5f32, (WORLD_HEIGHT_IN_CHUNKS * 16) as f32, 8.5)),
         ))
         .with(Inventory::new())
         .with(MainHand::new())
         .with(MainHandItemChanged)
         .build();
 
+    // A few cows and a zombie around the spawn, they wait in the air until the terrain below them is generated
+    for &(x, z) in [(4.0, 12.0), (12.0, 4.0), (-4.0, 6.0)].iter() {
+        world.create_entity()
+            .with(cow_mob(vec3(x, 195.0, z)))
+            .build();
+    }
+    world.create_entity()
+        .with(zombie_mob(vec3(-10.0, 195.0, -10.0)))
+        .build();
+
+    let mut update_achievements = UpdateAchievements::new();
     loop {
         dispatcher.dispatch(&world);
+        update_achievements.run_now(&world);
+        // Removes the entities deleted during the frame (e.g. projectiles that hit something)
+        world.maintain();
     }
 }
@@ -0,0 +1,110 @@
+use std::process::exit;
+use std::sync::mpsc::Receiver;
+use std::time::Instant;
+
+use glfw::{Action, Context, CursorMode, Glfw, MouseButton, Window, WindowEvent};
+
+use crate::constants::WINDOW_HEIGHT;
+use crate::menu::{draw_title_screen, draw_world_selection, TitleButton, world_row_at, WorldSelectionButton, WORLDS_VISIBLE_ROWS};
+use crate::shader_compilation::ShaderProgram;
+use crate::text::TextRenderer;
+use crate::world_info::{list_saved_worlds, WorldInfo};
+
+/// The screens shown before a world is loaded
+pub enum MainMenuScreen {
+    Title,
+    WorldSelection {
+        worlds: Vec<WorldInfo>,
+        scroll: usize,
+    },
+}
+
+/// Whether the player is still choosing a world or playing in one
+pub enum AppState {
+    MainMenu(MainMenuScreen),
+    InGame(WorldInfo),
+}
+
+impl MainMenuScreen {
+    /// The state the application goes to when clicking at (x, y), in screen coordinates (y pointing up)
+    fn click(&self, x: f32, y: f32) -> Option<AppState> {
+        match self {
+            MainMenuScreen::Title => match TitleButton::at(x, y)? {
+                TitleButton::Singleplayer => Some(AppState::MainMenu(MainMenuScreen::WorldSelection {
+                    worlds: list_saved_worlds(),
+                    scroll: 0,
+                })),
+                TitleButton::Exit => exit(0),
+            },
+            MainMenuScreen::WorldSelection { worlds, scroll } => {
+                if let Some(row) = world_row_at(x, y) {
+                    return worlds.get(scroll + row).cloned().map(AppState::InGame);
+                }
+                match WorldSelectionButton::at(x, y)? {
+                    WorldSelectionButton::NewWorld => Some(AppState::InGame(WorldInfo::new_random(&mut rand::thread_rng()))),
+                    WorldSelectionButton::Back => Some(AppState::MainMenu(MainMenuScreen::Title)),
+                }
+            }
+        }
+    }
+
+    fn scroll(&mut self, delta: f64) {
+        if let MainMenuScreen::WorldSelection { worlds, scroll } = self {
+            let max_scroll = worlds.len().saturating_sub(WORLDS_VISIBLE_ROWS);
+            *scroll = if delta > 0.0 {
+                scroll.saturating_sub(1)
+            } else {
+                (*scroll + 1).min(max_scroll)
+            };
+        }
+    }
+}
+
+/// Shows the title screen until the player picks a world to play in, closing the window quits the game
+pub fn run_main_menu(glfw: &mut Glfw, window: &mut Window, events: &Receiver<(f64, WindowEvent)>) -> WorldInfo {
+    window.set_cursor_mode(CursorMode::Normal);
+    gl_call!(gl::Disable(gl::DEPTH_TEST));
+    gl_call!(gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA));
+
+    let mut text_renderer = TextRenderer::new();
+    let mut shader = ShaderProgram::compile("src/shaders/gui.vert", "src/shaders/text.frag");
+    let start = Instant::now();
+    let mut app_state = AppState::MainMenu(MainMenuScreen::Title);
+    let mut cursor = (0.0, 0.0);
+
+    loop {
+        let mut screen = match app_state {
+            AppState::MainMenu(screen) => screen,
+            AppState::InGame(world_info) => {
+                gl_call!(gl::Enable(gl::DEPTH_TEST));
+                return world_info;
+            }
+        };
+        if window.should_close() {
+            exit(0);
+        }
+
+        let mut next_state = None;
+        glfw.poll_events();
+        for (_, event) in glfw::flush_messages(events) {
+            match event {
+                WindowEvent::CursorPos(x, y) => cursor = (x as f32, WINDOW_HEIGHT as f32 - y as f32),
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Press, _) => {
+                    next_state = next_state.or_else(|| screen.click(cursor.0, cursor.1));
+                }
+                WindowEvent::Scroll(_, y) => screen.scroll(y),
+                _ => {}
+            }
+        }
+
+        let time = start.elapsed().as_secs_f32();
+        match &screen {
+            MainMenuScreen::Title => draw_title_screen(time, &mut text_renderer, &mut shader),
+            MainMenuScreen::WorldSelection { worlds, scroll } =>
+                draw_world_selection(worlds, *scroll, time, &mut text_renderer, &mut shader),
+        }
+        window.swap_buffers();
+
+        app_state = next_state.unwrap_or(AppState::MainMenu(screen));
+    }
+}
@@ -0,0 +1,211 @@
+use std::ffi::c_void;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::{Mat4, vec3};
+
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{GUI_SCALING, WATER_LEVEL, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::gui::create_gui_vao;
+use crate::shader_compilation::ShaderProgram;
+use crate::shapes::quad;
+use crate::text::TextRenderer;
+
+/// Side of the area drawn on a map, in blocks. Each block column is a pixel of the map
+pub const MAP_SIZE: usize = 128;
+pub const MAP_TEXTURE_UNIT: u32 = 10;
+/// Size of the map in the bottom right corner of the screen, in GUI pixels
+const MAP_DRAW_SIZE: f32 = 64.0;
+const MAP_MARGIN: f32 = 8.0;
+const MAP_BORDER: f32 = 2.0;
+const MAP_BORDER_COLOR: (f32, f32, f32, f32) = (0.4, 0.3, 0.2, 1.0);
+const MARKER_SIZE: f32 = 2.0;
+const MARKER_COLOR: (f32, f32, f32, f32) = (1.0, 0.1, 0.1, 1.0);
+/// Blocks around the player revealed on the map while they walk
+const MAP_EXPLORE_RADIUS: i32 = 48;
+/// Blocks of height between two shades of a color
+const MAP_SHADE_STEP: i32 = 12;
+
+/// The colors of the map, a pixel is its color index times 4 plus its shade. The first one is for the unexplored pixels
+const MAP_COLORS: [(u8, u8, u8); 10] = [
+    (214, 199, 158),  // Unexplored
+    (118, 172, 70),   // Grass
+    (52, 110, 38),    // Leaves
+    (142, 104, 72),   // Dirt
+    (128, 128, 128),  // Stone
+    (64, 96, 220),    // Water
+    (220, 90, 20),    // Lava
+    (160, 126, 80),   // Wood
+    (120, 40, 40),    // Nether
+    (170, 160, 150),  // Anything else
+];
+/// Brightness of the shades of a color, from the lowest terrain to the highest
+const MAP_SHADES: [f32; 4] = [0.6, 0.75, 0.88, 1.0];
+
+fn map_color(block: BlockID) -> u8 {
+    use BlockID::*;
+    match block {
+        GrassBlock => 1,
+        _ if block.is_leaves() => 2,
+        Dirt | DirtPath => 3,
+        Stone | Cobblestone | Bedrock | Gravel | StoneBrick | Granite | PolishedGranite | Diorite | PolishedDiorite
+        | Andesite | PolishedAndesite | IronOre | DiamondOre => 4,
+        Water => 5,
+        Lava => 6,
+        OakLog | BirchLog | SpruceLog | JungleLog | OakPlanks => 7,
+        Netherrack | NetherBrick => 8,
+        _ => 9,
+    }
+}
+
+/// A pixel of the map, 0 when the column isn't loaded yet
+fn map_pixel(chunk_manager: &ChunkManager, x: i32, z: i32) -> u8 {
+    let column = match chunk_manager.get_column(x.div_euclid(16), z.div_euclid(16)) {
+        Some(column) => column,
+        None => return 0,
+    };
+    let height = column.heighest_blocks.read()[(16 * z.rem_euclid(16) + x.rem_euclid(16)) as usize] as i32;
+    let block = match chunk_manager.get_block(x, height, z) {
+        Some(block) if !block.is_air() => block,
+        _ => return 0,
+    };
+    let shade = ((height - WATER_LEVEL) / MAP_SHADE_STEP + 2).max(0).min(3) as u8;
+    map_color(block) * 4 + shade
+}
+
+/// The terrain seen from above of the MAP_SIZE x MAP_SIZE blocks around `center`, drawn once the map is first held
+pub struct MapItem {
+    pub center: (i32, i32),
+    /// Indexed [z][x] from the north west corner
+    pub map_data: [[u8; MAP_SIZE]; MAP_SIZE],
+    /// The block the player was last in, the map is only explored when they move to another one
+    last_position: Option<(i32, i32)>,
+}
+
+impl MapItem {
+    pub fn new(center: (i32, i32)) -> Self {
+        Self {
+            center,
+            map_data: [[0; MAP_SIZE]; MAP_SIZE],
+            last_position: None,
+        }
+    }
+
+    /// World coordinates of the north west corner of the map
+    fn origin(&self) -> (i32, i32) {
+        (self.center.0 - MAP_SIZE as i32 / 2, self.center.1 - MAP_SIZE as i32 / 2)
+    }
+
+    /// Fills the unexplored pixels of the loaded columns, all of them the first time and then the ones around
+    /// the player when they reach another block. Returns whether the map changed
+    pub fn explore(&mut self, chunk_manager: &ChunkManager, (x, z): (i32, i32)) -> bool {
+        let first_time = self.last_position.is_none();
+        if self.last_position == Some((x, z)) {
+            return false;
+        }
+        self.last_position = Some((x, z));
+
+        let (left, top) = self.origin();
+        let mut changed = false;
+        for row in 0..MAP_SIZE {
+            for col in 0..MAP_SIZE {
+                let (pixel_x, pixel_z) = (left + col as i32, top + row as i32);
+                if self.map_data[row][col] != 0
+                    || !first_time && ((pixel_x - x).abs() > MAP_EXPLORE_RADIUS || (pixel_z - z).abs() > MAP_EXPLORE_RADIUS) {
+                    continue;
+                }
+                let pixel = map_pixel(chunk_manager, pixel_x, pixel_z);
+                if pixel != 0 {
+                    self.map_data[row][col] = pixel;
+                    changed = true;
+                }
+            }
+        }
+        changed || first_time
+    }
+
+    /// Copies the map to the texture
+    pub fn upload(&self, texture: u32) {
+        let mut pixels = Vec::with_capacity(MAP_SIZE * MAP_SIZE * 4);
+        for &pixel in self.map_data.iter().flat_map(|row| row.iter()) {
+            let (r, g, b) = MAP_COLORS[(pixel / 4) as usize];
+            let shade = if pixel == 0 { 1.0 } else { MAP_SHADES[(pixel % 4) as usize] };
+            pixels.extend_from_slice(&[
+                (r as f32 * shade) as u8,
+                (g as f32 * shade) as u8,
+                (b as f32 * shade) as u8,
+                255,
+            ]);
+        }
+        gl_call!(gl::TextureSubImage2D(
+            texture, 0,
+            0, 0, MAP_SIZE as i32, MAP_SIZE as i32,
+            gl::RGBA, gl::UNSIGNED_BYTE,
+            pixels.as_ptr() as *mut c_void));
+    }
+
+    /// Where the player at (x, z) is on the map, in [0, 1] from the north west corner. None when off the map
+    fn marker_position(&self, x: f32, z: f32) -> Option<(f32, f32)> {
+        let (left, top) = self.origin();
+        let u = (x - left as f32) / MAP_SIZE as f32;
+        let v = (z - top as f32) / MAP_SIZE as f32;
+        if (0.0..1.0).contains(&u) && (0.0..1.0).contains(&v) {
+            Some((u, v))
+        } else {
+            None
+        }
+    }
+}
+
+pub fn create_map_texture() -> u32 {
+    let mut map_texture = 0;
+    gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut map_texture));
+    gl_call!(gl::TextureParameteri(map_texture, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32));
+    gl_call!(gl::TextureParameteri(map_texture, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32));
+    gl_call!(gl::TextureStorage2D(map_texture, 1, gl::RGBA8, MAP_SIZE as i32, MAP_SIZE as i32));
+    map_texture
+}
+
+pub fn create_map_vao() -> u32 {
+    create_gui_vao(&quad((0.0, 0.0, 1.0, 1.0)))
+}
+
+/// Bottom left corner and side of the map on the screen, in screen pixels
+fn map_screen_rect() -> (f32, f32, f32) {
+    let size = MAP_DRAW_SIZE * GUI_SCALING;
+    let margin = MAP_MARGIN * GUI_SCALING;
+    (WINDOW_WIDTH as f32 - margin - size, margin, size)
+}
+
+/// Draws the map in the bottom right corner
+pub fn draw_map(map_vao: u32, shader: &mut ShaderProgram) {
+    let (left, bottom, size) = map_screen_rect();
+    let translate_matrix = Matrix4::new_translation(&vec3(left + size / 2.0, bottom + size / 2.0, 0.0));
+    let scale_matrix: Mat4 = Matrix4::new_nonuniform_scaling(&vec3(size, size, 1.0));
+    let projection_matrix = nalgebra_glm::ortho(
+        0.0, WINDOW_WIDTH as f32, 0.0, WINDOW_HEIGHT as f32, -5.0, 5.0);
+
+    shader.use_program();
+    shader.set_uniform_matrix4fv("projection", projection_matrix.as_ptr());
+    shader.set_uniform1i("tex", MAP_TEXTURE_UNIT as i32);
+    let model_matrix = translate_matrix * scale_matrix;
+    shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+    gl_call!(gl::BindVertexArray(map_vao));
+    gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 6));
+}
+
+/// Draws the frame around the map and a marker where the player at (x, z) is, unless they left the area of the map
+pub fn draw_map_marker(map: &MapItem, (x, z): (f32, f32), text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    let (left, bottom, size) = map_screen_rect();
+    let border = MAP_BORDER * GUI_SCALING;
+    text_renderer.draw_rect(left - border, bottom - border, size + 2.0 * border, border, MAP_BORDER_COLOR, shader);
+    text_renderer.draw_rect(left - border, bottom + size, size + 2.0 * border, border, MAP_BORDER_COLOR, shader);
+    text_renderer.draw_rect(left - border, bottom, border, size, MAP_BORDER_COLOR, shader);
+    text_renderer.draw_rect(left + size, bottom, border, size, MAP_BORDER_COLOR, shader);
+
+    if let Some((u, v)) = map.marker_position(x, z) {
+        let marker_size = MARKER_SIZE * GUI_SCALING;
+        text_renderer.draw_rect(left + u * size - marker_size / 2.0, bottom + (1.0 - v) * size - marker_size / 2.0,
+                                marker_size, marker_size, MARKER_COLOR, shader);
+    }
+}
@@ -0,0 +1,557 @@
+use crate::constants::{GUI_SCALING, SCREENSHOTS_DIRECTORY, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::settings::Settings;
+use crate::statistics::Statistics;
+use crate::shader_compilation::ShaderProgram;
+use crate::text::{GLYPH_HEIGHT, TextRenderer};
+use crate::world_info::WorldInfo;
+
+/// Size of the buttons of the menus, in GUI pixels
+const BUTTON_WIDTH: f32 = 200.0;
+const BUTTON_HEIGHT: f32 = 20.0;
+/// Size of the track of the sliders and of the thumb moving along it, in GUI pixels
+const TRACK_WIDTH: f32 = 150.0;
+const TRACK_HEIGHT: f32 = 4.0;
+const THUMB_WIDTH: f32 = 4.0;
+const THUMB_HEIGHT: f32 = 12.0;
+/// Vertical distance between two sliders, in GUI pixels
+const ROW_SPACING: f32 = 24.0;
+/// Statistics listed at once, the others are reached by scrolling
+pub const STATISTICS_VISIBLE_ROWS: usize = 4;
+const STATISTICS_ROW_SPACING: f32 = 16.0;
+/// Worlds listed at once in the world selection screen, the others are reached by scrolling
+pub const WORLDS_VISIBLE_ROWS: usize = 4;
+const WORLD_ROW_HEIGHT: f32 = 24.0;
+const WORLD_ROW_SPACING: f32 = 26.0;
+/// Thumbnails on a page of the screenshot gallery, in rows of THUMBNAIL_COLUMNS
+pub const THUMBNAILS_PER_PAGE: usize = 12;
+const THUMBNAIL_COLUMNS: usize = 4;
+/// Size of a thumbnail and space between two of them, in GUI pixels
+const THUMBNAIL_SIZE: f32 = 44.0;
+const THUMBNAIL_SPACING: f32 = 8.0;
+/// Width of the buttons turning the pages of the gallery, on each side of the thumbnails
+const PAGE_BUTTON_WIDTH: f32 = 20.0;
+/// Size of the squares of the background of the main menu, in GUI pixels, and how fast it pans
+const BACKGROUND_TILE_SIZE: f32 = 32.0;
+const BACKGROUND_PAN_SPEED: f32 = 8.0;
+
+const BUTTON_COLOR: (f32, f32, f32, f32) = (0.35, 0.35, 0.35, 0.9);
+const TRACK_COLOR: (f32, f32, f32, f32) = (0.1, 0.1, 0.1, 0.9);
+const THUMB_COLOR: (f32, f32, f32, f32) = (0.8, 0.8, 0.8, 1.0);
+const TEXT_COLOR: (f32, f32, f32, f32) = (1.0, 1.0, 1.0, 1.0);
+const BACKGROUND_COLOR: (f32, f32, f32, f32) = (0.0, 0.0, 0.0, 0.5);
+const DEATH_BACKGROUND_COLOR: (f32, f32, f32, f32) = (0.5, 0.0, 0.0, 0.45);
+const MAIN_MENU_BACKGROUND_COLORS: [(f32, f32, f32, f32); 2] = [(0.24, 0.17, 0.11, 1.0), (0.2, 0.14, 0.09, 1.0)];
+const DETAILS_COLOR: (f32, f32, f32, f32) = (0.65, 0.65, 0.65, 1.0);
+const EMPTY_THUMBNAIL_COLOR: (f32, f32, f32, f32) = (0.1, 0.1, 0.1, 0.6);
+const SCREENSHOT_BACKGROUND_COLOR: (f32, f32, f32, f32) = (0.0, 0.0, 0.0, 1.0);
+
+/// The buttons of the pause menu, from top to bottom
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PauseButton {
+    BackToGame,
+    Options,
+    Statistics,
+    Screenshots,
+    QuitGame,
+}
+
+pub const PAUSE_BUTTONS: [PauseButton; 5] = [
+    PauseButton::BackToGame, PauseButton::Options, PauseButton::Statistics, PauseButton::Screenshots, PauseButton::QuitGame,
+];
+
+impl PauseButton {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PauseButton::BackToGame => "Back to game",
+            PauseButton::Options => "Options...",
+            PauseButton::Statistics => "Statistics",
+            PauseButton::Screenshots => "Screenshots",
+            PauseButton::QuitGame => "Quit game",
+        }
+    }
+
+    /// Center of the button in screen coordinates
+    fn position(&self) -> (f32, f32) {
+        let index = PAUSE_BUTTONS.iter().position(|button| button == self).unwrap();
+        (WINDOW_WIDTH as f32 / 2.0, WINDOW_HEIGHT as f32 / 2.0 + (1.5 - index as f32) * 30.0 * GUI_SCALING)
+    }
+
+    pub fn at(x: f32, y: f32) -> Option<PauseButton> {
+        PAUSE_BUTTONS.iter().cloned().find(|button| is_on_button(button.position(), x, y))
+    }
+}
+
+/// The buttons of the death screen, from top to bottom
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DeathButton {
+    Respawn,
+    TitleScreen,
+}
+
+pub const DEATH_BUTTONS: [DeathButton; 2] = [DeathButton::Respawn, DeathButton::TitleScreen];
+
+impl DeathButton {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DeathButton::Respawn => "Respawn",
+            DeathButton::TitleScreen => "Title screen",
+        }
+    }
+
+    /// Center of the button in screen coordinates
+    fn position(&self) -> (f32, f32) {
+        let index = DEATH_BUTTONS.iter().position(|button| button == self).unwrap();
+        (WINDOW_WIDTH as f32 / 2.0, WINDOW_HEIGHT as f32 / 2.0 - index as f32 * 30.0 * GUI_SCALING)
+    }
+
+    pub fn at(x: f32, y: f32) -> Option<DeathButton> {
+        DEATH_BUTTONS.iter().cloned().find(|button| is_on_button(button.position(), x, y))
+    }
+}
+
+/// The buttons on the left and right of the screenshot gallery
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PageButton {
+    Previous,
+    Next,
+}
+
+impl PageButton {
+    fn label(&self) -> &'static str {
+        match self {
+            PageButton::Previous => "<",
+            PageButton::Next => ">",
+        }
+    }
+
+    /// Center of the button in screen coordinates
+    fn position(&self) -> (f32, f32) {
+        let (left, bottom, width, height) = thumbnail_grid_rect();
+        let offset = width / 2.0 + (THUMBNAIL_SPACING + PAGE_BUTTON_WIDTH / 2.0) * GUI_SCALING;
+        let center_x = left + width / 2.0;
+        let x = match self {
+            PageButton::Previous => center_x - offset,
+            PageButton::Next => center_x + offset,
+        };
+        (x, bottom + height / 2.0)
+    }
+
+    pub fn at(x: f32, y: f32) -> Option<PageButton> {
+        [PageButton::Previous, PageButton::Next].iter().cloned().find(|button| {
+            let (center_x, center_y) = button.position();
+            (x - center_x).abs() <= PAGE_BUTTON_WIDTH * GUI_SCALING / 2.0 && (y - center_y).abs() <= BUTTON_HEIGHT * GUI_SCALING / 2.0
+        })
+    }
+}
+
+/// The buttons of the title screen, from top to bottom
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TitleButton {
+    Singleplayer,
+    Exit,
+}
+
+pub const TITLE_BUTTONS: [TitleButton; 2] = [TitleButton::Singleplayer, TitleButton::Exit];
+
+impl TitleButton {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TitleButton::Singleplayer => "Singleplayer",
+            TitleButton::Exit => "Exit",
+        }
+    }
+
+    /// Center of the button in screen coordinates
+    fn position(&self) -> (f32, f32) {
+        let index = TITLE_BUTTONS.iter().position(|button| button == self).unwrap();
+        (WINDOW_WIDTH as f32 / 2.0, WINDOW_HEIGHT as f32 / 2.0 - index as f32 * 30.0 * GUI_SCALING)
+    }
+
+    pub fn at(x: f32, y: f32) -> Option<TitleButton> {
+        TITLE_BUTTONS.iter().cloned().find(|button| is_on_button(button.position(), x, y))
+    }
+}
+
+/// The buttons below the list of the world selection screen, from top to bottom
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WorldSelectionButton {
+    NewWorld,
+    Back,
+}
+
+pub const WORLD_SELECTION_BUTTONS: [WorldSelectionButton; 2] = [WorldSelectionButton::NewWorld, WorldSelectionButton::Back];
+
+impl WorldSelectionButton {
+    pub fn label(&self) -> &'static str {
+        match self {
+            WorldSelectionButton::NewWorld => "New World",
+            WorldSelectionButton::Back => "Back",
+        }
+    }
+
+    /// Center of the button in screen coordinates
+    fn position(&self) -> (f32, f32) {
+        let index = WORLD_SELECTION_BUTTONS.iter().position(|button| button == self).unwrap();
+        (WINDOW_WIDTH as f32 / 2.0, WINDOW_HEIGHT as f32 / 2.0 - (3.0 + index as f32) * ROW_SPACING * GUI_SCALING)
+    }
+
+    pub fn at(x: f32, y: f32) -> Option<WorldSelectionButton> {
+        WORLD_SELECTION_BUTTONS.iter().cloned().find(|button| is_on_button(button.position(), x, y))
+    }
+}
+
+/// Center of the `row`th visible row of the world list in screen coordinates
+fn world_row_position(row: usize) -> (f32, f32) {
+    let top = WINDOW_HEIGHT as f32 / 2.0 + 2.5 * ROW_SPACING * GUI_SCALING;
+    (WINDOW_WIDTH as f32 / 2.0, top - (row as f32 + 0.5) * WORLD_ROW_SPACING * GUI_SCALING)
+}
+
+/// The visible row of the world list under the cursor
+pub fn world_row_at(x: f32, y: f32) -> Option<usize> {
+    (0..WORLDS_VISIBLE_ROWS).find(|&row| {
+        let (center_x, center_y) = world_row_position(row);
+        (x - center_x).abs() <= BUTTON_WIDTH * GUI_SCALING / 2.0 && (y - center_y).abs() <= WORLD_ROW_HEIGHT * GUI_SCALING / 2.0
+    })
+}
+
+/// The settings changed in the options screen, from top to bottom
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SettingSlider {
+    MouseSensitivityX,
+    MouseSensitivityY,
+    Fov,
+    RenderDistance,
+    AoStrength,
+    Anisotropy,
+}
+
+pub const SETTING_SLIDERS: [SettingSlider; 6] = [
+    SettingSlider::MouseSensitivityX,
+    SettingSlider::MouseSensitivityY,
+    SettingSlider::Fov,
+    SettingSlider::RenderDistance,
+    SettingSlider::AoStrength,
+    SettingSlider::Anisotropy,
+];
+
+impl SettingSlider {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SettingSlider::MouseSensitivityX => "Sensitivity X",
+            SettingSlider::MouseSensitivityY => "Sensitivity Y",
+            SettingSlider::Fov => "FOV",
+            SettingSlider::RenderDistance => "Render distance",
+            SettingSlider::AoStrength => "Smooth lighting",
+            SettingSlider::Anisotropy => "Anisotropic filtering",
+        }
+    }
+
+    /// Smallest and largest values of the setting
+    pub fn range(&self) -> (f32, f32) {
+        match self {
+            SettingSlider::MouseSensitivityX |
+            SettingSlider::MouseSensitivityY => (0.1, 5.0),
+            SettingSlider::Fov => (60.0, 110.0),
+            SettingSlider::RenderDistance => (2.0, 16.0),
+            SettingSlider::AoStrength => (0.0, 1.0),
+            // Powers of two, from 1x to 16x
+            SettingSlider::Anisotropy => (0.0, 4.0),
+        }
+    }
+
+    pub fn value(&self, settings: &Settings) -> f32 {
+        match self {
+            SettingSlider::MouseSensitivityX => settings.mouse_sensitivity_x,
+            SettingSlider::MouseSensitivityY => settings.mouse_sensitivity_y,
+            SettingSlider::Fov => settings.fov,
+            SettingSlider::RenderDistance => settings.render_distance as f32,
+            SettingSlider::AoStrength => settings.ao_strength,
+            SettingSlider::Anisotropy => (settings.anisotropy.max(1) as f32).log2(),
+        }
+    }
+
+    /// Changes the setting, `value` is clamped to the range of the slider
+    pub fn set_value(&self, settings: &mut Settings, value: f32) {
+        let (min, max) = self.range();
+        let value = value.max(min).min(max);
+        match self {
+            SettingSlider::MouseSensitivityX => settings.mouse_sensitivity_x = (value * 100.0).round() / 100.0,
+            SettingSlider::MouseSensitivityY => settings.mouse_sensitivity_y = (value * 100.0).round() / 100.0,
+            SettingSlider::Fov => settings.fov = value.round(),
+            SettingSlider::RenderDistance => settings.render_distance = value.round() as i32,
+            SettingSlider::AoStrength => settings.ao_strength = (value * 100.0).round() / 100.0,
+            SettingSlider::Anisotropy => settings.anisotropy = 1 << value.round() as u32,
+        }
+    }
+
+    fn format_value(&self, settings: &Settings) -> String {
+        match self {
+            SettingSlider::Fov |
+            SettingSlider::RenderDistance => format!("{}", self.value(settings)),
+            SettingSlider::Anisotropy => format!("{}x", settings.anisotropy),
+            _ => format!("{:.2}", self.value(settings)),
+        }
+    }
+
+    /// Left end and vertical center of the track in screen coordinates
+    fn track_position(&self) -> (f32, f32) {
+        let index = SETTING_SLIDERS.iter().position(|slider| slider == self).unwrap();
+        let x = (WINDOW_WIDTH as f32 - TRACK_WIDTH * GUI_SCALING) / 2.0;
+        let y = WINDOW_HEIGHT as f32 / 2.0 + (2.0 - index as f32) * ROW_SPACING * GUI_SCALING;
+        (x, y)
+    }
+
+    /// The slider whose track or thumb is under the cursor
+    pub fn at(x: f32, y: f32) -> Option<SettingSlider> {
+        SETTING_SLIDERS.iter().cloned().find(|slider| {
+            let (left, center_y) = slider.track_position();
+            let half_thumb_width = THUMB_WIDTH * GUI_SCALING / 2.0;
+            x >= left - half_thumb_width && x <= left + TRACK_WIDTH * GUI_SCALING + half_thumb_width
+                && (y - center_y).abs() <= THUMB_HEIGHT * GUI_SCALING / 2.0
+        })
+    }
+
+    /// Value of the setting when the thumb is dragged to the cursor
+    pub fn value_at(&self, x: f32) -> f32 {
+        let (left, _) = self.track_position();
+        let t = ((x - left) / (TRACK_WIDTH * GUI_SCALING)).max(0.0).min(1.0);
+        let (min, max) = self.range();
+        min + t * (max - min)
+    }
+}
+
+/// Center of the Done button of the options screen
+fn done_button_position() -> (f32, f32) {
+    (WINDOW_WIDTH as f32 / 2.0, WINDOW_HEIGHT as f32 / 2.0 - 4.0 * ROW_SPACING * GUI_SCALING)
+}
+
+pub fn is_on_done_button(x: f32, y: f32) -> bool {
+    is_on_button(done_button_position(), x, y)
+}
+
+fn is_on_button((center_x, center_y): (f32, f32), x: f32, y: f32) -> bool {
+    (x - center_x).abs() <= BUTTON_WIDTH * GUI_SCALING / 2.0 && (y - center_y).abs() <= BUTTON_HEIGHT * GUI_SCALING / 2.0
+}
+
+fn draw_button(label: &str, (center_x, center_y): (f32, f32), text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    let (width, height) = (BUTTON_WIDTH * GUI_SCALING, BUTTON_HEIGHT * GUI_SCALING);
+    text_renderer.draw_rect(center_x - width / 2.0, center_y - height / 2.0, width, height, BUTTON_COLOR, shader);
+    draw_centered_text(label, center_x, center_y, text_renderer, shader);
+}
+
+fn draw_centered_text(text: &str, center_x: f32, center_y: f32, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    let x = center_x - TextRenderer::text_width(text, GUI_SCALING) / 2.0;
+    let y = center_y - GLYPH_HEIGHT * GUI_SCALING / 2.0;
+    text_renderer.draw(text, x, y, GUI_SCALING, TEXT_COLOR, shader);
+}
+
+/// Darkens the game and draws the buttons of the pause menu
+pub fn draw_pause_menu(text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    text_renderer.draw_rect(0.0, 0.0, WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32, BACKGROUND_COLOR, shader);
+    draw_centered_text("Game paused", WINDOW_WIDTH as f32 / 2.0, WINDOW_HEIGHT as f32 / 2.0 + 70.0 * GUI_SCALING, text_renderer, shader);
+    for button in PAUSE_BUTTONS.iter() {
+        draw_button(button.label(), button.position(), text_renderer, shader);
+    }
+}
+
+/// Tints the game in red and draws how the player died, their score and the buttons of the death screen
+pub fn draw_death_screen(death_message: &str, score: u64, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    text_renderer.draw_rect(0.0, 0.0, WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32, DEATH_BACKGROUND_COLOR, shader);
+    let center_x = WINDOW_WIDTH as f32 / 2.0;
+    let top = WINDOW_HEIGHT as f32 / 2.0;
+    let title_scale = 2.0 * GUI_SCALING;
+    let title = "You Died!";
+    text_renderer.draw(title, center_x - TextRenderer::text_width(title, title_scale) / 2.0,
+                       top + 70.0 * GUI_SCALING, title_scale, TEXT_COLOR, shader);
+    draw_centered_text(death_message, center_x, top + 50.0 * GUI_SCALING, text_renderer, shader);
+    draw_centered_text(&format!("Score: {}", score), center_x, top + 35.0 * GUI_SCALING, text_renderer, shader);
+    for button in DEATH_BUTTONS.iter() {
+        draw_button(button.label(), button.position(), text_renderer, shader);
+    }
+}
+
+/// Darkens the game and draws a slider for every setting, with its name on the left and its value on the right
+pub fn draw_options(settings: &Settings, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    text_renderer.draw_rect(0.0, 0.0, WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32, BACKGROUND_COLOR, shader);
+    draw_centered_text("Options", WINDOW_WIDTH as f32 / 2.0, WINDOW_HEIGHT as f32 / 2.0 + 3.5 * ROW_SPACING * GUI_SCALING, text_renderer, shader);
+
+    let margin = 8.0 * GUI_SCALING;
+    let text_offset = GLYPH_HEIGHT * GUI_SCALING / 2.0;
+    for slider in SETTING_SLIDERS.iter() {
+        let (left, center_y) = slider.track_position();
+        let track_width = TRACK_WIDTH * GUI_SCALING;
+
+        let label = slider.label();
+        let label_x = left - margin - TextRenderer::text_width(label, GUI_SCALING);
+        text_renderer.draw(label, label_x, center_y - text_offset, GUI_SCALING, TEXT_COLOR, shader);
+
+        let track_height = TRACK_HEIGHT * GUI_SCALING;
+        text_renderer.draw_rect(left, center_y - track_height / 2.0, track_width, track_height, TRACK_COLOR, shader);
+
+        let (min, max) = slider.range();
+        let thumb_x = left + (slider.value(settings) - min) / (max - min) * track_width;
+        let (thumb_width, thumb_height) = (THUMB_WIDTH * GUI_SCALING, THUMB_HEIGHT * GUI_SCALING);
+        text_renderer.draw_rect(thumb_x - thumb_width / 2.0, center_y - thumb_height / 2.0, thumb_width, thumb_height, THUMB_COLOR, shader);
+
+        let value = slider.format_value(settings);
+        text_renderer.draw(&value, left + track_width + margin, center_y - text_offset, GUI_SCALING, TEXT_COLOR, shader);
+    }
+
+    draw_button("Done", done_button_position(), text_renderer, shader);
+}
+
+/// Darkens the game and lists STATISTICS_VISIBLE_ROWS statistics from the `scroll`th one,
+/// with a scrollbar on the right when they don't all fit
+pub fn draw_statistics(statistics: &Statistics, scroll: usize, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    text_renderer.draw_rect(0.0, 0.0, WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32, BACKGROUND_COLOR, shader);
+    draw_centered_text("Statistics", WINDOW_WIDTH as f32 / 2.0, WINDOW_HEIGHT as f32 / 2.0 + 3.5 * ROW_SPACING * GUI_SCALING, text_renderer, shader);
+
+    let rows = statistics.rows();
+    let width = BUTTON_WIDTH * GUI_SCALING;
+    let left = (WINDOW_WIDTH as f32 - width) / 2.0;
+    let top = WINDOW_HEIGHT as f32 / 2.0 + 2.0 * ROW_SPACING * GUI_SCALING;
+    let spacing = STATISTICS_ROW_SPACING * GUI_SCALING;
+    for (i, (name, value)) in rows.iter().skip(scroll).take(STATISTICS_VISIBLE_ROWS).enumerate() {
+        let y = top - i as f32 * spacing - GLYPH_HEIGHT * GUI_SCALING;
+        text_renderer.draw(name, left, y, GUI_SCALING, TEXT_COLOR, shader);
+        let value_x = left + width - TextRenderer::text_width(value, GUI_SCALING);
+        text_renderer.draw(value, value_x, y, GUI_SCALING, TEXT_COLOR, shader);
+    }
+
+    if rows.len() > STATISTICS_VISIBLE_ROWS {
+        let bar_x = left + width + 8.0 * GUI_SCALING;
+        let bar_width = 2.0 * GUI_SCALING;
+        let bar_height = STATISTICS_VISIBLE_ROWS as f32 * spacing;
+        text_renderer.draw_rect(bar_x, top - bar_height, bar_width, bar_height, TRACK_COLOR, shader);
+        let thumb_height = bar_height * STATISTICS_VISIBLE_ROWS as f32 / rows.len() as f32;
+        let thumb_y = top - thumb_height - bar_height * scroll as f32 / rows.len() as f32;
+        text_renderer.draw_rect(bar_x, thumb_y, bar_width, thumb_height, THUMB_COLOR, shader);
+    }
+
+    draw_button("Done", done_button_position(), text_renderer, shader);
+}
+
+/// Left, bottom, width and height of the grid of thumbnails of the gallery, in screen coordinates
+fn thumbnail_grid_rect() -> (f32, f32, f32, f32) {
+    let rows = (THUMBNAILS_PER_PAGE + THUMBNAIL_COLUMNS - 1) / THUMBNAIL_COLUMNS;
+    let width = (THUMBNAIL_COLUMNS as f32 * (THUMBNAIL_SIZE + THUMBNAIL_SPACING) - THUMBNAIL_SPACING) * GUI_SCALING;
+    let height = (rows as f32 * (THUMBNAIL_SIZE + THUMBNAIL_SPACING) - THUMBNAIL_SPACING) * GUI_SCALING;
+    let top = WINDOW_HEIGHT as f32 / 2.0 + 3.0 * ROW_SPACING * GUI_SCALING;
+    ((WINDOW_WIDTH as f32 - width) / 2.0, top - height, width, height)
+}
+
+/// Bottom left corner and side of the `slot`th thumbnail of a page, in screen coordinates
+pub fn thumbnail_rect(slot: usize) -> (f32, f32, f32) {
+    let (left, bottom, _, height) = thumbnail_grid_rect();
+    let size = THUMBNAIL_SIZE * GUI_SCALING;
+    let step = (THUMBNAIL_SIZE + THUMBNAIL_SPACING) * GUI_SCALING;
+    let (row, column) = (slot / THUMBNAIL_COLUMNS, slot % THUMBNAIL_COLUMNS);
+    (left + column as f32 * step, bottom + height - size - row as f32 * step, size)
+}
+
+/// The slot of the thumbnail at (x, y) on a page of the gallery, in screen coordinates
+pub fn thumbnail_at(x: f32, y: f32) -> Option<usize> {
+    (0..THUMBNAILS_PER_PAGE).find(|&slot| {
+        let (left, bottom, size) = thumbnail_rect(slot);
+        x >= left && x < left + size && y >= bottom && y < bottom + size
+    })
+}
+
+/// Darkens the game and draws the frame of a page of the gallery holding `count` screenshots,
+/// the thumbnails are drawn on top of it with the GUI shader
+pub fn draw_screenshot_gallery(count: usize, page: usize, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    text_renderer.draw_rect(0.0, 0.0, WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32, BACKGROUND_COLOR, shader);
+    let pages = (count + THUMBNAILS_PER_PAGE - 1) / THUMBNAILS_PER_PAGE;
+    let title = if pages > 1 { format!("Screenshots ({}/{})", page + 1, pages) } else { "Screenshots".to_string() };
+    draw_centered_text(&title, WINDOW_WIDTH as f32 / 2.0, WINDOW_HEIGHT as f32 / 2.0 + 3.5 * ROW_SPACING * GUI_SCALING, text_renderer, shader);
+
+    if count == 0 {
+        let (left, bottom, width, height) = thumbnail_grid_rect();
+        draw_centered_text(&format!("No screenshots in {}/", SCREENSHOTS_DIRECTORY), left + width / 2.0, bottom + height / 2.0, text_renderer, shader);
+    }
+    for slot in 0..THUMBNAILS_PER_PAGE.min(count.saturating_sub(page * THUMBNAILS_PER_PAGE)) {
+        let (left, bottom, size) = thumbnail_rect(slot);
+        text_renderer.draw_rect(left, bottom, size, size, EMPTY_THUMBNAIL_COLOR, shader);
+    }
+    if page > 0 {
+        let button = PageButton::Previous;
+        draw_page_button(button.label(), button.position(), text_renderer, shader);
+    }
+    if page + 1 < pages {
+        let button = PageButton::Next;
+        draw_page_button(button.label(), button.position(), text_renderer, shader);
+    }
+
+    draw_button("Done", done_button_position(), text_renderer, shader);
+}
+
+fn draw_page_button(label: &str, (center_x, center_y): (f32, f32), text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    let (width, height) = (PAGE_BUTTON_WIDTH * GUI_SCALING, BUTTON_HEIGHT * GUI_SCALING);
+    text_renderer.draw_rect(center_x - width / 2.0, center_y - height / 2.0, width, height, BUTTON_COLOR, shader);
+    draw_centered_text(label, center_x, center_y, text_renderer, shader);
+}
+
+/// Hides the game behind the screenshot viewed in full screen, drawn afterwards with the GUI shader
+pub fn draw_screenshot_background(text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    text_renderer.draw_rect(0.0, 0.0, WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32, SCREENSHOT_BACKGROUND_COLOR, shader);
+}
+
+/// Writes the name of the screenshot viewed in full screen and when it was taken at the top of the screen
+pub fn draw_screenshot_caption(name: &str, timestamp: &str, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    let line = format!("{} - {}", name, timestamp);
+    let height = BUTTON_HEIGHT * GUI_SCALING;
+    text_renderer.draw_rect(0.0, WINDOW_HEIGHT as f32 - height, WINDOW_WIDTH as f32, height, BACKGROUND_COLOR, shader);
+    draw_centered_text(&line, WINDOW_WIDTH as f32 / 2.0, WINDOW_HEIGHT as f32 - height / 2.0, text_renderer, shader);
+}
+
+/// Fills the screen with squares of two shades of brown, panning to the left as time goes
+fn draw_main_menu_background(time: f32, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    let size = BACKGROUND_TILE_SIZE * GUI_SCALING;
+    let offset = (time * BACKGROUND_PAN_SPEED * GUI_SCALING) % (2.0 * size);
+    let columns = (WINDOW_WIDTH as f32 / size).ceil() as i32 + 2;
+    let rows = (WINDOW_HEIGHT as f32 / size).ceil() as i32;
+    for row in 0..rows {
+        for column in 0..columns {
+            let color = MAIN_MENU_BACKGROUND_COLORS[((row + column) % 2) as usize];
+            text_renderer.draw_rect(column as f32 * size - offset, row as f32 * size, size, size, color, shader);
+        }
+    }
+}
+
+/// Draws the name of the game in large letters above the buttons of the title screen
+pub fn draw_title_screen(time: f32, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    draw_main_menu_background(time, text_renderer, shader);
+    let title_scale = 4.0 * GUI_SCALING;
+    let title = "MeinKraft";
+    text_renderer.draw(title, (WINDOW_WIDTH as f32 - TextRenderer::text_width(title, title_scale)) / 2.0,
+                       WINDOW_HEIGHT as f32 / 2.0 + 50.0 * GUI_SCALING, title_scale, TEXT_COLOR, shader);
+    for button in TITLE_BUTTONS.iter() {
+        draw_button(button.label(), button.position(), text_renderer, shader);
+    }
+}
+
+/// Lists WORLDS_VISIBLE_ROWS worlds from the `scroll`th one, with their name, seed and the day they were last played
+pub fn draw_world_selection(worlds: &[WorldInfo], scroll: usize, time: f32, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    draw_main_menu_background(time, text_renderer, shader);
+    draw_centered_text("Select World", WINDOW_WIDTH as f32 / 2.0, WINDOW_HEIGHT as f32 / 2.0 + 3.5 * ROW_SPACING * GUI_SCALING, text_renderer, shader);
+
+    if worlds.is_empty() {
+        let (center_x, center_y) = world_row_position(0);
+        draw_centered_text("No saved worlds", center_x, center_y, text_renderer, shader);
+    }
+
+    let (width, height) = (BUTTON_WIDTH * GUI_SCALING, WORLD_ROW_HEIGHT * GUI_SCALING);
+    let margin = 4.0 * GUI_SCALING;
+    for (row, world) in worlds.iter().skip(scroll).take(WORLDS_VISIBLE_ROWS).enumerate() {
+        let (center_x, center_y) = world_row_position(row);
+        let (left, bottom) = (center_x - width / 2.0, center_y - height / 2.0);
+        text_renderer.draw_rect(left, bottom, width, height, BUTTON_COLOR, shader);
+        text_renderer.draw(&world.name, left + margin, center_y + 2.0 * GUI_SCALING, GUI_SCALING, TEXT_COLOR, shader);
+        let details = format!("Seed {} - {}", world.seed, world.last_played_date());
+        text_renderer.draw(&details, left + margin, bottom + margin, GUI_SCALING, DETAILS_COLOR, shader);
+    }
+
+    for button in WORLD_SELECTION_BUTTONS.iter() {
+        draw_button(button.label(), button.position(), text_renderer, shader);
+    }
+}
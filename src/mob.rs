@@ -0,0 +1,399 @@
+use std::sync::Arc;
+
+use nalgebra_glm::{Vec3, vec2, vec3};
+use num_traits::Zero;
+use parking_lot::RwLock;
+use rand::random;
+
+use crate::aabb::{AABB, get_block_aabb};
+use crate::chunk::BlockID;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{BLAZE_FOLLOW_RANGE, BLAZE_WALKING_SPEED, COW_WALKING_SPEED, GRAVITY, IRON_GOLEM_WALKING_SPEED, JUMP_IMPULSE, KNOCKBACK_DAMPING, MAX_VERTICAL_VELOCITY, SLIME_BOUNCE_MIN_VELOCITY, SLIME_BOUNCE_RESTITUTION, VILLAGER_WALKING_SPEED, ZOMBIE_FOLLOW_RANGE, ZOMBIE_WALKING_SPEED};
+use crate::input::InputCache;
+use crate::loot::{BLAZE_DROPS, COW_DROPS, IRON_GOLEM_DROPS, LootTable, VILLAGER_DROPS, ZOMBIE_DROPS};
+use crate::pathfinding::is_walkable;
+
+/// Behaviour of a mob, called every game tick after its physics.
+/// The last parameter multiplies the damage dealt by the mob, it grows with the age of the world
+pub type MobTick = Box<dyn Fn(&mut MobEntityState, &ChunkManager, &InputCache, f32) + Send + Sync>;
+
+/// Everything the behaviour of a mob can read and change
+pub struct MobEntityState {
+    pub health: f32,
+    pub max_health: f32,
+    /// Center of the bottom face of the AABB
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub aabb: AABB,
+    pub is_flying: bool,
+    pub is_on_ground: bool,
+    /// Whether the mob walked into a wall during the last tick
+    pub is_blocked: bool,
+    /// Direction the mob is facing, around the Y axis
+    pub yaw: f32,
+    /// Duration of a tick, in seconds
+    pub dt: f32,
+    /// Free to use by the behaviours, e.g. to know when to change their mind
+    pub ai_timer: f32,
+    pub walk_direction: Vec3,
+    /// Color of the placeholder box drawn for the mob
+    pub color: (f32, f32, f32),
+    /// Position drawn on screen, interpolated between the last two ticks
+    pub previous_position: Vec3,
+    pub render_position: Vec3,
+
+    /// Mobs walk towards the player along a path when they are closer than this, 0 if they don't
+    pub follow_range: f32,
+    /// Speed at which the path is followed
+    pub walk_speed: f32,
+    /// Blocks left to walk through to reach the player, the next one first
+    pub path: Vec<(i32, i32, i32)>,
+    /// Game ticks since the path was requested
+    pub path_age: u32,
+    /// The paths are computed on another thread, which leaves them there
+    pub computed_path: Arc<RwLock<Option<Vec<(i32, i32, i32)>>>>,
+    pub is_path_requested: bool,
+    /// Game ticks left before the mob can hit the player again
+    pub attack_cooldown: u32,
+    /// Horizontal push of the last hit, added to the velocity set by the behaviour until it fades out
+    pub knockback: Vec3,
+}
+
+impl MobEntityState {
+    pub fn width(&self) -> f32 {
+        self.aabb.maxs.x - self.aabb.mins.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.aabb.maxs.y - self.aabb.mins.y
+    }
+
+    fn get_colliding_block_coords(&self, chunk_manager: &ChunkManager) -> Option<Vec3> {
+        let mins = &self.aabb.mins;
+        let maxs = &self.aabb.maxs;
+
+        for y in mins.y.floor() as i32..=maxs.y.floor() as i32 {
+            for z in mins.z.floor() as i32..=maxs.z.floor() as i32 {
+                for x in mins.x.floor() as i32..=maxs.x.floor() as i32 {
+                    if chunk_manager.is_solid_block_at(x, y, z) {
+                        let block_coords = vec3(x as f32, y as f32, z as f32);
+                        if self.aabb.intersects(&get_block_aabb(&block_coords)) {
+                            return Some(block_coords);
+                        }
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Pushes the AABB out of `block` it moved into along `v`, returns whether the mob landed on it.
+    /// A slime block bounces the mob back up instead
+    fn separate_from_block(&mut self, v: &Vec3, block_coords: &Vec3, block: BlockID) -> bool {
+        let block_aabb = get_block_aabb(block_coords);
+        let size = self.aabb.maxs - self.aabb.mins;
+        let mut landed = false;
+
+        if !v.x.is_zero() {
+            self.aabb.mins.x = if v.x < 0.0 { block_aabb.maxs.x } else { block_aabb.mins.x - size.x };
+            self.velocity.x = 0.0;
+            self.knockback.x = 0.0;
+            self.is_blocked = true;
+        }
+        if !v.y.is_zero() {
+            self.aabb.mins.y = if v.y < 0.0 { block_aabb.maxs.y } else { block_aabb.mins.y - size.y };
+            landed = v.y < 0.0;
+            if landed && block == BlockID::SlimeBlock && self.velocity.y < -SLIME_BOUNCE_MIN_VELOCITY {
+                self.velocity.y = -self.velocity.y * SLIME_BOUNCE_RESTITUTION;
+                landed = false;
+            } else {
+                self.velocity.y = 0.0;
+            }
+        }
+        if !v.z.is_zero() {
+            self.aabb.mins.z = if v.z < 0.0 { block_aabb.maxs.z } else { block_aabb.mins.z - size.z };
+            self.velocity.z = 0.0;
+            self.knockback.z = 0.0;
+            self.is_blocked = true;
+        }
+        self.aabb.maxs = self.aabb.mins + size;
+        landed
+    }
+
+    /// Block the feet of the mob are in
+    pub fn block_position(&self) -> (i32, i32, i32) {
+        (self.position.x.floor() as i32, self.position.y.floor() as i32, self.position.z.floor() as i32)
+    }
+
+    /// Whether the path has to be computed again because a block changed on the way
+    pub fn is_path_blocked(&self, chunk_manager: &ChunkManager) -> bool {
+        self.path.first().map_or(false, |&next| !is_walkable(next, chunk_manager))
+    }
+
+    /// Walks towards the next waypoint of the path, jumping when it is higher.
+    /// The mob stops once there are no more waypoints
+    pub fn follow_path(&mut self) {
+        let (_, y, _) = self.block_position();
+        while let Some(&(n_x, n_y, n_z)) = self.path.first() {
+            let direction = vec2(n_x as f32 + 0.5 - self.position.x, n_z as f32 + 0.5 - self.position.z);
+            if direction.norm() < 0.2 && n_y <= y {
+                self.path.remove(0);
+                continue;
+            }
+
+            let direction = direction.normalize();
+            self.velocity.x = direction.x * self.walk_speed;
+            self.velocity.z = direction.y * self.walk_speed;
+            self.yaw = direction.x.atan2(direction.y);
+            if self.is_on_ground && (n_y > y || self.is_blocked) {
+                self.velocity.y = *JUMP_IMPULSE;
+            }
+            return;
+        }
+        self.velocity.x = 0.0;
+        self.velocity.z = 0.0;
+    }
+
+    /// Applies gravity and moves the mob one axis at a time, stopping at the solid blocks
+    pub fn update_physics(&mut self, chunk_manager: &ChunkManager) {
+        let dt = self.dt;
+        if !self.is_flying {
+            self.velocity.y = (self.velocity.y + GRAVITY * dt).max(-MAX_VERTICAL_VELOCITY);
+        }
+
+        self.is_on_ground = false;
+        self.is_blocked = false;
+        let separated_axis = [
+            vec3(self.velocity.x + self.knockback.x, 0.0, 0.0),
+            vec3(0.0, 0.0, self.velocity.z + self.knockback.z),
+            vec3(0.0, self.velocity.y, 0.0)];
+
+        for v in separated_axis.iter() {
+            self.aabb.ip_translate(&(v * dt));
+            if let Some(block_coords) = self.get_colliding_block_coords(chunk_manager) {
+                let block = chunk_manager.get_block(block_coords.x as i32, block_coords.y as i32, block_coords.z as i32)
+                    .unwrap_or(BlockID::Air);
+                self.is_on_ground |= self.separate_from_block(v, &block_coords, block);
+            }
+        }
+
+        self.knockback *= 1.0 - KNOCKBACK_DAMPING;
+        if self.knockback.norm() < 0.1 {
+            self.knockback = Vec3::zeros();
+        }
+
+        let half_width = self.width() / 2.0;
+        self.position = vec3(self.aabb.mins.x + half_width, self.aabb.mins.y, self.aabb.mins.z + half_width);
+    }
+}
+
+/// The different mobs, spawned around the player by `SpawnMobs`
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum MobKind {
+    Cow,
+    Zombie,
+    /// Only comes out of the spawners of the Nether fortresses
+    Blaze,
+    /// Villagers and iron golems live in the villages, they are spawned with them
+    Villager,
+    IronGolem,
+}
+
+impl MobKind {
+    /// The mobs spawning naturally
+    pub const ALL: [MobKind; 2] = [MobKind::Cow, MobKind::Zombie];
+
+    /// Hostile mobs only spawn in the dark
+    pub fn is_hostile(&self) -> bool {
+        match self {
+            MobKind::Cow | MobKind::Villager | MobKind::IronGolem => false,
+            MobKind::Zombie | MobKind::Blaze => true,
+        }
+    }
+
+    /// Damage dealt to the player by a hit of the mob, before the difficulty multiplies it
+    pub fn attack_damage(&self) -> f32 {
+        match self {
+            MobKind::Zombie => 3.0,
+            MobKind::Blaze => 6.0,
+            MobKind::Cow | MobKind::Villager | MobKind::IronGolem => 0.0,
+        }
+    }
+
+    /// Shown on the death screen when the mob kills the player
+    pub fn death_message(&self) -> &'static str {
+        match self {
+            MobKind::Zombie => "Was slain by a zombie",
+            MobKind::Blaze => "Was burned by a blaze",
+            MobKind::Cow | MobKind::Villager | MobKind::IronGolem => "Died",
+        }
+    }
+
+    /// Experience points dropped when the mob is killed
+    pub fn xp_reward(&self) -> u32 {
+        match self {
+            MobKind::Cow => 3,
+            MobKind::Zombie => 5,
+            MobKind::Blaze => 10,
+            MobKind::Villager | MobKind::IronGolem => 0,
+        }
+    }
+
+    /// Items dropped when the mob is killed
+    pub fn loot_table(&self) -> &'static LootTable {
+        match self {
+            MobKind::Cow => &COW_DROPS,
+            MobKind::Zombie => &ZOMBIE_DROPS,
+            MobKind::Blaze => &BLAZE_DROPS,
+            MobKind::Villager => &VILLAGER_DROPS,
+            MobKind::IronGolem => &IRON_GOLEM_DROPS,
+        }
+    }
+
+    /// A new mob of this kind with its feet at `position`, the villagers and the iron golems staying around it
+    pub fn spawn(&self, position: Vec3) -> MobEntity {
+        match self {
+            MobKind::Cow => cow_mob(position),
+            MobKind::Zombie => zombie_mob(position),
+            MobKind::Blaze => blaze_mob(position),
+            MobKind::Villager => villager_mob(position, position, VILLAGER_DEFAULT_WANDER_RADIUS),
+            MobKind::IronGolem => iron_golem_mob(position, position, VILLAGER_DEFAULT_WANDER_RADIUS),
+        }
+    }
+}
+
+/// A living entity moved by `UpdateMobs`, its behaviour is given by `on_tick`
+pub struct MobEntity {
+    pub kind: MobKind,
+    pub state: MobEntityState,
+    pub on_tick: MobTick,
+    /// Loses health under the open sky during the day, unless it wears a helmet
+    pub burns_in_sunlight: bool,
+    pub wears_helmet: bool,
+}
+
+impl MobEntity {
+    pub fn new(kind: MobKind, position: Vec3, width: f32, height: f32, max_health: f32, color: (f32, f32, f32), on_tick: MobTick) -> Self {
+        let half_width = width / 2.0;
+        Self {
+            kind,
+            state: MobEntityState {
+                health: max_health,
+                max_health,
+                position,
+                velocity: Vec3::zeros(),
+                aabb: AABB::new(
+                    position - vec3(half_width, 0.0, half_width),
+                    position + vec3(half_width, height, half_width)),
+                is_flying: false,
+                is_on_ground: false,
+                is_blocked: false,
+                yaw: 0.0,
+                dt: 0.0,
+                ai_timer: 0.0,
+                walk_direction: Vec3::zeros(),
+                color,
+                previous_position: position,
+                render_position: position,
+                follow_range: 0.0,
+                walk_speed: 0.0,
+                path: Vec::new(),
+                path_age: 0,
+                computed_path: Arc::new(RwLock::new(None)),
+                is_path_requested: false,
+                attack_cooldown: 0,
+                knockback: Vec3::zeros(),
+            },
+            on_tick,
+            burns_in_sunlight: false,
+            wears_helmet: false,
+        }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.state.health <= 0.0
+    }
+}
+
+/// Walks in a random direction for 3 seconds, then stops for 2, hopping over the blocks in the way.
+/// With a home, the mob turns back towards it whenever it gets further than the given distance
+fn wander(state: &mut MobEntityState, speed: f32, home: Option<(Vec3, f32)>) {
+    let towards_home = home.and_then(|(home, radius)| {
+        let to_home = vec2(home.x - state.position.x, home.z - state.position.z);
+        if to_home.norm() > radius { Some(to_home) } else { None }
+    });
+    let is_walking_away = towards_home
+        .map_or(false, |to_home| to_home.dot(&vec2(state.walk_direction.x, state.walk_direction.z)) < 0.0);
+
+    state.ai_timer -= state.dt;
+    if state.ai_timer <= 0.0 || is_walking_away {
+        if state.walk_direction == Vec3::zeros() || is_walking_away {
+            let angle = match towards_home {
+                Some(to_home) => to_home.x.atan2(to_home.y),
+                None => random::<f32>() * std::f32::consts::PI * 2.0,
+            };
+            state.walk_direction = vec3(angle.sin(), 0.0, angle.cos());
+            state.yaw = angle;
+            state.ai_timer = 3.0;
+        } else {
+            state.walk_direction = Vec3::zeros();
+            state.ai_timer = 2.0;
+        }
+    }
+
+    state.velocity.x = state.walk_direction.x * speed;
+    state.velocity.z = state.walk_direction.z * speed;
+
+    // Hop over the blocks in the way
+    if state.is_blocked && state.is_on_ground && state.walk_direction != Vec3::zeros() {
+        state.velocity.y = *JUMP_IMPULSE;
+    }
+}
+
+/// A cow wandering around
+pub fn cow_mob(position: Vec3) -> MobEntity {
+    MobEntity::new(MobKind::Cow, position, 0.9, 1.4, 10.0, (0.4, 0.27, 0.18), Box::new(|state, _chunk_manager, _input_cache, _damage_multiplier| {
+        wander(state, COW_WALKING_SPEED, None);
+    }))
+}
+
+/// A zombie walking towards the player once they get close, `UpdateMobs` finds the way for it
+/// and moves it after its behaviour. It stands still otherwise
+pub fn zombie_mob(position: Vec3) -> MobEntity {
+    let mut zombie = MobEntity::new(MobKind::Zombie, position, 0.6, 1.95, 20.0, (0.33, 0.55, 0.3), Box::new(|state, _chunk_manager, _input_cache, _damage_multiplier| {
+        state.velocity.x = 0.0;
+        state.velocity.z = 0.0;
+    }));
+    zombie.state.follow_range = ZOMBIE_FOLLOW_RANGE;
+    zombie.state.walk_speed = ZOMBIE_WALKING_SPEED;
+    zombie.burns_in_sunlight = true;
+    zombie
+}
+
+/// A blaze, chasing the player like a zombie
+pub fn blaze_mob(position: Vec3) -> MobEntity {
+    let mut blaze = MobEntity::new(MobKind::Blaze, position, 0.6, 1.8, 20.0, (0.95, 0.65, 0.15), Box::new(|state, _chunk_manager, _input_cache, _damage_multiplier| {
+        state.velocity.x = 0.0;
+        state.velocity.z = 0.0;
+    }));
+    blaze.state.follow_range = BLAZE_FOLLOW_RANGE;
+    blaze.state.walk_speed = BLAZE_WALKING_SPEED;
+    blaze
+}
+
+/// Distance the villagers and the iron golems spawned on their own wander away from where they appeared
+const VILLAGER_DEFAULT_WANDER_RADIUS: f32 = 16.0;
+
+/// A villager wandering around, staying within `wander_radius` blocks of `home`
+pub fn villager_mob(position: Vec3, home: Vec3, wander_radius: f32) -> MobEntity {
+    MobEntity::new(MobKind::Villager, position, 0.6, 1.95, 20.0, (0.55, 0.4, 0.3), Box::new(move |state, _chunk_manager, _input_cache, _damage_multiplier| {
+        wander(state, VILLAGER_WALKING_SPEED, Some((home, wander_radius)));
+    }))
+}
+
+/// An iron golem guarding a village, slowly wandering around `home` like a villager
+pub fn iron_golem_mob(position: Vec3, home: Vec3, wander_radius: f32) -> MobEntity {
+    MobEntity::new(MobKind::IronGolem, position, 1.4, 2.7, 100.0, (0.8, 0.78, 0.74), Box::new(move |state, _chunk_manager, _input_cache, _damage_multiplier| {
+        wander(state, IRON_GOLEM_WALKING_SPEED, Some((home, wander_radius)));
+    }))
+}
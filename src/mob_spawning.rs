@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use nalgebra_glm::{Vec3, vec3};
+use rand::random;
+
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{BASE_MAX_HOSTILE_MOBS, COW_SPAWN_CAP, HOSTILE_SPAWN_MAX_LIGHT, MOB_SPAWN_ATTEMPTS, MOB_SPAWN_MAX_DISTANCE, MOB_SPAWN_MIN_DISTANCE};
+use crate::difficulty::max_hostile_mobs;
+use crate::mob::MobKind;
+
+/// How many mobs of each kind `SpawnMobs` lets live around the player
+pub struct MobSpawnManager {
+    pub max_mobs_per_type: HashMap<MobKind, u32>,
+}
+
+impl Default for MobSpawnManager {
+    fn default() -> Self {
+        let mut max_mobs_per_type = HashMap::new();
+        max_mobs_per_type.insert(MobKind::Zombie, BASE_MAX_HOSTILE_MOBS);
+        max_mobs_per_type.insert(MobKind::Cow, COW_SPAWN_CAP);
+        Self { max_mobs_per_type }
+    }
+}
+
+impl MobSpawnManager {
+    /// The caps of the hostile mobs grow with the age of the world
+    pub fn cap(&self, kind: MobKind, world_age: u64) -> u32 {
+        let cap = self.max_mobs_per_type.get(&kind).copied().unwrap_or(0);
+        if kind.is_hostile() {
+            cap * max_hostile_mobs(world_age) / BASE_MAX_HOSTILE_MOBS
+        } else {
+            cap
+        }
+    }
+}
+
+/// Whether nothing stands between the block at (x, y, z) and the sky
+pub fn is_under_sky(chunk_manager: &ChunkManager, x: i32, y: i32, z: i32) -> bool {
+    chunk_manager.topmost_solid_block(x, z).map_or(false, |top| y > top)
+}
+
+/// Light level at (x, y, z): the brightest block light plus `sky_light` for the blocks under the open sky
+pub fn light_at(chunk_manager: &ChunkManager, x: i32, y: i32, z: i32, sky_light: u8) -> u8 {
+    let (warm, cool) = chunk_manager.get_block_light(x, y, z);
+    let sky_light = if is_under_sky(chunk_manager, x, y, z) { sky_light } else { 0 };
+    warm.max(cool) + sky_light
+}
+
+/// Whether a mob can appear with its feet at (x, y, z): on an opaque block, with two blocks of air above it.
+/// Hostile mobs also need the place to be dark
+pub fn is_spawnable(kind: MobKind, (x, y, z): (i32, i32, i32), chunk_manager: &ChunkManager, sky_light: u8) -> bool {
+    let is_air = |y| chunk_manager.get_block(x, y, z).map_or(false, |block| block.is_air());
+    chunk_manager.get_block(x, y - 1, z).map_or(false, |block| block.is_opaque())
+        && is_air(y)
+        && is_air(y + 1)
+        && (!kind.is_hostile() || light_at(chunk_manager, x, y, z, sky_light) < HOSTILE_SPAWN_MAX_LIGHT)
+}
+
+/// Up to `count` random places around `center` where mobs of this kind can spawn, found in MOB_SPAWN_ATTEMPTS tries.
+/// Every try picks a column at the right distance and looks for a spot less than 16 blocks above or below the center
+pub fn find_spawn_positions(kind: MobKind, center: Vec3, count: u32, chunk_manager: &ChunkManager, sky_light: u8) -> Vec<Vec3> {
+    let mut positions = Vec::new();
+    for _ in 0..MOB_SPAWN_ATTEMPTS {
+        if positions.len() as u32 >= count {
+            break;
+        }
+
+        let angle = random::<f32>() * std::f32::consts::PI * 2.0;
+        let distance = MOB_SPAWN_MIN_DISTANCE + random::<f32>() * (MOB_SPAWN_MAX_DISTANCE - MOB_SPAWN_MIN_DISTANCE);
+        let x = (center.x + angle.sin() * distance).floor() as i32;
+        let z = (center.z + angle.cos() * distance).floor() as i32;
+        let center_y = center.y.floor() as i32;
+
+        if let Some(y) = (center_y - 16..=center_y + 16).rev()
+            .find(|&y| is_spawnable(kind, (x, y, z), chunk_manager, sky_light)) {
+            positions.push(vec3(x as f32 + 0.5, y as f32, z as f32 + 0.5));
+        }
+    }
+    positions
+}
@@ -0,0 +1,54 @@
+use std::any::Any;
+
+use crate::block_entity::BlockEntity;
+use crate::chunk::{BlockID, BlockMeta};
+use crate::chunk_manager::ChunkManager;
+
+/// Keeps track of the pulse of an observer, whether it is emitting power is in the block meta
+#[derive(Default)]
+pub struct ObserverBlockEntity {
+    pub was_powered: bool,
+    /// Game ticks since the observer saw a change, its pulse ends after OBSERVER_PULSE_TICKS
+    pub ticks_since_triggered: u8,
+}
+
+impl ObserverBlockEntity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BlockEntity for ObserverBlockEntity {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {}
+
+    fn serialize(&self) -> Vec<u8> {
+        vec![self.was_powered as u8, self.ticks_since_triggered]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The observers whose face looks at the block at (x, y, z)
+pub fn observers_watching(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> Vec<(i32, i32, i32)> {
+    (0..4)
+        .filter_map(|facing| {
+            let (dx, dz) = BlockMeta::default().with_facing(facing).facing_offset();
+            let (o_x, o_z) = (x - dx, z - dz);
+            let is_watching = chunk_manager.get_block(o_x, y, o_z) == Some(BlockID::Observer)
+                && chunk_manager.get_block_meta(o_x, y, o_z).map_or(false, |meta| meta.facing() == facing);
+            Some((o_x, y, o_z)).filter(|_| is_watching)
+        })
+        .collect()
+}
+
+/// Whether the observer at `observer` powers its neighbour `target`, only the block behind it gets its pulse
+pub fn observer_powers(meta: BlockMeta, (x, y, z): (i32, i32, i32), target: (i32, i32, i32)) -> bool {
+    let (dx, dz) = meta.facing_offset();
+    meta.is_powered() && target == (x - dx, y, z - dz)
+}
@@ -1,6 +1,6 @@
 use std::time::{Instant, Duration};
 
-use nalgebra_glm::{Mat4, Vec3, vec3, vec4};
+use nalgebra_glm::{Mat4, Vec3, Vec4, vec3, vec4};
 
 use crate::chunk_manager::ChunkManager;
 use crate::physics::{Interpolatable, Interpolator};
@@ -12,22 +12,28 @@ use rand::random;
 use crate::aabb::get_block_aabb;
 use num_traits::Zero;
 use crate::chunk::BlockID;
+use crate::constants::{FIREWORK_STAR_LIFETIME, FIREWORK_STARS, RAIN_DROP_SPEED};
+use crate::firework::unpack_color;
 use crate::types::TexturePack;
 use std::ptr::null;
 use itertools::Itertools;
 
+/// Size of the region of the block texture a block particle shows, in UV units
+const BLOCK_PARTICLE_UV_SIZE: f32 = 2.0 / 16.0;
+
+/// A fixed pool of particles. Emitting a particle reuses an inactive one,
+/// nothing is emitted while they are all alive.
 pub struct ParticleSystem {
-    _max_particles: usize,
-    particles: Vec<Particle>,
-    index_available: usize,
+    capacity: usize,
+    particle_pool: Vec<Particle>,
     last_updated: Instant,
     vao: u32,
     vbo: u32,
 }
 
 impl ParticleSystem {
-    pub fn new(max_instances: usize) -> ParticleSystem {
-        // Allocate VRAM for max_instances particles
+    pub fn new(capacity: usize) -> ParticleSystem {
+        // Allocate VRAM for capacity particles
         let mut vao = 0;
         gl_call!(gl::CreateVertexArrays(1, &mut vao));
 
@@ -41,76 +47,253 @@ impl ParticleSystem {
         gl_call!(gl::VertexArrayAttribFormat(vao, 1, 3 as i32, gl::FLOAT, gl::FALSE, (4 * std::mem::size_of::<f32>()) as u32));
         gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
 
-        // Pos and tex coords interleaved
+        // Tint
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 2));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 2, 4 as i32, gl::FLOAT, gl::FALSE, (7 * std::mem::size_of::<f32>()) as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 2, 0));
+
+        // Pos, tex coords and tint interleaved
         let vbo = {
             let mut vbo = 0;
             gl_call!(gl::CreateBuffers(1, &mut vbo));
             gl_call!(gl::NamedBufferData(vbo,
-                    (max_instances * 6 * 7 * std::mem::size_of::<f32>() as usize) as isize,
+                    (capacity * 6 * 11 * std::mem::size_of::<f32>() as usize) as isize,
                     null(),
                     gl::DYNAMIC_DRAW));
-            gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (7 * std::mem::size_of::<f32>()) as i32));
+            gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (11 * std::mem::size_of::<f32>()) as i32));
             vbo
         };
 
         ParticleSystem {
-            _max_particles: max_instances,
-            particles: {
+            capacity,
+            particle_pool: {
                 let mut vec = Vec::new();
-                vec.resize_with(max_instances, Particle::default);
+                vec.resize_with(capacity, Particle::default);
                 vec
             },
-            index_available: max_instances - 1,
             last_updated: Instant::now(),
             vao,
             vbo,
         }
     }
 
-    pub fn emit(&mut self, particle_props: &ParticleProps, uv_map: &TexturePack, block: BlockID) {
-        let get_texture_coords = |uv: (f32, f32, f32, f32), layer: f32| {
-            (&[
-                uv.0, uv.1, layer,
-                uv.2, uv.1, layer,
-                uv.2, uv.3, layer,
-                uv.2, uv.3, layer,
-                uv.0, uv.3, layer,
-                uv.0, uv.1, layer,
-            ]).to_vec()
+    /// Activates an inactive particle of the pool.
+    /// Returns false if every particle is already in use.
+    pub fn emit(&mut self, particle_props: &ParticleProps, tex_coords: Vec<f32>, tint: Vec4) -> bool {
+        let particle = match self.particle_pool.iter_mut().find(|p| !p.active) {
+            Some(particle) => particle,
+            None => return false,
         };
 
-        self.particles[self.index_available] = Particle {
-            active: true,
-            physics_properties: Interpolator::new(1. / 30., ParticlePhysicsProperties {
-                position: particle_props.position,
-                velocity: particle_props.velocity,
-                acceleration: particle_props.acceleration,
-            }),
-            tex_coords: {
-                let uvx = random::<f32>();
-                let uvy = random::<f32>();
-                let uv = (uvx, uvy, uvx + 0.2, uvy + 0.2);
-
-                get_texture_coords(uv, uv_map.get(&block).unwrap().get_uv_of_every_face().0 as f32)
-                // get_texture_coords((0.0, 0.0, 1.0, 1.0), uv_map.get(&block).unwrap().get_uv_of_every_face().0 as f32)
-            },
-            scale: particle_props.scale,
-            _life_time: particle_props.life_time,
-            life_remaining: particle_props.life_time,
+        particle.active = true;
+        particle.physics_properties = Interpolator::new(1. / 30., ParticlePhysicsProperties {
+            position: particle_props.position,
+            velocity: particle_props.velocity,
+            acceleration: particle_props.acceleration,
+        });
+        particle.tex_coords = tex_coords;
+        particle.tint = tint;
+        particle.scale = particle_props.scale;
+        particle._life_time = particle_props.life_time;
+        particle.life_remaining = particle_props.life_time;
+        true
+    }
+
+    /// Emits `count` particles flying away from the center of the block at `position`.
+    /// Each one shows a tiny random region of one of the faces of the block, which already gives it its color.
+    pub fn emit_block_particles(&mut self, block: BlockID, position: Vec3, count: u8, texture_pack: &TexturePack) {
+        let layers = match texture_pack.get(&block) {
+            Some(faces) => {
+                let (front, back, top, bottom, left, right) = faces.get_uv_of_every_face();
+                [front, back, top, bottom, left, right]
+            }
+            None => return,
+        };
+        let block_center = position + vec3(0.5, 0.5, 0.5);
+
+        for _ in 0..count {
+            let layer = layers[random::<usize>() % layers.len()];
+            let u = random::<f32>() * (1.0 - BLOCK_PARTICLE_UV_SIZE);
+            let v = random::<f32>() * (1.0 - BLOCK_PARTICLE_UV_SIZE);
+
+            let particle_pos = position + vec3(random::<f32>(), random::<f32>(), random::<f32>());
+            let emitted = self.emit(&ParticleProps {
+                position: particle_pos,
+                velocity: {
+                    let from_center = particle_pos - block_center;
+                    let vx = from_center.x * 5.0 + 4.0 * random::<f32>() - 2.0;
+                    let vy = from_center.y * 10.0 + 4.0 * random::<f32>() - 2.0;
+                    let vz = from_center.z * 5.0 + 4.0 * random::<f32>() - 2.0;
+                    vec3(vx, vy, vz)
+                },
+                acceleration: vec3(0.0, -30.0, 0.0),
+                life_time: Duration::from_millis(100 + random::<u64>() % 750),
+                scale: {
+                    let size = 0.1 + random::<f32>() * 1.5 / 10.0;
+                    Vec3::new(size, size, size)
+                },
+            }, quad_tex_coords(
+                (u, v, u + BLOCK_PARTICLE_UV_SIZE, v + BLOCK_PARTICLE_UV_SIZE),
+                layer as f32,
+            ), vec4(1.0, 1.0, 1.0, 1.0));
+
+            if !emitted {
+                break;
+            }
+        }
+    }
+
+    /// Emits `count` small bubbles rising from `position`, drawn with a pale piece of the water texture
+    pub fn emit_bubble_particles(&mut self, position: Vec3, count: u8, texture_pack: &TexturePack) {
+        let layer = match texture_pack.get(&BlockID::Water) {
+            Some(faces) => faces.get_uv_of_every_face().2,
+            None => return,
+        };
+
+        for _ in 0..count {
+            let offset = vec3(random::<f32>() - 0.5, random::<f32>() * 0.2, random::<f32>() - 0.5) * 0.4;
+            let emitted = self.emit(&ParticleProps {
+                position: position + offset,
+                velocity: vec3(offset.x, 1.0 + random::<f32>(), offset.z),
+                acceleration: vec3(0.0, 2.0, 0.0),
+                life_time: Duration::from_millis(400 + random::<u64>() % 400),
+                scale: {
+                    let size = 0.05 + random::<f32>() * 0.05;
+                    Vec3::new(size, size, size)
+                },
+            }, quad_tex_coords(
+                (0.0, 0.0, BLOCK_PARTICLE_UV_SIZE, BLOCK_PARTICLE_UV_SIZE),
+                layer as f32,
+            ), vec4(1.6, 1.8, 2.0, 0.8));
+
+            if !emitted {
+                break;
+            }
+        }
+    }
+
+    /// Emits a drop of lava falling from the bottom face of the block at `position`
+    pub fn emit_lava_drip_particle(&mut self, position: Vec3, texture_pack: &TexturePack) {
+        let layer = match texture_pack.get(&BlockID::Lava) {
+            Some(faces) => faces.get_uv_of_every_face().3,
+            None => return,
+        };
+
+        let offset = vec3(0.1 + random::<f32>() * 0.8, -0.05, 0.1 + random::<f32>() * 0.8);
+        self.emit(&ParticleProps {
+            position: position + offset,
+            velocity: Vec3::zero(),
+            acceleration: vec3(0.0, -20.0, 0.0),
+            life_time: Duration::from_millis(1000 + random::<u64>() % 500),
+            scale: Vec3::new(0.06, 0.06, 0.06),
+        }, quad_tex_coords(
+            (0.0, 0.0, BLOCK_PARTICLE_UV_SIZE, BLOCK_PARTICLE_UV_SIZE),
+            layer as f32,
+        ), vec4(1.5, 0.9, 0.4, 1.0));
+    }
+
+    /// Emits a puff of smoke rising from the campfire at `position` and drifting with the wind
+    pub fn emit_campfire_smoke_particle(&mut self, position: Vec3, texture_pack: &TexturePack) {
+        let layer = match texture_pack.get(&BlockID::IronBlock) {
+            Some(faces) => faces.get_uv_of_every_face().2,
+            None => return,
+        };
+
+        let offset = vec3(0.3 + random::<f32>() * 0.4, 1.0, 0.3 + random::<f32>() * 0.4);
+        let gray = 0.3 + random::<f32>() * 0.2;
+        self.emit(&ParticleProps {
+            position: position + offset,
+            velocity: vec3(random::<f32>() * 0.2 - 0.1, 1.0, random::<f32>() * 0.2 - 0.1),
+            acceleration: vec3(0.1, 0.2, 0.0),
+            life_time: Duration::from_millis(3000 + random::<u64>() % 2000),
+            scale: Vec3::new(0.2, 0.2, 0.2),
+        }, quad_tex_coords(
+            (0.0, 0.0, BLOCK_PARTICLE_UV_SIZE, BLOCK_PARTICLE_UV_SIZE),
+            layer as f32,
+        ), vec4(gray, gray, gray, 0.7));
+    }
+
+    /// Emits a drop of rain falling straight down from `position` for `fall_duration` seconds,
+    /// drawn as a thin streak of the water texture
+    pub fn emit_rain_drop_particle(&mut self, position: Vec3, fall_duration: f32, texture_pack: &TexturePack) -> bool {
+        let layer = match texture_pack.get(&BlockID::Water) {
+            Some(faces) => faces.get_uv_of_every_face().2,
+            None => return false,
         };
 
-        self.index_available = if self.index_available == 0 {
-            self.particles.len() - 1
-        } else {
-            self.index_available - 1
+        self.emit(&ParticleProps {
+            position,
+            velocity: vec3(0.0, -RAIN_DROP_SPEED, 0.0),
+            acceleration: Vec3::zero(),
+            life_time: Duration::from_secs_f32(fall_duration),
+            scale: Vec3::new(0.02, 0.4, 0.02),
+        }, quad_tex_coords(
+            (0.0, 0.0, BLOCK_PARTICLE_UV_SIZE, BLOCK_PARTICLE_UV_SIZE),
+            layer as f32,
+        ), vec4(1.2, 1.4, 1.8, 0.6))
+    }
+
+    /// Emits a spark left behind by a rising firework rocket
+    pub fn emit_firework_trail_particle(&mut self, position: Vec3, texture_pack: &TexturePack) {
+        let layer = match texture_pack.get(&BlockID::IronBlock) {
+            Some(faces) => faces.get_uv_of_every_face().2,
+            None => return,
+        };
+
+        self.emit(&ParticleProps {
+            position,
+            velocity: vec3(random::<f32>() - 0.5, -1.0, random::<f32>() - 0.5),
+            acceleration: vec3(0.0, -5.0, 0.0),
+            life_time: Duration::from_millis(200 + random::<u64>() % 300),
+            scale: Vec3::new(0.05, 0.05, 0.05),
+        }, quad_tex_coords(
+            (0.0, 0.0, BLOCK_PARTICLE_UV_SIZE, BLOCK_PARTICLE_UV_SIZE),
+            layer as f32,
+        ), vec4(1.8, 1.6, 1.2, 1.0));
+    }
+
+    /// Emits the stars of a firework bursting at `position`, flying away in every direction and falling.
+    /// Each star takes one of the `colors`, packed as 0xRRGGBBAA
+    pub fn emit_firework_burst(&mut self, position: Vec3, colors: &[u32], texture_pack: &TexturePack) {
+        let layer = match texture_pack.get(&BlockID::IronBlock) {
+            Some(faces) => faces.get_uv_of_every_face().2,
+            None => return,
         };
+        if colors.is_empty() {
+            return;
+        }
+
+        for _ in 0..FIREWORK_STARS {
+            // A random point of the unit sphere
+            let y = 2.0 * random::<f32>() - 1.0;
+            let angle = random::<f32>() * std::f32::consts::PI * 2.0;
+            let radius = (1.0 - y * y).sqrt();
+            let direction = vec3(radius * angle.cos(), y, radius * angle.sin());
+
+            let color = unpack_color(colors[random::<usize>() % colors.len()]);
+            let emitted = self.emit(&ParticleProps {
+                position,
+                velocity: direction * (8.0 + 2.0 * random::<f32>()),
+                acceleration: vec3(0.0, -10.0, 0.0),
+                life_time: Duration::from_secs_f32(FIREWORK_STAR_LIFETIME),
+                scale: Vec3::new(0.15, 0.15, 0.15),
+            }, quad_tex_coords(
+                (0.0, 0.0, BLOCK_PARTICLE_UV_SIZE, BLOCK_PARTICLE_UV_SIZE),
+                layer as f32,
+            ), vec4(color.x * 1.6, color.y * 1.6, color.z * 1.6, color.w));
+
+            if !emitted {
+                break;
+            }
+        }
     }
 
+    /// Returns the RGBA color of the texel at (u, v) of a layer of the array texture
     pub fn update_all_particles(&mut self, time: Instant, chunk_manager: &ChunkManager) {
         let time_passed = time.saturating_duration_since(self.last_updated);
         self.last_updated = time;
 
-        for p in &mut self.particles.iter_mut().filter(|p| p.active) {
+        for p in &mut self.particle_pool.iter_mut().filter(|p| p.active) {
             if let Some(life_remaining) = p.life_remaining.checked_sub(time_passed) {
                 p.life_remaining = life_remaining;
             } else {
@@ -122,11 +305,11 @@ impl ParticleSystem {
     }
 
     pub fn render_all_particles(&mut self, _shader: &mut ShaderProgram, view_matrix: &Mat4, projection_matrix: &Mat4) {
-        let mut vbo_data: Vec<f32> = Vec::new();
+        let mut vbo_data: Vec<f32> = Vec::with_capacity(self.capacity * 6 * 11);
 
         // Prepare the VBOs
         let mut active_particles = 0;
-        for particle in self.particles.iter().filter(|p| p.active) {
+        for particle in self.particle_pool.iter().filter(|p| p.active) {
             active_particles += 1;
             let state = particle.physics_properties.get_interpolated_state();
             let model_matrix = {
@@ -166,6 +349,7 @@ impl ParticleSystem {
                     1.0);
                 vbo_data.extend(&(mvp * pos));
                 vbo_data.extend(tex);
+                vbo_data.extend(&particle.tint);
             }
         }
 
@@ -233,7 +417,7 @@ impl Interpolator<ParticlePhysicsProperties> {
 
                 let mut colliding_block_aabb = None;
                 if let Some(block) = chunk_manager.get_block(containing_block.x, containing_block.y, containing_block.z) {
-                    if !block.is_air() {
+                    if block.is_solid() {
                         let block_aabb = get_block_aabb(&vec3(
                             containing_block.x as f32,
                             containing_block.y as f32,
@@ -296,6 +480,7 @@ struct Particle {
     active: bool,
     physics_properties: Interpolator<ParticlePhysicsProperties>,
     tex_coords: Vec<f32>,
+    tint: Vec4,
     scale: Vec3,
     _life_time: Duration,
     life_remaining: Duration,
@@ -307,6 +492,7 @@ impl Default for Particle {
             active: false,
             physics_properties: Interpolator::default(),
             tex_coords: Vec::default(),
+            tint: vec4(1.0, 1.0, 1.0, 1.0),
             scale: Vec3::zero(),
             _life_time: Default::default(),
             life_remaining: Default::default(),
@@ -314,39 +500,13 @@ impl Default for Particle {
     }
 }
 
-impl ParticleSystem {
-    pub fn spawn_block_breaking_particles(&mut self, pos: Vec3, uv_map: &TexturePack, block: BlockID) {
-        let block_center = pos + vec3(0.5, 0.5, 0.5);
-        let half_spacing = 1.0 / 8.0;
-
-        for x in 0..4 {
-            for y in 0..4 {
-                for z in 0..4 {
-                    let particle_pos = pos
-                        + vec3(half_spacing, half_spacing, half_spacing)
-                        + vec3(
-                        x as f32 * 2.0 * half_spacing,
-                        y as f32 * 2.0 * half_spacing,
-                        z as f32 * 2.0 * half_spacing);
-
-                    self.emit(&ParticleProps {
-                        position: particle_pos,
-                        velocity: {
-                            let from_center = particle_pos - block_center;
-                            let vx = from_center.x * 5.0 + 4.0 * random::<f32>() - 2.0;
-                            let vy = from_center.y * 10.0 + 4.0 * random::<f32>() - 2.0;
-                            let vz = from_center.z * 5.0 + 4.0 * random::<f32>() - 2.0;
-                            vec3(vx, vy, vz)
-                        },
-                        acceleration: vec3(0.0, -30.0, 0.0),
-                        life_time: Duration::from_millis(100 + random::<u64>() % 750),
-                        scale: {
-                            let size = 0.1 + random::<f32>() * 1.5 / 10.0;
-                            Vec3::new(size, size, size)
-                        },
-                    }, &uv_map, block);
-                }
-            }
-        }
-    }
-}
\ No newline at end of file
+fn quad_tex_coords(uv: (f32, f32, f32, f32), layer: f32) -> Vec<f32> {
+    (&[
+        uv.0, uv.1, layer,
+        uv.2, uv.1, layer,
+        uv.2, uv.3, layer,
+        uv.2, uv.3, layer,
+        uv.0, uv.3, layer,
+        uv.0, uv.1, layer,
+    ]).to_vec()
+}
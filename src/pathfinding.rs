@@ -0,0 +1,180 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use parking_lot::RwLock;
+use rand::Rng;
+
+use crate::chunk_manager::ChunkManager;
+use crate::constants::NAV_CACHE_MAX_SIZE;
+
+/// Whether a mob two blocks high can stand at (x, y, z): on a solid block, with its body and head in the air
+pub fn is_walkable((x, y, z): (i32, i32, i32), chunk_manager: &ChunkManager) -> bool {
+    chunk_manager.is_solid_block_at(x, y - 1, z)
+        && !chunk_manager.is_solid_block_at(x, y, z)
+        && !chunk_manager.is_solid_block_at(x, y + 1, z)
+}
+
+/// Remembers which blocks the mobs can stand at, so that the searches don't look at the same blocks again.
+/// `ProcessBlockUpdates` forgets the blocks around the changed ones
+#[derive(Default)]
+pub struct NavCache {
+    walkable: RwLock<HashMap<(i32, i32, i32), bool>>,
+}
+
+impl NavCache {
+    /// Same as `is_walkable`, the blocks of the chunks not loaded yet aren't cached
+    pub fn is_walkable(&self, position: (i32, i32, i32), chunk_manager: &ChunkManager) -> bool {
+        if let Some(&walkable) = self.walkable.read().get(&position) {
+            return walkable;
+        }
+        let walkable = is_walkable(position, chunk_manager);
+        let (x, y, z) = position;
+        if chunk_manager.get_block(x, y - 1, z).is_some() && chunk_manager.get_block(x, y + 1, z).is_some() {
+            let mut cache = self.walkable.write();
+            // The mobs roam around the player, the blocks they left long ago aren't worth keeping
+            if cache.len() >= NAV_CACHE_MAX_SIZE {
+                cache.clear();
+            }
+            cache.insert(position, walkable);
+        }
+        walkable
+    }
+
+    /// Must be called after the block at (x, y, z) changed, the mobs could stand on it, in it or under it
+    pub fn invalidate(&self, (x, y, z): (i32, i32, i32)) {
+        let mut cache = self.walkable.write();
+        for dy in -1..=1 {
+            cache.remove(&(x, y + dy, z));
+        }
+    }
+}
+
+/// The first walkable block at most `max_drop` blocks below `position`, e.g. under a jumping player
+pub fn ground_below((x, y, z): (i32, i32, i32), chunk_manager: &ChunkManager, max_drop: i32) -> Option<(i32, i32, i32)> {
+    (0..=max_drop)
+        .map(|drop| (x, y - drop, z))
+        .find(|&position| is_walkable(position, chunk_manager))
+}
+
+/// The walkable blocks a mob can reach in one step from `(x, y, z)`: next to it on the same level,
+/// one block higher by jumping or one block lower by stepping down. It only moves diagonally on flat ground,
+/// when both blocks it passes between are walkable so that it doesn't cut through the corner of a wall
+fn neighbours((x, y, z): (i32, i32, i32), chunk_manager: &ChunkManager) -> Vec<(i32, i32, i32)> {
+    let nav_cache = &chunk_manager.nav_cache;
+    let mut neighbours = Vec::new();
+    for &(dx, dz) in &[(1, 0), (-1, 0), (0, 1), (0, -1)] {
+        let (n_x, n_z) = (x + dx, z + dz);
+        if nav_cache.is_walkable((n_x, y, n_z), chunk_manager) {
+            neighbours.push((n_x, y, n_z));
+        } else if nav_cache.is_walkable((n_x, y + 1, n_z), chunk_manager) {
+            // The head must not hit a ceiling while jumping
+            if !chunk_manager.is_solid_block_at(x, y + 2, z) {
+                neighbours.push((n_x, y + 1, n_z));
+            }
+        } else if nav_cache.is_walkable((n_x, y - 1, n_z), chunk_manager) {
+            // The head must get past the block in front before stepping down
+            if !chunk_manager.is_solid_block_at(n_x, y + 1, n_z) {
+                neighbours.push((n_x, y - 1, n_z));
+            }
+        }
+    }
+    for &(dx, dz) in &[(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+        if nav_cache.is_walkable((x + dx, y, z + dz), chunk_manager)
+            && nav_cache.is_walkable((x + dx, y, z), chunk_manager)
+            && nav_cache.is_walkable((x, y, z + dz), chunk_manager) {
+            neighbours.push((x + dx, y, z + dz));
+        }
+    }
+    neighbours
+}
+
+fn distance((x0, y0, z0): (i32, i32, i32), (x1, y1, z1): (i32, i32, i32)) -> f32 {
+    let (dx, dy, dz) = ((x1 - x0) as f32, (y1 - y0) as f32, (z1 - z0) as f32);
+    (dx * dx + dy * dy + dz * dz).sqrt()
+}
+
+/// A block waiting to be visited, the one with the lowest estimated length of the whole path comes first
+struct OpenNode {
+    estimated_cost: f32,
+    position: (i32, i32, i32),
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimated_cost.partial_cmp(&self.estimated_cost).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for OpenNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimated_cost == other.estimated_cost
+    }
+}
+
+impl Eq for OpenNode {}
+
+/// Shortest walk from `start` to `goal` found with A*, guided by the straight line distance to the goal.
+/// The waypoints are the blocks the feet of the mob go through, `start` excluded and `goal` included.
+/// Returns None when the goal isn't reached after visiting `max_nodes` blocks
+pub fn astar_path(start: (i32, i32, i32), goal: (i32, i32, i32), chunk_manager: &ChunkManager, max_nodes: usize) -> Option<Vec<(i32, i32, i32)>> {
+    if start == goal {
+        return Some(Vec::new());
+    }
+
+    // Every reached block with the one it was reached from and the length of the walk to it
+    let mut came_from = HashMap::new();
+    came_from.insert(start, (start, 0.0));
+    let mut open = BinaryHeap::new();
+    open.push(OpenNode { estimated_cost: distance(start, goal), position: start });
+
+    let mut visited = 0;
+    while let Some(OpenNode { position, .. }) = open.pop() {
+        if position == goal {
+            let mut path = Vec::new();
+            let mut current = goal;
+            while current != start {
+                path.push(current);
+                current = came_from[&current].0;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        visited += 1;
+        if visited > max_nodes {
+            return None;
+        }
+
+        let cost = came_from[&position].1;
+        for neighbour in neighbours(position, chunk_manager) {
+            let neighbour_cost = cost + distance(position, neighbour);
+            if came_from.get(&neighbour).map_or(false, |&(_, known_cost)| known_cost <= neighbour_cost) {
+                continue;
+            }
+            came_from.insert(neighbour, (position, neighbour_cost));
+            open.push(OpenNode { estimated_cost: neighbour_cost + distance(neighbour, goal), position: neighbour });
+        }
+    }
+    None
+}
+
+/// A few random steps from `start`, for the mobs that can't find their way to the player
+pub fn wander_path(start: (i32, i32, i32), chunk_manager: &ChunkManager, steps: usize, rng: &mut impl Rng) -> Vec<(i32, i32, i32)> {
+    let mut path = Vec::new();
+    let mut position = start;
+    for _ in 0..steps {
+        let neighbours = neighbours(position, chunk_manager);
+        if neighbours.is_empty() {
+            break;
+        }
+        position = neighbours[rng.gen_range(0, neighbours.len())];
+        path.push(position);
+    }
+    path
+}
@@ -2,11 +2,39 @@ use std::time;
 
 use std::time::Instant;
 
+use crate::constants::{MAX_TIME_SCALE, MIN_TIME_SCALE, PHYSICS_TICKRATE};
+
 /// Fixed timestep physics simulation using the following method:
 /// https://gafferongames.com/post/fix_your_timestep/
 /// With this method, the physics are always deterministic and work independently
 /// of the performance of the game
 
+/// How fast the game runs, changed at runtime for slow motion and fast forward
+pub struct PhysicsConfig {
+    /// Duration of a tick of the player physics, in seconds of game time
+    pub physics_dt: f32,
+    /// Seconds of game time per real second, the global timer runs this much faster
+    pub time_scale: f32,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            physics_dt: 1.0 / PHYSICS_TICKRATE,
+            time_scale: 1.0,
+        }
+    }
+}
+
+impl PhysicsConfig {
+    /// Multiplies the speed of the game by `factor`, between MIN_TIME_SCALE and MAX_TIME_SCALE.
+    /// The physics ticks get longer with it, so they keep happening PHYSICS_TICKRATE times per real second
+    pub fn scale_time(&mut self, factor: f32) {
+        self.time_scale = (self.time_scale * factor).max(MIN_TIME_SCALE).min(MAX_TIME_SCALE);
+        self.physics_dt = self.time_scale / PHYSICS_TICKRATE;
+    }
+}
+
 pub trait Interpolatable {
     fn interpolate(&self, alpha: f32, other: &Self) -> Self;
 }
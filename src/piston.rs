@@ -0,0 +1,236 @@
+use std::any::Any;
+use std::time::Instant;
+
+use crate::block_entity::BlockEntity;
+use crate::chunk::{BlockID, BlockMeta};
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{PISTON_MOVE_TICKS, PISTON_PUSH_LIMIT, TICKS_PER_SECOND};
+use crate::types::BlockEntityRegistry;
+
+/// A block carried by a moving piston, drawn between its two positions until it reaches `destination`
+pub struct MovingBlock {
+    pub block: BlockID,
+    pub meta: BlockMeta,
+    pub destination: (i32, i32, i32),
+    pub block_entity: Option<Box<dyn BlockEntity>>,
+}
+
+/// The arm of a piston (and the blocks it carries) on its way.
+/// `PistonMoving` blocks hold the destinations until the move is over
+pub struct PistonMoving {
+    pub blocks: Vec<MovingBlock>,
+    /// Offset the blocks are moving by, a neighbour of the origin
+    pub direction: (i32, i32, i32),
+    pub is_extending: bool,
+    pub ticks: u32,
+    pub started: Instant,
+}
+
+impl PistonMoving {
+    /// Fraction of the move already done, from 0 to 1
+    pub fn progress(&self, now: Instant) -> f32 {
+        let duration = PISTON_MOVE_TICKS as f32 / TICKS_PER_SECOND;
+        (now.saturating_duration_since(self.started).as_secs_f32() / duration).min(1.0)
+    }
+}
+
+/// The state of a piston: the power it received during the last tick and its arm, if it is moving
+#[derive(Default)]
+pub struct PistonBlockEntity {
+    pub is_powered: bool,
+    pub moving: Option<PistonMoving>,
+}
+
+impl PistonBlockEntity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How far the arm sticks out of the piston, from 0 to 1
+    pub fn extension(&self, meta: BlockMeta, now: Instant) -> f32 {
+        match &self.moving {
+            Some(moving) if moving.is_extending => moving.progress(now),
+            Some(moving) => 1.0 - moving.progress(now),
+            None if meta.is_extended() => 1.0,
+            None => 0.0,
+        }
+    }
+}
+
+impl BlockEntity for PistonBlockEntity {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {}
+
+    fn serialize(&self) -> Vec<u8> {
+        vec![self.is_powered as u8]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The head at the end of an extended piston, it knows the piston it belongs to
+pub struct PistonArm {
+    pub base: (i32, i32, i32),
+}
+
+impl BlockEntity for PistonArm {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {}
+
+    fn serialize(&self) -> Vec<u8> {
+        let (x, y, z) = self.base;
+        let mut bytes = Vec::new();
+        bytes.extend(&x.to_le_bytes());
+        bytes.extend(&y.to_le_bytes());
+        bytes.extend(&z.to_le_bytes());
+        bytes
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Offset to the block in front of a piston, pistons face one of the 4 horizontal directions
+pub fn piston_direction(meta: BlockMeta) -> (i32, i32, i32) {
+    let (dx, dz) = meta.facing_offset();
+    (dx, 0, dz)
+}
+
+#[inline]
+fn offset((x, y, z): (i32, i32, i32), (dx, dy, dz): (i32, i32, i32), distance: i32) -> (i32, i32, i32) {
+    (x + dx * distance, y + dy * distance, z + dz * distance)
+}
+
+/// Whether a piston can push or pull the block at `position`
+fn is_movable(chunk_manager: &ChunkManager, block: BlockID, (x, y, z): (i32, i32, i32)) -> bool {
+    if block.is_immovable() {
+        return false;
+    }
+    // The arm of an extended piston holds it in place
+    !(block.is_piston() && chunk_manager.get_block_meta(x, y, z).map_or(true, |meta| meta.is_extended()))
+}
+
+/// Takes the block at `position` out of the world, with its block entity, to move it to `destination`
+fn lift_block(chunk_manager: &ChunkManager, block_entity_registry: &mut BlockEntityRegistry,
+              position: (i32, i32, i32), destination: (i32, i32, i32)) -> MovingBlock {
+    let (x, y, z) = position;
+    MovingBlock {
+        block: chunk_manager.get_block(x, y, z).unwrap_or(BlockID::Air),
+        meta: chunk_manager.get_block_meta(x, y, z).unwrap_or_default(),
+        destination,
+        block_entity: block_entity_registry.remove(&position),
+    }
+}
+
+/// Starts extending the arm of the piston at `position` and pushes the blocks in front of it.
+/// The line of blocks ends at the first air or liquid block, which gets replaced.
+/// Nothing happens if the line is longer than `PISTON_PUSH_LIMIT`, goes through an unloaded chunk
+/// or contains an immovable block
+pub fn extend_piston(chunk_manager: &ChunkManager, block_entity_registry: &mut BlockEntityRegistry,
+                     position: (i32, i32, i32), meta: BlockMeta, now: Instant) -> Option<PistonMoving> {
+    let direction = piston_direction(meta);
+
+    let mut length = 0;
+    loop {
+        let (x, y, z) = offset(position, direction, length + 1);
+        let block = chunk_manager.get_block(x, y, z)?;
+        if block.is_air() || block.is_liquid() {
+            break;
+        }
+        if length == PISTON_PUSH_LIMIT || !is_movable(chunk_manager, block, (x, y, z)) {
+            return None;
+        }
+        length += 1;
+    }
+
+    // Every block goes one step forward, starting from the far end so that none gets overwritten
+    let mut blocks = Vec::new();
+    for distance in (1..=length).rev() {
+        let source = offset(position, direction, distance);
+        let destination = offset(position, direction, distance + 1);
+        blocks.push(lift_block(chunk_manager, block_entity_registry, source, destination));
+        chunk_manager.put_block(BlockID::PistonMoving, destination.0, destination.1, destination.2);
+    }
+    // The head takes the place of the first block
+    let (x, y, z) = offset(position, direction, 1);
+    chunk_manager.put_block(BlockID::PistonMoving, x, y, z);
+
+    Some(PistonMoving {
+        blocks,
+        direction,
+        is_extending: true,
+        ticks: 0,
+        started: now,
+    })
+}
+
+/// Starts retracting the arm of the piston at `position`.
+/// A sticky piston pulls back the block in front of its head
+pub fn retract_piston(chunk_manager: &ChunkManager, block_entity_registry: &mut BlockEntityRegistry,
+                      position: (i32, i32, i32), block: BlockID, meta: BlockMeta, now: Instant) -> PistonMoving {
+    let direction = piston_direction(meta);
+    let head = offset(position, direction, 1);
+    let (x, y, z) = position;
+    chunk_manager.set_block_meta(meta.with_extended(false), x, y, z);
+
+    let mut blocks = Vec::new();
+    let pulled = offset(position, direction, 2);
+    let pulled_block = chunk_manager.get_block(pulled.0, pulled.1, pulled.2);
+    let is_pulling = block == BlockID::StickyPiston && pulled_block.map_or(false, |pulled_block| {
+        !pulled_block.is_air() && !pulled_block.is_liquid() && is_movable(chunk_manager, pulled_block, pulled)
+    });
+
+    block_entity_registry.remove(&head);
+    if is_pulling {
+        blocks.push(lift_block(chunk_manager, block_entity_registry, pulled, head));
+        chunk_manager.put_block(BlockID::Air, pulled.0, pulled.1, pulled.2);
+        chunk_manager.put_block(BlockID::PistonMoving, head.0, head.1, head.2);
+    } else {
+        chunk_manager.put_block(BlockID::Air, head.0, head.1, head.2);
+    }
+
+    PistonMoving {
+        blocks,
+        direction: (-direction.0, -direction.1, -direction.2),
+        is_extending: false,
+        ticks: 0,
+        started: now,
+    }
+}
+
+/// Puts the moved blocks at their destination, and the head in front of the piston if it extended
+pub fn finish_piston_move(chunk_manager: &ChunkManager, block_entity_registry: &mut BlockEntityRegistry,
+                          position: (i32, i32, i32), moving: PistonMoving) {
+    for moving_block in moving.blocks {
+        let (x, y, z) = moving_block.destination;
+        chunk_manager.put_block(moving_block.block, x, y, z);
+        if moving_block.meta != BlockMeta::default() {
+            chunk_manager.set_block_meta(moving_block.meta, x, y, z);
+        }
+        if let Some(block_entity) = moving_block.block_entity {
+            block_entity_registry.insert((x, y, z), block_entity);
+        }
+    }
+
+    if moving.is_extending {
+        let (x, y, z) = position;
+        let meta = match chunk_manager.get_block_meta(x, y, z) {
+            Some(meta) => meta,
+            None => return,
+        };
+        let head = offset(position, moving.direction, 1);
+        chunk_manager.put_block(BlockID::PistonHead, head.0, head.1, head.2);
+        chunk_manager.set_block_meta(BlockMeta::default().with_facing(meta.facing()), head.0, head.1, head.2);
+        block_entity_registry.insert(head, Box::new(PistonArm { base: position }));
+        chunk_manager.set_block_meta(meta.with_extended(true), x, y, z);
+    }
+}
@@ -1,35 +1,109 @@
+use std::f32::consts::PI;
 use std::time::Instant;
 
 use nalgebra::{clamp, Vector3};
 use nalgebra_glm::{IVec3, Mat4, pi, vec2, Vec3, vec3};
 use num_traits::Zero;
+use rand::Rng;
 
 use crate::aabb::{AABB, get_block_aabb};
+use crate::chunk::BlockID;
 use crate::chunk_manager::ChunkManager;
-use crate::constants::{FLYING_SPEED, FLYING_SPRINTING_SPEED, FOV, HORIZONTAL_ACCELERATION, IN_AIR_FRICTION, JUMP_IMPULSE, MAX_VERTICAL_VELOCITY, MOUSE_SENSITIVITY_X, MOUSE_SENSITIVITY_Y, ON_GROUND_FRICTION, PLAYER_EYES_HEIGHT, PLAYER_HALF_WIDTH, PLAYER_HEIGHT, PLAYER_WIDTH, SNEAKING_SPEED, SPRINTING_SPEED, WALKING_SPEED};
+use crate::constants::{AIR_PER_BUBBLE, AIR_REFILL_RATE, CAMERA_SHAKE_AMPLITUDE, CAMERA_SHAKE_DURATION, FALL_DAMAGE_MIN_VELOCITY, FALL_DAMAGE_PER_VELOCITY, FEATHER_FALLING_REDUCTION_PER_LEVEL, DROWNING_DAMAGE, DROWNING_DAMAGE_INTERVAL, FIRE_DAMAGE_PER_SECOND, FIRE_DURATION_TICKS, LAVA_DAMAGE_PER_SECOND, MAX_AIR_SUPPLY, FOOD_DEPLETION_RATE, HEAD_BOB_HEIGHT, HEAD_BOB_STRIDE, MAX_FOOD_LEVEL, MAX_HEALTH, REGENERATION_FOOD_LEVEL, REGENERATION_RATE, DASH_COOLDOWN, DASH_SPEED_MULTIPLIER, SPRINTING_FOOD_DEPLETION_MULTIPLIER, SPRINTING_MIN_FOOD_LEVEL, STARVATION_DAMAGE_RATE, TICKS_PER_SECOND, WORLD_SPAWN_POINT, FLYING_SPEED, FLYING_SPRINTING_SPEED, SPECTATOR_FLYING_SPEED, MAX_SPECTATOR_SPEED_MULTIPLIER, MIN_SPECTATOR_SPEED_MULTIPLIER, SPECTATOR_SPEED_STEP, FOV, HORIZONTAL_ACCELERATION, IN_AIR_FRICTION, JUMP_BOOST_PER_LEVEL, JUMP_IMPULSE, RESISTANCE_PER_LEVEL, SPEED_BONUS_PER_LEVEL, STRENGTH_DAMAGE_PER_LEVEL, MAX_VERTICAL_VELOCITY, ON_GROUND_FRICTION, PLAYER_EYES_HEIGHT, PLAYER_HALF_WIDTH, PLAYER_HEIGHT, PLAYER_SNEAK_HEIGHT, PLAYER_WIDTH, SLIME_BOUNCE_MIN_VELOCITY, SLIME_BOUNCE_RESTITUTION, SNEAKING_SPEED, SPRINTING_SPEED, WALKING_SPEED};
 use crate::input::InputCache;
+use crate::enchanting::EnchantmentId;
+use crate::inventory::armor::{ArmorInventory, ArmorSlot};
 use crate::physics::{Interpolatable, Interpolator};
+use crate::settings::Settings;
+use crate::status_effect::{StatusEffect, StatusEffects};
 use crate::util::Forward;
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum GameMode {
+    Survival,
+    Creative,
+    /// The player always flies, goes through the blocks and has no hand
+    Spectator,
+}
+
+impl GameMode {
+    /// The game mode coming after this one when cycling with F3+F4
+    pub fn next(&self) -> GameMode {
+        match self {
+            GameMode::Survival => GameMode::Creative,
+            GameMode::Creative => GameMode::Spectator,
+            GameMode::Spectator => GameMode::Survival,
+        }
+    }
+}
+
 pub struct PlayerState {
     pub rotation: Vec3,
     pub camera_height: Interpolator<f32>,
     pub fov: Interpolator<f32>,
     pub view_matrix: Mat4,
     pub projection_matrix: Mat4,
+    pub game_mode: GameMode,
 
     pub is_on_ground: bool,
     pub is_sneaking: bool,
     pub is_sprinting: bool,
     pub is_flying: bool,
+    /// Whether the player is holding onto a ladder, W and S then move them up and down
+    pub is_climbing: bool,
+    /// Whether the camera is under water
+    pub is_submerged: bool,
+    /// Whether the player has been put on the ground, they wait at the top of the world until then
+    pub is_spawned: bool,
+    /// The death screen stays open until the player respawns
+    pub is_dead: bool,
+    /// How the player died, shown on the death screen
+    pub death_message: &'static str,
 
     pub targeted_block: Option<((i32, i32, i32), IVec3)>,
+    /// The block being broken and when the player started breaking it
+    pub mining: Option<((i32, i32, i32), Instant)>,
+
+    pub health: f32,
+    pub food_level: f32,
+    pub food_saturation: f32,
+    /// Game ticks the player can still spend under water
+    pub air_supply: f32,
+    /// Game ticks spent without air since the last drowning damage
+    pub drowning_ticks: u32,
+    /// Set by touching lava, the player keeps burning for a while after leaving it
+    pub on_fire: bool,
+    /// Game ticks left before the fire goes out
+    pub fire_ticks: u32,
+    /// Experience points collected since the player spawned, minus the ones spent
+    pub xp_total: u64,
+    /// The level reached with `xp_total` points
+    pub level: u32,
+    /// When the player last reached a new level, the screen flashes for LEVEL_UP_FLASH_DURATION
+    pub level_up_started: Option<Instant>,
+
+    /// Where the player comes back to life, set by sleeping in a bed
+    pub spawn_point: Vec3,
+    /// When the player went to bed, until fully awake
+    pub sleep_started: Option<Instant>,
+    /// When the player started charging the crossbow in their hand, until it shoots or is put away
+    pub crossbow_charge_started: Option<Instant>,
+    /// Given by the beacons around
+    pub status_effects: StatusEffects,
+    /// Phase of the head bob while walking, in radians. The head is at its lowest when a foot hits the ground, every π
+    pub head_bob: f32,
+    /// How much the head bobs, 1 while walking and 0 when standing still
+    pub head_bob_amplitude: f32,
+    /// When the player last got hurt by a fall, the camera shakes for CAMERA_SHAKE_DURATION
+    pub camera_shake_started: Option<Instant>,
+    /// Multiplies the flying speed of the spectators, changed with the scroll wheel
+    pub spectator_speed_multiplier: f32,
 
     pub(crate) jump_last_executed: Instant,
     pub(crate) fly_throttle: bool,
     pub(crate) fly_last_toggled: Instant,
-    pub(crate) sprint_throttle: bool,
-    pub(crate) sprint_last_toggled: Instant,
+    /// When the player last dashed forward
+    pub(crate) dash_started: Option<Instant>,
     pub(crate) block_placing_last_executed: Instant,
 }
 
@@ -41,32 +115,311 @@ impl PlayerState {
             fov: Interpolator::new(1.0 / 30.0, *FOV),
             view_matrix: Mat4::identity(),
             projection_matrix: Mat4::identity(),
+            game_mode: GameMode::Survival,
 
             is_on_ground: false,
             is_sneaking: false,
             is_sprinting: false,
             is_flying: false,
+            is_climbing: false,
+            is_submerged: false,
+            is_spawned: false,
+            is_dead: false,
+            death_message: "",
 
             targeted_block: None,
+            mining: None,
+
+            health: MAX_HEALTH,
+            food_level: MAX_FOOD_LEVEL,
+            food_saturation: 5.0,
+            air_supply: MAX_AIR_SUPPLY,
+            drowning_ticks: 0,
+            on_fire: false,
+            fire_ticks: 0,
+            xp_total: 0,
+            level: 0,
+            level_up_started: None,
+
+            spawn_point: vec3(WORLD_SPAWN_POINT.0, WORLD_SPAWN_POINT.1, WORLD_SPAWN_POINT.2),
+            sleep_started: None,
+            crossbow_charge_started: None,
+            status_effects: StatusEffects::default(),
+            head_bob: 0.0,
+            head_bob_amplitude: 0.0,
+            camera_shake_started: None,
+            spectator_speed_multiplier: 1.0,
 
             jump_last_executed: Instant::now(),
             fly_throttle: false,
             fly_last_toggled: Instant::now(),
-            sprint_throttle: false,
-            sprint_last_toggled: Instant::now(),
+            dash_started: None,
             block_placing_last_executed: Instant::now(),
         }
     }
 
-    pub fn rotate_camera(&mut self, horizontal: f32, vertical: f32) {
-        self.rotation.y += horizontal / 100.0 * MOUSE_SENSITIVITY_X;
-        self.rotation.x -= vertical / 100.0 * MOUSE_SENSITIVITY_Y;
+    pub fn rotate_camera(&mut self, horizontal: f32, vertical: f32, settings: &Settings) {
+        self.rotation.y += horizontal / 100.0 * settings.mouse_sensitivity_x;
+        self.rotation.x -= vertical / 100.0 * settings.mouse_sensitivity_y;
         // Limit vertical movement
         self.rotation.x = clamp(
             self.rotation.x,
             -pi::<f32>() / 2.0 + 0.0001,
             pi::<f32>() / 2.0 - 0.0001);
     }
+
+    pub fn is_spectator(&self) -> bool {
+        self.game_mode == GameMode::Spectator
+    }
+
+    /// Spectators start flying right away, the others fall back to the ground when leaving spectator mode
+    pub fn set_game_mode(&mut self, game_mode: GameMode) {
+        if self.is_spectator() && game_mode != GameMode::Spectator {
+            self.is_flying = false;
+        }
+        self.game_mode = game_mode;
+        if self.is_spectator() {
+            self.is_flying = true;
+            self.is_sneaking = false;
+            self.is_climbing = false;
+            self.mining = None;
+            self.on_fire = false;
+            self.fire_ticks = 0;
+        }
+        info!("Game mode: {:?}", game_mode);
+    }
+
+    /// Speeds the spectator up for a positive `delta` of the scroll wheel, slows them down otherwise
+    pub fn scale_spectator_speed(&mut self, delta: f64) {
+        let factor = if delta > 0.0 { SPECTATOR_SPEED_STEP } else { 1.0 / SPECTATOR_SPEED_STEP };
+        self.spectator_speed_multiplier = (self.spectator_speed_multiplier * factor)
+            .max(MIN_SPECTATOR_SPEED_MULTIPLIER)
+            .min(MAX_SPECTATOR_SPEED_MULTIPLIER);
+    }
+
+    pub fn can_sprint(&self) -> bool {
+        self.food_level > SPRINTING_MIN_FOOD_LEVEL
+    }
+
+    /// Whether the player dashed less than DASH_COOLDOWN ago, they can't dash again until then
+    pub fn is_dashing(&self, now: Instant) -> bool {
+        self.dash_started.map_or(false, |started| now.saturating_duration_since(started) < *DASH_COOLDOWN)
+    }
+
+    /// Vertical velocity given by a jump, higher with Jump Boost
+    pub fn jump_impulse(&self, now: Instant) -> f32 {
+        *JUMP_IMPULSE + JUMP_BOOST_PER_LEVEL * self.status_effects.level(StatusEffect::JumpBoost, now) as f32
+    }
+
+    /// Damage dealt by an attack of `damage`, increased by Strength
+    pub fn attack_damage(&self, damage: f32, now: Instant) -> f32 {
+        if damage <= 0.0 {
+            return damage;
+        }
+        damage + STRENGTH_DAMAGE_PER_LEVEL * self.status_effects.level(StatusEffect::Strength, now) as f32
+    }
+
+    /// Damage taken from a hit of `damage`, reduced by Resistance
+    fn damage_taken(&self, damage: f32, now: Instant) -> f32 {
+        let resistance = self.status_effects.level(StatusEffect::Resistance, now) as f32;
+        damage * (1.0 - RESISTANCE_PER_LEVEL * resistance).max(0.0)
+    }
+
+    /// Consumes the food and regenerates or starves the player over `dt` seconds
+    pub fn update_hunger(&mut self, dt: f32) {
+        let mut depletion = FOOD_DEPLETION_RATE * dt;
+        if self.is_sprinting {
+            depletion *= SPRINTING_FOOD_DEPLETION_MULTIPLIER;
+        }
+        let from_saturation = depletion.min(self.food_saturation);
+        self.food_saturation -= from_saturation;
+        self.food_level = (self.food_level - (depletion - from_saturation)).max(0.0);
+
+        if self.food_level > REGENERATION_FOOD_LEVEL {
+            self.health = (self.health + REGENERATION_RATE * dt).min(MAX_HEALTH);
+        } else if self.food_level <= 0.0 {
+            self.health = (self.health - STARVATION_DAMAGE_RATE * dt).max(0.0);
+        }
+
+        if !self.can_sprint() {
+            self.is_sprinting = false;
+        }
+    }
+
+    /// Number of bubbles shown in the air bar
+    pub fn air_bubbles(&self) -> i32 {
+        (self.air_supply / AIR_PER_BUBBLE).ceil() as i32
+    }
+
+    /// Consumes the air under water and drowns the player without it, over one game tick.
+    /// The air comes back quickly once out of the water. Returns whether a bubble of the air bar popped
+    pub fn update_air_supply(&mut self, now: Instant) -> bool {
+        let bubbles = self.air_bubbles();
+
+        if !self.is_submerged {
+            self.air_supply = (self.air_supply + AIR_REFILL_RATE).min(MAX_AIR_SUPPLY);
+            self.drowning_ticks = 0;
+        } else if self.air_supply > 0.0 {
+            self.air_supply = (self.air_supply - 1.0).max(0.0);
+        } else {
+            self.drowning_ticks += 1;
+            if self.drowning_ticks >= DROWNING_DAMAGE_INTERVAL {
+                self.health = (self.health - self.damage_taken(DROWNING_DAMAGE, now)).max(0.0);
+                self.drowning_ticks = 0;
+            }
+        }
+
+        self.air_bubbles() < bubbles
+    }
+
+    /// Burns the player in lava or fire and for FIRE_DURATION_TICKS after leaving them, over one game tick.
+    /// Water puts the fire out
+    pub fn update_burning(&mut self, is_in_lava: bool, is_in_fire: bool, is_in_water: bool, now: Instant) {
+        if is_in_water {
+            self.on_fire = false;
+            self.fire_ticks = 0;
+        } else if is_in_lava || is_in_fire {
+            self.on_fire = true;
+            self.fire_ticks = FIRE_DURATION_TICKS;
+        }
+
+        let damage_per_second = if is_in_lava {
+            LAVA_DAMAGE_PER_SECOND
+        } else if self.on_fire {
+            self.fire_ticks = self.fire_ticks.saturating_sub(1);
+            self.on_fire = self.fire_ticks > 0;
+            FIRE_DAMAGE_PER_SECOND
+        } else {
+            return;
+        };
+        self.health = (self.health - self.damage_taken(damage_per_second / TICKS_PER_SECOND, now)).max(0.0);
+    }
+
+    /// Advances the head bob by the distance walked, returns whether the head went through its lowest point
+    pub fn advance_head_bob(&mut self, distance: f32) -> bool {
+        let half_cycle = |phase: f32| ((phase - PI / 2.0) / PI).floor();
+        let previous = self.head_bob;
+        self.head_bob += distance * PI / HEAD_BOB_STRIDE;
+        let is_step = half_cycle(self.head_bob) != half_cycle(previous);
+        self.head_bob %= 2.0 * PI;
+        is_step
+    }
+
+    /// Vertical offset of the camera caused by the head bob
+    pub fn head_bob_offset(&self) -> f32 {
+        -HEAD_BOB_HEIGHT * self.head_bob_amplitude * self.head_bob.sin().abs()
+    }
+
+    /// Hurts the player landing at `fall_velocity` after falling from higher than FALL_DAMAGE_HEIGHT,
+    /// Feather Falling on the boots softens the landing. Returns the damage taken
+    pub fn take_fall_damage(&mut self, fall_velocity: f32, armor: &mut ArmorInventory, now: Instant) -> f32 {
+        if fall_velocity <= *FALL_DAMAGE_MIN_VELOCITY {
+            return 0.0;
+        }
+        let feather_falling = armor.enchantment_level(ArmorSlot::Boots, EnchantmentId::FeatherFalling) as f32;
+        let damage = (fall_velocity - *FALL_DAMAGE_MIN_VELOCITY) * FALL_DAMAGE_PER_VELOCITY
+            * (1.0 - FEATHER_FALLING_REDUCTION_PER_LEVEL * feather_falling).max(0.0);
+        self.take_damage(damage, armor, now)
+    }
+
+    /// Offset of the camera while it shakes after a fall, in a random direction every frame
+    pub fn camera_shake_offset(&self, now: Instant) -> Vec3 {
+        match self.camera_shake_started {
+            Some(started) if now.saturating_duration_since(started).as_secs_f32() < CAMERA_SHAKE_DURATION => {
+                let mut rng = rand::thread_rng();
+                let mut offset = || rng.gen_range(-CAMERA_SHAKE_AMPLITUDE, CAMERA_SHAKE_AMPLITUDE);
+                vec3(offset(), offset(), offset())
+            }
+            _ => vec3(0.0, 0.0, 0.0),
+        }
+    }
+
+    pub fn die(&mut self, death_message: &'static str) {
+        self.health = 0.0;
+        self.is_dead = true;
+        self.death_message = death_message;
+    }
+
+    /// Comes back to life with full health, food and air. The player has to be moved to `spawn_point`
+    pub fn respawn(&mut self) {
+        self.is_dead = false;
+        self.health = MAX_HEALTH;
+        self.food_level = MAX_FOOD_LEVEL;
+        self.food_saturation = 5.0;
+        self.air_supply = MAX_AIR_SUPPLY;
+        self.drowning_ticks = 0;
+        self.on_fire = false;
+        self.fire_ticks = 0;
+        self.is_submerged = false;
+        self.is_climbing = false;
+        self.is_flying = self.is_spectator();
+        self.is_sprinting = false;
+        self.is_sneaking = false;
+        self.status_effects.clear();
+    }
+
+    /// The worn armor absorbs part of the damage and wears out, Resistance reduces the rest.
+    /// Returns the damage taken
+    pub fn take_damage(&mut self, damage: f32, armor: &mut ArmorInventory, now: Instant) -> f32 {
+        if damage <= 0.0 {
+            return 0.0;
+        }
+        let damage = self.damage_taken(armor.absorb_damage(damage), now);
+        self.health = (self.health - damage).max(0.0);
+        damage
+    }
+
+    /// Returns whether the player reached a new level
+    pub fn add_xp(&mut self, points: u32, now: Instant) -> bool {
+        self.xp_total += points as u64;
+        let level = level_for_xp(self.xp_total);
+        let leveled_up = level > self.level;
+        self.level = level;
+        if leveled_up {
+            self.level_up_started = Some(now);
+        }
+        leveled_up
+    }
+
+    /// The progress towards the next level is kept
+    pub fn spend_levels(&mut self, levels: u32) {
+        let levels = levels.min(self.level);
+        self.xp_total -= (xp_for_level(self.level) - xp_for_level(self.level - levels)) as u64;
+        self.level = level_for_xp(self.xp_total);
+    }
+
+    /// How far the player is from the next level, from 0 to 1
+    pub fn xp_progress(&self) -> f32 {
+        let xp_in_level = self.xp_total - xp_for_level(self.level) as u64;
+        xp_in_level as f32 / (xp_for_level(self.level + 1) - xp_for_level(self.level)) as f32
+    }
+
+    /// The saturation can't be higher than the food level
+    pub fn eat(&mut self, food_points: f32, saturation: f32) {
+        self.food_level = (self.food_level + food_points).min(MAX_FOOD_LEVEL);
+        self.food_saturation = (self.food_saturation + saturation).min(self.food_level);
+    }
+}
+
+/// Experience points needed in total to reach `level`
+pub fn xp_for_level(level: u32) -> u32 {
+    if level <= 16 {
+        level * level + 6 * level
+    } else if level <= 31 {
+        (5 * level * level + 720 - 81 * level) / 2
+    } else {
+        // Subtracting last, since 9 * level * level is smaller than 325 * level up to level 36
+        (9 * level * level + 4440 - 325 * level) / 2
+    }
+}
+
+/// The level reached with `xp_total` points in total
+pub fn level_for_xp(xp_total: u64) -> u32 {
+    let mut level = 0;
+    while xp_for_level(level + 1) as u64 <= xp_total {
+        level += 1;
+    }
+    level
 }
 
 #[derive(Clone)]
@@ -75,6 +428,8 @@ pub struct PlayerPhysicsState {
     pub aabb: AABB,
     pub velocity: Vec3,
     pub acceleration: Vec3,
+    /// Highest downward speed the player landed at since the fall damage was last applied
+    pub last_fall_velocity: f32,
 }
 
 impl PlayerPhysicsState {
@@ -88,10 +443,21 @@ impl PlayerPhysicsState {
             },
             velocity: vec3(0.0, 0.0, 0.0),
             acceleration: vec3(0.0, 0.0, 0.0),
+            last_fall_velocity: 0.0,
         }
     }
 }
 
+impl Interpolator<PlayerPhysicsState> {
+    /// Moves the player without interpolating from its previous position
+    pub fn teleport(&mut self, position: Vec3) {
+        let state = PlayerPhysicsState::new_at_position(position);
+        self.previous_state = state.clone();
+        self.current_state = state.clone();
+        self.interpolated_state = state;
+    }
+}
+
 impl Interpolatable for PlayerPhysicsState {
     fn interpolate(&self, alpha: f32, other: &Self) -> Self {
         let interpolate_vec3 = |from: &Vec3, to: &Vec3| {
@@ -106,12 +472,13 @@ impl Interpolatable for PlayerPhysicsState {
             },
             velocity: interpolate_vec3(&self.velocity, &other.velocity),
             acceleration: interpolate_vec3(&self.acceleration, &other.acceleration),
+            last_fall_velocity: self.last_fall_velocity,
         }
     }
 }
 
 impl PlayerPhysicsState {
-    pub fn apply_keyboard_mouvement(&mut self, player_properties: &mut PlayerState, input_cache: &InputCache) {
+    pub fn apply_keyboard_mouvement(&mut self, player_properties: &mut PlayerState, input_cache: &InputCache, now: Instant) {
         let rotation = &player_properties.rotation;
         if player_properties.is_flying {
             if input_cache.is_key_pressed(glfw::Key::Space) {
@@ -124,10 +491,9 @@ impl PlayerPhysicsState {
 
         // Jump
         if input_cache.is_key_pressed(glfw::Key::Space) {
-            let now = Instant::now();
             if now.duration_since(player_properties.jump_last_executed).as_secs_f32() >= 0.475 {
                 if player_properties.is_on_ground {
-                    self.velocity.y = *JUMP_IMPULSE;
+                    self.velocity.y = player_properties.jump_impulse(now);
                     player_properties.jump_last_executed = now;
                 }
             }
@@ -154,6 +520,31 @@ impl PlayerPhysicsState {
         }
     }
 
+    /// Height of the hitbox, lower while sneaking
+    pub fn height(&self) -> f32 {
+        self.aabb.maxs.y - self.aabb.mins.y
+    }
+
+    /// Crouches to PLAYER_SNEAK_HEIGHT while sneaking, and stands up to PLAYER_HEIGHT otherwise.
+    /// Returns false when there is no room to stand up, the player stays crouched in that case
+    pub fn update_height(&mut self, is_sneaking: bool, chunk_manager: &ChunkManager) -> bool {
+        if is_sneaking {
+            self.aabb.maxs.y = self.aabb.mins.y + PLAYER_SNEAK_HEIGHT;
+            return true;
+        }
+        if self.height() >= PLAYER_HEIGHT {
+            return true;
+        }
+
+        let mut standing = self.clone();
+        standing.aabb.maxs.y = standing.aabb.mins.y + PLAYER_HEIGHT;
+        if standing.get_colliding_block_coords(chunk_manager).is_some() {
+            return false;
+        }
+        self.aabb = standing.aabb;
+        true
+    }
+
     pub fn get_colliding_block_coords(&self, chunk_manager: &ChunkManager) -> Option<Vec3> {
         let player_mins = &self.aabb.mins;
         let player_maxs = &self.aabb.maxs;
@@ -170,13 +561,11 @@ impl PlayerPhysicsState {
         for y in block_mins.y..=block_maxs.y {
             for z in block_mins.z..=block_maxs.z {
                 for x in block_mins.x..=block_maxs.x {
-                    if let Some(block) = chunk_manager.get_block(x, y, z) {
-                        if !block.is_air() {
-                            let block_aabb = get_block_aabb(&vec3(x as f32, y as f32, z as f32));
-                            if self.aabb.intersects(&block_aabb) {
-                                colliding_block = Some(vec3(x as f32, y as f32, z as f32));
-                                break;
-                            }
+                    if chunk_manager.is_solid_block_at(x, y, z) {
+                        let block_aabb = get_block_aabb(&vec3(x as f32, y as f32, z as f32));
+                        if self.aabb.intersects(&block_aabb) {
+                            colliding_block = Some(vec3(x as f32, y as f32, z as f32));
+                            break;
                         }
                     }
                 }
@@ -185,9 +574,49 @@ impl PlayerPhysicsState {
         colliding_block
     }
 
-    pub fn separate_from_block(&mut self, v: &Vec3, block_coords: &Vec3) -> bool {
+    /// Whether a block of this kind overlaps the player
+    pub fn is_touching_block(&self, block: BlockID, chunk_manager: &ChunkManager) -> bool {
+        let mins = &self.aabb.mins;
+        let maxs = &self.aabb.maxs;
+
+        for y in mins.y.floor() as i32..=maxs.y.floor() as i32 {
+            for z in mins.z.floor() as i32..=maxs.z.floor() as i32 {
+                for x in mins.x.floor() as i32..=maxs.x.floor() as i32 {
+                    if chunk_manager.get_block(x, y, z) == Some(block) {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Whether the player is touching the climbable side of a ladder
+    pub fn is_touching_ladder(&self, chunk_manager: &ChunkManager) -> bool {
+        let mins = &self.aabb.mins;
+        let maxs = &self.aabb.maxs;
+
+        for y in mins.y.floor() as i32..=maxs.y.floor() as i32 {
+            for z in mins.z.floor() as i32..=maxs.z.floor() as i32 {
+                for x in mins.x.floor() as i32..=maxs.x.floor() as i32 {
+                    if chunk_manager.get_block(x, y, z).map_or(false, |block| block.is_climbable()) {
+                        let meta = chunk_manager.get_block_meta(x, y, z).unwrap_or_default();
+                        if self.aabb.overlaps_ladder_face(&vec3(x as f32, y as f32, z as f32), meta.0) {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    /// Pushes the player out of `block` they moved into along `v`, returns whether they landed on it.
+    /// A slime block bounces the player back up instead, unless they sneak, and never hurts them
+    pub fn separate_from_block(&mut self, v: &Vec3, block_coords: &Vec3, block: BlockID, is_sneaking: bool) -> bool {
         let mut is_player_on_ground = false;
         let block_aabb = get_block_aabb(&block_coords);
+        let height = self.height();
 
         if !v.x.is_zero() {
             if v.x < 0.0 {
@@ -208,11 +637,17 @@ impl PlayerPhysicsState {
             if v.y < 0.0 {
                 self.aabb = AABB::new(
                     vec3(self.aabb.mins.x, block_aabb.maxs.y, self.aabb.mins.z),
-                    vec3(self.aabb.maxs.x, block_aabb.maxs.y + PLAYER_HEIGHT, self.aabb.maxs.z));
+                    vec3(self.aabb.maxs.x, block_aabb.maxs.y + height, self.aabb.maxs.z));
+                if block != BlockID::SlimeBlock {
+                    self.last_fall_velocity = self.last_fall_velocity.max(self.velocity.y.min(0.0).abs());
+                } else if !is_sneaking && self.velocity.y < -SLIME_BOUNCE_MIN_VELOCITY {
+                    self.velocity.y = -self.velocity.y * SLIME_BOUNCE_RESTITUTION;
+                    return false;
+                }
                 is_player_on_ground = true;
             } else {
                 self.aabb = AABB::new(
-                    vec3(self.aabb.mins.x, block_aabb.mins.y - PLAYER_HEIGHT, self.aabb.mins.z),
+                    vec3(self.aabb.mins.x, block_aabb.mins.y - height, self.aabb.mins.z),
                     vec3(self.aabb.maxs.x, block_aabb.mins.y, self.aabb.maxs.z));
             }
             self.velocity.y = 0.0;
@@ -255,12 +690,16 @@ impl PlayerPhysicsState {
         }
     }
 
-    pub fn limit_velocity(&mut self, player_properties: &PlayerState) {
+    pub fn limit_velocity(&mut self, player_properties: &PlayerState, now: Instant) {
         // Limit the horizontal speed
         let mut horizontal_vel = vec2(self.velocity.x, self.velocity.z);
         let speed = horizontal_vel.magnitude();
 
-        let max_speed = if player_properties.is_flying {
+        let max_speed = if player_properties.is_spectator() {
+            let max_speed = SPECTATOR_FLYING_SPEED * player_properties.spectator_speed_multiplier;
+            self.velocity.y = clamp(self.velocity.y, -max_speed, max_speed);
+            max_speed
+        } else if player_properties.is_flying {
             self.velocity.y = clamp(self.velocity.y, -8.0, 8.0);
             if player_properties.is_sprinting {
                 FLYING_SPRINTING_SPEED
@@ -268,7 +707,10 @@ impl PlayerPhysicsState {
                 FLYING_SPEED
             }
         } else {
-            if player_properties.is_sprinting {
+            let speed_bonus = 1.0 + SPEED_BONUS_PER_LEVEL * player_properties.status_effects.level(StatusEffect::Speed, now) as f32;
+            speed_bonus * if player_properties.is_dashing(now) {
+                SPRINTING_SPEED * DASH_SPEED_MULTIPLIER
+            } else if player_properties.is_sprinting {
                 SPRINTING_SPEED
             } else if player_properties.is_sneaking {
                 SNEAKING_SPEED
@@ -292,4 +734,21 @@ impl PlayerPhysicsState {
     }
 }
 
-
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_past_the_thirtieth() {
+        assert_eq!(xp_for_level(30), 1395);
+        assert_eq!(xp_for_level(31), 1507);
+        assert_eq!(xp_for_level(32), 1628);
+        assert_eq!(xp_for_level(40), 2920);
+        for level in 30..=40 {
+            let xp = xp_for_level(level) as u64;
+            assert_eq!(level_for_xp(xp), level);
+            assert_eq!(level_for_xp(xp - 1), level - 1);
+            assert!(xp_for_level(level + 1) > xp_for_level(level));
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use std::any::Any;
+
+use crate::block_entity::BlockEntity;
+use crate::chunk_manager::ChunkManager;
+
+/// Keeps track of a pressure plate, whether it is pressed is in the block meta
+#[derive(Default)]
+pub struct PressurePlateBlockEntity {
+    /// Game ticks left before the plate springs back up, counted once nothing stands on it anymore
+    pub release_ticks: u32,
+}
+
+impl PressurePlateBlockEntity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BlockEntity for PressurePlateBlockEntity {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {}
+
+    fn serialize(&self) -> Vec<u8> {
+        vec![self.release_ticks as u8]
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,82 @@
+use nalgebra_glm::Vec3;
+
+use crate::chunk::BlockID;
+use crate::constants::{CROSSBOW_ARROW_DAMAGE, CROSSBOW_ARROW_SPEED};
+
+/// An item flying through the world, moved by `UpdateProjectiles`
+pub struct Projectile {
+    /// What was thrown, used to draw the projectile
+    pub item: BlockID,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub damage: f32,
+    /// The projectile disappears after traveling this far without hitting anything
+    pub max_distance: f32,
+    pub traveled: f32,
+    /// Burning projectiles ignite the TNT they hit, arrows catch fire when flying through lava
+    pub on_fire: bool,
+    /// The block an arrow is stuck in, it stays there until the block is gone or ARROW_DESPAWN_TIME is over
+    pub stuck_in: Option<(i32, i32, i32)>,
+    /// Seconds spent stuck in a block
+    pub stuck_time: f32,
+}
+
+impl Projectile {
+    /// The projectile thrown by right-clicking with the item, if it can be thrown.
+    /// `direction` must be normalized.
+    pub fn thrown(item: BlockID, position: Vec3, direction: Vec3) -> Option<Self> {
+        let (speed, damage, max_distance) = match item {
+            BlockID::Snowball => (20.0, 0.0, 64.0),
+            BlockID::Arrow => (30.0, 2.0, 128.0),
+            _ => return None,
+        };
+        Some(Projectile {
+            item,
+            position,
+            velocity: direction * speed,
+            damage,
+            max_distance,
+            traveled: 0.0,
+            on_fire: false,
+            stuck_in: None,
+            stuck_time: 0.0,
+        })
+    }
+
+    /// The arrow shot by a charged crossbow, faster and stronger than a thrown one.
+    /// `direction` must be normalized.
+    pub fn shot_from_crossbow(position: Vec3, direction: Vec3) -> Self {
+        let mut arrow = Projectile::thrown(BlockID::Arrow, position, direction).unwrap();
+        arrow.velocity = direction * CROSSBOW_ARROW_SPEED;
+        arrow.damage = CROSSBOW_ARROW_DAMAGE;
+        arrow
+    }
+
+    /// Half the size of the box drawn for the projectile, the Z axis pointing where it flies
+    pub fn half_extents(item: BlockID) -> (f32, f32, f32) {
+        match item {
+            BlockID::Arrow => (1.0 / 32.0, 1.0 / 32.0, 1.0 / 4.0),
+            _ => (1.0 / 8.0, 1.0 / 8.0, 1.0 / 8.0),
+        }
+    }
+}
+
+/// How far a crossbow is charged, stored in the meta of its item stack
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CrossbowState {
+    Uncharged = 0,
+    /// The right button is held, the crossbow charges for CROSSBOW_CHARGE_DURATION seconds
+    Charging = 1,
+    /// Releasing the right button shoots an arrow
+    Charged = 2,
+}
+
+impl CrossbowState {
+    pub fn from_meta(meta: u8) -> Self {
+        match meta {
+            1 => CrossbowState::Charging,
+            2 => CrossbowState::Charged,
+            _ => CrossbowState::Uncharged,
+        }
+    }
+}
@@ -78,4 +78,15 @@ pub fn raycast(is_solid_block_at: &dyn Fn(i32, i32, i32) -> bool,
     // no voxel hit found
     _hit_pos = origin.zip_map(&direction, |p, d| p + t * d);
     return None;
-}
\ No newline at end of file
+}
+
+/// Distance along the ray from `origin` to the face of `block` it hit, `normal` pointing out of that face.
+/// The ray started inside the block when the normal is zero
+pub fn distance_to_face(origin: &Vec3, direction: &Vec3, block: (i32, i32, i32), normal: &IVec3) -> f32 {
+    let block = [block.0, block.1, block.2];
+    (0..3).find(|&axis| normal[axis] != 0)
+        .map_or(0.0, |axis| {
+            let face = block[axis] as f32 + if normal[axis] > 0 { 1.0 } else { 0.0 };
+            (face - origin[axis]) / direction[axis]
+        })
+}
@@ -0,0 +1,131 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::chunk::{BlockID, BlockMeta};
+use crate::chunk_manager::ChunkManager;
+use crate::constants::MAX_REDSTONE_NETWORK_SIZE;
+use crate::observer::observer_powers;
+
+const NEIGHBOURS: [(i32, i32, i32); 6] = [
+    (1, 0, 0),
+    (-1, 0, 0),
+    (0, 1, 0),
+    (0, -1, 0),
+    (0, 0, 1),
+    (0, 0, -1),
+];
+
+/// Power emitted by the blocks powering the redstone wires
+pub const SOURCE_POWER: u8 = 15;
+
+/// Power carried by the redstone wires, recomputed for the wires connected to a block that changed
+#[derive(Default)]
+pub struct RedstoneSimulator {
+    /// Power level of the powered wires
+    pub powered: HashMap<(i32, i32, i32), u8>,
+}
+
+/// Neighbour of (x, y, z) in the horizontal direction `facing`, as stored in `BlockMeta`
+fn horizontal_neighbour((x, y, z): (i32, i32, i32), facing: u8) -> (i32, i32, i32) {
+    let (dx, dz) = BlockMeta::default().with_facing(facing).facing_offset();
+    (x + dx, y, z + dz)
+}
+
+fn is_wire_at(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> bool {
+    chunk_manager.get_block(x, y, z) == Some(BlockID::RedstoneWire)
+}
+
+/// Whether the block at `source` powers its neighbour `target`. Pressure plates are power sources while they are pressed,
+/// observers only power the block behind them during their pulse
+fn powers(chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32), target: (i32, i32, i32)) -> bool {
+    chunk_manager.get_block(x, y, z).map_or(false, |block| {
+        let meta = || chunk_manager.get_block_meta(x, y, z).unwrap_or_default();
+        block.is_power_source()
+            || (block.is_pressure_plate() && meta().is_pressed())
+            || (block == BlockID::Observer && observer_powers(meta(), (x, y, z), target))
+    })
+}
+
+impl RedstoneSimulator {
+    /// Strongest power received by the block at `position` from the power sources and the wires next to it
+    pub fn input_power(&self, chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) -> u8 {
+        NEIGHBOURS.iter()
+            .map(|&(dx, dy, dz)| {
+                let neighbour = (x + dx, y + dy, z + dz);
+                if powers(chunk_manager, neighbour, (x, y, z)) {
+                    SOURCE_POWER
+                } else {
+                    self.powered.get(&neighbour).cloned().unwrap_or(0)
+                }
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Recomputes the power and the connections of the wires next to `position`, and of every wire connected to them.
+    /// The wires next to a power source get its full power, which decreases by one for every wire after them
+    pub fn update(&mut self, chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) {
+        // Every wire connected to the changed block
+        let mut network = HashSet::new();
+        let mut stack = vec![(x, y, z)];
+        stack.extend(NEIGHBOURS.iter().map(|&(dx, dy, dz)| (x + dx, y + dy, z + dz)));
+        while let Some(position) = stack.pop() {
+            if network.len() >= MAX_REDSTONE_NETWORK_SIZE || !is_wire_at(chunk_manager, position) || !network.insert(position) {
+                continue;
+            }
+            stack.extend((0..4).map(|facing| horizontal_neighbour(position, facing)));
+        }
+        for position in NEIGHBOURS.iter().map(|&(dx, dy, dz)| (x + dx, y + dy, z + dz)).chain(Some((x, y, z))) {
+            if !network.contains(&position) {
+                self.powered.remove(&position);
+            }
+        }
+
+        // Spread the power from the sources
+        let mut power = HashMap::new();
+        let mut queue = VecDeque::new();
+        for &(w_x, w_y, w_z) in network.iter() {
+            let is_powered = NEIGHBOURS.iter()
+                .any(|&(dx, dy, dz)| powers(chunk_manager, (w_x + dx, w_y + dy, w_z + dz), (w_x, w_y, w_z)));
+            if is_powered {
+                power.insert((w_x, w_y, w_z), SOURCE_POWER);
+                queue.push_back((w_x, w_y, w_z));
+            }
+        }
+        while let Some(position) = queue.pop_front() {
+            let level = power[&position];
+            if level <= 1 {
+                continue;
+            }
+            for facing in 0..4 {
+                let neighbour = horizontal_neighbour(position, facing);
+                if network.contains(&neighbour) && power.get(&neighbour).map_or(true, |&l| l < level - 1) {
+                    power.insert(neighbour, level - 1);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+
+        // Store the power and the connections in the metadata of the wires, for their mesh
+        for &position in network.iter() {
+            let level = power.get(&position).cloned().unwrap_or(0);
+            if level > 0 {
+                self.powered.insert(position, level);
+            } else {
+                self.powered.remove(&position);
+            }
+
+            let connections = (0..4)
+                .filter(|&facing| {
+                    let neighbour = horizontal_neighbour(position, facing);
+                    is_wire_at(chunk_manager, neighbour) || powers(chunk_manager, neighbour, position)
+                })
+                .fold(0, |connections, facing| connections | (1 << facing));
+
+            let meta = BlockMeta::default().with_power(level).with_wire_connections(connections);
+            let (w_x, w_y, w_z) = position;
+            if chunk_manager.get_block_meta(w_x, w_y, w_z) != Some(meta) {
+                chunk_manager.set_block_meta(meta, w_x, w_y, w_z);
+            }
+        }
+    }
+}
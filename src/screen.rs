@@ -0,0 +1,80 @@
+use crate::chunk::BlockID;
+
+/// The GUI screen currently opened on top of the game.
+/// While a screen is open the cursor is released and the player can't look around
+/// or interact with the world.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Screen {
+    None,
+    Inventory,
+    Chest((i32, i32, i32)),
+    Furnace((i32, i32, i32)),
+    CraftingTable((i32, i32, i32)),
+    EnchantingTable((i32, i32, i32)),
+    Sign((i32, i32, i32)),
+    Pause,
+    Options,
+    /// The first statistic listed
+    Statistics(usize),
+    /// The page of the screenshot gallery shown
+    Screenshots(usize),
+    /// The screenshot viewed in full screen, from the gallery
+    Screenshot(usize),
+    /// Shown when the player dies, until they respawn
+    Death,
+    /// The message being typed, at the bottom of the screen
+    Chat,
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Screen::None
+    }
+}
+
+impl Screen {
+    pub fn is_open(&self) -> bool {
+        *self != Screen::None
+    }
+
+    /// Screens where the player types text, the keyboard doesn't control the game meanwhile
+    pub fn captures_keyboard(&self) -> bool {
+        match self {
+            Screen::Sign(_) | Screen::Chat => true,
+            _ => false
+        }
+    }
+
+    /// The pause menu, the screens opened from it and the death screen, the game is paused meanwhile
+    pub fn is_menu(&self) -> bool {
+        match self {
+            Screen::Pause | Screen::Options | Screen::Statistics(_) | Screen::Screenshots(_) | Screen::Screenshot(_) | Screen::Death => true,
+            _ => false
+        }
+    }
+
+    /// The screen opened by right-clicking on the block, if any
+    pub fn of_block(block: BlockID, position: (i32, i32, i32)) -> Option<Screen> {
+        match block {
+            BlockID::Chest => Some(Screen::Chest(position)),
+            BlockID::Furnace => Some(Screen::Furnace(position)),
+            BlockID::CraftingTable => Some(Screen::CraftingTable(position)),
+            BlockID::EnchantingTable => Some(Screen::EnchantingTable(position)),
+            BlockID::Sign => Some(Screen::Sign(position)),
+            _ => None
+        }
+    }
+
+    /// Position of the block the screen belongs to
+    pub fn block_position(&self) -> Option<(i32, i32, i32)> {
+        match *self {
+            Screen::None | Screen::Inventory | Screen::Pause | Screen::Options | Screen::Statistics(_) | Screen::Screenshots(_)
+            | Screen::Screenshot(_) | Screen::Death | Screen::Chat => None,
+            Screen::Chest(position) |
+            Screen::Furnace(position) |
+            Screen::CraftingTable(position) |
+            Screen::EnchantingTable(position) |
+            Screen::Sign(position) => Some(position),
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use image::RgbaImage;
+use nalgebra::Matrix4;
+use nalgebra_glm::{Mat4, vec3};
+
+use crate::constants::{SCREENSHOTS_DIRECTORY, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::gui::create_gui_vao;
+use crate::menu::{thumbnail_rect, THUMBNAILS_PER_PAGE};
+use crate::shader_compilation::ShaderProgram;
+use crate::shapes::quad;
+use crate::world_info::format_date;
+
+pub const SCREENSHOT_TEXTURE_UNIT: u32 = 11;
+/// Side of the previews of the gallery, in pixels
+const THUMBNAIL_RESOLUTION: u32 = 128;
+
+/// A PNG file of the screenshots directory
+pub struct ScreenshotEntry {
+    pub path: PathBuf,
+    pub name: String,
+    /// Seconds since the Unix epoch
+    pub modified: u64,
+}
+
+impl ScreenshotEntry {
+    /// When the screenshot was taken, as YYYY-MM-DD HH:MM in UTC
+    pub fn timestamp(&self) -> String {
+        format!("{} {:02}:{:02}", format_date(self.modified), self.modified / 3600 % 24, self.modified / 60 % 60)
+    }
+}
+
+/// The screenshots listed in the gallery of the pause menu, their textures are loaded when they are first drawn
+#[derive(Default)]
+pub struct ScreenshotGallery {
+    /// The most recent first
+    pub screenshots: Vec<ScreenshotEntry>,
+    /// 0 for the files that couldn't be read
+    thumbnails: HashMap<PathBuf, u32>,
+    /// The screenshot viewed in full screen with its texture and its size
+    full_view: Option<(PathBuf, u32, (u32, u32))>,
+}
+
+impl ScreenshotGallery {
+    /// Lists the PNG files of the screenshots directory again
+    pub fn refresh(&mut self) {
+        self.screenshots = match fs::read_dir(SCREENSHOTS_DIRECTORY) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().map_or(false, |extension| extension.eq_ignore_ascii_case("png")))
+                .map(|path| ScreenshotEntry {
+                    name: path.file_name().map_or(String::new(), |name| name.to_string_lossy().into_owned()),
+                    modified: fs::metadata(&path)
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                        .map_or(0, |duration| duration.as_secs()),
+                    path,
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        self.screenshots.sort_by(|a, b| b.modified.cmp(&a.modified));
+    }
+
+    pub fn pages(&self) -> usize {
+        (self.screenshots.len() + THUMBNAILS_PER_PAGE - 1) / THUMBNAILS_PER_PAGE
+    }
+
+    fn thumbnail(&mut self, index: usize) -> u32 {
+        let path = &self.screenshots[index].path;
+        if let Some(&texture) = self.thumbnails.get(path) {
+            return texture;
+        }
+        let texture = load_image(path)
+            .map_or(0, |image| upload_texture(&image.thumbnail_exact(THUMBNAIL_RESOLUTION, THUMBNAIL_RESOLUTION).to_rgba()));
+        self.thumbnails.insert(path.clone(), texture);
+        texture
+    }
+
+    /// Frees the texture of the screenshot viewed in full screen
+    pub fn close_full_view(&mut self) {
+        if let Some((_, texture, _)) = self.full_view.take() {
+            gl_call!(gl::DeleteTextures(1, &texture));
+        }
+    }
+
+    /// Draws the thumbnails of the screenshots of a page of the gallery with the GUI shader
+    pub fn draw_thumbnails(&mut self, page: usize, vao: u32, shader: &mut ShaderProgram) {
+        self.close_full_view();
+        let first = page * THUMBNAILS_PER_PAGE;
+        for index in first..self.screenshots.len().min(first + THUMBNAILS_PER_PAGE) {
+            let texture = self.thumbnail(index);
+            if texture != 0 {
+                let (left, bottom, size) = thumbnail_rect(index - first);
+                draw_texture(texture, (left, bottom, size, size), vao, shader);
+            }
+        }
+    }
+
+    /// Draws the screenshot as large as it fits on the screen with the GUI shader
+    pub fn draw_full_view(&mut self, index: usize, vao: u32, shader: &mut ShaderProgram) {
+        let path = self.screenshots[index].path.clone();
+        if self.full_view.as_ref().map_or(true, |(viewed, _, _)| *viewed != path) {
+            self.close_full_view();
+            self.full_view = load_image(&path).map(|image| {
+                let image = image.to_rgba();
+                let texture = upload_texture(&image);
+                (path, texture, image.dimensions())
+            });
+        }
+
+        if let Some((_, texture, (width, height))) = self.full_view {
+            let scale = (WINDOW_WIDTH as f32 / width as f32).min(WINDOW_HEIGHT as f32 / height as f32);
+            let (width, height) = (width as f32 * scale, height as f32 * scale);
+            let rect = ((WINDOW_WIDTH as f32 - width) / 2.0, (WINDOW_HEIGHT as f32 - height) / 2.0, width, height);
+            draw_texture(texture, rect, vao, shader);
+        }
+    }
+}
+
+fn load_image(path: &Path) -> Option<image::DynamicImage> {
+    match image::open(path) {
+        Ok(image) => Some(image),
+        Err(err) => {
+            error!("Couldn't read the screenshot {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+fn upload_texture(image: &RgbaImage) -> u32 {
+    let mut texture = 0;
+    gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut texture));
+    gl_call!(gl::TextureParameteri(texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32));
+    gl_call!(gl::TextureParameteri(texture, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32));
+    gl_call!(gl::TextureStorage2D(texture, 1, gl::RGBA8, image.width() as i32, image.height() as i32));
+    gl_call!(gl::TextureSubImage2D(
+        texture, 0,
+        0, 0, image.width() as i32, image.height() as i32,
+        gl::RGBA, gl::UNSIGNED_BYTE,
+        image.as_ptr() as *mut c_void));
+    texture
+}
+
+pub fn create_screenshot_vao() -> u32 {
+    create_gui_vao(&quad((0.0, 0.0, 1.0, 1.0)))
+}
+
+/// Draws the texture over the rectangle (left, bottom, width, height), in screen coordinates
+fn draw_texture(texture: u32, (left, bottom, width, height): (f32, f32, f32, f32), vao: u32, shader: &mut ShaderProgram) {
+    let translate_matrix = Matrix4::new_translation(&vec3(left + width / 2.0, bottom + height / 2.0, 0.0));
+    let scale_matrix: Mat4 = Matrix4::new_nonuniform_scaling(&vec3(width, height, 1.0));
+    let projection_matrix = nalgebra_glm::ortho(
+        0.0, WINDOW_WIDTH as f32, 0.0, WINDOW_HEIGHT as f32, -5.0, 5.0);
+
+    gl_call!(gl::BindTextureUnit(SCREENSHOT_TEXTURE_UNIT, texture));
+    shader.use_program();
+    shader.set_uniform_matrix4fv("projection", projection_matrix.as_ptr());
+    shader.set_uniform1i("tex", SCREENSHOT_TEXTURE_UNIT as i32);
+    let model_matrix = translate_matrix * scale_matrix;
+    shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+    gl_call!(gl::BindVertexArray(vao));
+    gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 6));
+}
@@ -0,0 +1,78 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{ANISOTROPY, AO_STRENGTH, FOV, MOUSE_SENSITIVITY_X, MOUSE_SENSITIVITY_Y, RENDER_DISTANCE, SETTINGS_PATH};
+
+/// The options the player can change in the options screen, saved in SETTINGS_PATH.
+/// Settings missing from the file keep the compile-time defaults
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub mouse_sensitivity_x: f32,
+    pub mouse_sensitivity_y: f32,
+    /// Vertical field of view, in degrees
+    pub fov: f32,
+    /// In chunks
+    pub render_distance: i32,
+    /// How dark the corners get, from 0 (no ambient occlusion) to 1
+    pub ao_strength: f32,
+    /// Anisotropic filtering of the textures, 1, 2, 4, 8 or 16
+    pub anisotropy: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            mouse_sensitivity_x: MOUSE_SENSITIVITY_X,
+            mouse_sensitivity_y: MOUSE_SENSITIVITY_Y,
+            fov: FOV.to_degrees(),
+            render_distance: RENDER_DISTANCE,
+            ao_strength: AO_STRENGTH,
+            anisotropy: ANISOTROPY,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, the defaults are used when it doesn't exist or can't be parsed
+    pub fn load() -> Self {
+        let text = match fs::read_to_string(SETTINGS_PATH) {
+            Ok(text) => text,
+            Err(_) => return Settings::default(),
+        };
+        match toml::from_str(&text) {
+            Ok(settings) => settings,
+            Err(err) => {
+                error!("Invalid settings in {}: {}", SETTINGS_PATH, err);
+                Settings::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if let Some(directory) = Path::new(SETTINGS_PATH).parent() {
+            if let Err(err) = fs::create_dir_all(directory) {
+                error!("Couldn't create {}: {}", directory.display(), err);
+                return;
+            }
+        }
+        let text = match toml::to_string(self) {
+            Ok(text) => text,
+            Err(err) => {
+                error!("Couldn't serialize the settings: {}", err);
+                return;
+            }
+        };
+        match fs::write(SETTINGS_PATH, text) {
+            Ok(_) => info!("Saved the settings to {}", SETTINGS_PATH),
+            Err(err) => error!("Couldn't write {}: {}", SETTINGS_PATH, err),
+        }
+    }
+
+    /// Vertical field of view, in radians
+    pub fn fov_radians(&self) -> f32 {
+        self.fov.to_radians()
+    }
+}
@@ -146,6 +146,17 @@ impl ShaderProgram {
         self
     }
 
+    /// Binds the uniform block `name` of the program to the buffer bound at `binding_point`
+    pub fn bind_ubo_block(&mut self, name: &str, binding_point: u32) -> &Self {
+        let c_name = CString::new(name).unwrap();
+        let index = gl_call!(gl::GetUniformBlockIndex(self.id, c_name.as_ptr()));
+        if index == gl::INVALID_INDEX {
+            panic!("Can't find uniform block '{}' in program with id: {}", name, self.id);
+        }
+        gl_call!(gl::UniformBlockBinding(self.id, index, binding_point));
+        self
+    }
+
     pub fn from_shaders(vertex: ShaderPart, fragment: ShaderPart) -> Result<ShaderProgram, String> {
         let program_id = gl_call!(gl::CreateProgram());
 
@@ -0,0 +1,14 @@
+#version 450 core
+
+out vec4 Color;
+
+uniform vec3 color;
+
+in VertexAttributes {
+    float height;
+} attrs;
+
+void main() {
+    // Fades out towards the top of the world
+    Color = vec4(color, 0.6 * (1.0 - attrs.height));
+}
@@ -0,0 +1,29 @@
+#version 450 core
+
+uniform mat4 model;
+// The camera, the fog and the time, the same for every shader drawing the world
+layout(std140, binding = 0) uniform FrameData {
+    mat4 view;
+    mat4 projection;
+    vec4 sun_direction;
+    vec4 fog_color;
+    // Seconds since the start of the game
+    float global_time;
+    float render_distance;
+    // Thicker fog when it rains
+    float fog_density_multiplier;
+};
+uniform float top_width;
+
+layout (location = 0) in vec3 pos;
+
+out VertexAttributes {
+    float height;
+} attrs;
+
+void main() {
+    attrs.height = pos.y;
+    // 1 block wide at the base, widening up to the top of the world
+    float width = mix(1.0, top_width, pos.y);
+    gl_Position = projection * view * model * vec4(pos.x * width, pos.y, pos.z * width, 1.0);
+}
@@ -0,0 +1,38 @@
+#version 450 core
+
+out vec4 Color;
+
+// The camera, the fog and the time, the same for every shader drawing the world
+layout(std140, binding = 0) uniform FrameData {
+    mat4 view;
+    mat4 projection;
+    vec4 sun_direction;
+    vec4 fog_color;
+    // Seconds since the start of the game
+    float global_time;
+    float render_distance;
+    // Thicker fog when it rains
+    float fog_density_multiplier;
+};
+
+uniform sampler2D clouds;
+uniform vec3 color;
+// Blocks covered by a texel of the cloud texture, and blocks per second the clouds drift along +x
+uniform float texel_size;
+uniform float speed;
+// The clouds fade out towards the edges of the layer, this far from the camera
+uniform vec3 camera_position;
+uniform float radius;
+
+in VertexAttributes {
+    vec3 world_position;
+} attrs;
+
+void main() {
+    vec2 uv = (attrs.world_position.xz - vec2(global_time * speed, 0.0)) / (texel_size * vec2(textureSize(clouds, 0)));
+    if (texture(clouds, uv).r < 0.5) {
+        discard;
+    }
+    float distance = length(attrs.world_position.xz - camera_position.xz);
+    Color = vec4(color, 0.8 * (1.0 - smoothstep(0.5 * radius, radius, distance)));
+}
@@ -0,0 +1,45 @@
+#version 450 core
+
+// Ordered dithering thresholds, the same as in voxel.frag
+const float BAYER_4X4[16] = float[](
+     0.0,  8.0,  2.0, 10.0,
+    12.0,  4.0, 14.0,  6.0,
+     3.0, 11.0,  1.0,  9.0,
+    15.0,  7.0, 13.0,  5.0
+);
+
+uniform sampler2DArray array_texture;
+uniform int campfire_layer;
+uniform int campfire_frame_layer;
+
+in VertexAttributes {
+    vec3 texture_coords;
+    vec3 normal;
+    float ao;
+    flat int emissive;
+    float visibility;
+    vec2 climate;
+    vec2 block_light;
+    vec3 world_position;
+    float chunk_opacity;
+} attrs;
+
+// Only writes the depth of the chunks. It must discard exactly the fragments voxel.frag discards,
+// which then only shades the fragments whose depth is equal to the one written here
+void main() {
+    if (attrs.chunk_opacity < 1.0) {
+        ivec2 pixel = ivec2(gl_FragCoord.xy) % 4;
+        if ((BAYER_4X4[pixel.y * 4 + pixel.x] + 0.5) / 16.0 > attrs.chunk_opacity) {
+            discard;
+        }
+    }
+
+    // The frames of the flames of the campfires don't cover the same texels
+    vec3 texture_coords = attrs.texture_coords;
+    if (int(texture_coords.z + 0.5) == campfire_layer) {
+        texture_coords.z = float(campfire_frame_layer);
+    }
+    if (texture(array_texture, texture_coords).a == 0) {
+        discard;
+    }
+}
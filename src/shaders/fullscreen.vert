@@ -0,0 +1,12 @@
+#version 450 core
+
+out VertexAttributes {
+    vec2 texture_coords;
+} attrs;
+
+void main() {
+    // A single triangle covering the whole screen, drawn without any vertex buffer
+    vec2 position = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+    attrs.texture_coords = position;
+    gl_Position = vec4(position * 2.0 - 1.0, 0.0, 1.0);
+}
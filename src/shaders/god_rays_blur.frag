@@ -0,0 +1,25 @@
+#version 450 core
+
+out vec4 Color;
+
+uniform sampler2D occlusion_map;
+uniform vec2 sun_position;
+
+in VertexAttributes {
+    vec2 texture_coords;
+} attrs;
+
+const int SAMPLES = 16;
+// Fraction of the distance to the sun covered by the blur
+const float STRENGTH = 0.15;
+
+void main() {
+    // Average the samples on the line going through the pixel and the sun
+    vec2 direction = attrs.texture_coords - sun_position;
+    vec3 sum = vec3(0.0);
+    for (int i = 0; i < SAMPLES; i++) {
+        float scale = 1.0 - STRENGTH * float(i) / float(SAMPLES);
+        sum += texture(occlusion_map, sun_position + direction * scale).rgb;
+    }
+    Color = vec4(sum / float(SAMPLES), 1.0);
+}
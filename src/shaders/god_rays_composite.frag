@@ -0,0 +1,16 @@
+#version 450 core
+
+out vec4 Color;
+
+uniform sampler2D god_rays_map;
+uniform vec3 sun_color;
+uniform float intensity;
+
+in VertexAttributes {
+    vec2 texture_coords;
+} attrs;
+
+void main() {
+    // Added to the scene
+    Color = vec4(texture(god_rays_map, attrs.texture_coords).rgb * sun_color * intensity, 1.0);
+}
@@ -0,0 +1,43 @@
+#version 450 core
+
+out vec4 Color;
+
+uniform sampler2D depth_map;
+// Position of the sun on the screen, in texture coordinates
+uniform vec2 sun_position;
+// Depth of the sun, anything closer hides it
+uniform float sun_depth;
+uniform float aspect_ratio;
+
+in VertexAttributes {
+    vec2 texture_coords;
+} attrs;
+
+const int SAMPLES = 64;
+const float DECAY = 0.97;
+// How fast the light fades away from the sun, in screen heights
+const float GLOW_FALLOFF = 3.0;
+
+// Brightness of the sky around the sun
+float sun_glow(vec2 position) {
+    vec2 offset = (position - sun_position) * vec2(aspect_ratio, 1.0);
+    return exp(-length(offset) * GLOW_FALLOFF);
+}
+
+void main() {
+    // March from the pixel to the sun and accumulate the light of the sky seen on the way
+    vec2 position = attrs.texture_coords;
+    vec2 step = (sun_position - position) / float(SAMPLES);
+    float weight = 1.0;
+    float total_weight = 0.0;
+    float light = 0.0;
+    for (int i = 0; i < SAMPLES; i++) {
+        if (texture(depth_map, position).r >= sun_depth) {
+            light += sun_glow(position) * weight;
+        }
+        total_weight += weight;
+        weight *= DECAY;
+        position += step;
+    }
+    Color = vec4(vec3(light / total_weight), 1.0);
+}
@@ -0,0 +1,16 @@
+#version 450 core
+
+out vec4 Color;
+
+uniform vec3 color;
+
+in VertexAttributes {
+    vec3 normal;
+} attrs;
+
+void main() {
+    Color = vec4(color, 1.0);
+    // Same directional shading as the chunks, the normals can be rotated by the model matrix
+    Color.rgb *= 1.0 - abs(attrs.normal.x) * 0.35;
+    Color.rgb *= 1.0 - abs(attrs.normal.z) * 0.15;
+}
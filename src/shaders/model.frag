@@ -0,0 +1,21 @@
+#version 450 core
+
+out vec4 Color;
+
+uniform sampler2DArray tex;
+
+in VertexAttributes {
+    vec3 texture_coords;
+    vec3 normal;
+} attrs;
+
+void main() {
+    vec4 diffuse_frag = texture(tex, attrs.texture_coords);
+    if (diffuse_frag.a == 0.0) {
+        discard;
+    }
+    Color = diffuse_frag;
+    // Same directional shading as the chunks, the normals can be rotated by the model matrix
+    Color.rgb *= 1.0 - abs(attrs.normal.x) * 0.35;
+    Color.rgb *= 1.0 - abs(attrs.normal.z) * 0.15;
+}
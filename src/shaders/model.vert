@@ -0,0 +1,30 @@
+#version 450 core
+
+uniform mat4 model;
+// The camera, the fog and the time, the same for every shader drawing the world
+layout(std140, binding = 0) uniform FrameData {
+    mat4 view;
+    mat4 projection;
+    vec4 sun_direction;
+    vec4 fog_color;
+    // Seconds since the start of the game
+    float global_time;
+    float render_distance;
+    // Thicker fog when it rains
+    float fog_density_multiplier;
+};
+
+layout (location = 0) in vec3 pos;
+layout (location = 1) in vec3 texture_coords;
+layout (location = 2) in vec3 normal;
+
+out VertexAttributes {
+    vec3 texture_coords;
+    vec3 normal;
+} attrs;
+
+void main() {
+    attrs.texture_coords = texture_coords;
+    attrs.normal = vec3(model * vec4(normal, 0.0));
+    gl_Position = projection * view * model * vec4(pos, 1.0);
+}
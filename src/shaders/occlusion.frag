@@ -0,0 +1,5 @@
+#version 450 core
+
+// Only the depth test matters, the color writes are disabled while drawing the bounding boxes
+void main() {
+}
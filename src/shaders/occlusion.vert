@@ -0,0 +1,21 @@
+#version 450 core
+
+uniform mat4 model;
+// The camera, the fog and the time, the same for every shader drawing the world
+layout(std140, binding = 0) uniform FrameData {
+    mat4 view;
+    mat4 projection;
+    vec4 sun_direction;
+    vec4 fog_color;
+    // Seconds since the start of the game
+    float global_time;
+    float render_distance;
+    // Thicker fog when it rains
+    float fog_density_multiplier;
+};
+
+layout (location = 0) in vec3 pos;
+
+void main() {
+    gl_Position = projection * view * model * vec4(pos, 1.0);
+}
@@ -2,6 +2,9 @@
 
 out vec4 Color;
 
+// Tells whether the item in hand is the right tool for the targeted block
+uniform vec4 outline_color;
+
 void main() {
-    Color = vec4(0.0, 0.0, 0.0, 0.35);
+    Color = outline_color;
 }
@@ -6,9 +6,10 @@ uniform sampler2DArray array_texture;
 
 in VertexAttributes {
     vec3 texture_coords;
+    vec4 tint;
 } attrs;
 
 void main() {
     vec4 diffuse_frag = texture(array_texture, attrs.texture_coords);
-    Color = diffuse_frag;
+    Color = diffuse_frag * attrs.tint;
 }
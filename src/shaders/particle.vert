@@ -2,12 +2,15 @@
 
 layout (location = 0) in vec4 pos;
 layout (location = 1) in vec3 texture_coords;
+layout (location = 2) in vec4 tint;
 
 out VertexAttributes {
     vec3 texture_coords;
+    vec4 tint;
 } attrs;
 
 void main() {
     attrs.texture_coords = texture_coords;
+    attrs.tint = tint;
     gl_Position = pos;
 }
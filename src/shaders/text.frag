@@ -0,0 +1,18 @@
+#version 450 core
+
+out vec4 Color;
+
+uniform sampler2D tex;
+uniform vec4 color;
+
+in VertexAttributes {
+    vec2 texture_coords;
+} attrs;
+
+void main() {
+    vec4 diffuse_frag = texture(tex, attrs.texture_coords);
+    if (diffuse_frag.a == 0.0) {
+        discard;
+    }
+    Color = diffuse_frag * color;
+}
@@ -2,23 +2,117 @@
 
 out vec4 Color;
 
+const vec3 WARM_LIGHT = vec3(1.0, 0.75, 0.4);
+const vec3 COOL_LIGHT = vec3(0.45, 0.8, 1.0);
+const float BLOCK_LIGHT_INTENSITY = 0.6;
+const float SEA_LANTERN_PULSE_PERIOD = 1.5;
+// Radians per second the center of a portal turns
+const float PORTAL_SWIRL_SPEED = 1.5;
+// The caustics fade out over this many blocks under the surface
+const float CAUSTICS_DEPTH = 12.0;
+// Ordered dithering thresholds, used to fade the chunks without sorting them
+const float BAYER_4X4[16] = float[](
+     0.0,  8.0,  2.0, 10.0,
+    12.0,  4.0, 14.0,  6.0,
+     3.0, 11.0,  1.0,  9.0,
+    15.0,  7.0, 13.0,  5.0
+);
+
+// The camera, the fog and the time, the same for every shader drawing the world
+layout(std140, binding = 0) uniform FrameData {
+    mat4 view;
+    mat4 projection;
+    vec4 sun_direction;
+    vec4 fog_color;
+    // Seconds since the start of the game
+    float global_time;
+    float render_distance;
+    // Thicker fog when it rains
+    float fog_density_multiplier;
+};
+
 uniform sampler2DArray array_texture;
+uniform sampler2D grasscolor_map;
+uniform int grass_top_layer;
+uniform int portal_layer;
+// The flames of the campfires are drawn with the frame of their animation in campfire_frame_layer
+uniform int campfire_layer;
+uniform int campfire_frame_layer;
 uniform bool enable_fog;
-uniform vec3 sky_color;
-
+// The game tick, used to animate the light sources
+uniform int light_tick;
+// Light patterns of the waves on the blocks under the water surface, seen from above the water
+uniform sampler2D caustics;
+uniform float water_level;
+uniform bool underwater;
+// How dark the ambient occlusion makes the corners, set in the options screen
+uniform float ao_strength;
+// Daylight reaching the world, from 0.1 at night to 1.0 during the day
+uniform float sky_brightness;
+// The clouds shade the terrain under them, seen from the sun
+uniform sampler2D clouds;
+uniform float cloud_height;
+uniform float cloud_texel_size;
+uniform float cloud_speed;
+uniform float cloud_shadow_strength;
 in VertexAttributes {
     vec3 texture_coords;
     vec3 normal;
     float ao;
+    // The emissive mask of the face is in the layer after its texture
+    flat int emissive;
     float visibility;
+    vec2 climate;
+    vec2 block_light;
+    vec3 world_position;
+    // The chunks fade in once loaded and fade out before being unloaded
+    float chunk_opacity;
 } attrs;
 
+float hash(vec3 p) {
+    return fract(sin(dot(p, vec3(12.9898, 78.233, 37.719))) * 43758.5453);
+}
+
 void main() {
-    vec4 diffuse_frag = texture(array_texture, attrs.texture_coords);
+    // A fading chunk discards a growing part of its fragments, the depth buffer stays correct
+    if (attrs.chunk_opacity < 1.0) {
+        ivec2 pixel = ivec2(gl_FragCoord.xy) % 4;
+        if ((BAYER_4X4[pixel.y * 4 + pixel.x] + 0.5) / 16.0 > attrs.chunk_opacity) {
+            discard;
+        }
+    }
+
+    // The portals swirl, their texture turns faster towards its center
+    vec3 texture_coords = attrs.texture_coords;
+    if (int(texture_coords.z + 0.5) == portal_layer) {
+        vec2 centered = fract(texture_coords.xy) - 0.5;
+        float angle = global_time * PORTAL_SWIRL_SPEED * (1.0 - length(centered));
+        texture_coords.xy = fract(mat2(cos(angle), sin(angle), -sin(angle), cos(angle)) * centered + 0.5);
+    }
+    if (int(texture_coords.z + 0.5) == campfire_layer) {
+        texture_coords.z = float(campfire_frame_layer);
+    }
+
+    vec4 diffuse_frag = texture(array_texture, texture_coords);
     if (diffuse_frag.a == 0) {
         discard;
     }
     Color = diffuse_frag;
+
+    // The glowing parts of self-lit blocks ignore the lighting and stay at full brightness
+    bool is_lit = attrs.emissive == 1
+        && texture(array_texture, texture_coords + vec3(0.0, 0.0, 1.0)).a > 0.5;
+    if (is_lit) {
+        if (enable_fog) {
+            Color = mix(vec4(fog_color.rgb, 1.0), Color, attrs.visibility);
+        }
+        return;
+    }
+
+    // The top of the grass blocks changes color depending on the climate
+    if (int(attrs.texture_coords.z + 0.5) == grass_top_layer && attrs.normal.y > 0.0) {
+        Color.rgb *= texture(grasscolor_map, vec2(attrs.climate.x, 1.0 - attrs.climate.y)).rgb;
+    }
     if (attrs.normal.x != 0.0) {
         Color.rgb *= 0.65;
     } else if (attrs.normal.z != 0.0) {
@@ -26,9 +120,33 @@ void main() {
     } else if (attrs.normal.y != 0.0) {
 //        Color.rgb *= 0.9;
     }
-    Color.rgb *= (1.0 - attrs.ao * 0.15);
+    Color.rgb *= (1.0 - attrs.ao * 0.15 * ao_strength);
+
+    // Glowstone flickers by one level every tick, Sea Lanterns pulse between 14 and 15
+    float red_light = attrs.block_light.x;
+    if (red_light > 0.0) {
+        vec2 block_xz = floor(attrs.world_position.xz - attrs.normal.xz * 0.5);
+        red_light = clamp(red_light + floor(hash(vec3(block_xz, light_tick)) * 3.0) - 1.0, 0.0, 15.0);
+    }
+    float pulse = 14.5 + 0.5 * sin(6.2831853 * global_time / SEA_LANTERN_PULSE_PERIOD);
+    float blue_light = attrs.block_light.y * pulse / 15.0;
+    vec3 light_color = mix(WARM_LIGHT, COOL_LIGHT, blue_light / (red_light + blue_light + 0.001));
+    Color.rgb *= sky_brightness + light_color * max(red_light, blue_light) / 15.0 * BLOCK_LIGHT_INTENSITY;
+
+    if (cloud_shadow_strength > 0.0 && attrs.world_position.y < cloud_height) {
+        vec3 sun = normalize(sun_direction.xyz);
+        vec2 cloud_position = attrs.world_position.xz + sun.xz / sun.y * (cloud_height - attrs.world_position.y);
+        vec2 uv = (cloud_position - vec2(global_time * cloud_speed, 0.0)) / (cloud_texel_size * vec2(textureSize(clouds, 0)));
+        Color.rgb *= 1.0 - cloud_shadow_strength * texture(clouds, uv).r;
+    }
+
+    if (!underwater && attrs.world_position.y < water_level) {
+        float caustic = texture(caustics, attrs.world_position.xz / 4.0 + global_time * 0.05).r;
+        float strength = 1.0 - clamp((water_level - attrs.world_position.y) / CAUSTICS_DEPTH, 0.0, 1.0);
+        Color.rgb *= mix(1.0, 0.75 + caustic * 0.75, strength);
+    }
 
     if (enable_fog) {
-        Color = mix(vec4(sky_color, 1.0), Color, attrs.visibility);
+        Color = mix(vec4(fog_color.rgb, 1.0), Color, attrs.visibility);
     }
 }
\ No newline at end of file
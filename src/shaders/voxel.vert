@@ -2,33 +2,70 @@
 
 const float fog_gradient = 20.0;
 
-uniform mat4 model;
-uniform mat4 view;
-uniform mat4 projection;
-uniform float render_distance;
+// The camera, the fog and the time, the same for every shader drawing the world
+layout(std140, binding = 0) uniform FrameData {
+    mat4 view;
+    mat4 projection;
+    vec4 sun_direction;
+    vec4 fog_color;
+    // Seconds since the start of the game
+    float global_time;
+    float render_distance;
+    // Thicker fog when it rains
+    float fog_density_multiplier;
+};
+// Only used when GL_CLIP_DISTANCE0 is enabled (reflection pass)
+uniform vec4 clip_plane;
 
 layout (location = 0) in vec3 pos;
 layout (location = 1) in vec3 texture_coords;
 layout (location = 2) in vec3 normal;
+// From 0 to 3, plus 4 on the faces with an emissive mask
 layout (location = 3) in float ao;
+layout (location = 4) in vec2 block_light;
+// The same for every vertex of a chunk: its origin in the world and its opacity,
+// then the climate at the corners (-x -z, +x -z, -x +z, +x +z) of its column
+layout (location = 5) in vec4 chunk_origin_opacity;
+layout (location = 6) in vec4 column_temperature;
+layout (location = 7) in vec4 column_humidity;
 
 out VertexAttributes {
     vec3 texture_coords;
     vec3 normal;
     float ao;
+    flat int emissive;
     float visibility;
+    vec2 climate;
+    vec2 block_light;
+    vec3 world_position;
+    float chunk_opacity;
 } attrs;
 
+// The depth prepass draws the chunks with this vertex shader too, the depths must match exactly
+invariant gl_Position;
+
 void main() {
     attrs.texture_coords = texture_coords;
     attrs.normal = normal;
-    attrs.ao = ao;
+    attrs.ao = mod(ao, 4.0);
+    attrs.emissive = ao >= 4.0 ? 1 : 0;
     attrs.visibility = 1.0;
-    vec4 frag_pos = view * model * vec4(pos, 1.0f);
+    attrs.block_light = block_light;
+    attrs.chunk_opacity = chunk_origin_opacity.w;
+
+    vec2 t = pos.xz / 16.0;
+    vec2 temperature = mix(column_temperature.xy, column_temperature.zw, t.y);
+    vec2 humidity = mix(column_humidity.xy, column_humidity.zw, t.y);
+    attrs.climate = vec2(mix(temperature.x, temperature.y, t.x), mix(humidity.x, humidity.y, t.x));
+
+    vec4 world_pos = vec4(chunk_origin_opacity.xyz + pos, 1.0f);
+    attrs.world_position = world_pos.xyz;
+    gl_ClipDistance[0] = dot(world_pos, clip_plane);
+    vec4 frag_pos = view * world_pos;
     gl_Position = projection * frag_pos;
 
     // Fog
-    float fog_density = 0.066 / render_distance;
+    float fog_density = 0.066 / render_distance * fog_density_multiplier;
     float distance = length(frag_pos.xyz);
     attrs.visibility = exp(-pow(distance * fog_density, fog_gradient));
 }
\ No newline at end of file
@@ -0,0 +1,55 @@
+#version 450 core
+
+const vec3 water_normal = vec3(0.0, 1.0, 0.0);
+const vec3 underwater_tint = vec3(0.55, 0.7, 0.9);
+
+out vec4 Color;
+
+// The camera, the fog and the time, the same for every shader drawing the world
+layout(std140, binding = 0) uniform FrameData {
+    mat4 view;
+    mat4 projection;
+    vec4 sun_direction;
+    vec4 fog_color;
+    // Seconds since the start of the game
+    float global_time;
+    float render_distance;
+    // Thicker fog when it rains
+    float fog_density_multiplier;
+};
+
+uniform sampler2DArray array_texture;
+uniform sampler2D reflection_texture;
+uniform vec2 screen_size;
+uniform vec3 camera_position;
+uniform bool enable_fog;
+// The chunks fade in once loaded and fade out before being unloaded
+uniform float chunk_opacity;
+
+in VertexAttributes {
+    vec3 texture_coords;
+    vec3 normal;
+    vec3 world_pos;
+    float visibility;
+} attrs;
+
+void main() {
+    vec4 diffuse_frag = texture(array_texture, attrs.texture_coords);
+    vec3 refraction_color = diffuse_frag.rgb * underwater_tint;
+    Color = vec4(refraction_color, diffuse_frag.a);
+
+    // Only the surface of the water reflects
+    if (attrs.normal.y > 0.0) {
+        // The reflection texture was rendered with the same projection, so the screen position is the UV
+        vec3 reflection_color = texture(reflection_texture, gl_FragCoord.xy / screen_size).rgb;
+        vec3 view_dir = normalize(camera_position - attrs.world_pos);
+        float fresnel = pow(1.0 - max(dot(view_dir, water_normal), 0.0), 4.0);
+        Color.rgb = mix(refraction_color, reflection_color, fresnel);
+        Color.a = mix(diffuse_frag.a, 1.0, fresnel);
+    }
+
+    if (enable_fog) {
+        Color = mix(vec4(fog_color.rgb, 1.0), Color, attrs.visibility);
+    }
+    Color.a *= chunk_opacity;
+}
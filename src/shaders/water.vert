@@ -0,0 +1,42 @@
+#version 450 core
+
+const float fog_gradient = 20.0;
+
+uniform mat4 model;
+// The camera, the fog and the time, the same for every shader drawing the world
+layout(std140, binding = 0) uniform FrameData {
+    mat4 view;
+    mat4 projection;
+    vec4 sun_direction;
+    vec4 fog_color;
+    // Seconds since the start of the game
+    float global_time;
+    float render_distance;
+    // Thicker fog when it rains
+    float fog_density_multiplier;
+};
+
+layout (location = 0) in vec3 pos;
+layout (location = 1) in vec3 texture_coords;
+layout (location = 2) in vec3 normal;
+
+out VertexAttributes {
+    vec3 texture_coords;
+    vec3 normal;
+    vec3 world_pos;
+    float visibility;
+} attrs;
+
+void main() {
+    attrs.texture_coords = texture_coords;
+    attrs.normal = normal;
+    vec4 world_pos = model * vec4(pos, 1.0f);
+    attrs.world_pos = world_pos.xyz;
+    vec4 frag_pos = view * world_pos;
+    gl_Position = projection * frag_pos;
+
+    // Fog
+    float fog_density = 0.066 / render_distance * fog_density_multiplier;
+    float distance = length(frag_pos.xyz);
+    attrs.visibility = exp(-pow(distance * fog_density, fog_gradient));
+}
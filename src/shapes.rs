@@ -1,4 +1,4 @@
-use crate::types::{UVFaces, TextureLayer};
+use crate::types::{EmissiveFaces, UVFaces, TextureLayer};
 
 
 pub fn quad(uv: (f32, f32, f32, f32)) -> Vec<f32> {
@@ -23,14 +23,20 @@ pub fn quad_array_texture() -> Vec<f32> {
     ]).to_vec()
 }
 
+/// Height of the top of a dirt path, a sixteenth lower than a full block
+pub const DIRT_PATH_HEIGHT: f32 = 15.0 / 16.0;
+
 // bl = bottom left
 // tr = top right
-// Creates and write the vertices of a cube directly into "ptr" (usually a VBO mapped to virtual memory)
-pub unsafe fn write_unit_cube_to_ptr(ptr: *mut f32, x: f32, y: f32, z: f32,
-                                     (front_layer, back_layer, top_layer, bottom_layer, left_layer, right_layer): (TextureLayer, TextureLayer, TextureLayer, TextureLayer, TextureLayer, TextureLayer),
-                                     [right, left, top, bottom, front, back]: [bool; 6],
-                                     ao: [[u8; 4]; 6]) -> u32 {
-    let vertex_size = 10;
+// Creates and write the vertices of a box directly into "ptr" (usually a VBO mapped to virtual memory).
+// It is a unit cube whose top is at `height`, 1 for a full block
+pub unsafe fn write_box_to_ptr(ptr: *mut f32, x: f32, y: f32, z: f32, height: f32,
+                               (front_layer, back_layer, top_layer, bottom_layer, left_layer, right_layer): (TextureLayer, TextureLayer, TextureLayer, TextureLayer, TextureLayer, TextureLayer),
+                               (front_emissive, back_emissive, top_emissive, bottom_emissive, left_emissive, right_emissive): EmissiveFaces,
+                               [right, left, top, bottom, front, back]: [bool; 6],
+                               ao: [[u8; 4]; 6],
+                               light: [(u8, u8); 6]) -> u32 {
+    let vertex_size = 12;
     let vertices_per_face = 6;
     let face_size = vertex_size * vertices_per_face;
 
@@ -38,82 +44,122 @@ pub unsafe fn write_unit_cube_to_ptr(ptr: *mut f32, x: f32, y: f32, z: f32,
     let mut copied_vertices = 0;
 
     let uv = (0.0, 0.0, 1.0, 1.0);
+    // The sides of a lower box show the top of their texture
+    let side_uv = (0.0, 1.0 - height, 1.0, 1.0);
 
-    // First 3 floats contain the position, last 2 are the UV coordinates
-    if front {
-        ptr.offset(i).copy_from_nonoverlapping([
-            0.0f32 + x,  0.0 + y, 1.0 + z, uv.0, uv.1, front_layer as f32, 0.0, 0.0, 1.0, ao[4][0] as f32,
-            1.0 + x,  0.0 + y,  1.0 + z, uv.2, uv.1, front_layer as f32, 0.0, 0.0, 1.0, ao[4][1] as f32,
-            1.0 + x,  1.0 + y,  1.0 + z, uv.2, uv.3, front_layer as f32, 0.0, 0.0, 1.0, ao[4][2] as f32,
-            1.0 + x,  1.0 + y,  1.0 + z, uv.2, uv.3, front_layer as f32, 0.0, 0.0, 1.0, ao[4][2] as f32,
-            0.0 + x,  1.0 + y,  1.0 + z, uv.0, uv.3, front_layer as f32, 0.0, 0.0, 1.0, ao[4][3] as f32,
-            0.0 + x,  0.0 + y,  1.0 + z, uv.0, uv.1, front_layer as f32, 0.0, 0.0, 1.0, ao[4][0] as f32,
-        ].as_ptr(), face_size);
+    // Writes the 2 triangles of a face given its 4 corners, counterclockwise.
+    // The AO is interpolated across each triangle, so the quad is split along the diagonal
+    // whose corners are the least occluded. Otherwise a dark stripe shows up along the diagonal.
+    let mut write_face = |corners: [(f32, f32, f32, f32, f32); 4], layer: TextureLayer, emissive: bool, normal: (f32, f32, f32), face: usize| {
+        let ao = ao[face];
+        let order = if ao[0] + ao[2] > ao[1] + ao[3] {
+            [1, 2, 3, 3, 0, 1]
+        } else {
+            [0, 1, 2, 2, 3, 0]
+        };
+
+        // Position, UV coordinates and layer, normal, AO and the block light (warm, cool).
+        // The AO goes from 0 to 3, 4 is added to it when the face has an emissive mask
+        let emissive_flag = if emissive { 4.0 } else { 0.0 };
+        let mut vertices = [0.0f32; 72];
+        for (vertex, &corner) in vertices.chunks_exact_mut(vertex_size).zip(order.iter()) {
+            let (p_x, p_y, p_z, u, v) = corners[corner];
+            vertex.copy_from_slice(&[
+                p_x + x, p_y + y, p_z + z, u, v, layer as f32, normal.0, normal.1, normal.2,
+                ao[corner] as f32 + emissive_flag, light[face].0 as f32, light[face].1 as f32,
+            ]);
+        }
+        ptr.offset(i).copy_from_nonoverlapping(vertices.as_ptr(), face_size);
         i += face_size as isize;
         copied_vertices += vertices_per_face;
+    };
+
+    if front {
+        write_face([
+            (0.0, 0.0, 1.0, side_uv.0, side_uv.1),
+            (1.0, 0.0, 1.0, side_uv.2, side_uv.1),
+            (1.0, height, 1.0, side_uv.2, side_uv.3),
+            (0.0, height, 1.0, side_uv.0, side_uv.3),
+        ], front_layer, front_emissive, (0.0, 0.0, 1.0), 4);
     }
     if back {
-        ptr.offset(i).copy_from_nonoverlapping([
-            1.0 + x,  0.0 + y,  0.0 + z, uv.0, uv.1, back_layer as f32, 0.0, 0.0, -1.0, ao[5][0] as f32,
-            0.0 + x,  0.0 + y,  0.0 + z, uv.2, uv.1, back_layer as f32, 0.0, 0.0, -1.0, ao[5][1] as f32,
-            0.0 + x,  1.0 + y,  0.0 + z, uv.2, uv.3, back_layer as f32, 0.0, 0.0, -1.0, ao[5][2] as f32,
-            0.0 + x,  1.0 + y,  0.0 + z, uv.2, uv.3, back_layer as f32, 0.0, 0.0, -1.0, ao[5][2] as f32,
-            1.0 + x,  1.0 + y,  0.0 + z, uv.0, uv.3, back_layer as f32, 0.0, 0.0, -1.0, ao[5][3] as f32,
-            1.0 + x,  0.0 + y,  0.0 + z, uv.0, uv.1, back_layer as f32, 0.0, 0.0, -1.0, ao[5][0] as f32,
-        ].as_ptr(), face_size);
-        i += face_size as isize;
-        copied_vertices += vertices_per_face;
+        write_face([
+            (1.0, 0.0, 0.0, side_uv.0, side_uv.1),
+            (0.0, 0.0, 0.0, side_uv.2, side_uv.1),
+            (0.0, height, 0.0, side_uv.2, side_uv.3),
+            (1.0, height, 0.0, side_uv.0, side_uv.3),
+        ], back_layer, back_emissive, (0.0, 0.0, -1.0), 5);
     }
     if left {
-        ptr.offset(i).copy_from_nonoverlapping([
-            0.0 + x,  0.0 + y,  0.0 + z, uv.0, uv.1, left_layer as f32, -1.0, 0.0, 0.0, ao[1][0] as f32,
-            0.0 + x,  0.0 + y,  1.0 + z, uv.2, uv.1, left_layer as f32, -1.0, 0.0, 0.0, ao[1][1] as f32,
-            0.0 + x,  1.0 + y,  1.0 + z, uv.2, uv.3, left_layer as f32, -1.0, 0.0, 0.0, ao[1][2] as f32,
-            0.0 + x,  1.0 + y,  1.0 + z, uv.2, uv.3, left_layer as f32, -1.0, 0.0, 0.0, ao[1][2] as f32,
-            0.0 + x,  1.0 + y,  0.0 + z, uv.0, uv.3, left_layer as f32, -1.0, 0.0, 0.0, ao[1][3] as f32,
-            0.0 + x,  0.0 + y,  0.0 + z, uv.0, uv.1, left_layer as f32, -1.0, 0.0, 0.0, ao[1][0] as f32,
-        ].as_ptr(), face_size);
-        i += face_size as isize;
-        copied_vertices += vertices_per_face;
+        write_face([
+            (0.0, 0.0, 0.0, side_uv.0, side_uv.1),
+            (0.0, 0.0, 1.0, side_uv.2, side_uv.1),
+            (0.0, height, 1.0, side_uv.2, side_uv.3),
+            (0.0, height, 0.0, side_uv.0, side_uv.3),
+        ], left_layer, left_emissive, (-1.0, 0.0, 0.0), 1);
     }
     if right {
-        ptr.offset(i).copy_from_nonoverlapping([
-            1.0 + x,  0.0 + y,  1.0 + z, uv.0, uv.1, right_layer as f32, 1.0, 0.0, 0.0, ao[0][0] as f32,
-            1.0 + x,  0.0 + y,  0.0 + z, uv.2, uv.1, right_layer as f32, 1.0, 0.0, 0.0, ao[0][1] as f32,
-            1.0 + x,  1.0 + y,  0.0 + z, uv.2, uv.3, right_layer as f32, 1.0, 0.0, 0.0, ao[0][2] as f32,
-            1.0 + x,  1.0 + y,  0.0 + z, uv.2, uv.3, right_layer as f32, 1.0, 0.0, 0.0, ao[0][2] as f32,
-            1.0 + x,  1.0 + y,  1.0 + z, uv.0, uv.3, right_layer as f32, 1.0, 0.0, 0.0, ao[0][3] as f32,
-            1.0 + x,  0.0 + y,  1.0 + z, uv.0, uv.1, right_layer as f32, 1.0, 0.0, 0.0, ao[0][0] as f32,
-        ].as_ptr(), face_size);
-        i += face_size as isize;
-        copied_vertices += vertices_per_face;
+        write_face([
+            (1.0, 0.0, 1.0, side_uv.0, side_uv.1),
+            (1.0, 0.0, 0.0, side_uv.2, side_uv.1),
+            (1.0, height, 0.0, side_uv.2, side_uv.3),
+            (1.0, height, 1.0, side_uv.0, side_uv.3),
+        ], right_layer, right_emissive, (1.0, 0.0, 0.0), 0);
     }
     if top {
-        ptr.offset(i).copy_from_nonoverlapping([
-            0.0 + x,  1.0 + y,  1.0 + z, uv.0, uv.1, top_layer as f32, 0.0, 1.0, 0.0, ao[2][0] as f32,
-            1.0 + x,  1.0 + y,  1.0 + z, uv.2, uv.1, top_layer as f32, 0.0, 1.0, 0.0, ao[2][1] as f32,
-            1.0 + x,  1.0 + y,  0.0 + z, uv.2, uv.3, top_layer as f32, 0.0, 1.0, 0.0, ao[2][2] as f32,
-            1.0 + x,  1.0 + y,  0.0 + z, uv.2, uv.3, top_layer as f32, 0.0, 1.0, 0.0, ao[2][2] as f32,
-            0.0 + x,  1.0 + y,  0.0 + z, uv.0, uv.3, top_layer as f32, 0.0, 1.0, 0.0, ao[2][3] as f32,
-            0.0 + x,  1.0 + y,  1.0 + z, uv.0, uv.1, top_layer as f32, 0.0, 1.0, 0.0, ao[2][0] as f32,
-        ].as_ptr(), face_size);
-        i += face_size as isize;
-        copied_vertices += vertices_per_face;
+        write_face([
+            (0.0, height, 1.0, uv.0, uv.1),
+            (1.0, height, 1.0, uv.2, uv.1),
+            (1.0, height, 0.0, uv.2, uv.3),
+            (0.0, height, 0.0, uv.0, uv.3),
+        ], top_layer, top_emissive, (0.0, 1.0, 0.0), 2);
     }
     if bottom {
-        ptr.offset(i).copy_from_nonoverlapping([
-            0.0 + x,  0.0 + y,  0.0 + z, uv.0, uv.1, bottom_layer as f32, 0.0, -1.0, 0.0, ao[3][0] as f32,
-            1.0 + x,  0.0 + y,  0.0 + z, uv.2, uv.1, bottom_layer as f32, 0.0, -1.0, 0.0, ao[3][1] as f32,
-            1.0 + x,  0.0 + y,  1.0 + z, uv.2, uv.3, bottom_layer as f32, 0.0, -1.0, 0.0, ao[3][2] as f32,
-            1.0 + x,  0.0 + y,  1.0 + z, uv.2, uv.3, bottom_layer as f32, 0.0, -1.0, 0.0, ao[3][2] as f32,
-            0.0 + x,  0.0 + y,  1.0 + z, uv.0, uv.3, bottom_layer as f32, 0.0, -1.0, 0.0, ao[3][3] as f32,
-            0.0 + x,  0.0 + y,  0.0 + z, uv.0, uv.1, bottom_layer as f32, 0.0, -1.0, 0.0, ao[3][0] as f32,
-        ].as_ptr(), face_size);
-        copied_vertices += vertices_per_face;
+        write_face([
+            (0.0, 0.0, 0.0, uv.0, uv.1),
+            (1.0, 0.0, 0.0, uv.2, uv.1),
+            (1.0, 0.0, 1.0, uv.2, uv.3),
+            (0.0, 0.0, 1.0, uv.0, uv.3),
+        ], bottom_layer, bottom_emissive, (0.0, -1.0, 0.0), 3);
     }
     copied_vertices as u32
 }
 
+/// Writes a redstone wire lying on the floor of the block at (x, y, z): a square in the middle
+/// and an arm towards each direction set in `connections` (bit i for the facing i of `BlockMeta`).
+/// Every quad samples the row of the texture matching the power of the wire, from dark to bright red
+pub unsafe fn write_redstone_wire_to_ptr(ptr: *mut f32, x: f32, y: f32, z: f32, layer: TextureLayer,
+                                         power: u8, connections: u8, light: (u8, u8)) -> u32 {
+    let vertex_size = 12;
+    let vertices_per_quad = 6;
+    let height = y + 1.0 / 64.0;
+    let v = (power as f32 + 0.5) / 16.0;
+
+    // (min x, min z, max x, max z) of each quad, in sixteenths of a block
+    let mut quads = vec![(5.0, 5.0, 11.0, 11.0)];
+    let arms = [(6.0, 11.0, 10.0, 16.0), (0.0, 6.0, 5.0, 10.0), (6.0, 0.0, 10.0, 5.0), (11.0, 6.0, 16.0, 10.0)];
+    for (facing, &arm) in arms.iter().enumerate() {
+        if connections & (1 << facing) != 0 {
+            quads.push(arm);
+        }
+    }
+
+    let mut i = 0;
+    for &(min_x, min_z, max_x, max_z) in quads.iter() {
+        let (min_x, min_z, max_x, max_z): (f32, f32, f32, f32) = (min_x / 16.0, min_z / 16.0, max_x / 16.0, max_z / 16.0);
+        // Counterclockwise seen from above, like the top face of a cube
+        let corners = [(min_x, max_z), (max_x, max_z), (max_x, min_z), (min_x, min_z)];
+        for &corner in [0, 1, 2, 2, 3, 0].iter() {
+            let (c_x, c_z) = corners[corner];
+            ptr.offset(i).copy_from_nonoverlapping([
+                x + c_x, height, z + c_z, c_x, v, layer as f32, 0.0, 1.0, 0.0, 0.0, light.0 as f32, light.1 as f32,
+            ].as_ptr(), vertex_size);
+            i += vertex_size as isize;
+        }
+    }
+    (quads.len() * vertices_per_quad) as u32
+}
+
 pub fn block_outline() -> &'static [f32; 72] {
     // Groups of parallel lines for each dimension
     &[
@@ -183,4 +229,124 @@ pub fn centered_unit_cube(x: f32, y: f32, z: f32, (front_layer, back_layer, top_
         0.0 + x,  0.0 + y,  1.0 + z, uv.0, uv.3, bottom_layer as f32, 0.0, -1.0, 0.0,
         0.0 + x,  0.0 + y,  0.0 + z, uv.0, uv.1, bottom_layer as f32, 0.0, -1.0, 0.0,
     ].to_vec()
-}
\ No newline at end of file
+}
+/// A box going from `mins` to `maxs` with the same vertex layout as `centered_unit_cube`.
+/// The UV coordinates follow the position of the vertices so that a box smaller than
+/// a block only shows the matching part of the block texture.
+pub fn textured_box(mins: (f32, f32, f32), maxs: (f32, f32, f32), (front_layer, back_layer, top_layer, bottom_layer, left_layer, right_layer): UVFaces) -> Vec<f32> {
+    let (x0, y0, z0) = mins;
+    let (x1, y1, z1) = maxs;
+    let (front_layer, back_layer, top_layer, bottom_layer, left_layer, right_layer) =
+        (front_layer as f32, back_layer as f32, top_layer as f32, bottom_layer as f32, left_layer as f32, right_layer as f32);
+
+    [
+        x0, y0, z1, x0, y0, front_layer, 0.0, 0.0, 1.0,
+        x1, y0, z1, x1, y0, front_layer, 0.0, 0.0, 1.0,
+        x1, y1, z1, x1, y1, front_layer, 0.0, 0.0, 1.0,
+        x1, y1, z1, x1, y1, front_layer, 0.0, 0.0, 1.0,
+        x0, y1, z1, x0, y1, front_layer, 0.0, 0.0, 1.0,
+        x0, y0, z1, x0, y0, front_layer, 0.0, 0.0, 1.0,
+
+        x1, y0, z0, 1.0 - x1, y0, back_layer, 0.0, 0.0, -1.0,
+        x0, y0, z0, 1.0 - x0, y0, back_layer, 0.0, 0.0, -1.0,
+        x0, y1, z0, 1.0 - x0, y1, back_layer, 0.0, 0.0, -1.0,
+        x0, y1, z0, 1.0 - x0, y1, back_layer, 0.0, 0.0, -1.0,
+        x1, y1, z0, 1.0 - x1, y1, back_layer, 0.0, 0.0, -1.0,
+        x1, y0, z0, 1.0 - x1, y0, back_layer, 0.0, 0.0, -1.0,
+
+        x0, y0, z0, z0, y0, left_layer, -1.0, 0.0, 0.0,
+        x0, y0, z1, z1, y0, left_layer, -1.0, 0.0, 0.0,
+        x0, y1, z1, z1, y1, left_layer, -1.0, 0.0, 0.0,
+        x0, y1, z1, z1, y1, left_layer, -1.0, 0.0, 0.0,
+        x0, y1, z0, z0, y1, left_layer, -1.0, 0.0, 0.0,
+        x0, y0, z0, z0, y0, left_layer, -1.0, 0.0, 0.0,
+
+        x1, y0, z1, 1.0 - z1, y0, right_layer, 1.0, 0.0, 0.0,
+        x1, y0, z0, 1.0 - z0, y0, right_layer, 1.0, 0.0, 0.0,
+        x1, y1, z0, 1.0 - z0, y1, right_layer, 1.0, 0.0, 0.0,
+        x1, y1, z0, 1.0 - z0, y1, right_layer, 1.0, 0.0, 0.0,
+        x1, y1, z1, 1.0 - z1, y1, right_layer, 1.0, 0.0, 0.0,
+        x1, y0, z1, 1.0 - z1, y0, right_layer, 1.0, 0.0, 0.0,
+
+        x0, y1, z1, x0, 1.0 - z1, top_layer, 0.0, 1.0, 0.0,
+        x1, y1, z1, x1, 1.0 - z1, top_layer, 0.0, 1.0, 0.0,
+        x1, y1, z0, x1, 1.0 - z0, top_layer, 0.0, 1.0, 0.0,
+        x1, y1, z0, x1, 1.0 - z0, top_layer, 0.0, 1.0, 0.0,
+        x0, y1, z0, x0, 1.0 - z0, top_layer, 0.0, 1.0, 0.0,
+        x0, y1, z1, x0, 1.0 - z1, top_layer, 0.0, 1.0, 0.0,
+
+        x0, y0, z0, x0, z0, bottom_layer, 0.0, -1.0, 0.0,
+        x1, y0, z0, x1, z0, bottom_layer, 0.0, -1.0, 0.0,
+        x1, y0, z1, x1, z1, bottom_layer, 0.0, -1.0, 0.0,
+        x1, y0, z1, x1, z1, bottom_layer, 0.0, -1.0, 0.0,
+        x0, y0, z1, x0, z1, bottom_layer, 0.0, -1.0, 0.0,
+        x0, y0, z0, x0, z0, bottom_layer, 0.0, -1.0, 0.0,
+    ].to_vec()
+}
+
+/// Depth of the board of a sign, centered in the block
+pub const SIGN_BOARD_DEPTH: f32 = 2.0 / 16.0;
+/// Bottom and top of the board of a sign
+pub const SIGN_BOARD_HEIGHT: (f32, f32) = (8.0 / 16.0, 1.0);
+
+/// A board on a post, with the same vertex layout as `textured_box`.
+/// The front of the board faces `facing` (as stored in `BlockMeta`).
+pub fn sign_mesh(facing: u8, uvs: UVFaces) -> Vec<f32> {
+    let (board_bottom, board_top) = SIGN_BOARD_HEIGHT;
+    let mut vertices = textured_box(
+        (0.0, board_bottom, 0.5 - SIGN_BOARD_DEPTH / 2.0),
+        (1.0, board_top, 0.5 + SIGN_BOARD_DEPTH / 2.0), uvs);
+    vertices.extend(textured_box(
+        (7.0 / 16.0, 0.0, 7.0 / 16.0),
+        (9.0 / 16.0, board_bottom, 9.0 / 16.0), uvs));
+
+    rotate_towards_facing(&mut vertices, facing);
+    vertices
+}
+
+/// Depth of the panel of a fence gate, centered in the block
+pub const FENCE_GATE_DEPTH: f32 = 2.0 / 16.0;
+
+/// The panel of a closed fence gate facing +Z, with the same vertex layout as `textured_box`.
+/// Its hinge is the vertical edge at x = 0
+pub fn fence_gate_mesh(uvs: UVFaces) -> Vec<f32> {
+    textured_box(
+        (0.0, 0.0, 0.5 - FENCE_GATE_DEPTH / 2.0),
+        (1.0, 1.0, 0.5 + FENCE_GATE_DEPTH / 2.0), uvs)
+}
+
+/// Height of the mattress of a bed
+pub const BED_HEIGHT: f32 = 9.0 / 16.0;
+
+/// One half of a bed, with the same vertex layout as `textured_box`.
+/// The head of the bed is in the direction of `facing` (as stored in `BlockMeta`).
+pub fn bed_mesh(facing: u8, uvs: UVFaces) -> Vec<f32> {
+    let mut vertices = textured_box((0.0, 0.0, 0.0), (1.0, BED_HEIGHT, 1.0), uvs);
+    rotate_towards_facing(&mut vertices, facing);
+    vertices
+}
+
+/// Rotates the positions and the normals around the center of the block so that +Z points to `facing`
+fn rotate_towards_facing(vertices: &mut [f32], facing: u8) {
+    let angle = -(facing as f32) * std::f32::consts::FRAC_PI_2;
+    let (sin, cos) = angle.sin_cos();
+    for vertex in vertices.chunks_mut(9) {
+        let (x, z) = (vertex[0] - 0.5, vertex[2] - 0.5);
+        vertex[0] = x * cos + z * sin + 0.5;
+        vertex[2] = -x * sin + z * cos + 0.5;
+        let (nx, nz) = (vertex[6], vertex[8]);
+        vertex[6] = nx * cos + nz * sin;
+        vertex[8] = -nx * sin + nz * cos;
+    }
+}
+
+/// Sides of a pressure plate, inset by a pixel from the edges of the block
+pub const PRESSURE_PLATE_INSET: f32 = 1.0 / 16.0;
+
+/// A thin plate lying on the floor of the block, `height_fraction` of a block tall,
+/// with the same vertex layout as `textured_box`
+pub fn flat_slab_mesh(height_fraction: f32, uvs: UVFaces) -> Vec<f32> {
+    textured_box(
+        (PRESSURE_PLATE_INSET, 0.0, PRESSURE_PLATE_INSET),
+        (1.0 - PRESSURE_PLATE_INSET, height_fraction, 1.0 - PRESSURE_PLATE_INSET), uvs)
+}
@@ -0,0 +1,110 @@
+use std::any::Any;
+
+use crate::block_entity::BlockEntity;
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{GUI_SCALING, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::shader_compilation::ShaderProgram;
+use crate::text::{GLYPH_ADVANCE, GLYPH_HEIGHT, TextRenderer};
+
+pub const SIGN_LINES: usize = 4;
+pub const SIGN_LINE_LENGTH: usize = 15;
+/// Vertical space taken by a line of text on a sign, in font pixels
+pub const SIGN_LINE_HEIGHT: f32 = GLYPH_HEIGHT + 3.0;
+
+#[derive(Default)]
+pub struct SignBlockEntity {
+    pub lines: [String; SIGN_LINES],
+}
+
+impl SignBlockEntity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BlockEntity for SignBlockEntity {
+    fn tick(&mut self, _position: (i32, i32, i32), _chunk_manager: &ChunkManager) {}
+
+    /// Every line as its length on 1 byte followed by its characters
+    fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for line in self.lines.iter() {
+            bytes.push(line.len() as u8);
+            bytes.extend(line.as_bytes());
+        }
+        bytes
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// The text being typed on a sign.
+/// The sign itself is only updated when the player is done editing.
+#[derive(Default)]
+pub struct SignEditor {
+    pub position: Option<(i32, i32, i32)>,
+    pub lines: [String; SIGN_LINES],
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SignEditor {
+    pub fn open(&mut self, position: (i32, i32, i32), sign: &SignBlockEntity) {
+        self.position = Some(position);
+        self.lines = sign.lines.clone();
+        self.line = 0;
+        self.column = self.lines[0].len();
+    }
+
+    pub fn close(&mut self) {
+        self.position = None;
+    }
+
+    /// Only printable ASCII characters exist in the font
+    pub fn insert_char(&mut self, c: char) {
+        let line = &mut self.lines[self.line];
+        if c.is_ascii() && !c.is_ascii_control() && line.len() < SIGN_LINE_LENGTH {
+            line.insert(self.column, c);
+            self.column += 1;
+        }
+    }
+
+    pub fn delete_char(&mut self) {
+        if self.column > 0 {
+            self.column -= 1;
+            self.lines[self.line].remove(self.column);
+        }
+    }
+
+    pub fn move_cursor(&mut self, lines: i32, columns: i32) {
+        self.line = (self.line as i32 + lines).max(0).min(SIGN_LINES as i32 - 1) as usize;
+        let line_length = self.lines[self.line].len() as i32;
+        self.column = (self.column as i32 + columns).max(0).min(line_length) as usize;
+    }
+
+    /// Draws the lines in the middle of the screen with a cursor after the current character
+    pub fn draw(&self, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+        let scale = 2.0 * GUI_SCALING;
+        let line_height = SIGN_LINE_HEIGHT * scale;
+        let width = (SIGN_LINE_LENGTH as f32 * GLYPH_ADVANCE + 8.0) * scale;
+        let height = (SIGN_LINES as f32 * SIGN_LINE_HEIGHT + 8.0) * scale;
+        let (x, y) = ((WINDOW_WIDTH as f32 - width) / 2.0, (WINDOW_HEIGHT as f32 - height) / 2.0);
+        text_renderer.draw_rect(x, y, width, height, (0.62, 0.47, 0.28, 1.0), shader);
+
+        for (i, line) in self.lines.iter().enumerate() {
+            let line_x = (WINDOW_WIDTH as f32 - TextRenderer::text_width(line, scale)) / 2.0;
+            let line_y = y + height - 4.0 * scale - (i + 1) as f32 * line_height + 1.5 * scale;
+            text_renderer.draw(line, line_x, line_y, scale, (0.0, 0.0, 0.0, 1.0), shader);
+            if i == self.line {
+                let cursor_x = line_x + TextRenderer::text_width(&line[..self.column], scale);
+                text_renderer.draw_rect(cursor_x, line_y - scale, scale, (GLYPH_HEIGHT + 2.0) * scale, (0.0, 0.0, 0.0, 1.0), shader);
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::constants::SAVES_DIRECTORY;
+
+/// Directory where the files of the world generated from `seed` are saved
+pub fn world_save_directory(seed: u32) -> PathBuf {
+    PathBuf::from(SAVES_DIRECTORY).join(seed.to_string())
+}
+
+/// What the player did in the world, shown in the statistics screen of the pause menu
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct Statistics {
+    pub blocks_placed: u64,
+    pub blocks_broken: u64,
+    /// In blocks, only the horizontal movement on foot is counted
+    pub distance_walked: u64,
+    pub mobs_killed: u64,
+    pub deaths: u64,
+    pub time_played_seconds: u64,
+
+    /// The world the statistics belong to
+    #[serde(skip)]
+    pub world_seed: u32,
+}
+
+impl Statistics {
+    fn path(seed: u32) -> PathBuf {
+        world_save_directory(seed).join("statistics.toml")
+    }
+
+    /// Reads the statistics of the world, a new world starts from zero
+    pub fn load(seed: u32) -> Self {
+        let path = Self::path(seed);
+        let statistics = match fs::read_to_string(&path) {
+            Ok(text) => match toml::from_str(&text) {
+                Ok(statistics) => statistics,
+                Err(err) => {
+                    error!("Invalid statistics in {}: {}", path.display(), err);
+                    Statistics::default()
+                }
+            },
+            Err(_) => Statistics::default(),
+        };
+        Statistics {
+            world_seed: seed,
+            ..statistics
+        }
+    }
+
+    pub fn save(&self) {
+        let directory = world_save_directory(self.world_seed);
+        if let Err(err) = fs::create_dir_all(&directory) {
+            error!("Couldn't create {}: {}", directory.display(), err);
+            return;
+        }
+        let text = match toml::to_string(self) {
+            Ok(text) => text,
+            Err(err) => {
+                error!("Couldn't serialize the statistics: {}", err);
+                return;
+            }
+        };
+        let path = Self::path(self.world_seed);
+        if let Err(err) = fs::write(&path, text) {
+            error!("Couldn't write {}: {}", path.display(), err);
+        }
+    }
+
+    pub fn distance_walked_km(&self) -> f64 {
+        self.distance_walked as f64 / 1000.0
+    }
+
+    /// The name and value of every statistic, in the order they are listed
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let minutes = self.time_played_seconds / 60;
+        vec![
+            ("Time played", format!("{}h {:02}m", minutes / 60, minutes % 60)),
+            ("Distance walked", format!("{:.2} km", self.distance_walked_km())),
+            ("Blocks placed", self.blocks_placed.to_string()),
+            ("Blocks broken", self.blocks_broken.to_string()),
+            ("Mobs killed", self.mobs_killed.to_string()),
+            ("Deaths", self.deaths.to_string()),
+        ]
+    }
+}
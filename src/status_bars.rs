@@ -0,0 +1,190 @@
+use std::ffi::c_void;
+
+use nalgebra::Matrix4;
+use nalgebra_glm::{Mat4, vec3};
+
+use crate::constants::{GUI_SCALING, LEVEL_UP_FLASH_ALPHA, LEVEL_UP_FLASH_DURATION, WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::player::PlayerState;
+use crate::shader_compilation::ShaderProgram;
+use crate::shapes::quad;
+use crate::text::TextRenderer;
+
+const ICON_SIZE: f32 = 9.0;
+const ICON_SPACING: f32 = 8.0;
+const ICONS_PER_BAR: i32 = 10;
+/// Half the width of the hotbar, the bars are aligned with its edges
+const HOTBAR_HALF_WIDTH: f32 = 91.0;
+const BARS_HEIGHT: f32 = 30.0;
+/// The armor bar is above the health bar
+const ARMOR_BAR_HEIGHT: f32 = BARS_HEIGHT + 10.0;
+/// The experience bar lies between the hotbar and the other bars
+const XP_BAR_HEIGHT: f32 = 24.0;
+const XP_BAR_THICKNESS: f32 = 4.0;
+/// The filled part is inside the background of the bar
+const XP_BAR_FILL_THICKNESS: f32 = 2.0;
+const XP_BAR_BACKGROUND_COLOR: (f32, f32, f32, f32) = (0.0, 0.0, 0.0, 0.8);
+const XP_COLOR: (f32, f32, f32, f32) = (0.5, 1.0, 0.13, 1.0);
+
+// Position of the sprites in the icons texture, in pixels
+const HEART_BACKGROUND: (f32, f32) = (16.0, 0.0);
+const HEART_FULL: (f32, f32) = (52.0, 0.0);
+const HEART_HALF: (f32, f32) = (61.0, 0.0);
+const FOOD_BACKGROUND: (f32, f32) = (16.0, 27.0);
+const FOOD_FULL: (f32, f32) = (52.0, 27.0);
+const FOOD_HALF: (f32, f32) = (61.0, 27.0);
+const ARMOR_EMPTY: (f32, f32) = (16.0, 9.0);
+const ARMOR_HALF: (f32, f32) = (25.0, 9.0);
+const ARMOR_FULL: (f32, f32) = (34.0, 9.0);
+const BUBBLE: (f32, f32) = (16.0, 18.0);
+const BUBBLE_POPPED: (f32, f32) = (25.0, 18.0);
+
+/// Draws the health and the food level of the player above the hotbar,
+/// the hearts on the left and the drumsticks on the right.
+/// The defense points of the worn armor are shown above the hearts,
+/// the air left under water above the drumsticks.
+pub struct StatusBars {
+    vao: u32,
+    vbo: u32,
+}
+
+impl StatusBars {
+    pub fn new() -> Self {
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        // Position
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+        // Texture coords
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 1, 2 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+        let mut vbo = 0;
+        gl_call!(gl::CreateBuffers(1, &mut vbo));
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (5 * std::mem::size_of::<f32>()) as i32));
+        gl_call!(gl::NamedBufferData(vbo,
+                    (30 * std::mem::size_of::<f32>() as usize) as isize,
+                    std::ptr::null(),
+                    gl::DYNAMIC_DRAW));
+
+        StatusBars { vao, vbo }
+    }
+
+    /// Draws an icon of the icons texture, (x, y) being the bottom left corner on the screen
+    fn draw_icon(&self, x: f32, y: f32, (u, v): (f32, f32), shader: &mut ShaderProgram) {
+        let texture_size = 256.0;
+        let vbo_data = quad((u / texture_size, v / texture_size, (u + ICON_SIZE) / texture_size, (v + ICON_SIZE) / texture_size));
+        gl_call!(gl::NamedBufferSubData(self.vbo,
+                    0,
+                    (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                    vbo_data.as_ptr() as *mut c_void));
+
+        let model_matrix = {
+            let half_size = ICON_SIZE * GUI_SCALING / 2.0;
+            let translate_matrix = Matrix4::new_translation(&vec3(x + half_size, y + half_size, 0.0));
+            let scale_matrix: Mat4 = Matrix4::new_nonuniform_scaling(&vec3(ICON_SIZE * GUI_SCALING, ICON_SIZE * GUI_SCALING, 1.0));
+            translate_matrix * scale_matrix
+        };
+        shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+        gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, 6));
+    }
+
+    /// Every icon is worth 2 points, `x_of` gives the position of the icon of index i
+    fn draw_bar(&self, value: f32, height: f32, x_of: &dyn Fn(i32) -> f32,
+                background: (f32, f32), full: (f32, f32), half: (f32, f32), shader: &mut ShaderProgram) {
+        let value = value.ceil() as i32;
+        let y = height * GUI_SCALING;
+        for i in 0..ICONS_PER_BAR {
+            let x = x_of(i);
+            self.draw_icon(x, y, background, shader);
+            if value >= 2 * i + 2 {
+                self.draw_icon(x, y, full, shader);
+            } else if value == 2 * i + 1 {
+                self.draw_icon(x, y, half, shader);
+            }
+        }
+    }
+
+    pub fn draw(&self, player_state: &PlayerState, defense_points: u8, shader: &mut ShaderProgram) {
+        let projection_matrix = nalgebra_glm::ortho(
+            0.0, WINDOW_WIDTH as f32, 0.0, WINDOW_HEIGHT as f32, -5.0, 5.0);
+
+        shader.use_program();
+        shader.set_uniform_matrix4fv("projection", projection_matrix.as_ptr());
+        shader.set_uniform1i("tex", 1);
+        gl_call!(gl::BindVertexArray(self.vao));
+
+        let center_x = WINDOW_WIDTH as f32 / 2.0;
+        let left_icon_x = |i: i32| center_x + (-HOTBAR_HALF_WIDTH + i as f32 * ICON_SPACING) * GUI_SCALING;
+        self.draw_bar(player_state.health, BARS_HEIGHT, &left_icon_x,
+                      HEART_BACKGROUND, HEART_FULL, HEART_HALF, shader);
+        if defense_points > 0 {
+            self.draw_bar(defense_points as f32, ARMOR_BAR_HEIGHT, &left_icon_x,
+                          ARMOR_EMPTY, ARMOR_FULL, ARMOR_HALF, shader);
+        }
+        // The food bar fills up from the right
+        self.draw_bar(player_state.food_level, BARS_HEIGHT,
+                      &|i| center_x + (HOTBAR_HALF_WIDTH - ICON_SIZE - i as f32 * ICON_SPACING) * GUI_SCALING,
+                      FOOD_BACKGROUND, FOOD_FULL, FOOD_HALF, shader);
+
+        // A bubble for every AIR_PER_BUBBLE ticks of air, they pop from the left
+        if player_state.is_submerged {
+            let bubbles = player_state.air_bubbles();
+            let y = ARMOR_BAR_HEIGHT * GUI_SCALING;
+            for i in 0..ICONS_PER_BAR {
+                let x = center_x + (HOTBAR_HALF_WIDTH - ICON_SIZE - i as f32 * ICON_SPACING) * GUI_SCALING;
+                let icon = if i < bubbles { BUBBLE } else { BUBBLE_POPPED };
+                self.draw_icon(x, y, icon, shader);
+            }
+        }
+    }
+}
+
+/// Draws the experience bar above the hotbar, filled up to the next level, with the current level centered above it
+pub fn draw_xp_bar(player_state: &PlayerState, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    let x = WINDOW_WIDTH as f32 / 2.0 - HOTBAR_HALF_WIDTH * GUI_SCALING;
+    let y = XP_BAR_HEIGHT * GUI_SCALING;
+    let width = 2.0 * HOTBAR_HALF_WIDTH * GUI_SCALING;
+    let thickness = XP_BAR_THICKNESS * GUI_SCALING;
+    let fill_thickness = XP_BAR_FILL_THICKNESS * GUI_SCALING;
+    let inset = (thickness - fill_thickness) / 2.0;
+    text_renderer.draw_rect(x, y, width, thickness, XP_BAR_BACKGROUND_COLOR, shader);
+    text_renderer.draw_rect(x + inset, y + inset, (width - 2.0 * inset) * player_state.xp_progress(), fill_thickness,
+                            XP_COLOR, shader);
+
+    if player_state.level > 0 {
+        let text = player_state.level.to_string();
+        let text_x = (WINDOW_WIDTH as f32 - TextRenderer::text_width(&text, GUI_SCALING)) / 2.0;
+        let text_y = y + thickness + GUI_SCALING;
+        text_renderer.draw(&text, text_x + GUI_SCALING, text_y - GUI_SCALING, GUI_SCALING, (0.0, 0.0, 0.0, 1.0), shader);
+        text_renderer.draw(&text, text_x, text_y, GUI_SCALING, XP_COLOR, shader);
+    }
+}
+
+/// The screen flashes with the color of the experience bar when the player reaches a new level
+pub fn draw_level_up_flash(elapsed: f32, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    if elapsed >= LEVEL_UP_FLASH_DURATION {
+        return;
+    }
+    let (r, g, b, _) = XP_COLOR;
+    let alpha = LEVEL_UP_FLASH_ALPHA * (1.0 - elapsed / LEVEL_UP_FLASH_DURATION);
+    text_renderer.draw_rect(0.0, 0.0, WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32, (r, g, b, alpha), shader);
+}
+
+/// Flames licking the bottom of the screen while the player is on fire, `time` in seconds makes them flicker
+pub fn draw_fire_overlay(time: f32, text_renderer: &mut TextRenderer, shader: &mut ShaderProgram) {
+    let flames = 24;
+    let width = WINDOW_WIDTH as f32 / flames as f32;
+    for i in 0..flames {
+        let x = i as f32 * width;
+        let phase = i as f32 * 1.7;
+        let height = WINDOW_HEIGHT as f32
+            * (0.2 + 0.08 * (time * 9.0 + phase).sin() + 0.05 * (time * 13.0 + 2.3 * phase).sin());
+        text_renderer.draw_rect(x, 0.0, width, height, (1.0, 0.45, 0.05, 0.4), shader);
+        // The hotter core of the flame
+        text_renderer.draw_rect(x + width / 4.0, 0.0, width / 2.0, height * 0.6, (1.0, 0.85, 0.2, 0.4), shader);
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum StatusEffect {
+    /// Walk and sprint faster
+    Speed,
+    /// Break the blocks faster
+    Haste,
+    /// Take less damage
+    Resistance,
+    /// Jump higher
+    JumpBoost,
+    /// Hit harder
+    Strength,
+}
+
+impl StatusEffect {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusEffect::Speed => "Speed",
+            StatusEffect::Haste => "Haste",
+            StatusEffect::Resistance => "Resistance",
+            StatusEffect::JumpBoost => "Jump Boost",
+            StatusEffect::Strength => "Strength",
+        }
+    }
+}
+
+/// The effects a player is under, with their level and when they wear off
+#[derive(Default)]
+pub struct StatusEffects {
+    effects: HashMap<StatusEffect, (u8, Instant)>,
+}
+
+impl StatusEffects {
+    /// Gives `effect` at `level` for `duration`. A higher level of the same effect isn't replaced before it wears off
+    pub fn give(&mut self, effect: StatusEffect, level: u8, duration: Duration, now: Instant) {
+        let until = now + duration;
+        let replace = match self.effects.get(&effect) {
+            Some(&(current_level, current_until)) if current_until > now => {
+                level > current_level || (level == current_level && until > current_until)
+            }
+            _ => true,
+        };
+        if replace {
+            self.effects.insert(effect, (level, until));
+        }
+    }
+
+    /// Level of `effect`, 0 when the player isn't under it
+    pub fn level(&self, effect: StatusEffect, now: Instant) -> u8 {
+        match self.effects.get(&effect) {
+            Some(&(level, until)) if until > now => level,
+            _ => 0,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+}
@@ -0,0 +1,617 @@
+use std::collections::{HashMap, HashSet};
+
+use nalgebra_glm::vec3;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::block_entity::BlockEntity;
+use crate::chest::LootChestBlockEntity;
+use crate::chunk::{BlockID, BlockMeta};
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{NETHER_FORTRESS_CHANCE, NETHER_FORTRESS_REGION_SIZE, NETHER_FORTRESS_Y, NETHER_LAVA_LEVEL, VILLAGE_CHANCE, VILLAGE_RADIUS, VILLAGE_REGION_SIZE, WORLD_HEIGHT_IN_CHUNKS};
+use crate::dungeon::SpawnerBlockEntity;
+use crate::loot::{NETHER_FORTRESS_LOOT, roll};
+use crate::mob::{iron_golem_mob, MobKind, villager_mob};
+
+/// Box around a structure, both corners included
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct StructureBounds {
+    pub min: (i32, i32, i32),
+    pub max: (i32, i32, i32),
+}
+
+impl StructureBounds {
+    pub fn contains(&self, (x, y, z): (i32, i32, i32)) -> bool {
+        self.min.0 <= x && x <= self.max.0 &&
+            self.min.1 <= y && y <= self.max.1 &&
+            self.min.2 <= z && z <= self.max.2
+    }
+
+    pub fn intersects(&self, other: &StructureBounds) -> bool {
+        self.min.0 <= other.max.0 && self.max.0 >= other.min.0 &&
+            self.min.1 <= other.max.1 && self.max.1 >= other.min.1 &&
+            self.min.2 <= other.max.2 && self.max.2 >= other.min.2
+    }
+}
+
+/// The structures generated in a dimension, by the region they belong to
+pub type StructureRegistry = HashMap<(i32, i32), StructureBounds>;
+
+/// The blocks of a structure relative to its origin, with their metadata
+/// and whether they replace the terrain or only fill the air and the liquids
+#[derive(Default)]
+struct Blueprint {
+    blocks: HashMap<(i32, i32, i32), (BlockID, BlockMeta, bool)>,
+    block_entities: Vec<((i32, i32, i32), Box<dyn BlockEntity>)>,
+}
+
+impl Blueprint {
+    fn put(&mut self, position: (i32, i32, i32), block: BlockID, meta: BlockMeta) {
+        self.blocks.insert(position, (block, meta, true));
+    }
+
+    /// Fills the box between two corners, both included
+    fn fill(&mut self, (x0, y0, z0): (i32, i32, i32), (x1, y1, z1): (i32, i32, i32), block: BlockID) {
+        for x in x0.min(x1)..=x0.max(x1) {
+            for y in y0.min(y1)..=y0.max(y1) {
+                for z in z0.min(z1)..=z0.max(z1) {
+                    self.put((x, y, z), block, BlockMeta::default());
+                }
+            }
+        }
+    }
+
+    /// Fills the air and the liquids of a column going down from `(x, top, z)` to `(x, bottom, z)`
+    fn support(&mut self, x: i32, top: i32, bottom: i32, z: i32, block: BlockID) {
+        for y in bottom..=top {
+            self.blocks.entry((x, y, z)).or_insert((block, BlockMeta::default(), false));
+        }
+    }
+}
+
+/// Distance from the center of the central room to its walls
+const ROOM_HALF_SIZE: i32 = 5;
+/// The ceiling of the central room is this many blocks above its floor
+const ROOM_HEIGHT: i32 = 6;
+const CORRIDOR_LENGTH: i32 = 16;
+/// Distance from the middle of a corridor to its walls, the corridors are 3 blocks wide inside
+const CORRIDOR_HALF_WIDTH: i32 = 2;
+/// The ceiling of a corridor is this many blocks above its floor
+const CORRIDOR_HEIGHT: i32 = 4;
+const BRIDGE_LENGTH: i32 = 12;
+/// The bridges stand on a pillar every this many blocks
+const PILLAR_SPACING: i32 = 4;
+/// Distance from the center of a fortress to the end of its bridges
+const REACH: i32 = ROOM_HALF_SIZE + CORRIDOR_LENGTH + BRIDGE_LENGTH;
+
+/// Decides the structure of a region, only depending on the world seed
+fn region_rng(world_seed: u32, region_x: i32, region_z: i32) -> StdRng {
+    let seed = (world_seed as u64) << 32
+        ^ (region_x as u32 as u64).wrapping_mul(73_856_093)
+        ^ (region_z as u32 as u64).wrapping_mul(19_349_663);
+    StdRng::seed_from_u64(seed)
+}
+
+/// Facing of a block looking in the direction (dx, dz), see `BlockMeta::facing`
+fn facing_towards((dx, dz): (i32, i32)) -> u8 {
+    match (dx.signum(), dz.signum()) {
+        (0, 1) => 0,
+        (-1, 0) => 1,
+        (0, -1) => 2,
+        _ => 3,
+    }
+}
+
+/// Generates the Nether fortresses: a central room holding a blaze spawner, with a corridor
+/// leading out of each of its walls and continuing into a bridge. Everything is made of
+/// nether bricks and stands on a flat Netherrack platform, the floor being at NETHER_FORTRESS_Y
+pub struct NetherFortressGenerator {
+    world_seed: u32,
+}
+
+impl NetherFortressGenerator {
+    pub fn new(world_seed: u32) -> Self {
+        Self { world_seed }
+    }
+
+    /// Center of the floor of the fortress of a NETHER_FORTRESS_REGION_SIZE wide region, if it has one.
+    /// It keeps away from the edges of the region so that the fortresses never overlap
+    pub fn fortress_center(&self, region_x: i32, region_z: i32) -> Option<(i32, i32, i32)> {
+        let mut rng = region_rng(self.world_seed, region_x, region_z);
+        if !rng.gen_bool(NETHER_FORTRESS_CHANCE) {
+            return None;
+        }
+        let x = region_x * NETHER_FORTRESS_REGION_SIZE + rng.gen_range(REACH, NETHER_FORTRESS_REGION_SIZE - REACH);
+        let z = region_z * NETHER_FORTRESS_REGION_SIZE + rng.gen_range(REACH, NETHER_FORTRESS_REGION_SIZE - REACH);
+        Some((x, NETHER_FORTRESS_Y, z))
+    }
+
+    /// Box around the fortress whose floor is centered on `(x, y, z)`, pillars included
+    pub fn fortress_bounds((x, y, z): (i32, i32, i32)) -> StructureBounds {
+        StructureBounds {
+            min: (x - REACH, NETHER_LAVA_LEVEL, z - REACH),
+            max: (x + REACH, y + ROOM_HEIGHT, z + REACH),
+        }
+    }
+
+    /// The blocks of a fortress, relative to the center of its floor
+    fn fortress_blueprint(rng: &mut StdRng) -> Blueprint {
+        let mut blueprint = Blueprint::default();
+        let pillar_bottom = NETHER_LAVA_LEVEL - NETHER_FORTRESS_Y;
+
+        // Central room
+        blueprint.fill((-ROOM_HALF_SIZE, -1, -ROOM_HALF_SIZE), (ROOM_HALF_SIZE, -1, ROOM_HALF_SIZE), BlockID::Netherrack);
+        blueprint.fill((-ROOM_HALF_SIZE, 0, -ROOM_HALF_SIZE), (ROOM_HALF_SIZE, ROOM_HEIGHT, ROOM_HALF_SIZE), BlockID::NetherBrick);
+        blueprint.fill((-ROOM_HALF_SIZE + 1, 1, -ROOM_HALF_SIZE + 1), (ROOM_HALF_SIZE - 1, ROOM_HEIGHT - 1, ROOM_HALF_SIZE - 1), BlockID::Air);
+        blueprint.put((0, 1, 0), BlockID::Spawner, BlockMeta::default());
+        blueprint.block_entities.push(((0, 1, 0), Box::new(SpawnerBlockEntity::new(MobKind::Blaze))));
+
+        for &direction in &[(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            // `along` goes away from the room, `across` from one wall to the other
+            let at = |along: i32, y: i32, across: i32| {
+                (direction.0 * along + direction.1 * across, y, direction.1 * along + direction.0 * across)
+            };
+
+            // Corridor, going through the wall of the room
+            let corridor_end = ROOM_HALF_SIZE + CORRIDOR_LENGTH;
+            blueprint.fill(at(ROOM_HALF_SIZE, -1, -CORRIDOR_HALF_WIDTH), at(corridor_end, -1, CORRIDOR_HALF_WIDTH), BlockID::Netherrack);
+            blueprint.fill(at(ROOM_HALF_SIZE, 0, -CORRIDOR_HALF_WIDTH), at(corridor_end, CORRIDOR_HEIGHT, CORRIDOR_HALF_WIDTH), BlockID::NetherBrick);
+            blueprint.fill(at(ROOM_HALF_SIZE, 1, -CORRIDOR_HALF_WIDTH + 1), at(corridor_end, CORRIDOR_HEIGHT - 1, CORRIDOR_HALF_WIDTH - 1), BlockID::Air);
+
+            // A chest against one of the walls, facing the middle of the corridor
+            if rng.gen_bool(0.5) {
+                let along = rng.gen_range(ROOM_HALF_SIZE + 2, corridor_end - 1);
+                let across = if rng.gen_bool(0.5) { CORRIDOR_HALF_WIDTH - 1 } else { -(CORRIDOR_HALF_WIDTH - 1) };
+                let (dx, _, dz) = at(0, 0, -across);
+                let meta = BlockMeta::default().with_facing(facing_towards((dx, dz)));
+                let chest = LootChestBlockEntity::new(roll(&NETHER_FORTRESS_LOOT, rng));
+                blueprint.put(at(along, 1, across), BlockID::Chest, meta);
+                blueprint.block_entities.push((at(along, 1, across), Box::new(chest)));
+            }
+
+            // Bridge, a walkway with low walls standing on pillars above the lava
+            for along in corridor_end + 1..=corridor_end + BRIDGE_LENGTH {
+                for across in -CORRIDOR_HALF_WIDTH..=CORRIDOR_HALF_WIDTH {
+                    let is_wall = across.abs() == CORRIDOR_HALF_WIDTH;
+                    blueprint.put(at(along, 0, across), BlockID::NetherBrick, BlockMeta::default());
+                    blueprint.put(at(along, 1, across), if is_wall { BlockID::NetherBrick } else { BlockID::Air }, BlockMeta::default());
+                    blueprint.fill(at(along, 2, across), at(along, CORRIDOR_HEIGHT - 1, across), BlockID::Air);
+                    if along % PILLAR_SPACING == 0 && !is_wall {
+                        let (x, _, z) = at(along, 0, across);
+                        blueprint.support(x, -1, pillar_bottom, z, BlockID::NetherBrick);
+                    }
+                }
+            }
+        }
+        blueprint
+    }
+
+    /// Builds the parts of the fortresses that lie in the newly generated columns, the others already got theirs.
+    /// The fortresses are recorded in the structure registry of the chunk manager, and their block entities
+    /// are queued in it for the block entity registry
+    pub fn place(&self, chunk_manager: &ChunkManager, new_columns: &[(i32, i32)]) {
+        if new_columns.is_empty() {
+            return;
+        }
+        let new_column_set: HashSet<(i32, i32)> = new_columns.iter().cloned().collect();
+
+        let min_x = new_columns.iter().map(|&(x, _)| 16 * x).min().unwrap();
+        let max_x = new_columns.iter().map(|&(x, _)| 16 * x + 16).max().unwrap();
+        let min_z = new_columns.iter().map(|&(_, z)| 16 * z).min().unwrap();
+        let max_z = new_columns.iter().map(|&(_, z)| 16 * z + 16).max().unwrap();
+
+        for region_x in min_x.div_euclid(NETHER_FORTRESS_REGION_SIZE)..=max_x.div_euclid(NETHER_FORTRESS_REGION_SIZE) {
+            for region_z in min_z.div_euclid(NETHER_FORTRESS_REGION_SIZE)..=max_z.div_euclid(NETHER_FORTRESS_REGION_SIZE) {
+                let center = match self.fortress_center(region_x, region_z) {
+                    Some(center) => center,
+                    None => continue,
+                };
+                let bounds = Self::fortress_bounds(center);
+                if bounds.max.0 < min_x || bounds.min.0 >= max_x || bounds.max.2 < min_z || bounds.min.2 >= max_z {
+                    continue;
+                }
+
+                // Another structure is already there
+                {
+                    let mut structures = chunk_manager.structures.write();
+                    let overlaps = structures.iter()
+                        .any(|(&region, other)| region != (region_x, region_z) && other.intersects(&bounds));
+                    if overlaps {
+                        continue;
+                    }
+                    structures.insert((region_x, region_z), bounds);
+                }
+
+                // Seeded by the region so that every column gets the same fortress
+                let mut rng = region_rng(self.world_seed, region_x, region_z);
+                let blueprint = Self::fortress_blueprint(&mut rng);
+                let (x, y, z) = center;
+                let mut is_built = false;
+                for ((dx, dy, dz), (block, meta, replaces_terrain)) in blueprint.blocks {
+                    let (w_x, w_y, w_z) = (x + dx, y + dy, z + dz);
+                    let (c_x, _, c_z, b_x, b_y, b_z) = ChunkManager::get_chunk_coords(w_x, w_y, w_z);
+                    if !new_column_set.contains(&(c_x, c_z)) {
+                        continue;
+                    }
+                    let column = match chunk_manager.get_column(c_x, c_z) {
+                        Some(column) => column,
+                        None => continue,
+                    };
+
+                    let chunk = column.get_chunk(w_y / 16);
+                    let current = chunk.get_block(b_x, b_y, b_z);
+                    if !replaces_terrain && !current.is_air() && !current.is_liquid() {
+                        continue;
+                    }
+                    chunk.set_block(block, b_x, b_y, b_z);
+                    chunk.set_block_meta(meta, b_x, b_y, b_z);
+                    // The fortress rises above the netherrack and hollows it out, the heightmap follows
+                    column.update_heighest_block(block, b_x, w_y as u32, b_z);
+                    is_built = true;
+                }
+                for ((dx, dy, dz), block_entity) in blueprint.block_entities {
+                    let (w_x, w_y, w_z) = (x + dx, y + dy, z + dz);
+                    let (c_x, _, c_z, _, _, _) = ChunkManager::get_chunk_coords(w_x, w_y, w_z);
+                    if new_column_set.contains(&(c_x, c_z)) && chunk_manager.get_column(c_x, c_z).is_some() {
+                        chunk_manager.generated_block_entities.write().push(((w_x, w_y, w_z), block_entity));
+                    }
+                }
+                if is_built {
+                    info!("Generated a Nether fortress at ({} {} {})", x, y, z);
+                }
+            }
+        }
+    }
+}
+
+/// The blocks of a house relative to the middle of its doorway at floor level, the door opening towards -z
+type HouseTemplate = Vec<(i32, i32, i32, BlockID)>;
+
+/// A house `2 * half_width + 1` blocks wide and `depth` blocks deep with walls `height` blocks high, made of
+/// `wall` between `corner` pillars under a flat roof. There are windows in its sides and its back
+fn house_template(half_width: i32, depth: i32, height: i32, wall: BlockID, corner: BlockID, furniture: &[(i32, i32, i32, BlockID)]) -> HouseTemplate {
+    let mut blocks = Vec::new();
+    for x in -half_width..=half_width {
+        for z in 0..depth {
+            let is_side = x.abs() == half_width;
+            let is_front_or_back = z == 0 || z == depth - 1;
+            let is_window = (is_side && z == depth / 2) || (x == 0 && z == depth - 1);
+            blocks.push((x, 0, z, BlockID::Cobblestone));
+            for y in 1..=height {
+                let block = if is_side && is_front_or_back {
+                    corner
+                } else if !is_side && !is_front_or_back || (x == 0 && z == 0 && y <= 2) {
+                    BlockID::Air
+                } else if is_window && y == 2 {
+                    BlockID::Glass
+                } else {
+                    wall
+                };
+                blocks.push((x, y, z, block));
+            }
+            blocks.push((x, height + 1, z, BlockID::OakPlanks));
+        }
+    }
+    blocks.extend_from_slice(furniture);
+    blocks
+}
+
+lazy_static! {
+    /// The houses the villages are made of
+    static ref HOUSE_TEMPLATES: [HouseTemplate; 3] = [
+        // A small hut
+        house_template(2, 5, 3, BlockID::OakPlanks, BlockID::OakLog, &[(1, 1, 3, BlockID::CraftingTable)]),
+        // A large house
+        house_template(3, 7, 4, BlockID::OakPlanks, BlockID::OakLog, &[
+            (-2, 1, 5, BlockID::CraftingTable),
+            (2, 1, 5, BlockID::Bookshelf),
+            (2, 2, 5, BlockID::Bookshelf),
+        ]),
+        // A library made of stone
+        house_template(2, 6, 4, BlockID::Cobblestone, BlockID::OakLog, &[
+            (-1, 1, 4, BlockID::Bookshelf),
+            (1, 1, 4, BlockID::Bookshelf),
+            (-1, 2, 4, BlockID::Bookshelf),
+            (1, 2, 4, BlockID::Bookshelf),
+        ]),
+    ];
+}
+
+/// Turns `(x, z)` around the origin by `rotation` quarter turns
+fn rotate((x, z): (i32, i32), rotation: u8) -> (i32, i32) {
+    match rotation % 4 {
+        0 => (x, z),
+        1 => (-z, x),
+        2 => (-x, -z),
+        _ => (z, -x),
+    }
+}
+
+/// Whether two rectangles, given by their corners, come within `margin` blocks of each other
+fn rectangles_overlap((min_a, max_a): ((i32, i32), (i32, i32)), (min_b, max_b): ((i32, i32), (i32, i32)), margin: i32) -> bool {
+    min_a.0 - margin <= max_b.0 && max_a.0 + margin >= min_b.0 &&
+        min_a.1 - margin <= max_b.1 && max_a.1 + margin >= min_b.1
+}
+
+/// Villages are built where it is neither cold nor humid, in the plains and the deserts
+fn is_village_climate(temperature: f32, humidity: f32) -> bool {
+    temperature >= 0.35 && humidity < 0.6
+}
+
+/// Height of the ground at (x, z) and its block, under the trees growing nearby
+fn ground_at(chunk_manager: &ChunkManager, x: i32, z: i32) -> Option<(i32, BlockID)> {
+    let (c_x, _, c_z, b_x, _, b_z) = ChunkManager::get_chunk_coords(x, 0, z);
+    let column = chunk_manager.get_column(c_x, c_z)?;
+    let top = column.heighest_blocks.read()[(16 * b_z + b_x) as usize] as i32;
+    (0..=top).rev()
+        .map(|y| (y, chunk_manager.get_block(x, y, z).unwrap_or(BlockID::Air)))
+        .find(|(_, block)| !block.is_air() && !block.is_log() && !block.is_leaves())
+}
+
+/// Distance from the well to its rim
+const WELL_HALF_SIZE: i32 = 1;
+/// The doors of the houses are between these distances from the well
+const HOUSE_MIN_DISTANCE: f32 = 8.0;
+const HOUSE_MAX_DISTANCE: f32 = (VILLAGE_RADIUS - 8) as f32;
+/// Blocks kept free between two houses for the paths
+const HOUSE_SPACING: i32 = 2;
+/// Most blocks of foundation put under a house or the well standing above a slope
+const MAX_FOUNDATION_DEPTH: i32 = 8;
+
+struct House {
+    /// Middle of the doorway
+    door: (i32, i32),
+    /// Quarter turns of the template
+    rotation: u8,
+    template: usize,
+    villagers: u32,
+}
+
+impl House {
+    /// Corners of the ground covered by the house, both included
+    fn footprint(&self) -> ((i32, i32), (i32, i32)) {
+        let mut min = self.door;
+        let mut max = self.door;
+        for &(x, _, z, _) in HOUSE_TEMPLATES[self.template].iter() {
+            let (dx, dz) = rotate((x, z), self.rotation);
+            min = (min.0.min(self.door.0 + dx), min.1.min(self.door.1 + dz));
+            max = (max.0.max(self.door.0 + dx), max.1.max(self.door.1 + dz));
+        }
+        (min, max)
+    }
+
+    /// In front of the door, where the path coming from the well ends
+    fn entrance(&self) -> (i32, i32) {
+        let (dx, dz) = rotate((0, -1), self.rotation);
+        (self.door.0 + dx, self.door.1 + dz)
+    }
+}
+
+struct Village {
+    /// Middle of the well
+    center: (i32, i32),
+    houses: Vec<House>,
+}
+
+impl Village {
+    fn well_footprint(&self) -> ((i32, i32), (i32, i32)) {
+        let (x, z) = self.center;
+        ((x - WELL_HALF_SIZE, z - WELL_HALF_SIZE), (x + WELL_HALF_SIZE, z + WELL_HALF_SIZE))
+    }
+
+    fn bounds(&self) -> StructureBounds {
+        let (x, z) = self.center;
+        StructureBounds {
+            min: (x - VILLAGE_RADIUS, 0, z - VILLAGE_RADIUS),
+            max: (x + VILLAGE_RADIUS, WORLD_HEIGHT_IN_CHUNKS * 16 - 1, z + VILLAGE_RADIUS),
+        }
+    }
+
+    /// The blocks paved between the well and the doors, going along x then along z around the houses
+    fn paths(&self) -> HashSet<(i32, i32)> {
+        let (c_x, c_z) = self.center;
+        let mut paths = HashSet::new();
+        for house in &self.houses {
+            let (e_x, e_z) = house.entrance();
+            for x in c_x.min(e_x)..=c_x.max(e_x) {
+                paths.insert((x, c_z));
+            }
+            for z in c_z.min(e_z)..=c_z.max(e_z) {
+                paths.insert((e_x, z));
+            }
+        }
+        let is_inside = |(x, z): (i32, i32), (min, max): ((i32, i32), (i32, i32))| {
+            min.0 <= x && x <= max.0 && min.1 <= z && z <= max.1
+        };
+        paths.retain(|&block| !is_inside(block, self.well_footprint())
+            && self.houses.iter().all(|house| !is_inside(block, house.footprint())));
+        paths
+    }
+}
+
+/// Generates the villages: a well in the middle of 4 to 8 houses, linked to each of them by a gravel path.
+/// An iron golem guards the well and 1 to 3 villagers live in every house, none of them wandering out of the village
+pub struct VillageGenerator {
+    world_seed: u32,
+}
+
+impl VillageGenerator {
+    pub fn new(world_seed: u32) -> Self {
+        Self { world_seed }
+    }
+
+    /// The village of a VILLAGE_REGION_SIZE wide region, if it has one. It keeps away from the edges of the region
+    /// so that the villages never overlap, `climate` gives the temperature and the humidity at some block coordinates
+    fn village(&self, region_x: i32, region_z: i32, climate: &impl Fn(i32, i32) -> (f32, f32)) -> Option<Village> {
+        let mut rng = region_rng(self.world_seed, region_x, region_z);
+        if !rng.gen_bool(VILLAGE_CHANCE) {
+            return None;
+        }
+        let x = region_x * VILLAGE_REGION_SIZE + rng.gen_range(VILLAGE_RADIUS, VILLAGE_REGION_SIZE - VILLAGE_RADIUS);
+        let z = region_z * VILLAGE_REGION_SIZE + rng.gen_range(VILLAGE_RADIUS, VILLAGE_REGION_SIZE - VILLAGE_RADIUS);
+        let (temperature, humidity) = climate(x, z);
+        if !is_village_climate(temperature, humidity) {
+            return None;
+        }
+
+        let mut village = Village { center: (x, z), houses: Vec::new() };
+        let house_count = rng.gen_range(4, 9);
+        // Gives up on the houses that can't find a free spot after a few tries
+        for _ in 0..4 * house_count {
+            if village.houses.len() == house_count {
+                break;
+            }
+            let angle = rng.gen_range(0.0, std::f32::consts::PI * 2.0);
+            let distance = rng.gen_range(HOUSE_MIN_DISTANCE, HOUSE_MAX_DISTANCE);
+            let house = House {
+                door: (x + (angle.cos() * distance).round() as i32, z + (angle.sin() * distance).round() as i32),
+                rotation: rng.gen_range(0, 4),
+                template: rng.gen_range(0, HOUSE_TEMPLATES.len()),
+                villagers: rng.gen_range(1, 4),
+            };
+            let footprint = house.footprint();
+            let (min, max) = footprint;
+            let is_free = min.0 >= x - VILLAGE_RADIUS && max.0 <= x + VILLAGE_RADIUS
+                && min.1 >= z - VILLAGE_RADIUS && max.1 <= z + VILLAGE_RADIUS
+                && !rectangles_overlap(footprint, village.well_footprint(), HOUSE_SPACING)
+                && village.houses.iter().all(|other| !rectangles_overlap(footprint, other.footprint(), HOUSE_SPACING));
+            if is_free {
+                village.houses.push(house);
+            }
+        }
+        Some(village)
+    }
+
+    /// Builds the parts of the village that lie in the column (cx, cz), once its terrain is generated.
+    /// The village is recorded in the structure registry of the chunk manager, and its mobs are queued in it
+    pub fn place(&self, cx: i32, cz: i32, chunk_manager: &ChunkManager, climate: impl Fn(i32, i32) -> (f32, f32)) {
+        // The villages never cross the edges of their region
+        let region = ((16 * cx).div_euclid(VILLAGE_REGION_SIZE), (16 * cz).div_euclid(VILLAGE_REGION_SIZE));
+        let village = match self.village(region.0, region.1, &climate) {
+            Some(village) => village,
+            None => return,
+        };
+        let bounds = village.bounds();
+        if bounds.max.0 < 16 * cx || bounds.min.0 >= 16 * cx + 16 || bounds.max.2 < 16 * cz || bounds.min.2 >= 16 * cz + 16 {
+            return;
+        }
+
+        // Another structure is already there
+        {
+            let mut structures = chunk_manager.structures.write();
+            let overlaps = structures.iter()
+                .any(|(&other_region, other)| other_region != region && other.intersects(&bounds));
+            if overlaps {
+                return;
+            }
+            structures.insert(region, bounds);
+        }
+
+        let is_in_column = |(x, z): (i32, i32)| x.div_euclid(16) == cx && z.div_euclid(16) == cz;
+        let (x, z) = village.center;
+        let home = vec3(x as f32 + 0.5, 0.0, z as f32 + 0.5);
+        let wander_radius = VILLAGE_RADIUS as f32;
+
+        if is_in_column(village.center) {
+            if let Some(y) = Self::build_well(chunk_manager, village.center) {
+                let golem_position = vec3(x as f32 + 0.5, y as f32 + 1.0, (z + WELL_HALF_SIZE + 2) as f32 + 0.5);
+                chunk_manager.generated_mobs.write().push(iron_golem_mob(golem_position, home, wander_radius));
+                info!("Generated a village of {} houses at ({} {} {})", village.houses.len(), x, y, z);
+            }
+        }
+
+        for (p_x, p_z) in village.paths() {
+            if !is_in_column((p_x, p_z)) {
+                continue;
+            }
+            match ground_at(chunk_manager, p_x, p_z) {
+                // Bridges over the water
+                Some((y, block)) if block.is_liquid() => { chunk_manager.set_block(BlockID::OakPlanks, p_x, y, p_z); }
+                Some((y, _)) => { chunk_manager.set_block(BlockID::Gravel, p_x, y, p_z); }
+                None => {}
+            }
+        }
+
+        for house in village.houses.iter().filter(|house| is_in_column(house.door)) {
+            let floor = match Self::build_house(chunk_manager, house) {
+                Some(floor) => floor,
+                None => continue,
+            };
+            let (dx, dz) = rotate((0, 2), house.rotation);
+            let position = vec3((house.door.0 + dx) as f32 + 0.5, floor as f32 + 1.0, (house.door.1 + dz) as f32 + 0.5);
+            for _ in 0..house.villagers {
+                chunk_manager.generated_mobs.write().push(villager_mob(position, home, wander_radius));
+            }
+        }
+    }
+
+    /// Fills the air and the water under (x, top, z) with cobblestone
+    fn build_foundations(chunk_manager: &ChunkManager, x: i32, top: i32, z: i32) {
+        for y in (top - MAX_FOUNDATION_DEPTH..=top).rev() {
+            match chunk_manager.get_block(x, y, z) {
+                Some(block) if block.is_air() || block.is_liquid() || block.is_leaves() => {
+                    chunk_manager.set_block(BlockID::Cobblestone, x, y, z);
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Removes the terrain above (x, y, z), up to the top of the column
+    fn clear_above(chunk_manager: &ChunkManager, x: i32, y: i32, z: i32) {
+        if let Some((top, _)) = ground_at(chunk_manager, x, z) {
+            for y in y + 1..=top {
+                chunk_manager.set_block(BlockID::Air, x, y, z);
+            }
+        }
+    }
+
+    /// A rim of cobblestone around a shaft of water, under a roof standing on four pillars.
+    /// Returns the height of the rim, the well isn't built in the water
+    fn build_well(chunk_manager: &ChunkManager, (x, z): (i32, i32)) -> Option<i32> {
+        let (y, block) = ground_at(chunk_manager, x, z)?;
+        if block.is_liquid() {
+            return None;
+        }
+        for dx in -WELL_HALF_SIZE..=WELL_HALF_SIZE {
+            for dz in -WELL_HALF_SIZE..=WELL_HALF_SIZE {
+                let (w_x, w_z) = (x + dx, z + dz);
+                let is_corner = dx.abs() == WELL_HALF_SIZE && dz.abs() == WELL_HALF_SIZE;
+                Self::clear_above(chunk_manager, w_x, y + 3, w_z);
+                Self::build_foundations(chunk_manager, w_x, y - 1, w_z);
+                chunk_manager.set_block(BlockID::Cobblestone, w_x, y, w_z);
+                for dy in 1..=2 {
+                    chunk_manager.set_block(if is_corner { BlockID::OakLog } else { BlockID::Air }, w_x, y + dy, w_z);
+                }
+                chunk_manager.set_block(BlockID::Cobblestone, w_x, y + 3, w_z);
+            }
+        }
+        for dy in -3..=0 {
+            chunk_manager.set_block(if dy == -3 { BlockID::Cobblestone } else { BlockID::Water }, x, y + dy, z);
+        }
+        Some(y)
+    }
+
+    /// Builds the house on the ground in front of its door, standing on foundations above the slopes.
+    /// Returns the height of its floor, the houses aren't built in the water
+    fn build_house(chunk_manager: &ChunkManager, house: &House) -> Option<i32> {
+        let (entrance_x, entrance_z) = house.entrance();
+        let (floor, block) = ground_at(chunk_manager, entrance_x, entrance_z)?;
+        if block.is_liquid() {
+            return None;
+        }
+        let (min, max) = house.footprint();
+        let roof = HOUSE_TEMPLATES[house.template].iter().map(|&(_, y, _, _)| y).max().unwrap_or(0);
+        for x in min.0..=max.0 {
+            for z in min.1..=max.1 {
+                Self::clear_above(chunk_manager, x, floor + roof, z);
+                Self::build_foundations(chunk_manager, x, floor - 1, z);
+            }
+        }
+        for &(dx, dy, dz, block) in HOUSE_TEMPLATES[house.template].iter() {
+            let (dx, dz) = rotate((dx, dz), house.rotation);
+            chunk_manager.set_block(block, house.door.0 + dx, floor + dy, house.door.1 + dz);
+        }
+        Some(floor)
+    }
+}
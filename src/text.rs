@@ -0,0 +1,171 @@
+use std::ffi::c_void;
+
+use image::GenericImageView;
+use nalgebra::Matrix4;
+use nalgebra_glm::Mat4;
+
+use crate::constants::{WINDOW_HEIGHT, WINDOW_WIDTH};
+use crate::shader_compilation::ShaderProgram;
+
+/// Texture unit the font texture is bound to
+pub const FONT_TEXTURE_UNIT: u32 = 6;
+
+/// Horizontal space taken by a character, in font pixels
+pub const GLYPH_ADVANCE: f32 = 6.0;
+/// Height of a character, in font pixels
+pub const GLYPH_HEIGHT: f32 = 7.0;
+
+/// Character drawn as a filled cell, used for backgrounds
+pub const SOLID_GLYPH: char = '\u{7f}';
+pub const STAR_GLYPH: char = '\u{80}';
+
+const FIRST_GLYPH: u32 = 32;
+const LAST_GLYPH: u32 = 128;
+// Characters are laid out in 8x8 cells, 16 per row, in a 128x128 texture
+const CELL_SIZE: f32 = 8.0;
+const CELLS_PER_ROW: u32 = 16;
+const FONT_TEXTURE_SIZE: f32 = 128.0;
+
+pub fn create_font_texture() -> u32 {
+    let font_image = match image::open("textures/gui/font.png") {
+        Ok(img) => img,
+        Err(err) => panic!("Filename: {}, error: {}", "textures/gui/font.png", err.to_string())
+    };
+    match font_image.color() {
+        image::RGBA(8) => {}
+        _ => panic!("Texture format not supported")
+    };
+
+    // Upload the image to the GPU
+    let mut font_texture = 0;
+    gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut font_texture));
+    gl_call!(gl::TextureParameteri(font_texture, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32));
+    gl_call!(gl::TextureParameteri(font_texture, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32));
+    gl_call!(gl::TextureStorage2D(font_texture, 1, gl::RGBA8, font_image.width() as i32, font_image.height() as i32));
+    gl_call!(gl::TextureSubImage2D(
+            font_texture, 0,
+            0, 0, font_image.width() as i32, font_image.height() as i32,
+            gl::RGBA, gl::UNSIGNED_BYTE,
+            font_image.raw_pixels().as_ptr() as *mut c_void));
+    font_texture
+}
+
+/// Draws strings with the bitmap font in screen coordinates (y pointing up).
+/// Every character of a string is written into a single buffer and drawn at once.
+pub struct TextRenderer {
+    vao: u32,
+    vbo: u32,
+    vbo_capacity: usize,
+}
+
+impl TextRenderer {
+    pub fn new() -> Self {
+        let mut vao = 0;
+        gl_call!(gl::CreateVertexArrays(1, &mut vao));
+
+        // Position
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 0));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 0, 3 as i32, gl::FLOAT, gl::FALSE, 0));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 0, 0));
+
+        // Texture coords
+        gl_call!(gl::EnableVertexArrayAttrib(vao, 1));
+        gl_call!(gl::VertexArrayAttribFormat(vao, 1, 2 as i32, gl::FLOAT, gl::FALSE, 3 * std::mem::size_of::<f32>() as u32));
+        gl_call!(gl::VertexArrayAttribBinding(vao, 1, 0));
+
+        let mut vbo = 0;
+        gl_call!(gl::CreateBuffers(1, &mut vbo));
+        gl_call!(gl::VertexArrayVertexBuffer(vao, 0, vbo, 0, (5 * std::mem::size_of::<f32>()) as i32));
+
+        TextRenderer {
+            vao,
+            vbo,
+            vbo_capacity: 0,
+        }
+    }
+
+    /// Width of a string drawn with the given scale
+    pub fn text_width(text: &str, scale: f32) -> f32 {
+        text.chars().count() as f32 * GLYPH_ADVANCE * scale
+    }
+
+    /// Draws `text` with its bottom left corner at (x, y)
+    pub fn draw(&mut self, text: &str, x: f32, y: f32, scale: f32, color: (f32, f32, f32, f32), shader: &mut ShaderProgram) {
+        let projection_matrix = nalgebra_glm::ortho(
+            0.0, WINDOW_WIDTH as f32, 0.0, WINDOW_HEIGHT as f32, -5.0, 5.0);
+        self.draw_transformed(text, x, y, scale, color, &Matrix4::identity(), &projection_matrix, shader);
+    }
+
+    /// Same as `draw` but in the space given by the model matrix, to draw text in the world
+    pub fn draw_transformed(&mut self, text: &str, x: f32, y: f32, scale: f32, color: (f32, f32, f32, f32),
+                            model_matrix: &Mat4, projection_matrix: &Mat4, shader: &mut ShaderProgram) {
+        // Glyphs are drawn in the top rows of their cell
+        let y = y - (CELL_SIZE - GLYPH_HEIGHT) * scale;
+        let mut vbo_data = Vec::with_capacity(30 * text.len());
+        for (i, c) in text.chars().enumerate() {
+            let left = x + i as f32 * GLYPH_ADVANCE * scale;
+            vbo_data.extend(glyph_quad(c, left, y, CELL_SIZE * scale, CELL_SIZE * scale));
+        }
+        self.draw_vertices(&vbo_data, color, model_matrix, projection_matrix, shader);
+    }
+
+    /// Draws a filled rectangle, (x, y) being its bottom left corner
+    pub fn draw_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: (f32, f32, f32, f32), shader: &mut ShaderProgram) {
+        let vbo_data = glyph_quad(SOLID_GLYPH, x, y, width, height);
+        let projection_matrix = nalgebra_glm::ortho(
+            0.0, WINDOW_WIDTH as f32, 0.0, WINDOW_HEIGHT as f32, -5.0, 5.0);
+        self.draw_vertices(&vbo_data, color, &Matrix4::identity(), &projection_matrix, shader);
+    }
+
+    fn draw_vertices(&mut self, vbo_data: &[f32], (r, g, b, a): (f32, f32, f32, f32),
+                     model_matrix: &Mat4, projection_matrix: &Mat4, shader: &mut ShaderProgram) {
+        if vbo_data.is_empty() {
+            return;
+        }
+
+        // Grow the buffer when the text doesn't fit anymore
+        if vbo_data.len() > self.vbo_capacity {
+            self.vbo_capacity = vbo_data.len().next_power_of_two();
+            gl_call!(gl::NamedBufferData(self.vbo,
+                    (self.vbo_capacity * std::mem::size_of::<f32>()) as isize,
+                    std::ptr::null(),
+                    gl::DYNAMIC_DRAW));
+        }
+        gl_call!(gl::NamedBufferSubData(self.vbo,
+                    0,
+                    (vbo_data.len() * std::mem::size_of::<f32>()) as isize,
+                    vbo_data.as_ptr() as *mut c_void));
+
+        shader.use_program();
+        shader.set_uniform_matrix4fv("model", model_matrix.as_ptr());
+        shader.set_uniform_matrix4fv("projection", projection_matrix.as_ptr());
+        shader.set_uniform1i("tex", FONT_TEXTURE_UNIT as i32);
+        shader.set_uniform4f("color", &[r, g, b, a]);
+
+        gl_call!(gl::BindVertexArray(self.vao));
+        gl_call!(gl::DrawArrays(gl::TRIANGLES, 0, (vbo_data.len() / 5) as i32));
+    }
+}
+
+/// Two triangles covering the cell of a character, (x, y) being the bottom left corner
+fn glyph_quad(c: char, x: f32, y: f32, width: f32, height: f32) -> Vec<f32> {
+    let code = c as u32;
+    let index = if code >= FIRST_GLYPH && code <= LAST_GLYPH {
+        code - FIRST_GLYPH
+    } else {
+        '?' as u32 - FIRST_GLYPH
+    };
+
+    // The first row of the texture is the top of the image
+    let u0 = (index % CELLS_PER_ROW) as f32 * CELL_SIZE / FONT_TEXTURE_SIZE;
+    let v0 = (index / CELLS_PER_ROW) as f32 * CELL_SIZE / FONT_TEXTURE_SIZE;
+    let (u1, v1) = (u0 + CELL_SIZE / FONT_TEXTURE_SIZE, v0 + CELL_SIZE / FONT_TEXTURE_SIZE);
+    vec![
+        x, y, 0.0, u0, v1,
+        x + width, y, 0.0, u1, v1,
+        x + width, y + height, 0.0, u1, v0,
+        x + width, y + height, 0.0, u1, v0,
+        x, y + height, 0.0, u0, v0,
+        x, y, 0.0, u0, v1,
+    ]
+}
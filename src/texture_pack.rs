@@ -1,20 +1,195 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::os::raw::c_void;
+use std::path::{Path, PathBuf};
 
 use image::{DynamicImage, GenericImageView};
+use parking_lot::RwLock;
+use serde::Deserialize;
 
-use crate::block_texture_faces::BlockFaces;
+use crate::block_texture_faces::{BlockFaces, BlockTextures};
 use crate::chunk::BlockID;
-use crate::constants::ITEM_ARRAY_TEXTURE_LAYERS;
-use crate::types::{TextureLayer, TexturePack};
+use crate::constants::{ITEM_ARRAY_TEXTURE_LAYERS, TEXTURE_PACK_MANIFEST};
+use crate::types::TexturePack;
 
-pub fn generate_array_texture() -> (u32, TexturePack) {
+/// From GL_ARB_texture_filter_anisotropic, only core since OpenGL 4.6 so the bindings don't have them
+const TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: gl::types::GLenum = 0x84FF;
+
+/// Builds the array texture of the blocks and the items, the PNG files of the texture pack
+/// replace the built-in textures of the same name
+pub fn generate_array_texture(texture_pack: Option<&Path>) -> (u32, TexturePack) {
     let face_images = create_face_images_map();
+    let emissive_masks = create_emissive_masks_map();
+    let animation_frames = create_animation_frames_map();
+    let overrides = match texture_pack {
+        Some(directory) => load_texture_pack(directory, &face_images, &emissive_masks, &animation_frames),
+        None => HashMap::new(),
+    };
     let array_texture = create_array_texture(ITEM_ARRAY_TEXTURE_LAYERS as i32);
-    let face_uvs = create_face_uvs_map(array_texture, face_images);
+    let face_uvs = create_face_uvs_map(array_texture, face_images, &emissive_masks, &animation_frames, &overrides);
+    gl_call!(gl::GenerateTextureMipmap(array_texture));
     (array_texture, face_uvs)
 }
 
+/// Number of mipmap levels of a texture, down to 1x1
+pub fn mip_levels(width: u32, height: u32) -> i32 {
+    32 - width.max(height).max(1).leading_zeros() as i32
+}
+
+/// Sets how many samples the texture gets when seen at an oblique angle, at most what the GPU supports
+pub fn set_anisotropy(texture: u32, anisotropy: u32) {
+    let mut max_anisotropy = 0.0;
+    gl_call!(gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max_anisotropy));
+    // Anisotropic filtering isn't supported
+    if max_anisotropy < 1.0 {
+        return;
+    }
+    gl_call!(gl::TextureParameterf(texture, TEXTURE_MAX_ANISOTROPY, (anisotropy.max(1) as f32).min(max_anisotropy)));
+}
+
+/// The array texture of the blocks and the items, bound to the texture unit 0. Replaced when its images change
+#[derive(Default)]
+pub struct ArrayTexture(pub RwLock<u32>);
+
+/// The textures seen at oblique angles, filtered with the anisotropy chosen in the settings
+#[derive(Default)]
+pub struct AnisotropicTextures {
+    pub textures: Vec<u32>,
+    /// Anisotropy the textures currently have, 0 until it is first set
+    pub anisotropy: u32,
+}
+
+impl AnisotropicTextures {
+    pub fn new(textures: Vec<u32>) -> Self {
+        Self { textures, anisotropy: 0 }
+    }
+}
+
+/// The manifest of a texture pack, for files that aren't named after the texture they replace
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct TexturePackManifest {
+    /// File name without its extension -> name of the built-in texture, e.g. "cobble" -> "cobblestone"
+    pub textures: HashMap<String, String>,
+}
+
+impl TexturePackManifest {
+    /// Reads the manifest of the texture pack, an empty one is used when it doesn't exist or can't be parsed
+    pub fn load(directory: &Path) -> Self {
+        let path = directory.join(TEXTURE_PACK_MANIFEST);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => return TexturePackManifest::default(),
+        };
+        match serde_json::from_str(&text) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                error!("Invalid texture pack manifest {}: {}", path.display(), err);
+                TexturePackManifest::default()
+            }
+        }
+    }
+}
+
+/// Name of a built-in texture, the file name of its image without the extension
+fn texture_name(image_path: &str) -> &str {
+    Path::new(image_path).file_stem().and_then(|stem| stem.to_str()).unwrap_or(image_path)
+}
+
+/// Finds the PNG files of the texture pack, returns the file replacing each built-in texture.
+/// The files that don't match any texture are ignored
+fn load_texture_pack(directory: &Path, face_images: &HashMap<BlockID, BlockFaces<&str>>,
+                     emissive_masks: &HashMap<&str, &str>, animation_frames: &HashMap<&str, Vec<&str>>) -> HashMap<String, PathBuf> {
+    let mut known_names = HashSet::new();
+    for faces in face_images.values() {
+        let (front, back, top, bottom, left, right) = faces.get_every_face();
+        known_names.extend([front, back, top, bottom, left, right].iter().map(|path| texture_name(path)));
+    }
+    known_names.extend(emissive_masks.values().map(|path| texture_name(path)));
+    known_names.extend(animation_frames.values().flatten().map(|path| texture_name(path)));
+
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(err) => {
+            error!("Couldn't open the texture pack {}: {}", directory.display(), err);
+            return HashMap::new();
+        }
+    };
+    let manifest = TexturePackManifest::load(directory);
+
+    let mut overrides = HashMap::new();
+    for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        if path.extension().and_then(|extension| extension.to_str()) != Some("png") {
+            continue;
+        }
+        let file_name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(file_name) => file_name,
+            None => continue,
+        };
+        let name = manifest.textures.get(file_name).map(String::as_str).unwrap_or(file_name);
+        if !known_names.contains(name) {
+            warn!("Ignoring {} from the texture pack, there is no texture named \"{}\"", path.display(), name);
+            continue;
+        }
+        overrides.insert(name.to_string(), path);
+    }
+    info!("Loaded {} textures from the texture pack {}", overrides.len(), directory.display());
+    overrides
+}
+
+/// Grass color depending on the climate, sampled at (temperature, 1 - humidity)
+pub fn create_grass_colormap_texture() -> u32 {
+    let colormap = match image::open("textures/grasscolor.png") {
+        Ok(img) => img,
+        Err(err) => panic!("Filename: {}, error: {}", "textures/grasscolor.png", err.to_string())
+    };
+    match colormap.color() {
+        image::RGBA(8) => {}
+        _ => panic!("Texture format not supported")
+    };
+
+    let mut colormap_texture = 0;
+    gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut colormap_texture));
+    gl_call!(gl::TextureParameteri(colormap_texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32));
+    gl_call!(gl::TextureParameteri(colormap_texture, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32));
+    gl_call!(gl::TextureParameteri(colormap_texture, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32));
+    gl_call!(gl::TextureParameteri(colormap_texture, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32));
+    gl_call!(gl::TextureStorage2D(colormap_texture, 1, gl::RGBA8, colormap.width() as i32, colormap.height() as i32));
+    gl_call!(gl::TextureSubImage2D(
+            colormap_texture, 0,
+            0, 0, colormap.width() as i32, colormap.height() as i32,
+            gl::RGBA, gl::UNSIGNED_BYTE,
+            colormap.raw_pixels().as_ptr() as *mut c_void));
+    colormap_texture
+}
+
+/// Bright light patterns cast by the waves on the bottom of the water, tiled over the world
+pub fn create_caustics_texture() -> u32 {
+    let caustics = match image::open("textures/caustics.png") {
+        Ok(img) => img,
+        Err(err) => panic!("Filename: {}, error: {}", "textures/caustics.png", err.to_string())
+    };
+    match caustics.color() {
+        image::RGBA(8) => {}
+        _ => panic!("Texture format not supported")
+    };
+
+    let mut caustics_texture = 0;
+    gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut caustics_texture));
+    gl_call!(gl::TextureParameteri(caustics_texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32));
+    gl_call!(gl::TextureParameteri(caustics_texture, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32));
+    gl_call!(gl::TextureParameteri(caustics_texture, gl::TEXTURE_WRAP_S, gl::REPEAT as i32));
+    gl_call!(gl::TextureParameteri(caustics_texture, gl::TEXTURE_WRAP_T, gl::REPEAT as i32));
+    gl_call!(gl::TextureStorage2D(caustics_texture, 1, gl::RGBA8, caustics.width() as i32, caustics.height() as i32));
+    gl_call!(gl::TextureSubImage2D(
+            caustics_texture, 0,
+            0, 0, caustics.width() as i32, caustics.height() as i32,
+            gl::RGBA, gl::UNSIGNED_BYTE,
+            caustics.raw_pixels().as_ptr() as *mut c_void));
+    caustics_texture
+}
+
 fn create_face_images_map() -> HashMap<BlockID, BlockFaces<&'static str>> {
     let mut face_images: HashMap<BlockID, BlockFaces<&str>> = HashMap::new();
     face_images.insert(BlockID::Dirt, BlockFaces::All("textures/blocks/dirt.png"));
@@ -35,6 +210,199 @@ fn create_face_images_map() -> HashMap<BlockID, BlockFaces<&'static str>> {
     face_images.insert(BlockID::OakLeaves, BlockFaces::All("textures/blocks/oak_leaves_mod.png"));
     face_images.insert(BlockID::OakPlanks, BlockFaces::All("textures/blocks/oak_planks.png"));
     face_images.insert(BlockID::Glass, BlockFaces::All("textures/blocks/glass.png"));
+    face_images.insert(BlockID::Water, BlockFaces::All("textures/blocks/water_still.png"));
+    face_images.insert(BlockID::Chest, BlockFaces::Each {
+        top: "textures/blocks/chest_top.png",
+        bottom: "textures/blocks/chest_top.png",
+        front: "textures/blocks/chest_front.png",
+        back: "textures/blocks/chest_side.png",
+        left: "textures/blocks/chest_side.png",
+        right: "textures/blocks/chest_side.png",
+    });
+    face_images.insert(BlockID::Furnace, BlockFaces::Each {
+        top: "textures/blocks/furnace_top.png",
+        bottom: "textures/blocks/furnace_top.png",
+        front: "textures/blocks/furnace_front.png",
+        back: "textures/blocks/furnace_side.png",
+        left: "textures/blocks/furnace_side.png",
+        right: "textures/blocks/furnace_side.png",
+    });
+    face_images.insert(BlockID::Netherrack, BlockFaces::All("textures/blocks/netherrack.png"));
+    face_images.insert(BlockID::Lava, BlockFaces::All("textures/blocks/lava_still.png"));
+    face_images.insert(BlockID::NetherPortal, BlockFaces::All("textures/blocks/nether_portal.png"));
+    face_images.insert(BlockID::Sign, BlockFaces::All("textures/blocks/oak_planks.png"));
+    face_images.insert(BlockID::Bread, BlockFaces::All("textures/items/bread.png"));
+    face_images.insert(BlockID::Apple, BlockFaces::All("textures/items/apple.png"));
+    face_images.insert(BlockID::BedHead, BlockFaces::Each {
+        top: "textures/blocks/bed_head_top.png",
+        bottom: "textures/blocks/oak_planks.png",
+        front: "textures/blocks/bed_head_end.png",
+        back: "textures/blocks/bed_head_end.png",
+        left: "textures/blocks/bed_head_side.png",
+        right: "textures/blocks/bed_head_side.png",
+    });
+    face_images.insert(BlockID::BedFoot, BlockFaces::Each {
+        top: "textures/blocks/bed_foot_top.png",
+        bottom: "textures/blocks/oak_planks.png",
+        front: "textures/blocks/bed_foot_end.png",
+        back: "textures/blocks/bed_foot_end.png",
+        left: "textures/blocks/bed_foot_side.png",
+        right: "textures/blocks/bed_foot_side.png",
+    });
+    face_images.insert(BlockID::LeatherHelmet, BlockFaces::All("textures/items/leather_helmet.png"));
+    face_images.insert(BlockID::LeatherChestplate, BlockFaces::All("textures/items/leather_chestplate.png"));
+    face_images.insert(BlockID::LeatherLeggings, BlockFaces::All("textures/items/leather_leggings.png"));
+    face_images.insert(BlockID::LeatherBoots, BlockFaces::All("textures/items/leather_boots.png"));
+    face_images.insert(BlockID::GoldenHelmet, BlockFaces::All("textures/items/golden_helmet.png"));
+    face_images.insert(BlockID::GoldenChestplate, BlockFaces::All("textures/items/golden_chestplate.png"));
+    face_images.insert(BlockID::GoldenLeggings, BlockFaces::All("textures/items/golden_leggings.png"));
+    face_images.insert(BlockID::GoldenBoots, BlockFaces::All("textures/items/golden_boots.png"));
+    face_images.insert(BlockID::IronHelmet, BlockFaces::All("textures/items/iron_helmet.png"));
+    face_images.insert(BlockID::IronChestplate, BlockFaces::All("textures/items/iron_chestplate.png"));
+    face_images.insert(BlockID::IronLeggings, BlockFaces::All("textures/items/iron_leggings.png"));
+    face_images.insert(BlockID::IronBoots, BlockFaces::All("textures/items/iron_boots.png"));
+    face_images.insert(BlockID::DiamondHelmet, BlockFaces::All("textures/items/diamond_helmet.png"));
+    face_images.insert(BlockID::DiamondChestplate, BlockFaces::All("textures/items/diamond_chestplate.png"));
+    face_images.insert(BlockID::DiamondLeggings, BlockFaces::All("textures/items/diamond_leggings.png"));
+    face_images.insert(BlockID::DiamondBoots, BlockFaces::All("textures/items/diamond_boots.png"));
+    face_images.insert(BlockID::Tnt, BlockFaces::Sides {
+        sides: "textures/blocks/tnt_side.png",
+        top: "textures/blocks/tnt_top.png",
+        bottom: "textures/blocks/tnt_bottom.png",
+    });
+    face_images.insert(BlockID::Snowball, BlockFaces::All("textures/items/snowball.png"));
+    face_images.insert(BlockID::Arrow, BlockFaces::All("textures/items/arrow.png"));
+    face_images.insert(BlockID::Crossbow, BlockFaces::All("textures/items/crossbow.png"));
+    face_images.insert(BlockID::WoodenPickaxe, BlockFaces::All("textures/items/wooden_pickaxe.png"));
+    face_images.insert(BlockID::StonePickaxe, BlockFaces::All("textures/items/stone_pickaxe.png"));
+    face_images.insert(BlockID::IronPickaxe, BlockFaces::All("textures/items/iron_pickaxe.png"));
+    face_images.insert(BlockID::DiamondPickaxe, BlockFaces::All("textures/items/diamond_pickaxe.png"));
+    face_images.insert(BlockID::WoodenAxe, BlockFaces::All("textures/items/wooden_axe.png"));
+    face_images.insert(BlockID::StoneAxe, BlockFaces::All("textures/items/stone_axe.png"));
+    face_images.insert(BlockID::IronAxe, BlockFaces::All("textures/items/iron_axe.png"));
+    face_images.insert(BlockID::DiamondAxe, BlockFaces::All("textures/items/diamond_axe.png"));
+    face_images.insert(BlockID::WoodenShovel, BlockFaces::All("textures/items/wooden_shovel.png"));
+    face_images.insert(BlockID::StoneShovel, BlockFaces::All("textures/items/stone_shovel.png"));
+    face_images.insert(BlockID::IronShovel, BlockFaces::All("textures/items/iron_shovel.png"));
+    face_images.insert(BlockID::DiamondShovel, BlockFaces::All("textures/items/diamond_shovel.png"));
+    face_images.insert(BlockID::Glowstone, BlockFaces::All("textures/blocks/glowstone.png"));
+    face_images.insert(BlockID::SeaLantern, BlockFaces::All("textures/blocks/sea_lantern.png"));
+    face_images.insert(BlockID::Ladder, BlockFaces::All("textures/blocks/ladder.png"));
+    face_images.insert(BlockID::RedstoneWire, BlockFaces::All("textures/blocks/redstone_wire.png"));
+    face_images.insert(BlockID::RedstoneBlock, BlockFaces::All("textures/blocks/redstone_block.png"));
+    face_images.insert(BlockID::Piston, BlockFaces::Each {
+        top: "textures/blocks/piston_side.png",
+        bottom: "textures/blocks/piston_side.png",
+        front: "textures/blocks/piston_top.png",
+        back: "textures/blocks/piston_bottom.png",
+        left: "textures/blocks/piston_side.png",
+        right: "textures/blocks/piston_side.png",
+    });
+    face_images.insert(BlockID::StickyPiston, BlockFaces::Each {
+        top: "textures/blocks/piston_side.png",
+        bottom: "textures/blocks/piston_side.png",
+        front: "textures/blocks/piston_top_sticky.png",
+        back: "textures/blocks/piston_bottom.png",
+        left: "textures/blocks/piston_side.png",
+        right: "textures/blocks/piston_side.png",
+    });
+    face_images.insert(BlockID::Observer, BlockFaces::Each {
+        top: "textures/blocks/observer_top.png",
+        bottom: "textures/blocks/observer_top.png",
+        front: "textures/blocks/observer_front.png",
+        back: "textures/blocks/observer_back.png",
+        left: "textures/blocks/observer_side.png",
+        right: "textures/blocks/observer_side.png",
+    });
+    face_images.insert(BlockID::PistonHead, BlockFaces::All("textures/blocks/piston_inner.png"));
+    face_images.insert(BlockID::StoneBrick, BlockFaces::All("textures/blocks/stone_bricks.png"));
+    face_images.insert(BlockID::Spawner, BlockFaces::All("textures/blocks/spawner.png"));
+    face_images.insert(BlockID::StonePressurePlate, BlockFaces::All("textures/blocks/stone.png"));
+    face_images.insert(BlockID::WoodenPressurePlate, BlockFaces::All("textures/blocks/oak_planks.png"));
+    face_images.insert(BlockID::CraftingTable, BlockFaces::Each {
+        top: "textures/blocks/crafting_table_top.png",
+        bottom: "textures/blocks/oak_planks.png",
+        front: "textures/blocks/crafting_table_front.png",
+        back: "textures/blocks/crafting_table_front.png",
+        left: "textures/blocks/crafting_table_side.png",
+        right: "textures/blocks/crafting_table_side.png",
+    });
+    face_images.insert(BlockID::Fire, BlockFaces::All("textures/blocks/fire.png"));
+    face_images.insert(BlockID::BirchLog, BlockFaces::Sides {
+        sides: "textures/blocks/birch_log.png",
+        top: "textures/blocks/birch_log_top.png",
+        bottom: "textures/blocks/birch_log_top.png",
+    });
+    face_images.insert(BlockID::BirchLeaves, BlockFaces::All("textures/blocks/birch_leaves.png"));
+    face_images.insert(BlockID::SpruceLog, BlockFaces::Sides {
+        sides: "textures/blocks/spruce_log.png",
+        top: "textures/blocks/spruce_log_top.png",
+        bottom: "textures/blocks/spruce_log_top.png",
+    });
+    face_images.insert(BlockID::SpruceLeaves, BlockFaces::All("textures/blocks/spruce_leaves.png"));
+    face_images.insert(BlockID::JungleLog, BlockFaces::Sides {
+        sides: "textures/blocks/jungle_log.png",
+        top: "textures/blocks/jungle_log_top.png",
+        bottom: "textures/blocks/jungle_log_top.png",
+    });
+    face_images.insert(BlockID::JungleLeaves, BlockFaces::All("textures/blocks/jungle_leaves.png"));
+    face_images.insert(BlockID::Beacon, BlockFaces::All("textures/blocks/beacon.png"));
+    face_images.insert(BlockID::IronBlock, BlockFaces::All("textures/blocks/iron_block.png"));
+    face_images.insert(BlockID::GoldBlock, BlockFaces::All("textures/blocks/gold_block.png"));
+    face_images.insert(BlockID::DiamondBlock, BlockFaces::All("textures/blocks/diamond_block.png"));
+    face_images.insert(BlockID::EmeraldBlock, BlockFaces::All("textures/blocks/emerald_block.png"));
+    face_images.insert(BlockID::RawBeef, BlockFaces::All("textures/items/raw_beef.png"));
+    face_images.insert(BlockID::CookedBeef, BlockFaces::All("textures/items/cooked_beef.png"));
+    face_images.insert(BlockID::RottenFlesh, BlockFaces::All("textures/items/rotten_flesh.png"));
+    face_images.insert(BlockID::IronIngot, BlockFaces::All("textures/items/iron_ingot.png"));
+    face_images.insert(BlockID::GoldIngot, BlockFaces::All("textures/items/gold_ingot.png"));
+    face_images.insert(BlockID::NetherBrick, BlockFaces::All("textures/blocks/nether_bricks.png"));
+    face_images.insert(BlockID::BlazeRod, BlockFaces::All("textures/items/blaze_rod.png"));
+    face_images.insert(BlockID::NetherWart, BlockFaces::All("textures/items/nether_wart.png"));
+    face_images.insert(BlockID::Granite, BlockFaces::All("textures/blocks/granite.png"));
+    face_images.insert(BlockID::PolishedGranite, BlockFaces::All("textures/blocks/polished_granite.png"));
+    face_images.insert(BlockID::Diorite, BlockFaces::All("textures/blocks/diorite.png"));
+    face_images.insert(BlockID::PolishedDiorite, BlockFaces::All("textures/blocks/polished_diorite.png"));
+    face_images.insert(BlockID::Andesite, BlockFaces::All("textures/blocks/andesite.png"));
+    face_images.insert(BlockID::PolishedAndesite, BlockFaces::All("textures/blocks/polished_andesite.png"));
+    face_images.insert(BlockID::OakFenceGate, BlockFaces::All("textures/blocks/oak_planks.png"));
+    face_images.insert(BlockID::EnchantingTable, BlockFaces::Sides {
+        sides: "textures/blocks/enchanting_table_side.png",
+        top: "textures/blocks/enchanting_table_top.png",
+        bottom: "textures/blocks/obsidian.png",
+    });
+    face_images.insert(BlockID::Bookshelf, BlockFaces::Sides {
+        sides: "textures/blocks/bookshelf.png",
+        top: "textures/blocks/oak_planks.png",
+        bottom: "textures/blocks/oak_planks.png",
+    });
+    face_images.insert(BlockID::Stick, BlockFaces::All("textures/items/stick.png"));
+    face_images.insert(BlockID::WoodenSword, BlockFaces::All("textures/items/wooden_sword.png"));
+    face_images.insert(BlockID::StoneSword, BlockFaces::All("textures/items/stone_sword.png"));
+    face_images.insert(BlockID::Compass, BlockFaces::All("textures/items/compass.png"));
+    face_images.insert(BlockID::FlintAndSteel, BlockFaces::All("textures/items/flint_and_steel.png"));
+    face_images.insert(BlockID::FireworkRocket, BlockFaces::All("textures/items/firework_rocket.png"));
+    face_images.insert(BlockID::Map, BlockFaces::All("textures/items/map.png"));
+    face_images.insert(BlockID::SlimeBlock, BlockFaces::All("textures/blocks/slime.png"));
+    face_images.insert(BlockID::Gravel, BlockFaces::All("textures/blocks/gravel.png"));
+    face_images.insert(BlockID::DirtPath, BlockFaces::Sides {
+        sides: "textures/blocks/dirt_path_side.png",
+        top: "textures/blocks/dirt_path_top.png",
+        bottom: "textures/blocks/dirt.png",
+    });
+    face_images.insert(BlockID::Campfire, BlockFaces::Sides {
+        sides: "textures/blocks/campfire_fire.png",
+        top: "textures/blocks/campfire_top.png",
+        bottom: "textures/blocks/oak_log_top.png",
+    });
+    face_images.insert(BlockID::CampfireExtinguished, BlockFaces::Sides {
+        sides: "textures/blocks/campfire_log.png",
+        top: "textures/blocks/campfire_top_extinguished.png",
+        bottom: "textures/blocks/oak_log_top.png",
+    });
+    face_images.insert(BlockID::IronOre, BlockFaces::All("textures/blocks/iron_ore.png"));
+    face_images.insert(BlockID::DiamondOre, BlockFaces::All("textures/blocks/diamond_ore.png"));
+    face_images.insert(BlockID::Diamond, BlockFaces::All("textures/items/diamond.png"));
     face_images.insert(BlockID::Urss, BlockFaces::All("textures/blocks/urss.png"));
     face_images.insert(BlockID::Hitler, BlockFaces::All("textures/blocks/hitler.png"));
     face_images.insert(BlockID::Debug, BlockFaces::All("textures/blocks/debug.png"));
@@ -42,59 +410,130 @@ fn create_face_images_map() -> HashMap<BlockID, BlockFaces<&'static str>> {
     face_images
 }
 
+/// The parts of the face textures glowing in the dark, the opaque texels of the mask ignore the lighting
+fn create_emissive_masks_map() -> HashMap<&'static str, &'static str> {
+    let mut emissive_masks = HashMap::new();
+    emissive_masks.insert("textures/blocks/glowstone.png", "textures/blocks/glowstone_emissive.png");
+    emissive_masks.insert("textures/blocks/sea_lantern.png", "textures/blocks/sea_lantern_emissive.png");
+    emissive_masks.insert("textures/blocks/fire.png", "textures/blocks/fire_emissive.png");
+    emissive_masks.insert("textures/blocks/beacon.png", "textures/blocks/beacon_emissive.png");
+    emissive_masks.insert("textures/blocks/campfire_fire.png", "textures/blocks/campfire_fire_emissive.png");
+    emissive_masks.insert("textures/blocks/campfire_fire_1.png", "textures/blocks/campfire_fire_1_emissive.png");
+    emissive_masks.insert("textures/blocks/campfire_fire_2.png", "textures/blocks/campfire_fire_2_emissive.png");
+    emissive_masks.insert("textures/blocks/campfire_fire_3.png", "textures/blocks/campfire_fire_3_emissive.png");
+    emissive_masks
+}
+
+/// The other frames of the animated face textures, put in the layers following the first frame.
+/// Every frame has an emissive mask if the first one has one, so that they all take as many layers
+fn create_animation_frames_map() -> HashMap<&'static str, Vec<&'static str>> {
+    let mut animation_frames = HashMap::new();
+    animation_frames.insert("textures/blocks/campfire_fire.png", vec![
+        "textures/blocks/campfire_fire_1.png",
+        "textures/blocks/campfire_fire_2.png",
+        "textures/blocks/campfire_fire_3.png",
+    ]);
+    animation_frames
+}
+
 fn create_array_texture(layers: i32) -> u32 {
     let mut item_array_texture: u32 = 0;
     gl_call!(gl::CreateTextures(gl::TEXTURE_2D_ARRAY, 1, &mut item_array_texture));
-    gl_call!(gl::TextureParameteri(item_array_texture, gl::TEXTURE_MIN_FILTER, gl::NEAREST_MIPMAP_LINEAR as i32));
+    // Blends between the mipmaps in the distance, the textures stay pixelated up close
+    gl_call!(gl::TextureParameteri(item_array_texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32));
     gl_call!(gl::TextureParameteri(item_array_texture, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32));
-    gl_call!(gl::TextureStorage3D(item_array_texture, 1, gl::RGBA8, 16, 16, layers));
+    gl_call!(gl::TextureStorage3D(item_array_texture, mip_levels(16, 16), gl::RGBA8, 16, 16, layers));
     item_array_texture
 }
 
-fn create_face_uvs_map(array_texture: u32, face_images: HashMap<BlockID, BlockFaces<&str>>) -> HashMap<BlockID, BlockFaces<TextureLayer>> {
+fn create_face_uvs_map(array_texture: u32, face_images: HashMap<BlockID, BlockFaces<&str>>,
+                       emissive_masks: &HashMap<&str, &str>, animation_frames: &HashMap<&str, Vec<&str>>,
+                       overrides: &HashMap<String, PathBuf>) -> TexturePack {
     // Load all the images and fill the UV map for all the blocks
     // TODO don't load the same texture multiple times if reused for another block
 
     let mut layer: u32 = 0;
 
-    // Puts an image into the array texture at layer "layer"
-    let mut put_image_into_array_texture = |image: &DynamicImage| {
+    // Puts the image of a face into the array texture at layer "layer", followed by its emissive mask if it has one,
+    // then the other frames of its animation with their own masks
+    let mut put_image_into_array_texture = |image_path: &str| {
         let layer_blit = layer;
-        blit_image_to_texture(image, array_texture, layer_blit as i32);
-        // Advance to the next available layer in the texture
-        layer += 1;
+        let emissive_mask = emissive_masks.get(image_path);
+        let frames = animation_frames.get(image_path).map_or(&[][..], |frames| &frames[..]);
+        for &frame in std::iter::once(&image_path).chain(frames) {
+            blit_image_to_texture(&read_image_or_override(frame, overrides), array_texture, layer as i32);
+            // Advance to the next available layer in the texture
+            layer += 1;
+            if let Some(emissive_mask) = emissive_mask {
+                let frame_mask = emissive_masks.get(frame).unwrap_or(emissive_mask);
+                blit_image_to_texture(&read_image_or_override(frame_mask, overrides), array_texture, layer as i32);
+                layer += 1;
+            }
+        }
         // Return layer where we put the texture
-        layer_blit
+        (layer_blit, emissive_mask.is_some())
     };
 
-    let mut face_uvs = HashMap::<BlockID, BlockFaces<TextureLayer>>::new();
+    // Always in the same order, a texture gets the same layer when the array texture is built again
+    let mut face_images: Vec<_> = face_images.into_iter().collect();
+    face_images.sort_by_key(|&(block, _)| block as u32);
+
+    let mut face_uvs = TexturePack::new();
     for (block, faces) in face_images {
-        match faces {
+        let textures = match faces {
             BlockFaces::All(all) => {
-                face_uvs.insert(block, BlockFaces::All(put_image_into_array_texture(&mut read_image(all))));
+                let (all, all_emissive) = put_image_into_array_texture(all);
+                BlockTextures {
+                    faces: BlockFaces::All(all),
+                    emissive: BlockFaces::All(all_emissive),
+                }
             }
             BlockFaces::Sides { sides, top, bottom } => {
-                face_uvs.insert(block, BlockFaces::Sides {
-                    sides: put_image_into_array_texture(&mut read_image(sides)),
-                    top: put_image_into_array_texture(&mut read_image(top)),
-                    bottom: put_image_into_array_texture(&mut read_image(bottom)),
-                });
+                let (sides, sides_emissive) = put_image_into_array_texture(sides);
+                let (top, top_emissive) = put_image_into_array_texture(top);
+                let (bottom, bottom_emissive) = put_image_into_array_texture(bottom);
+                BlockTextures {
+                    faces: BlockFaces::Sides { sides, top, bottom },
+                    emissive: BlockFaces::Sides { sides: sides_emissive, top: top_emissive, bottom: bottom_emissive },
+                }
             }
             BlockFaces::Each { top, bottom, front, back, left, right } => {
-                face_uvs.insert(block, BlockFaces::Each {
-                    top: put_image_into_array_texture(&mut read_image(top)),
-                    bottom: put_image_into_array_texture(&mut read_image(bottom)),
-                    front: put_image_into_array_texture(&mut read_image(front)),
-                    back: put_image_into_array_texture(&mut read_image(back)),
-                    left: put_image_into_array_texture(&mut read_image(left)),
-                    right: put_image_into_array_texture(&mut read_image(right)),
-                });
+                let (top, top_emissive) = put_image_into_array_texture(top);
+                let (bottom, bottom_emissive) = put_image_into_array_texture(bottom);
+                let (front, front_emissive) = put_image_into_array_texture(front);
+                let (back, back_emissive) = put_image_into_array_texture(back);
+                let (left, left_emissive) = put_image_into_array_texture(left);
+                let (right, right_emissive) = put_image_into_array_texture(right);
+                BlockTextures {
+                    faces: BlockFaces::Each { top, bottom, front, back, left, right },
+                    emissive: BlockFaces::Each {
+                        top: top_emissive,
+                        bottom: bottom_emissive,
+                        front: front_emissive,
+                        back: back_emissive,
+                        left: left_emissive,
+                        right: right_emissive,
+                    },
+                }
             }
-        }
+        };
+        face_uvs.insert(block, textures);
     }
     face_uvs
 }
 
+/// The image of the texture pack replacing the built-in one, when it has one that is a 16x16 RGBA image
+fn read_image_or_override(image_path: &str, overrides: &HashMap<String, PathBuf>) -> DynamicImage {
+    if let Some(override_path) = overrides.get(texture_name(image_path)) {
+        match image::open(override_path) {
+            Ok(img) if img.color() == image::RGBA(8) && img.dimensions() == (16, 16) => return img.flipv(),
+            Ok(_) => error!("{} must be a 16x16 RGBA image, using the built-in texture", override_path.display()),
+            Err(err) => error!("Filename: {}, error: {}, using the built-in texture", override_path.display(), err),
+        }
+    }
+    read_image(image_path)
+}
+
 fn read_image(image_path: &str) -> DynamicImage {
     let img = match image::open(image_path) {
         Ok(img) => img.flipv(), // We flip the y axis for OpenGL
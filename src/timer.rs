@@ -1,9 +1,10 @@
-use std::time::{Instant, Duration};
-use std::ops::Sub;
+use std::time::Instant;
 
+/// The time of the game, which stops while it is paused and runs slower or faster than the real time
+/// depending on the time scale
 pub struct Timer {
     current: Instant,
-    time_paused: Duration,
+    last_tick: Instant,
     paused: bool,
 }
 
@@ -17,14 +18,14 @@ impl Timer {
     pub fn new() -> Self {
         Self {
             current: Instant::now(),
-            time_paused: Duration::new(0, 0),
+            last_tick: Instant::now(),
             paused: false,
         }
     }
 
     pub fn restart(&mut self) {
         self.current = Instant::now();
-        self.time_paused = Duration::new(0, 0);
+        self.last_tick = self.current;
     }
 
     pub fn pause(&mut self) {
@@ -35,12 +36,13 @@ impl Timer {
         self.paused = false;
     }
 
-    pub fn tick(&mut self) {
-        if self.paused {
-            self.time_paused = Instant::now().duration_since(self.current);
-        } else {
-            self.current = Instant::now().sub(self.time_paused);
+    /// Advances the time by the real time elapsed since the last tick, times `time_scale`
+    pub fn tick(&mut self, time_scale: f32) {
+        let now = Instant::now();
+        if !self.paused {
+            self.current += now.saturating_duration_since(self.last_tick).mul_f32(time_scale);
         }
+        self.last_tick = now;
     }
 
     pub fn time(&self) -> Instant {
@@ -50,4 +52,4 @@ impl Timer {
     pub fn is_paused(&self) -> bool {
         self.paused
     }
-}
\ No newline at end of file
+}
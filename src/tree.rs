@@ -0,0 +1,140 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use crate::chunk::BlockID;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TreeKind {
+    Oak,
+    Birch,
+    /// Tall and narrow, grows in cold climates
+    Spruce,
+    /// Huge with a 2x2 trunk, grows in hot and humid climates
+    Jungle,
+}
+
+impl TreeKind {
+    /// The trees growing with this temperature and humidity, both from 0 to 1
+    pub fn from_climate(temperature: f32, humidity: f32) -> TreeKind {
+        if temperature < 0.35 {
+            TreeKind::Spruce
+        } else if temperature > 0.6 && humidity > 0.6 {
+            TreeKind::Jungle
+        } else if humidity < 0.4 {
+            TreeKind::Birch
+        } else {
+            TreeKind::Oak
+        }
+    }
+
+    pub fn log(&self) -> BlockID {
+        match self {
+            TreeKind::Oak => BlockID::OakLog,
+            TreeKind::Birch => BlockID::BirchLog,
+            TreeKind::Spruce => BlockID::SpruceLog,
+            TreeKind::Jungle => BlockID::JungleLog,
+        }
+    }
+
+    pub fn leaves(&self) -> BlockID {
+        match self {
+            TreeKind::Oak => BlockID::OakLeaves,
+            TreeKind::Birch => BlockID::BirchLeaves,
+            TreeKind::Spruce => BlockID::SpruceLeaves,
+            TreeKind::Jungle => BlockID::JungleLeaves,
+        }
+    }
+
+    /// Number of logs in the trunk of a tree of this kind
+    pub fn random_trunk_height(&self, rng: &mut impl Rng) -> u8 {
+        match self {
+            TreeKind::Oak => 5,
+            TreeKind::Birch => rng.gen_range(5, 8),
+            TreeKind::Spruce => 8,
+            TreeKind::Jungle => rng.gen_range(10, 15),
+        }
+    }
+}
+
+/// Decides the height of the tree growing at (x, z), only depending on the world seed
+pub fn tree_rng(world_seed: u32, x: i32, z: i32) -> StdRng {
+    let seed = (world_seed as u64) << 32
+        ^ (x as u32 as u64).wrapping_mul(49_979_687)
+        ^ (z as u32 as u64).wrapping_mul(67_867_967);
+    StdRng::seed_from_u64(seed)
+}
+
+/// The blocks of a tree growing on the ground block at (0, 0, 0), as (x, y, z, block).
+/// The leaves never replace the trunk
+pub fn generate_tree(kind: TreeKind, trunk_height: u8) -> Vec<(i32, i32, i32, BlockID)> {
+    let h = trunk_height as i32;
+    let (log, leaves) = (kind.log(), kind.leaves());
+    let mut blocks = Vec::new();
+
+    // Jungle trees have a 2x2 trunk going from (0, 0) to (1, 1)
+    let trunk_width = if kind == TreeKind::Jungle { 2 } else { 1 };
+    let is_trunk = |x: i32, y: i32, z: i32| {
+        (1..=h).contains(&y) && (0..trunk_width).contains(&x) && (0..trunk_width).contains(&z)
+    };
+
+    for y in 1..=h {
+        for x in 0..trunk_width {
+            for z in 0..trunk_width {
+                blocks.push((x, y, z, log));
+            }
+        }
+    }
+
+    let mut put_leaves = |x: i32, y: i32, z: i32| {
+        if !is_trunk(x, y, z) {
+            blocks.push((x, y, z, leaves));
+        }
+    };
+
+    match kind {
+        TreeKind::Oak | TreeKind::Birch => {
+            for y in h - 2..=h - 1 {
+                for x in -2..=2 {
+                    for z in -2..=2 {
+                        put_leaves(x, y, z);
+                    }
+                }
+            }
+            for x in -1..=1 {
+                for z in -1..=1 {
+                    put_leaves(x, h, z);
+                }
+            }
+            for &(x, z) in &[(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)] {
+                put_leaves(x, h + 1, z);
+            }
+        }
+        TreeKind::Spruce => {
+            // Only the 4 top layers have leaves, one block around the trunk
+            for y in h - 2..=h {
+                for x in -1..=1 {
+                    for z in -1..=1 {
+                        put_leaves(x, y, z);
+                    }
+                }
+            }
+            for &(x, z) in &[(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)] {
+                put_leaves(x, h + 1, z);
+            }
+        }
+        TreeKind::Jungle => {
+            // A round canopy centered on the middle of the trunk, narrower at the top
+            for &(y, radius) in &[(h - 1, 3.5f32), (h, 3.5), (h + 1, 2.5)] {
+                for x in -4..=5 {
+                    for z in -4..=5 {
+                        let (dx, dz) = (x as f32 - 0.5, z as f32 - 0.5);
+                        if dx * dx + dz * dz <= radius * radius {
+                            put_leaves(x, y, z);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    blocks
+}
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
+use crate::block_entity::BlockEntity;
 use crate::chunk::BlockID;
-use crate::block_texture_faces::BlockFaces;
+use crate::block_texture_faces::BlockTextures;
 use crate::particle_system::ParticleSystem;
 use crate::shader_compilation::ShaderProgram;
 
 pub type TextureLayer = u32;
 pub type UVFaces = (TextureLayer, TextureLayer, TextureLayer, TextureLayer, TextureLayer, TextureLayer);
-pub type TexturePack = HashMap<BlockID, BlockFaces<TextureLayer>>;
+pub type EmissiveFaces = (bool, bool, bool, bool, bool, bool);
+pub type TexturePack = HashMap<BlockID, BlockTextures>;
 pub type ParticleSystems = HashMap<&'static str, ParticleSystem>;
-pub type Shaders = HashMap<&'static str, ShaderProgram>;
\ No newline at end of file
+pub type Shaders = HashMap<&'static str, ShaderProgram>;
+pub type BlockEntityRegistry = HashMap<(i32, i32, i32), Box<dyn BlockEntity>>;
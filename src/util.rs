@@ -1,4 +1,4 @@
-use nalgebra_glm::{Vec3, vec3};
+use nalgebra_glm::{Mat4, Vec3, vec3, vec4};
 
 pub trait Forward {
     fn forward(&self) -> Self;
@@ -24,6 +24,24 @@ impl Zero for Vec3 {
     }
 }
 
+/// Whether a sphere is at least partially inside the view frustum of `view_projection`.
+/// The planes of the frustum are extracted from the rows of the matrix (Gribb & Hartmann).
+pub fn is_sphere_in_frustum(view_projection: &Mat4, center: &Vec3, radius: f32) -> bool {
+    let rows = [
+        view_projection.row(0).transpose(),
+        view_projection.row(1).transpose(),
+        view_projection.row(2).transpose(),
+        view_projection.row(3).transpose(),
+    ];
+    let point = vec4(center.x, center.y, center.z, 1.0);
+    (0..3).all(|i| {
+        [rows[3] + rows[i], rows[3] - rows[i]].iter().all(|plane| {
+            let normal_length = vec3(plane.x, plane.y, plane.z).norm();
+            plane.dot(&point) / normal_length >= -radius
+        })
+    })
+}
+
 // pub trait MappedArc<U: ?Sized, F> {
 //     fn map(arc: Arc<F>, f: &dyn FnOnce(&F) -> &U) -> ArcGuard<U, F>;
 // }
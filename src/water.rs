@@ -0,0 +1,132 @@
+use std::collections::{HashSet, VecDeque};
+
+use nalgebra::Matrix4;
+use nalgebra_glm::{Mat4, vec3};
+
+use crate::chunk::{BlockID, BlockMeta};
+use crate::chunk_manager::ChunkManager;
+use crate::constants::{MAX_WATER_FLOW_LEVEL, WATER_LEVEL};
+use crate::util::is_sphere_in_frustum;
+
+const HORIZONTAL_NEIGHBOURS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// Water blocks waiting to spread to the air around them.
+/// Water falls into the air under it, otherwise it spreads sideways one level weaker than itself
+#[derive(Default)]
+pub struct WaterFlowSimulator {
+    queue: VecDeque<(i32, i32, i32)>,
+    queued: HashSet<(i32, i32, i32)>,
+}
+
+impl WaterFlowSimulator {
+    /// Must be called after the block at (x, y, z) changed.
+    /// The water that was put there, or that is next to a block that was removed, may be able to flow
+    pub fn on_block_changed(&mut self, chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32)) {
+        // The block itself, the water above it and the water beside it
+        let candidates = [(x, y, z), (x, y + 1, z), (x + 1, y, z), (x - 1, y, z), (x, y, z + 1), (x, y, z - 1)];
+        for &(x, y, z) in candidates.iter() {
+            if chunk_manager.get_block(x, y, z) == Some(BlockID::Water) {
+                self.enqueue((x, y, z));
+            }
+        }
+    }
+
+    fn enqueue(&mut self, position: (i32, i32, i32)) {
+        if self.queued.insert(position) {
+            self.queue.push_back(position);
+        }
+    }
+
+    /// Spreads the water of the next queued block, returns false when there was none
+    pub fn step(&mut self, chunk_manager: &ChunkManager) -> bool {
+        let (x, y, z) = match self.queue.pop_front() {
+            Some(position) => position,
+            None => return false,
+        };
+        self.queued.remove(&(x, y, z));
+
+        // The water may have been removed since it was queued, or its chunk unloaded
+        let meta = match (chunk_manager.get_block(x, y, z), chunk_manager.get_block_meta(x, y, z)) {
+            (Some(BlockID::Water), Some(meta)) => meta,
+            _ => return true,
+        };
+
+        // Falling water keeps the strength of a source
+        if chunk_manager.get_block(x, y - 1, z) == Some(BlockID::Air) {
+            self.put_water(chunk_manager, (x, y - 1, z), 0);
+            return true;
+        }
+
+        let level = meta.flow_level();
+        if level >= MAX_WATER_FLOW_LEVEL {
+            return true;
+        }
+        for &(dx, dz) in HORIZONTAL_NEIGHBOURS.iter() {
+            if chunk_manager.get_block(x + dx, y, z + dz) == Some(BlockID::Air) {
+                self.put_water(chunk_manager, (x + dx, y, z + dz), level + 1);
+            }
+        }
+        true
+    }
+
+    fn put_water(&mut self, chunk_manager: &ChunkManager, (x, y, z): (i32, i32, i32), level: u8) {
+        chunk_manager.put_block(BlockID::Water, x, y, z);
+        chunk_manager.set_block_meta(BlockMeta::default().with_flow_level(level), x, y, z);
+        self.enqueue((x, y, z));
+    }
+}
+
+/// Height of the surface of the water, the plane the reflections are computed against
+pub fn water_surface_height() -> f32 {
+    WATER_LEVEL as f32 + 1.0
+}
+
+/// Whether the surface of the sea is drawn somewhere in the view frustum of `view_projection`,
+/// that is whether a loaded chunk at the water level has water faces inside the frustum
+pub fn is_water_surface_in_view(chunk_manager: &ChunkManager, view_projection: &Mat4) -> bool {
+    let chunk_y = WATER_LEVEL as usize / 16;
+    chunk_manager.loaded_chunk_columns.read().iter().any(|((x, z), chunk_column)| {
+        let chunk = &chunk_column.chunks[chunk_y];
+        if *chunk.water_vertices_drawn.read() == 0 {
+            return false;
+        }
+        let center = vec3(*x as f32 * 16.0 + 8.0, water_surface_height(), *z as f32 * 16.0 + 8.0);
+        // Half of the diagonal of the column
+        is_sphere_in_frustum(view_projection, &center, 11.32)
+    })
+}
+
+/// Mirrors the world across the plane y = height.
+/// Multiplying a view matrix by this matrix gives the view of a camera placed under the water.
+pub fn reflection_matrix(height: f32) -> Mat4 {
+    Matrix4::new_translation(&vec3(0.0, height, 0.0))
+        * Matrix4::new_nonuniform_scaling(&vec3(1.0, -1.0, 1.0))
+        * Matrix4::new_translation(&vec3(0.0, -height, 0.0))
+}
+
+/// Creates a framebuffer with a color texture (the reflection) and a depth renderbuffer.
+/// Returns (framebuffer, color texture)
+pub fn create_reflection_framebuffer(width: i32, height: i32) -> (u32, u32) {
+    let mut reflection_texture = 0;
+    gl_call!(gl::CreateTextures(gl::TEXTURE_2D, 1, &mut reflection_texture));
+    gl_call!(gl::TextureParameteri(reflection_texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32));
+    gl_call!(gl::TextureParameteri(reflection_texture, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32));
+    gl_call!(gl::TextureParameteri(reflection_texture, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32));
+    gl_call!(gl::TextureParameteri(reflection_texture, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32));
+    gl_call!(gl::TextureStorage2D(reflection_texture, 1, gl::RGBA8, width, height));
+
+    let mut depth_renderbuffer = 0;
+    gl_call!(gl::CreateRenderbuffers(1, &mut depth_renderbuffer));
+    gl_call!(gl::NamedRenderbufferStorage(depth_renderbuffer, gl::DEPTH_COMPONENT24, width, height));
+
+    let mut framebuffer = 0;
+    gl_call!(gl::CreateFramebuffers(1, &mut framebuffer));
+    gl_call!(gl::NamedFramebufferTexture(framebuffer, gl::COLOR_ATTACHMENT0, reflection_texture, 0));
+    gl_call!(gl::NamedFramebufferRenderbuffer(framebuffer, gl::DEPTH_ATTACHMENT, gl::RENDERBUFFER, depth_renderbuffer));
+
+    let status = gl_call!(gl::CheckNamedFramebufferStatus(framebuffer, gl::FRAMEBUFFER));
+    if status != gl::FRAMEBUFFER_COMPLETE {
+        error!("Reflection framebuffer is incomplete (status {:#x})", status);
+    }
+    (framebuffer, reflection_texture)
+}
@@ -0,0 +1,151 @@
+use std::str::FromStr;
+
+use rand::Rng;
+
+use crate::constants::{CLEAR_WEATHER_DURATION, RAIN_DURATION, RAIN_FADE_DURATION, RAIN_FOG_DENSITY_INCREASE, RAIN_SKY_BRIGHTNESS, THUNDER_CHANCE, THUNDER_FLASH_DURATION, THUNDER_FLASH_INTERVAL};
+use crate::dimension::Dimension;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    /// Rain with lightning striking around the player
+    Thunder,
+}
+
+impl FromStr for WeatherKind {
+    type Err = String;
+
+    /// Parses the argument of the `/weather clear|rain|thunder` command
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clear" => Ok(WeatherKind::Clear),
+            "rain" => Ok(WeatherKind::Rain),
+            "thunder" => Ok(WeatherKind::Thunder),
+            _ => Err(format!("Unknown weather \"{}\", expected clear, rain or thunder", s)),
+        }
+    }
+}
+
+/// The weather of the Overworld, the Nether has none
+pub struct Weather {
+    pub kind: WeatherKind,
+    /// Seconds before the weather changes by itself
+    pub duration_remaining: f32,
+    /// Goes from 0 to 1 in RAIN_FADE_DURATION seconds when it starts raining, and back when it stops
+    pub rain_strength: f32,
+    /// Seconds before the next lightning strike, during thunderstorms
+    pub next_lightning: f32,
+    /// Seconds elapsed since the last lightning strike
+    pub since_lightning: f32,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Weather {
+    pub fn new() -> Self {
+        Weather {
+            kind: WeatherKind::Clear,
+            duration_remaining: random_duration(CLEAR_WEATHER_DURATION),
+            rain_strength: 0.0,
+            next_lightning: 0.0,
+            since_lightning: THUNDER_FLASH_DURATION,
+        }
+    }
+
+    pub fn is_raining(&self) -> bool {
+        self.kind != WeatherKind::Clear
+    }
+
+    /// Changes the weather right away, it keeps changing by itself afterwards
+    pub fn set(&mut self, kind: WeatherKind) {
+        self.kind = kind;
+        self.duration_remaining = random_duration(match kind {
+            WeatherKind::Clear => CLEAR_WEATHER_DURATION,
+            WeatherKind::Rain | WeatherKind::Thunder => RAIN_DURATION,
+        });
+        self.next_lightning = rand::thread_rng().gen_range(THUNDER_FLASH_INTERVAL.0, THUNDER_FLASH_INTERVAL.1);
+        info!("The weather is now {:?}", kind);
+    }
+
+    /// Runs the `/weather clear|rain|thunder` command, overriding the random cycle
+    pub fn run_command(&mut self, argument: &str) -> Result<(), String> {
+        self.set(argument.trim().parse()?);
+        Ok(())
+    }
+
+    /// Advances the weather by `dt` seconds and returns whether a lightning bolt strikes
+    pub fn advance(&mut self, dt: f32) -> bool {
+        let target_strength = if self.is_raining() { 1.0 } else { 0.0 };
+        let step = dt / RAIN_FADE_DURATION;
+        self.rain_strength = if self.rain_strength < target_strength {
+            (self.rain_strength + step).min(target_strength)
+        } else {
+            (self.rain_strength - step).max(target_strength)
+        };
+
+        self.duration_remaining -= dt;
+        if self.duration_remaining <= 0.0 {
+            let mut rng = rand::thread_rng();
+            let next_kind = match self.kind {
+                WeatherKind::Clear => WeatherKind::Rain,
+                WeatherKind::Rain if rng.gen_bool(THUNDER_CHANCE) => WeatherKind::Thunder,
+                WeatherKind::Rain | WeatherKind::Thunder => WeatherKind::Clear,
+            };
+            self.set(next_kind);
+        }
+
+        self.since_lightning += dt;
+        if self.kind != WeatherKind::Thunder {
+            return false;
+        }
+        self.next_lightning -= dt;
+        if self.next_lightning > 0.0 {
+            return false;
+        }
+        self.next_lightning = rand::thread_rng().gen_range(THUNDER_FLASH_INTERVAL.0, THUNDER_FLASH_INTERVAL.1);
+        self.since_lightning = 0.0;
+        true
+    }
+
+    /// Multiplies the color of the sky, from 1 when it's clear to RAIN_SKY_BRIGHTNESS when it's raining
+    pub fn sky_brightness(&self) -> f32 {
+        1.0 - (1.0 - RAIN_SKY_BRIGHTNESS) * self.rain_strength
+    }
+
+    /// Multiplies the density of the fog of `dimension`, the rain makes it thicker in the Overworld
+    pub fn fog_density_multiplier(&self, dimension: Dimension) -> f32 {
+        if dimension != Dimension::Overworld {
+            return 1.0;
+        }
+        1.0 + RAIN_FOG_DENSITY_INCREASE * self.rain_strength
+    }
+
+    /// Alpha of the white flash covering the screen, it brightens then fades out in THUNDER_FLASH_DURATION seconds
+    pub fn flash_brightness(&self) -> f32 {
+        if self.since_lightning >= THUNDER_FLASH_DURATION {
+            return 0.0;
+        }
+        let half_duration = THUNDER_FLASH_DURATION / 2.0;
+        1.0 - (self.since_lightning - half_duration).abs() / half_duration
+    }
+
+    /// The color of the sky of `dimension`, darkened by the rain in the Overworld
+    pub fn sky_color(&self, dimension: Dimension) -> (f32, f32, f32, f32) {
+        let (r, g, b, a) = dimension.sky_color();
+        if dimension != Dimension::Overworld {
+            return (r, g, b, a);
+        }
+        let brightness = self.sky_brightness();
+        (r * brightness, g * brightness, b * brightness, a)
+    }
+}
+
+/// A duration between half and one and a half times `average`
+fn random_duration(average: f32) -> f32 {
+    average * rand::thread_rng().gen_range(0.5, 1.5)
+}
@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::SAVES_DIRECTORY;
+use crate::statistics::world_save_directory;
+
+const NAME_ADJECTIVES: [&str; 8] = ["Quiet", "Windy", "Hidden", "Frozen", "Sunny", "Misty", "Golden", "Lonely"];
+const NAME_NOUNS: [&str; 8] = ["Valley", "Peaks", "Meadow", "Island", "Forest", "Canyon", "Shore", "Hills"];
+
+/// Name of a world and when it was last played, listed in the world selection screen
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct WorldInfo {
+    pub name: String,
+    /// Seconds since the Unix epoch
+    pub last_played: u64,
+
+    /// The directory of the world is named after it
+    #[serde(skip)]
+    pub seed: u32,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs())
+}
+
+impl WorldInfo {
+    fn path(seed: u32) -> PathBuf {
+        world_save_directory(seed).join("world.toml")
+    }
+
+    /// A world with a random seed and a generated name, it is saved once played
+    pub fn new_random(rng: &mut impl Rng) -> Self {
+        let name = format!("{} {}", NAME_ADJECTIVES.choose(rng).unwrap(), NAME_NOUNS.choose(rng).unwrap());
+        Self {
+            name,
+            last_played: now(),
+            seed: rng.gen(),
+        }
+    }
+
+    /// Reads the info of the world, the worlds without a name are named after their seed
+    pub fn load(seed: u32) -> Self {
+        let path = Self::path(seed);
+        let world_info = match fs::read_to_string(&path) {
+            Ok(text) => match toml::from_str(&text) {
+                Ok(world_info) => world_info,
+                Err(err) => {
+                    error!("Invalid world info in {}: {}", path.display(), err);
+                    WorldInfo::default()
+                }
+            },
+            // Saved before the world info existed, the directory was last modified when the world was last played
+            Err(_) => WorldInfo {
+                last_played: fs::metadata(world_save_directory(seed))
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |duration| duration.as_secs()),
+                ..WorldInfo::default()
+            },
+        };
+        let name = if world_info.name.is_empty() {
+            format!("World {}", seed)
+        } else {
+            world_info.name
+        };
+        WorldInfo {
+            name,
+            seed,
+            ..world_info
+        }
+    }
+
+    pub fn save(&self) {
+        let directory = world_save_directory(self.seed);
+        if let Err(err) = fs::create_dir_all(&directory) {
+            error!("Couldn't create {}: {}", directory.display(), err);
+            return;
+        }
+        let text = match toml::to_string(self) {
+            Ok(text) => text,
+            Err(err) => {
+                error!("Couldn't serialize the world info: {}", err);
+                return;
+            }
+        };
+        let path = Self::path(self.seed);
+        if let Err(err) = fs::write(&path, text) {
+            error!("Couldn't write {}: {}", path.display(), err);
+        }
+    }
+
+    /// Marks the world as played now
+    pub fn touch(&mut self) {
+        self.last_played = now();
+        self.save();
+    }
+
+    /// The day the world was last played, as YYYY-MM-DD
+    pub fn last_played_date(&self) -> String {
+        if self.last_played == 0 {
+            return "Never played".to_string();
+        }
+        format_date(self.last_played)
+    }
+}
+
+/// The day of a time in seconds since the Unix epoch, as YYYY-MM-DD
+pub fn format_date(seconds: u64) -> String {
+    // Converts days since the epoch to a date of the proleptic Gregorian calendar
+    let days = (seconds / 86400) as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// The worlds in the saves directory, the most recently played first
+pub fn list_saved_worlds() -> Vec<WorldInfo> {
+    let entries = match fs::read_dir(SAVES_DIRECTORY) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut worlds: Vec<WorldInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().and_then(|name| name.parse().ok()))
+        .map(WorldInfo::load)
+        .collect();
+    worlds.sort_by(|a, b| b.last_played.cmp(&a.last_played));
+    worlds
+}
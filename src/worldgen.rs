@@ -0,0 +1,155 @@
+use std::fs;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::chunk::BlockID;
+use crate::constants::WORLDGEN_CONFIG_PATH;
+
+/// The parameters of the overworld generation, read from WORLDGEN_CONFIG_PATH.
+/// Parameters missing from the file keep their defaults
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct WorldGenConfig {
+    /// Horizontal size of the hills, in blocks
+    pub terrain_scale: f64,
+    /// How far the noise moves the surface up or down
+    pub terrain_amplitude: f64,
+    /// Added to the noise, the higher the lower the terrain
+    pub terrain_height_offset: f64,
+    /// A tree grows where the foliage noise exceeds its 8 neighbours by more than this
+    pub tree_density_threshold: f64,
+}
+
+impl Default for WorldGenConfig {
+    fn default() -> Self {
+        WorldGenConfig {
+            terrain_scale: 90.0,
+            terrain_amplitude: 80.0,
+            terrain_height_offset: 64.0,
+            tree_density_threshold: 0.0,
+        }
+    }
+}
+
+impl WorldGenConfig {
+    /// Reads the configuration file, the defaults are used when it doesn't exist or can't be parsed
+    pub fn load() -> Self {
+        let text = match fs::read_to_string(WORLDGEN_CONFIG_PATH) {
+            Ok(text) => text,
+            Err(_) => return WorldGenConfig::default(),
+        };
+        match toml::from_str(&text) {
+            Ok(config) => config,
+            Err(err) => {
+                error!("Invalid world generation config in {}: {}", WORLDGEN_CONFIG_PATH, err);
+                WorldGenConfig::default()
+            }
+        }
+    }
+
+    /// Last time the configuration file was written to, None if it doesn't exist
+    pub fn modified_time() -> Option<SystemTime> {
+        fs::metadata(WORLDGEN_CONFIG_PATH).and_then(|metadata| metadata.modified()).ok()
+    }
+}
+
+/// The kinds of terrain of the overworld, chosen by the climate
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Biome {
+    Plains,
+    Hills,
+    Swamp,
+    Mountains,
+}
+
+impl Biome {
+    pub const ALL: [Biome; 4] = [Biome::Plains, Biome::Hills, Biome::Swamp, Biome::Mountains];
+
+    /// The biome at this temperature and humidity, both from 0 to 1
+    pub fn from_climate(temperature: f32, humidity: f32) -> Biome {
+        if temperature < 0.2 {
+            Biome::Mountains
+        } else if humidity > 0.65 {
+            Biome::Swamp
+        } else if humidity < 0.4 {
+            Biome::Plains
+        } else {
+            Biome::Hills
+        }
+    }
+
+    /// Multiplies the terrain amplitude of the config
+    pub fn height_scale(self) -> f64 {
+        match self {
+            Biome::Plains => 0.6,
+            Biome::Hills => 1.0,
+            Biome::Swamp => 0.3,
+            Biome::Mountains => 1.8,
+        }
+    }
+
+    /// Added to the terrain height offset of the config, the higher the lower the terrain
+    pub fn height_offset(self) -> f64 {
+        match self {
+            Biome::Swamp => 8.0,
+            Biome::Mountains => -12.0,
+            _ => 0.0,
+        }
+    }
+
+    /// The top block of the columns
+    pub fn surface_block(self) -> BlockID {
+        match self {
+            Biome::Mountains => BlockID::Stone,
+            _ => BlockID::GrassBlock,
+        }
+    }
+
+    /// The blocks under the surface block
+    pub fn filler_block(self) -> BlockID {
+        match self {
+            Biome::Mountains => BlockID::Stone,
+            _ => BlockID::Dirt,
+        }
+    }
+}
+
+/// The biomes of a block column near the boundaries between them. The climate is sampled 8 blocks around the
+/// center of its chunk column, at its corners, and `weights` holds how much each of Biome::ALL takes part
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BlendedBiomeResult {
+    /// The biome with the highest weight, giving the surface blocks
+    pub primary: Biome,
+    pub weights: [f32; 4],
+}
+
+impl BlendedBiomeResult {
+    /// Blends the biomes at the corners (-x -z, +x -z, -x +z, +x +z) of a chunk column for the block column at
+    /// (b_x, b_z) in it. The weights are bilinear so that they match on both sides of the chunk borders
+    pub fn new(corners: &[(f32, f32); 4], b_x: usize, b_z: usize) -> Self {
+        let (tx, tz) = (b_x as f32 / 16.0, b_z as f32 / 16.0);
+        let corner_weights = [(1.0 - tx) * (1.0 - tz), tx * (1.0 - tz), (1.0 - tx) * tz, tx * tz];
+
+        let mut weights = [0.0; 4];
+        for (&(temperature, humidity), weight) in corners.iter().zip(corner_weights.iter()) {
+            weights[Biome::from_climate(temperature, humidity) as usize] += weight;
+        }
+        let primary = (0..4)
+            .max_by(|&a, &b| weights[a].partial_cmp(&weights[b]).unwrap())
+            .map_or(Biome::Hills, |i| Biome::ALL[i]);
+        BlendedBiomeResult { primary, weights }
+    }
+
+    fn blend(&self, property: fn(Biome) -> f64) -> f64 {
+        Biome::ALL.iter().zip(self.weights.iter()).map(|(&biome, &weight)| property(biome) * weight as f64).sum()
+    }
+
+    pub fn height_scale(&self) -> f64 {
+        self.blend(Biome::height_scale)
+    }
+
+    pub fn height_offset(&self) -> f64 {
+        self.blend(Biome::height_offset)
+    }
+}
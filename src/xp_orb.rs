@@ -0,0 +1,37 @@
+use nalgebra_glm::{Vec3, vec3};
+use rand::Rng;
+
+use crate::constants::XP_ORB_MAX_VALUE;
+
+/// Experience dropped by a killed mob, collected when the player touches it
+pub struct XpOrb {
+    pub value: u32,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Seconds since the orb was dropped
+    pub age: f32,
+}
+
+impl XpOrb {
+    /// An orb popping out of `position` in a random direction
+    pub fn new(value: u32, position: Vec3) -> Self {
+        let mut rng = rand::thread_rng();
+        XpOrb {
+            value,
+            position: position + vec3(0.0, 0.5, 0.0),
+            velocity: vec3(rng.gen_range(-1.5, 1.5), rng.gen_range(3.0, 5.0), rng.gen_range(-1.5, 1.5)),
+            age: 0.0,
+        }
+    }
+}
+
+/// The values of the orbs dropped for `xp` points, none of them worth more than XP_ORB_MAX_VALUE
+pub fn split_into_orbs(mut xp: u32) -> Vec<u32> {
+    let mut values = Vec::new();
+    while xp > 0 {
+        let value = xp.min(XP_ORB_MAX_VALUE);
+        values.push(value);
+        xp -= value;
+    }
+    values
+}